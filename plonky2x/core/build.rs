@@ -0,0 +1,33 @@
+//! Builds the gnark verifier in `../verifier` as a static library for the `gnark-ffi` feature.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    if env::var_os("CARGO_FEATURE_GNARK_FFI").is_none() {
+        return;
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let verifier_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("../verifier");
+    let status = Command::new(env::var("GO").unwrap_or_else(|_| "go".to_string()))
+        .args(["build", "-buildmode=c-archive", "-o"])
+        .arg(out_dir.join("libgnarkverifier.a"))
+        .arg(".")
+        .current_dir(&verifier_dir)
+        .status()
+        .expect("the gnark-ffi feature needs a Go toolchain to build the gnark verifier");
+    assert!(status.success(), "failed to build the gnark verifier");
+
+    println!("cargo:rerun-if-changed={}", verifier_dir.display());
+    println!("cargo:rerun-if-env-changed=GO");
+    println!("cargo:rustc-link-search=native={}", out_dir.display());
+    println!("cargo:rustc-link-lib=static=gnarkverifier");
+    // The Go runtime links against these frameworks on macOS.
+    if env::var("CARGO_CFG_TARGET_OS").unwrap() == "macos" {
+        println!("cargo:rustc-link-lib=framework=CoreFoundation");
+        println!("cargo:rustc-link-lib=framework=Security");
+    }
+}
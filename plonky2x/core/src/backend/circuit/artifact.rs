@@ -0,0 +1,309 @@
+//! A client for fetching and publishing circuit artifacts by digest.
+//!
+//! Artifacts are content addressed: a serialized circuit is stored under its id, as returned by
+//! `CircuitBuild::id`, and a fetched artifact is rejected unless it deserializes to a circuit with
+//! the requested id. Wrapped circuits are additionally indexed by the digest of their wrapper,
+//! which is the `VerifierDigest` stored in their on-chain verifier, so that provers and verifiers
+//! can resolve the exact artifact a deployed verifier refers to.
+
+use core::time::Duration;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use anyhow::{anyhow, Result};
+use log::{debug, info};
+use plonky2::util::serialization::GateSerializer;
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use super::serialization::hints::HintSerializer;
+use super::{CircuitBuild, PlonkParameters};
+
+/// The route under which artifacts are stored, by circuit id.
+const ARTIFACTS_ROUTE: &str = "/api/artifacts";
+
+/// The route mapping on-chain verifier digests to circuit ids.
+const VERIFIERS_ROUTE: &str = "/api/artifacts/verifiers";
+
+/// The directory where circuits are saved and looked up by id.
+pub const DEFAULT_BUILD_DIR: &str = "./build";
+
+/// The entry of a verifier digest in the registry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifierEntry {
+    pub verifier_digest: String,
+    pub circuit_id: String,
+}
+
+/// A client for a registry of circuit artifacts, with a local cache.
+#[derive(Debug, Clone)]
+pub struct ArtifactRegistry {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    cache_dir: PathBuf,
+}
+
+impl ArtifactRegistry {
+    /// Creates a client for the registry at `ARTIFACT_REGISTRY_URL`, authenticated with
+    /// `ARTIFACT_REGISTRY_API_KEY` if set. Returns `None` if no registry is configured.
+    pub fn from_env() -> Option<Self> {
+        let url = env::var("ARTIFACT_REGISTRY_URL").ok()?;
+        let mut registry = Self::new(url, DEFAULT_BUILD_DIR);
+        registry.api_key = env::var("ARTIFACT_REGISTRY_API_KEY").ok();
+        Some(registry)
+    }
+
+    /// Creates a client for the registry at `url`, caching artifacts in `cache_dir`.
+    pub fn new<P: AsRef<Path>>(url: String, cache_dir: P) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: url.trim_end_matches('/').to_string(),
+            api_key: None,
+            cache_dir: cache_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The path of the cached artifact of the circuit `id`.
+    pub fn cache_path(&self, id: &str) -> PathBuf {
+        self.cache_dir.join(format!("{}.circuit", id))
+    }
+
+    fn authenticated(&self, request: RequestBuilder) -> RequestBuilder {
+        let request = request.timeout(Duration::from_secs(300));
+        match &self.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+
+    /// Fetches the serialized circuit `id` from the registry, without checking it.
+    pub fn fetch_bytes(&self, id: &str) -> Result<Vec<u8>> {
+        let endpoint = format!("{}{}/{}", self.base_url, ARTIFACTS_ROUTE, id);
+        debug!("fetching circuit artifact: url={}", endpoint);
+        let response = self.authenticated(self.client.get(&endpoint)).send()?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "failed to fetch circuit {}: status={}",
+                id,
+                response.status()
+            ));
+        }
+        Ok(response.bytes()?.to_vec())
+    }
+
+    /// Returns whether the registry has the artifact of the circuit `id`.
+    pub fn contains(&self, id: &str) -> Result<bool> {
+        let endpoint = format!("{}{}/{}", self.base_url, ARTIFACTS_ROUTE, id);
+        let response = self.authenticated(self.client.head(&endpoint)).send()?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(false),
+            status if status.is_success() => Ok(true),
+            status => Err(anyhow!(
+                "failed to look up circuit {}: status={}",
+                id,
+                status
+            )),
+        }
+    }
+
+    /// Publishes a circuit under its id. Publishing the same circuit twice is a no-op.
+    pub fn publish<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        circuit: &CircuitBuild<L, D>,
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+        hint_serializer: &impl HintSerializer<L, D>,
+    ) -> Result<String> {
+        let id = circuit.id();
+        if self.contains(&id)? {
+            debug!("circuit {} is already published", id);
+            return Ok(id);
+        }
+        let bytes = circuit
+            .serialize(gate_serializer, hint_serializer)
+            .map_err(|e| anyhow!("failed to serialize circuit {}: {:?}", id, e))?;
+        let endpoint = format!("{}{}/{}", self.base_url, ARTIFACTS_ROUTE, id);
+        let response = self
+            .authenticated(self.client.put(&endpoint))
+            .body(bytes)
+            .send()?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "failed to publish circuit {}: status={}",
+                id,
+                response.status()
+            ));
+        }
+        info!("published circuit {}", id);
+        Ok(id)
+    }
+
+    /// Records that the on-chain verifier with `verifier_digest` verifies wrapped proofs of the
+    /// circuit `circuit_id`.
+    pub fn publish_verifier_digest(&self, verifier_digest: &str, circuit_id: &str) -> Result<()> {
+        let entry = VerifierEntry {
+            verifier_digest: verifier_digest.to_string(),
+            circuit_id: circuit_id.to_string(),
+        };
+        let endpoint = format!("{}{}", self.base_url, VERIFIERS_ROUTE);
+        let response = self
+            .authenticated(self.client.post(&endpoint))
+            .json(&entry)
+            .send()?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "failed to publish verifier digest {}: status={}",
+                verifier_digest,
+                response.status()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the id of the circuit verified by the on-chain verifier with `verifier_digest`.
+    pub fn resolve_verifier_digest(&self, verifier_digest: &str) -> Result<String> {
+        let endpoint = format!("{}{}/{}", self.base_url, VERIFIERS_ROUTE, verifier_digest);
+        let response = self.authenticated(self.client.get(&endpoint)).send()?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "failed to resolve verifier digest {}: status={}",
+                verifier_digest,
+                response.status()
+            ));
+        }
+        let entry: VerifierEntry = response.json()?;
+        if entry.verifier_digest != verifier_digest {
+            return Err(anyhow!(
+                "registry returned the entry of verifier digest {} instead of {}",
+                entry.verifier_digest,
+                verifier_digest
+            ));
+        }
+        Ok(entry.circuit_id)
+    }
+
+    /// Returns the circuit `id`, from the local cache if present and from the registry otherwise.
+    /// Fetched artifacts are checked against `id` before being cached.
+    pub fn resolve<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        id: &str,
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+        hint_serializer: &impl HintSerializer<L, D>,
+    ) -> Result<CircuitBuild<L, D>> {
+        let path = self.cache_path(id);
+        if path.exists() {
+            let bytes = fs::read(&path)?;
+            return checked_deserialize(id, &bytes, gate_serializer, hint_serializer);
+        }
+
+        let bytes = self.fetch_bytes(id)?;
+        let circuit = checked_deserialize(id, &bytes, gate_serializer, hint_serializer)?;
+        fs::create_dir_all(&self.cache_dir)?;
+        fs::write(&path, bytes)?;
+        debug!("cached circuit {} at {}", id, path.display());
+        Ok(circuit)
+    }
+
+    /// Returns the circuit verified by the on-chain verifier with `verifier_digest`.
+    pub fn resolve_by_verifier_digest<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        verifier_digest: &str,
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+        hint_serializer: &impl HintSerializer<L, D>,
+    ) -> Result<CircuitBuild<L, D>> {
+        let id = self.resolve_verifier_digest(verifier_digest)?;
+        self.resolve(&id, gate_serializer, hint_serializer)
+    }
+}
+
+/// Deserializes a circuit and checks that it is the circuit `id`.
+fn checked_deserialize<L: PlonkParameters<D>, const D: usize>(
+    id: &str,
+    bytes: &[u8],
+    gate_serializer: &impl GateSerializer<L::Field, D>,
+    hint_serializer: &impl HintSerializer<L, D>,
+) -> Result<CircuitBuild<L, D>> {
+    let circuit = CircuitBuild::<L, D>::deserialize(bytes, gate_serializer, hint_serializer)
+        .map_err(|e| anyhow!("failed to deserialize circuit {}: {:?}", id, e))?;
+    if circuit.id() != id {
+        return Err(anyhow!(
+            "artifact of circuit {} contains circuit {}",
+            id,
+            circuit.id()
+        ));
+    }
+    Ok(circuit)
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
+    /// Loads the circuit `id` from the build directory, falling back to the artifact registry
+    /// configured by `ARTIFACT_REGISTRY_URL` if it has not been built locally.
+    pub fn load_by_id(
+        id: &str,
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+        hint_serializer: &impl HintSerializer<L, D>,
+    ) -> Result<Self> {
+        match ArtifactRegistry::from_env() {
+            Some(registry) => registry.resolve(id, gate_serializer, hint_serializer),
+            None => {
+                let path = Path::new(DEFAULT_BUILD_DIR).join(format!("{}.circuit", id));
+                let bytes = fs::read(&path)
+                    .map_err(|e| anyhow!("failed to read {}: {}", path.display(), e))?;
+                checked_deserialize(id, &bytes, gate_serializer, hint_serializer)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::circuit::{DefaultParameters, GateRegistry, HintRegistry};
+    use crate::frontend::hint::synchronous::Async;
+    use crate::prelude::*;
+    use crate::utils::test_circuits::{build_square_circuit, SquareHint};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    #[test]
+    fn test_resolve_from_cache() {
+        let circuit = build_square_circuit();
+
+        // The circuit only deserializes with its hint registered.
+        let gate_serializer = GateRegistry::<L, D>::new();
+        let mut hint_serializer = HintRegistry::<L, D>::new();
+        hint_serializer.register_async_hint::<Async<SquareHint>>();
+
+        // The registry is never reached when the artifact is cached.
+        let cache_dir = "./build/test_resolve_from_cache";
+        let registry = ArtifactRegistry::new("http://localhost:1".to_string(), cache_dir);
+        let id = circuit.id();
+        circuit.save(
+            &registry.cache_path(&id).to_str().unwrap().to_string(),
+            &gate_serializer,
+            &hint_serializer,
+        );
+        let resolved = registry
+            .resolve::<L, D>(&id, &gate_serializer, &hint_serializer)
+            .unwrap();
+        assert_eq!(resolved.id(), id);
+        let mut input = resolved.input();
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(3));
+        let (proof, mut output) = resolved.prove(&input);
+        resolved.verify(&proof, &input, &output);
+        assert_eq!(
+            output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(9)
+        );
+
+        // An artifact stored under the wrong id is rejected.
+        let wrong_id = "0x00000000000000000000";
+        fs::copy(registry.cache_path(&id), registry.cache_path(wrong_id)).unwrap();
+        assert!(registry
+            .resolve::<L, D>(wrong_id, &gate_serializer, &hint_serializer)
+            .is_err());
+    }
+}
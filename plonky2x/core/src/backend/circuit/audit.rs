@@ -0,0 +1,223 @@
+//! Structured audit records for generated proofs.
+//!
+//! Every proof submitted on-chain should be traceable to the circuit and input it was generated
+//! from and to the resources its generation used. `CircuitBuild::prove_with_audit` produces a
+//! [`ProofAuditRecord`] for each proof and hands it to an [`AuditSink`], which decides where the
+//! record is retained.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use log::info;
+use plonky2::field::types::PrimeField64;
+use plonky2::plonk::config::GenericHashOut;
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::serialization::serialize_proof;
+use super::{CircuitBuild, PlonkParameters, PublicInput};
+use crate::utils::hex;
+
+/// The audit record of one proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofAuditRecord {
+    /// The circuit id, as returned by `CircuitBuild::id`.
+    pub circuit_id: String,
+    /// The full circuit digest.
+    pub circuit_digest: String,
+    /// The sha256 hash of the input. For bytes io, this is the input hash that is verified
+    /// on-chain.
+    pub input_hash: String,
+    /// The number of witness generators of the circuit, by generator id.
+    pub hints: BTreeMap<String, usize>,
+    /// The number of asynchronous hints of the circuit.
+    pub nb_async_hints: usize,
+    pub witness_duration_ms: u128,
+    pub prove_duration_ms: u128,
+    /// The peak resident set size of the process after proving, where the platform reports it.
+    pub peak_rss_bytes: Option<u64>,
+    /// The size of the proof, in its versioned serialization.
+    pub proof_size_bytes: usize,
+    /// The time the proof was generated at, in seconds since the unix epoch.
+    pub timestamp: u64,
+}
+
+impl ProofAuditRecord {
+    pub fn new<L: PlonkParameters<D>, const D: usize>(
+        circuit: &CircuitBuild<L, D>,
+        input: &PublicInput<L, D>,
+        proof: &ProofWithPublicInputs<L::Field, L::Config, D>,
+        witness_duration: Duration,
+        prove_duration: Duration,
+    ) -> Self {
+        let circuit_digest = hex!(circuit
+            .data
+            .verifier_only
+            .circuit_digest
+            .to_vec()
+            .iter()
+            .flat_map(|e| e.to_canonical_u64().to_be_bytes())
+            .collect::<Vec<u8>>());
+        let input_hash = match input {
+            PublicInput::Bytes(bytes) => Sha256::digest(bytes),
+            input => Sha256::digest(bincode::serialize(input).unwrap()),
+        };
+        let mut hints = BTreeMap::new();
+        for generator in circuit.data.prover_only.generators.iter() {
+            *hints.entry(generator.0.id()).or_insert(0) += 1;
+        }
+        Self {
+            circuit_id: circuit.id(),
+            circuit_digest,
+            input_hash: hex!(input_hash.to_vec()),
+            hints,
+            nb_async_hints: circuit.async_hints.len(),
+            witness_duration_ms: witness_duration.as_millis(),
+            prove_duration_ms: prove_duration.as_millis(),
+            peak_rss_bytes: peak_rss_bytes(),
+            proof_size_bytes: serialize_proof(proof).unwrap().len(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        }
+    }
+}
+
+/// Reads the peak resident set size of the process from `/proc/self/status`.
+fn peak_rss_bytes() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kilobytes = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// A destination for audit records.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &ProofAuditRecord) -> Result<()>;
+}
+
+/// Logs each audit record as JSON.
+#[derive(Debug, Clone, Default)]
+pub struct LogAuditSink;
+
+impl AuditSink for LogAuditSink {
+    fn record(&self, record: &ProofAuditRecord) -> Result<()> {
+        info!("proof audit: {}", serde_json::to_string(record)?);
+        Ok(())
+    }
+}
+
+/// Appends each audit record as one line of JSON to a file.
+#[derive(Debug)]
+pub struct JsonLinesAuditSink {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl JsonLinesAuditSink {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns a sink writing to the file given by the env variable `PROOF_AUDIT_LOG`, if set.
+    pub fn from_env() -> Option<Self> {
+        env::var("PROOF_AUDIT_LOG").ok().map(Self::new)
+    }
+}
+
+impl AuditSink for JsonLinesAuditSink {
+    fn record(&self, record: &ProofAuditRecord) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::prelude::*;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    /// Keeps the records in memory.
+    #[derive(Default)]
+    struct MemorySink(Arc<Mutex<Vec<ProofAuditRecord>>>);
+
+    impl AuditSink for MemorySink {
+        fn record(&self, record: &ProofAuditRecord) -> Result<()> {
+            self.0.lock().unwrap().push(record.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_proof_audit_record() {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let a = builder.evm_read::<ByteVariable>();
+        let b = builder.evm_read::<ByteVariable>();
+        let c = builder.xor(a, b);
+        builder.evm_write(c);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.evm_write::<ByteVariable>(0x12);
+        input.evm_write::<ByteVariable>(0x34);
+
+        let sink = MemorySink::default();
+        let (proof, output) = circuit.prove_with_audit(&input, &sink);
+        circuit.verify(&proof, &input, &output);
+
+        let records = sink.0.lock().unwrap();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.circuit_id, circuit.id());
+        assert!(record.circuit_digest.starts_with(&record.circuit_id));
+        assert_eq!(
+            record.input_hash,
+            hex!(Sha256::digest([0x12, 0x34]).to_vec())
+        );
+        assert_eq!(
+            record.hints.values().sum::<usize>(),
+            circuit.data.prover_only.generators.len()
+        );
+        assert_eq!(
+            record.proof_size_bytes,
+            serialize_proof(&proof).unwrap().len()
+        );
+
+        let path = env::temp_dir().join(format!("{}.audit.jsonl", circuit.id()));
+        let _ = fs::remove_file(&path);
+        let sink = JsonLinesAuditSink::new(&path);
+        sink.record(record).unwrap();
+        sink.record(record).unwrap();
+        let lines = fs::read_to_string(&path).unwrap();
+        let lines = lines.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            &serde_json::from_str::<ProofAuditRecord>(lines[1]).unwrap(),
+            record
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! Proving and verification of batches of proofs.
+//!
+//! Provers generate many proofs of the same circuit, so `CircuitBuild::prove_batch` proves them in
+//! parallel on a thread pool. Each worker keeps the scratch space of witness generation across its
+//! proofs, and the async hints of the whole batch share a single runtime instead of starting one
+//! per proof.
+//!
+//! Relayers validate every proof of a batch before submitting it on-chain, and need to know which
+//! proofs failed rather than only the first failure. `CircuitBuild::verify_batch` verifies the
+//! proofs in parallel and reports the result and verification time of each one.
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use log::debug;
+use plonky2::iop::witness::PartialWitness;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use plonky2::plonk::prover::prove_with_partition_witness;
+use plonky2::util::timing::TimingTree;
+use starkyx::maybe_rayon::rayon::ThreadPoolBuilder;
+use starkyx::maybe_rayon::*;
+use tokio::runtime::Runtime;
+
+use super::witness::{generate_witness_with_buffers, WitnessBuffers};
+use super::{CircuitBuild, PlonkParameters, PublicInput, PublicOutput};
+
+/// A proof of a batch, with the input and output it is claimed to prove.
+pub type BatchProof<L, const D: usize> = (
+    ProofWithPublicInputs<<L as PlonkParameters<D>>::Field, <L as PlonkParameters<D>>::Config, D>,
+    PublicInput<L, D>,
+    PublicOutput<L, D>,
+);
+
+/// The verification of one proof of a batch.
+#[derive(Debug)]
+pub struct ProofVerification {
+    /// The index of the proof in the batch.
+    pub index: usize,
+    pub result: Result<()>,
+    pub duration: Duration,
+}
+
+/// The verification of a batch of proofs, with one `ProofVerification` per proof in batch order.
+#[derive(Debug)]
+pub struct BatchVerificationReport {
+    pub proofs: Vec<ProofVerification>,
+    /// The wall-clock time of the whole batch.
+    pub duration: Duration,
+}
+
+impl BatchVerificationReport {
+    /// Whether every proof of the batch is valid.
+    pub fn is_valid(&self) -> bool {
+        self.proofs.iter().all(|proof| proof.result.is_ok())
+    }
+
+    /// The proofs that failed to verify.
+    pub fn failures(&self) -> impl Iterator<Item = &ProofVerification> {
+        self.proofs.iter().filter(|proof| proof.result.is_err())
+    }
+
+    /// The sum of the verification times of the proofs, which exceeds `duration` when the proofs
+    /// are verified in parallel.
+    pub fn total_proof_duration(&self) -> Duration {
+        self.proofs.iter().map(|proof| proof.duration).sum()
+    }
+
+    /// The longest verification time of a proof of the batch.
+    pub fn max_proof_duration(&self) -> Duration {
+        self.proofs
+            .iter()
+            .map(|proof| proof.duration)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
+    /// Generates a proof for each input on the global thread pool. Returns the proofs and outputs
+    /// in the same order as the inputs.
+    #[allow(clippy::type_complexity)]
+    pub fn prove_batch(
+        &self,
+        inputs: &[PublicInput<L, D>],
+    ) -> Vec<(
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let start_time = Instant::now();
+        let runtime = Runtime::new().expect("failed to create runtime");
+        let proofs = inputs
+            .par_iter()
+            .map_init(WitnessBuffers::new, |buffers, input| {
+                let mut pw = PartialWitness::new();
+                self.io.set_witness(&mut pw, input);
+                let partition_witness = generate_witness_with_buffers(
+                    pw,
+                    &self.data.prover_only,
+                    &self.data.common,
+                    &self.async_hints,
+                    runtime.handle(),
+                    buffers,
+                )
+                .unwrap();
+                let proof_with_pis = prove_with_partition_witness::<L::Field, L::Config, D>(
+                    &self.data.prover_only,
+                    &self.data.common,
+                    partition_witness,
+                    &mut TimingTree::default(),
+                )
+                .unwrap();
+                let output = PublicOutput::from_proof_with_pis(&self.io, &proof_with_pis);
+                (proof_with_pis, output)
+            })
+            .collect::<Vec<_>>();
+        debug!(
+            "proving a batch of {} proofs took {:?}",
+            inputs.len(),
+            start_time.elapsed()
+        );
+        proofs
+    }
+
+    /// Generates a proof for each input like `prove_batch`, on a dedicated pool of `nb_workers`
+    /// threads.
+    #[allow(clippy::type_complexity)]
+    pub fn prove_batch_with_workers(
+        &self,
+        inputs: &[PublicInput<L, D>],
+        nb_workers: usize,
+    ) -> Vec<(
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(nb_workers)
+            .build()
+            .expect("failed to create thread pool");
+        pool.install(|| self.prove_batch(inputs))
+    }
+
+    /// Verifies the proofs of a batch in parallel, reporting the result of each of them.
+    pub fn verify_batch(&self, batch: &[BatchProof<L, D>]) -> BatchVerificationReport
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let start_time = Instant::now();
+        let proofs = batch
+            .par_iter()
+            .enumerate()
+            .map(|(index, (proof, input, output))| {
+                let proof_start_time = Instant::now();
+                let result = self.try_verify(proof, input, output);
+                ProofVerification {
+                    index,
+                    result,
+                    duration: proof_start_time.elapsed(),
+                }
+            })
+            .collect::<Vec<_>>();
+        BatchVerificationReport {
+            proofs,
+            duration: start_time.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::utils::test_circuits::{add_input, build_add_circuit, build_square_circuit};
+
+    #[test]
+    fn test_prove_batch() {
+        // The squares are computed by an async hint, run on the runtime shared by the batch.
+        let circuit = build_square_circuit();
+        let inputs = (0..5u64)
+            .map(|i| {
+                let mut input = circuit.input();
+                input.write::<Variable>(GoldilocksField::from_canonical_u64(i));
+                input
+            })
+            .collect::<Vec<_>>();
+        let proofs = circuit.prove_batch_with_workers(&inputs, 2);
+        assert_eq!(proofs.len(), inputs.len());
+        for (i, ((proof, mut output), input)) in proofs.into_iter().zip(&inputs).enumerate() {
+            circuit.verify(&proof, input, &output);
+            assert_eq!(
+                output.read::<Variable>(),
+                GoldilocksField::from_canonical_u64((i * i) as u64)
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let circuit = build_add_circuit();
+        let mut batch = (0..4u64)
+            .map(|i| {
+                let input = add_input(&circuit, i, 1);
+                let (proof, output) = circuit.prove(&input);
+                (proof, input, output)
+            })
+            .collect::<Vec<_>>();
+        // Claim the output of the first proof for the second one.
+        batch[1].2 = batch[0].2.clone();
+        // Break the third proof without changing its public inputs.
+        batch[2].0.proof.opening_proof.pow_witness += GoldilocksField::ONE;
+
+        let report = circuit.verify_batch(&batch);
+        assert!(!report.is_valid());
+        assert_eq!(report.proofs.len(), 4);
+        let failures = report
+            .failures()
+            .map(|proof| proof.index)
+            .collect::<Vec<_>>();
+        assert_eq!(failures, vec![1, 2]);
+        assert!(report.max_proof_duration() <= report.total_proof_duration());
+    }
+}
@@ -3,7 +3,8 @@ use std::fs;
 use std::path::Path;
 use std::time::Instant;
 
-use log::{debug, trace};
+use anyhow::{ensure, Result};
+use log::{debug, log_enabled, trace, Level};
 use plonky2::field::types::PrimeField64;
 use plonky2::iop::witness::PartialWitness;
 use plonky2::plonk::circuit_data::CircuitData;
@@ -13,12 +14,15 @@ use plonky2::plonk::prover::prove_with_partition_witness;
 use plonky2::util::serialization::{Buffer, GateSerializer, IoResult, Read, Write};
 use plonky2::util::timing::TimingTree;
 
+use super::audit::{AuditSink, ProofAuditRecord};
 use super::config::PlonkParameters;
 use super::input::PublicInput;
 use super::output::PublicOutput;
 use super::serialization::hints::HintSerializer;
 use super::serialization::{GateRegistry, HintRegistry};
-use super::witness::{generate_witness, generate_witness_async};
+use super::timing::{ProofTimings, WitnessTimings};
+use super::witness::{generate_witness, generate_witness_async, generate_witness_timed};
+use crate::frontend::builder::assertion::NamedAssertion;
 use crate::frontend::builder::CircuitIO;
 use crate::frontend::hint::asynchronous::generator::AsyncHintDataRef;
 use crate::utils::hex;
@@ -32,6 +36,8 @@ pub struct CircuitBuild<L: PlonkParameters<D>, const D: usize> {
     pub data: CircuitData<L::Field, L::Config, D>,
     pub io: CircuitIO<D>,
     pub async_hints: BTreeMap<usize, AsyncHintDataRef<L, D>>,
+    /// The assertions made with `assert_is_equal_named`, which are not serialized.
+    pub named_assertions: Vec<NamedAssertion>,
 }
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
@@ -40,14 +46,18 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
         PublicInput::new(&self.io)
     }
 
-    /// Generates a proof for the circuit using a plonky2 partial witness. The proof can be verified
-    /// using `verify`.
-    pub fn prove_with_partial_witness(
+    /// Generates a proof for the circuit using a plonky2 partial witness, also returning the time
+    /// spent generating the witness and the proof. The time spent in each generator is only
+    /// measured if `time_generators` is set.
+    #[allow(clippy::type_complexity)]
+    fn prove_with_partial_witness_timed(
         &self,
         pw: PartialWitness<L::Field>,
+        time_generators: bool,
     ) -> (
         ProofWithPublicInputs<L::Field, L::Config, D>,
         PublicOutput<L, D>,
+        ProofTimings,
     )
     where
         <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
@@ -55,15 +65,31 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
     {
         let start_time = Instant::now();
         trace!("generating witness...");
-        let partition_witness = generate_witness(
-            pw,
-            &self.data.prover_only,
-            &self.data.common,
-            &self.async_hints,
-        )
-        .unwrap();
-        let elapsed_time = start_time.elapsed();
-        debug!("Witness generation took {:?}", elapsed_time);
+        let (partition_witness, mut witness_timings) = match time_generators {
+            true => generate_witness_timed(
+                pw,
+                &self.data.prover_only,
+                &self.data.common,
+                &self.async_hints,
+            )
+            .unwrap(),
+            false => (
+                generate_witness(
+                    pw,
+                    &self.data.prover_only,
+                    &self.data.common,
+                    &self.async_hints,
+                )
+                .unwrap(),
+                WitnessTimings::default(),
+            ),
+        };
+        let witness_duration = start_time.elapsed();
+        witness_timings.total = witness_duration;
+        match time_generators {
+            true => debug!("{}", witness_timings),
+            false => debug!("Witness generation took {:?}", witness_duration),
+        }
         trace!("finished generating witness");
         trace!("generating proof...");
         let proof_with_pis = prove_with_partition_witness::<L::Field, L::Config, D>(
@@ -77,6 +103,28 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
         let output = PublicOutput::from_proof_with_pis(&self.io, &proof_with_pis);
         let elapsed_time = start_time.elapsed();
         debug!("proving took: {:?}", elapsed_time);
+        let timings = ProofTimings {
+            witness: witness_timings,
+            prove: elapsed_time - witness_duration,
+        };
+        (proof_with_pis, output, timings)
+    }
+
+    /// Generates a proof for the circuit using a plonky2 partial witness. The proof can be verified
+    /// using `verify`.
+    pub fn prove_with_partial_witness(
+        &self,
+        pw: PartialWitness<L::Field>,
+    ) -> (
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let (proof_with_pis, output, _) =
+            self.prove_with_partial_witness_timed(pw, log_enabled!(Level::Debug));
         (proof_with_pis, output)
     }
 
@@ -97,6 +145,55 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
         self.prove_with_partial_witness(pw)
     }
 
+    /// Generates a proof for the circuit, also returning the time spent in each generator of the
+    /// witness and in the prover.
+    #[allow(clippy::type_complexity)]
+    pub fn prove_with_timings(
+        &self,
+        input: &PublicInput<L, D>,
+    ) -> (
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+        ProofTimings,
+    )
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let mut pw = PartialWitness::new();
+        self.io.set_witness(&mut pw, input);
+        self.prove_with_partial_witness_timed(pw, true)
+    }
+
+    /// Generates a proof for the circuit and hands its audit record to `sink`. Failing to record
+    /// the audit is fatal, so that no proof leaves the prover without one.
+    pub fn prove_with_audit(
+        &self,
+        input: &PublicInput<L, D>,
+        sink: &dyn AuditSink,
+    ) -> (
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let mut pw = PartialWitness::new();
+        self.io.set_witness(&mut pw, input);
+        let (proof_with_pis, output, timings) =
+            self.prove_with_partial_witness_timed(pw, log_enabled!(Level::Debug));
+        let record = ProofAuditRecord::new(
+            self,
+            input,
+            &proof_with_pis,
+            timings.witness.total,
+            timings.prove,
+        );
+        sink.record(&record).expect("failed to record proof audit");
+        (proof_with_pis, output)
+    }
+
     /// Generates a proof for the circuit using a plonky2 partial witness. The proof can be verified
     /// using `verify`.
     pub async fn prove_with_partial_witness_async(
@@ -156,6 +253,32 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
         self.prove_with_partial_witness_async(pw).await
     }
 
+    /// Reconstructs the public input of a proof of the circuit.
+    ///
+    /// For cyclic circuits, the verifier data is recovered from the public inputs of the proof and
+    /// checked to be the verifier data of this circuit. The proof of the previous step is not part
+    /// of the proof and must be written with `PublicInput::proof_write` before re-proving.
+    pub fn input_from_proof(
+        &self,
+        proof: &ProofWithPublicInputs<L::Field, L::Config, D>,
+    ) -> Result<PublicInput<L, D>> {
+        let mut input = PublicInput::<L, D>::from_proof_with_pis(&self.io, proof);
+        if let (CircuitIO::CyclicProof(io), PublicInput::CyclicProof(_, _, data)) =
+            (&self.io, &mut input)
+        {
+            let offset = io.input.len() + io.output.len();
+            let verifier_only = &self.data.verifier_only;
+            let mut expected = verifier_only.circuit_digest.to_vec();
+            expected.extend(verifier_only.constants_sigmas_cap.flatten());
+            ensure!(
+                proof.public_inputs.get(offset..) == Some(&expected[..]),
+                "the proof does not embed the verifier data of the circuit"
+            );
+            *data = Box::new(Some(self.data.verifier_data()));
+        }
+        Ok(input)
+    }
+
     /// Verifies a proof for the circuit.
     pub fn verify(
         &self,
@@ -170,6 +293,26 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
         self.data.verify(proof.clone()).unwrap();
     }
 
+    /// Verifies a proof for the circuit, returning an error instead of panicking if it is invalid.
+    pub fn try_verify(
+        &self,
+        proof: &ProofWithPublicInputs<L::Field, L::Config, D>,
+        input: &PublicInput<L, D>,
+        output: &PublicOutput<L, D>,
+    ) -> Result<()> {
+        let expected_input = PublicInput::<L, D>::from_proof_with_pis(&self.io, proof);
+        let expected_output = PublicOutput::<L, D>::from_proof_with_pis(&self.io, proof);
+        ensure!(
+            input == &expected_input,
+            "the proof does not match the input"
+        );
+        ensure!(
+            output == &expected_output,
+            "the proof does not match the output"
+        );
+        self.data.verify(proof.clone())
+    }
+
     /// A unique identifier for the circuit.
     pub fn id(&self) -> String {
         let circuit_digest = hex!(self
@@ -238,6 +381,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
             data,
             io,
             async_hints,
+            named_assertions: Vec::new(),
         })
     }
 
@@ -298,8 +442,11 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
 #[cfg(test)]
 pub(crate) mod tests {
 
-    use crate::backend::circuit::CircuitBuild;
+    use crate::backend::circuit::{CircuitBuild, PublicInput};
+    use crate::frontend::builder::io::{CyclicProofIO, RecursiveProofsIO};
+    use crate::frontend::builder::CircuitIO;
     use crate::prelude::*;
+    use crate::utils::test_circuits::{add_input, build_add_circuit};
 
     type L = DefaultParameters;
     const D: usize = 2;
@@ -421,4 +568,69 @@ pub(crate) mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_input_from_proof_with_field_io() {
+        let circuit = build_add_circuit();
+        let input = add_input(&circuit, 1, 2);
+        let (proof, _) = circuit.prove(&input);
+
+        assert_eq!(circuit.input_from_proof(&proof).unwrap(), input);
+    }
+
+    #[test]
+    fn test_input_from_proof_with_recursive_proofs_io() {
+        let mut circuit = build_add_circuit();
+        let input = add_input(&circuit, 1, 2);
+        let (proof, _) = circuit.prove(&input);
+
+        // The inner proofs and the inputs of recursive circuits are not public inputs.
+        let CircuitIO::Elements(io) = circuit.io.clone() else {
+            panic!("expected field io");
+        };
+        circuit.io = CircuitIO::RecursiveProofs(RecursiveProofsIO {
+            proof_input: vec![],
+            input: io.input,
+            output: io.output,
+        });
+        let PublicInput::RecursiveProofs(proofs, elements) =
+            circuit.input_from_proof(&proof).unwrap()
+        else {
+            panic!("expected recursive proofs input");
+        };
+        assert!(proofs.is_empty());
+        assert!(elements.is_empty());
+    }
+
+    #[test]
+    fn test_input_from_proof_without_verifier_data() {
+        let mut circuit = build_add_circuit();
+        let input = add_input(&circuit, 1, 2);
+        let (proof, _) = circuit.prove(&input);
+
+        // Read as a cyclic proof, the output takes the place of the verifier data.
+        let CircuitIO::Elements(io) = circuit.io.clone() else {
+            panic!("expected field io");
+        };
+        circuit.io = CircuitIO::CyclicProof(CyclicProofIO {
+            input: io.input,
+            proof: None,
+            verifier_data: None,
+            output: vec![],
+            closed: true,
+        });
+        let error = circuit.input_from_proof(&proof).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the proof does not embed the verifier data of the circuit"
+        );
+
+        // The digest of the circuit alone is not the verifier data either.
+        let mut forged = proof.clone();
+        forged.public_inputs.truncate(2);
+        forged
+            .public_inputs
+            .extend(circuit.data.verifier_only.circuit_digest.elements);
+        assert!(circuit.input_from_proof(&forged).is_err());
+    }
 }
@@ -0,0 +1,202 @@
+//! A cache of built circuits keyed by the definition of the circuit.
+//!
+//! Building a large circuit takes minutes, but its result only depends on the code of
+//! `Circuit::define` and the parameters it is built with. A circuit that implements
+//! `Circuit::version` opts into the cache: the version, the circuit type and the parameters are
+//! hashed into a key, and a circuit built under the same key is loaded from disk instead of being
+//! rebuilt. Bumping the version whenever the definition changes invalidates the cache.
+
+use std::any::type_name;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use log::{debug, info};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::util::serialization::GateSerializer;
+use sha2::{Digest, Sha256};
+
+use super::serialization::hints::HintSerializer;
+use super::{Circuit, CircuitBuild, PlonkParameters};
+use crate::prelude::CircuitBuilder;
+use crate::utils::hex;
+
+/// A cache of built circuits, stored next to the circuits of a build folder.
+#[derive(Debug, Clone)]
+pub struct BuildCache {
+    dir: PathBuf,
+}
+
+impl BuildCache {
+    /// Creates a cache for the build folder `dir`. Circuits are saved as `{dir}/{id}.circuit` and
+    /// each key records the id of its circuit in `{dir}/cache/{key}`.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The cache key of the circuit `C` built with the parameters `L`, or `None` if `C` has no
+    /// version.
+    pub fn key<C: Circuit, L: PlonkParameters<D>, const D: usize>() -> Option<String> {
+        let version = C::version()?;
+        let mut hasher = Sha256::new();
+        for part in [
+            env!("CARGO_PKG_VERSION"),
+            type_name::<C>(),
+            type_name::<L>(),
+            &D.to_string(),
+            &version,
+        ] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        Some(hex!(hasher.finalize().to_vec()))
+    }
+
+    fn index_path(&self, key: &str) -> PathBuf {
+        self.dir.join("cache").join(key)
+    }
+
+    fn circuit_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.circuit", id))
+    }
+
+    /// Loads the circuit built under `key`, if any.
+    pub fn load<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        key: &str,
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+        hint_serializer: &impl HintSerializer<L, D>,
+    ) -> Result<Option<CircuitBuild<L, D>>> {
+        let Ok(id) = fs::read_to_string(self.index_path(key)) else {
+            return Ok(None);
+        };
+        let id = id.trim();
+        let Ok(bytes) = fs::read(self.circuit_path(id)) else {
+            debug!("build cache entry {} points to missing circuit {}", key, id);
+            return Ok(None);
+        };
+        let circuit = CircuitBuild::<L, D>::deserialize(&bytes, gate_serializer, hint_serializer)
+            .map_err(|_| anyhow!("failed to deserialize cached circuit {}", id))?;
+        if circuit.id() != id {
+            return Err(anyhow!(
+                "cached circuit has id {}, expected {}",
+                circuit.id(),
+                id
+            ));
+        }
+        Ok(Some(circuit))
+    }
+
+    /// Saves `circuit` and records it under `key`.
+    pub fn store<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        key: &str,
+        circuit: &CircuitBuild<L, D>,
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+        hint_serializer: &impl HintSerializer<L, D>,
+    ) -> Result<()> {
+        let id = circuit.id();
+        let bytes = circuit
+            .serialize(gate_serializer, hint_serializer)
+            .map_err(|_| anyhow!("failed to serialize circuit {}", id))?;
+        let index_path = self.index_path(key);
+        fs::create_dir_all(index_path.parent().unwrap())?;
+        fs::write(self.circuit_path(&id), bytes)?;
+        fs::write(index_path, &id)?;
+        Ok(())
+    }
+
+    /// Returns the circuit `C` from the cache, building and caching it on a miss. Circuits without
+    /// a version are always rebuilt.
+    pub fn get_or_build<C: Circuit, L: PlonkParameters<D>, const D: usize>(
+        &self,
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+        hint_serializer: &impl HintSerializer<L, D>,
+    ) -> Result<CircuitBuild<L, D>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    {
+        let key = Self::key::<C, L, D>();
+        if let Some(key) = &key {
+            if let Some(circuit) = self.load(key, gate_serializer, hint_serializer)? {
+                info!("Loaded circuit {} from the build cache.", circuit.id());
+                return Ok(circuit);
+            }
+        }
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        C::define::<L, D>(&mut builder);
+        let circuit = builder.build();
+        if let Some(key) = &key {
+            self.store(key, &circuit, gate_serializer, hint_serializer)?;
+        }
+        Ok(circuit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::backend::circuit::{DefaultParameters, GateRegistry, HintRegistry};
+    use crate::prelude::Variable;
+
+    const D: usize = 2;
+    type L = DefaultParameters;
+
+    #[derive(Debug, Clone)]
+    struct VersionedCircuit;
+
+    impl Circuit for VersionedCircuit {
+        fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
+        where
+            <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+                AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+        {
+            let a = builder.read::<Variable>();
+            let b = builder.add(a, a);
+            builder.write(b);
+        }
+
+        fn version() -> Option<String> {
+            Some("1".to_string())
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct UnversionedCircuit;
+
+    impl Circuit for UnversionedCircuit {
+        fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
+        where
+            <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+                AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+        {
+            VersionedCircuit::define(builder);
+        }
+    }
+
+    #[test]
+    fn test_build_cache() {
+        let dir = env::temp_dir().join("plonky2x-build-cache");
+        let _ = fs::remove_dir_all(&dir);
+        let cache = BuildCache::new(&dir);
+        let gates = GateRegistry::<L, D>::new();
+        let hints = HintRegistry::<L, D>::new();
+
+        assert!(BuildCache::key::<UnversionedCircuit, L, D>().is_none());
+        let key = BuildCache::key::<VersionedCircuit, L, D>().unwrap();
+        assert!(cache.load(&key, &gates, &hints).unwrap().is_none());
+
+        let circuit = cache
+            .get_or_build::<VersionedCircuit, L, D>(&gates, &hints)
+            .unwrap();
+        let cached = cache.load(&key, &gates, &hints).unwrap().unwrap();
+        assert_eq!(cached.id(), circuit.id());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -2,6 +2,7 @@ use core::fmt::Debug;
 
 use plonky2::field::extension::Extendable;
 use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::Field;
 use plonky2::hash::hash_types::RichField;
 use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 use serde::{Deserialize, Serialize};
@@ -10,8 +11,17 @@ use starkyx::math::prelude::CubicParameters;
 use starkyx::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
 
 use crate::backend::wrapper::plonky2_config::PoseidonBN128GoldilocksConfig;
+use crate::frontend::hash::poseidon::poseidon2::Poseidon2GoldilocksConfig;
 
 /// Parameters such as the field, hash function, etc. used for the circuit.
+///
+/// The builder and the variables are generic over these parameters, so a circuit can be
+/// instantiated with any field and config that plonky2 and curta support. The field must be a
+/// plonky2 `RichField` of at least 64 bits: the uint gadgets compute products of 32-bit limbs
+/// without reduction, so fields such as BabyBear cannot hold their intermediate values. As of
+/// plonky2 v0.2, Goldilocks is the only such field, and alternative parameters differ in their
+/// configs, as `Groth16WrapperParameters` does. The field size is checked at compile time by
+/// `FIELD_BITS_CHECK`.
 pub trait PlonkParameters<const D: usize>:
     Debug + Clone + PartialEq + Sync + Send + 'static
 {
@@ -27,6 +37,14 @@ pub trait PlonkParameters<const D: usize>:
     >;
 
     type CubicParams: CubicParameters<Self::Field>;
+
+    /// Fails to compile for fields of less than 64 bits. It is evaluated by `CircuitBuilder::new`
+    /// for every parameters the builder is instantiated with, and must not be overridden.
+    #[doc(hidden)]
+    const FIELD_BITS_CHECK: () = assert!(
+        <Self::Field as Field>::BITS >= 64,
+        "the uint gadgets need a field of at least 64 bits"
+    );
 }
 
 /// Default parameters for the circuit. Uses the `PoseidonGoldilocksConfig` in Plonky2.
@@ -56,3 +74,34 @@ impl PlonkParameters<2> for Groth16WrapperParameters {
 
     type CurtaConfig = CurtaPoseidonGoldilocksConfig;
 }
+
+/// Parameters which use Poseidon2 as the hasher of the circuit. Uses the
+/// `Poseidon2GoldilocksConfig`, whose permutation gate is cheaper to verify recursively than the
+/// Poseidon gate. The curta proofs keep using Poseidon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Poseidon2Parameters;
+
+impl PlonkParameters<2> for Poseidon2Parameters {
+    type Field = GoldilocksField;
+
+    type CubicParams = GoldilocksCubicParameters;
+
+    type Config = Poseidon2GoldilocksConfig;
+
+    type CurtaConfig = CurtaPoseidonGoldilocksConfig;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_bits_check() {
+        // Evaluating the check is a compile error for the parameters with a field that is too
+        // small, so the ones of the crate must all evaluate it.
+        let () = <DefaultParameters as PlonkParameters<2>>::FIELD_BITS_CHECK;
+        let () = <Groth16WrapperParameters as PlonkParameters<2>>::FIELD_BITS_CHECK;
+        let () = <Poseidon2Parameters as PlonkParameters<2>>::FIELD_BITS_CHECK;
+        assert_eq!(<GoldilocksField as Field>::BITS, 64);
+    }
+}
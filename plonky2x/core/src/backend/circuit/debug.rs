@@ -0,0 +1,167 @@
+//! A proving mode reporting unsatisfied constraints instead of panicking in the prover.
+//!
+//! `CircuitBuild::debug_prove` generates the witness while checking every generated value against
+//! the values already in the witness. A conflict means that the copy constraint between the two
+//! targets is not satisfied, and is reported as a `ConstraintFailure` naming the assertion made
+//! with `assert_is_equal_named` which connected them, if any.
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use anyhow::Result;
+use plonky2::field::types::Field;
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartialWitness, PartitionWitness};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use plonky2::plonk::prover::prove_with_partition_witness;
+use plonky2::util::timing::TimingTree;
+
+use super::witness::{generate_witness_checked, WitnessConflict};
+use super::{CircuitBuild, PlonkParameters, PublicInput, PublicOutput};
+use crate::frontend::builder::assertion::NamedAssertion;
+
+/// An unsatisfied constraint found by `CircuitBuild::debug_prove`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintFailure<F: Field> {
+    /// The named assertion which is not satisfied, if the conflicting targets were connected by
+    /// one.
+    pub assertion: Option<NamedAssertion>,
+    /// The index of the conflicting pair of targets in the targets of the assertion.
+    pub limb: Option<usize>,
+    pub conflict: WitnessConflict<F>,
+}
+
+impl<F: Field> Display for ConstraintFailure<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match (&self.assertion, self.limb) {
+            (Some(assertion), Some(limb)) => write!(
+                f,
+                "assertion {} failed: {} != {} (limb {}), set by {} on {:?}",
+                assertion,
+                self.conflict.existing,
+                self.conflict.conflicting,
+                limb,
+                self.conflict.generator,
+                self.conflict.target
+            ),
+            _ => write!(f, "unsatisfied copy constraint: {}", self.conflict),
+        }
+    }
+}
+
+impl<F: Field> std::error::Error for ConstraintFailure<F> {}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
+    /// Generates a proof for the circuit like `prove`, but an unsatisfied constraint returns a
+    /// `ConstraintFailure` error describing it instead of panicking.
+    #[allow(clippy::type_complexity)]
+    pub fn debug_prove(
+        &self,
+        input: &PublicInput<L, D>,
+    ) -> Result<(
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let partition_witness = self.generate_witness_checked(input)?;
+        let proof_with_pis = prove_with_partition_witness::<L::Field, L::Config, D>(
+            &self.data.prover_only,
+            &self.data.common,
+            partition_witness,
+            &mut TimingTree::default(),
+        )?;
+        let output = PublicOutput::from_proof_with_pis(&self.io, &proof_with_pis);
+        Ok((proof_with_pis, output))
+    }
+
+    /// Generates the witness of `input`, returning a `ConstraintFailure` error if a copy constraint
+    /// is not satisfied.
+    pub(crate) fn generate_witness_checked(
+        &self,
+        input: &PublicInput<L, D>,
+    ) -> Result<PartitionWitness<L::Field>> {
+        let mut pw = PartialWitness::new();
+        self.io.set_witness(&mut pw, input);
+        generate_witness_checked(
+            pw,
+            &self.data.prover_only,
+            &self.data.common,
+            &self.async_hints,
+        )
+        .map_err(|e| match e.downcast::<WitnessConflict<L::Field>>() {
+            Ok(conflict) => self.constraint_failure(conflict).into(),
+            Err(e) => e,
+        })
+    }
+
+    /// Finds the named assertion connecting the target of `conflict` to the target which was set
+    /// first, as the assertion whose targets are in the same partition.
+    fn constraint_failure(
+        &self,
+        conflict: WitnessConflict<L::Field>,
+    ) -> ConstraintFailure<L::Field> {
+        let num_wires = self.data.common.config.num_wires;
+        let degree = self.data.common.degree();
+        let representative_map = &self.data.prover_only.representative_map;
+        let partition = |target: Target| representative_map[target.index(num_wires, degree)];
+
+        let conflict_partition = partition(conflict.target);
+        let failure = self.named_assertions.iter().find_map(|assertion| {
+            assertion
+                .targets
+                .iter()
+                .position(|(t1, _)| partition(*t1) == conflict_partition)
+                .map(|limb| (assertion.clone(), limb))
+        });
+        let (assertion, limb) = failure.unzip();
+        ConstraintFailure {
+            assertion,
+            limb,
+            conflict,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::PrimeField64;
+
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_debug_prove_named_assertion() {
+        let mut builder = DefaultBuilder::new();
+        let slot = builder.read::<Variable>();
+        let header_slot = builder.read::<Variable>();
+        let doubled = builder.add(slot, slot);
+        builder.assert_is_equal_named(doubled, header_slot, "slot matches header");
+        builder.write(doubled);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(3));
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(6));
+        let (proof, output) = circuit.debug_prove(&input).unwrap();
+        circuit.verify(&proof, &input, &output);
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(3));
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(7));
+        let error = circuit.debug_prove(&input).unwrap_err();
+        let failure = error
+            .downcast_ref::<ConstraintFailure<GoldilocksField>>()
+            .unwrap();
+        let assertion = failure.assertion.as_ref().unwrap();
+        assert_eq!(assertion.name, "slot matches header");
+        assert!(assertion.location.starts_with(file!()));
+        let mut values = [failure.conflict.existing, failure.conflict.conflicting]
+            .map(|value| value.to_canonical_u64());
+        values.sort();
+        assert_eq!(values, [6, 7]);
+        assert!(error.to_string().contains("slot matches header"));
+    }
+}
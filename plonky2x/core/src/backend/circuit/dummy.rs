@@ -0,0 +1,189 @@
+//! A cache of dummy proofs for padding recursive circuits.
+//!
+//! Aggregation trees are padded with proofs of a dummy circuit of the same shape as the inner
+//! circuit, and cyclic circuits start from a base proof of such a dummy circuit. Generating a dummy
+//! proof costs as much as a real proof of the inner circuit, so they are generated once per shape
+//! and saved in `{dir}/dummy`, after which every prover reuses the same proof.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use log::{debug, info};
+use plonky2::plonk::circuit_data::{CommonCircuitData, VerifierOnlyCircuitData};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use plonky2::recursion::dummy_circuit::{cyclic_base_proof, dummy_circuit, dummy_proof};
+use plonky2::util::serialization::GateSerializer;
+use sha2::{Digest, Sha256};
+
+use super::serialization::{deserialize_proof, serialize_proof};
+use super::PlonkParameters;
+use crate::utils::hex;
+
+/// A proof of the dummy circuit with the shape of an inner circuit, with the verifier data of the
+/// dummy circuit.
+#[derive(Debug, Clone)]
+pub struct DummyProof<L: PlonkParameters<D>, const D: usize> {
+    pub proof: ProofWithPublicInputs<L::Field, L::Config, D>,
+    pub verifier_data: VerifierOnlyCircuitData<L::Config, D>,
+}
+
+/// A cache of dummy proofs, stored in the `dummy` folder of a build folder.
+#[derive(Debug, Clone)]
+pub struct DummyProofCache {
+    dir: PathBuf,
+}
+
+impl DummyProofCache {
+    /// Creates a cache for the build folder `dir`.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().join("dummy"),
+        }
+    }
+
+    /// The cache key of the dummy proofs with the shape `common_data`.
+    pub fn key<L: PlonkParameters<D>, const D: usize>(
+        common_data: &CommonCircuitData<L::Field, D>,
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+    ) -> Result<String> {
+        let bytes = common_data
+            .to_bytes(gate_serializer)
+            .map_err(|_| anyhow!("failed to serialize the common circuit data"))?;
+        Ok(hex!(Sha256::digest(bytes).to_vec()))
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    fn load_proof<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        name: &str,
+    ) -> Result<Option<ProofWithPublicInputs<L::Field, L::Config, D>>> {
+        let Ok(bytes) = fs::read(self.path(name)) else {
+            return Ok(None);
+        };
+        Ok(Some(deserialize_proof(&bytes, None)?))
+    }
+
+    fn store(&self, name: &str, bytes: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(name), bytes)?;
+        Ok(())
+    }
+
+    /// Returns the dummy proof with the shape `common_data`, generating and caching it on a miss.
+    pub fn dummy_proof<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        common_data: &CommonCircuitData<L::Field, D>,
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+    ) -> Result<DummyProof<L, D>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    {
+        let key = Self::key::<L, D>(common_data, gate_serializer)?;
+        let proof_name = format!("{}.proof", key);
+        let verifier_name = format!("{}.verifier", key);
+        if let (Some(proof), Ok(verifier_bytes)) = (
+            self.load_proof::<L, D>(&proof_name)?,
+            fs::read(self.path(&verifier_name)),
+        ) {
+            let verifier_data = VerifierOnlyCircuitData::from_bytes(verifier_bytes)
+                .map_err(|_| anyhow!("failed to deserialize cached dummy verifier {}", key))?;
+            debug!("Loaded dummy proof {} from the cache.", key);
+            return Ok(DummyProof {
+                proof,
+                verifier_data,
+            });
+        }
+
+        info!("Generating dummy proof {}.", key);
+        let circuit = dummy_circuit::<L::Field, L::Config, D>(common_data);
+        let proof = dummy_proof(&circuit, vec![].into_iter().enumerate().collect())?;
+        let verifier_bytes = circuit
+            .verifier_only
+            .to_bytes()
+            .map_err(|_| anyhow!("failed to serialize dummy verifier {}", key))?;
+        self.store(&proof_name, &serialize_proof(&proof)?)?;
+        self.store(&verifier_name, &verifier_bytes)?;
+        Ok(DummyProof {
+            proof,
+            verifier_data: circuit.verifier_only,
+        })
+    }
+
+    /// Returns the base proof of the cyclic circuit with the given data, generating and caching it
+    /// on a miss.
+    pub fn cyclic_base_proof<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        common_data: &CommonCircuitData<L::Field, D>,
+        verifier_data: &VerifierOnlyCircuitData<L::Config, D>,
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+    ) -> Result<ProofWithPublicInputs<L::Field, L::Config, D>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    {
+        // The base proof also depends on the verifier data of the cyclic circuit, which it exposes
+        // as public inputs.
+        let key = Self::key::<L, D>(common_data, gate_serializer)?;
+        let digest = hex!(verifier_data.circuit_digest.to_bytes());
+        let name = format!("{}-{}.cyclic", key, digest);
+        if let Some(proof) = self.load_proof::<L, D>(&name)? {
+            debug!("Loaded cyclic base proof {} from the cache.", name);
+            return Ok(proof);
+        }
+
+        info!("Generating cyclic base proof {}.", name);
+        let proof = cyclic_base_proof(
+            common_data,
+            verifier_data,
+            vec![].into_iter().enumerate().collect(),
+        );
+        self.store(&name, &serialize_proof(&proof)?)?;
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::backend::circuit::{DefaultParameters, GateRegistry};
+    use crate::prelude::{DefaultBuilder, Variable};
+
+    const D: usize = 2;
+    type L = DefaultParameters;
+
+    #[test]
+    fn test_dummy_proof_cache() {
+        let dir = env::temp_dir().join("plonky2x-dummy-proof-cache");
+        let _ = fs::remove_dir_all(&dir);
+        let cache = DummyProofCache::new(&dir);
+        let gates = GateRegistry::<L, D>::new();
+
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.add(a, a);
+        builder.write(b);
+        let circuit = builder.build();
+        let common_data = &circuit.data.common;
+
+        let dummy = cache.dummy_proof::<L, D>(common_data, &gates).unwrap();
+        assert_eq!(
+            dummy.proof.public_inputs.len(),
+            common_data.num_public_inputs
+        );
+        let dummy_circuit = dummy_circuit::<_, <L as PlonkParameters<D>>::Config, D>(common_data);
+        assert_eq!(dummy.verifier_data, dummy_circuit.verifier_only);
+        dummy_circuit.verify(dummy.proof.clone()).unwrap();
+
+        let cached = cache.dummy_proof::<L, D>(common_data, &gates).unwrap();
+        assert_eq!(cached.proof, dummy.proof);
+        assert_eq!(cached.verifier_data, dummy.verifier_data);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
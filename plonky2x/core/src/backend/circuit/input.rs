@@ -1,4 +1,6 @@
+use anyhow::{anyhow, bail, Result};
 use itertools::Itertools;
+use plonky2::field::types::Field;
 use plonky2::plonk::circuit_data::VerifierCircuitData;
 use plonky2::plonk::proof::ProofWithPublicInputs;
 use serde::{Deserialize, Serialize};
@@ -11,6 +13,7 @@ use crate::prelude::{ByteVariable, CircuitVariable};
 
 /// Public inputs to the circuit. In the form of bytes, field elements, or recursive proofs.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub enum PublicInput<L: PlonkParameters<D>, const D: usize> {
     Bytes(Vec<u8>),
     Elements(Vec<L::Field>),
@@ -62,10 +65,17 @@ impl<L: PlonkParameters<D>, const D: usize> PublicInput<L, D> {
                 PublicInput::Elements(elements)
             }
             CircuitIO::RecursiveProofs(_) => {
-                todo!()
+                // Recursive circuits only expose their outputs, so the inner proofs and the inputs
+                // are not part of the proof.
+                PublicInput::RecursiveProofs(vec![], vec![])
             }
-            CircuitIO::CyclicProof(_) => {
-                todo!()
+            CircuitIO::CyclicProof(io) => {
+                // The proof of the previous step is a witness of the cyclic circuit and cannot be
+                // recovered. The verifier data needs the common data of the circuit, see
+                // `CircuitBuild::input_from_proof`.
+                let offset = io.input.len();
+                let elements = proof_with_pis.public_inputs[..offset].to_vec();
+                PublicInput::CyclicProof(elements, Box::new(None), Box::new(None))
             }
             CircuitIO::None() => PublicInput::None(),
         }
@@ -123,6 +133,28 @@ impl<L: PlonkParameters<D>, const D: usize> PublicInput<L, D> {
         };
     }
 
+    /// Writes `0x`-prefixed hex bytes to the public circuit input, such as an abi encoded input.
+    pub fn evm_write_hex(&mut self, value: &str) -> Result<()> {
+        let PublicInput::Bytes(input) = self else {
+            bail!("evm io is not enabled");
+        };
+        let digits = value
+            .strip_prefix("0x")
+            .ok_or_else(|| anyhow!("hex input {:?} is not 0x-prefixed", value))?;
+        let bytes =
+            hex::decode(digits).map_err(|e| anyhow!("invalid hex input {:?}: {}", value, e))?;
+        input.extend(bytes);
+        Ok(())
+    }
+
+    /// Reads the bytes of the public circuit input as `0x`-prefixed hex.
+    pub fn read_hex(&self) -> Result<String> {
+        match self {
+            PublicInput::Bytes(input) => Ok(format!("0x{}", hex::encode(input))),
+            _ => bail!("evm io is not enabled"),
+        }
+    }
+
     /// Writes a proof to the public circuit input.
     pub fn proof_write(&mut self, proof: ProofWithPublicInputs<L::Field, L::Config, D>) {
         match self {
@@ -157,9 +189,77 @@ impl<L: PlonkParameters<D>, const D: usize> PublicInput<L, D> {
         };
     }
 
-    /// Sets a value to the circuit input. This method only works if the circuit is using
-    /// field element-based IO.
-    pub fn set<V: CircuitVariable>(&mut self, _: V, _: V::ValueType<L::Field>) {
-        todo!()
+    /// Sets a value to the circuit input at the position of `variable` in the inputs of `io`, so
+    /// that inputs can be set in any order. Inputs that are never set are zero. This method only
+    /// works if the circuit is using field element-based IO.
+    pub fn set<V: CircuitVariable>(
+        &mut self,
+        io: &CircuitIO<D>,
+        variable: V,
+        value: V::ValueType<L::Field>,
+    ) {
+        let (input_variables, input) = match (io, self) {
+            (CircuitIO::Elements(io), PublicInput::Elements(input)) => (&io.input, input),
+            (CircuitIO::RecursiveProofs(io), PublicInput::RecursiveProofs(_, input)) => {
+                (&io.input, input)
+            }
+            (CircuitIO::CyclicProof(io), PublicInput::CyclicProof(input, _, _)) => {
+                (&io.input, input)
+            }
+            _ => panic!("field io is not enabled"),
+        };
+        if input.len() < input_variables.len() {
+            input.resize(input_variables.len(), L::Field::ZERO);
+        }
+        let elements = V::elements::<L::Field>(value);
+        for (variable, element) in variable.variables().iter().zip(elements) {
+            let index = input_variables
+                .iter()
+                .position(|input_variable| input_variable == variable)
+                .expect("variable is not an input of the circuit");
+            input[index] = element;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_set_input() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<U32Variable>();
+        let c = builder.sub(a, b.variable);
+        builder.write(c);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.set::<U32Variable>(&circuit.io, b, 3);
+        input.set::<Variable>(&circuit.io, a, GoldilocksField::from_canonical_u64(10));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(
+            output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(7)
+        );
+    }
+
+    #[test]
+    fn test_hex_input() {
+        let mut input = PublicInput::<DefaultParameters, 2>::Bytes(vec![]);
+        input.evm_write_hex("0x0102").unwrap();
+        input.evm_write_hex("0xFF").unwrap();
+        assert_eq!(input.read_hex().unwrap(), "0x0102ff");
+
+        assert!(input.evm_write_hex("0102").is_err());
+        assert!(input.evm_write_hex("0x123").is_err());
+        assert!(input.evm_write_hex("0xzz").is_err());
+        assert_eq!(input.read_hex().unwrap(), "0x0102ff");
+
+        let mut elements = PublicInput::<DefaultParameters, 2>::Elements(vec![]);
+        assert!(elements.evm_write_hex("0x01").is_err());
     }
 }
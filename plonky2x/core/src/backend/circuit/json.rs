@@ -0,0 +1,283 @@
+//! The canonical JSON encoding of `PublicInput` and `PublicOutput`, exchanged by the function CLI
+//! and the remote prover.
+//!
+//! Bytes are `0x`-prefixed hex, field elements are decimal strings, proofs are the base64 encoding
+//! of their versioned serialization and proof ids are UUIDs. Payloads are wrapped as
+//! `{ "version": 1, "input": { "type": ..., ... } }`, or `"output"` for outputs. Payloads without a
+//! version are decoded with the serde encoding of the types, which was used before versioning.
+
+use anyhow::{anyhow, ensure, Result};
+use plonky2::field::types::PrimeField64;
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::serialization::{deserialize_proof, serialize_proof};
+use super::{PlonkParameters, PublicInput, PublicOutput};
+use crate::backend::prover::ProofId;
+
+/// The latest version of the JSON encoding of public inputs and outputs.
+pub const PUBLIC_IO_JSON_VERSION: u64 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum PublicInputJson {
+    Bytes {
+        input: String,
+    },
+    Elements {
+        input: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    RecursiveProofs {
+        proofs: Vec<String>,
+        input: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    RemoteRecursiveProofs {
+        proof_ids: Vec<ProofId>,
+    },
+    /// The verifier data is not encoded, as in the serde encoding of `PublicInput`.
+    CyclicProof {
+        input: Vec<String>,
+        proof: Option<String>,
+    },
+    None,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum PublicOutputJson {
+    Bytes { output: String },
+    Elements { output: Vec<String> },
+    Proofs { output: Vec<String> },
+    None,
+}
+
+fn encode_bytes(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn decode_bytes(bytes: &str) -> Result<Vec<u8>> {
+    let bytes = bytes
+        .strip_prefix("0x")
+        .ok_or_else(|| anyhow!("bytes must be 0x-prefixed hex"))?;
+    Ok(hex::decode(bytes)?)
+}
+
+fn encode_elements<F: PrimeField64>(elements: &[F]) -> Vec<String> {
+    elements
+        .iter()
+        .map(|element| element.to_canonical_u64().to_string())
+        .collect()
+}
+
+fn decode_elements<F: PrimeField64>(elements: &[String]) -> Result<Vec<F>> {
+    elements
+        .iter()
+        .map(|element| {
+            let value = element.parse::<u64>()?;
+            ensure!(value < F::ORDER, "field element {} is not canonical", value);
+            Ok(F::from_canonical_u64(value))
+        })
+        .collect()
+}
+
+fn encode_proof<L: PlonkParameters<D>, const D: usize>(
+    proof: &ProofWithPublicInputs<L::Field, L::Config, D>,
+) -> Result<String> {
+    Ok(base64::encode(serialize_proof(proof)?))
+}
+
+fn decode_proof<L: PlonkParameters<D>, const D: usize>(
+    proof: &str,
+) -> Result<ProofWithPublicInputs<L::Field, L::Config, D>> {
+    deserialize_proof(&base64::decode(proof)?, None)
+}
+
+/// Extracts the payload of a versioned JSON encoding, or `None` for an unversioned one.
+fn versioned_payload(value: Value, field: &str) -> Result<Option<Value>> {
+    let Some(version) = value.get("version") else {
+        return Ok(None);
+    };
+    let version = version
+        .as_u64()
+        .ok_or_else(|| anyhow!("invalid public io version: {}", version))?;
+    ensure!(
+        version == PUBLIC_IO_JSON_VERSION,
+        "unsupported public io version {}: this binary supports versions up to {}",
+        version,
+        PUBLIC_IO_JSON_VERSION
+    );
+    value
+        .get(field)
+        .cloned()
+        .map(Some)
+        .ok_or_else(|| anyhow!("public io is missing the `{}` field", field))
+}
+
+impl<L: PlonkParameters<D>, const D: usize> PublicInput<L, D> {
+    /// Encodes the input in the latest version of the canonical JSON encoding.
+    pub fn to_json(&self) -> Result<String> {
+        let input = match self {
+            PublicInput::Bytes(input) => PublicInputJson::Bytes {
+                input: encode_bytes(input),
+            },
+            PublicInput::Elements(input) => PublicInputJson::Elements {
+                input: encode_elements(input),
+            },
+            PublicInput::RecursiveProofs(proofs, input) => PublicInputJson::RecursiveProofs {
+                proofs: proofs
+                    .iter()
+                    .map(encode_proof::<L, D>)
+                    .collect::<Result<_>>()?,
+                input: encode_elements(input),
+            },
+            PublicInput::RemoteRecursiveProofs(proof_ids) => {
+                PublicInputJson::RemoteRecursiveProofs {
+                    proof_ids: proof_ids.clone(),
+                }
+            }
+            PublicInput::CyclicProof(input, proof, _) => PublicInputJson::CyclicProof {
+                input: encode_elements(input),
+                proof: proof
+                    .as_ref()
+                    .as_ref()
+                    .map(encode_proof::<L, D>)
+                    .transpose()?,
+            },
+            PublicInput::None() => PublicInputJson::None,
+        };
+        Ok(serde_json::to_string(&json!({
+            "version": PUBLIC_IO_JSON_VERSION,
+            "input": input,
+        }))?)
+    }
+
+    /// Decodes an input from any version of the canonical JSON encoding.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        let Some(input) = versioned_payload(value.clone(), "input")? else {
+            return Ok(serde_json::from_value(value)?);
+        };
+        Ok(match serde_json::from_value(input)? {
+            PublicInputJson::Bytes { input } => PublicInput::Bytes(decode_bytes(&input)?),
+            PublicInputJson::Elements { input } => PublicInput::Elements(decode_elements(&input)?),
+            PublicInputJson::RecursiveProofs { proofs, input } => PublicInput::RecursiveProofs(
+                proofs
+                    .iter()
+                    .map(|proof| decode_proof::<L, D>(proof))
+                    .collect::<Result<_>>()?,
+                decode_elements(&input)?,
+            ),
+            PublicInputJson::RemoteRecursiveProofs { proof_ids } => {
+                PublicInput::RemoteRecursiveProofs(proof_ids)
+            }
+            PublicInputJson::CyclicProof { input, proof } => PublicInput::CyclicProof(
+                decode_elements(&input)?,
+                Box::new(
+                    proof
+                        .map(|proof| decode_proof::<L, D>(&proof))
+                        .transpose()?,
+                ),
+                Box::new(None),
+            ),
+            PublicInputJson::None => PublicInput::None(),
+        })
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> PublicOutput<L, D> {
+    /// Encodes the output in the latest version of the canonical JSON encoding.
+    pub fn to_json(&self) -> Result<String> {
+        let output = match self {
+            PublicOutput::Bytes(output) => PublicOutputJson::Bytes {
+                output: encode_bytes(output),
+            },
+            PublicOutput::Elements(output) => PublicOutputJson::Elements {
+                output: encode_elements(output),
+            },
+            PublicOutput::Proofs(output) => PublicOutputJson::Proofs {
+                output: encode_elements(output),
+            },
+            PublicOutput::None() => PublicOutputJson::None,
+        };
+        Ok(serde_json::to_string(&json!({
+            "version": PUBLIC_IO_JSON_VERSION,
+            "output": output,
+        }))?)
+    }
+
+    /// Decodes an output from any version of the canonical JSON encoding.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        let Some(output) = versioned_payload(value.clone(), "output")? else {
+            return Ok(serde_json::from_value(value)?);
+        };
+        Ok(match serde_json::from_value(output)? {
+            PublicOutputJson::Bytes { output } => PublicOutput::Bytes(decode_bytes(&output)?),
+            PublicOutputJson::Elements { output } => {
+                PublicOutput::Elements(decode_elements(&output)?)
+            }
+            PublicOutputJson::Proofs { output } => PublicOutput::Proofs(decode_elements(&output)?),
+            PublicOutputJson::None => PublicOutput::None(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::backend::circuit::DefaultParameters;
+    use crate::prelude::{DefaultBuilder, GoldilocksField, Variable};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    #[test]
+    fn test_public_io_json() {
+        let bytes = PublicInput::<L, D>::Bytes(vec![1, 2, 255]);
+        let json = bytes.to_json().unwrap();
+        assert!(json.contains("\"0x0102ff\""));
+        assert_eq!(PublicInput::from_json(&json).unwrap(), bytes);
+
+        let elements =
+            PublicInput::<L, D>::Elements(vec![GoldilocksField::ONE, GoldilocksField::NEG_ONE]);
+        let json = elements.to_json().unwrap();
+        assert!(json.contains("\"18446744069414584320\""));
+        assert_eq!(PublicInput::from_json(&json).unwrap(), elements);
+
+        let remote = PublicInput::<L, D>::RemoteRecursiveProofs(vec![ProofId(Uuid::from_u128(42))]);
+        let json = remote.to_json().unwrap();
+        assert_eq!(PublicInput::from_json(&json).unwrap(), remote);
+
+        // Unversioned payloads use the serde encoding.
+        let legacy = serde_json::to_string(&elements).unwrap();
+        assert_eq!(PublicInput::from_json(&legacy).unwrap(), elements);
+
+        let output = PublicOutput::<L, D>::Bytes(vec![7]);
+        let json = output.to_json().unwrap();
+        assert_eq!(PublicOutput::from_json(&json).unwrap(), output);
+
+        let unsupported = json.replacen("\"version\":1", "\"version\":2", 1);
+        assert!(PublicOutput::<L, D>::from_json(&unsupported).is_err());
+    }
+
+    #[test]
+    fn test_recursive_input_json() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        builder.write(a);
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::ONE);
+        let (proof, _) = circuit.prove(&input);
+
+        let recursive = PublicInput::<L, D>::RecursiveProofs(vec![proof], vec![]);
+        let json = recursive.to_json().unwrap();
+        assert_eq!(PublicInput::from_json(&json).unwrap(), recursive);
+    }
+}
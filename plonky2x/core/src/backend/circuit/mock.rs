@@ -1,14 +1,22 @@
 use alloc::collections::BTreeMap;
+use core::fmt::{Display, Formatter, Result as FmtResult};
 use std::collections::HashMap;
 
-use plonky2::iop::witness::{PartialWitness, PartitionWitness};
+use anyhow::Result;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::types::{Field, PrimeField64};
+use plonky2::hash::hash_types::HashOut;
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartialWitness, PartitionWitness, Witness};
 use plonky2::plonk::circuit_data::MockCircuitData;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::plonk::vars::EvaluationVars;
+use starkyx::maybe_rayon::*;
 
 use super::input::PublicInput;
 use super::output::PublicOutput;
 use super::witness::generate_witness;
-use super::PlonkParameters;
+use super::{CircuitBuild, PlonkParameters};
 use crate::frontend::builder::CircuitIO;
 use crate::frontend::hint::asynchronous::generator::AsyncHintDataRef;
 
@@ -61,14 +69,222 @@ impl<L: PlonkParameters<D>, const D: usize> MockCircuitBuild<L, D> {
     }
 }
 
+/// The selector value of the rows outside of a group of gates.
+const UNUSED_SELECTOR: u64 = u32::MAX as u64;
+
+/// The constraints of a gate which are not satisfied on a row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GateConstraintFailure {
+    pub row: usize,
+    /// The id of the gate of the row.
+    pub gate: String,
+    /// The indices of the unsatisfied constraints of the gate.
+    pub constraints: Vec<usize>,
+}
+
+/// The rows of a witness whose gate constraints are not satisfied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsatisfiedGates(pub Vec<GateConstraintFailure>);
+
+impl Display for UnsatisfiedGates {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} rows with unsatisfied gate constraints:",
+            self.0.len()
+        )?;
+        for failure in self.0.iter() {
+            write!(
+                f,
+                "\n  row {}: {} constraints {:?}",
+                failure.row, failure.gate, failure.constraints
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnsatisfiedGates {}
+
+/// The values of the wires of each row of a circuit.
+pub(crate) type WireRows<F> = Vec<Vec<F>>;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
+    /// Checks that the circuit is satisfied by `input` without generating a proof, and returns the
+    /// output.
+    ///
+    /// The witness is generated like in `debug_prove`, so an unsatisfied copy constraint returns a
+    /// `ConstraintFailure` error. The constraints of the gate of every row are then evaluated on
+    /// the witness, and the unsatisfied ones are returned as an `UnsatisfiedGates` error. This
+    /// skips the commitments and FRI, which are most of the time of `prove`, but does not check
+    /// the lookup arguments.
+    pub fn mock_prove(&self, input: &PublicInput<L, D>) -> Result<PublicOutput<L, D>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let witness = self.generate_witness_checked(input)?;
+        let public_inputs_hash = self.public_inputs_hash(&witness);
+        let wires = self.wire_rows(&witness);
+        let failures = self.unsatisfied_gates(&wires, &public_inputs_hash);
+        if !failures.is_empty() {
+            return Err(UnsatisfiedGates(failures).into());
+        }
+        Ok(PublicOutput::from_witness(&self.io, &witness))
+    }
+
+    /// The hash of the public inputs in the witness, which is an input of the public input gate.
+    pub(crate) fn public_inputs_hash(
+        &self,
+        witness: &PartitionWitness<L::Field>,
+    ) -> HashOut<L::Field> {
+        let public_inputs = witness.get_targets(&self.data.prover_only.public_inputs);
+        <<L::Config as GenericConfig<D>>::InnerHasher as Hasher<L::Field>>::hash_no_pad(
+            &public_inputs,
+        )
+    }
+
+    /// Reads the values of the wires of every row from the witness. The wires which are not set,
+    /// such as those of the padding rows, are zero as in the prover.
+    pub(crate) fn wire_rows(&self, witness: &PartitionWitness<L::Field>) -> WireRows<L::Field> {
+        let num_wires = self.data.common.config.num_wires;
+        (0..self.data.common.degree())
+            .map(|row| {
+                (0..num_wires)
+                    .map(|column| {
+                        witness
+                            .try_get_target(Target::wire(row, column))
+                            .unwrap_or(L::Field::ZERO)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The values of the constant columns of every row, selectors included.
+    fn constant_rows(&self) -> Vec<Vec<L::Field>> {
+        let columns = self
+            .data
+            .prover_only
+            .constants_sigmas_commitment
+            .polynomials[..self.data.common.num_constants]
+            .iter()
+            .map(|coeffs| coeffs.fft().values)
+            .collect::<Vec<_>>();
+        (0..self.data.common.degree())
+            .map(|row| columns.iter().map(|column| column[row]).collect())
+            .collect()
+    }
+
+    /// Evaluates the constraints of the gate of every row on `wires`, and returns the rows with
+    /// unsatisfied constraints.
+    pub(crate) fn unsatisfied_gates(
+        &self,
+        wires: &WireRows<L::Field>,
+        public_inputs_hash: &HashOut<L::Field>,
+    ) -> Vec<GateConstraintFailure> {
+        let common = &self.data.common;
+        let num_selectors = common.selectors_info.num_selectors();
+        let constants_offset = num_selectors + common.num_lookup_selectors;
+        let constant_rows = self.constant_rows();
+        let extend = |values: &[L::Field]| {
+            values
+                .iter()
+                .map(|x| <L::Field as Extendable<D>>::Extension::from_basefield(*x))
+                .collect::<Vec<_>>()
+        };
+
+        let mut failures = (0..common.degree())
+            .into_par_iter()
+            .filter_map(|row| {
+                let constants = &constant_rows[row];
+                // Every row belongs to one selector group, whose selector is the index of the
+                // gate of the row.
+                let gate_index = constants[..num_selectors]
+                    .iter()
+                    .map(|selector| selector.to_canonical_u64())
+                    .find(|selector| *selector != UNUSED_SELECTOR)?;
+                let gate = &common.gates[gate_index as usize];
+                let local_constants = extend(&constants[constants_offset..]);
+                let local_wires = extend(&wires[row]);
+                let vars = EvaluationVars {
+                    local_constants: &local_constants,
+                    local_wires: &local_wires,
+                    public_inputs_hash,
+                };
+                let constraints = gate
+                    .0
+                    .eval_unfiltered(vars)
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, value)| *value != <L::Field as Extendable<D>>::Extension::ZERO)
+                    .map(|(i, _)| i)
+                    .collect::<Vec<_>>();
+                match constraints.is_empty() {
+                    true => None,
+                    false => Some(GateConstraintFailure {
+                        row,
+                        gate: gate.0.id(),
+                        constraints,
+                    }),
+                }
+            })
+            .collect::<Vec<_>>();
+        failures.sort_by_key(|failure| failure.row);
+        failures
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
 
     use log::debug;
 
+    use super::*;
     use crate::prelude::*;
     use crate::utils;
 
+    #[test]
+    fn test_circuit_mock_prove() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let c = builder.mul(a, b);
+        let d = builder.add(c, a);
+        builder.write(d);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(3));
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(5));
+        let mut output = circuit.mock_prove(&input).unwrap();
+        assert_eq!(
+            output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(18)
+        );
+
+        // Tampering with a wire of a row with constraints breaks the constraints of that row.
+        let witness = circuit.generate_witness_checked(&input).unwrap();
+        let public_inputs_hash = circuit.public_inputs_hash(&witness);
+        let mut wires = circuit.wire_rows(&witness);
+        assert!(circuit
+            .unsatisfied_gates(&wires, &public_inputs_hash)
+            .is_empty());
+        let row = (0..wires.len())
+            .find(|row| wires[*row].iter().any(|value| *value == c.get(&witness)))
+            .unwrap();
+        for value in wires[row].iter_mut() {
+            *value += GoldilocksField::ONE;
+        }
+        let failures = circuit.unsatisfied_gates(&wires, &public_inputs_hash);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].row, row);
+        assert!(!failures[0].constraints.is_empty());
+        assert!(UnsatisfiedGates(failures)
+            .to_string()
+            .contains(&format!("row {}", row)));
+    }
+
     #[test]
     fn test_mock_circuit_with_field_io() {
         utils::setup_logger();
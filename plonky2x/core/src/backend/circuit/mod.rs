@@ -1,24 +1,48 @@
+mod artifact;
+mod audit;
+mod batch;
 mod build;
+mod cache;
 pub mod config;
+mod debug;
+mod dummy;
 mod input;
+mod json;
 mod mock;
 mod output;
+mod reproducibility;
 mod serialization;
+mod timing;
 mod witness;
 
 use core::fmt::Debug;
 
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 
+pub use self::artifact::{ArtifactRegistry, VerifierEntry, DEFAULT_BUILD_DIR};
+pub use self::audit::{AuditSink, JsonLinesAuditSink, LogAuditSink, ProofAuditRecord};
+pub use self::batch::{BatchProof, BatchVerificationReport, ProofVerification};
 pub use self::build::CircuitBuild;
-pub use self::config::{DefaultParameters, Groth16WrapperParameters, PlonkParameters};
+pub use self::cache::BuildCache;
+pub use self::config::{
+    DefaultParameters, Groth16WrapperParameters, PlonkParameters, Poseidon2Parameters,
+};
+pub use self::debug::ConstraintFailure;
+pub use self::dummy::{DummyProof, DummyProofCache};
 pub use self::input::PublicInput;
-pub use self::mock::MockCircuitBuild;
+pub use self::json::PUBLIC_IO_JSON_VERSION;
+pub use self::mock::{GateConstraintFailure, MockCircuitBuild, UnsatisfiedGates};
 pub use self::output::PublicOutput;
+pub use self::reproducibility::{check_reproducible, CircuitFingerprint};
 pub use self::serialization::{
-    CircuitSerializer, DefaultSerializer, GateRegistry, HintRegistry, Serializer,
+    decode_proof_archive, deserialize_proof, encode_proof_archive, read_proof_archive,
+    serialize_proof, write_proof_archive, CircuitSerializer, DefaultSerializer, GateRegistry,
+    HintRegistry, ProofCompression, ProofFormat, Serializer,
+};
+pub use self::timing::{GeneratorTiming, ProofTimings, WitnessTimings};
+pub use self::witness::{
+    generate_witness, generate_witness_async, generate_witness_timed, WitnessConflict,
 };
-pub use self::witness::{generate_witness, generate_witness_async};
 use crate::prelude::CircuitBuilder;
 
 pub trait Circuit: Debug + Clone + Send + Sync + 'static {
@@ -44,6 +68,12 @@ pub trait Circuit: Debug + Clone + Send + Sync + 'static {
     {
     }
 
+    /// The version of the circuit definition, used as the key of the build cache. Circuits that
+    /// return `None` are rebuilt every time, and the version must change whenever `define` does.
+    fn version() -> Option<String> {
+        None
+    }
+
     // Tests that the circuit can be serialized and deserialized.
     fn test_serialization<L: PlonkParameters<D>, const D: usize>()
     where
@@ -61,4 +91,12 @@ pub trait Circuit: Debug + Clone + Send + Sync + 'static {
 
         circuit.test_serializers(&gate_registry, &generator_registry);
     }
+
+    /// Tests that the circuit builds to the same artifact every time.
+    fn test_reproducibility<L: PlonkParameters<D>, const D: usize>()
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    {
+        check_reproducible::<L, D, _>(Self::define::<L, D>).unwrap();
+    }
 }
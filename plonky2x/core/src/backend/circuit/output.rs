@@ -1,3 +1,4 @@
+use anyhow::{bail, Result};
 use itertools::Itertools;
 use plonky2::iop::witness::PartitionWitness;
 use plonky2::plonk::proof::ProofWithPublicInputs;
@@ -10,6 +11,7 @@ use crate::prelude::{ByteVariable, CircuitVariable};
 
 /// An output from the circuit. Can either be in the form of bytes, field elements, or proofs.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub enum PublicOutput<L: PlonkParameters<D>, const D: usize> {
     Bytes(Vec<u8>),
     Elements(Vec<L::Field>),
@@ -124,6 +126,14 @@ impl<L: PlonkParameters<D>, const D: usize> PublicOutput<L, D> {
         }
     }
 
+    /// Reads the entire stream of bytes from the public circuit output as `0x`-prefixed hex.
+    pub fn read_hex(&self) -> Result<String> {
+        match self {
+            PublicOutput::Bytes(output) => Ok(format!("0x{}", hex::encode(output))),
+            _ => bail!("evm io is not enabled"),
+        }
+    }
+
     /// Reads a value from the circuit output. It also can access the value of any intermediate
     /// variable in the circuit.
     pub fn get<V: CircuitVariable>(&self, _: V) -> V::ValueType<L::Field> {
@@ -0,0 +1,260 @@
+//! Checks that a circuit builds to the same artifact every time.
+//!
+//! The digest of a circuit changes whenever its gates, copy constraints or constants change, so a
+//! circuit whose definition iterates over a `HashMap` or uses randomness may build to a different
+//! artifact on every run. A `CircuitFingerprint` records the digest together with the parts of the
+//! circuit it commits to, so that two builds can be compared, in the same process or across
+//! machines, and the first difference can be traced back to its likely source.
+
+use std::fs::{self, File};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{CircuitBuild, PlonkParameters};
+use crate::frontend::builder::CircuitBuilder;
+use crate::utils::hex;
+
+/// A summary of a built circuit, with one entry per part the circuit digest depends on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitFingerprint {
+    /// The circuit digest, as returned by `CircuitBuild::id`.
+    pub id: String,
+    pub degree_bits: usize,
+    pub num_public_inputs: usize,
+    /// The ids of the gates, in the order the circuit uses them.
+    pub gates: Vec<String>,
+    /// The ids of the witness generators, in the order they were registered.
+    pub generators: Vec<String>,
+    /// A hash of the commitment to the constants and the copy constraints.
+    pub constants_sigmas_cap: String,
+    /// A hash of the serialized circuit io.
+    pub io: String,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex!(Sha256::digest(bytes).to_vec())
+}
+
+impl CircuitFingerprint {
+    pub fn new<L: PlonkParameters<D>, const D: usize>(circuit: &CircuitBuild<L, D>) -> Self {
+        let cap_bytes = circuit
+            .data
+            .verifier_only
+            .constants_sigmas_cap
+            .0
+            .iter()
+            .flat_map(|h| h.to_bytes())
+            .collect_vec();
+        Self {
+            id: circuit.id(),
+            degree_bits: circuit.data.common.degree_bits(),
+            num_public_inputs: circuit.data.common.num_public_inputs,
+            gates: circuit.data.common.gates.iter().map(|g| g.0.id()).collect(),
+            generators: circuit
+                .data
+                .prover_only
+                .generators
+                .iter()
+                .map(|g| g.0.id())
+                .collect(),
+            constants_sigmas_cap: sha256_hex(&cap_bytes),
+            io: sha256_hex(&bincode::serialize(&circuit.io).unwrap()),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Describes how `other` differs from `self`, with the likely cause of each difference. The
+    /// result is empty if and only if the fingerprints are equal.
+    pub fn diff(&self, other: &Self) -> Vec<String> {
+        let mut diagnostics = Vec::new();
+        if self.degree_bits != other.degree_bits
+            || self.num_public_inputs != other.num_public_inputs
+        {
+            diagnostics.push(format!(
+                "circuit shape differs (degree_bits {} vs {}, public inputs {} vs {}): the \
+                 definition depends on external state such as environment variables, files or \
+                 network responses",
+                self.degree_bits,
+                other.degree_bits,
+                self.num_public_inputs,
+                other.num_public_inputs
+            ));
+        }
+        if self.io != other.io {
+            diagnostics.push(
+                "circuit io differs: inputs or outputs are read or written in a different order"
+                    .to_string(),
+            );
+        }
+        diagnostics.extend(diff_ids("gates", &self.gates, &other.gates));
+        diagnostics.extend(diff_ids("generators", &self.generators, &other.generators));
+        if self.constants_sigmas_cap != other.constants_sigmas_cap {
+            diagnostics.push(
+                "constants or copy constraints differ: check for randomness (e.g. `rand`) or \
+                 values that change between runs used as circuit constants, or for variables \
+                 connected while iterating over a `HashMap` or `HashSet`"
+                    .to_string(),
+            );
+        }
+        if diagnostics.is_empty() && self.id != other.id {
+            diagnostics.push("circuit digest differs".to_string());
+        }
+        diagnostics
+    }
+
+    /// Compares the fingerprint with the one saved at `path`, or saves it if there is none yet.
+    /// Committing the saved fingerprint lets CI check that it builds the same artifact as the
+    /// machine that produced it.
+    pub fn check_or_save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        if !path.as_ref().exists() {
+            return self.save(path);
+        }
+        let expected = Self::load(&path)?;
+        let diagnostics = expected.diff(self);
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "circuit {} does not match the fingerprint at {}:\n{}",
+                self.id,
+                path.as_ref().display(),
+                diagnostics.join("\n")
+            ))
+        }
+    }
+}
+
+/// Describes how two lists of gate or generator ids differ.
+fn diff_ids(name: &str, expected: &[String], actual: &[String]) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    if expected.iter().sorted().eq(actual.iter().sorted()) {
+        let position = expected
+            .iter()
+            .zip(actual)
+            .position(|(a, b)| a != b)
+            .unwrap();
+        return Some(format!(
+            "{} are the same but in a different order, starting at index {} ({} vs {}): they \
+             were probably added while iterating over a `HashMap` or `HashSet`",
+            name, position, expected[position], actual[position]
+        ));
+    }
+    let missing = expected
+        .iter()
+        .filter(|id| !actual.contains(id))
+        .collect_vec();
+    let added = actual
+        .iter()
+        .filter(|id| !expected.contains(id))
+        .collect_vec();
+    Some(format!(
+        "{} differ ({} vs {}, missing {:?}, added {:?}): the definition takes a different path \
+         between builds",
+        name,
+        expected.len(),
+        actual.len(),
+        missing,
+        added
+    ))
+}
+
+/// Builds the circuit defined by `define` twice and checks that both builds are identical.
+pub fn check_reproducible<L: PlonkParameters<D>, const D: usize, F>(
+    define: F,
+) -> Result<CircuitFingerprint>
+where
+    F: Fn(&mut CircuitBuilder<L, D>),
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    let build = || {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        define(&mut builder);
+        CircuitFingerprint::new(&builder.build())
+    };
+    let first = build();
+    let second = build();
+    let diagnostics = first.diff(&second);
+    if diagnostics.is_empty() {
+        Ok(first)
+    } else {
+        Err(anyhow!(
+            "circuit is not reproducible ({} vs {}):\n{}",
+            first.id,
+            second.id,
+            diagnostics.join("\n")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+
+    use super::*;
+    use crate::backend::circuit::DefaultParameters;
+    use crate::prelude::*;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    #[test]
+    fn test_check_reproducible() {
+        let fingerprint = check_reproducible::<L, D, _>(|builder| {
+            let a = builder.read::<U64Variable>();
+            let b = builder.read::<U64Variable>();
+            let c = builder.mul(a, b);
+            builder.write(c);
+        })
+        .unwrap();
+
+        let path = "./build/test_check_reproducible/fingerprint.json";
+        let _ = fs::remove_file(path);
+        fingerprint.check_or_save(path).unwrap();
+        fingerprint.check_or_save(path).unwrap();
+
+        // A constant that changes between builds is detected.
+        let result = check_reproducible::<L, D, _>(|builder| {
+            let a = builder.read::<Variable>();
+            let noise = builder.constant::<Variable>(GoldilocksField::from_canonical_u64(
+                rand::random::<u32>() as u64,
+            ));
+            let b = builder.add(a, noise);
+            builder.write(b);
+        });
+        assert!(result.unwrap_err().to_string().contains("constants"));
+
+        // Reordered generators are reported as such.
+        let mut reordered = fingerprint.clone();
+        let other = reordered
+            .generators
+            .iter()
+            .position(|id| *id != reordered.generators[0])
+            .unwrap();
+        reordered.generators.swap(0, other);
+        reordered.id = "reordered".to_string();
+        let diagnostics = fingerprint.diff(&reordered);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].contains("different order"));
+    }
+}
@@ -0,0 +1,247 @@
+//! Batched container files for intermediate proofs.
+//!
+//! Large mapreduce runs produce thousands of leaf and intermediate proofs. Rather than writing
+//! each of them as its own JSON file, a proof archive stores a batch of proofs in a single file,
+//! each encoded with [`serialize_proof`], and optionally compresses the whole batch with zstd.
+//!
+//! An archive starts with the magic bytes `P2XA`, the archive version and the compression used,
+//! followed by the (possibly compressed) payload: the number of proofs and then each proof as a
+//! length-prefixed versioned encoding.
+
+use std::path::Path;
+use std::{env, fs};
+
+use anyhow::{anyhow, Context, Result};
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::config::GenericConfig;
+use plonky2::plonk::proof::ProofWithPublicInputs;
+
+use super::proof::{deserialize_proof, serialize_proof};
+
+/// Magic bytes that mark a proof archive.
+const PROOF_ARCHIVE_MAGIC: &[u8; 4] = b"P2XA";
+
+/// The version of the archive layout.
+const PROOF_ARCHIVE_VERSION: u8 = 1;
+
+/// The default zstd compression level, which favors speed since proofs are mostly random.
+const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+/// The compression applied to the payload of a proof archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofCompression {
+    None,
+    /// zstd at the given level. Requires the `compression` feature.
+    Zstd(i32),
+}
+
+impl ProofCompression {
+    /// Reads the compression from the env variable `PROOF_COMPRESSION`, which can either be
+    /// `none`, `zstd` or `zstd:<level>`. Defaults to no compression.
+    pub fn from_env() -> Result<Self> {
+        match env::var("PROOF_COMPRESSION") {
+            Ok(value) => value
+                .parse()
+                .with_context(|| format!("invalid PROOF_COMPRESSION {}", value)),
+            Err(_) => Ok(ProofCompression::None),
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            ProofCompression::None => 0,
+            ProofCompression::Zstd(_) => 1,
+        }
+    }
+
+    fn compress(&self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        match self {
+            ProofCompression::None => Ok(bytes),
+            #[cfg(feature = "compression")]
+            ProofCompression::Zstd(level) => Ok(zstd::encode_all(bytes.as_slice(), *level)?),
+            #[cfg(not(feature = "compression"))]
+            ProofCompression::Zstd(_) => Err(anyhow!(
+                "zstd compression requires the `compression` feature"
+            )),
+        }
+    }
+
+    fn decompress(byte: u8, bytes: &[u8]) -> Result<Vec<u8>> {
+        match byte {
+            0 => Ok(bytes.to_vec()),
+            #[cfg(feature = "compression")]
+            1 => Ok(zstd::decode_all(bytes)?),
+            #[cfg(not(feature = "compression"))]
+            1 => Err(anyhow!(
+                "zstd compression requires the `compression` feature"
+            )),
+            _ => Err(anyhow!("unsupported proof archive compression {}", byte)),
+        }
+    }
+}
+
+impl std::str::FromStr for ProofCompression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            None if s == "none" => Ok(ProofCompression::None),
+            None if s == "zstd" => Ok(ProofCompression::Zstd(DEFAULT_ZSTD_LEVEL)),
+            Some(("zstd", level)) => {
+                Ok(ProofCompression::Zstd(level.parse().with_context(
+                    || format!("invalid zstd level {}", level),
+                )?))
+            }
+            _ => Err(anyhow!("unknown proof compression {}", s)),
+        }
+    }
+}
+
+/// Encodes a batch of proofs as a proof archive.
+pub fn encode_proof_archive<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    proofs: &[ProofWithPublicInputs<F, C, D>],
+    compression: ProofCompression,
+) -> Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    payload.extend((proofs.len() as u64).to_le_bytes());
+    for proof in proofs {
+        let bytes = serialize_proof(proof)?;
+        payload.extend((bytes.len() as u64).to_le_bytes());
+        payload.extend(bytes);
+    }
+
+    let mut archive = PROOF_ARCHIVE_MAGIC.to_vec();
+    archive.push(PROOF_ARCHIVE_VERSION);
+    archive.push(compression.to_byte());
+    archive.extend(compression.compress(payload)?);
+    Ok(archive)
+}
+
+/// Decodes a proof archive written by `encode_proof_archive`.
+pub fn decode_proof_archive<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    bytes: &[u8],
+) -> Result<Vec<ProofWithPublicInputs<F, C, D>>> {
+    let header_len = PROOF_ARCHIVE_MAGIC.len() + 2;
+    if bytes.len() < header_len || !bytes.starts_with(PROOF_ARCHIVE_MAGIC) {
+        return Err(anyhow!("not a proof archive"));
+    }
+    let version = bytes[PROOF_ARCHIVE_MAGIC.len()];
+    if version != PROOF_ARCHIVE_VERSION {
+        return Err(anyhow!("unsupported proof archive version {}", version));
+    }
+    let payload = ProofCompression::decompress(bytes[header_len - 1], &bytes[header_len..])?;
+
+    let mut cursor = payload.as_slice();
+    let read_u64 = |cursor: &mut &[u8]| -> Result<u64> {
+        if cursor.len() < 8 {
+            return Err(anyhow!("truncated proof archive"));
+        }
+        let (value, rest) = cursor.split_at(8);
+        *cursor = rest;
+        Ok(u64::from_le_bytes(value.try_into().unwrap()))
+    };
+    let nb_proofs = read_u64(&mut cursor)?;
+    let mut proofs = Vec::new();
+    for _ in 0..nb_proofs {
+        let len = read_u64(&mut cursor)? as usize;
+        if cursor.len() < len {
+            return Err(anyhow!("truncated proof archive"));
+        }
+        let (proof, rest) = cursor.split_at(len);
+        proofs.push(deserialize_proof(proof, None)?);
+        cursor = rest;
+    }
+    if !cursor.is_empty() {
+        return Err(anyhow!("trailing bytes in proof archive"));
+    }
+    Ok(proofs)
+}
+
+/// Writes a batch of proofs to a single archive file, creating its parent folders if needed.
+pub fn write_proof_archive<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    path: impl AsRef<Path>,
+    proofs: &[ProofWithPublicInputs<F, C, D>],
+    compression: ProofCompression,
+) -> Result<()> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, encode_proof_archive(proofs, compression)?)?;
+    Ok(())
+}
+
+/// Reads a batch of proofs from an archive file.
+pub fn read_proof_archive<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    path: impl AsRef<Path>,
+) -> Result<Vec<ProofWithPublicInputs<F, C, D>>> {
+    decode_proof_archive(&fs::read(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::utils::test_circuits::{add_input, build_add_circuit, build_xor_lookup_circuit};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+    type F = <L as PlonkParameters<D>>::Field;
+    type C = <L as PlonkParameters<D>>::Config;
+
+    #[test]
+    fn test_proof_archive_round_trip() {
+        // Proofs of different circuits have different sizes, and the lookup circuit has lookup
+        // openings.
+        let circuit = build_add_circuit();
+        let lookup_circuit = build_xor_lookup_circuit();
+        let mut proofs = (0..3u64)
+            .map(|i| circuit.prove(&add_input(&circuit, i, 1)).0)
+            .collect::<Vec<_>>();
+        proofs.insert(1, lookup_circuit.prove(&add_input(&lookup_circuit, 2, 3)).0);
+        assert_ne!(
+            serialize_proof(&proofs[0]).unwrap().len(),
+            serialize_proof(&proofs[1]).unwrap().len()
+        );
+
+        let archive = encode_proof_archive(&proofs, ProofCompression::None).unwrap();
+        assert_eq!(decode_proof_archive::<F, C, D>(&archive).unwrap(), proofs);
+
+        let mut truncated = archive.clone();
+        truncated.pop();
+        assert!(decode_proof_archive::<F, C, D>(&truncated).is_err());
+
+        #[cfg(feature = "compression")]
+        {
+            let compressed = encode_proof_archive(&proofs, ProofCompression::Zstd(3)).unwrap();
+            assert_eq!(
+                decode_proof_archive::<F, C, D>(&compressed).unwrap(),
+                proofs
+            );
+        }
+
+        assert_eq!(
+            "zstd:7".parse::<ProofCompression>().unwrap(),
+            ProofCompression::Zstd(7)
+        );
+        assert!("gzip".parse::<ProofCompression>().is_err());
+        let error = "zstd:fast".parse::<ProofCompression>().unwrap_err();
+        assert_eq!(error.to_string(), "invalid zstd level fast");
+    }
+}
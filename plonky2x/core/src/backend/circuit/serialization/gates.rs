@@ -22,18 +22,47 @@ use plonky2::gates::reducing::ReducingGate;
 use plonky2::gates::reducing_extension::ReducingExtensionGate;
 use plonky2::hash::hash_types::RichField;
 use plonky2::plonk::circuit_data::CommonCircuitData;
-use plonky2::util::serialization::{Buffer, GateSerializer, IoResult, Read, Write};
+use plonky2::util::serialization::{Buffer, GateSerializer, IoResult};
 use starkyx::plonky2::cubic::arithmetic_gate::ArithmeticCubicGate;
 use starkyx::plonky2::cubic::mul_gate::MulCubicGate;
 
 use super::registry::{SerializationRegistry, Serializer};
 use super::PlonkParameters;
+use crate::frontend::hash::poseidon::poseidon2::gate::Poseidon2Gate;
 use crate::frontend::uint::num::u32::gates::add_many_u32::U32AddManyGate;
 use crate::frontend::uint::num::u32::gates::arithmetic_u32::U32ArithmeticGate;
 use crate::frontend::uint::num::u32::gates::comparison::ComparisonGate;
 use crate::frontend::uint::num::u32::gates::range_check_u32::U32RangeCheckGate;
 use crate::frontend::uint::num::u32::gates::subtraction_u32::U32SubtractionGate;
 
+/// The ids of the default gates in the order of the registry before gates had stable ids, which is
+/// the index of each gate in circuits serialized in the legacy format.
+const LEGACY_GATE_IDS: [&str; 23] = [
+    "plonky2/arithmetic_base",
+    "plonky2/arithmetic_extension",
+    "plonky2/base_sum_2",
+    "plonky2/constant",
+    "plonky2/coset_interpolation",
+    "plonky2/exponentiation",
+    "plonky2/lookup",
+    "plonky2/lookup_table",
+    "plonky2/multiplication_extension",
+    "plonky2/noop",
+    "plonky2/poseidon_mds",
+    "plonky2/poseidon",
+    "plonky2/public_input",
+    "plonky2/random_access",
+    "plonky2/reducing_extension",
+    "plonky2/reducing",
+    "plonky2x/u32_comparison",
+    "plonky2x/u32_add_many",
+    "plonky2x/u32_arithmetic",
+    "plonky2x/u32_subtraction",
+    "plonky2x/u32_range_check",
+    "starkyx/arithmetic_cubic",
+    "starkyx/mul_cubic",
+];
+
 /// A registry to store serializers for gates.
 ///
 /// New gates can be added to the registry by calling the `register` method.
@@ -69,11 +98,15 @@ impl<F: RichField + Extendable<D>, G: AnyGate<F, D>, const D: usize> Serializer<
 }
 
 impl<L: PlonkParameters<D>, const D: usize> GateRegistry<L, D> {
-    /// Registers a new gate.
-    pub fn register<G: AnyGate<L::Field, D>>(&mut self) {
+    /// Registers a new gate, identified in serialized circuits by `id`.
+    ///
+    /// The id must be unique in the registry and must not change once circuits with the gate have
+    /// been serialized. Circuits in the legacy format identify gates by their index in
+    /// `LEGACY_GATE_IDS`, followed by the gates registered after the default ones.
+    pub fn register<G: AnyGate<L::Field, D>>(&mut self, id: &str) {
         let type_id = TypeId::of::<G>();
         self.0
-            .register(type_id, GateSerializerFn::<G>(PhantomData))
+            .register(type_id, id.to_string(), GateSerializerFn::<G>(PhantomData))
             .unwrap();
     }
 
@@ -82,29 +115,33 @@ impl<L: PlonkParameters<D>, const D: usize> GateRegistry<L, D> {
     pub fn new() -> Self {
         let mut r = Self(SerializationRegistry::new());
 
-        r.register::<ArithmeticGate>();
-        r.register::<ArithmeticExtensionGate<D>>();
-        r.register::<BaseSumGate<2>>();
-        r.register::<ConstantGate>();
-        r.register::<CosetInterpolationGate<L::Field, D>>();
-        r.register::<ExponentiationGate<L::Field, D>>();
-        r.register::<LookupGate>();
-        r.register::<LookupTableGate>();
-        r.register::<MulExtensionGate<D>>();
-        r.register::<NoopGate>();
-        r.register::<PoseidonMdsGate<L::Field, D>>();
-        r.register::<PoseidonGate<L::Field, D>>();
-        r.register::<PublicInputGate>();
-        r.register::<RandomAccessGate<L::Field, D>>();
-        r.register::<ReducingExtensionGate<D>>();
-        r.register::<ReducingGate<D>>();
-        r.register::<ComparisonGate<L::Field, D>>();
-        r.register::<U32AddManyGate<L::Field, D>>();
-        r.register::<U32ArithmeticGate<L::Field, D>>();
-        r.register::<U32SubtractionGate<L::Field, D>>();
-        r.register::<U32RangeCheckGate<L::Field, D>>();
-        r.register::<ArithmeticCubicGate>();
-        r.register::<MulCubicGate>();
+        r.register::<ArithmeticGate>("plonky2/arithmetic_base");
+        r.register::<ArithmeticExtensionGate<D>>("plonky2/arithmetic_extension");
+        r.register::<BaseSumGate<2>>("plonky2/base_sum_2");
+        r.register::<ConstantGate>("plonky2/constant");
+        r.register::<CosetInterpolationGate<L::Field, D>>("plonky2/coset_interpolation");
+        r.register::<ExponentiationGate<L::Field, D>>("plonky2/exponentiation");
+        r.register::<LookupGate>("plonky2/lookup");
+        r.register::<LookupTableGate>("plonky2/lookup_table");
+        r.register::<MulExtensionGate<D>>("plonky2/multiplication_extension");
+        r.register::<NoopGate>("plonky2/noop");
+        r.register::<PoseidonMdsGate<L::Field, D>>("plonky2/poseidon_mds");
+        r.register::<PoseidonGate<L::Field, D>>("plonky2/poseidon");
+        r.register::<PublicInputGate>("plonky2/public_input");
+        r.register::<RandomAccessGate<L::Field, D>>("plonky2/random_access");
+        r.register::<ReducingExtensionGate<D>>("plonky2/reducing_extension");
+        r.register::<ReducingGate<D>>("plonky2/reducing");
+        r.register::<ComparisonGate<L::Field, D>>("plonky2x/u32_comparison");
+        r.register::<U32AddManyGate<L::Field, D>>("plonky2x/u32_add_many");
+        r.register::<U32ArithmeticGate<L::Field, D>>("plonky2x/u32_arithmetic");
+        r.register::<U32SubtractionGate<L::Field, D>>("plonky2x/u32_subtraction");
+        r.register::<U32RangeCheckGate<L::Field, D>>("plonky2x/u32_range_check");
+        r.register::<ArithmeticCubicGate>("starkyx/arithmetic_cubic");
+        r.register::<MulCubicGate>("starkyx/mul_cubic");
+        r.register::<Poseidon2Gate<L::Field, D>>("plonky2x/poseidon2");
+
+        let legacy_keys = LEGACY_GATE_IDS.map(|id| r.0.keys[id]).to_vec();
+        r.0.set_legacy_keys(legacy_keys).unwrap();
 
         r
     }
@@ -116,14 +153,10 @@ impl<L: PlonkParameters<D>, const D: usize> GateSerializer<L::Field, D> for Gate
         buf: &mut Buffer,
         common_data: &CommonCircuitData<L::Field, D>,
     ) -> IoResult<GateRef<L::Field, D>> {
-        let idx = buf.read_usize()?;
-        let type_id = self.0.identifiers[idx];
-
-        self.0
-            .registry
-            .get(&type_id)
-            .unwrap_or_else(|| panic!("Gate type not registered {:?}", type_id))
-            .read(buf, common_data)
+        Ok(self
+            .0
+            .read(buf, common_data)?
+            .unwrap_or_else(|id| panic!("Gate type not registered {}", id)))
     }
 
     fn write_gate(
@@ -132,21 +165,10 @@ impl<L: PlonkParameters<D>, const D: usize> GateSerializer<L::Field, D> for Gate
         gate: &GateRef<L::Field, D>,
         common_data: &CommonCircuitData<L::Field, D>,
     ) -> IoResult<()> {
-        // let type_id = Any::type_id(&(*gate.0));
         let type_id = gate.0.as_any().type_id();
-        let idx = self
-            .0
-            .index
-            .get(&type_id)
-            .unwrap_or_else(|| panic!("Gate type not registered {:?}", gate));
-        buf.write_usize(*idx)?;
-
         self.0
-            .registry
-            .get(&type_id)
+            .write(buf, &type_id, gate, common_data)
             .unwrap_or_else(|| panic!("Gate type not registered {:?}", gate))
-            .write(buf, gate, common_data)?;
-        Ok(())
     }
 }
 
@@ -155,7 +177,10 @@ mod tests {
     use plonky2::field::goldilocks_field::GoldilocksField;
     use plonky2::gates::arithmetic_base::ArithmeticGate;
     use plonky2::gates::gate::GateRef;
-    use plonky2::util::serialization::{Buffer, GateSerializer};
+    use plonky2::gates::noop::NoopGate;
+    use plonky2::gates::public_input::PublicInputGate;
+    use plonky2::util::serialization::{Buffer, GateSerializer, Read, Write};
+    use starkyx::plonky2::cubic::mul_gate::MulCubicGate;
 
     use crate::backend::circuit::serialization::gates::GateRegistry;
     use crate::backend::circuit::DefaultParameters;
@@ -184,5 +209,54 @@ mod tests {
         let read_gate = registry.read_gate(&mut buffer, &common_data).unwrap();
 
         assert_eq!(raw_gate, read_gate);
+
+        // The gate is written after the tag of the format and its id.
+        let mut buffer = Buffer::new(&bytes);
+        assert_eq!(buffer.read_usize().unwrap(), usize::MAX - 1);
+        assert_eq!(buffer.read_bytes().unwrap(), b"plonky2/arithmetic_base");
+    }
+
+    #[test]
+    fn test_gate_legacy_deserialization() {
+        let builder = CircuitBuilder::<L, D>::new();
+        let common_data = builder.build().data.common;
+
+        let registry = GateRegistry::<L, D>::new();
+
+        // In the legacy format, gates are written after the index of their type in the registry
+        // before stable ids, which gates added since then do not shift.
+        let raw_gates: [(usize, GateRef<F, D>); 4] = [
+            (
+                0,
+                GateRef::new(ArithmeticGate::new_from_config(&common_data.config)),
+            ),
+            (9, GateRef::new(NoopGate)),
+            (12, GateRef::new(PublicInputGate)),
+            (22, GateRef::new(MulCubicGate)),
+        ];
+        for (index, raw_gate) in raw_gates {
+            let mut bytes = Vec::<u8>::new();
+            bytes.write_usize(index).unwrap();
+            raw_gate.0.serialize(&mut bytes, &common_data).unwrap();
+
+            let mut buffer = Buffer::new(&bytes);
+            let read_gate = registry.read_gate(&mut buffer, &common_data).unwrap();
+            assert_eq!(raw_gate, read_gate);
+        }
+    }
+
+    #[test]
+    fn test_gate_newer_format_version() {
+        let builder = CircuitBuilder::<L, D>::new();
+        let common_data = builder.build().data.common;
+
+        let registry = GateRegistry::<L, D>::new();
+
+        let mut bytes = Vec::<u8>::new();
+        bytes.write_usize(usize::MAX - 2).unwrap();
+        bytes.write_bytes(b"plonky2/noop").unwrap();
+
+        let mut buffer = Buffer::new(&bytes);
+        assert!(registry.read_gate(&mut buffer, &common_data).is_err());
     }
 }
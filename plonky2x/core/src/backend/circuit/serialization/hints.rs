@@ -28,7 +28,7 @@ use plonky2::iop::generator::{
 use plonky2::plonk::circuit_data::CommonCircuitData;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 use plonky2::recursion::dummy_circuit::DummyProofGenerator;
-use plonky2::util::serialization::{Buffer, IoResult, Read, WitnessGeneratorSerializer, Write};
+use plonky2::util::serialization::{Buffer, IoResult, WitnessGeneratorSerializer};
 use starkyx::machine::hash::blake::blake2b::BLAKE2B;
 use starkyx::machine::hash::sha::sha256::SHA256;
 use starkyx::machine::hash::sha::sha512::SHA512;
@@ -38,26 +38,37 @@ use starkyx::plonky2::cubic::mul_gate::MulCubicGenerator;
 use super::registry::{SerializationRegistry, Serializer};
 use super::PlonkParameters;
 use crate as plonky2x;
+use crate::frontend::builder::sort::SortHint;
+use crate::frontend::ecc::bls12_381::native::Fp;
+use crate::frontend::ecc::bn254::native::Fq;
 use crate::frontend::ecc::curve25519::curta::proof_hint::EcOpProofHint;
 use crate::frontend::ecc::curve25519::curta::result_hint::EcOpResultHint;
+use crate::frontend::ecc::nonnative::NonNativeInverseHint;
+use crate::frontend::ecc::tower::fp::{FpInverseHint, FpReduceHint};
+use crate::frontend::ecc::tower::fp12::Fp12InverseHint;
+use crate::frontend::ecc::tower::fp2::Fp2InverseHint;
 use crate::frontend::eth::beacon::generators::{
     BeaconAllWithdrawalsHint, BeaconBalanceBatchWitnessHint, BeaconBalanceGenerator,
-    BeaconBalanceWitnessHint, BeaconBalancesGenerator, BeaconBlockRootsHint, BeaconGraffitiHint,
-    BeaconHeaderHint, BeaconHeadersFromOffsetRangeHint, BeaconHistoricalBlockHint,
-    BeaconPartialBalancesHint, BeaconPartialValidatorsHint, BeaconValidatorBatchHint,
-    BeaconValidatorGenerator, BeaconValidatorsGenerator, BeaconValidatorsHint,
-    BeaconWithdrawalGenerator, BeaconWithdrawalsGenerator, CompressedBeaconValidatorBatchHint,
+    BeaconBalanceSumHint, BeaconBalanceWitnessHint, BeaconBalancesGenerator, BeaconBlockRootsHint,
+    BeaconGraffitiHint, BeaconHeaderHint, BeaconHeadersFromOffsetRangeHint,
+    BeaconHistoricalBlockHint, BeaconPartialBalancesHint, BeaconPartialValidatorsHint,
+    BeaconPendingConsolidationHint, BeaconPendingConsolidationsHint, BeaconPendingDepositHint,
+    BeaconPendingDepositsHint, BeaconValidatorBatchHint, BeaconValidatorGenerator,
+    BeaconValidatorsGenerator, BeaconValidatorsHint, BeaconWithdrawalGenerator,
+    BeaconWithdrawalsGenerator, CompressedBeaconValidatorBatchHint,
 };
 use crate::frontend::eth::beacon::vars::{
     BeaconBalancesVariable, BeaconHeaderVariable, BeaconValidatorVariable,
     BeaconValidatorsVariable, BeaconWithdrawalVariable, BeaconWithdrawalsVariable,
 };
 use crate::frontend::eth::storage::generators::{
-    EthBlockGenerator, EthLogGenerator, EthStorageKeyGenerator, EthStorageProofHint,
+    EthAccountStorageProofHint, EthBlockGenerator, EthLogGenerator, EthStorageKeyGenerator,
+    EthStorageProofBatchHint, EthStorageProofHint,
 };
 use crate::frontend::hash::curta::digest_hint::HashDigestHint;
 use crate::frontend::hash::curta::proof_hint::HashProofHint;
 use crate::frontend::hash::keccak::keccak256::Keccak256Generator;
+use crate::frontend::hash::poseidon::poseidon2::gate::Poseidon2Generator;
 use crate::frontend::hash::poseidon::poseidon256::PoseidonHashOutVariable;
 use crate::frontend::hint::asynchronous::generator::{AsyncHintDataRef, AsyncHintRef};
 use crate::frontend::hint::asynchronous::hint::AsyncHint;
@@ -134,7 +145,9 @@ impl<L: PlonkParameters<D>, const D: usize> HintRegistry<L, D> {
     /// Registers a new witness generator with the given id.
     pub fn register_generator<W: WitnessGenerator<L::Field, D>>(&mut self, id: String) {
         let serializer = WitnessGeneratorSerializerFn::<W>(PhantomData);
-        self.generators.register(id, serializer).unwrap()
+        self.generators
+            .register(id.clone(), id, serializer)
+            .unwrap()
     }
 
     /// Registers a new simple witness generator with the given id.
@@ -146,7 +159,9 @@ impl<L: PlonkParameters<D>, const D: usize> HintRegistry<L, D> {
     pub fn register_hint<H: Hint<L, D>>(&mut self) {
         let serializer = SimpleHintSerializer::<L, H>::new();
         let id = H::id();
-        self.generators.register(id, serializer).unwrap();
+        self.generators
+            .register(id.clone(), id, serializer)
+            .unwrap();
     }
 
     /// Registers an asynchronous hint into the registry.
@@ -154,9 +169,20 @@ impl<L: PlonkParameters<D>, const D: usize> HintRegistry<L, D> {
         let serializer = AsyncHintSerializer::<L, H>::new();
         let id = AsyncHintRef::<L, D>::id(H::id());
         self.generators
-            .register(id.clone(), serializer.clone())
+            .register(id.clone(), id.clone(), serializer.clone())
+            .unwrap();
+        self.async_hints
+            .register(id.clone(), id, serializer)
             .unwrap();
-        self.async_hints.register(id, serializer).unwrap();
+    }
+
+    /// Registers the hint generating the proofs of a starky prover.
+    #[cfg(feature = "starky")]
+    pub fn register_starky_prover<P: crate::frontend::starky::StarkyProver<L, D>>(&mut self)
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    {
+        self.register_hint::<crate::frontend::starky::StarkyProofHint<P>>();
     }
 }
 
@@ -448,6 +474,40 @@ where
             ArrayVariable<Bytes32Variable, 8192>
         );
 
+        // The generators above are the ones of the legacy format, in its order. Generators added
+        // since then are registered below, so that they do not shift the legacy indices.
+        let legacy_keys = r.generators.legacy_keys.clone();
+
+        let poseidon2_generator_id = Poseidon2Generator::<L::Field, D>::id();
+        r.register_simple::<Poseidon2Generator<L::Field, D>>(poseidon2_generator_id);
+
+        r.register_async_hint::<EthStorageProofBatchHint<L, D>>();
+        r.register_async_hint::<EthAccountStorageProofHint<L, D>>();
+
+        register_powers_of_two!(r, BeaconBalanceSumHint);
+
+        r.register_hint::<SortHint<U32Variable>>();
+        r.register_hint::<SortHint<U64Variable>>();
+        r.register_hint::<SortHint<U256Variable>>();
+        r.register_hint::<SortHint<Bytes32Variable>>();
+
+        r.register_hint::<FpReduceHint<Fq>>();
+        r.register_hint::<FpInverseHint<Fq>>();
+        r.register_hint::<Fp2InverseHint<Fq>>();
+        r.register_hint::<Fp12InverseHint<Fq>>();
+        r.register_hint::<FpReduceHint<Fp>>();
+        r.register_hint::<FpInverseHint<Fp>>();
+        r.register_hint::<Fp2InverseHint<Fp>>();
+        r.register_hint::<Fp12InverseHint<Fp>>();
+        r.register_hint::<NonNativeInverseHint>();
+
+        r.register_hint::<BeaconPendingDepositsHint>();
+        r.register_hint::<BeaconPendingDepositHint>();
+        r.register_hint::<BeaconPendingConsolidationsHint>();
+        r.register_hint::<BeaconPendingConsolidationHint>();
+
+        r.generators.set_legacy_keys(legacy_keys).unwrap();
+
         r
     }
 }
@@ -460,14 +520,10 @@ impl<L: PlonkParameters<D>, const D: usize> WitnessGeneratorSerializer<L::Field,
         buf: &mut Buffer,
         common_data: &CommonCircuitData<L::Field, D>,
     ) -> IoResult<WitnessGeneratorRef<L::Field, D>> {
-        let idx = buf.read_usize()?;
-        let id = &self.generators.identifiers[idx];
-
-        self.generators
-            .registry
-            .get(id)
-            .unwrap_or_else(|| panic!("Generator type not registered {}", id))
-            .read(buf, common_data)
+        Ok(self
+            .generators
+            .read(buf, common_data)?
+            .unwrap_or_else(|id| panic!("Generator type not registered {}", id)))
     }
 
     fn write_generator(
@@ -477,19 +533,9 @@ impl<L: PlonkParameters<D>, const D: usize> WitnessGeneratorSerializer<L::Field,
         common_data: &CommonCircuitData<L::Field, D>,
     ) -> IoResult<()> {
         let id = generator.0.id();
-        let idx = self
-            .generators
-            .index
-            .get(&id)
-            .unwrap_or_else(|| panic!("Generator type not registered {}", id));
-        buf.write_usize(*idx)?;
-
         self.generators
-            .registry
-            .get(&id)
+            .write(buf, &id, generator, common_data)
             .unwrap_or_else(|| panic!("Generator type not registered {}", id))
-            .write(buf, generator, common_data)?;
-        Ok(())
     }
 }
 
@@ -499,14 +545,10 @@ impl<L: PlonkParameters<D>, const D: usize> HintSerializer<L, D> for HintRegistr
         buf: &mut Buffer,
         common_data: &CommonCircuitData<L::Field, D>,
     ) -> IoResult<AsyncHintDataRef<L, D>> {
-        let idx = buf.read_usize()?;
-        let id = &self.async_hints.identifiers[idx];
-
-        self.async_hints
-            .registry
-            .get(id)
-            .unwrap_or_else(|| panic!("Hint type not registered {}", id))
-            .read(buf, common_data)
+        Ok(self
+            .async_hints
+            .read(buf, common_data)?
+            .unwrap_or_else(|id| panic!("Hint type not registered {}", id)))
     }
 
     fn write_async_hint(
@@ -516,27 +558,18 @@ impl<L: PlonkParameters<D>, const D: usize> HintSerializer<L, D> for HintRegistr
         common_data: &CommonCircuitData<L::Field, D>,
     ) -> IoResult<()> {
         let id = hint.0.id();
-        let idx = self
-            .async_hints
-            .index
-            .get(&id)
-            .unwrap_or_else(|| panic!("Generator type not registered {}", id));
-        buf.write_usize(*idx)?;
-
         self.async_hints
-            .registry
-            .get(&id)
+            .write(buf, &id, hint, common_data)
             .unwrap_or_else(|| panic!("Generator type not registered {}", id))
-            .write(buf, hint, common_data)?;
-        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::gates::poseidon_mds::PoseidonMdsGenerator;
     use plonky2::iop::generator::{ConstantGenerator, SimpleGenerator, WitnessGeneratorRef};
-    use plonky2::util::serialization::{Buffer, WitnessGeneratorSerializer};
+    use plonky2::util::serialization::{Buffer, WitnessGeneratorSerializer, Write};
 
     use crate::backend::circuit::serialization::hints::HintRegistry;
     use crate::backend::circuit::DefaultParameters;
@@ -564,4 +597,34 @@ mod tests {
         let read_generator = registry.read_generator(&mut buffer, &common_data).unwrap();
         assert_eq!(raw_generator, read_generator);
     }
+
+    #[test]
+    fn test_witness_generator_legacy_deserialization() {
+        let builder = CircuitBuilder::<L, D>::new();
+        let common_data = builder.build().data.common;
+
+        let registry = HintRegistry::<L, D>::new();
+
+        // In the legacy format, generators are written after the index of their type in the
+        // registry before stable ids, which generators added since then do not shift.
+        let raw_generators: [(usize, WitnessGeneratorRef<F, D>); 2] = [
+            (
+                1,
+                WitnessGeneratorRef::new(ConstantGenerator::<F>::default().adapter()),
+            ),
+            (
+                3,
+                WitnessGeneratorRef::new(PoseidonMdsGenerator::<D>::default().adapter()),
+            ),
+        ];
+        for (index, raw_generator) in raw_generators {
+            let mut bytes = Vec::<u8>::new();
+            bytes.write_usize(index).unwrap();
+            raw_generator.0.serialize(&mut bytes, &common_data).unwrap();
+
+            let mut buffer = Buffer::new(&bytes);
+            let read_generator = registry.read_generator(&mut buffer, &common_data).unwrap();
+            assert_eq!(raw_generator, read_generator);
+        }
+    }
 }
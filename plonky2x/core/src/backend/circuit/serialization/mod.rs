@@ -1,12 +1,19 @@
+pub mod archive;
 pub mod gates;
 pub mod hints;
+pub mod proof;
 pub mod registry;
 
 use core::fmt::Debug;
 
+pub use archive::{
+    decode_proof_archive, encode_proof_archive, read_proof_archive, write_proof_archive,
+    ProofCompression,
+};
 pub use gates::GateRegistry;
 pub use hints::HintRegistry;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+pub use proof::{deserialize_proof, serialize_proof, ProofFormat};
 pub use registry::Serializer;
 
 use super::{Circuit, PlonkParameters};
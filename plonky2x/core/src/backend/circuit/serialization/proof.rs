@@ -0,0 +1,289 @@
+//! Versioned proof serialization.
+//!
+//! Proofs are serialized with bincode, so their encoding changes whenever the layout of the
+//! plonky2 proof types changes. To keep archived proofs verifiable across dependency upgrades,
+//! `serialize_proof` prefixes the encoding with the format version and `deserialize_proof`
+//! understands every format we have emitted historically, translating older formats to the
+//! current proof type. The hex-encoded proofs of the function requests and results use these
+//! encodings, so requests and results saved by older versions can still be loaded.
+
+use anyhow::{anyhow, Result};
+use bincode::Options;
+use plonky2::field::extension::Extendable;
+use plonky2::fri::proof::FriProof;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::plonk::config::GenericConfig;
+use plonky2::plonk::proof::{OpeningSet, Proof, ProofWithPublicInputs};
+use serde::{Deserialize, Serialize};
+
+/// Magic bytes that mark a versioned proof encoding.
+const VERSIONED_PROOF_MAGIC: &[u8; 4] = b"P2XP";
+
+/// The proof encodings emitted by the plonky2 releases this crate has been pinned to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofFormat {
+    /// plonky2 0.1, before lookup arguments were added to the opening set.
+    V0_1 = 1,
+    /// plonky2 0.2, the current format.
+    V0_2 = 2,
+}
+
+impl ProofFormat {
+    /// The format written by `serialize_proof`.
+    pub const CURRENT: ProofFormat = ProofFormat::V0_2;
+
+    fn from_byte(byte: u8) -> Result<Self> {
+        match byte {
+            1 => Ok(ProofFormat::V0_1),
+            2 => Ok(ProofFormat::V0_2),
+            _ => Err(anyhow!("unsupported proof format version {}", byte)),
+        }
+    }
+}
+
+/// The opening set of plonky2 0.1, which had no lookup openings.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct OpeningSetV0_1<F: RichField + Extendable<D>, const D: usize> {
+    constants: Vec<F::Extension>,
+    plonk_sigmas: Vec<F::Extension>,
+    wires: Vec<F::Extension>,
+    plonk_zs: Vec<F::Extension>,
+    plonk_zs_next: Vec<F::Extension>,
+    partial_products: Vec<F::Extension>,
+    quotient_polys: Vec<F::Extension>,
+}
+
+/// The proof of plonky2 0.1.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct ProofV0_1<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+    wires_cap: MerkleCap<F, C::Hasher>,
+    plonk_zs_partial_products_cap: MerkleCap<F, C::Hasher>,
+    quotient_polys_cap: MerkleCap<F, C::Hasher>,
+    openings: OpeningSetV0_1<F, D>,
+    opening_proof: FriProof<F, C::Hasher, D>,
+}
+
+/// The proof with public inputs of plonky2 0.1.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct ProofWithPublicInputsV0_1<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+> {
+    proof: ProofV0_1<F, C, D>,
+    public_inputs: Vec<F>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+    From<ProofWithPublicInputsV0_1<F, C, D>> for ProofWithPublicInputs<F, C, D>
+{
+    fn from(legacy: ProofWithPublicInputsV0_1<F, C, D>) -> Self {
+        let openings = legacy.proof.openings;
+        ProofWithPublicInputs {
+            proof: Proof {
+                wires_cap: legacy.proof.wires_cap,
+                plonk_zs_partial_products_cap: legacy.proof.plonk_zs_partial_products_cap,
+                quotient_polys_cap: legacy.proof.quotient_polys_cap,
+                openings: OpeningSet {
+                    constants: openings.constants,
+                    plonk_sigmas: openings.plonk_sigmas,
+                    wires: openings.wires,
+                    plonk_zs: openings.plonk_zs,
+                    plonk_zs_next: openings.plonk_zs_next,
+                    partial_products: openings.partial_products,
+                    quotient_polys: openings.quotient_polys,
+                    // Circuits built with plonky2 0.1 cannot contain lookups.
+                    lookup_zs: vec![],
+                    lookup_zs_next: vec![],
+                },
+                opening_proof: legacy.proof.opening_proof,
+            },
+            public_inputs: legacy.public_inputs,
+        }
+    }
+}
+
+/// Serializes a proof in the current versioned format.
+pub fn serialize_proof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    proof: &ProofWithPublicInputs<F, C, D>,
+) -> Result<Vec<u8>> {
+    let mut bytes = VERSIONED_PROOF_MAGIC.to_vec();
+    bytes.push(ProofFormat::CURRENT as u8);
+    bytes.extend(bincode::serialize(proof)?);
+    Ok(bytes)
+}
+
+/// Deserializes a proof encoded in the given format.
+pub fn deserialize_proof_with_format<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    bytes: &[u8],
+    format: ProofFormat,
+) -> Result<ProofWithPublicInputs<F, C, D>> {
+    // Same encoding as `bincode::deserialize`, but trailing bytes are rejected so that an encoding
+    // of one format is never mistaken for another.
+    let options = bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes();
+    match format {
+        ProofFormat::V0_1 => {
+            let legacy: ProofWithPublicInputsV0_1<F, C, D> = options.deserialize(bytes)?;
+            Ok(legacy.into())
+        }
+        ProofFormat::V0_2 => Ok(options.deserialize(bytes)?),
+    }
+}
+
+/// Deserializes a proof written by `serialize_proof` or by any earlier version of this crate.
+///
+/// Versioned encodings are decoded with the format recorded in their header. Unversioned
+/// bincode encodings are tried against every known format, newest first. If `common_data` is
+/// given, plonky2's own compressed byte encoding (`ProofWithPublicInputs::to_bytes`) is accepted
+/// as well.
+pub fn deserialize_proof<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    bytes: &[u8],
+    common_data: Option<&CommonCircuitData<F, D>>,
+) -> Result<ProofWithPublicInputs<F, C, D>> {
+    if bytes.len() > VERSIONED_PROOF_MAGIC.len() && bytes.starts_with(VERSIONED_PROOF_MAGIC) {
+        let format = ProofFormat::from_byte(bytes[VERSIONED_PROOF_MAGIC.len()])?;
+        return deserialize_proof_with_format(&bytes[VERSIONED_PROOF_MAGIC.len() + 1..], format);
+    }
+
+    for format in [ProofFormat::V0_2, ProofFormat::V0_1] {
+        if let Ok(proof) = deserialize_proof_with_format(bytes, format) {
+            return Ok(proof);
+        }
+    }
+
+    if let Some(common_data) = common_data {
+        if let Ok(proof) = ProofWithPublicInputs::from_bytes(bytes.to_vec(), common_data) {
+            return Ok(proof);
+        }
+    }
+
+    Err(anyhow!("proof is not encoded in any supported format"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::circuit::CircuitBuild;
+    use crate::backend::function::ProofResult;
+    use crate::prelude::*;
+    use crate::utils::test_circuits::{add_input, build_add_circuit, build_xor_lookup_circuit};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+    type F = <L as PlonkParameters<D>>::Field;
+    type C = <L as PlonkParameters<D>>::Config;
+
+    /// Proves the input `1, 2` of `circuit`, which reads two field elements.
+    fn prove(
+        circuit: CircuitBuild<L, D>,
+    ) -> (ProofWithPublicInputs<F, C, D>, CommonCircuitData<F, D>) {
+        let (proof, _) = circuit.prove(&add_input(&circuit, 1, 2));
+        (proof, circuit.data.common)
+    }
+
+    #[test]
+    fn test_proof_format_round_trip() {
+        // Proofs of circuits with lookups also have lookup openings.
+        let proofs = [
+            prove(build_add_circuit()),
+            prove(build_xor_lookup_circuit()),
+        ];
+        assert!(!proofs[1].0.proof.openings.lookup_zs.is_empty());
+
+        for (proof, common_data) in proofs {
+            // Versioned encoding.
+            let bytes = serialize_proof(&proof).unwrap();
+            assert_eq!(deserialize_proof::<F, C, D>(&bytes, None).unwrap(), proof);
+
+            // Unversioned bincode encoding, as written by earlier versions of this crate.
+            let bytes = bincode::serialize(&proof).unwrap();
+            assert_eq!(deserialize_proof::<F, C, D>(&bytes, None).unwrap(), proof);
+
+            // plonky2's own byte encoding.
+            let bytes = proof.to_bytes();
+            assert_eq!(
+                deserialize_proof::<F, C, D>(&bytes, Some(&common_data)).unwrap(),
+                proof
+            );
+        }
+    }
+
+    /// The proof of plonky2 0.1 with the same openings, for a proof without lookup openings.
+    fn to_v0_1(proof: &ProofWithPublicInputs<F, C, D>) -> ProofWithPublicInputsV0_1<F, C, D> {
+        assert!(proof.proof.openings.lookup_zs.is_empty());
+        let openings = proof.proof.openings.clone();
+        ProofWithPublicInputsV0_1 {
+            proof: ProofV0_1 {
+                wires_cap: proof.proof.wires_cap.clone(),
+                plonk_zs_partial_products_cap: proof.proof.plonk_zs_partial_products_cap.clone(),
+                quotient_polys_cap: proof.proof.quotient_polys_cap.clone(),
+                openings: OpeningSetV0_1 {
+                    constants: openings.constants,
+                    plonk_sigmas: openings.plonk_sigmas,
+                    wires: openings.wires,
+                    plonk_zs: openings.plonk_zs,
+                    plonk_zs_next: openings.plonk_zs_next,
+                    partial_products: openings.partial_products,
+                    quotient_polys: openings.quotient_polys,
+                },
+                opening_proof: proof.proof.opening_proof.clone(),
+            },
+            public_inputs: proof.public_inputs.clone(),
+        }
+    }
+
+    #[test]
+    fn test_proof_result_legacy_formats() {
+        let circuit = build_add_circuit();
+        let (proof, output) = circuit.prove(&add_input(&circuit, 1, 2));
+
+        // Earlier versions of this crate wrote the hex of the unversioned bincode encoding, of the
+        // proof types of plonky2 0.2 or, before that, of plonky2 0.1.
+        let legacy_encodings = [
+            bincode::serialize(&proof).unwrap(),
+            bincode::serialize(&to_v0_1(&proof)).unwrap(),
+        ];
+        for bytes in legacy_encodings {
+            let result = ProofResult::<L, D>::from_proof_output(proof.clone(), output.clone());
+            let mut json = serde_json::to_value(&result).unwrap();
+            json["data"]["proof"] = format!("0x{}", hex::encode(bytes)).into();
+
+            let (loaded, loaded_output) = serde_json::from_value::<ProofResult<L, D>>(json)
+                .unwrap()
+                .as_proof_and_output();
+            assert_eq!(loaded, proof);
+            assert_eq!(loaded_output, output);
+            circuit.data.verify(loaded.clone()).unwrap();
+
+            // Saving the result again writes the current versioned format.
+            let result = ProofResult::<L, D>::from_proof_output(loaded, loaded_output);
+            let json = serde_json::to_value(&result).unwrap();
+            let encoded = json["data"]["proof"].as_str().unwrap();
+            let bytes = hex::decode(&encoded[2..]).unwrap();
+            assert!(bytes.starts_with(VERSIONED_PROOF_MAGIC));
+        }
+    }
+
+    #[test]
+    fn test_proof_format_unknown_version() {
+        let (proof, _) = prove(build_add_circuit());
+        let mut bytes = serialize_proof(&proof).unwrap();
+        bytes[VERSIONED_PROOF_MAGIC.len()] = 255;
+        assert!(deserialize_proof::<F, C, D>(&bytes, None).is_err());
+    }
+}
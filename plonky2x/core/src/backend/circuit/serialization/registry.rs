@@ -7,7 +7,9 @@ use anyhow::{anyhow, Result};
 use plonky2::field::extension::Extendable;
 use plonky2::hash::hash_types::RichField;
 use plonky2::plonk::circuit_data::CommonCircuitData;
-use plonky2::util::serialization::{Buffer, IoResult};
+use plonky2::util::serialization::{Buffer, IoError, IoResult, Read, Write};
+
+use crate::utils::serde::{BufferRead, BufferWrite};
 
 /// A trait for serializing and deserializing objects compatible with plonky2 traits.
 pub trait Serializer<F: RichField + Extendable<D>, T, const D: usize>: 'static {
@@ -20,12 +22,33 @@ pub trait Serializer<F: RichField + Extendable<D>, T, const D: usize>: 'static {
     ) -> IoResult<()>;
 }
 
+/// The version of the format of the serialized objects.
+///
+/// Version 0 is the legacy format, where each object is serialized after the position of its
+/// type in the registry. From version 1, each object is serialized after the tag of the version
+/// and the stable id of its type.
+pub const REGISTRY_FORMAT_VERSION: usize = 1;
+
+/// The tag written before the id of an object in the given version of the format. Tags count down
+/// from `usize::MAX`, so they are never the index of a type in the legacy format.
+fn format_tag(version: usize) -> usize {
+    usize::MAX - version
+}
+
 /// A registry for storing serializers of objects.
+///
+/// Each object is serialized after the stable id of its type, rather than after the position of
+/// the type in the registry, so that a circuit can be deserialized by any registry which contains
+/// the types of its objects, whatever the order they were registered in. Objects in the legacy
+/// format are still read by their position in `legacy_keys`, the order of the registry before
+/// stable ids, which new default types must not change.
 pub(crate) struct SerializationRegistry<K: Hash, F: RichField + Extendable<D>, T, const D: usize> {
     pub registry: HashMap<K, Box<dyn Serializer<F, T, D>>>,
-    pub index: HashMap<K, usize>,
-    pub identifiers: Vec<K>,
-    pub current_index: usize,
+    pub ids: HashMap<K, String>,
+    pub keys: HashMap<String, K>,
+    /// The types of the legacy format, by index. Types registered after `set_legacy_keys` are
+    /// appended, as they were appended to the default types in the legacy format.
+    pub legacy_keys: Vec<K>,
 }
 
 impl<K: Hash + Debug, F: RichField + Extendable<D>, T: Debug, const D: usize> Debug
@@ -33,10 +56,7 @@ impl<K: Hash + Debug, F: RichField + Extendable<D>, T: Debug, const D: usize> De
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("SerializationRegistry")
-            .field("ids of registered objects", &self.registry.keys())
-            .field("index", &self.index)
-            .field("identifiers", &self.identifiers)
-            .field("current_index", &self.current_index)
+            .field("ids of registered objects", &self.keys.keys())
             .finish()
     }
 }
@@ -47,24 +67,86 @@ impl<F: RichField + Extendable<D>, K: PartialEq + Eq + Hash + Clone, T: Any, con
     pub fn new() -> Self {
         Self {
             registry: HashMap::new(),
-            index: HashMap::new(),
-            identifiers: Vec::new(),
-            current_index: 0,
+            ids: HashMap::new(),
+            keys: HashMap::new(),
+            legacy_keys: Vec::new(),
         }
     }
 
-    /// Registers a new serializer for the given object type.
-    pub fn register<S: Serializer<F, T, D>>(&mut self, key: K, serializer: S) -> Result<()> {
-        let exists = self.registry.insert(key.clone(), Box::new(serializer));
+    /// Sets the types of the legacy format, by index. Returns an error if one of the types is not
+    /// registered.
+    pub fn set_legacy_keys(&mut self, legacy_keys: Vec<K>) -> Result<()> {
+        if legacy_keys
+            .iter()
+            .any(|key| !self.registry.contains_key(key))
+        {
+            return Err(anyhow!("Legacy object type not registered"));
+        }
+        self.legacy_keys = legacy_keys;
+        Ok(())
+    }
 
-        if exists.is_some() {
+    /// Registers a new serializer for the given object type, identified by `id` in the
+    /// serialized objects.
+    pub fn register<S: Serializer<F, T, D>>(
+        &mut self,
+        key: K,
+        id: String,
+        serializer: S,
+    ) -> Result<()> {
+        if self.registry.contains_key(&key) {
             return Err(anyhow!("Object type already registered"));
         }
+        if self.keys.contains_key(&id) {
+            return Err(anyhow!("Object id already registered {}", id));
+        }
 
-        self.identifiers.push(key.clone());
-        self.index.insert(key, self.current_index);
-        self.current_index += 1;
+        self.registry.insert(key.clone(), Box::new(serializer));
+        self.ids.insert(key.clone(), id.clone());
+        self.keys.insert(id, key.clone());
+        self.legacy_keys.push(key);
 
         Ok(())
     }
+
+    /// Writes the tag of the format, the id of the object type `key` and `object`. Returns `None`
+    /// if the type is not registered.
+    pub fn write(
+        &self,
+        buf: &mut Vec<u8>,
+        key: &K,
+        object: &T,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> Option<IoResult<()>> {
+        let id = self.ids.get(key)?;
+        let serializer = self.registry.get(key)?;
+        Some(
+            buf.write_usize(format_tag(REGISTRY_FORMAT_VERSION))
+                .and_then(|_| buf.write_bytes(id.as_bytes()))
+                .and_then(|_| serializer.write(buf, object, common_data)),
+        )
+    }
+
+    /// Reads an object written by `write`, or by the index of its type in the legacy format.
+    /// Returns the id or the index read if its type is not registered.
+    pub fn read(
+        &self,
+        buf: &mut Buffer,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<core::result::Result<T, String>> {
+        let tag = buf.read_usize()?;
+        let (key, id) = if tag == format_tag(REGISTRY_FORMAT_VERSION) {
+            let id = String::from_utf8(buf.read_bytes()?).map_err(|_| IoError)?;
+            (self.keys.get(&id), id)
+        } else if tag > usize::MAX / 2 {
+            // The tag of a newer version of the format.
+            return Err(IoError);
+        } else {
+            (self.legacy_keys.get(tag), format!("at index {}", tag))
+        };
+        match key.and_then(|key| self.registry.get(key)) {
+            Some(serializer) => serializer.read(buf, common_data).map(Ok),
+            None => Ok(Err(id)),
+        }
+    }
 }
@@ -0,0 +1,224 @@
+//! Timings of witness generation and proving.
+//!
+//! `CircuitBuild::prove_with_timings` reports the time spent generating the witness, broken down
+//! by generator id, and the time spent in the plonky2 prover. The breakdown is also logged by
+//! `prove` when debug logging is enabled.
+//!
+//! A synchronous generator is timed while it runs. An asynchronous hint does its work on the hint
+//! handler, so it is also timed from its first poll until its output is ready: this is the latency
+//! of the hint, such as a call to an external data source, and overlaps with the other generators.
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use plonky2::iop::generator::WitnessGeneratorRef;
+use plonky2::plonk::circuit_data::ProverOnlyCircuitData;
+
+use super::PlonkParameters;
+
+/// The time spent running the generators with the same id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratorTiming {
+    pub id: String,
+    /// Whether the generators are asynchronous hints.
+    pub is_async: bool,
+    /// The number of generators with this id in the circuit.
+    pub instances: usize,
+    /// The number of times the generators were run or polled.
+    pub runs: usize,
+    /// The time spent running the generators on the witness generation thread.
+    pub busy: Duration,
+    /// For asynchronous hints, the total time from the first poll of each hint until its output
+    /// was ready.
+    pub wait: Duration,
+}
+
+/// The timings of the generation of a witness.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WitnessTimings {
+    pub total: Duration,
+    /// The timings by generator id, from the slowest. Empty if the generators were not timed.
+    pub generators: Vec<GeneratorTiming>,
+}
+
+/// The timings of a proof.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProofTimings {
+    pub witness: WitnessTimings,
+    /// The time spent in the plonky2 prover, after the witness was generated.
+    pub prove: Duration,
+}
+
+impl WitnessTimings {
+    /// Returns the timing of the generators with the given id, if any.
+    pub fn generator(&self, id: &str) -> Option<&GeneratorTiming> {
+        self.generators.iter().find(|timing| timing.id == id)
+    }
+}
+
+impl Display for WitnessTimings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(f, "witness generation took {:?}", self.total)?;
+        for timing in self.generators.iter() {
+            write!(
+                f,
+                "  {}: busy={:?}, runs={}, instances={}",
+                timing.id, timing.busy, timing.runs, timing.instances
+            )?;
+            if timing.is_async {
+                write!(f, ", wait={:?}", timing.wait)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ProofTimings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(
+            f,
+            "witness={:?}, prove={:?}",
+            self.witness.total, self.prove
+        )?;
+        write!(f, "{}", self.witness)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct RunTiming {
+    runs: usize,
+    busy: Duration,
+    first_run: Option<Instant>,
+    wait: Duration,
+}
+
+/// Collects the timings of every generator of a circuit during witness generation.
+#[derive(Debug, Clone)]
+pub(crate) struct GeneratorTimer {
+    start: Instant,
+    runs: Vec<RunTiming>,
+}
+
+impl GeneratorTimer {
+    pub(crate) fn new(nb_generators: usize) -> Self {
+        Self {
+            start: Instant::now(),
+            runs: vec![RunTiming::default(); nb_generators],
+        }
+    }
+
+    /// Records a run of the generator `index` which started at `start`.
+    pub(crate) fn record(&mut self, index: usize, start: Instant) {
+        let run = &mut self.runs[index];
+        run.runs += 1;
+        run.busy += start.elapsed();
+    }
+
+    /// Records a poll of the asynchronous hint `index` once its inputs were set, with `ready`
+    /// whether its output is ready.
+    pub(crate) fn record_poll(&mut self, index: usize, start: Instant, ready: bool) {
+        self.record(index, start);
+        let run = &mut self.runs[index];
+        let first_run = *run.first_run.get_or_insert(start);
+        if ready {
+            run.wait = first_run.elapsed();
+        }
+    }
+
+    /// Aggregates the timings by generator id.
+    pub(crate) fn finish<L: PlonkParameters<D>, const D: usize>(
+        self,
+        prover_data: &ProverOnlyCircuitData<L::Field, L::Config, D>,
+        is_async: impl Fn(usize) -> bool,
+    ) -> WitnessTimings {
+        let generators: &[WitnessGeneratorRef<L::Field, D>] = &prover_data.generators;
+        let mut timings = BTreeMap::<String, GeneratorTiming>::new();
+        for (index, run) in self.runs.iter().enumerate() {
+            let id = generators[index].0.id();
+            let timing = timings.entry(id.clone()).or_insert(GeneratorTiming {
+                id,
+                is_async: is_async(index),
+                instances: 0,
+                runs: 0,
+                busy: Duration::ZERO,
+                wait: Duration::ZERO,
+            });
+            timing.instances += 1;
+            timing.runs += run.runs;
+            timing.busy += run.busy;
+            timing.wait += run.wait;
+        }
+        let mut generators = timings.into_values().collect::<Vec<_>>();
+        generators.sort_by(|a, b| (b.busy + b.wait).cmp(&(a.busy + a.wait)));
+        WitnessTimings {
+            total: self.start.elapsed(),
+            generators,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use serde::{Deserialize, Serialize};
+    use tokio::time::sleep;
+
+    use super::*;
+    use crate::frontend::hint::asynchronous::hint::AsyncHint;
+    use crate::frontend::vars::{ValueStream, VariableStream};
+    use crate::prelude::*;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct SleepHint;
+
+    #[async_trait]
+    impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for SleepHint {
+        async fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let value = input_stream.read_value::<Variable>();
+            sleep(Duration::from_millis(200)).await;
+            output_stream.write_value::<Variable>(value);
+        }
+    }
+
+    #[test]
+    fn test_prove_with_timings() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        let output_stream = builder.async_hint(input_stream, SleepHint);
+        let b = output_stream.read::<Variable>(&mut builder);
+        let c = builder.add(a, b);
+        builder.write(c);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+        let (proof, mut output, timings) = circuit.prove_with_timings(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(
+            output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(4)
+        );
+
+        let witness = &timings.witness;
+        let hint = witness
+            .generators
+            .iter()
+            .find(|timing| timing.is_async)
+            .unwrap();
+        assert_eq!(hint.instances, 1);
+        assert!(hint.runs >= 1);
+        assert!(hint.wait >= Duration::from_millis(200));
+        assert!(witness.total >= hint.wait);
+        assert_eq!(witness.generator(&hint.id), Some(hint));
+        assert!(witness.generators.iter().any(|timing| !timing.is_async));
+        assert!(timings.to_string().contains("wait="));
+    }
+}
@@ -7,17 +7,24 @@
 //! [1] : https://github.com/mir-protocol/plonky2/blob/main/plonky2/src/iop/generator.rs#L19
 
 use alloc::collections::BTreeMap;
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::mem;
 use std::collections::HashSet;
+use std::time::Instant;
 
 use anyhow::{anyhow, Error, Result};
 use log::trace;
+use plonky2::field::types::Field;
 use plonky2::iop::generator::{GeneratedValues, WitnessGeneratorRef};
-use plonky2::iop::witness::{PartialWitness, PartitionWitness, WitnessWrite};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartialWitness, PartitionWitness, Witness, WitnessWrite};
 use plonky2::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
 use starkyx::maybe_rayon::rayon;
+use tokio::runtime::Handle;
 use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::oneshot;
 
+use super::timing::{GeneratorTimer, WitnessTimings};
 use super::PlonkParameters;
 use crate::frontend::hint::asynchronous::generator::{AsyncHintDataRef, AsyncHintRef, HintPoll};
 use crate::frontend::hint::asynchronous::handler::HintHandler;
@@ -29,6 +36,62 @@ pub fn generate_witness<'a, L: PlonkParameters<D>, const D: usize>(
     prover_data: &'a ProverOnlyCircuitData<L::Field, L::Config, D>,
     common_data: &'a CommonCircuitData<L::Field, D>,
     async_generator_refs: &'a BTreeMap<usize, AsyncHintDataRef<L, D>>,
+) -> Result<PartitionWitness<'a, L::Field>> {
+    generate_witness_with_options(
+        inputs,
+        prover_data,
+        common_data,
+        async_generator_refs,
+        None,
+        false,
+    )
+}
+
+/// Generates a witness like `generate_witness`, also returning the time spent in each generator.
+pub fn generate_witness_timed<'a, L: PlonkParameters<D>, const D: usize>(
+    inputs: PartialWitness<L::Field>,
+    prover_data: &'a ProverOnlyCircuitData<L::Field, L::Config, D>,
+    common_data: &'a CommonCircuitData<L::Field, D>,
+    async_generator_refs: &'a BTreeMap<usize, AsyncHintDataRef<L, D>>,
+) -> Result<(PartitionWitness<'a, L::Field>, WitnessTimings)> {
+    let mut timer = GeneratorTimer::new(prover_data.generators.len());
+    let witness = generate_witness_with_options(
+        inputs,
+        prover_data,
+        common_data,
+        async_generator_refs,
+        Some(&mut timer),
+        false,
+    )?;
+    let timings = timer.finish::<L, D>(prover_data, |i| async_generator_refs.contains_key(&i));
+    Ok((witness, timings))
+}
+
+/// Generates a witness like `generate_witness`, but a generator setting a value which contradicts
+/// the witness fails with a `WitnessConflict` error instead of panicking.
+pub(crate) fn generate_witness_checked<'a, L: PlonkParameters<D>, const D: usize>(
+    inputs: PartialWitness<L::Field>,
+    prover_data: &'a ProverOnlyCircuitData<L::Field, L::Config, D>,
+    common_data: &'a CommonCircuitData<L::Field, D>,
+    async_generator_refs: &'a BTreeMap<usize, AsyncHintDataRef<L, D>>,
+) -> Result<PartitionWitness<'a, L::Field>> {
+    generate_witness_with_options(
+        inputs,
+        prover_data,
+        common_data,
+        async_generator_refs,
+        None,
+        true,
+    )
+}
+
+fn generate_witness_with_options<'a, L: PlonkParameters<D>, const D: usize>(
+    inputs: PartialWitness<L::Field>,
+    prover_data: &'a ProverOnlyCircuitData<L::Field, L::Config, D>,
+    common_data: &'a CommonCircuitData<L::Field, D>,
+    async_generator_refs: &'a BTreeMap<usize, AsyncHintDataRef<L, D>>,
+    timer: Option<&mut GeneratorTimer>,
+    check_conflicts: bool,
 ) -> Result<PartitionWitness<'a, L::Field>> {
     // If async hints are present, set up the a handler and initialize the generators with the
     // handler's communication channel.
@@ -63,6 +126,52 @@ pub fn generate_witness<'a, L: PlonkParameters<D>, const D: usize>(
         common_data,
         async_generators,
         rx_handler_error,
+        &mut WitnessBuffers::new(),
+        timer,
+        check_conflicts,
+    )
+}
+
+/// Generates a witness like `generate_witness`, running the hint handler of the async hints on
+/// `runtime` and reusing `buffers` instead of setting up both for every witness.
+pub(crate) fn generate_witness_with_buffers<'a, L: PlonkParameters<D>, const D: usize>(
+    inputs: PartialWitness<L::Field>,
+    prover_data: &'a ProverOnlyCircuitData<L::Field, L::Config, D>,
+    common_data: &'a CommonCircuitData<L::Field, D>,
+    async_generator_refs: &'a BTreeMap<usize, AsyncHintDataRef<L, D>>,
+    runtime: &Handle,
+    buffers: &mut WitnessBuffers<L::Field>,
+) -> Result<PartitionWitness<'a, L::Field>> {
+    let (tx_handler_error, rx_handler_error) = oneshot::channel();
+    let async_generators = match async_generator_refs.is_empty() {
+        true => BTreeMap::new(),
+        false => {
+            let (tx, rx) = unbounded_channel();
+            let mut hint_handler = HintHandler::<L, D>::new(rx);
+            runtime.spawn(async move {
+                let result = hint_handler.run().await;
+                if let Err(e) = result {
+                    tx_handler_error.send(e).unwrap();
+                }
+            });
+
+            BTreeMap::from_iter(
+                async_generator_refs
+                    .iter()
+                    .map(|(i, g)| (*i, g.0.generator(tx.clone()))),
+            )
+        }
+    };
+
+    fill_witness_values::<L, D>(
+        inputs,
+        prover_data,
+        common_data,
+        async_generators,
+        rx_handler_error,
+        buffers,
+        None,
+        false,
     )
 }
 
@@ -105,31 +214,114 @@ pub async fn generate_witness_async<'a, L: PlonkParameters<D>, const D: usize>(
             common_data,
             async_generators,
             rx_handler_error,
+            &mut WitnessBuffers::new(),
+            None,
+            false,
+        )
+    })
+}
+
+/// The scratch space of witness generation, which can be reused across the witnesses of a batch.
+pub(crate) struct WitnessBuffers<F: Field> {
+    values: GeneratedValues<F>,
+    pending: HashSet<usize>,
+    next_pending: HashSet<usize>,
+    is_expired: Vec<bool>,
+}
+
+impl<F: Field> WitnessBuffers<F> {
+    pub(crate) fn new() -> Self {
+        Self {
+            values: GeneratedValues::empty(),
+            pending: HashSet::new(),
+            next_pending: HashSet::new(),
+            is_expired: Vec::new(),
+        }
+    }
+}
+
+/// A value set during witness generation which contradicts the value already in the witness for
+/// the same partition of targets, meaning that a copy constraint of the circuit is not satisfied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessConflict<F: Field> {
+    /// The target which was set.
+    pub target: Target,
+    /// The value already in the witness.
+    pub existing: F,
+    /// The value which was set.
+    pub conflicting: F,
+    /// The id of the generator which set the value, or `input` for the inputs of the circuit.
+    pub generator: String,
+}
+
+impl<F: Field> Display for WitnessConflict<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} set {:?} to {} but its partition already has the value {}",
+            self.generator, self.target, self.conflicting, self.existing
         )
+    }
+}
+
+impl<F: Field> std::error::Error for WitnessConflict<F> {}
+
+/// Returns the first of `values` which contradicts `witness`, if any.
+fn find_conflict<F: Field>(
+    witness: &PartitionWitness<F>,
+    values: &[(Target, F)],
+    generator: impl FnOnce() -> String,
+) -> Option<WitnessConflict<F>> {
+    let (target, existing, conflicting) =
+        values
+            .iter()
+            .find_map(|(target, value)| match witness.try_get_target(*target) {
+                Some(existing) if existing != *value => Some((*target, existing, *value)),
+                _ => None,
+            })?;
+    Some(WitnessConflict {
+        target,
+        existing,
+        conflicting,
+        generator: generator(),
     })
 }
 
-/// Fill in the witness after intiializing async generators.
+/// Fill in the witness after intiializing async generators, recording the runs of the generators
+/// in `timer` if any. If `check_conflicts` is set, a generated value contradicting the witness
+/// returns a `WitnessConflict` error.
+#[allow(clippy::too_many_arguments)]
 fn fill_witness_values<'a, L: PlonkParameters<D>, const D: usize>(
     inputs: PartialWitness<L::Field>,
     prover_data: &'a ProverOnlyCircuitData<L::Field, L::Config, D>,
     common_data: &'a CommonCircuitData<L::Field, D>,
     mut async_generators: BTreeMap<usize, AsyncHintRef<L, D>>,
     mut rx_handler_error: oneshot::Receiver<Error>,
+    buffers: &mut WitnessBuffers<L::Field>,
+    mut timer: Option<&mut GeneratorTimer>,
+    check_conflicts: bool,
 ) -> Result<PartitionWitness<'a, L::Field>> {
     let config = &common_data.config;
     let generators = &prover_data.generators;
     let generator_indices_by_watches = &prover_data.generator_indices_by_watches;
+    let WitnessBuffers {
+        values: buffer,
+        pending: pending_generator_indices,
+        next_pending: next_pending_generator_indices,
+        is_expired: generator_is_expired,
+    } = buffers;
+    buffer.target_values.clear();
 
     // Build a list of "pending" generators which are queued to be run. Initially, all generators
     // are queued.
-    let mut pending_generator_indices: HashSet<_> = (0..generators.len()).collect();
+    pending_generator_indices.clear();
+    pending_generator_indices.extend(0..generators.len());
 
     // We also track a list of "expired" generators which have already returned false.
-    let mut generator_is_expired = vec![false; generators.len()];
+    generator_is_expired.clear();
+    generator_is_expired.resize(generators.len(), false);
     let mut remaining_generators = generators.len();
 
-    let mut buffer = GeneratedValues::empty();
     let mut witness = PartitionWitness::new(
         config.num_wires,
         common_data.degree(),
@@ -137,24 +329,37 @@ fn fill_witness_values<'a, L: PlonkParameters<D>, const D: usize>(
     );
 
     for (t, v) in inputs.target_values.into_iter() {
+        if check_conflicts {
+            if let Some(conflict) = find_conflict(&witness, &[(t, v)], || "input".to_string()) {
+                return Err(conflict.into());
+            }
+        }
         witness.set_target(t, v);
     }
 
     // Keep running generators until we fail to make progress.
     while !pending_generator_indices.is_empty() {
-        let mut next_pending_generator_indices = HashSet::new();
-        for &generator_idx in &pending_generator_indices {
+        next_pending_generator_indices.clear();
+        for &generator_idx in pending_generator_indices.iter() {
             if generator_is_expired[generator_idx] {
                 continue;
             }
 
             // Run the generator, depending on whether it is an asyncronous or not.
+            let start = timer.as_ref().map(|_| Instant::now());
             if let Some(async_gen) = async_generators.get_mut(&generator_idx) {
                 // Check if the hint handler has returned an error, and if so, return it.
                 if let Ok(e) = rx_handler_error.try_recv() {
                     return Err(e);
                 }
-                let pol = async_gen.0.run(&witness, &mut buffer);
+                let pol = async_gen.0.run(&witness, buffer);
+                if let (Some(timer), Some(start)) = (timer.as_deref_mut(), start) {
+                    match pol {
+                        HintPoll::InputPending => timer.record(generator_idx, start),
+                        HintPoll::Pending => timer.record_poll(generator_idx, start, false),
+                        HintPoll::Ready => timer.record_poll(generator_idx, start, true),
+                    }
+                }
                 match pol {
                     HintPoll::InputPending => {}
                     HintPoll::Pending => {
@@ -166,13 +371,25 @@ fn fill_witness_values<'a, L: PlonkParameters<D>, const D: usize>(
                     }
                 }
             } else {
-                let finished = generators[generator_idx].0.run(&witness, &mut buffer);
+                let finished = generators[generator_idx].0.run(&witness, buffer);
+                if let (Some(timer), Some(start)) = (timer.as_deref_mut(), start) {
+                    timer.record(generator_idx, start);
+                }
                 if finished {
                     generator_is_expired[generator_idx] = true;
                     remaining_generators -= 1;
                 }
             }
 
+            if check_conflicts {
+                let conflict = find_conflict(&witness, &buffer.target_values, || {
+                    generators[generator_idx].0.id()
+                });
+                if let Some(conflict) = conflict {
+                    return Err(conflict.into());
+                }
+            }
+
             // Merge any generated values into our witness, and get a list of newly-populated
             // targets' representatives.
             let new_target_reps = buffer
@@ -195,7 +412,7 @@ fn fill_witness_values<'a, L: PlonkParameters<D>, const D: usize>(
             }
         }
 
-        pending_generator_indices = next_pending_generator_indices;
+        mem::swap(pending_generator_indices, next_pending_generator_indices);
     }
 
     if remaining_generators > 0 {
@@ -215,7 +432,7 @@ fn fill_witness_values<'a, L: PlonkParameters<D>, const D: usize>(
 #[inline]
 fn get_generator_error<L: PlonkParameters<D>, const D: usize>(
     generators: &[WitnessGeneratorRef<L::Field, D>],
-    generator_is_expired: Vec<bool>,
+    generator_is_expired: &[bool],
 ) -> Error {
     let mut generators_not_run = Vec::new();
     for i in 0..generator_is_expired.len() {
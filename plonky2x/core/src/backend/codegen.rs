@@ -0,0 +1,230 @@
+//! Generation of the Solidity contracts verifying the wrapped proofs of a circuit on-chain.
+//!
+//! The proof-verification contract depends on the verifying key of the gnark verifier only, and is
+//! exported by it to `Verifier.sol` in the wrapper path. `VerifierContract` appends to it the
+//! `FunctionVerifier` contract called on-chain, with the digest of the wrapper circuit of a built
+//! circuit baked in, so that the deployed contracts are generated from the circuit instead of being
+//! maintained by hand.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut};
+
+use crate::backend::circuit::PlonkParameters;
+use crate::backend::wrapper::wrap::{WrappedCircuit, WrapperBackend};
+
+/// The Solidity verifier of the wrapped proofs of a circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifierContract {
+    /// The digest of the wrapper circuit, as a `bytes32` literal.
+    pub circuit_digest: String,
+    pub backend: WrapperBackend,
+}
+
+impl VerifierContract {
+    /// Returns the verifier of the proofs of `wrapped_circuit`.
+    pub fn new<
+        InnerParameters: PlonkParameters<D>,
+        OuterParameters: PlonkParameters<D, Field = InnerParameters::Field>,
+        const D: usize,
+    >(
+        wrapped_circuit: &WrappedCircuit<InnerParameters, OuterParameters, D>,
+    ) -> Self
+    where
+        <InnerParameters::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<InnerParameters::Field>,
+    {
+        // to_bytes() returns the representation as LE, but we want to save it on-chain as BE
+        // because that is the format of the public input to the gnark plonky2 verifier.
+        let mut circuit_digest_bytes = wrapped_circuit
+            .wrapper_circuit
+            .data
+            .verifier_only
+            .circuit_digest
+            .to_bytes();
+        circuit_digest_bytes.reverse();
+
+        // The VerifierDigest is stored onchain as a bytes32, so we need to pad it with 0s
+        // to store it in the solidity smart contract.
+        //
+        // Note that we don't need to do any sort of truncation of the most significant bits
+        // because the circuit digest already lives in the bn254 field because the prover config
+        // uses the Poseidon bn254 hasher.
+        //
+        // In the solidity smart contract we should not truncate the 3 most significant bits
+        // like we do with input_hash and output_hash as the circuit digest has a small
+        // probability of being greater than 2^253 given that the field modulus is 254 bits.
+        let mut padded = vec![0u8; 32];
+        let digest_len = circuit_digest_bytes.len();
+        padded[(32 - digest_len)..].copy_from_slice(&circuit_digest_bytes);
+
+        Self {
+            circuit_digest: format!("0x{}", hex::encode(padded)),
+            backend: wrapped_circuit.backend(),
+        }
+    }
+
+    /// Returns the `FunctionVerifier` contract, which inherits from the proof-verification
+    /// contract exported by gnark.
+    pub fn function_verifier(&self) -> String {
+        let template = match self.backend {
+            WrapperBackend::Plonk => PLONK_FUNCTION_VERIFIER,
+            WrapperBackend::Groth16 => GROTH16_FUNCTION_VERIFIER,
+        };
+        template.replace("{CIRCUIT_DIGEST}", &self.circuit_digest)
+    }
+
+    /// Returns the source of the proof-verification contract `gnark_verifier` exported by gnark,
+    /// adapted to be called by the `FunctionVerifier`, followed by the `FunctionVerifier`.
+    pub fn generate(&self, gnark_verifier: &str) -> String {
+        let verifier = match self.backend {
+            WrapperBackend::Plonk => gnark_verifier
+                .replace("pragma solidity ^0.8.19;", "pragma solidity ^0.8.16;")
+                .replace("function Verify", "function verifyProof"),
+            WrapperBackend::Groth16 => gnark_verifier.to_string(),
+        };
+        verifier + &self.function_verifier()
+    }
+
+    /// Generates the contract from the proof-verification contract exported by the gnark verifier
+    /// at `wrapper_path`.
+    pub fn generate_from_wrapper_path<P: AsRef<Path>>(&self, wrapper_path: P) -> Result<String> {
+        let gnark_verifier_path = wrapper_path.as_ref().join("Verifier.sol");
+        let gnark_verifier = fs::read_to_string(&gnark_verifier_path)
+            .with_context(|| format!("failed to read {}", gnark_verifier_path.display()))?;
+        Ok(self.generate(&gnark_verifier))
+    }
+
+    /// Generates the contract from the gnark verifier at `wrapper_path` and saves it to `path`.
+    pub fn save<P: AsRef<Path>, Q: AsRef<Path>>(&self, wrapper_path: P, path: Q) -> Result<()> {
+        let contract = self.generate_from_wrapper_path(wrapper_path)?;
+        fs::write(path, contract)?;
+        Ok(())
+    }
+}
+
+/// The `FunctionVerifier` appended to the `PlonkVerifier` contract generated by gnark.
+const PLONK_FUNCTION_VERIFIER: &str = "
+
+interface IFunctionVerifier {
+    function verify(bytes32 _inputHash, bytes32 _outputHash, bytes memory _proof) external view returns (bool);
+
+    function verificationKeyHash() external pure returns (bytes32);
+}
+
+contract FunctionVerifier is IFunctionVerifier, PlonkVerifier {
+
+    bytes32 public constant CIRCUIT_DIGEST = {CIRCUIT_DIGEST};
+
+    function verify(bytes32 _inputHash, bytes32 _outputHash, bytes memory _proof) external view returns (bool) {
+        uint256[] memory input = new uint256[](3);
+        input[0] = uint256(CIRCUIT_DIGEST);
+        input[1] = uint256(_inputHash) & ((1 << 253) - 1);
+        input[2] = uint256(_outputHash) & ((1 << 253) - 1); 
+
+        return this.verifyProof(_proof, input);
+    }
+
+    function verificationKeyHash() external pure returns (bytes32) {
+        return CIRCUIT_DIGEST;
+    }
+}
+";
+
+/// The `FunctionVerifier` appended to the Groth16 `Verifier` contract generated by gnark, whose
+/// `verifyProof` takes the proof as 8 words and reverts if it is invalid.
+const GROTH16_FUNCTION_VERIFIER: &str = "
+
+interface IFunctionVerifier {
+    function verify(bytes32 _inputHash, bytes32 _outputHash, bytes memory _proof) external view returns (bool);
+
+    function verificationKeyHash() external pure returns (bytes32);
+}
+
+contract FunctionVerifier is IFunctionVerifier, Verifier {
+
+    bytes32 public constant CIRCUIT_DIGEST = {CIRCUIT_DIGEST};
+
+    function verify(bytes32 _inputHash, bytes32 _outputHash, bytes memory _proof) external view returns (bool) {
+        uint256[3] memory input;
+        input[0] = uint256(CIRCUIT_DIGEST);
+        input[1] = uint256(_inputHash) & ((1 << 253) - 1);
+        input[2] = uint256(_outputHash) & ((1 << 253) - 1);
+        uint256[8] memory proof = abi.decode(_proof, (uint256[8]));
+
+        try this.verifyProof(proof, input) {
+            return true;
+        } catch {
+            return false;
+        }
+    }
+
+    function verificationKeyHash() external pure returns (bytes32) {
+        return CIRCUIT_DIGEST;
+    }
+}
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::circuit::{DefaultParameters, Groth16WrapperParameters};
+    use crate::frontend::builder::CircuitBuilder;
+    use crate::frontend::vars::ByteVariable;
+
+    const PLONK_VERIFIER: &str = "pragma solidity ^0.8.19;
+
+contract PlonkVerifier {
+    function Verify(bytes calldata proof, uint256[] calldata public_inputs) public view returns (bool success) {}
+}
+";
+
+    #[test]
+    fn test_verifier_contract() {
+        let digest = format!("0x{}", "0a".repeat(32));
+        let contract = VerifierContract {
+            circuit_digest: digest.clone(),
+            backend: WrapperBackend::Plonk,
+        };
+        let source = contract.generate(PLONK_VERIFIER);
+        assert!(source.starts_with("pragma solidity ^0.8.16;"));
+        assert!(source.contains("function verifyProof(bytes calldata proof"));
+        assert!(source.contains("contract FunctionVerifier is IFunctionVerifier, PlonkVerifier"));
+        assert!(source.contains(&format!("CIRCUIT_DIGEST = {};", digest)));
+        assert!(!source.contains("{CIRCUIT_DIGEST}"));
+
+        let contract = VerifierContract {
+            circuit_digest: digest.clone(),
+            backend: WrapperBackend::Groth16,
+        };
+        let source = contract.generate("contract Verifier {}\n");
+        assert!(source.contains("contract FunctionVerifier is IFunctionVerifier, Verifier"));
+        assert!(source.contains("abi.decode(_proof, (uint256[8]))"));
+        assert!(source.contains(&format!("CIRCUIT_DIGEST = {};", digest)));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verifier_contract_circuit_digest() {
+        let mut builder = CircuitBuilder::<DefaultParameters, 2>::new();
+        let a = builder.evm_read::<ByteVariable>();
+        builder.evm_write(a);
+        let circuit = builder.build();
+        let wrapped_circuit =
+            WrappedCircuit::<DefaultParameters, Groth16WrapperParameters, 2>::build(circuit);
+
+        let contract = VerifierContract::new(&wrapped_circuit);
+        assert_eq!(contract.backend, WrapperBackend::Plonk);
+        let mut digest = wrapped_circuit
+            .wrapper_circuit
+            .data
+            .verifier_only
+            .circuit_digest
+            .to_bytes();
+        digest.reverse();
+        assert_eq!(contract.circuit_digest.len(), 2 + 2 * 32);
+        assert!(contract.circuit_digest.ends_with(&hex::encode(digest)));
+    }
+}
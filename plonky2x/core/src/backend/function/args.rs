@@ -1,5 +1,7 @@
 use clap::{Parser, Subcommand};
 
+use crate::backend::wrapper::wrap::WrapperBackend;
+
 #[derive(Parser, Debug, Clone)]
 #[command(about = "Compile a circuit.")]
 pub struct BuildArgs {
@@ -8,11 +10,19 @@ pub struct BuildArgs {
 
     #[arg(long, default_value = "/verifier-build")]
     pub wrapper_path: String,
+
+    /// The proof system of the gnark verifier built at `wrapper_path`.
+    #[arg(long, value_enum, default_value_t = WrapperBackend::Plonk)]
+    pub wrapper_backend: WrapperBackend,
+
+    /// Rebuilds the circuit even if the build cache has an entry for its version.
+    #[arg(long)]
+    pub no_cache: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
 #[command(
-    about = "Generate a proof for a circuit and wrap it into a groth16 proof using the gnark verifier."
+    about = "Generate a proof for a circuit and wrap it into a BN254 proof using the gnark verifier."
 )]
 pub struct ProveArgs {
     pub input_json: String,
@@ -22,6 +32,10 @@ pub struct ProveArgs {
 
     #[arg(long, default_value = "/verifier-build")]
     pub wrapper_path: String,
+
+    /// The proof system of the gnark verifier built at `wrapper_path`.
+    #[arg(long, value_enum, default_value_t = WrapperBackend::Plonk)]
+    pub wrapper_backend: WrapperBackend,
 }
 
 #[derive(Subcommand, Debug, Clone)]
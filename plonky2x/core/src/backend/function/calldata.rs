@@ -0,0 +1,192 @@
+//! Calldata encoding for fulfilling requests on the `SuccinctGateway` contract.
+//!
+//! The layouts below mirror `SuccinctGateway.fulfillCallback` and `SuccinctGateway.fulfillCall`,
+//! so relayers can submit a proof result without re-implementing the ABI encoding.
+
+use anyhow::{anyhow, Result};
+use ethers::abi::{encode, Token};
+use ethers::types::{Address, H256, U256};
+use ethers::utils::id;
+
+use super::ProofResult;
+use crate::backend::circuit::{PlonkParameters, PublicOutput};
+
+/// The signature of `SuccinctGateway.fulfillCallback`.
+pub const FULFILL_CALLBACK_SIGNATURE: &str =
+    "fulfillCallback(uint32,bytes32,bytes32,address,bytes4,uint32,bytes,bytes,bytes)";
+
+/// The signature of `SuccinctGateway.fulfillCall`.
+pub const FULFILL_CALL_SIGNATURE: &str = "fulfillCall(bytes32,bytes,bytes,bytes,address,bytes)";
+
+/// The fields of a callback request, as emitted by the gateway's `RequestCallback` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackRequest {
+    pub nonce: u32,
+    pub function_id: H256,
+    pub input_hash: H256,
+    pub callback_address: Address,
+    pub callback_selector: [u8; 4],
+    pub callback_gas_limit: u32,
+    pub context: Vec<u8>,
+}
+
+/// The fields of a call request, as emitted by the gateway's `RequestCall` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallRequest {
+    pub function_id: H256,
+    pub input: Vec<u8>,
+    pub callback_address: Address,
+    pub callback_data: Vec<u8>,
+}
+
+/// Returns the output bytes the gateway expects for the given circuit output.
+fn output_bytes<L: PlonkParameters<D>, const D: usize>(
+    output: &PublicOutput<L, D>,
+) -> Result<Vec<u8>> {
+    match output {
+        PublicOutput::Bytes(output) => Ok(output.clone()),
+        _ => Err(anyhow!(
+            "only circuits with bytes io can be fulfilled on-chain"
+        )),
+    }
+}
+
+/// Prepends the 4-byte selector of `signature` to the ABI encoding of `tokens`.
+fn encode_call(signature: &str, tokens: &[Token]) -> Vec<u8> {
+    let mut calldata = id(signature).to_vec();
+    calldata.extend(encode(tokens));
+    calldata
+}
+
+/// Encodes the calldata of `fulfillCallback` for the given request, circuit output and proof.
+pub fn fulfill_callback_calldata<L: PlonkParameters<D>, const D: usize>(
+    request: &CallbackRequest,
+    output: &PublicOutput<L, D>,
+    proof: &[u8],
+) -> Result<Vec<u8>> {
+    let tokens = [
+        Token::Uint(U256::from(request.nonce)),
+        Token::FixedBytes(request.function_id.as_bytes().to_vec()),
+        Token::FixedBytes(request.input_hash.as_bytes().to_vec()),
+        Token::Address(request.callback_address),
+        Token::FixedBytes(request.callback_selector.to_vec()),
+        Token::Uint(U256::from(request.callback_gas_limit)),
+        Token::Bytes(request.context.clone()),
+        Token::Bytes(output_bytes(output)?),
+        Token::Bytes(proof.to_vec()),
+    ];
+    Ok(encode_call(FULFILL_CALLBACK_SIGNATURE, &tokens))
+}
+
+/// Encodes the calldata of `fulfillCall` for the given request, circuit output and proof.
+pub fn fulfill_call_calldata<L: PlonkParameters<D>, const D: usize>(
+    request: &CallRequest,
+    output: &PublicOutput<L, D>,
+    proof: &[u8],
+) -> Result<Vec<u8>> {
+    let tokens = [
+        Token::FixedBytes(request.function_id.as_bytes().to_vec()),
+        Token::Bytes(request.input.clone()),
+        Token::Bytes(output_bytes(output)?),
+        Token::Bytes(proof.to_vec()),
+        Token::Address(request.callback_address),
+        Token::Bytes(request.callback_data.clone()),
+    ];
+    Ok(encode_call(FULFILL_CALL_SIGNATURE, &tokens))
+}
+
+impl<L: PlonkParameters<D>, const D: usize> ProofResult<L, D> {
+    /// Encodes the calldata of `fulfillCallback` for this (wrapped) proof result.
+    pub fn fulfill_callback_calldata(&self, request: &CallbackRequest) -> Result<Vec<u8>> {
+        match self {
+            ProofResult::Bytes(result) => fulfill_callback_calldata::<L, D>(
+                request,
+                &PublicOutput::Bytes(result.data.output.clone()),
+                &result.data.proof,
+            ),
+            _ => Err(anyhow!(
+                "only bytes proof results can be fulfilled on-chain"
+            )),
+        }
+    }
+
+    /// Encodes the calldata of `fulfillCall` for this (wrapped) proof result.
+    pub fn fulfill_call_calldata(&self, request: &CallRequest) -> Result<Vec<u8>> {
+        match self {
+            ProofResult::Bytes(result) => fulfill_call_calldata::<L, D>(
+                request,
+                &PublicOutput::Bytes(result.data.output.clone()),
+                &result.data.proof,
+            ),
+            _ => Err(anyhow!(
+                "only bytes proof results can be fulfilled on-chain"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{decode, ParamType};
+
+    use super::*;
+    use crate::backend::circuit::DefaultParameters;
+    use crate::utils::{address, bytes32};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    #[test]
+    fn test_fulfill_callback_calldata() {
+        let request = CallbackRequest {
+            nonce: 7,
+            function_id: bytes32!(
+                "0x281dc31bb78779a1ede7bf0f4d2bc5f07ddebc9f9d1155e413d8804384604bbe"
+            ),
+            input_hash: bytes32!(
+                "0xad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5"
+            ),
+            callback_address: address!("0x55032650b14df07b85bF18A3a3eC8E0Af2e028d5"),
+            callback_selector: [0xde, 0xad, 0xbe, 0xef],
+            callback_gas_limit: 500_000,
+            context: vec![1, 2, 3],
+        };
+        let result = ProofResult::<L, D>::from_bytes(vec![9; 64], vec![4, 5]);
+        let calldata = result.fulfill_callback_calldata(&request).unwrap();
+
+        assert_eq!(calldata[..4], id(FULFILL_CALLBACK_SIGNATURE));
+        let tokens = decode(
+            &[
+                ParamType::Uint(32),
+                ParamType::FixedBytes(32),
+                ParamType::FixedBytes(32),
+                ParamType::Address,
+                ParamType::FixedBytes(4),
+                ParamType::Uint(32),
+                ParamType::Bytes,
+                ParamType::Bytes,
+                ParamType::Bytes,
+            ],
+            &calldata[4..],
+        )
+        .unwrap();
+        assert_eq!(tokens[0], Token::Uint(U256::from(7)));
+        assert_eq!(tokens[3], Token::Address(request.callback_address));
+        assert_eq!(tokens[4], Token::FixedBytes(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(tokens[6], Token::Bytes(vec![1, 2, 3]));
+        assert_eq!(tokens[7], Token::Bytes(vec![4, 5]));
+        assert_eq!(tokens[8], Token::Bytes(vec![9; 64]));
+    }
+
+    #[test]
+    fn test_fulfill_call_calldata_rejects_elements_output() {
+        let request = CallRequest {
+            function_id: H256::zero(),
+            input: vec![],
+            callback_address: Address::zero(),
+            callback_data: vec![],
+        };
+        let output = PublicOutput::<L, D>::Elements(vec![]);
+        assert!(fulfill_call_calldata(&request, &output, &[]).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! A compact binary encoding of the function request and result envelopes.
+//!
+//! The JSON format of `ProofRequest` and `ProofResult` hex-encodes proofs and stringifies field
+//! elements, which roughly triples the size of every payload. The envelopes below carry the same
+//! information and are encoded with bincode.
+
+use anyhow::{anyhow, Result};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    BytesRequestData, ElementsRequestData, ProofRequest, ProofRequestBase, ProofResult,
+    RecursiveProofsRequestData, RemoteRecursiveProofsRequestData,
+};
+use crate::backend::circuit::{PlonkParameters, PublicInput, PublicOutput};
+
+/// The binary envelope of a `ProofRequest`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct ProofRequestEnvelope<L: PlonkParameters<D>, const D: usize> {
+    release_id: String,
+    parent_id: Option<String>,
+    files: Option<Vec<String>>,
+    circuit_id: Option<String>,
+    input: PublicInput<L, D>,
+}
+
+/// The binary envelope of a `ProofResult`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+enum ProofResultEnvelope<L: PlonkParameters<D>, const D: usize> {
+    /// A result whose proof is an opaque byte string, e.g. a wrapped proof.
+    Bytes { proof: Vec<u8>, output: Vec<u8> },
+    /// A result with a plonky2 proof.
+    Proof {
+        proof: ProofWithPublicInputs<L::Field, L::Config, D>,
+        output: PublicOutput<L, D>,
+    },
+}
+
+impl<L: PlonkParameters<D>, const D: usize> ProofRequest<L, D> {
+    /// Serializes the request into its compact binary representation.
+    pub fn to_bincode(&self) -> Result<Vec<u8>> {
+        let (base, circuit_id, input) = match self {
+            ProofRequest::Bytes(request) => (
+                request.without_data(),
+                None,
+                PublicInput::Bytes(request.data.input.clone()),
+            ),
+            ProofRequest::Elements(request) => (
+                request.without_data(),
+                Some(request.data.circuit_id.clone()),
+                PublicInput::Elements(request.data.input.clone()),
+            ),
+            ProofRequest::RecursiveProofs(request) => (
+                request.without_data(),
+                Some(request.data.circuit_id.clone()),
+                PublicInput::RecursiveProofs(request.data.proofs.clone(), vec![]),
+            ),
+            ProofRequest::RemoteRecursiveProofs(request) => (
+                request.without_data(),
+                Some(request.data.circuit_id.clone()),
+                PublicInput::RemoteRecursiveProofs(request.data.proof_ids.clone()),
+            ),
+        };
+        let envelope = ProofRequestEnvelope::<L, D> {
+            release_id: base.release_id,
+            parent_id: base.parent_id,
+            files: base.files,
+            circuit_id,
+            input,
+        };
+        Ok(bincode::serialize(&envelope)?)
+    }
+
+    /// Deserializes a request from its compact binary representation.
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self> {
+        let envelope: ProofRequestEnvelope<L, D> = bincode::deserialize(bytes)?;
+        let base = ProofRequestBase {
+            release_id: envelope.release_id,
+            parent_id: envelope.parent_id,
+            files: envelope.files,
+            data: (),
+        };
+        let circuit_id = envelope.circuit_id;
+        let circuit_id = || {
+            circuit_id.ok_or_else(|| anyhow!("proof request envelope is missing the circuit id"))
+        };
+        let request = match envelope.input {
+            PublicInput::Bytes(input) => {
+                ProofRequest::Bytes(base.with_data(BytesRequestData { input }))
+            }
+            PublicInput::Elements(input) => {
+                ProofRequest::Elements(base.with_data(ElementsRequestData {
+                    circuit_id: circuit_id()?,
+                    input,
+                }))
+            }
+            PublicInput::RecursiveProofs(proofs, _) => {
+                ProofRequest::RecursiveProofs(base.with_data(RecursiveProofsRequestData {
+                    circuit_id: circuit_id()?,
+                    proofs,
+                }))
+            }
+            PublicInput::RemoteRecursiveProofs(proof_ids) => ProofRequest::RemoteRecursiveProofs(
+                base.with_data(RemoteRecursiveProofsRequestData {
+                    circuit_id: circuit_id()?,
+                    proof_ids,
+                }),
+            ),
+            _ => return Err(anyhow!("unsupported proof request envelope input")),
+        };
+        Ok(request)
+    }
+}
+
+impl<D> ProofRequestBase<D> {
+    /// Returns the common fields of the request without its data.
+    fn without_data(&self) -> ProofRequestBase<()> {
+        ProofRequestBase {
+            release_id: self.release_id.clone(),
+            parent_id: self.parent_id.clone(),
+            files: self.files.clone(),
+            data: (),
+        }
+    }
+}
+
+impl ProofRequestBase<()> {
+    /// Attaches the given data to the common fields of a request.
+    fn with_data<D>(self, data: D) -> ProofRequestBase<D> {
+        ProofRequestBase {
+            release_id: self.release_id,
+            parent_id: self.parent_id,
+            files: self.files,
+            data,
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> ProofResult<L, D> {
+    /// Serializes the result into its compact binary representation.
+    pub fn to_bincode(&self) -> Result<Vec<u8>> {
+        let envelope = match self {
+            ProofResult::Bytes(result) => ProofResultEnvelope::<L, D>::Bytes {
+                proof: result.data.proof.clone(),
+                output: result.data.output.clone(),
+            },
+            _ => {
+                let (proof, output) = self.as_proof_and_output();
+                ProofResultEnvelope::Proof { proof, output }
+            }
+        };
+        Ok(bincode::serialize(&envelope)?)
+    }
+
+    /// Deserializes a result from its compact binary representation.
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self> {
+        let envelope: ProofResultEnvelope<L, D> = bincode::deserialize(bytes)?;
+        match envelope {
+            ProofResultEnvelope::Bytes { proof, output } => {
+                Ok(ProofResult::from_bytes(proof, output))
+            }
+            ProofResultEnvelope::Proof { proof, output } => match output {
+                PublicOutput::Elements(_) | PublicOutput::Proofs(_) => {
+                    Ok(ProofResult::from_proof_output(proof, output))
+                }
+                _ => Err(anyhow!("unsupported proof result envelope output")),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::circuit::DefaultParameters;
+    use crate::backend::prover::ProofId;
+    use crate::prelude::*;
+    use crate::utils::test_circuits::{add_input, build_add_circuit};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    fn base<T>(data: T) -> ProofRequestBase<T> {
+        ProofRequestBase {
+            release_id: "release".to_string(),
+            parent_id: Some("parent".to_string()),
+            files: Some(vec!["circuit.circuit".to_string()]),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_proof_request_bincode_round_trip() {
+        let circuit = build_add_circuit();
+        let (proof, _) = circuit.prove(&add_input(&circuit, 1, 2));
+
+        let requests = vec![
+            ProofRequest::<L, D>::Bytes(base(BytesRequestData {
+                input: vec![1, 2, 3],
+            })),
+            ProofRequest::Elements(base(ElementsRequestData {
+                circuit_id: circuit.id(),
+                input: vec![GoldilocksField::ONE, GoldilocksField::TWO],
+            })),
+            ProofRequest::RecursiveProofs(base(RecursiveProofsRequestData {
+                circuit_id: circuit.id(),
+                proofs: vec![proof.clone(), proof],
+            })),
+            ProofRequest::RemoteRecursiveProofs(base(RemoteRecursiveProofsRequestData {
+                circuit_id: circuit.id(),
+                proof_ids: vec![ProofId(Default::default())],
+            })),
+        ];
+
+        for request in requests {
+            let bytes = request.to_bincode().unwrap();
+            let decoded = ProofRequest::<L, D>::from_bincode(&bytes).unwrap();
+            assert_eq!(
+                serde_json::to_string(&request).unwrap(),
+                serde_json::to_string(&decoded).unwrap()
+            );
+            assert!(bytes.len() < serde_json::to_vec(&request).unwrap().len());
+        }
+    }
+
+    #[test]
+    fn test_proof_result_bincode_round_trip() {
+        let circuit = build_add_circuit();
+        let (proof, output) = circuit.prove(&add_input(&circuit, 1, 2));
+
+        let results = vec![
+            ProofResult::<L, D>::from_proof_output(proof, output),
+            ProofResult::from_bytes(vec![4, 5, 6], vec![7, 8]),
+        ];
+
+        for result in results {
+            let bytes = result.to_bincode().unwrap();
+            let decoded = ProofResult::<L, D>::from_bincode(&bytes).unwrap();
+            assert_eq!(
+                serde_json::to_string(&result).unwrap(),
+                serde_json::to_string(&decoded).unwrap()
+            );
+        }
+    }
+}
@@ -1,22 +1,26 @@
 pub mod args;
+pub mod calldata;
+pub mod envelope;
 pub mod request;
 pub mod result;
 
+use std::fs;
 use std::fs::File;
 use std::io::{BufReader, Write};
-use std::{fs, path};
 
 use clap::Parser;
 use log::info;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 pub use request::*;
 pub use result::*;
 use serde::Serialize;
 
 use self::args::{BuildArgs, ProveArgs};
 use crate::backend::circuit::*;
+use crate::backend::codegen::VerifierContract;
 use crate::backend::function::args::{Args, Commands};
-use crate::backend::wrapper::wrap::WrappedCircuit;
+use crate::backend::wrapper::gnark::GnarkWrapper;
+use crate::backend::wrapper::wrap::{WrappedCircuit, WrapperBackend};
 use crate::frontend::builder::CircuitIO;
 use crate::prelude::CircuitBuilder;
 
@@ -48,7 +52,8 @@ pub trait Plonky2xFunction {
     /// The entry point for the function when using the CLI.
     fn entrypoint();
 
-    /// Returns the verifier contract for the circuit.
+    /// Returns the verifier contract for the circuit, for proofs wrapped with the default PLONK
+    /// backend. `VerifierContract` generates the verifiers of the other backends.
     fn verifier(circuit_digest: &str, wrapper_path: &str) -> String;
 }
 
@@ -62,11 +67,22 @@ impl<C: Circuit> Plonky2xFunction for C {
     ) where
         <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
     {
-        // Build the circuit.
+        let mut generator_registry = HintRegistry::new();
+        let mut gate_registry = GateRegistry::new();
+        C::register_generators::<L, D>(&mut generator_registry);
+        C::register_gates::<L, D>(&mut gate_registry);
+
+        // Build the circuit, or load it from the build cache if its version has not changed.
         info!("Building circuit...");
-        let mut builder = CircuitBuilder::<L, D>::new();
-        C::define::<L, D>(&mut builder);
-        let circuit = builder.build();
+        let circuit = if args.no_cache {
+            let mut builder = CircuitBuilder::<L, D>::new();
+            C::define::<L, D>(&mut builder);
+            builder.build()
+        } else {
+            BuildCache::new(&args.build_dir)
+                .get_or_build::<C, L, D>(&gate_registry, &generator_registry)
+                .unwrap()
+        };
         info!("Successfully built circuit.");
         info!("> Circuit: {}", circuit.id());
         info!("> Degree: {}", circuit.data.common.degree());
@@ -74,10 +90,6 @@ impl<C: Circuit> Plonky2xFunction for C {
 
         // Serialize the circuit to disk.
         let path = format!("{}/main.circuit", args.build_dir);
-        let mut generator_registry = HintRegistry::new();
-        let mut gate_registry = GateRegistry::new();
-        C::register_generators::<L, D>(&mut generator_registry);
-        C::register_gates::<L, D>(&mut gate_registry);
         circuit.save(&path, &gate_registry, &generator_registry);
         info!("Successfully saved circuit to disk at {}.", path);
 
@@ -90,34 +102,14 @@ impl<C: Circuit> Plonky2xFunction for C {
             // The wrapper circuit digest will get saved in the Solidity smart contract, which will
             // use this value as a public input `VerifierDigest` in the gnark plonky2 verifier.
             info!("First building wrapper circuit to get the wrapper circuit digest...");
-            let wrapped_circuit = WrappedCircuit::<L, WrapperParameters, D>::build(circuit);
-
-            // to_bytes() returns the representation as LE, but we want to save it on-chain as BE
-            // because that is the format of the public input to the gnark plonky2 verifier.
-            let mut circuit_digest_bytes = wrapped_circuit
-                .wrapper_circuit
-                .data
-                .verifier_only
-                .circuit_digest
-                .to_bytes();
-            circuit_digest_bytes.reverse();
-
-            // The VerifierDigest is stored onchain as a bytes32, so we need to pad it with 0s
-            // to store it in the solidity smart contract.
-            //
-            // Note that we don't need to do any sort of truncation of the most significant bits
-            // because the circuit digest already lives in the bn254 field because the prover config
-            // uses the Poseidon bn254 hasher.
-            //
-            // In the solidity smart contract we should not truncate the 3 most significant bits
-            // like we do with input_hash and output_hash as the circuit digest has a small
-            // probability of being greater than 2^253 given that the field modulus is 254 bits.
-            let mut padded = vec![0u8; 32];
-            let digest_len = circuit_digest_bytes.len();
-            padded[(32 - digest_len)..].copy_from_slice(&circuit_digest_bytes);
-            let circuit_digest = format!("0x{}", hex::encode(padded));
+            let wrapped_circuit = WrappedCircuit::<L, WrapperParameters, D>::build_with_backend(
+                circuit,
+                args.wrapper_backend,
+            );
 
-            let verifier_contract = Self::verifier(&circuit_digest, &args.wrapper_path);
+            let verifier_contract = VerifierContract::new(&wrapped_circuit)
+                .generate_from_wrapper_path(&args.wrapper_path)
+                .expect("Failed to generate verifier contract");
             contract_file
                 .write_all(verifier_contract.as_bytes())
                 .unwrap();
@@ -141,26 +133,13 @@ impl<C: Circuit> Plonky2xFunction for C {
         OuterParameters::Config: Serialize,
     {
         // If the request is of type bytes and the wrapper path is not empty, then we need to
-        // start the gnark wrapper process.
-        let gnark_wrapper_process = if let ProofRequest::Bytes(_) = request {
-            if !args.wrapper_path.is_empty() {
-                let child_process = std::process::Command::new(
-                    path::Path::new(&args.wrapper_path).join("verifier"),
-                )
-                .arg("-prove")
-                .arg("-data")
-                .arg(path::Path::new(&args.wrapper_path))
-                .stdout(std::process::Stdio::inherit())
-                .stderr(std::process::Stdio::inherit())
-                .stdin(std::process::Stdio::piped())
-                .spawn()
-                .expect("Failed to start gnark wrapper process");
-                Some(child_process)
-            } else {
-                None
-            }
-        } else {
-            None
+        // start loading the gnark wrapper.
+        let gnark_wrapper = match request {
+            ProofRequest::Bytes(_) if !args.wrapper_path.is_empty() => Some(
+                GnarkWrapper::load(&args.wrapper_path, args.wrapper_backend)
+                    .expect("Failed to start gnark wrapper"),
+            ),
+            _ => None,
         };
 
         let mut generator_registry = HintRegistry::new();
@@ -191,7 +170,10 @@ impl<C: Circuit> Plonky2xFunction for C {
         info!("Successfully loaded circuit.");
 
         let input = request.input();
-        let (proof, output) = circuit.prove(&input);
+        let (proof, output) = match JsonLinesAuditSink::from_env() {
+            Some(sink) => circuit.prove_with_audit(&input, &sink),
+            None => circuit.prove(&input),
+        };
         info!(
             "Successfully generated proof, wrapping proof with {}",
             args.wrapper_path
@@ -206,40 +188,20 @@ impl<C: Circuit> Plonky2xFunction for C {
             // choose to rebuild here instead of loading from disk.
             info!("Output Bytes: 0x{}", hex::encode(output_bytes.clone()));
             let wrapped_circuit =
-                WrappedCircuit::<InnerParameters, OuterParameters, D>::build(circuit);
+                WrappedCircuit::<InnerParameters, OuterParameters, D>::build_with_backend(
+                    circuit,
+                    args.wrapper_backend,
+                );
             let wrapped_proof = wrapped_circuit.prove(&proof).expect("failed to wrap proof");
             wrapped_proof
                 .save("wrapped")
                 .expect("failed to save wrapped proof");
 
-            // The gnark_wrapper_process should have been started.
-            let mut gnark_wrapper_process = gnark_wrapper_process.unwrap();
-            let mut stdin_opt = None;
-            while stdin_opt.is_none() {
-                stdin_opt = match gnark_wrapper_process.stdin.as_mut() {
-                    Some(stdin) => {
-                        info!("Got stdin of child process");
-                        Some(stdin)
-                    }
-                    None => {
-                        info!("Failed to open stdin of gnark wrapper. Retrying...");
-                        std::thread::sleep(std::time::Duration::from_secs(1));
-                        None
-                    }
-                };
-            }
-
-            let stdin = stdin_opt.unwrap();
-            stdin
-                .write_all(b"wrapped\n")
-                .expect("Failed to write to stdin");
-            let verifier_output = gnark_wrapper_process
-                .wait_with_output()
-                .expect("failed to execute process");
-
-            if !verifier_output.status.success() {
-                panic!("verifier failed");
-            }
+            // The gnark_wrapper should have been started.
+            gnark_wrapper
+                .unwrap()
+                .prove("wrapped")
+                .expect("gnark wrapper failed");
 
             // Read result from gnark verifier.
             let file = std::fs::File::open("proof.json").unwrap();
@@ -285,39 +247,11 @@ impl<C: Circuit> Plonky2xFunction for C {
     }
 
     fn verifier(circuit_digest: &str, wrapper_path: &str) -> String {
-        let wrapper_verifier_path = format!("{}/Verifier.sol", wrapper_path);
-        let wrapper_verifier_contract = fs::read_to_string(wrapper_verifier_path)
-            .expect("Failed to read wrapper_verifier_path");
-        let generated_contract = wrapper_verifier_contract
-            .replace("pragma solidity ^0.8.19;", "pragma solidity ^0.8.16;")
-            .replace("function Verify", "function verifyProof");
-
-        let verifier_contract = "
-
-interface IFunctionVerifier {
-    function verify(bytes32 _inputHash, bytes32 _outputHash, bytes memory _proof) external view returns (bool);
-
-    function verificationKeyHash() external pure returns (bytes32);
-}
-
-contract FunctionVerifier is IFunctionVerifier, PlonkVerifier {
-
-    bytes32 public constant CIRCUIT_DIGEST = {CIRCUIT_DIGEST};
-
-    function verify(bytes32 _inputHash, bytes32 _outputHash, bytes memory _proof) external view returns (bool) {
-        uint256[] memory input = new uint256[](3);
-        input[0] = uint256(CIRCUIT_DIGEST);
-        input[1] = uint256(_inputHash) & ((1 << 253) - 1);
-        input[2] = uint256(_outputHash) & ((1 << 253) - 1); 
-
-        return this.verifyProof(_proof, input);
-    }
-
-    function verificationKeyHash() external pure returns (bytes32) {
-        return CIRCUIT_DIGEST;
-    }
-}
-".replace("{CIRCUIT_DIGEST}", circuit_digest);
-        generated_contract + &verifier_contract
+        VerifierContract {
+            circuit_digest: circuit_digest.to_string(),
+            backend: WrapperBackend::default(),
+        }
+        .generate_from_wrapper_path(wrapper_path)
+        .expect("Failed to read wrapper_verifier_path")
     }
 }
@@ -1,8 +1,10 @@
 use core::fmt::Debug;
 use std::env;
 
+use anyhow::{anyhow, ensure, Result};
 use plonky2::plonk::proof::ProofWithPublicInputs;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::backend::circuit::{PlonkParameters, PublicInput};
 use crate::backend::prover::ProofId;
@@ -11,6 +13,9 @@ use crate::utils::serde::{
     serialize_hex, serialize_proof_with_pis_vec,
 };
 
+/// The latest version of the gateway request format understood by `ProofRequest::decode`.
+pub const PROOF_REQUEST_VERSION: u64 = 3;
+
 /// Fields for a function request that uses bytes io.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BytesRequestData {
@@ -57,6 +62,29 @@ pub struct ProofRequestBase<D> {
     pub data: D,
 }
 
+impl<T> ProofRequestBase<T> {
+    fn with_data<U>(self, data: U) -> ProofRequestBase<U> {
+        ProofRequestBase {
+            release_id: self.release_id,
+            parent_id: self.parent_id,
+            files: self.files,
+            data,
+        }
+    }
+}
+
+/// A request in version 3 of the gateway request format, whose input is encoded with
+/// `PublicInput::to_json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProofRequestV3 {
+    release_id: String,
+    parent_id: Option<String>,
+    files: Option<Vec<String>>,
+    circuit_id: Option<String>,
+    input: Value,
+}
+
 /// The standard request format for running "functions".
 ///
 /// Note that this is a standard enforced by the remote provers. Locally, you can just use
@@ -75,61 +103,141 @@ pub enum ProofRequest<L: PlonkParameters<D>, const D: usize> {
     RemoteRecursiveProofs(ProofRequestBase<RemoteRecursiveProofsRequestData>),
 }
 
+/// Extracts the request wrapped by a versioned request.
+fn request_field(mut value: Value, version: u64) -> Result<Value> {
+    value
+        .get_mut("request")
+        .map(Value::take)
+        .ok_or_else(|| anyhow!("proof request v{} is missing the `request` field", version))
+}
+
 impl<L: PlonkParameters<D>, const D: usize> ProofRequest<L, D> {
     /// Creates a new function request from a circuit and public input.
     pub fn new(circuit_id: &str, input: &PublicInput<L, D>) -> Self {
-        let release_id = env::var("RELEASE_ID").unwrap();
-        let parent_id = Some(env::var("PROOF_ID").unwrap());
-        match input {
-            PublicInput::Bytes(input) => ProofRequest::Bytes(ProofRequestBase {
-                release_id,
-                parent_id,
-                files: Some(vec![format!("main.circuit")]),
-                data: BytesRequestData {
-                    input: input.clone(),
-                },
-            }),
-            PublicInput::Elements(input) => ProofRequest::Elements(ProofRequestBase {
-                release_id,
-                parent_id,
-                files: Some(vec![format!("{}.circuit", circuit_id)]),
-                data: ElementsRequestData {
-                    circuit_id: circuit_id.to_string(),
+        Self::try_new(circuit_id, input).unwrap()
+    }
+
+    /// Creates a new function request from a circuit and public input. Fails if the `RELEASE_ID`
+    /// or `PROOF_ID` environment variables are not set, or if the input cannot be proven remotely.
+    pub fn try_new(circuit_id: &str, input: &PublicInput<L, D>) -> Result<Self> {
+        let env_var = |name: &str| env::var(name).map_err(|_| anyhow!("{} is not set", name));
+        let file = match input {
+            PublicInput::Bytes(_) => "main.circuit".to_string(),
+            _ => format!("{}.circuit", circuit_id),
+        };
+        let base = ProofRequestBase {
+            release_id: env_var("RELEASE_ID")?,
+            parent_id: Some(env_var("PROOF_ID")?),
+            files: Some(vec![file]),
+            data: (),
+        };
+        Self::from_input(base, circuit_id, input)
+    }
+
+    /// Creates the request of the given kind of input, with the common fields of `base`.
+    fn from_input(
+        base: ProofRequestBase<()>,
+        circuit_id: &str,
+        input: &PublicInput<L, D>,
+    ) -> Result<Self> {
+        let circuit_id = circuit_id.to_string();
+        let request = match input {
+            PublicInput::Bytes(input) => ProofRequest::Bytes(base.with_data(BytesRequestData {
+                input: input.clone(),
+            })),
+            PublicInput::Elements(input) => {
+                ProofRequest::Elements(base.with_data(ElementsRequestData {
+                    circuit_id,
                     input: input.clone(),
-                },
-            }),
+                }))
+            }
             PublicInput::RecursiveProofs(input, _) => {
-                ProofRequest::RecursiveProofs(ProofRequestBase {
-                    release_id,
-                    parent_id,
-                    files: Some(vec![format!("{}.circuit", circuit_id)]),
-                    data: RecursiveProofsRequestData {
-                        circuit_id: circuit_id.to_string(),
-                        proofs: input.clone(),
-                    },
-                })
+                ProofRequest::RecursiveProofs(base.with_data(RecursiveProofsRequestData {
+                    circuit_id,
+                    proofs: input.clone(),
+                }))
             }
-            PublicInput::RemoteRecursiveProofs(input) => {
-                ProofRequest::RemoteRecursiveProofs(ProofRequestBase {
-                    release_id,
-                    parent_id,
-                    files: Some(vec![format!("{}.circuit", circuit_id)]),
-                    data: RemoteRecursiveProofsRequestData {
-                        circuit_id: circuit_id.to_string(),
-                        proof_ids: input.clone(),
-                    },
-                })
+            PublicInput::RemoteRecursiveProofs(input) => ProofRequest::RemoteRecursiveProofs(
+                base.with_data(RemoteRecursiveProofsRequestData {
+                    circuit_id,
+                    proof_ids: input.clone(),
+                }),
+            ),
+            PublicInput::CyclicProof(_, _, _) | PublicInput::None() => {
+                return Err(anyhow!("unsupported input for a proof request"))
             }
-            PublicInput::CyclicProof(_, _, _) => todo!(),
-            PublicInput::None() => todo!(),
-        }
+        };
+        Ok(request)
     }
 
     /// Loads a function request from a file.
     pub fn load(path: &String) -> Self {
-        let file = std::fs::File::open(path).unwrap();
-        let rdr = std::io::BufReader::new(file);
-        serde_json::from_reader(rdr).unwrap()
+        let json = std::fs::read_to_string(path).unwrap();
+        Self::decode(&json).unwrap()
+    }
+
+    /// Decodes a function request from any supported version of the gateway request format.
+    ///
+    /// - Version 1 is the bare request object, optionally with `"version": 1`.
+    /// - Version 2 wraps the request as `{ "version": 2, "request": { ... } }`.
+    /// - Version 3 wraps the request like version 2, with the fields `releaseId`, `parentId`,
+    ///   `files`, `circuitId` and the canonical JSON encoding of the public input as `input`.
+    pub fn decode(json: &str) -> Result<Self> {
+        Self::decode_value(serde_json::from_str(json)?)
+    }
+
+    /// Decodes a function request from a parsed JSON value, like `decode`.
+    pub fn decode_value(value: Value) -> Result<Self> {
+        let version = match value.get("version") {
+            None => 1,
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| anyhow!("invalid proof request version: {}", version))?,
+        };
+        match version {
+            1 => {
+                let mut value = value;
+                if let Some(object) = value.as_object_mut() {
+                    object.remove("version");
+                }
+                Ok(serde_json::from_value(value)?)
+            }
+            2 => Ok(serde_json::from_value(request_field(value, version)?)?),
+            3 => {
+                let request: ProofRequestV3 =
+                    serde_json::from_value(request_field(value, version)?)?;
+                let input = PublicInput::from_json(&request.input.to_string())?;
+                let circuit_id = request.circuit_id.unwrap_or_default();
+                ensure!(
+                    !circuit_id.is_empty() || matches!(input, PublicInput::Bytes(_)),
+                    "proof request v3 is missing the `circuitId` field"
+                );
+                let base = ProofRequestBase {
+                    release_id: request.release_id,
+                    parent_id: request.parent_id,
+                    files: request.files,
+                    data: (),
+                };
+                Self::from_input(base, &circuit_id, &input)
+            }
+            _ => Err(anyhow!(
+                "unsupported proof request version {}: this binary supports versions 1 to {}",
+                version,
+                PROOF_REQUEST_VERSION
+            )),
+        }
+    }
+
+    /// Encodes the function request in version 1 of the gateway request format, the bare request
+    /// object, which is the format accepted by the proof service. The later versions are only
+    /// decoded.
+    pub fn encode(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Encodes the function request like `encode`, as a JSON value.
+    pub fn encode_value(&self) -> Result<Value> {
+        Ok(serde_json::to_value(self)?)
     }
 
     /// Gets the public input from the function request.
@@ -144,7 +252,112 @@ impl<L: PlonkParameters<D>, const D: usize> ProofRequest<L, D> {
             ProofRequest::RecursiveProofs(ProofRequestBase { data, .. }) => {
                 PublicInput::RecursiveProofs(data.proofs.clone(), vec![])
             }
-            _ => panic!("invalid proof request type"),
+            ProofRequest::RemoteRecursiveProofs(ProofRequestBase { data, .. }) => {
+                PublicInput::RemoteRecursiveProofs(data.proof_ids.clone())
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::backend::circuit::DefaultParameters;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    const REQUEST: &str = r#"{
+        "type": "req_bytes",
+        "releaseId": "release",
+        "parentId": null,
+        "files": ["main.circuit"],
+        "data": { "input": "0x0102" }
+    }"#;
+
+    #[test]
+    fn test_decode_proof_request_versions() {
+        let v1 = ProofRequest::<L, D>::decode(REQUEST).unwrap();
+        let PublicInput::Bytes(input) = v1.input() else {
+            panic!("expected a bytes request");
+        };
+        assert_eq!(input, vec![1, 2]);
+
+        let v1_explicit = REQUEST.replacen('{', r#"{ "version": 1,"#, 1);
+        assert!(ProofRequest::<L, D>::decode(&v1_explicit).is_ok());
+
+        let v2 = format!(r#"{{ "version": 2, "request": {} }}"#, REQUEST);
+        let decoded = ProofRequest::<L, D>::decode(&v2).unwrap();
+        assert_eq!(
+            serde_json::to_string(&decoded).unwrap(),
+            serde_json::to_string(&v1).unwrap()
+        );
+
+        // Requests are encoded as the bare version 1, which decodes back to the same request.
+        let encoded = v1.encode().unwrap();
+        assert!(serde_json::from_str::<Value>(&encoded)
+            .unwrap()
+            .get("version")
+            .is_none());
+        let decoded = ProofRequest::<L, D>::decode(&encoded).unwrap();
+        assert_eq!(
+            serde_json::to_string(&decoded).unwrap(),
+            serde_json::to_string(&v1).unwrap()
+        );
+
+        let v4 = format!(r#"{{ "version": 4, "request": {} }}"#, REQUEST);
+        let err = ProofRequest::<L, D>::decode(&v4).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unsupported proof request version 4"));
+    }
+
+    #[test]
+    fn test_proof_request_canonical_input() {
+        let v3 = r#"{
+            "version": 3,
+            "request": {
+                "releaseId": "release",
+                "parentId": null,
+                "files": ["circuit.circuit"],
+                "circuitId": "circuit",
+                "input": { "version": 1, "input": { "type": "elements", "input": ["1", "2"] } }
+            }
+        }"#;
+        let request = ProofRequest::<L, D>::decode(v3).unwrap();
+        let ProofRequest::Elements(ProofRequestBase { data, .. }) = &request else {
+            panic!("expected an elements request");
+        };
+        assert_eq!(data.circuit_id, "circuit");
+        assert_eq!(
+            request.input(),
+            PublicInput::Elements(vec![GoldilocksField::ONE, GoldilocksField::TWO])
+        );
+
+        // The request is sent as the bare version 1 with the same input.
+        let encoded = request.encode_value().unwrap();
+        assert_eq!(encoded["type"], "req_elements");
+        assert_eq!(encoded["data"]["circuitId"], "circuit");
+        let decoded = ProofRequest::<L, D>::decode_value(encoded).unwrap();
+        assert_eq!(decoded.input(), request.input());
+
+        let remote = ProofRequest::<L, D>::RemoteRecursiveProofs(ProofRequestBase {
+            release_id: "release".to_string(),
+            parent_id: None,
+            files: None,
+            data: RemoteRecursiveProofsRequestData {
+                circuit_id: "circuit".to_string(),
+                proof_ids: vec![ProofId(Uuid::from_u128(7))],
+            },
+        });
+        let decoded = ProofRequest::<L, D>::decode(&remote.encode().unwrap()).unwrap();
+        assert_eq!(decoded.input(), remote.input());
+
+        let missing_circuit = v3.replacen(r#""circuitId": "circuit","#, "", 1);
+        assert!(ProofRequest::<L, D>::decode(&missing_circuit).is_err());
+    }
+}
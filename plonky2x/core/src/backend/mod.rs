@@ -1,7 +1,11 @@
 pub mod circuit;
 
+pub mod codegen;
+
 pub mod function;
 
 pub mod wrapper;
 
+pub mod pipeline;
+
 pub mod prover;
@@ -0,0 +1,281 @@
+//! Declarative composition of circuits into proving pipelines.
+//!
+//! A pipeline is a sequence of stages in which every stage after the first verifies the proof of
+//! the previous stage, optionally followed by the wrapper. The pipeline builds each stage against
+//! the circuit of the previous one, routes the proof of each stage into the witness of the next
+//! and proves the stages in order.
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
+
+use super::circuit::{CircuitBuild, PlonkParameters, PublicInput, PublicOutput};
+use super::wrapper::wrap::{WrappedCircuit, WrappedOutput};
+use crate::frontend::builder::CircuitBuilder;
+
+/// A stage of a pipeline.
+#[derive(Debug)]
+pub struct PipelineStage<L: PlonkParameters<D>, const D: usize> {
+    pub name: String,
+    pub circuit: CircuitBuild<L, D>,
+    /// The proof of the previous stage, verified by this stage.
+    previous_proof: Option<ProofWithPublicInputsTarget<D>>,
+}
+
+/// The proofs and outputs of every stage of a pipeline, in order.
+#[derive(Debug)]
+pub struct PipelineOutput<L: PlonkParameters<D>, const D: usize> {
+    pub proofs: Vec<ProofWithPublicInputs<L::Field, L::Config, D>>,
+    pub outputs: Vec<PublicOutput<L, D>>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> PipelineOutput<L, D> {
+    fn new() -> Self {
+        Self {
+            proofs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// The proof of the last stage.
+    pub fn proof(&self) -> &ProofWithPublicInputs<L::Field, L::Config, D> {
+        self.proofs.last().unwrap()
+    }
+
+    /// The output of the last stage.
+    pub fn output(&self) -> &PublicOutput<L, D> {
+        self.outputs.last().unwrap()
+    }
+}
+
+/// A sequence of circuits, each verifying the proof of the previous one.
+///
+/// ```ignore
+/// let pipeline = Pipeline::<L, D>::new("fetch", |builder| { ... })
+///     .then("aggregate", |builder, fetch_proof| { ... });
+/// let output = pipeline.prove(&inputs)?;
+/// ```
+#[derive(Debug)]
+pub struct Pipeline<L: PlonkParameters<D>, const D: usize> {
+    stages: Vec<PipelineStage<L, D>>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Pipeline<L, D>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    /// Creates a pipeline whose first stage is defined by `define`.
+    pub fn new<F>(name: &str, define: F) -> Self
+    where
+        F: FnOnce(&mut CircuitBuilder<L, D>),
+    {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        define(&mut builder);
+        let stage = PipelineStage {
+            name: name.to_string(),
+            circuit: builder.build(),
+            previous_proof: None,
+        };
+        debug!("built pipeline stage {}: id={}", name, stage.circuit.id());
+        Self {
+            stages: vec![stage],
+        }
+    }
+
+    /// Appends a stage defined by `define`. The builder passed to `define` already verifies the
+    /// proof of the previous stage, whose public inputs can be read from the given target.
+    pub fn then<F>(mut self, name: &str, define: F) -> Self
+    where
+        F: FnOnce(&mut CircuitBuilder<L, D>, &ProofWithPublicInputsTarget<D>),
+    {
+        let previous = &self.stages.last().unwrap().circuit;
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let verifier_data = builder.constant_verifier_data::<L>(&previous.data);
+        let proof = builder.add_virtual_proof_with_pis(&previous.data.common);
+        builder.verify_proof::<L>(&proof, &verifier_data, &previous.data.common);
+        define(&mut builder, &proof);
+
+        let stage = PipelineStage {
+            name: name.to_string(),
+            circuit: builder.build(),
+            previous_proof: Some(proof),
+        };
+        debug!("built pipeline stage {}: id={}", name, stage.circuit.id());
+        self.stages.push(stage);
+        self
+    }
+
+    /// The stages of the pipeline, in order.
+    pub fn stages(&self) -> &[PipelineStage<L, D>] {
+        &self.stages
+    }
+
+    /// Returns an empty input for every stage of the pipeline. Stages that only consume the proof
+    /// of the previous stage can be left empty.
+    pub fn inputs(&self) -> Vec<PublicInput<L, D>> {
+        self.stages.iter().map(|s| s.circuit.input()).collect()
+    }
+
+    /// Proves every stage in order, feeding the proof of each stage to the next.
+    pub fn prove(&self, inputs: &[PublicInput<L, D>]) -> Result<PipelineOutput<L, D>> {
+        if inputs.len() != self.stages.len() {
+            return Err(anyhow!(
+                "expected {} stage inputs, got {}",
+                self.stages.len(),
+                inputs.len()
+            ));
+        }
+        let mut output = PipelineOutput::new();
+        for (stage, input) in self.stages.iter().zip(inputs) {
+            prove_stage(
+                &stage.name,
+                &stage.circuit,
+                &stage.previous_proof,
+                input,
+                &mut output,
+            )?;
+        }
+        Ok(output)
+    }
+
+    /// Wraps the last stage of the pipeline, so that its proofs can be verified on-chain. The last
+    /// stage must use bytes io.
+    pub fn wrap<OuterParameters: PlonkParameters<D, Field = L::Field>>(
+        mut self,
+    ) -> WrappedPipeline<L, OuterParameters, D> {
+        let last = self.stages.pop().unwrap();
+        let wrapper = WrappedCircuit::<L, OuterParameters, D>::build(last.circuit);
+        WrappedPipeline {
+            pipeline: self,
+            name: last.name,
+            previous_proof: last.previous_proof,
+            wrapper,
+        }
+    }
+}
+
+/// A pipeline whose last stage is wrapped.
+#[derive(Debug)]
+pub struct WrappedPipeline<
+    L: PlonkParameters<D>,
+    OuterParameters: PlonkParameters<D, Field = L::Field>,
+    const D: usize,
+> where
+    <L::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+{
+    /// The stages before the last one.
+    pipeline: Pipeline<L, D>,
+    name: String,
+    previous_proof: Option<ProofWithPublicInputsTarget<D>>,
+    pub wrapper: WrappedCircuit<L, OuterParameters, D>,
+}
+
+impl<
+        L: PlonkParameters<D>,
+        OuterParameters: PlonkParameters<D, Field = L::Field>,
+        const D: usize,
+    > WrappedPipeline<L, OuterParameters, D>
+where
+    <L::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+{
+    /// Returns an empty input for every stage of the pipeline, including the wrapped one.
+    pub fn inputs(&self) -> Vec<PublicInput<L, D>> {
+        let mut inputs = self.pipeline.inputs();
+        inputs.push(self.wrapper.circuit().input());
+        inputs
+    }
+
+    /// Proves every stage in order and wraps the proof of the last stage.
+    pub fn prove(
+        &self,
+        inputs: &[PublicInput<L, D>],
+    ) -> Result<(PipelineOutput<L, D>, WrappedOutput<OuterParameters, D>)> {
+        let (last_input, inputs) = inputs
+            .split_last()
+            .ok_or_else(|| anyhow!("missing the input of the wrapped stage"))?;
+        let mut output = self.pipeline.prove(inputs)?;
+        prove_stage(
+            &self.name,
+            self.wrapper.circuit(),
+            &self.previous_proof,
+            last_input,
+            &mut output,
+        )?;
+        debug!("wrapping pipeline stage {}", self.name);
+        let wrapped_output = self.wrapper.prove(output.proof())?;
+        Ok((output, wrapped_output))
+    }
+}
+
+/// Proves a stage, using the last proof of `output` as the proof of the previous stage, and
+/// appends the result to `output`.
+fn prove_stage<L: PlonkParameters<D>, const D: usize>(
+    name: &str,
+    circuit: &CircuitBuild<L, D>,
+    previous_proof: &Option<ProofWithPublicInputsTarget<D>>,
+    input: &PublicInput<L, D>,
+    output: &mut PipelineOutput<L, D>,
+) -> Result<()>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    debug!("proving pipeline stage {}: id={}", name, circuit.id());
+    let mut pw = PartialWitness::new();
+    circuit.io.set_witness(&mut pw, input);
+    if let Some(target) = previous_proof {
+        pw.set_proof_with_pis_target(target, output.proof());
+    }
+    let (proof, stage_output) = circuit.prove_with_partial_witness(pw);
+    circuit.data.verify(proof.clone())?;
+    output.proofs.push(proof);
+    output.outputs.push(stage_output);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::circuit::DefaultParameters;
+    use crate::prelude::*;
+    use crate::utils::proof::ProofWithPublicInputsTargetUtils;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    #[test]
+    fn test_pipeline() {
+        let pipeline = Pipeline::<L, D>::new("increment", |builder| {
+            let x = builder.read::<U64Variable>();
+            let one = builder.constant::<U64Variable>(1);
+            let y = builder.add(x, one);
+            builder.write(y);
+        })
+        .then("scale", |builder, proof| {
+            let y = proof.read_end_from_pis::<U64Variable>();
+            let factor = builder.read::<U64Variable>();
+            let z = builder.mul(y, factor);
+            builder.write(z);
+        });
+        assert_eq!(pipeline.stages().len(), 2);
+
+        let mut inputs = pipeline.inputs();
+        inputs[0].write::<U64Variable>(5);
+        inputs[1].write::<U64Variable>(3);
+        let mut output = pipeline.prove(&inputs).unwrap();
+
+        for (i, stage) in pipeline.stages().iter().enumerate() {
+            stage
+                .circuit
+                .verify(&output.proofs[i], &inputs[i], &output.outputs[i]);
+        }
+        assert_eq!(output.outputs[0].read::<U64Variable>(), 6);
+        assert_eq!(output.outputs[1].read::<U64Variable>(), 18);
+
+        // Stage inputs must match the stages.
+        assert!(pipeline.prove(&inputs[..1]).is_err());
+    }
+}
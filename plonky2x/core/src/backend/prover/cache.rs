@@ -0,0 +1,228 @@
+//! A content-addressed cache of proofs.
+//!
+//! A proof is keyed by the digest of its circuit and of its input, so requesting a proof for an
+//! input that was already proven returns the cached proof without proving it again. The proofs are
+//! stored by a `ProofCacheBackend`, such as a folder on disk or a shared key-value store, and the
+//! cache is enabled for `LocalProver` and `RemoteProver` by setting `PROOF_CACHE_DIR`.
+
+use core::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{env, fs};
+
+use anyhow::Result;
+use log::{debug, warn};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use sha2::{Digest, Sha256};
+
+use crate::backend::circuit::{PlonkParameters, PublicInput, PublicOutput};
+use crate::utils::hex;
+
+/// A store of the cached proofs, as opaque bytes under their keys.
+pub trait ProofCacheBackend: Debug + Send + Sync {
+    /// Returns the bytes stored under `key`, if any.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Stores `value` under `key`, replacing any previous value.
+    fn put(&self, key: &str, value: &[u8]) -> Result<()>;
+}
+
+/// A backend storing each proof in the file `{dir}/{key}.proof`.
+#[derive(Debug, Clone)]
+pub struct DiskProofCacheBackend {
+    dir: PathBuf,
+}
+
+impl DiskProofCacheBackend {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.proof", key))
+    }
+}
+
+impl ProofCacheBackend for DiskProofCacheBackend {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        // Write to a temporary file first so that concurrent readers never see a partial proof.
+        let tmp_path = self.dir.join(format!("{}.proof.tmp", key));
+        fs::write(&tmp_path, value)?;
+        fs::rename(tmp_path, self.path(key))?;
+        Ok(())
+    }
+}
+
+/// A cache of proofs keyed by `(circuit digest, input digest)`.
+#[derive(Debug, Clone)]
+pub struct ProofCache {
+    backend: Arc<dyn ProofCacheBackend>,
+}
+
+impl ProofCache {
+    pub fn new<B: ProofCacheBackend + 'static>(backend: B) -> Self {
+        Self {
+            backend: Arc::new(backend),
+        }
+    }
+
+    /// Creates a cache on disk in the folder given by `PROOF_CACHE_DIR`. Returns `None` if the
+    /// cache is not configured.
+    pub fn from_env() -> Option<Self> {
+        env::var("PROOF_CACHE_DIR")
+            .ok()
+            .map(|dir| Self::new(DiskProofCacheBackend::new(dir)))
+    }
+
+    /// The key of the proof of the circuit `circuit_id` over `input`. The id of a circuit is
+    /// already the digest of the circuit.
+    pub fn key<L: PlonkParameters<D>, const D: usize>(
+        circuit_id: &str,
+        input: &PublicInput<L, D>,
+    ) -> Result<String> {
+        let mut hasher = Sha256::new();
+        hasher.update((circuit_id.len() as u64).to_le_bytes());
+        hasher.update(circuit_id.as_bytes());
+        hasher.update(bincode::serialize(input)?);
+        Ok(hex!(hasher.finalize().to_vec()))
+    }
+
+    /// Returns the cached proof of the circuit `circuit_id` over `input`, if any.
+    #[allow(clippy::type_complexity)]
+    pub fn get<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        circuit_id: &str,
+        input: &PublicInput<L, D>,
+    ) -> Result<
+        Option<(
+            ProofWithPublicInputs<L::Field, L::Config, D>,
+            PublicOutput<L, D>,
+        )>,
+    > {
+        let key = Self::key(circuit_id, input)?;
+        let Some(bytes) = self.backend.get(&key)? else {
+            return Ok(None);
+        };
+        debug!("proof cache hit: circuit_id={}, key={}", circuit_id, key);
+        Ok(Some(bincode::deserialize(&bytes)?))
+    }
+
+    /// Caches the proof of the circuit `circuit_id` over `input`.
+    pub fn put<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        circuit_id: &str,
+        input: &PublicInput<L, D>,
+        proof: &ProofWithPublicInputs<L::Field, L::Config, D>,
+        output: &PublicOutput<L, D>,
+    ) -> Result<()> {
+        let key = Self::key(circuit_id, input)?;
+        self.backend
+            .put(&key, &bincode::serialize(&(proof, output))?)
+    }
+
+    /// Like `get`, but a failure to read the cache is logged and treated as a miss so that it
+    /// never prevents proving.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn lookup<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        circuit_id: &str,
+        input: &PublicInput<L, D>,
+    ) -> Option<(
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )> {
+        self.get(circuit_id, input).unwrap_or_else(|e| {
+            warn!(
+                "failed to read proof cache: circuit_id={}: {:?}",
+                circuit_id, e
+            );
+            None
+        })
+    }
+
+    /// Like `put`, but a failure to write the cache is only logged.
+    pub(crate) fn store<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        circuit_id: &str,
+        input: &PublicInput<L, D>,
+        proof: &ProofWithPublicInputs<L::Field, L::Config, D>,
+        output: &PublicOutput<L, D>,
+    ) {
+        if let Err(e) = self.put(circuit_id, input, proof, output) {
+            warn!(
+                "failed to write proof cache: circuit_id={}: {:?}",
+                circuit_id, e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::prover::LocalProver;
+    use crate::prelude::*;
+    use crate::utils::test_circuits::{add_input, build_add_circuit, build_xor_lookup_circuit};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    #[test]
+    fn test_proof_cache() {
+        let dir = env::temp_dir().join("plonky2x-proof-cache");
+        let _ = fs::remove_dir_all(&dir);
+        let cache = ProofCache::new(DiskProofCacheBackend::new(&dir));
+
+        // Inputs with the same output and circuits with the same input have different keys.
+        let circuit = build_add_circuit();
+        let input = add_input(&circuit, 1, 2);
+        let other_input = add_input(&circuit, 2, 1);
+        let lookup_circuit = build_xor_lookup_circuit();
+        assert_ne!(
+            ProofCache::key(&circuit.id(), &input).unwrap(),
+            ProofCache::key(&circuit.id(), &other_input).unwrap()
+        );
+        assert_ne!(
+            ProofCache::key(&circuit.id(), &input).unwrap(),
+            ProofCache::key(&lookup_circuit.id(), &input).unwrap()
+        );
+
+        // The first request proves the input and caches the proof, which the second one returns.
+        let prover = LocalProver::with_cache(cache.clone());
+        assert!(cache.get(&circuit.id(), &input).unwrap().is_none());
+        let (proof, output) = prover
+            .prove(&circuit, &input)
+            .unwrap()
+            .materialize()
+            .unwrap();
+        let (cached_proof, mut cached_output) = cache.get(&circuit.id(), &input).unwrap().unwrap();
+        assert_eq!(cached_proof, proof);
+        assert_eq!(cached_output, output);
+        circuit.verify(&cached_proof, &input, &cached_output);
+        assert_eq!(
+            cached_output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(3)
+        );
+        let (reproof, _) = prover
+            .prove(&circuit, &input)
+            .unwrap()
+            .materialize()
+            .unwrap();
+        assert_eq!(reproof, proof);
+        assert!(cache.get(&circuit.id(), &other_input).unwrap().is_none());
+        assert!(cache.get(&lookup_circuit.id(), &input).unwrap().is_none());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
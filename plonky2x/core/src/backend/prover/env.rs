@@ -1,20 +1,156 @@
 use std::env;
+use std::future::Future;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use log::warn;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 
 use super::local::LocalProver;
+use super::service::ProofService;
 use super::{ProverOutput, ProverOutputs, RemoteProver};
 use crate::backend::circuit::{CircuitBuild, CircuitSerializer, PlonkParameters, PublicInput};
 
+/// The default largest circuit, in log2 of its number of rows, that `ProverPolicy::Fallback`
+/// proves locally.
+const DEFAULT_FALLBACK_MAX_DEGREE_BITS: usize = 20;
+
+/// Where `EnvProver` generates proofs, read from the env variable `PROVER`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProverPolicy {
+    /// `PROVER=local`, the default.
+    Local,
+    /// `PROVER=remote`.
+    Remote,
+    /// `PROVER=fallback`: proofs are generated remotely, unless the proof service is unreachable
+    /// or fails to generate them, in which case circuits with at most `max_degree_bits` (read from
+    /// `PROVER_FALLBACK_MAX_DEGREE_BITS`) are proven locally.
+    Fallback { max_degree_bits: usize },
+}
+
+impl ProverPolicy {
+    /// Reads the policy from the env variables `PROVER` and `PROVER_FALLBACK_MAX_DEGREE_BITS`.
+    pub fn from_env() -> Result<Self> {
+        let policy = match env::var("PROVER").unwrap_or("local".to_string()).as_str() {
+            "remote" => ProverPolicy::Remote,
+            "fallback" => {
+                let max_degree_bits = match env::var("PROVER_FALLBACK_MAX_DEGREE_BITS") {
+                    Ok(bits) => bits.parse().map_err(|_| {
+                        anyhow!(
+                            "invalid value for PROVER_FALLBACK_MAX_DEGREE_BITS: {}",
+                            bits
+                        )
+                    })?,
+                    Err(_) => DEFAULT_FALLBACK_MAX_DEGREE_BITS,
+                };
+                ProverPolicy::Fallback { max_degree_bits }
+            }
+            _ => ProverPolicy::Local,
+        };
+        Ok(policy)
+    }
+}
+
 /// A prover that can generate proofs locally or remotely based on the env variable `PROVER` which
-/// can either be `remote` or `local`.
-pub struct EnvProver;
+/// can either be `remote`, `local` or `fallback` (see [`ProverPolicy`]).
+pub struct EnvProver {
+    policy: Option<ProverPolicy>,
+    service_url: Option<String>,
+}
 
 impl EnvProver {
+    /// Creates a prover whose policy is read from the environment when it proves.
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self {}
+        Self {
+            policy: None,
+            service_url: None,
+        }
+    }
+
+    pub fn with_policy(policy: ProverPolicy) -> Self {
+        Self {
+            policy: Some(policy),
+            service_url: None,
+        }
+    }
+
+    /// Requests proofs from the service at `url` instead of `PROOF_SERVICE_URL`.
+    pub fn with_service_url(mut self, url: impl Into<String>) -> Self {
+        self.service_url = Some(url.into());
+        self
+    }
+
+    fn policy(&self) -> Result<ProverPolicy> {
+        self.policy.map_or_else(ProverPolicy::from_env, Ok)
+    }
+
+    fn service_url(&self) -> Result<String> {
+        match &self.service_url {
+            Some(url) => Ok(url.clone()),
+            None => {
+                env::var("PROOF_SERVICE_URL").map_err(|_| anyhow!("PROOF_SERVICE_URL is not set"))
+            }
+        }
+    }
+
+    fn load_local<L: PlonkParameters<D>, S: CircuitSerializer, const D: usize>(
+        circuit_id: &str,
+    ) -> Result<CircuitBuild<L, D>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let gate_serializer = S::gate_registry::<L, D>();
+        let generator_serializer = S::generator_registry::<L, D>();
+        CircuitBuild::<L, D>::load_by_id(circuit_id, &gate_serializer, &generator_serializer)
+    }
+
+    /// Generates the proofs with `remote` or `local` according to the policy. With the fallback
+    /// policy, the proofs are generated locally if the proof service is unreachable or fails, as
+    /// long as the circuit is small enough to be proven on this machine.
+    fn prove_with<L: PlonkParameters<D>, S: CircuitSerializer, O, F, const D: usize>(
+        &self,
+        circuit_id: &str,
+        remote: impl FnOnce(RemoteProver) -> F,
+        local: impl FnOnce(&CircuitBuild<L, D>) -> Result<O>,
+    ) -> Result<O>
+    where
+        F: Future<Output = Result<O>>,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let block_on = |future: F| tokio::runtime::Runtime::new()?.block_on(future);
+        let max_degree_bits = match self.policy()? {
+            ProverPolicy::Local => return local(&Self::load_local::<L, S, D>(circuit_id)?),
+            ProverPolicy::Remote => {
+                return block_on(remote(RemoteProver::from_url(self.service_url()?)))
+            }
+            ProverPolicy::Fallback { max_degree_bits } => max_degree_bits,
+        };
+
+        let url = self.service_url()?;
+        let error = if ProofService::is_reachable(&url) {
+            match block_on(remote(RemoteProver::from_url(url))) {
+                Ok(output) => return Ok(output),
+                Err(e) => e,
+            }
+        } else {
+            anyhow!("proof service at {} is unreachable", url)
+        };
+
+        let circuit = Self::load_local::<L, S, D>(circuit_id)?;
+        let degree_bits = circuit.data.common.degree_bits();
+        if degree_bits > max_degree_bits {
+            return Err(error.context(format!(
+                "circuit {} is too large to prove locally: degree_bits={}, max_degree_bits={}",
+                circuit_id, degree_bits, max_degree_bits
+            )));
+        }
+        warn!(
+            "remote proving failed, proving circuit {} locally: {:?}",
+            circuit_id, error
+        );
+        local(&circuit)
     }
 
     pub fn prove<L: PlonkParameters<D>, S: CircuitSerializer, const D: usize>(
@@ -26,18 +162,11 @@ impl EnvProver {
         <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
             AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
     {
-        if env::var("PROVER").unwrap_or("local".to_string()) == "remote" {
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            rt.block_on(async { RemoteProver::new().prove(circuit_id, input).await })
-        } else {
-            let gate_serializer = S::gate_registry::<L, D>();
-            let generator_serializer = S::generator_registry::<L, D>();
-            let circuit_path = format!("./build/{}.circuit", circuit_id);
-            let circuit =
-                CircuitBuild::<L, D>::load(&circuit_path, &gate_serializer, &generator_serializer)
-                    .unwrap();
-            LocalProver::new().prove(&circuit, input)
-        }
+        self.prove_with::<L, S, _, _, D>(
+            circuit_id,
+            |prover| async move { prover.prove(circuit_id, input).await },
+            |circuit| LocalProver::new().prove(circuit, input),
+        )
     }
 
     pub fn batch_prove<L: PlonkParameters<D>, S: CircuitSerializer, const D: usize>(
@@ -49,17 +178,66 @@ impl EnvProver {
         <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
             AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
     {
-        if env::var("PROVER").unwrap_or("local".to_string()) == "remote" {
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            rt.block_on(async { RemoteProver::new().batch_prove(circuit_id, inputs).await })
-        } else {
-            let gate_serializer = S::gate_registry::<L, D>();
-            let generator_serializer = S::generator_registry::<L, D>();
-            let circuit_path = format!("./build/{}.circuit", circuit_id);
-            let circuit =
-                CircuitBuild::<L, D>::load(&circuit_path, &gate_serializer, &generator_serializer)
-                    .unwrap();
-            LocalProver::new().batch_prove(&circuit, inputs)
+        self.prove_with::<L, S, _, _, D>(
+            circuit_id,
+            |prover| async move { prover.batch_prove(circuit_id, inputs).await },
+            |circuit| LocalProver::new().batch_prove(circuit, inputs),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::circuit::DefaultSerializer;
+    use crate::backend::prover::MockProofService;
+    use crate::prelude::*;
+    use crate::utils::test_circuits::{add_input, build_add_circuit};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    #[test]
+    fn test_env_prover_fallback() {
+        let circuit = build_add_circuit();
+        let gate_serializer = DefaultSerializer::gate_registry::<L, D>();
+        let generator_serializer = DefaultSerializer::generator_registry::<L, D>();
+        circuit.save(
+            &format!("./build/{}.circuit", circuit.id()),
+            &gate_serializer,
+            &generator_serializer,
+        );
+
+        let input = add_input(&circuit, 1, 2);
+        let degree_bits = circuit.data.common.degree_bits();
+
+        // Nothing listens on this port, so the service is unreachable. The service emulator knows
+        // no circuit, so the requests sent to it fail.
+        let failing_service = MockProofService::<L, D>::start(vec![]).unwrap();
+        for url in ["http://127.0.0.1:9".to_string(), failing_service.url()] {
+            // Circuits of up to `max_degree_bits` are proven locally.
+            let prover = EnvProver::with_policy(ProverPolicy::Fallback {
+                max_degree_bits: degree_bits,
+            })
+            .with_service_url(url.clone());
+            let (proof, mut output) = prover
+                .prove::<L, DefaultSerializer, D>(&circuit.id(), &input)
+                .unwrap()
+                .materialize()
+                .unwrap();
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(
+                output.read::<Variable>(),
+                GoldilocksField::from_canonical_u64(3)
+            );
+
+            let prover = EnvProver::with_policy(ProverPolicy::Fallback {
+                max_degree_bits: degree_bits - 1,
+            })
+            .with_service_url(url);
+            assert!(prover
+                .prove::<L, DefaultSerializer, D>(&circuit.id(), &input)
+                .is_err());
         }
     }
 }
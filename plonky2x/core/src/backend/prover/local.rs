@@ -2,17 +2,28 @@ use anyhow::Result;
 use log::debug;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 
+use super::cache::ProofCache;
 use super::{ProverOutput, ProverOutputs};
 use crate::backend::circuit::{CircuitBuild, PlonkParameters, PublicInput};
 
 /// A prover that generates proofs locally.
 #[derive(Debug, Clone)]
-pub struct LocalProver;
+pub struct LocalProver {
+    cache: Option<ProofCache>,
+}
 
 impl LocalProver {
+    /// Creates a prover, with the proof cache given by `PROOF_CACHE_DIR` if it is set.
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self {}
+        Self {
+            cache: ProofCache::from_env(),
+        }
+    }
+
+    /// Creates a prover which returns the proofs of `cache` for the inputs it already proved.
+    pub fn with_cache(cache: ProofCache) -> Self {
+        Self { cache: Some(cache) }
     }
 
     #[allow(clippy::type_complexity)]
@@ -25,11 +36,20 @@ impl LocalProver {
         <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
             AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
     {
+        let Some(cache) = &self.cache else {
+            let (proof, output) = circuit.prove(input);
+            return Ok(ProverOutput::Local(proof, output));
+        };
+        let circuit_id = circuit.id();
+        if let Some((proof, output)) = cache.lookup(&circuit_id, input) {
+            return Ok(ProverOutput::Local(proof, output));
+        }
         let (proof, output) = circuit.prove(input);
+        cache.store(&circuit_id, input, &proof, &output);
         Ok(ProverOutput::Local(proof, output))
     }
 
-    /// Generates a batch of proofs with the given input.
+    /// Generates a batch of proofs with the given inputs in parallel.
     #[allow(clippy::type_complexity)]
     pub fn batch_prove<L: PlonkParameters<D>, const D: usize>(
         &self,
@@ -40,19 +60,42 @@ impl LocalProver {
         <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
             AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
     {
-        let mut outputs = Vec::new();
-        for input in inputs {
-            debug!("batch_prove: circuit_id={}", circuit.id());
-            let output = self.prove(circuit, input)?;
-            outputs.push(output);
-        }
-        let (proofs, outputs) = outputs
+        debug!(
+            "batch_prove: circuit_id={}, nb_inputs={}",
+            circuit.id(),
+            inputs.len()
+        );
+        let Some(cache) = &self.cache else {
+            let (proofs, outputs) = circuit.prove_batch(inputs).into_iter().unzip();
+            return Ok(ProverOutputs::Local(proofs, outputs));
+        };
+
+        // Only prove the inputs which are not cached.
+        let circuit_id = circuit.id();
+        let mut results = inputs
+            .iter()
+            .map(|input| cache.lookup(&circuit_id, input))
+            .collect::<Vec<_>>();
+        let missing = (0..inputs.len())
+            .filter(|i| results[*i].is_none())
+            .collect::<Vec<_>>();
+        debug!(
+            "batch_prove: nb_cached={}/{}",
+            inputs.len() - missing.len(),
+            inputs.len()
+        );
+        let missing_inputs = missing
+            .iter()
+            .map(|i| inputs[*i].clone())
+            .collect::<Vec<_>>();
+        for (i, (proof, output)) in missing
             .into_iter()
-            .map(|x| match x {
-                ProverOutput::Local(proof, output) => (proof, output),
-                _ => unreachable!(),
-            })
-            .unzip();
+            .zip(circuit.prove_batch(&missing_inputs))
+        {
+            cache.store(&circuit_id, &inputs[i], &proof, &output);
+            results[i] = Some((proof, output));
+        }
+        let (proofs, outputs) = results.into_iter().map(Option::unwrap).unzip();
         Ok(ProverOutputs::Local(proofs, outputs))
     }
 }
@@ -0,0 +1,493 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use anyhow::{anyhow, Result};
+use log::{debug, error};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use serde::Serialize;
+use serde_json::Value;
+use uuid::{Builder, Uuid};
+
+use super::local::LocalProver;
+use super::service::{
+    BatchProofId, ProofId, ProofRequestStatus, SubmitProofBatchRequestResponse,
+    SubmitProofRequestResponse,
+};
+use super::ProverOutput;
+use crate::backend::circuit::{CircuitBuild, PlonkParameters, PublicInput};
+use crate::backend::function::{ProofRequest, ProofRequestBase, ProofResult};
+
+/// Generates a random UUID V4, the format the hosted service uses for its identifiers.
+fn new_uuid() -> Uuid {
+    Builder::from_random_bytes(rand::random()).into_uuid()
+}
+
+/// A proof request tracked by the mock service.
+struct MockProof<L: PlonkParameters<D>, const D: usize> {
+    status: ProofRequestStatus,
+    result: Option<ProofResult<L, D>>,
+}
+
+/// The state shared between a `MockProofService` handle and its server thread.
+struct MockState<L: PlonkParameters<D>, const D: usize> {
+    circuits: HashMap<String, CircuitBuild<L, D>>,
+    proofs: HashMap<ProofId, MockProof<L, D>>,
+    batches: HashMap<BatchProofId, Vec<ProofId>>,
+}
+
+/// The response served for `GET /api/proof/{id}`.
+#[derive(Serialize)]
+#[serde(bound = "")]
+struct MockProofResponse<'a, L: PlonkParameters<D>, const D: usize> {
+    id: ProofId,
+    status: ProofRequestStatus,
+    result: Option<&'a ProofResult<L, D>>,
+}
+
+/// The response served for `GET /api/proof/batch/status/{id}`.
+#[derive(Serialize)]
+struct MockProofBatchResponse {
+    statuses: HashMap<ProofRequestStatus, u64>,
+}
+
+/// The body served for requests that fail.
+#[derive(Serialize)]
+struct MockErrorResponse {
+    error: String,
+}
+
+/// A failed request, answered with its HTTP status and a JSON error body.
+#[derive(Debug)]
+struct MockError {
+    status: &'static str,
+    message: String,
+}
+
+impl MockError {
+    fn bad_request(e: impl Display) -> Self {
+        Self {
+            status: "400 Bad Request",
+            message: e.to_string(),
+        }
+    }
+
+    fn not_found(e: impl Display) -> Self {
+        Self {
+            status: "404 Not Found",
+            message: e.to_string(),
+        }
+    }
+
+    fn internal(e: impl Display) -> Self {
+        Self {
+            status: "500 Internal Server Error",
+            message: e.to_string(),
+        }
+    }
+}
+
+/// A local emulator of the remote proof service.
+///
+/// It serves the same HTTP API as the hosted service on a random local port and generates the
+/// requested proofs with `LocalProver`, so that `ProofService`, `RemoteProver` and `EnvProver`
+/// can be exercised in tests. Point them at the emulator by setting `PROOF_SERVICE_URL` to
+/// `MockProofService::url()`.
+pub struct MockProofService<L: PlonkParameters<D>, const D: usize> {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState<L, D>>>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> MockProofService<L, D>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    /// Starts the emulator in a background thread. Requests are proven with the circuit whose id
+    /// matches the circuit file of the request.
+    pub fn start(circuits: Vec<CircuitBuild<L, D>>) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState {
+            circuits: circuits
+                .into_iter()
+                .map(|circuit| (circuit.id(), circuit))
+                .collect(),
+            proofs: HashMap::new(),
+            batches: HashMap::new(),
+        }));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let state = state.clone();
+            let shutdown = shutdown.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    match stream {
+                        Ok(stream) => {
+                            if let Err(e) = Self::handle_connection(&state, stream) {
+                                error!("mock proof service: {:?}", e);
+                            }
+                        }
+                        Err(e) => error!("mock proof service: {:?}", e),
+                    }
+                }
+            })
+        };
+        debug!("mock proof service listening on {}", addr);
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// The base url of the emulator.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Overrides the status of a proof request, e.g. to emulate failures or timeouts.
+    pub fn set_status(&self, id: ProofId, status: ProofRequestStatus) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(proof) = state.proofs.get_mut(&id) {
+            proof.status = status;
+        }
+    }
+
+    /// The number of proof requests the emulator has received.
+    pub fn nb_requests(&self) -> usize {
+        self.state.lock().unwrap().proofs.len()
+    }
+
+    fn handle_connection(state: &Mutex<MockState<L, D>>, stream: TcpStream) -> Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        // Parse the request line and the headers.
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let path = parts.next().unwrap_or_default().to_string();
+        let mut content_length = Ok(0);
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line)?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse::<usize>();
+                }
+            }
+        }
+        let body = match content_length {
+            Ok(content_length) => {
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body)?;
+                Ok(body)
+            }
+            Err(e) => Err(MockError::bad_request(format!(
+                "invalid content length: {}",
+                e
+            ))),
+        };
+        debug!("mock proof service: {} {}", method, path);
+
+        let response = body.and_then(|body| {
+            let mut state = state.lock().unwrap();
+            Self::route(&mut state, &method, &path, &body)
+        });
+        match response {
+            Ok(response) => Self::respond(stream, "200 OK", &response),
+            Err(e) => {
+                debug!("mock proof service: {} {}: {}", method, path, e.message);
+                let body = serde_json::to_string(&MockErrorResponse { error: e.message })?;
+                Self::respond(stream, e.status, &body)
+            }
+        }
+    }
+
+    /// Serves a request, returning the JSON body of the response.
+    fn route(
+        state: &mut MockState<L, D>,
+        method: &str,
+        path: &str,
+        body: &[u8],
+    ) -> Result<String, MockError> {
+        let response = match (method, path) {
+            ("POST", "/api/proof/new") => {
+                let request = serde_json::from_slice(body)
+                    .map_err(MockError::bad_request)
+                    .and_then(|value| {
+                        ProofRequest::decode_value(value).map_err(MockError::bad_request)
+                    })?;
+                let proof_id = Self::create_proof(state, request);
+                serde_json::to_string(&SubmitProofRequestResponse { proof_id })
+            }
+            ("POST", "/api/proof/batch/new") => {
+                let requests = serde_json::from_slice::<Vec<Value>>(body)
+                    .map_err(MockError::bad_request)?
+                    .into_iter()
+                    .map(ProofRequest::decode_value)
+                    .collect::<Result<Vec<_>>>()
+                    .map_err(MockError::bad_request)?;
+                let proof_ids = requests
+                    .into_iter()
+                    .map(|request| Self::create_proof(state, request))
+                    .collect::<Vec<_>>();
+                let proof_batch_id = BatchProofId(new_uuid());
+                state.batches.insert(proof_batch_id, proof_ids.clone());
+                serde_json::to_string(&SubmitProofBatchRequestResponse {
+                    proof_batch_id,
+                    proof_ids,
+                })
+            }
+            ("GET", path) if path.starts_with("/api/proof/batch/status/") => {
+                let id = Uuid::parse_str(path.trim_start_matches("/api/proof/batch/status/"))
+                    .map(BatchProofId)
+                    .map_err(MockError::bad_request)?;
+                let proof_ids = state
+                    .batches
+                    .get(&id)
+                    .ok_or_else(|| MockError::not_found(format!("unknown proof batch {:?}", id)))?;
+                let mut statuses = HashMap::new();
+                for proof_id in proof_ids {
+                    *statuses.entry(state.proofs[proof_id].status).or_insert(0) += 1;
+                }
+                serde_json::to_string(&MockProofBatchResponse { statuses })
+            }
+            ("GET", path) if path.starts_with("/api/proof/") => {
+                let id = Uuid::parse_str(path.trim_start_matches("/api/proof/"))
+                    .map(ProofId)
+                    .map_err(MockError::bad_request)?;
+                let proof = state
+                    .proofs
+                    .get(&id)
+                    .ok_or_else(|| MockError::not_found(format!("unknown proof {:?}", id)))?;
+                serde_json::to_string(&MockProofResponse {
+                    id,
+                    status: proof.status,
+                    result: proof.result.as_ref(),
+                })
+            }
+            _ => {
+                return Err(MockError::not_found(format!(
+                    "no route for {} {}",
+                    method, path
+                )))
+            }
+        };
+        response.map_err(MockError::internal)
+    }
+
+    /// Generates the proof for a request and records the result under a fresh proof id.
+    fn create_proof(state: &mut MockState<L, D>, request: ProofRequest<L, D>) -> ProofId {
+        let proof_id = ProofId(new_uuid());
+        let proof = match Self::prove(state, &request) {
+            Ok(ProverOutput::Local(proof, output)) => MockProof {
+                status: ProofRequestStatus::Success,
+                result: Some(ProofResult::from_proof_output(proof, output)),
+            },
+            Ok(ProverOutput::Remote(_)) => unreachable!(),
+            Err(e) => {
+                error!("mock proof service: proof {:?} failed: {:?}", proof_id, e);
+                MockProof {
+                    status: ProofRequestStatus::Failure,
+                    result: None,
+                }
+            }
+        };
+        state.proofs.insert(proof_id, proof);
+        proof_id
+    }
+
+    fn prove(state: &MockState<L, D>, request: &ProofRequest<L, D>) -> Result<ProverOutput<L, D>> {
+        let (files, input) = match request {
+            ProofRequest::Bytes(ProofRequestBase { files, .. })
+            | ProofRequest::Elements(ProofRequestBase { files, .. })
+            | ProofRequest::RecursiveProofs(ProofRequestBase { files, .. }) => {
+                (files, request.input())
+            }
+            ProofRequest::RemoteRecursiveProofs(ProofRequestBase { files, data, .. }) => {
+                // Resolve the proof ids to the proofs this emulator generated earlier.
+                let proofs = data
+                    .proof_ids
+                    .iter()
+                    .map(|id| {
+                        state
+                            .proofs
+                            .get(id)
+                            .and_then(|proof| proof.result.as_ref())
+                            .map(|result| result.as_proof_and_output().0)
+                            .ok_or_else(|| anyhow!("proof {:?} is not available", id))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                (files, PublicInput::RecursiveProofs(proofs, vec![]))
+            }
+        };
+
+        let circuit_id = files
+            .as_ref()
+            .and_then(|files| files.first())
+            .map(|file| file.trim_end_matches(".circuit").to_string())
+            .ok_or_else(|| anyhow!("proof request has no circuit file"))?;
+        let circuit = match state.circuits.get(&circuit_id) {
+            Some(circuit) => circuit,
+            // Bytes requests always reference `main.circuit`.
+            None if state.circuits.len() == 1 => state.circuits.values().next().unwrap(),
+            None => return Err(anyhow!("unknown circuit {}", circuit_id)),
+        };
+        LocalProver::new().prove(circuit, &input)
+    }
+
+    fn respond(mut stream: TcpStream, status: &str, body: &str) -> Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )?;
+        stream.flush()?;
+        Ok(())
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Drop for MockProofService<L, D> {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the server thread so that it observes the shutdown flag.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::backend::prover::ProofService;
+    use crate::prelude::*;
+    use crate::utils::test_circuits::{add_input, build_add_circuit, build_square_circuit};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    #[test]
+    fn test_mock_proof_service() {
+        env::set_var("RELEASE_ID", "mock");
+        env::set_var("PROOF_ID", "mock");
+
+        let circuit = build_add_circuit();
+        let input = add_input(&circuit, 2, 2);
+        let square_circuit = build_square_circuit();
+        let mut square_input = square_circuit.input();
+        square_input.write::<Variable>(GoldilocksField::from_canonical_u64(3));
+
+        let mock = MockProofService::start(vec![circuit, build_square_circuit()]).unwrap();
+        let service = ProofService::new(mock.url());
+        let circuit = build_add_circuit();
+        let request = ProofRequest::<L, D>::new(&circuit.id(), &input);
+
+        // A single request is proven and can be fetched by its id.
+        let proof_id = service.submit(request.clone()).unwrap();
+        let response = service.get::<L, D>(proof_id).unwrap();
+        assert_eq!(response.id, proof_id);
+        assert_eq!(response.status, ProofRequestStatus::Success);
+        let (proof, mut output) = response.result.unwrap().as_proof_and_output();
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(
+            output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(4)
+        );
+
+        // Requests are proven by the circuit with their circuit id.
+        let square_request = ProofRequest::<L, D>::new(&square_circuit.id(), &square_input);
+        let square_id = service.submit(square_request).unwrap();
+        let response = service.get::<L, D>(square_id).unwrap();
+        let (proof, mut output) = response.result.unwrap().as_proof_and_output();
+        square_circuit.verify(&proof, &square_input, &output);
+        assert_eq!(
+            output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(9)
+        );
+
+        // Batches report the aggregated statuses of their proofs.
+        let requests = vec![request.clone(), request];
+        let (batch_id, proof_ids) = service.submit_batch(&requests).unwrap();
+        assert_eq!(proof_ids.len(), 2);
+        mock.set_status(proof_ids[1], ProofRequestStatus::Running);
+        let batch = service.get_batch::<L, D>(batch_id).unwrap();
+        assert_eq!(batch.statuses[&ProofRequestStatus::Success], 1);
+        assert_eq!(batch.statuses[&ProofRequestStatus::Running], 1);
+        assert_eq!(mock.nb_requests(), 4);
+
+        // Many proofs are fetched in the order of their ids.
+        let ids = vec![proof_ids[1], proof_id, proof_ids[0]];
+        let responses = service.get_many::<L, D>(&ids).unwrap();
+        let statuses = responses.iter().map(|r| r.status).collect::<Vec<_>>();
+        assert_eq!(responses.iter().map(|r| r.id).collect::<Vec<_>>(), ids);
+        assert_eq!(
+            statuses,
+            vec![
+                ProofRequestStatus::Running,
+                ProofRequestStatus::Success,
+                ProofRequestStatus::Success
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mock_proof_service_errors() {
+        let mock = MockProofService::<L, D>::start(vec![build_add_circuit()]).unwrap();
+        let service = ProofService::new(mock.url());
+
+        // Unknown proofs and batches are reported by the client as failed requests.
+        let error = service.get::<L, D>(ProofId(new_uuid())).unwrap_err();
+        assert!(error.to_string().contains("404"), "{}", error);
+        let error = service
+            .get_batch::<L, D>(BatchProofId(new_uuid()))
+            .unwrap_err();
+        assert!(error.to_string().contains("404"), "{}", error);
+
+        // Malformed requests are answered with a JSON error body instead of a closed connection.
+        let client = reqwest::blocking::Client::new();
+        let response = client
+            .post(format!("{}/api/proof/new", mock.url()))
+            .body("not a proof request")
+            .send()
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+        let body: Value = serde_json::from_str(&response.text().unwrap()).unwrap();
+        assert!(body["error"].is_string());
+
+        let response = client
+            .get(format!("{}/api/proof/not-a-uuid", mock.url()))
+            .send()
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+        let response = client
+            .get(format!("{}/api/unknown", mock.url()))
+            .send()
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+        assert_eq!(mock.nb_requests(), 0);
+    }
+}
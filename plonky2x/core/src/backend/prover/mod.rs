@@ -1,14 +1,18 @@
+mod cache;
 mod env;
 mod local;
+mod mock;
 mod remote;
 mod service;
 
-use anyhow::Result;
-pub use env::EnvProver;
+use anyhow::{anyhow, Result};
+pub use cache::{DiskProofCacheBackend, ProofCache, ProofCacheBackend};
+pub use env::{EnvProver, ProverPolicy};
 pub use local::LocalProver;
+pub use mock::MockProofService;
 use plonky2::plonk::proof::ProofWithPublicInputs;
-pub use remote::RemoteProver;
-pub use service::{BatchProofId, ProofId, ProofService};
+pub use remote::{RemoteProver, RemoteProverConfig};
+pub use service::{BatchProofId, ProofId, ProofRequestStatus, ProofService, RetryPolicy};
 
 use super::circuit::{PlonkParameters, PublicOutput};
 
@@ -41,8 +45,11 @@ impl<L: PlonkParameters<D>, const D: usize> ProverOutput<L, D> {
             ProverOutput::Local(proof, output) => (proof, output),
             ProverOutput::Remote(proof_id) => {
                 let service = ProofService::new_from_env();
-                let response = service.get::<L, D>(proof_id).unwrap();
-                response.result.unwrap().as_proof_and_output()
+                let response = service.get::<L, D>(proof_id)?;
+                response
+                    .result
+                    .ok_or_else(|| anyhow!("proof {:?} has no result", proof_id))?
+                    .as_proof_and_output()
             }
         };
         Ok((proof, output))
@@ -63,9 +70,11 @@ impl<L: PlonkParameters<D>, const D: usize> ProverOutputs<L, D> {
                 let service = ProofService::new_from_env();
                 let mut proofs = Vec::new();
                 let mut outputs = Vec::new();
-                for proof_id in proof_ids {
-                    let response = service.get::<L, D>(proof_id).unwrap();
-                    let (proof, output) = response.result.unwrap().as_proof_and_output();
+                for response in service.get_many::<L, D>(&proof_ids)? {
+                    let (proof, output) = response
+                        .result
+                        .ok_or_else(|| anyhow!("proof {:?} has no result", response.id))?
+                        .as_proof_and_output();
                     proofs.push(proof);
                     outputs.push(output);
                 }
@@ -1,40 +1,220 @@
 use core::time::Duration;
 use std::env;
-use std::net::ToSocketAddrs;
 
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use log::debug;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 use rand::Rng;
-use reqwest::Client;
-use tokio::time::sleep;
+use tokio::task::spawn_blocking;
+use tokio::time::{sleep, Instant};
 
+use super::cache::ProofCache;
 use super::ProverOutput;
 use crate::backend::circuit::{PlonkParameters, PublicInput};
 use crate::backend::function::ProofRequest;
-use crate::backend::prover::service::{ProofRequestStatus, ProofService};
+use crate::backend::prover::service::{
+    env_var, BatchProofId, GetProofBatchRequestResponse, GetProofRequestResponse, ProofId,
+    ProofRequestStatus, ProofService, RetryPolicy,
+};
 use crate::backend::prover::ProverOutputs;
 
+/// The timeouts of the remote prover and how it retries failed requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteProverConfig {
+    pub retry: RetryPolicy,
+    /// The delay between two polls of the status of a pending proof.
+    pub poll_interval: Duration,
+    /// How long to wait for a proof before giving up on it.
+    pub proof_timeout: Duration,
+    /// How long to wait for a batch of proofs before giving up on it.
+    pub batch_timeout: Duration,
+}
+
+impl Default for RemoteProverConfig {
+    fn default() -> Self {
+        Self {
+            retry: RetryPolicy::default(),
+            poll_interval: Duration::from_secs(10),
+            proof_timeout: Duration::from_secs(60 * 60),
+            batch_timeout: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl RemoteProverConfig {
+    /// The default config, overridden by the `PROOF_POLL_INTERVAL_SECS`, `PROOF_TIMEOUT_SECS`
+    /// and `PROOF_BATCH_TIMEOUT_SECS` environment variables and by `RetryPolicy::from_env`.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let secs = |name: &str, default: Duration| {
+            env_var(name).map(Duration::from_secs).unwrap_or(default)
+        };
+        Self {
+            retry: RetryPolicy::from_env(),
+            poll_interval: secs("PROOF_POLL_INTERVAL_SECS", default.poll_interval),
+            proof_timeout: secs("PROOF_TIMEOUT_SECS", default.proof_timeout),
+            batch_timeout: secs("PROOF_BATCH_TIMEOUT_SECS", default.batch_timeout),
+        }
+    }
+
+    /// The number of polls that fit in `timeout`.
+    fn max_polls(&self, timeout: Duration) -> u128 {
+        timeout.as_millis() / self.poll_interval.as_millis().max(1)
+    }
+}
+
 /// A prover that generates proofs remotely on another machine.
+///
+/// The requests are sent by a `ProofService` on a blocking thread, so that the routes, the
+/// authentication and the retries of the service only live in one client.
 #[derive(Debug, Clone, Default)]
 pub struct RemoteProver {
-    pub client: Client,
+    pub config: RemoteProverConfig,
+    base_url: String,
+    cache: Option<ProofCache>,
 }
 
 impl RemoteProver {
+    /// Creates a prover for the service at `PROOF_SERVICE_URL`.
     pub fn new() -> Self {
-        let proof_service_url = env::var("PROOF_SERVICE_URL").unwrap();
-        let host = &proof_service_url.split("://").last().unwrap();
-        let sock_addrs = format!("{}:443", host)
-            .to_socket_addrs()
-            .unwrap()
-            .collect::<Vec<_>>();
+        Self::from_url(env::var("PROOF_SERVICE_URL").unwrap())
+    }
+
+    /// Creates a prover for the service at `url`, configured from the environment.
+    pub fn from_url(url: String) -> Self {
         Self {
-            client: Client::builder()
-                .resolve_to_addrs(host, &sock_addrs)
-                .build()
-                .unwrap(),
+            config: RemoteProverConfig::from_env(),
+            base_url: url,
+            cache: ProofCache::from_env(),
+        }
+    }
+
+    /// Returns the proofs of `cache` for the inputs it already proved instead of requesting them
+    /// again, and caches the proofs downloaded from the service.
+    pub fn with_cache(mut self, cache: ProofCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Replaces the timeouts and the retry policy of the prover.
+    pub fn with_config(mut self, config: RemoteProverConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Runs `f` with a client for the service on a blocking thread. The client is created and
+    /// dropped on that thread too, since the blocking client must not be used from async code.
+    async fn with_service<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&ProofService) -> Result<T> + Send + 'static,
+    {
+        let url = self.base_url.clone();
+        let retry = self.config.retry;
+        spawn_blocking(move || f(&ProofService::new(url).with_retry_policy(retry))).await?
+    }
+
+    /// Submits a request for the service to generate a proof. Returns the proof id.
+    pub async fn submit<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        request: ProofRequest<L, D>,
+    ) -> Result<ProofId> {
+        self.with_service(move |service| service.submit(request))
+            .await
+    }
+
+    /// Submits a batch of requests for the service to generate proofs.
+    pub async fn submit_batch<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        requests: &[ProofRequest<L, D>],
+    ) -> Result<(BatchProofId, Vec<ProofId>)> {
+        let requests = requests.to_vec();
+        self.with_service(move |service| service.submit_batch(&requests))
+            .await
+    }
+
+    /// Submits many requests for the service to generate proofs, like
+    /// `ProofService::create_proofs`. Returns the proof ids in the same order as the requests.
+    pub async fn create_proofs<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        requests: &[ProofRequest<L, D>],
+    ) -> Result<Vec<ProofId>> {
+        let requests = requests.to_vec();
+        self.with_service(move |service| service.create_proofs(&requests))
+            .await
+    }
+
+    /// Gets the status of many proof requests. Returns the responses in the same order as the ids.
+    pub async fn get_many<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        ids: &[ProofId],
+    ) -> Result<Vec<GetProofRequestResponse<L, D>>> {
+        let ids = ids.to_vec();
+        self.with_service(move |service| service.get_many(&ids))
+            .await
+    }
+
+    /// Gets the number of proofs of the batch `batch_id` in each status.
+    pub async fn get_batch<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        batch_id: BatchProofId,
+    ) -> Result<GetProofBatchRequestResponse> {
+        self.with_service(move |service| service.get_batch::<L, D>(batch_id))
+            .await
+    }
+
+    /// Gets the status of the proof `proof_id`.
+    pub async fn poll_status(&self, proof_id: ProofId) -> Result<ProofRequestStatus> {
+        self.with_service(move |service| service.get_status(proof_id))
+            .await
+    }
+
+    /// Polls the proof `proof_id` until it is generated and returns it. Fails if the service
+    /// reports that the proof failed, or if it is still pending after `proof_timeout`.
+    pub async fn await_proof<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        proof_id: ProofId,
+    ) -> Result<ProverOutput<L, D>> {
+        let deadline = Instant::now() + self.config.proof_timeout;
+        let mut nb_polls = 0;
+        loop {
+            let response = self
+                .with_service(move |service| service.get::<L, D>(proof_id))
+                .await?;
+            nb_polls += 1;
+            debug!(
+                "proof {:?}: status={:?}, nb_polls={}",
+                proof_id, response.status, nb_polls
+            );
+            match response.status {
+                ProofRequestStatus::Pending
+                | ProofRequestStatus::Running
+                | ProofRequestStatus::Requested => {}
+                ProofRequestStatus::Success => {
+                    let result = response
+                        .result
+                        .ok_or_else(|| anyhow!("proof {:?} has no result", proof_id))?;
+                    let (proof, output) = result.as_proof_and_output();
+                    return Ok(ProverOutput::Local(proof, output));
+                }
+                status => {
+                    return Err(anyhow!(
+                        "could not generate proof {:?}: status={:?}",
+                        proof_id,
+                        status
+                    ))
+                }
+            }
+            if Instant::now() + self.config.poll_interval > deadline {
+                return Err(anyhow!(
+                    "timed out waiting for proof {:?}: status={:?}, nb_polls={}",
+                    proof_id,
+                    response.status,
+                    nb_polls
+                ));
+            }
+            sleep(self.config.poll_interval).await;
         }
     }
 
@@ -48,62 +228,123 @@ impl RemoteProver {
             AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
     {
         debug!("prove: circuit_id={}", circuit_id);
+        if let Some(cache) = &self.cache {
+            if let Some((proof, output)) = cache.lookup(circuit_id, input) {
+                return Ok(ProverOutput::Local(proof, output));
+            }
+        }
 
-        // Initialize the proof service.
-        let service = ProofService::new_from_env();
-
-        // Submit the proof request.
-        let mut rng = rand::thread_rng();
-        let sleep_time = rng.gen_range(0..=5000);
+        // Submit the proof request, spreading out the requests of concurrent provers.
+        let sleep_time = rand::thread_rng().gen_range(0..=5000);
         sleep(Duration::from_millis(sleep_time)).await;
-        let request = ProofRequest::new(circuit_id, input);
-        let proof_id = service
-            .submit::<L, D>(request)
-            .expect("failed to submit proof request");
-
-        // Default timeout for a proof is 60 minutes. Users can override this value by
-        // setting the PROOF_TIMEOUT_SECS environment variable.
-        const DEFAULT_PROOF_TIMEOUT_SECS: u64 = 60 * 60;
-        let proof_timeout_secs = env::var("PROOF_TIMEOUT_SECS")
-            .unwrap_or(DEFAULT_PROOF_TIMEOUT_SECS.to_string())
-            .parse::<u64>()
-            .unwrap();
-        let poll_secs = 10;
+        let request = ProofRequest::try_new(circuit_id, input)?;
+        let proof_id = self.submit::<L, D>(request).await?;
+        let output = self.await_proof(proof_id).await?;
+        if let (Some(cache), ProverOutput::Local(proof, output)) = (&self.cache, &output) {
+            cache.store(circuit_id, input, proof, output);
+        }
+        Ok(output)
+    }
+
+    /// Generates proofs for many inputs of the same circuit. All requests are submitted up front
+    /// with as few HTTP requests as possible and the pending proofs are then polled together, so
+    /// the total latency is that of the slowest proof rather than the sum of all of them. Inputs
+    /// with a cached proof are not requested. Returns the outputs in the same order as the inputs.
+    pub async fn prove_many<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        circuit_id: &str,
+        inputs: &[PublicInput<L, D>],
+    ) -> Result<Vec<ProverOutput<L, D>>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        debug!(
+            "prove_many: circuit_id={}, nb_inputs={}",
+            circuit_id,
+            inputs.len()
+        );
+
+        // Submit the proof requests of the inputs which are not cached.
+        let mut outputs = inputs
+            .iter()
+            .map(|input| {
+                let cache = self.cache.as_ref()?;
+                let (proof, output) = cache.lookup(circuit_id, input)?;
+                Some(ProverOutput::Local(proof, output))
+            })
+            .collect_vec();
+        let missing = (0..inputs.len())
+            .filter(|i| outputs[*i].is_none())
+            .collect_vec();
+        if missing.is_empty() {
+            return Ok(outputs.into_iter().map(Option::unwrap).collect());
+        }
+        let requests = missing
+            .iter()
+            .map(|i| ProofRequest::try_new(circuit_id, &inputs[*i]))
+            .collect::<Result<Vec<_>>>()?;
+        let proof_ids = self.create_proofs(&requests).await?;
+
         // Maximum number of polls for proof status before timeout.
-        let max_polls = proof_timeout_secs / poll_secs;
+        let max_polls = self.config.max_polls(self.config.proof_timeout);
 
-        let mut status = ProofRequestStatus::Pending;
+        let mut pending = (0..proof_ids.len()).collect_vec();
         for i in 0..max_polls {
-            sleep(Duration::from_secs(poll_secs)).await;
-            let request = service.get::<L, D>(proof_id)?;
+            sleep(self.config.poll_interval).await;
+            let ids = pending.iter().map(|j| proof_ids[*j]).collect_vec();
+            let responses = self.get_many::<L, D>(&ids).await?;
+
+            let mut still_pending = Vec::new();
+            for (j, response) in pending.into_iter().zip(responses) {
+                match response.status {
+                    ProofRequestStatus::Pending
+                    | ProofRequestStatus::Running
+                    | ProofRequestStatus::Requested => still_pending.push(j),
+                    ProofRequestStatus::Success => {
+                        let result = response
+                            .result
+                            .ok_or_else(|| anyhow!("proof {:?} has no result", response.id))?;
+                        let (proof, output) = result.as_proof_and_output();
+                        let index = missing[j];
+                        if let Some(cache) = &self.cache {
+                            cache.store(circuit_id, &inputs[index], &proof, &output);
+                        }
+                        outputs[index] = Some(ProverOutput::Local(proof, output));
+                    }
+                    status => {
+                        return Err(anyhow!(
+                            "could not generate proof {:?}: status={:?}",
+                            response.id,
+                            status
+                        ))
+                    }
+                }
+            }
+            pending = still_pending;
             debug!(
-                "proof {:?}: status={:?}, nb_polls={}/{}",
-                proof_id,
-                request.status,
+                "prove_many: nb_pending={}/{}, nb_polls={}/{}",
+                pending.len(),
+                proof_ids.len(),
                 i + 1,
                 max_polls,
             );
 
-            status = request.status;
-            match request.status {
-                ProofRequestStatus::Pending => {}
-                ProofRequestStatus::Running => {}
-                ProofRequestStatus::Success => {
-                    let (proof, output) = request.result.unwrap().as_proof_and_output();
-                    return Ok(ProverOutput::Local(proof, output));
-                }
-                _ => break,
-            };
+            if pending.is_empty() {
+                return Ok(outputs.into_iter().map(Option::unwrap).collect());
+            }
         }
 
-        // Return an error if the proof failed to generate.
+        // Return an error if the proofs did not finish before the timeout.
         Err(anyhow!(
-            "could not generate proof {:?}: status={:?}",
-            proof_id,
-            status
+            "could not generate proofs: nb_pending={}/{}",
+            pending.len(),
+            proof_ids.len()
         ))
     }
 
+    /// Generates proofs for many inputs with a single batch request. The proofs are returned as
+    /// ids and stay on the service, so they bypass the proof cache.
     pub async fn batch_prove<L: PlonkParameters<D>, const D: usize>(
         &self,
         circuit_id: &str,
@@ -113,30 +354,19 @@ impl RemoteProver {
         <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
             AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
     {
-        // Initialize the proof service.
-        let service = ProofService::new_from_env();
-
         // Submit the batch proof request.
         let requests = inputs
             .iter()
-            .map(|input| ProofRequest::new(circuit_id, input))
-            .collect_vec();
-        let (batch_id, proof_ids) = service.submit_batch(&requests)?;
-
-        // Default timeout for a batch proof is 60 minutes. Users can override this value by
-        // setting the PROOF_BATCH_TIMEOUT_SECS environment variable.
-        const DEFAULT_PROOF_BATCH_TIMEOUT_SECS: u64 = 60 * 60;
-        let proof_batch_timeout_secs = env::var("PROOF_BATCH_TIMEOUT_SECS")
-            .unwrap_or(DEFAULT_PROOF_BATCH_TIMEOUT_SECS.to_string())
-            .parse::<u64>()
-            .unwrap();
-        let poll_secs = 10;
+            .map(|input| ProofRequest::try_new(circuit_id, input))
+            .collect::<Result<Vec<_>>>()?;
+        let (batch_id, proof_ids) = self.submit_batch(&requests).await?;
+
         // Maximum number of polls for proof status before timeout.
-        let max_polls = proof_batch_timeout_secs / poll_secs;
+        let max_polls = self.config.max_polls(self.config.batch_timeout);
 
         for i in 0..max_polls {
-            sleep(Duration::from_secs(poll_secs)).await;
-            let request = match service.get_batch::<L, D>(batch_id) {
+            sleep(self.config.poll_interval).await;
+            let request = match self.get_batch::<L, D>(batch_id).await {
                 Ok(request) => request,
                 Err(e) => {
                     debug!("proof batch {:?}: error={:?}", batch_id, e);
@@ -178,3 +408,111 @@ impl RemoteProver {
         Err(anyhow!("could not generate proof {:?}", batch_id,))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::prover::MockProofService;
+    use crate::prelude::*;
+    use crate::utils::test_circuits::{add_input, build_add_circuit};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    fn test_config() -> RemoteProverConfig {
+        RemoteProverConfig {
+            retry: RetryPolicy {
+                max_retries: 2,
+                initial_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(20),
+                request_timeout: Duration::from_secs(10),
+            },
+            poll_interval: Duration::from_millis(10),
+            proof_timeout: Duration::from_millis(100),
+            batch_timeout: Duration::from_millis(100),
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_backoff() {
+        let retry = RetryPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            ..RetryPolicy::default()
+        };
+        let backoffs = (1..=4).map(|n| retry.backoff(n).as_secs()).collect_vec();
+        assert_eq!(backoffs, vec![1, 2, 4, 5]);
+        assert_eq!(retry.backoff(64), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_remote_prover_await_proof() {
+        env::set_var("RELEASE_ID", "mock");
+        env::set_var("PROOF_ID", "mock");
+
+        let circuit = build_add_circuit();
+        let input = add_input(&circuit, 1, 2);
+
+        let mock = MockProofService::start(vec![circuit]).unwrap();
+        let circuit = build_add_circuit();
+        let prover = RemoteProver::from_url(mock.url()).with_config(test_config());
+        let request = ProofRequest::<L, D>::new(&circuit.id(), &input);
+
+        let proof_id = prover.submit(request).await.unwrap();
+        assert_eq!(
+            prover.poll_status(proof_id).await.unwrap(),
+            ProofRequestStatus::Success
+        );
+        let (proof, mut output) = prover
+            .await_proof::<L, D>(proof_id)
+            .await
+            .unwrap()
+            .materialize()
+            .unwrap();
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(
+            output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(3)
+        );
+
+        // A failed proof is reported as an error, and a pending one times out.
+        mock.set_status(proof_id, ProofRequestStatus::Failure);
+        assert!(prover.await_proof::<L, D>(proof_id).await.is_err());
+        mock.set_status(proof_id, ProofRequestStatus::Running);
+        let error = prover.await_proof::<L, D>(proof_id).await.err().unwrap();
+        assert!(error.to_string().contains("timed out"));
+
+        // Nothing listens on this port, so the request fails once the retries are exhausted.
+        let prover =
+            RemoteProver::from_url("http://127.0.0.1:9".to_string()).with_config(test_config());
+        assert!(prover.poll_status(proof_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remote_prover_prove_many() {
+        env::set_var("RELEASE_ID", "mock");
+        env::set_var("PROOF_ID", "mock");
+
+        let circuit = build_add_circuit();
+        let inputs = (0..3u64).map(|i| add_input(&circuit, i, 1)).collect_vec();
+
+        let mock = MockProofService::start(vec![circuit]).unwrap();
+        let circuit = build_add_circuit();
+        let prover = RemoteProver::from_url(mock.url()).with_config(test_config());
+
+        let outputs = prover.prove_many(&circuit.id(), &inputs).await.unwrap();
+        assert_eq!(mock.nb_requests(), 3);
+        for (i, (input, output)) in inputs.iter().zip(outputs).enumerate() {
+            let (proof, mut output) = output.materialize().unwrap();
+            circuit.verify(&proof, input, &output);
+            assert_eq!(
+                output.read::<Variable>(),
+                GoldilocksField::from_canonical_u64(i as u64 + 1)
+            );
+        }
+
+        let outputs = prover.batch_prove(&circuit.id(), &inputs).await.unwrap();
+        assert!(matches!(outputs, ProverOutputs::Remote(ids) if ids.len() == 3));
+        assert_eq!(mock.nb_requests(), 6);
+    }
+}
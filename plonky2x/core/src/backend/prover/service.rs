@@ -1,14 +1,17 @@
 use core::fmt::Debug;
 use core::time::Duration;
 use std::collections::HashMap;
-use std::env;
-use std::net::ToSocketAddrs;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::str::FromStr;
+use std::{env, thread};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use log::{debug, trace};
-use reqwest::blocking::Client;
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::StatusCode;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use uuid::Uuid;
 
 use crate::backend::circuit::PlonkParameters;
@@ -26,6 +29,9 @@ const GET_PROOF_REQUEST_ROUTE: &str = "/api/proof";
 /// The endpoint for getting the status of a proof request.
 const GET_PROOF_BATCH_REQUEST_ROUTE: &str = "/api/proof/batch/status";
 
+/// The maximum number of proof requests sent to the service in a single HTTP request.
+const MAX_REQUESTS_PER_BATCH: usize = 256;
+
 /// A UUID V4 identifer for a proof request.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ProofId(pub Uuid);
@@ -70,17 +76,139 @@ pub struct GetProofRequestResponse<L: PlonkParameters<D>, const D: usize> {
     pub result: Option<ProofResult<L, D>>,
 }
 
+/// The part of the response from getting a proof that is needed to poll its status.
+#[derive(Debug, Clone, Deserialize)]
+struct GetProofStatusResponse {
+    status: ProofRequestStatus,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(bound = "")]
 pub struct GetProofBatchRequestResponse {
     pub statuses: HashMap<ProofRequestStatus, u64>,
 }
 
+/// Encodes a batch of requests in the latest version of the gateway request format.
+fn encode_requests<L: PlonkParameters<D>, const D: usize>(
+    requests: &[ProofRequest<L, D>],
+) -> Result<Vec<Value>> {
+    requests.iter().map(ProofRequest::encode_value).collect()
+}
+
+/// Checks that the service returned one proof id per request of a batch.
+fn check_proof_ids(nb_requests: usize, proof_ids: &[ProofId]) -> Result<()> {
+    if proof_ids.len() != nb_requests {
+        return Err(anyhow!(
+            "expected {} proof ids from the service, got {}",
+            nb_requests,
+            proof_ids.len()
+        ));
+    }
+    Ok(())
+}
+
+/// How requests to the proof service are retried when they fail.
+///
+/// A request is retried if it never reached the service or was rejected because the service was
+/// overloaded. Requests which only read from the service are also retried on timeouts and server
+/// errors, while submissions are not since the service may already have created the proofs. The
+/// delay before a retry starts at `initial_backoff` and doubles with every retry up to
+/// `max_backoff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// The timeout of a single attempt.
+    pub request_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            request_timeout: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The default policy, with the number of retries and the timeout of a request overridden by
+    /// the `PROOF_SERVICE_MAX_RETRIES` and `PROOF_SERVICE_REQUEST_TIMEOUT_SECS` environment
+    /// variables.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            max_retries: env_var("PROOF_SERVICE_MAX_RETRIES").unwrap_or(default.max_retries),
+            request_timeout: env_var("PROOF_SERVICE_REQUEST_TIMEOUT_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(default.request_timeout),
+            ..default
+        }
+    }
+
+    /// The delay before the retry following `nb_attempts` failed attempts.
+    pub fn backoff(&self, nb_attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(nb_attempts.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Whether a request which failed with `error` after `nb_attempts` attempts is retried.
+    fn should_retry(&self, error: &reqwest::Error, nb_attempts: u32, idempotent: bool) -> bool {
+        if nb_attempts > self.max_retries {
+            return false;
+        }
+        let overloaded = error.status().map_or(false, |status| {
+            status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE
+        });
+        let transient = error.is_timeout()
+            || error
+                .status()
+                .map_or(false, |status| status.is_server_error());
+        error.is_connect() || overloaded || (idempotent && transient)
+    }
+}
+
+/// Parses the environment variable `name`, if it is set.
+pub(crate) fn env_var<T: FromStr>(name: &str) -> Option<T> {
+    env::var(name).ok().map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid value for {}: {}", name, value))
+    })
+}
+
+/// Decodes the body of a response from the proof service.
+fn decode_response<O: DeserializeOwned>(endpoint: &str, text: &str) -> Result<O> {
+    serde_json::from_str(text)
+        .map_err(|e| anyhow!("invalid response from {}: {}: {}", endpoint, e, text))
+}
+
+/// Resolves the host of the proof service ahead of time so that the client does not repeat the
+/// DNS lookup for every request. Returns `None` if the url already specifies a port, such as a
+/// local emulator of the service.
+fn resolve_service_host(url: &str) -> Option<(String, Vec<SocketAddr>)> {
+    let host = url.split("://").last().unwrap();
+    if host.contains(':') {
+        return None;
+    }
+    let sock_addrs = format!("{}:443", host)
+        .to_socket_addrs()
+        .unwrap()
+        .collect::<Vec<_>>();
+    Some((host.to_string(), sock_addrs))
+}
+
 /// A client for connecting to the proof service which can generate proofs remotely.
 #[derive(Debug, Clone)]
 pub struct ProofService {
     client: Client,
     base_url: String,
+    retry: RetryPolicy,
 }
 
 impl ProofService {
@@ -91,17 +219,84 @@ impl ProofService {
 
     /// Creates a new instance of the function service client.
     pub fn new(url: String) -> Self {
-        let host = url.split("://").last().unwrap();
-        let sock_addrs = format!("{}:443", host)
-            .to_socket_addrs()
-            .unwrap()
-            .collect::<Vec<_>>();
+        let mut builder = Client::builder();
+        if let Some((host, sock_addrs)) = resolve_service_host(&url) {
+            builder = builder.resolve_to_addrs(&host, &sock_addrs);
+        }
         Self {
-            client: Client::builder()
-                .resolve_to_addrs(host, &sock_addrs)
-                .build()
-                .unwrap(),
+            client: builder.build().unwrap(),
             base_url: url,
+            retry: RetryPolicy::from_env(),
+        }
+    }
+
+    /// Replaces the policy used to retry failed requests.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Checks whether the service at `PROOF_SERVICE_URL` answers at all.
+    pub fn is_reachable_from_env() -> bool {
+        env::var("PROOF_SERVICE_URL").is_ok_and(|url| Self::is_reachable(&url))
+    }
+
+    /// Checks whether the service at `url` answers at all, without resolving its host ahead of
+    /// time so that an unknown host is reported instead of panicking.
+    pub fn is_reachable(url: &str) -> bool {
+        let reachable = Client::new()
+            .get(url)
+            .timeout(Duration::from_secs(10))
+            .send()
+            .is_ok();
+        debug!(
+            "proof service reachable: url={}, reachable={}",
+            url, reachable
+        );
+        reachable
+    }
+
+    /// Sends the request built by `request` until it succeeds or the retry policy gives up, and
+    /// decodes the response.
+    fn send_json<O>(
+        &self,
+        endpoint: &str,
+        idempotent: bool,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<O>
+    where
+        O: DeserializeOwned,
+    {
+        let mut nb_attempts = 0;
+        loop {
+            nb_attempts += 1;
+            let result = request()
+                .timeout(self.retry.request_timeout)
+                .send()
+                .and_then(|response| response.error_for_status())
+                .and_then(|response| response.text());
+            match result {
+                Ok(text) => {
+                    debug!("response: {:?}", text);
+                    return decode_response(endpoint, &text);
+                }
+                Err(e) if self.retry.should_retry(&e, nb_attempts, idempotent) => {
+                    let backoff = self.retry.backoff(nb_attempts);
+                    debug!(
+                        "request to {} failed, retrying in {:?}: attempt={}, error={:?}",
+                        endpoint, backoff, nb_attempts, e
+                    );
+                    thread::sleep(backoff);
+                }
+                Err(e) => {
+                    return Err(anyhow!(
+                        "request to {} failed after {} attempts: {}",
+                        endpoint,
+                        nb_attempts,
+                        e
+                    ))
+                }
+            }
         }
     }
 
@@ -113,17 +308,15 @@ impl ProofService {
         let endpoint = format!("{}{}", self.base_url, route);
         let token = env::var("PROOF_SERVICE_API_KEY").unwrap_or("".to_string());
         trace!("sending get request: url={}", endpoint);
-        self.client
-            .get(endpoint)
-            .bearer_auth(token)
-            .timeout(Duration::from_secs(300))
-            .send()?
-            .json()
-            .map_err(|e| e.into())
+        self.send_json(&endpoint, true, || {
+            self.client.get(&endpoint).bearer_auth(&token)
+        })
     }
 
-    /// Sends a POST request to the given route with the given input serialized as JSON.
-    fn post_json<I, O>(&self, route: &str, input: I) -> Result<O>
+    /// Sends a POST request to the given route with the given input serialized as JSON. Only
+    /// `idempotent` requests, which do not create anything on the service, are retried on
+    /// timeouts and server errors.
+    fn post_json<I, O>(&self, route: &str, input: I, idempotent: bool) -> Result<O>
     where
         I: Debug + Serialize + Sized,
         O: DeserializeOwned,
@@ -131,16 +324,9 @@ impl ProofService {
         let endpoint = format!("{}{}", self.base_url, route);
         let token = env::var("PROOF_SERVICE_API_KEY").unwrap_or("".to_string());
         trace!("sending post request: url={}, input={:?}", endpoint, input);
-        let response = self
-            .client
-            .post(endpoint)
-            .bearer_auth(token)
-            .timeout(Duration::from_secs(300))
-            .json(&input)
-            .send()?;
-        let text = response.text()?;
-        debug!("response: {:?}", text);
-        Ok(serde_json::from_str(&text).unwrap())
+        self.send_json(&endpoint, idempotent, || {
+            self.client.post(&endpoint).bearer_auth(&token).json(&input)
+        })
     }
 
     /// Submits a request for the service to generate a proof. Returns the proof id.
@@ -149,7 +335,7 @@ impl ProofService {
         request: ProofRequest<L, D>,
     ) -> Result<ProofId> {
         let response: SubmitProofRequestResponse =
-            self.post_json(SUBMIT_PROOF_REQUEST_ROUTE, request)?;
+            self.post_json(SUBMIT_PROOF_REQUEST_ROUTE, request.encode_value()?, false)?;
         Ok(response.proof_id)
     }
 
@@ -158,11 +344,39 @@ impl ProofService {
         &self,
         requests: &[ProofRequest<L, D>],
     ) -> Result<(BatchProofId, Vec<ProofId>)> {
-        let response: SubmitProofBatchRequestResponse =
-            self.post_json(SUBMIT_PROOF_BATCH_REQUEST_ROUTE, requests)?;
+        let response: SubmitProofBatchRequestResponse = self.post_json(
+            SUBMIT_PROOF_BATCH_REQUEST_ROUTE,
+            encode_requests(requests)?,
+            false,
+        )?;
+        check_proof_ids(requests.len(), &response.proof_ids)?;
         Ok((response.proof_batch_id, response.proof_ids))
     }
 
+    /// Submits many requests for the service to generate proofs. The requests are sent in chunks
+    /// of at most `MAX_REQUESTS_PER_BATCH`, so the number of HTTP round trips is independent of the
+    /// size of each chunk. Returns the proof ids in the same order as the requests.
+    pub fn create_proofs<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        requests: &[ProofRequest<L, D>],
+    ) -> Result<Vec<ProofId>> {
+        let mut proof_ids = Vec::with_capacity(requests.len());
+        for chunk in requests.chunks(MAX_REQUESTS_PER_BATCH) {
+            let (_, ids) = self.submit_batch(chunk)?;
+            proof_ids.extend(ids);
+        }
+        Ok(proof_ids)
+    }
+
+    /// Gets the status of many proof requests, one at a time since the service only serves the
+    /// results of a single proof. Returns the responses in the same order as the ids.
+    pub fn get_many<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        ids: &[ProofId],
+    ) -> Result<Vec<GetProofRequestResponse<L, D>>> {
+        ids.iter().map(|id| self.get(*id)).collect()
+    }
+
     /// Gets the status of a proof request with the given proof id.
     pub fn get<L: PlonkParameters<D>, const D: usize>(
         &self,
@@ -171,6 +385,13 @@ impl ProofService {
         self.get_json(&format!("{}/{}", GET_PROOF_REQUEST_ROUTE, id.0))
     }
 
+    /// Gets the status of a proof request with the given proof id, without decoding the proof.
+    pub fn get_status(&self, id: ProofId) -> Result<ProofRequestStatus> {
+        let response: GetProofStatusResponse =
+            self.get_json(&format!("{}/{}", GET_PROOF_REQUEST_ROUTE, id.0))?;
+        Ok(response.status)
+    }
+
     /// Gets the status of a proof request with the given proof id.
     pub fn get_batch<L: PlonkParameters<D>, const D: usize>(
         &self,
@@ -0,0 +1,122 @@
+//! Proving of the BN254 proof wrapping a plonky2 proof, with the gnark verifier built at a wrapper
+//! path.
+//!
+//! By default, `GnarkWrapper` spawns the `verifier` binary of the wrapper path. With the
+//! `gnark-ffi` feature, the verifier is built from `plonky2x/verifier` and linked as a static
+//! library, so it runs in-process and deployments only need the compiled circuit and keys at the
+//! wrapper path. Either way, the proving key is loaded while the plonky2 proof is generated, and
+//! the proof is saved to `proof.json` in the current directory.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use super::wrap::WrapperBackend;
+
+/// A gnark prover of the wrapped proofs, loading its proving key.
+#[derive(Debug)]
+pub struct GnarkWrapper {
+    #[cfg(not(feature = "gnark-ffi"))]
+    process: std::process::Child,
+    #[cfg(feature = "gnark-ffi")]
+    loading: std::thread::JoinHandle<Result<()>>,
+}
+
+impl GnarkWrapper {
+    /// Starts loading the circuit and the proving key of `backend` from `wrapper_path`.
+    pub fn load(wrapper_path: &str, backend: WrapperBackend) -> Result<Self> {
+        let data_path = PathBuf::from(wrapper_path);
+
+        #[cfg(not(feature = "gnark-ffi"))]
+        {
+            use std::process::{Command, Stdio};
+
+            let process = Command::new(data_path.join("verifier"))
+                .arg("-prove")
+                .arg("-system")
+                .arg(backend.system())
+                .arg("-data")
+                .arg(&data_path)
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .stdin(Stdio::piped())
+                .spawn()?;
+            Ok(Self { process })
+        }
+
+        #[cfg(feature = "gnark-ffi")]
+        {
+            let loading =
+                std::thread::spawn(move || ffi::load_prover(&data_path, backend.system()));
+            Ok(Self { loading })
+        }
+    }
+
+    /// Proves the wrapped proof saved at `wrapped_path` by `WrappedOutput::save`.
+    pub fn prove<P: AsRef<Path>>(self, wrapped_path: P) -> Result<()> {
+        #[cfg(not(feature = "gnark-ffi"))]
+        {
+            use std::io::Write;
+
+            let mut process = self.process;
+            let mut stdin = process
+                .stdin
+                .take()
+                .ok_or_else(|| anyhow::anyhow!("failed to open stdin of the gnark wrapper"))?;
+            writeln!(stdin, "{}", wrapped_path.as_ref().display())?;
+            drop(stdin);
+            let status = process.wait()?;
+            anyhow::ensure!(status.success(), "gnark wrapper failed with {}", status);
+            Ok(())
+        }
+
+        #[cfg(feature = "gnark-ffi")]
+        {
+            self.loading
+                .join()
+                .map_err(|_| anyhow::anyhow!("loading the gnark prover panicked"))??;
+            ffi::prove(wrapped_path.as_ref())
+        }
+    }
+}
+
+#[cfg(feature = "gnark-ffi")]
+mod ffi {
+    use std::ffi::{c_char, CStr, CString};
+    use std::path::Path;
+
+    use anyhow::{anyhow, Result};
+
+    extern "C" {
+        fn gnark_load_prover(data_path: *const c_char, system: *const c_char) -> *mut c_char;
+        fn gnark_prove(circuit_path: *const c_char) -> *mut c_char;
+        fn gnark_free_string(s: *mut c_char);
+    }
+
+    fn path_string(path: &Path) -> Result<CString> {
+        Ok(CString::new(path.to_string_lossy().into_owned())?)
+    }
+
+    /// Converts the error message returned by an exported function, if any.
+    fn result(error: *mut c_char) -> Result<()> {
+        if error.is_null() {
+            return Ok(());
+        }
+        let message = unsafe { CStr::from_ptr(error) }
+            .to_string_lossy()
+            .into_owned();
+        unsafe { gnark_free_string(error) };
+        Err(anyhow!("gnark wrapper failed: {}", message))
+    }
+
+    pub(super) fn load_prover(data_path: &Path, system: &str) -> Result<()> {
+        let data_path = path_string(data_path)?;
+        let system = CString::new(system)?;
+        result(unsafe { gnark_load_prover(data_path.as_ptr(), system.as_ptr()) })
+    }
+
+    pub(super) fn prove(circuit_path: &Path) -> Result<()> {
+        let circuit_path = path_string(circuit_path)?;
+        result(unsafe { gnark_prove(circuit_path.as_ptr()) })
+    }
+}
@@ -1,3 +1,4 @@
+pub mod gnark;
 pub mod plonky2_config;
 pub mod poseidon_bn128;
 pub mod poseidon_bn128_constants;
@@ -2,6 +2,7 @@ use std::fs::{self, File};
 use std::path::Path;
 
 use anyhow::Result;
+use clap::ValueEnum;
 use log::{debug, info};
 use plonky2::iop::witness::{PartialWitness, WitnessWrite};
 use plonky2::plonk::circuit_data::{
@@ -9,11 +10,36 @@ use plonky2::plonk::circuit_data::{
 };
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::backend::circuit::{CircuitBuild, PlonkParameters};
 use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::vars::{ByteVariable, CircuitVariable, Variable};
+
+/// The proof system of the BN254 proof which the gnark verifier wraps the plonky2 proof into for
+/// on-chain verification.
+///
+/// PLONK uses the universal Aztec Ignition setup. Groth16 needs a setup specific to the gnark
+/// verifier circuit, but its proofs cost ~200k gas to verify on-chain, less than PLONK proofs,
+/// and can be verified by existing Groth16 verifier contracts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum WrapperBackend {
+    #[default]
+    Plonk,
+    Groth16,
+}
+
+impl WrapperBackend {
+    /// The name of the proof system, as given to the `-system` flag of the gnark verifier.
+    pub fn system(&self) -> &'static str {
+        match self {
+            WrapperBackend::Plonk => "plonk",
+            WrapperBackend::Groth16 => "groth16",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct WrappedCircuit<
     InnerParameters: PlonkParameters<D>,
@@ -32,6 +58,7 @@ pub struct WrappedCircuit<
     pub wrapper_circuit: CircuitBuild<OuterParameters, D>,
     proof_target: ProofWithPublicInputsTarget<D>,
     verifier_target: VerifierCircuitTarget,
+    backend: WrapperBackend,
 }
 
 impl<
@@ -42,7 +69,17 @@ impl<
 where
     <InnerParameters::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<InnerParameters::Field>,
 {
+    /// Builds the wrapper of `circuit` for the default `WrapperBackend`.
     pub fn build(circuit: CircuitBuild<InnerParameters, D>) -> Self {
+        Self::build_with_backend(circuit, WrapperBackend::default())
+    }
+
+    /// Builds the wrapper of `circuit` whose proofs are wrapped by `backend`. The plonky2 circuits
+    /// are the same for every backend, so the wrapper circuit digest does not depend on it.
+    pub fn build_with_backend(
+        circuit: CircuitBuild<InnerParameters, D>,
+        backend: WrapperBackend,
+    ) -> Self {
         // Standartize the public inputs/outputs to their hash and verify the circuit recursively.
         let mut hash_builder = CircuitBuilder::<InnerParameters, D>::new();
         let circuit_proof_target = hash_builder.add_virtual_proof_with_pis(&circuit.data.common);
@@ -169,9 +206,20 @@ where
             wrapper_circuit,
             proof_target,
             verifier_target,
+            backend,
         }
     }
 
+    /// The inner circuit whose proofs are wrapped.
+    pub fn circuit(&self) -> &CircuitBuild<InnerParameters, D> {
+        &self.circuit
+    }
+
+    /// The proof system which wraps the proofs of the wrapper circuit.
+    pub fn backend(&self) -> WrapperBackend {
+        self.backend
+    }
+
     pub fn prove(
         &self,
         inner_proof: &ProofWithPublicInputs<InnerParameters::Field, InnerParameters::Config, D>,
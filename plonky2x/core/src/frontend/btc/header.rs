@@ -0,0 +1,197 @@
+//! Verification of Bitcoin block headers for SPV light clients.
+//!
+//! A header is 80 bytes: the version, the hash of the previous block, the merkle root of the
+//! transactions, the timestamp, the compact target `bits` and the nonce, with the integers in
+//! little endian. The hash of a block is the double SHA-256 of its header, which is displayed
+//! reversed, and must be at most the target as a little endian integer.
+//!
+//! The work of a block is the expected number of hashes to meet its target,
+//! `2^256 / (target + 1)`. Only the proof of work is checked: the retargeting of the difficulty
+//! every 2016 blocks is left to the caller, who can compare the targets returned for the headers.
+
+use ethers::types::U256;
+
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::*;
+
+/// The length of a serialized header.
+pub const BTC_HEADER_LEN: usize = 80;
+
+/// The offsets of the previous block hash and of the compact target in a header.
+const PREV_HASH_OFFSET: usize = 4;
+const BITS_OFFSET: usize = 72;
+
+/// The range of the exponents of the compact targets supported, so that the mantissa is shifted
+/// left by whole bytes and stays within 256 bits.
+const MIN_EXPONENT: usize = 3;
+const MAX_EXPONENT: usize = 32;
+
+/// A header whose proof of work has been checked.
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(BtcVerifiedHeader)]
+#[value_derive(PartialEq, Eq)]
+pub struct BtcVerifiedHeaderVariable {
+    /// The hash of the block, in internal byte order.
+    pub hash: Bytes32Variable,
+    /// The hash of the previous block, in internal byte order.
+    pub prev_hash: Bytes32Variable,
+    pub target: U256Variable,
+    pub work: U256Variable,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Computes the double SHA-256 hash of a header, in internal byte order.
+    pub fn btc_header_hash(&mut self, header: &BytesVariable<BTC_HEADER_LEN>) -> Bytes32Variable {
+        let digest = self.curta_sha256(&header.0);
+        self.curta_sha256(&digest.as_bytes())
+    }
+
+    /// Expands the compact target `bits` of a header, in little endian, into the target
+    /// `mantissa * 256^(exponent - 3)`. The mantissa must not have its sign bit set, and the
+    /// exponent must be between 3 and 32, which covers every target of the main network.
+    pub fn btc_bits_to_target(&mut self, bits: &BytesVariable<4>) -> U256Variable {
+        let true_variable = self._true();
+        let false_variable = self._false();
+        let zero_byte = self.constant::<ByteVariable>(0);
+        let [low, middle, high, exponent] = bits.0;
+        self.assert_is_equal(high.as_be_bits()[0], false_variable);
+        let mantissa = [high, middle, low];
+
+        // Place the mantissa at the position given by the exponent in a big endian word.
+        let exponent = exponent.to_variable(self);
+        let mut target = [zero_byte; 32];
+        let mut is_supported = false_variable;
+        for e in MIN_EXPONENT..=MAX_EXPONENT {
+            let e_variable = self.constant::<Variable>(L::Field::from_canonical_usize(e));
+            let is_exponent = self.is_equal(exponent, e_variable);
+            is_supported = self.or(is_supported, is_exponent);
+            for (k, byte) in mantissa.iter().enumerate() {
+                let index = 32 - e + k;
+                target[index] = self.select(is_exponent, *byte, target[index]);
+            }
+        }
+        self.assert_is_equal(is_supported, true_variable);
+        U256Variable::decode(self, &target)
+    }
+
+    /// Computes the work of a block with the given target, `2^256 / (target + 1)`, as
+    /// `(2^256 - 1 - target) / (target + 1) + 1` so that it fits in 256 bits.
+    pub fn btc_work(&mut self, target: U256Variable) -> U256Variable {
+        let one = self.one::<U256Variable>();
+        let max = self.constant::<U256Variable>(U256::MAX);
+        let complement = self.sub(max, target);
+        let target_plus_one = self.add(target, one);
+        let quotient = self.div(complement, target_plus_one);
+        self.add(quotient, one)
+    }
+
+    /// Checks that the hash of a header meets the target of its `bits`, and returns its hash, the
+    /// hash of its previous block, its target and its work.
+    pub fn btc_verify_header(
+        &mut self,
+        header: &BytesVariable<BTC_HEADER_LEN>,
+    ) -> BtcVerifiedHeaderVariable {
+        let true_variable = self._true();
+        let hash = self.btc_header_hash(header);
+        let prev_hash = header.0[PREV_HASH_OFFSET..PREV_HASH_OFFSET + 32].to_vec();
+        let bits = header.0[BITS_OFFSET..BITS_OFFSET + 4].to_vec();
+        let target = self.btc_bits_to_target(&BytesVariable(bits.try_into().unwrap()));
+
+        // The hash is compared to the target as a little endian integer.
+        let mut hash_be = hash.as_bytes();
+        hash_be.reverse();
+        let hash_value = U256Variable::decode(self, &hash_be);
+        let meets_target = self.lte(hash_value, target);
+        self.assert_is_equal(meets_target, true_variable);
+
+        BtcVerifiedHeaderVariable {
+            hash,
+            prev_hash: Bytes32Variable(BytesVariable(prev_hash.try_into().unwrap())),
+            target,
+            work: self.btc_work(target),
+        }
+    }
+
+    /// Verifies a run of headers extending the block `prev_hash`: each header must meet its
+    /// target and point to the previous one. Returns the verified headers and their cumulative
+    /// work.
+    pub fn btc_verify_header_chain<const N: usize>(
+        &mut self,
+        prev_hash: Bytes32Variable,
+        headers: &ArrayVariable<BytesVariable<BTC_HEADER_LEN>, N>,
+    ) -> (Vec<BtcVerifiedHeaderVariable>, U256Variable) {
+        let mut prev_hash = prev_hash;
+        let mut chain_work = self.zero::<U256Variable>();
+        let mut verified = Vec::with_capacity(N);
+        for header in headers.as_vec().iter() {
+            let header = self.btc_verify_header(header);
+            self.assert_is_equal(header.prev_hash, prev_hash);
+            chain_work = self.add(chain_work, header.work);
+            prev_hash = header.hash;
+            verified.push(header);
+        }
+        (verified, chain_work)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::*;
+
+    /// The headers of the first three blocks of the main network.
+    const HEADERS: [&str; 3] = [
+        "0x0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c",
+        "0x010000006fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000982051fd1e4ba744bbbe680e1fee14677ba1a3c3540bf7b1cdb606e857233e0e61bc6649ffff001d01e36299",
+        "0x010000004860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000d5fdcc541e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9bb0bc6649ffff001d08d2bd61",
+    ];
+
+    /// The displayed hash of the third block.
+    const LAST_HASH: &str = "0x000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd";
+
+    fn verify(headers: Vec<[u8; BTC_HEADER_LEN]>) -> (H256, U256, U256) {
+        let mut builder = DefaultBuilder::new();
+        let prev_hash = builder.read::<Bytes32Variable>();
+        let headers_variable = builder.read::<ArrayVariable<BytesVariable<BTC_HEADER_LEN>, 3>>();
+        let (verified, chain_work) = builder.btc_verify_header_chain(prev_hash, &headers_variable);
+        builder.write(verified[2].hash);
+        builder.write(verified[2].target);
+        builder.write(chain_work);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(H256::zero());
+        input.write::<ArrayVariable<BytesVariable<BTC_HEADER_LEN>, 3>>(headers);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        (
+            output.read::<Bytes32Variable>(),
+            output.read::<U256Variable>(),
+            output.read::<U256Variable>(),
+        )
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_btc_verify_header_chain() {
+        let headers = HEADERS.iter().map(|header| bytes!(header)).collect();
+        let (last_hash, target, chain_work) = verify(headers);
+
+        let mut expected_hash = bytes32!(LAST_HASH);
+        expected_hash.0.reverse();
+        assert_eq!(last_hash, expected_hash);
+        assert_eq!(target, U256::from(0xffff) << (8 * (0x1d - 3)));
+        assert_eq!(chain_work, U256::from(3 * 0x100010001u64));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    #[should_panic]
+    fn test_btc_verify_header_chain_wrong_nonce() {
+        let mut headers: Vec<[u8; BTC_HEADER_LEN]> =
+            HEADERS.iter().map(|header| bytes!(header)).collect();
+        headers[2][BTC_HEADER_LEN - 1] ^= 1;
+        verify(headers);
+    }
+}
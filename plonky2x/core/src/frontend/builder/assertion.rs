@@ -0,0 +1,49 @@
+//! Assertions carrying a name and the source location they were made at, so that a failing
+//! assertion can be reported by `CircuitBuild::debug_prove`.
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::panic::Location;
+
+use plonky2::iop::target::Target;
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::vars::CircuitVariable;
+
+/// An equality asserted with `assert_is_equal_named`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedAssertion {
+    pub name: String,
+    /// The source location of the assertion, as `file:line:column`.
+    pub location: String,
+    /// The pairs of targets asserted to be equal.
+    pub targets: Vec<(Target, Target)>,
+}
+
+impl Display for NamedAssertion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "`{}` at {}", self.name, self.location)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Fails if i1 != i2, like `assert_is_equal`. The name and the location of the assertion are
+    /// kept in the circuit to report the assertion if it fails in `CircuitBuild::debug_prove`.
+    #[track_caller]
+    pub fn assert_is_equal_named<V: CircuitVariable>(&mut self, i1: V, i2: V, name: &str) {
+        let location = Location::caller();
+        let targets = i1
+            .targets()
+            .into_iter()
+            .zip(i2.targets())
+            .collect::<Vec<_>>();
+        for (t1, t2) in targets.iter() {
+            self.api.connect(*t1, *t2);
+        }
+        self.named_assertions.push(NamedAssertion {
+            name: name.to_string(),
+            location: location.to_string(),
+            targets,
+        });
+    }
+}
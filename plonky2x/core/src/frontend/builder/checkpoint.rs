@@ -0,0 +1,155 @@
+//! Poseidon-committed checkpoints of intermediate circuit state.
+//!
+//! A long pipeline can be split across several circuits by committing the state at the end of one
+//! circuit to a single Poseidon hash, which is the only public value handed to the next circuit.
+//! The successor circuit witnesses the full state privately and opens the checkpoint by checking
+//! that the state hashes to the commitment.
+
+use plonky2::hash::hash_types::HashOut;
+use plonky2::hash::hashing::hash_n_to_hash_no_pad;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::plonk::proof::ProofWithPublicInputsTarget;
+
+use super::{CircuitBuilder, CircuitIO};
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::hash::poseidon::poseidon256::PoseidonHashOutVariable;
+use crate::frontend::vars::CircuitVariable;
+use crate::utils::proof::ProofWithPublicInputsTargetUtils;
+
+/// Computes the commitment to a checkpoint outside of the circuit.
+pub fn checkpoint_commitment<L: PlonkParameters<D>, V: CircuitVariable, const D: usize>(
+    state: V::ValueType<L::Field>,
+) -> HashOut<L::Field>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    hash_n_to_hash_no_pad::<
+        L::Field,
+        <<<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher as Hasher<
+            <L as PlonkParameters<D>>::Field,
+        >>::Permutation,
+    >(&V::elements::<L::Field>(state))
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Computes the commitment to a checkpoint of `state`.
+    pub fn checkpoint_commitment<V: CircuitVariable>(
+        &mut self,
+        state: &V,
+    ) -> PoseidonHashOutVariable
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        self.poseidon_hash(&state.variables())
+    }
+
+    /// Commits to `state` and writes the commitment to the circuit output, so that a successor
+    /// circuit can open the checkpoint with `open_checkpoint_from_proof` if it is the last output.
+    pub fn commit_checkpoint<V: CircuitVariable>(&mut self, state: &V) -> PoseidonHashOutVariable
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let commitment = self.checkpoint_commitment(state);
+        match self.io {
+            CircuitIO::RecursiveProofs(_) => self.proof_write(commitment.clone()),
+            _ => self.write(commitment.clone()),
+        }
+        commitment
+    }
+
+    /// Reads the state of a checkpoint from the circuit input and checks it against `commitment`.
+    ///
+    /// The state is only private if the circuit reads recursive proofs, whose circuit inputs are
+    /// not registered as public inputs. With field io, the state would be public as well.
+    pub fn open_checkpoint<V: CircuitVariable>(&mut self, commitment: PoseidonHashOutVariable) -> V
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let state = self.read::<V>();
+        let expected_commitment = self.checkpoint_commitment(&state);
+        self.assert_is_equal(expected_commitment, commitment);
+        state
+    }
+
+    /// Opens the checkpoint committed to by the last public inputs of `proof`. The proof must be
+    /// verified separately.
+    pub fn open_checkpoint_from_proof<V: CircuitVariable>(
+        &mut self,
+        proof: &ProofWithPublicInputsTarget<D>,
+    ) -> V
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let commitment = proof.read_end_from_pis::<PoseidonHashOutVariable>();
+        self.open_checkpoint(commitment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::circuit::DefaultParameters;
+    use crate::prelude::*;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    #[test]
+    fn test_checkpoint_across_circuits() {
+        type State = ArrayVariable<U64Variable, 16>;
+
+        // The predecessor derives a large state from a small input and only exposes its
+        // commitment.
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let seed = builder.read::<U64Variable>();
+        let state = (0..16)
+            .map(|i| {
+                let offset = builder.constant::<U64Variable>(i);
+                builder.add(seed, offset)
+            })
+            .collect::<Vec<_>>();
+        let state = State::new(state);
+        builder.commit_checkpoint(&state);
+        let predecessor = builder.build();
+
+        let mut input = predecessor.input();
+        input.write::<U64Variable>(100);
+        let (predecessor_proof, mut output) = predecessor.prove(&input);
+        predecessor.verify(&predecessor_proof, &input, &output);
+
+        let state_value = (100..116).collect::<Vec<u64>>();
+        assert_eq!(
+            output.read::<PoseidonHashOutVariable>(),
+            checkpoint_commitment::<L, State, D>(state_value.clone())
+        );
+
+        // The successor verifies the predecessor and continues from the opened state.
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let verifier_data = builder.constant_verifier_data::<L>(&predecessor.data);
+        let proof = builder.proof_read(&predecessor.data.common);
+        builder.verify_proof::<L>(&proof, &verifier_data, &predecessor.data.common);
+        let state = builder.open_checkpoint_from_proof::<State>(&proof);
+        let sum = state
+            .as_vec()
+            .into_iter()
+            .reduce(|acc, x| builder.add(acc, x))
+            .unwrap();
+        builder.proof_write(sum);
+        let successor = builder.build();
+
+        let mut input = successor.input();
+        input.proof_write(predecessor_proof.clone());
+        input.write::<State>(state_value);
+        let (proof, mut output) = successor.prove(&input);
+        successor.data.verify(proof.clone()).unwrap();
+
+        // Only the sum is public.
+        assert_eq!(proof.public_inputs.len(), U64Variable::nb_elements());
+        assert_eq!(output.proof_read::<U64Variable>(), (100..116).sum::<u64>());
+    }
+}
@@ -0,0 +1,163 @@
+//! Byte lookup tables shared by every gadget of a circuit.
+//!
+//! Gadgets enqueue lookups into one of the [`ByteTable`]s while the circuit is being defined.
+//! When the circuit is built, each table that received at least one lookup is added to the
+//! circuit exactly once and all its queued lookups are constrained against it, so gadgets never
+//! have to manage tables of their own.
+
+use alloc::collections::BTreeMap;
+use std::sync::Arc;
+
+use plonky2::iop::target::Target;
+
+use super::CircuitBuilder;
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::vars::Variable;
+
+/// A lookup table over bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ByteTable {
+    /// Maps `256 * lhs + rhs` to `lhs & rhs`.
+    And,
+    /// Maps `256 * lhs + rhs` to `lhs | rhs`.
+    Or,
+    /// Maps `256 * lhs + rhs` to `lhs ^ rhs`.
+    Xor,
+    /// Maps every value below `2^8` to itself.
+    Byte,
+    /// Maps every value below `2^7` to itself.
+    Ascii,
+}
+
+impl ByteTable {
+    /// The `(input, output)` pairs of the table.
+    pub fn pairs(&self) -> Vec<(u16, u16)> {
+        let binary = |op: fn(u8, u8) -> u8| {
+            (0..=u16::MAX)
+                .map(|input| {
+                    let [lhs, rhs] = input.to_be_bytes();
+                    (input, op(lhs, rhs) as u16)
+                })
+                .collect()
+        };
+        match self {
+            ByteTable::And => binary(|lhs, rhs| lhs & rhs),
+            ByteTable::Or => binary(|lhs, rhs| lhs | rhs),
+            ByteTable::Xor => binary(|lhs, rhs| lhs ^ rhs),
+            ByteTable::Byte => (0..1 << 8).map(|x| (x, x)).collect(),
+            ByteTable::Ascii => (0..1 << 7).map(|x| (x, x)).collect(),
+        }
+    }
+}
+
+/// The lookups enqueued into each table, as pairs of input and output targets.
+#[derive(Debug, Clone, Default)]
+pub struct ByteLookups {
+    requests: BTreeMap<ByteTable, Vec<(Target, Target)>>,
+}
+
+impl ByteLookups {
+    /// The number of tables that will be added to the circuit.
+    pub fn nb_tables(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// The number of lookups enqueued into `table`.
+    pub fn nb_lookups(&self, table: ByteTable) -> usize {
+        self.requests
+            .get(&table)
+            .map_or(0, |requests| requests.len())
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Enqueues a lookup of `input` into `table` and returns the output of the lookup. The proof
+    /// fails if `input` is not in the table.
+    pub fn byte_lookup(&mut self, table: ByteTable, input: Variable) -> Variable {
+        let output = self.api.add_virtual_target();
+        self.byte_lookups
+            .requests
+            .entry(table)
+            .or_default()
+            .push((input.0, output));
+        Variable(output)
+    }
+
+    /// Asserts that `value` is less than `2^8`.
+    pub fn assert_is_byte(&mut self, value: Variable) {
+        self.byte_lookup(ByteTable::Byte, value);
+    }
+
+    /// Asserts that `value` is less than `2^7`, i.e. that it is an ASCII character.
+    pub fn assert_is_ascii(&mut self, value: Variable) {
+        self.byte_lookup(ByteTable::Ascii, value);
+    }
+
+    /// Adds each table that was used to the circuit and constrains its lookups.
+    pub(crate) fn constrain_byte_lookups(&mut self) {
+        let requests = core::mem::take(&mut self.byte_lookups.requests);
+        for (table, lookups) in requests {
+            let index = self
+                .api
+                .add_lookup_table_from_pairs(Arc::new(table.pairs()));
+            for (input, output) in lookups {
+                let result = self.api.add_lookup_from_index(input, index);
+                self.api.connect(result, output);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+
+    use super::*;
+    use crate::prelude::*;
+
+    type L = DefaultParameters;
+    type F = <L as PlonkParameters<D>>::Field;
+    const D: usize = 2;
+
+    #[test]
+    fn test_byte_lookups() {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let x = builder.read::<Variable>();
+        let y = builder.read::<Variable>();
+        builder.assert_is_byte(x);
+        builder.assert_is_byte(y);
+        builder.assert_is_ascii(y);
+        let shift = builder.constant::<Variable>(F::from_canonical_u64(256));
+        let x_shifted = builder.mul(x, shift);
+        let input = builder.add(x_shifted, y);
+        let x_xor_y = builder.byte_lookup(ByteTable::Xor, input);
+        builder.write(x_xor_y);
+
+        assert_eq!(builder.byte_lookups.nb_tables(), 3);
+        assert_eq!(builder.byte_lookups.nb_lookups(ByteTable::Byte), 2);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<Variable>(F::from_canonical_u64(0xa5));
+        input.write::<Variable>(F::from_canonical_u64(0x3c));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(
+            output.read::<Variable>(),
+            F::from_canonical_u64(0xa5 ^ 0x3c)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_byte_lookups_out_of_range() {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let x = builder.read::<Variable>();
+        builder.assert_is_ascii(x);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<Variable>(F::from_canonical_u64(0x80));
+        circuit.prove(&input);
+    }
+}
@@ -1,7 +1,13 @@
+pub mod assertion;
 mod boolean;
+pub mod checkpoint;
 pub mod io;
+pub mod lookup;
 pub mod permutation;
+pub mod profile;
 mod proof;
+pub mod search;
+pub mod sort;
 pub mod watch;
 
 use alloc::collections::BTreeMap;
@@ -21,14 +27,18 @@ use starkyx::machine::hash::sha::sha256::SHA256;
 use starkyx::machine::hash::sha::sha512::SHA512;
 use tokio::runtime::Runtime;
 
+use self::assertion::NamedAssertion;
 pub use self::io::CircuitIO;
+use self::lookup::ByteLookups;
+use self::profile::ScopeProfiler;
 use super::ecc::curve25519::curta::accelerator::EcOpAccelerator;
 use super::hash::blake2::curta::BLAKE2BAccelerator;
 use super::hash::sha::sha256::curta::SHA256Accelerator;
 use super::hash::sha::sha512::curta::SHA512Accelerator;
 use super::hint::HintGenerator;
-use super::vars::EvmVariable;
+use super::vars::BitDecomposition;
 use crate::backend::circuit::{CircuitBuild, DefaultParameters, MockCircuitBuild, PlonkParameters};
+use crate::frontend::eth::beacon::fork::BeaconFork;
 use crate::frontend::hint::asynchronous::generator::AsyncHintDataRef;
 use crate::frontend::vars::{BoolVariable, CircuitVariable, Variable};
 use crate::prelude::ArrayVariable;
@@ -41,11 +51,15 @@ pub struct CircuitBuilder<L: PlonkParameters<D>, const D: usize> {
     pub execution_client: Option<Provider<Http>>,
     pub chain_id: Option<u64>,
     pub beacon_client: Option<BeaconClient>,
+    pub beacon_fork: BeaconFork,
     pub debug: bool,
     pub debug_variables: HashMap<usize, String>,
     pub(crate) hints: Vec<Box<dyn HintGenerator<L, D>>>,
     pub(crate) async_hints: Vec<AsyncHintDataRef<L, D>>,
     pub(crate) async_hints_indices: Vec<usize>,
+    pub(crate) byte_lookups: ByteLookups,
+    pub(crate) profiler: ScopeProfiler,
+    pub(crate) named_assertions: Vec<NamedAssertion>,
 
     pub blake2b_accelerator: Option<BLAKE2BAccelerator>,
     pub sha256_accelerator: Option<SHA256Accelerator>,
@@ -66,12 +80,14 @@ impl DefaultBuilder {
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     /// Creates a new builder.
     pub fn new() -> Self {
+        let () = L::FIELD_BITS_CHECK;
         let config = CircuitConfig::standard_recursion_config();
         let api = CircuitAPI::new(config);
         let mut builder = Self {
             api,
             io: CircuitIO::new(),
             beacon_client: None,
+            beacon_fork: BeaconFork::default(),
             execution_client: None,
             chain_id: None,
             debug: false,
@@ -79,6 +95,9 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             hints: Vec::new(),
             async_hints: Vec::new(),
             async_hints_indices: Vec::new(),
+            byte_lookups: ByteLookups::default(),
+            profiler: ScopeProfiler::default(),
+            named_assertions: Vec::new(),
             blake2b_accelerator: None,
             sha256_accelerator: None,
             sha512_accelerator: None,
@@ -131,6 +150,11 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         self.beacon_client = Some(client);
     }
 
+    /// Sets the fork whose beacon state layout the beacon gadgets prove against.
+    pub fn set_beacon_fork(&mut self, fork: BeaconFork) {
+        self.beacon_fork = fork;
+    }
+
     /// Adds all the constraints nedded before building the circuit and registering hints.
     fn pre_build(&mut self) {
         let blake2b_accelerator = self.blake2b_accelerator.clone();
@@ -153,6 +177,8 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             self.curta_constrain_ec_op(accelerator);
         }
 
+        self.constrain_byte_lookups();
+
         for (index, gen_ref) in self
             .async_hints_indices
             .iter()
@@ -242,12 +268,18 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     /// Build the circuit.
     pub fn build(mut self) -> CircuitBuild<L, D> {
         self.pre_build();
+        self.build_prepared()
+    }
+
+    /// Builds the circuit once `pre_build` has run.
+    fn build_prepared(self) -> CircuitBuild<L, D> {
         let data = self.api.build();
         let async_hints = Self::async_hint_map(&data.prover_only.generators, self.async_hints);
         CircuitBuild {
             data,
             io: self.io,
             async_hints,
+            named_assertions: self.named_assertions,
         }
     }
 
@@ -262,6 +294,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
                 data,
                 io: self.io,
                 async_hints,
+                named_assertions: self.named_assertions,
             },
             success,
         )
@@ -393,13 +426,21 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         }
     }
 
-    pub fn to_le_bits<V: EvmVariable>(&mut self, variable: V) -> Vec<BoolVariable> {
+    pub fn to_le_bits<V: BitDecomposition>(&mut self, variable: V) -> Vec<BoolVariable> {
         variable.to_le_bits(self)
     }
 
-    pub fn to_be_bits<V: EvmVariable>(&mut self, variable: V) -> Vec<BoolVariable> {
+    pub fn to_be_bits<V: BitDecomposition>(&mut self, variable: V) -> Vec<BoolVariable> {
         variable.to_be_bits(self)
     }
+
+    pub fn from_le_bits<V: BitDecomposition>(&mut self, bits: &[BoolVariable]) -> V {
+        V::from_le_bits(bits, self)
+    }
+
+    pub fn from_be_bits<V: BitDecomposition>(&mut self, bits: &[BoolVariable]) -> V {
+        V::from_be_bits(bits, self)
+    }
 }
 
 impl<L: PlonkParameters<D>, const D: usize> Default for CircuitBuilder<L, D> {
@@ -0,0 +1,248 @@
+//! Attribution of the rows of a circuit to named scopes.
+//!
+//! Gadgets are wrapped in scopes with `push_scope` and `pop_scope`, and `build_with_profile`
+//! reports the gate rows added inside each scope. Rows are the cost of a plonky2 circuit: every
+//! row is checked against the constraints of the whole gate set, so the number of constraints per
+//! row is the same for every scope and the rows are what a gadget can save. Gates which share rows,
+//! like arithmetic operations, are attributed to the scope which opened the row.
+//!
+//! The report prints as a tree, and `CircuitProfile::folded` renders it in the folded stack format
+//! read by flamegraph tools.
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::backend::circuit::{CircuitBuild, PlonkParameters};
+use crate::frontend::builder::CircuitBuilder;
+
+/// The rows added by a scope of a circuit, and by the scopes it opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeProfile {
+    pub name: String,
+    /// The number of times the scope was opened in its parent. Scopes with the same name and
+    /// parent are merged.
+    pub calls: usize,
+    /// The rows added inside the scope, including its children.
+    pub rows: usize,
+    pub children: Vec<ScopeProfile>,
+}
+
+impl ScopeProfile {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            calls: 0,
+            rows: 0,
+            children: Vec::new(),
+        }
+    }
+
+    /// The rows added inside the scope but outside of its children.
+    pub fn self_rows(&self) -> usize {
+        self.rows - self.children.iter().map(|child| child.rows).sum::<usize>()
+    }
+
+    /// Returns the child scope with the given name, if any.
+    pub fn child(&self, name: &str) -> Option<&ScopeProfile> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Adds `scope` to the children, merging it with a child of the same name.
+    fn merge_child(&mut self, scope: ScopeProfile) {
+        match self
+            .children
+            .iter_mut()
+            .find(|child| child.name == scope.name)
+        {
+            Some(child) => {
+                child.calls += scope.calls;
+                child.rows += scope.rows;
+                for grandchild in scope.children {
+                    child.merge_child(grandchild);
+                }
+            }
+            None => self.children.push(scope),
+        }
+    }
+
+    fn write_folded(&self, prefix: &str, out: &mut String) {
+        let stack = match prefix {
+            "" => self.name.clone(),
+            _ => format!("{};{}", prefix, self.name),
+        };
+        if self.self_rows() > 0 {
+            out.push_str(&format!("{} {}\n", stack, self.self_rows()));
+        }
+        for child in self.children.iter() {
+            child.write_folded(&stack, out);
+        }
+    }
+
+    fn write_tree(&self, f: &mut Formatter<'_>, depth: usize, total: usize) -> FmtResult {
+        let share = match total {
+            0 => 0.0,
+            _ => 100.0 * self.rows as f64 / total as f64,
+        };
+        writeln!(
+            f,
+            "{:indent$}{}: rows={} ({:.1}%), self_rows={}, calls={}",
+            "",
+            self.name,
+            self.rows,
+            share,
+            self.self_rows(),
+            self.calls,
+            indent = 2 * depth
+        )?;
+        let mut children = self.children.iter().collect::<Vec<_>>();
+        children.sort_by(|a, b| b.rows.cmp(&a.rows));
+        for child in children {
+            child.write_tree(f, depth + 1, total)?;
+        }
+        Ok(())
+    }
+}
+
+/// The profile of a circuit, whose root scope holds the rows of the whole circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitProfile {
+    pub root: ScopeProfile,
+    /// The number of rows of the circuit after padding to a power of two.
+    pub degree: usize,
+}
+
+impl CircuitProfile {
+    /// Renders the profile as folded stacks, one `root;scope;child rows` line per scope with rows
+    /// of its own.
+    pub fn folded(&self) -> String {
+        let mut out = String::new();
+        self.root.write_folded("", &mut out);
+        out
+    }
+}
+
+impl Display for CircuitProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(f, "degree={}", self.degree)?;
+        self.root.write_tree(f, 0, self.root.rows)
+    }
+}
+
+/// The scopes opened while building a circuit.
+#[derive(Debug, Clone)]
+pub(crate) struct ScopeProfiler {
+    root: ScopeProfile,
+    /// The open scopes, with the number of rows of the circuit when each was opened.
+    stack: Vec<(ScopeProfile, usize)>,
+}
+
+impl Default for ScopeProfiler {
+    fn default() -> Self {
+        Self {
+            root: ScopeProfile::new("circuit"),
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl ScopeProfiler {
+    fn push(&mut self, name: &str, rows: usize) {
+        self.stack.push((ScopeProfile::new(name), rows));
+    }
+
+    fn pop(&mut self, rows: usize) {
+        let (mut scope, start_rows) = self.stack.pop().expect("no scope to pop");
+        scope.calls += 1;
+        scope.rows = rows - start_rows;
+        match self.stack.last_mut() {
+            Some((parent, _)) => parent.merge_child(scope),
+            None => self.root.merge_child(scope),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Opens a scope to which the rows added until the matching `pop_scope` are attributed.
+    pub fn push_scope(&mut self, name: &str) {
+        let rows = self.api.num_gates();
+        self.profiler.push(name, rows);
+    }
+
+    /// Closes the scope opened by the last `push_scope`.
+    pub fn pop_scope(&mut self) {
+        let rows = self.api.num_gates();
+        self.profiler.pop(rows);
+    }
+
+    /// Runs `f` in a scope named `name`.
+    pub fn with_scope<T>(&mut self, name: &str, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.push_scope(name);
+        let result = f(self);
+        self.pop_scope();
+        result
+    }
+
+    /// Builds the circuit and reports the rows added inside each scope. The constraints added at
+    /// build time, such as the accelerators and the lookups, are attributed to a `build` scope.
+    pub fn build_with_profile(mut self) -> (CircuitBuild<L, D>, CircuitProfile) {
+        assert!(
+            self.profiler.stack.is_empty(),
+            "scopes left open: {:?}",
+            self.profiler
+                .stack
+                .iter()
+                .map(|(scope, _)| scope.name.clone())
+                .collect::<Vec<_>>()
+        );
+        self.push_scope("build");
+        self.pre_build();
+        self.pop_scope();
+        let mut root = self.profiler.root.clone();
+        root.calls = 1;
+        root.rows = self.api.num_gates();
+
+        let circuit = self.build_prepared();
+        let degree = circuit.data.common.degree();
+        (circuit, CircuitProfile { root, degree })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_build_with_profile() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let mut c = a;
+        for _ in 0..2 {
+            c = builder.with_scope("hash", |builder| {
+                builder.push_scope("poseidon");
+                let hash = builder.poseidon_hash(&[c, b]);
+                builder.pop_scope();
+                hash.elements.as_vec()[0]
+            });
+        }
+        builder.push_scope("range_check");
+        builder.api.range_check(c.0, 32);
+        builder.pop_scope();
+        builder.write(c);
+
+        let (circuit, profile) = builder.build_with_profile();
+        let hash = profile.root.child("hash").unwrap();
+        assert_eq!(hash.calls, 2);
+        let poseidon = hash.child("poseidon").unwrap();
+        assert_eq!(poseidon.calls, 2);
+        assert!(poseidon.rows >= 2);
+        assert_eq!(hash.self_rows(), 0);
+        let range_check = profile.root.child("range_check").unwrap();
+        assert!(range_check.rows > 0);
+        assert!(profile.root.rows >= hash.rows + range_check.rows);
+        assert_eq!(profile.degree, circuit.data.common.degree());
+        assert!(profile
+            .folded()
+            .contains(&format!("circuit;hash;poseidon {}", poseidon.rows)));
+        assert!(profile.to_string().contains("range_check: rows="));
+    }
+}
@@ -1,4 +1,6 @@
-use plonky2::plonk::circuit_data::{CircuitData, CommonCircuitData, VerifierCircuitTarget};
+use plonky2::plonk::circuit_data::{
+    CircuitData, CommonCircuitData, VerifierCircuitTarget, VerifierOnlyCircuitData,
+};
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut};
 use plonky2::plonk::proof::ProofWithPublicInputsTarget;
 
@@ -29,17 +31,26 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     pub fn constant_verifier_data<P: PlonkParameters<D, Field = L::Field>>(
         &mut self,
         data: &CircuitData<P::Field, P::Config, D>,
+    ) -> VerifierCircuitTarget {
+        self.constant_verifier_only_data::<P>(
+            data.common.config.fri_config.cap_height,
+            &data.verifier_only,
+        )
+    }
+
+    /// Returns constant verifier data for the given verifier key, whose circuit has the given cap
+    /// height. Useful for circuits which are not built alongside, such as dummy circuits.
+    pub fn constant_verifier_only_data<P: PlonkParameters<D, Field = L::Field>>(
+        &mut self,
+        cap_height: usize,
+        verifier_only: &VerifierOnlyCircuitData<P::Config, D>,
     ) -> VerifierCircuitTarget {
         // Set the verifier data target to be the verifier data, which is a constant.
-        let vd = self
-            .api
-            .add_virtual_verifier_data(data.common.config.fri_config.cap_height);
+        let vd = self.api.add_virtual_verifier_data(cap_height);
 
         // Set the circuit digest.
         for i in 0..vd.circuit_digest.elements.len() {
-            let constant = self
-                .api
-                .constant(data.verifier_only.circuit_digest.to_vec()[i]);
+            let constant = self.api.constant(verifier_only.circuit_digest.to_vec()[i]);
             self.api.connect(vd.circuit_digest.elements[i], constant);
         }
 
@@ -49,7 +60,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             for j in 0..cap.len() {
                 let constant = self
                     .api
-                    .constant(data.verifier_only.constants_sigmas_cap.0[i].to_vec()[j]);
+                    .constant(verifier_only.constants_sigmas_cap.0[i].to_vec()[j]);
                 self.api.connect(cap[j], constant);
             }
         }
@@ -0,0 +1,98 @@
+use itertools::Itertools;
+use plonky2::field::types::Field;
+
+use super::CircuitBuilder;
+use crate::prelude::{ArrayVariable, BoolVariable, PlonkParameters, U64Variable, Variable};
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Fails if `array` is not sorted in non-decreasing order.
+    pub fn assert_is_sorted<const N: usize>(&mut self, array: &ArrayVariable<U64Variable, N>) {
+        let true_v = self._true();
+        for (previous, next) in array.data.iter().tuple_windows() {
+            let is_ordered = self.lte(*previous, *next);
+            self.assert_is_equal(is_ordered, true_v);
+        }
+    }
+
+    /// Searches the sorted `array` for `target`, returning the number of items of `array` that are
+    /// smaller than `target` and whether `target` is in `array`.
+    ///
+    /// The index is the position of the first occurrence of `target` if it is in `array`, and
+    /// otherwise the position at which it would be inserted. The search takes `O(log N)` selects
+    /// from `array`. It assumes that `array` is sorted: use `assert_is_sorted` if this is not
+    /// already guaranteed, as the result is meaningless otherwise.
+    pub fn binary_search<const N: usize>(
+        &mut self,
+        array: &ArrayVariable<U64Variable, N>,
+        target: U64Variable,
+    ) -> (Variable, BoolVariable) {
+        // Pad the array with the maximum value to `2^k > N` items, so that every probe below is in
+        // bounds and the padding is never smaller than `target`.
+        let padded_len = (N + 1).next_power_of_two();
+        let max = self.constant::<U64Variable>(u64::MAX);
+        let mut padded = array.data.clone();
+        padded.resize(padded_len, max);
+
+        // Build the index bit by bit: each step keeps `padded[index - 1] < target`, so the index
+        // ends on the first item that is not smaller than `target`, which is at most `N`.
+        let mut index = self.zero::<Variable>();
+        let zero = self.zero::<Variable>();
+        let mut step = padded_len / 2;
+        while step > 0 {
+            let offset = self.constant::<Variable>(L::Field::from_canonical_usize(step - 1));
+            let probe = self.add(index, offset);
+            let item = self.select_slice_index(&padded, probe);
+            let is_smaller = self.lt(item, target);
+            let step_v = self.constant::<Variable>(L::Field::from_canonical_usize(step));
+            let increment = self.select(is_smaller, step_v, zero);
+            index = self.add(index, increment);
+            step /= 2;
+        }
+
+        // At index `N` there is only padding, which may equal `target`.
+        let item = self.select_slice_index(&padded, index);
+        let is_target = self.is_equal(item, target);
+        let len = self.constant::<Variable>(L::Field::from_canonical_usize(N));
+        let is_end = self.is_equal(index, len);
+        let within_array = self.not(is_end);
+        let found = self.and(is_target, within_array);
+        (index, found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_binary_search() {
+        const N: usize = 7;
+        let values: Vec<u64> = vec![1, 3, 3, 8, 12, 40, u64::MAX];
+        let targets: Vec<(u64, usize, bool)> = vec![
+            (0, 0, false),
+            (3, 1, true),
+            (5, 3, false),
+            (12, 4, true),
+            (41, 6, false),
+            (u64::MAX, 6, true),
+        ];
+
+        let mut builder = DefaultBuilder::new();
+        let array = builder.read::<ArrayVariable<U64Variable, N>>();
+        builder.assert_is_sorted(&array);
+        for (target, index, found) in targets {
+            let target = builder.constant::<U64Variable>(target);
+            let (index_v, found_v) = builder.binary_search(&array, target);
+            let index = builder.constant::<Variable>(GoldilocksField::from_canonical_usize(index));
+            let found = builder.constant::<BoolVariable>(found);
+            builder.assert_is_equal(index_v, index);
+            builder.assert_is_equal(found_v, found);
+        }
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<U64Variable, N>>(values);
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
@@ -0,0 +1,207 @@
+use core::marker::PhantomData;
+
+use itertools::Itertools;
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::challenger::RecursiveChallenger;
+use serde::{Deserialize, Serialize};
+
+use super::CircuitBuilder;
+use crate::frontend::extension::CubicExtensionVariable;
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::vars::{ValueStream, VariableStream};
+use crate::prelude::{
+    ArrayVariable, CircuitVariable, LessThanOrEqual, PlonkParameters, U32Variable, Variable,
+};
+
+/// Sorts items by their keys, given the field elements of every item followed by the keys.
+///
+/// The sort is stable, so items with equal keys keep their order. The sorted items are followed by
+/// their indices in the input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortHint<K> {
+    nb_items: usize,
+    item_size: usize,
+    _marker: PhantomData<K>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize, K: CircuitVariable> Hint<L, D> for SortHint<K>
+where
+    K::ValueType<L::Field>: Ord,
+{
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let items = (0..self.nb_items)
+            .map(|_| input_stream.read_exact(self.item_size).to_vec())
+            .collect_vec();
+        let keys = input_stream.read_vec::<K>(self.nb_items);
+
+        let mut order = (0..self.nb_items).collect_vec();
+        order.sort_by(|i, j| keys[*i].cmp(&keys[*j]));
+        for i in order.iter() {
+            output_stream.write_slice(&items[*i]);
+        }
+        for i in order {
+            output_stream.write_value::<U32Variable>(i as u32);
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the items of `array` sorted by `key_fn`, with items of equal keys in their original
+    /// order.
+    ///
+    /// The sorted array is provided by a hint along with the index of every item in `array`. It is
+    /// constrained by comparing adjacent items by their keys, then by their indices when the keys
+    /// are equal, and by checking that the items paired with their indices are the same multiset
+    /// in both arrays: with challenges `alpha` and `gamma` drawn from both arrays, the products of
+    /// `gamma - sum_j alpha^j * item_j` over the two arrays must be equal. This costs `O(N)`
+    /// comparisons instead of the `O(N^2)` of checking the permutation directly.
+    ///
+    /// The keys of the hint are compared out of circuit by their values, so `key_fn` must return a
+    /// type whose value ordering matches its `LessThanOrEqual` implementation.
+    pub fn sort<V, K, const N: usize>(
+        &mut self,
+        array: &ArrayVariable<V, N>,
+        key_fn: impl Fn(&mut Self, &V) -> K,
+    ) -> ArrayVariable<V, N>
+    where
+        V: CircuitVariable,
+        K: CircuitVariable + LessThanOrEqual<L, D>,
+        K::ValueType<L::Field>: Ord,
+    {
+        let keys = array
+            .data
+            .iter()
+            .map(|item| key_fn(self, item))
+            .collect_vec();
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(array);
+        input_stream.write_slice(&keys);
+        let hint = SortHint::<K> {
+            nb_items: N,
+            item_size: V::nb_elements(),
+            _marker: PhantomData,
+        };
+        let output_stream = self.hint(input_stream, hint);
+        let sorted = output_stream.read::<ArrayVariable<V, N>>(self);
+        let sorted_indices = output_stream.read::<ArrayVariable<U32Variable, N>>(self);
+
+        // Check that the sorted array is ordered by the keys, and by the indices for equal keys.
+        let sorted_keys = sorted
+            .data
+            .iter()
+            .map(|item| key_fn(self, item))
+            .collect_vec();
+        let true_v = self._true();
+        for ((previous, previous_index), (next, next_index)) in sorted_keys
+            .into_iter()
+            .zip(sorted_indices.data.iter().copied())
+            .tuple_windows()
+        {
+            let is_ordered = self.lte(previous.clone(), next.clone());
+            self.assert_is_equal(is_ordered, true_v);
+            let is_tied = self.is_equal(previous, next);
+            let is_not_tied = self.not(is_tied);
+            let is_index_ordered = self.lt(previous_index, next_index);
+            let is_stable = self.or(is_not_tied, is_index_ordered);
+            self.assert_is_equal(is_stable, true_v);
+        }
+
+        // Check that the items paired with their indices are a permutation of the array.
+        let indexed = ArrayVariable::<(V, U32Variable), N>::new(
+            array
+                .data
+                .iter()
+                .enumerate()
+                .map(|(i, item)| (item.clone(), self.constant::<U32Variable>(i as u32)))
+                .collect(),
+        );
+        let sorted_indexed = ArrayVariable::<(V, U32Variable), N>::new(
+            sorted
+                .data
+                .iter()
+                .cloned()
+                .zip(sorted_indices.data.iter().copied())
+                .collect(),
+        );
+        let mut challenger = RecursiveChallenger::<L::Field, PoseidonHash, D>::new(&mut self.api);
+        let targets = indexed
+            .variables()
+            .into_iter()
+            .chain(sorted_indexed.variables())
+            .map(|v| v.0)
+            .collect_vec();
+        challenger.observe_elements(&targets);
+        let challenges = challenger
+            .get_n_challenges(&mut self.api, 6)
+            .into_iter()
+            .map(Variable::from)
+            .collect_vec();
+        let alpha = CubicExtensionVariable::new(challenges[0], challenges[1], challenges[2]);
+        let gamma = CubicExtensionVariable::new(challenges[3], challenges[4], challenges[5]);
+        let product = self.multiset_product(&indexed, alpha, gamma);
+        let sorted_product = self.multiset_product(&sorted_indexed, alpha, gamma);
+        self.assert_is_equal(product, sorted_product);
+
+        sorted
+    }
+
+    /// Returns the product of `gamma - sum_j alpha^j * item_j` over the items of `array`.
+    fn multiset_product<V: CircuitVariable, const N: usize>(
+        &mut self,
+        array: &ArrayVariable<V, N>,
+        alpha: CubicExtensionVariable,
+        gamma: CubicExtensionVariable,
+    ) -> CubicExtensionVariable {
+        let mut product = self.one::<CubicExtensionVariable>();
+        for item in array.data.iter() {
+            let mut compressed = self.zero::<CubicExtensionVariable>();
+            for variable in item.variables().into_iter().rev() {
+                let element = variable.as_cubic_extension(self);
+                compressed = self.mul(compressed, alpha);
+                compressed = self.add(compressed, element);
+            }
+            let term = self.sub(gamma, compressed);
+            product = self.mul(product, term);
+        }
+        product
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_sort() {
+        const N: usize = 8;
+        let values: Vec<(u64, u32)> = vec![
+            (5, 0),
+            (3, 1),
+            (9, 2),
+            (3, 3),
+            (0, 4),
+            (7, 5),
+            (5, 6),
+            (1, 7),
+        ];
+
+        let mut builder = DefaultBuilder::new();
+        let array = builder.read::<ArrayVariable<(U64Variable, U32Variable), N>>();
+        let sorted = builder.sort(&array, |_, item| item.0);
+        builder.write(sorted);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<(U64Variable, U32Variable), N>>(values.clone());
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let mut expected = values;
+        expected.sort_by_key(|item| item.0);
+        assert_eq!(
+            output.read::<ArrayVariable<(U64Variable, U32Variable), N>>(),
+            expected
+        );
+    }
+}
@@ -1,3 +1,9 @@
+//! Logging of the values of variables during witness generation.
+//!
+//! A watch adds a generator which logs the values of its variables once they are set, and no
+//! constraints. Watches are only added once `CircuitBuilder::set_debug` is called, so they can be
+//! left in production circuits at no cost.
+
 use std::marker::PhantomData;
 
 use log::{log, Level};
@@ -20,67 +26,51 @@ pub struct WatchGenerator<L: PlonkParameters<D>, const D: usize, V: CircuitVaria
 }
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Logs the value of `variable` at the info level when it is set during witness generation.
     pub fn watch<V: CircuitVariable>(&mut self, variable: &V, log: &str) {
-        let variable = variable.clone();
-        let log = String::from(log);
-
-        let generator: WatchGenerator<L, D, V> = WatchGenerator {
-            variables: vec![variable],
-            log,
-            log_level: Level::Info,
-            _phantom: PhantomData,
-        };
-        self.add_simple_generator(generator);
+        self.watch_slice_with_level(&[variable.clone()], log, Level::Info);
     }
 
+    /// Logs the value of `variable` at `log_level` when it is set during witness generation.
     pub fn watch_with_level<V: CircuitVariable>(
         &mut self,
         variable: &V,
         log: &str,
         log_level: Level,
     ) {
-        let variable = variable.clone();
-        let log = String::from(log);
-
-        let generator: WatchGenerator<L, D, V> = WatchGenerator {
-            variables: vec![variable],
-            log,
-            log_level,
-            _phantom: PhantomData,
-        };
-        self.add_simple_generator(generator);
+        self.watch_slice_with_level(&[variable.clone()], log, log_level);
     }
 
+    /// Logs the values of `variables` at the info level when they are set during witness
+    /// generation.
     pub fn watch_slice<V: CircuitVariable>(&mut self, variables: &[V], log: &str) {
-        let variables = variables.to_vec();
-        let log = String::from(log);
-
-        let generator: WatchGenerator<L, D, V> = WatchGenerator {
-            variables,
-            log,
-            log_level: Level::Info,
-            _phantom: PhantomData,
-        };
-        self.add_simple_generator(generator);
+        self.watch_slice_with_level(variables, log, Level::Info);
     }
 
+    /// Logs the values of `variables` at `log_level` when they are set during witness generation.
     pub fn watch_slice_with_level<V: CircuitVariable>(
         &mut self,
         variables: &[V],
         log: &str,
         log_level: Level,
     ) {
-        let variables = variables.to_vec();
-        let log = String::from(log);
-
+        if !self.watches_enabled() {
+            return;
+        }
         let generator: WatchGenerator<L, D, V> = WatchGenerator {
-            variables,
-            log,
+            variables: variables.to_vec(),
+            log: String::from(log),
             log_level,
             _phantom: PhantomData,
         };
         self.add_simple_generator(generator);
     }
+
+    /// Whether the watches are added to the circuit, i.e. whether the builder is in debug mode.
+    /// Otherwise they add nothing to the circuit.
+    fn watches_enabled(&self) -> bool {
+        self.debug
+    }
 }
 
 impl<L: PlonkParameters<D>, const D: usize, V: CircuitVariable> WatchGenerator<L, D, V> {
@@ -172,12 +162,15 @@ impl<L: PlonkParameters<D>, V: CircuitVariable, const D: usize> SimpleGenerator<
         witness: &PartitionWitness<L::Field>,
         _out_buffer: &mut GeneratedValues<L::Field>,
     ) {
-        let values: Vec<V::ValueType<L::Field>> =
-            self.variables.iter().map(|x| x.get(witness)).collect();
+        let values: Vec<String> = self
+            .variables
+            .iter()
+            .map(|x| V::format_value::<L::Field>(&x.get(witness)))
+            .collect();
         let formatted_log = if values.len() == 1 {
-            format!("[Watch] {}: {:?}", self.log, values[0])
+            format!("[Watch] {}: {}", self.log, values[0])
         } else {
-            format!("[Watch] {}: {:?}", self.log, values)
+            format!("[Watch] {}: [{}]", self.log, values.join(", "))
         };
         log!(self.log_level, "{}", formatted_log);
     }
@@ -185,16 +178,44 @@ impl<L: PlonkParameters<D>, V: CircuitVariable, const D: usize> SimpleGenerator<
 
 #[cfg(test)]
 mod tests {
+    use ethers::types::{H256, U256};
     use log::{debug, Level};
 
+    use crate::frontend::eth::vars::AddressVariable;
     use crate::prelude::*;
     use crate::utils;
 
+    type F = GoldilocksField;
+
+    #[test]
+    fn test_watch_format_value() {
+        assert_eq!(
+            Bytes32Variable::format_value::<F>(&H256::repeat_byte(0xab)),
+            format!("0x{}", "ab".repeat(32))
+        );
+        assert_eq!(
+            U256Variable::format_value::<F>(&U256::from(255)),
+            "255 (0xff)"
+        );
+        assert_eq!(BytesVariable::<2>::format_value::<F>(&[1, 15]), "0x010f");
+        assert_eq!(
+            AddressVariable::format_value::<F>(&address!(
+                "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+            )),
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+        assert_eq!(
+            ArrayVariable::<U64Variable, 2>::format_value::<F>(&vec![1, 16]),
+            "[1 (0x1), 16 (0x10)]"
+        );
+    }
+
     #[test]
     fn test_watcher() {
         utils::setup_logger();
 
         let mut builder = DefaultBuilder::new();
+        builder.set_debug();
         let a = builder.read::<Variable>();
         let b = builder.read::<Variable>();
         let c = builder.add(a, b);
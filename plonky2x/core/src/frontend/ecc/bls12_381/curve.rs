@@ -0,0 +1,436 @@
+//! Arithmetic over the groups `G1` and `G2` of BLS12-381, in affine coordinates, and the
+//! decompression of points from the encoding of Ethereum public keys and signatures.
+//!
+//! Additions use incomplete formulas and constrain the points to have distinct `x` coordinates, so
+//! the rare inputs hitting the exceptional cases make the proof fail instead of being accepted.
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::fp::{Fp2Value, Fp2Variable, FpVariable};
+use super::native::{Fp, Fp2, G1Affine, G2Affine, NativeField, BLS_X};
+use crate::frontend::hint::simple::hint::Hint;
+use crate::prelude::*;
+
+/// A point of `G1`.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(G1AffineValue)]
+pub struct G1AffineVariable {
+    pub x: FpVariable,
+    pub y: FpVariable,
+}
+
+/// A point of the twist `G2`.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(G2AffineValue)]
+pub struct G2AffineVariable {
+    pub x: Fp2Variable,
+    pub y: Fp2Variable,
+}
+
+impl<F: RichField> From<G1Affine> for G1AffineValue<F> {
+    fn from(point: G1Affine) -> Self {
+        Self {
+            x: point.x.0,
+            y: point.y.0,
+        }
+    }
+}
+
+impl<F: RichField> From<G2Affine> for G2AffineValue<F> {
+    fn from(point: G2Affine) -> Self {
+        Self {
+            x: Fp2Value::from(point.x),
+            y: Fp2Value::from(point.y),
+        }
+    }
+}
+
+impl<F: RichField> From<G2AffineValue<F>> for G2Affine {
+    fn from(point: G2AffineValue<F>) -> Self {
+        G2Affine::new(Fp2::from(point.x), Fp2::from(point.y))
+    }
+}
+
+/// The cube root of unity of `Fp` for which `phi(x, y) = (beta * x, y)` acts on `G1` as the
+/// multiplication by `-x^2`.
+fn g1_phi_beta() -> Fp {
+    Fp::new(
+        BigUint::parse_bytes(
+            b"5f19672fdf76ce51ba69c6076a0f77eaddb3a93be6f89688de17d813620a00022e01fffffffefffe",
+            16,
+        )
+        .unwrap(),
+    )
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    pub fn bls12_381_g1_constant(&mut self, point: &G1Affine) -> G1AffineVariable {
+        G1AffineVariable {
+            x: self.fp_constant(&point.x),
+            y: self.fp_constant(&point.y),
+        }
+    }
+
+    /// The right-hand side `x^3 + 4` of the equation of `G1`.
+    fn bls12_381_g1_rhs(&mut self, x: &FpVariable) -> FpVariable {
+        let x_squared = self.fp_mul(x, x);
+        let x_cubed = self.fp_poly_mul(&x_squared, x);
+        let b = self.fp_constant(&Fp::from_u64(4));
+        let b = self.fp_to_poly(&b);
+        let rhs = self.fp_poly_add(&x_cubed, &b);
+        self.fp_reduce(&rhs)
+    }
+
+    pub fn bls12_381_g1_assert_on_curve(&mut self, point: &G1AffineVariable) {
+        let rhs = self.bls12_381_g1_rhs(&point.x);
+        let lhs = self.fp_mul(&point.y, &point.y);
+        self.assert_is_equal(lhs, rhs);
+    }
+
+    pub fn bls12_381_g1_neg(&mut self, point: &G1AffineVariable) -> G1AffineVariable {
+        G1AffineVariable {
+            x: point.x.clone(),
+            y: self.fp_neg(&point.y),
+        }
+    }
+
+    /// Checks that a point lies in the subgroup `G1` of order `r`, using that `phi(P) = [-x^2] P`
+    /// exactly for points of `G1`.
+    pub fn bls12_381_g1_assert_in_subgroup(&mut self, point: &G1AffineVariable) {
+        let multiple = self.bls12_381_g1_mul_by_x(point);
+        let multiple = self.bls12_381_g1_mul_by_x(&multiple);
+        let multiple = self.bls12_381_g1_neg(&multiple);
+        let phi = self.bls12_381_g1_phi(point);
+        self.assert_is_equal(multiple, phi);
+    }
+
+    /// The endomorphism `phi(x, y) = (beta * x, y)`, where `beta` is the cube root of unity for
+    /// which `phi` acts on `G1` as the multiplication by `-x^2`.
+    pub fn bls12_381_g1_phi(&mut self, point: &G1AffineVariable) -> G1AffineVariable {
+        let beta = self.fp_constant(&g1_phi_beta());
+        G1AffineVariable {
+            x: self.fp_mul(&point.x, &beta),
+            y: point.y.clone(),
+        }
+    }
+
+    /// The third point on the line of the given slope through `a` and `b`, negated.
+    fn bls12_381_g1_add_with_slope(
+        &mut self,
+        a: &G1AffineVariable,
+        b: &G1AffineVariable,
+        slope: &FpVariable,
+    ) -> G1AffineVariable {
+        let slope_squared = self.fp_poly_mul(slope, slope);
+        let a_x = self.fp_to_poly(&a.x);
+        let b_x = self.fp_to_poly(&b.x);
+        let x_sum = self.fp_poly_add(&a_x, &b_x);
+        let x = self.fp_poly_sub(&slope_squared, &x_sum);
+        let x = self.fp_reduce(&x);
+
+        let dx = self.fp_sub(&a.x, &x);
+        let y = self.fp_poly_mul(slope, &dx);
+        let a_y = self.fp_to_poly(&a.y);
+        let y = self.fp_poly_sub(&y, &a_y);
+        let y = self.fp_reduce(&y);
+        G1AffineVariable { x, y }
+    }
+
+    /// Adds two points with distinct `x` coordinates.
+    pub fn bls12_381_g1_add(
+        &mut self,
+        a: &G1AffineVariable,
+        b: &G1AffineVariable,
+    ) -> G1AffineVariable {
+        let dy = self.fp_sub(&b.y, &a.y);
+        let dx = self.fp_sub(&b.x, &a.x);
+        let slope = self.fp_div(&dy, &dx);
+        self.bls12_381_g1_add_with_slope(a, b, &slope)
+    }
+
+    pub fn bls12_381_g1_double(&mut self, point: &G1AffineVariable) -> G1AffineVariable {
+        let x_squared = self.fp_poly_mul(&point.x, &point.x);
+        let numerator = self.fp_poly_scale(&x_squared, 3);
+        let numerator = self.fp_reduce(&numerator);
+        let denominator = self.fp_add(&point.y, &point.y);
+        let slope = self.fp_div(&numerator, &denominator);
+        self.bls12_381_g1_add_with_slope(point, point, &slope)
+    }
+
+    /// Multiplies a point by the negative parameter `x` of the curve, by double-and-add.
+    pub fn bls12_381_g1_mul_by_x(&mut self, point: &G1AffineVariable) -> G1AffineVariable {
+        let scalar = BigUint::from(BLS_X);
+        let mut result = point.clone();
+        for i in (0..scalar.bits() - 1).rev() {
+            result = self.bls12_381_g1_double(&result);
+            if scalar.bit(i) {
+                result = self.bls12_381_g1_add(&result, point);
+            }
+        }
+        self.bls12_381_g1_neg(&result)
+    }
+
+    /// Decompresses a point of `G1` from its 48-byte encoding, which must be a valid encoding of
+    /// a point other than the point at infinity for the proof to be valid. The point is not
+    /// checked to lie in the subgroup.
+    pub fn bls12_381_g1_decompress(&mut self, bytes: &[ByteVariable; 48]) -> G1AffineVariable {
+        let (x_bytes, is_largest) = self.bls12_381_decompression_flags(bytes);
+        let x: FpVariable = self.fp_from_be_bytes(&x_bytes);
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&x);
+        input_stream.write(&is_largest);
+        let output_stream = self.hint(input_stream, G1DecompressHint);
+        let y = output_stream.read::<FpVariable>(self);
+
+        let point = G1AffineVariable { x, y };
+        self.bls12_381_g1_assert_on_curve(&point);
+        let y_is_largest = self.fp_is_lexicographically_largest(&point.y);
+        self.assert_is_equal(y_is_largest, is_largest);
+        point
+    }
+
+    /// Checks the flags of a compressed point, which must not be the point at infinity, and
+    /// returns the encoding with the flags cleared and the sign of `y`.
+    fn bls12_381_decompression_flags(
+        &mut self,
+        bytes: &[ByteVariable],
+    ) -> (Vec<ByteVariable>, BoolVariable) {
+        let true_variable = self._true();
+        let false_variable = self._false();
+        let flags = bytes[0].as_be_bits();
+        self.assert_is_equal(flags[0], true_variable);
+        self.assert_is_equal(flags[1], false_variable);
+
+        let mut first_byte = flags;
+        first_byte[..3].fill(false_variable);
+        let mut x_bytes = bytes.to_vec();
+        x_bytes[0] = ByteVariable::from_be_bits(first_byte);
+        (x_bytes, flags[2])
+    }
+
+    pub fn bls12_381_g2_constant(&mut self, point: &G2Affine) -> G2AffineVariable {
+        G2AffineVariable {
+            x: self.fp2_constant(&point.x),
+            y: self.fp2_constant(&point.y),
+        }
+    }
+
+    /// The right-hand side `x^3 + 4 * xi` of the equation of the twist.
+    fn bls12_381_g2_rhs(&mut self, x: &Fp2Variable) -> Fp2Variable {
+        let x_squared = self.fp2_mul(x, x);
+        let x_cubed = self.fp2_poly_mul(&x_squared, x);
+        let b = self.fp2_constant(&G2Affine::twist_b());
+        let b = self.fp2_to_poly(&b);
+        let rhs = self.fp2_poly_add(&x_cubed, &b);
+        self.fp2_reduce(&rhs)
+    }
+
+    pub fn bls12_381_g2_assert_on_curve(&mut self, point: &G2AffineVariable) {
+        let rhs = self.bls12_381_g2_rhs(&point.x);
+        let lhs = self.fp2_square(&point.y);
+        self.assert_is_equal(lhs, rhs);
+    }
+
+    /// Checks that a point of the twist lies in the subgroup `G2` of order `r`, using that
+    /// `psi(Q) = [x] Q` exactly for points of `G2`.
+    pub fn bls12_381_g2_assert_in_subgroup(&mut self, point: &G2AffineVariable) {
+        let multiple = self.bls12_381_g2_mul_by_x(point);
+        let psi = self.bls12_381_g2_psi(point);
+        self.assert_is_equal(multiple, psi);
+    }
+
+    pub fn bls12_381_g2_neg(&mut self, point: &G2AffineVariable) -> G2AffineVariable {
+        G2AffineVariable {
+            x: point.x.clone(),
+            y: self.fp2_neg(&point.y),
+        }
+    }
+
+    /// The endomorphism `psi`, obtained by untwisting, applying the Frobenius map and twisting
+    /// back.
+    pub fn bls12_381_g2_psi(&mut self, point: &G2AffineVariable) -> G2AffineVariable {
+        let (gamma_x, gamma_y) = G2Affine::psi_coefficients();
+        let x = self.fp2_conjugate(&point.x);
+        let gamma_x = self.fp2_constant(&gamma_x);
+        let y = self.fp2_conjugate(&point.y);
+        let gamma_y = self.fp2_constant(&gamma_y);
+        G2AffineVariable {
+            x: self.fp2_mul(&x, &gamma_x),
+            y: self.fp2_mul(&y, &gamma_y),
+        }
+    }
+
+    /// The third point on the line of the given slope through `a` and `b`, negated.
+    fn bls12_381_g2_add_with_slope(
+        &mut self,
+        a: &G2AffineVariable,
+        b: &G2AffineVariable,
+        slope: &Fp2Variable,
+    ) -> G2AffineVariable {
+        let slope_squared = self.fp2_poly_mul(slope, slope);
+        let a_x = self.fp2_to_poly(&a.x);
+        let b_x = self.fp2_to_poly(&b.x);
+        let x_sum = self.fp2_poly_add(&a_x, &b_x);
+        let x = self.fp2_poly_sub(&slope_squared, &x_sum);
+        let x = self.fp2_reduce(&x);
+
+        let dx = self.fp2_sub(&a.x, &x);
+        let y = self.fp2_poly_mul(slope, &dx);
+        let a_y = self.fp2_to_poly(&a.y);
+        let y = self.fp2_poly_sub(&y, &a_y);
+        let y = self.fp2_reduce(&y);
+        G2AffineVariable { x, y }
+    }
+
+    /// Doubles a point, also returning the slope of the tangent.
+    pub fn bls12_381_g2_double_with_slope(
+        &mut self,
+        point: &G2AffineVariable,
+    ) -> (G2AffineVariable, Fp2Variable) {
+        let x_squared = self.fp2_poly_mul(&point.x, &point.x);
+        let numerator = self.fp2_poly_scale(&x_squared, 3);
+        let numerator = self.fp2_reduce(&numerator);
+        let denominator = self.fp2_add(&point.y, &point.y);
+        let slope = self.fp2_div(&numerator, &denominator);
+        (
+            self.bls12_381_g2_add_with_slope(point, point, &slope),
+            slope,
+        )
+    }
+
+    /// Adds two points with distinct `x` coordinates, also returning the slope of the chord.
+    pub fn bls12_381_g2_add_with_chord(
+        &mut self,
+        a: &G2AffineVariable,
+        b: &G2AffineVariable,
+    ) -> (G2AffineVariable, Fp2Variable) {
+        let dy = self.fp2_sub(&b.y, &a.y);
+        let dx = self.fp2_sub(&b.x, &a.x);
+        let slope = self.fp2_div(&dy, &dx);
+        (self.bls12_381_g2_add_with_slope(a, b, &slope), slope)
+    }
+
+    /// Adds two points with distinct `x` coordinates.
+    pub fn bls12_381_g2_add(
+        &mut self,
+        a: &G2AffineVariable,
+        b: &G2AffineVariable,
+    ) -> G2AffineVariable {
+        self.bls12_381_g2_add_with_chord(a, b).0
+    }
+
+    pub fn bls12_381_g2_double(&mut self, point: &G2AffineVariable) -> G2AffineVariable {
+        self.bls12_381_g2_double_with_slope(point).0
+    }
+
+    /// Multiplies a point by the negative parameter `x` of the curve, by double-and-add.
+    pub fn bls12_381_g2_mul_by_x(&mut self, point: &G2AffineVariable) -> G2AffineVariable {
+        let scalar = BigUint::from(BLS_X);
+        let mut result = point.clone();
+        for i in (0..scalar.bits() - 1).rev() {
+            result = self.bls12_381_g2_double(&result);
+            if scalar.bit(i) {
+                result = self.bls12_381_g2_add(&result, point);
+            }
+        }
+        self.bls12_381_g2_neg(&result)
+    }
+
+    /// Decompresses a point of the twist from its 96-byte encoding, which must be a valid encoding
+    /// of a point other than the point at infinity for the proof to be valid. The point is not
+    /// checked to lie in the subgroup.
+    pub fn bls12_381_g2_decompress(&mut self, bytes: &[ByteVariable; 96]) -> G2AffineVariable {
+        let (x_bytes, is_largest) = self.bls12_381_decompression_flags(bytes);
+        let c1 = self.fp_from_be_bytes(&x_bytes[..48]);
+        let c0 = self.fp_from_be_bytes(&x_bytes[48..]);
+        let x = Fp2Variable { c0, c1 };
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&x);
+        input_stream.write(&is_largest);
+        let output_stream = self.hint(input_stream, G2DecompressHint);
+        let y = output_stream.read::<Fp2Variable>(self);
+
+        let point = G2AffineVariable { x, y };
+        self.bls12_381_g2_assert_on_curve(&point);
+        let y_is_largest = self.fp2_is_lexicographically_largest(&point.y);
+        self.assert_is_equal(y_is_largest, is_largest);
+        point
+    }
+}
+
+/// Computes the `y` coordinate of a point of `G1` from `x` and the sign of `y`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct G1DecompressHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for G1DecompressHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let x = Fp::new(input_stream.read_value::<FpVariable>());
+        let is_largest = input_stream.read_value::<BoolVariable>();
+        // An `x` with no point on the curve cannot satisfy the constraints, so any `y` will do.
+        let y = (x.square() * x + Fp::from_u64(4))
+            .sqrt()
+            .unwrap_or_else(Fp::zero);
+        let y = if y.is_lexicographically_largest() == is_largest {
+            y
+        } else {
+            -y
+        };
+        output_stream.write_value::<FpVariable>(y.0);
+    }
+}
+
+/// Computes the `y` coordinate of a point of the twist from `x` and the sign of `y`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct G2DecompressHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for G2DecompressHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let x = Fp2::from(input_stream.read_value::<Fp2Variable>());
+        let is_largest = input_stream.read_value::<BoolVariable>();
+        let y = (x.square() * x + G2Affine::twist_b())
+            .sqrt()
+            .unwrap_or_else(Fp2::zero);
+        let y = if y.is_lexicographically_largest() == is_largest {
+            y
+        } else {
+            -y
+        };
+        output_stream.write_value::<Fp2Variable>(y.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_bls12_381_decompress() {
+        let mut builder = DefaultBuilder::new();
+        let g1_bytes = builder.read::<BytesVariable<48>>();
+        let g2_bytes = builder.read::<BytesVariable<96>>();
+        let g1 = builder.bls12_381_g1_decompress(&g1_bytes.0);
+        builder.bls12_381_g1_assert_in_subgroup(&g1);
+        builder.write(g1);
+        let g2 = builder.bls12_381_g2_decompress(&g2_bytes.0);
+        builder.bls12_381_g2_assert_in_subgroup(&g2);
+        builder.write(g2);
+        let circuit = builder.build();
+
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator().double().negate();
+        let mut input = circuit.input();
+        input.write::<BytesVariable<48>>(g1.compress());
+        input.write::<BytesVariable<96>>(g2.compress());
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let g1_value = output.read::<G1AffineVariable>();
+        assert_eq!(g1_value.x, g1.x.0);
+        assert_eq!(g1_value.y, g1.y.0);
+        assert_eq!(G2Affine::from(output.read::<G2AffineVariable>()), g2);
+    }
+}
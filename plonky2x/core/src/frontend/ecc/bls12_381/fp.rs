@@ -0,0 +1,78 @@
+//! The BLS12-381 base field `Fp` and its tower `Fp2 = Fp[u]/(u^2 + 1)` and
+//! `Fp12 = Fp2[w]/(w^6 - xi)` with `xi = 1 + u`, as instances of the non-native arithmetic of the
+//! `tower` module.
+
+use num_bigint::BigUint;
+
+use super::native::{fp_modulus, frobenius_coefficients, Fp, Fp12, Fp2};
+use crate::frontend::ecc::tower::{fp, fp12, fp2, TowerField, TowerFp12, TowerFp2};
+use crate::prelude::*;
+
+/// An element of the BLS12-381 base field.
+pub type FpVariable = fp::FpVariable<Fp>;
+pub type FpPoly = fp::FpPoly<Fp>;
+pub type Fp2Variable = fp2::Fp2Variable<Fp>;
+pub type Fp2Value<F> = fp2::Fp2Value<Fp, F>;
+pub type Fp2Poly = fp2::Fp2Poly<Fp>;
+pub type Fp12Variable = fp12::Fp12Variable<Fp>;
+pub type Fp12Coefficient = fp12::Fp12Coefficient<Fp>;
+
+impl TowerField for Fp {
+    const NB_LIMBS: usize = 24;
+    const XI_C0: u64 = 1;
+
+    type Fp2 = Fp2;
+    type Fp12 = Fp12;
+
+    fn modulus() -> BigUint {
+        fp_modulus()
+    }
+
+    fn from_biguint(value: BigUint) -> Self {
+        Fp::new(value)
+    }
+
+    fn as_biguint(&self) -> &BigUint {
+        &self.0
+    }
+
+    fn frobenius_coefficients(k: u32) -> Vec<Fp2> {
+        frobenius_coefficients(k)
+    }
+}
+
+impl TowerFp2 for Fp2 {
+    type Base = Fp;
+
+    fn from_coefficients(c0: Fp, c1: Fp) -> Self {
+        Fp2::new(c0, c1)
+    }
+
+    fn coefficients(&self) -> [Fp; 2] {
+        [self.c0.clone(), self.c1.clone()]
+    }
+}
+
+impl TowerFp12 for Fp12 {
+    type Base = Fp;
+
+    fn from_coefficients(coefficients: [Fp2; 6]) -> Self {
+        Fp12(coefficients)
+    }
+
+    fn coefficients(&self) -> [Fp2; 6] {
+        self.0.clone()
+    }
+}
+
+impl<F: RichField> From<Fp2> for Fp2Value<F> {
+    fn from(value: Fp2) -> Self {
+        Self::from_native(&value)
+    }
+}
+
+impl<F: RichField> From<Fp2Value<F>> for Fp2 {
+    fn from(value: Fp2Value<F>) -> Self {
+        value.to_native()
+    }
+}
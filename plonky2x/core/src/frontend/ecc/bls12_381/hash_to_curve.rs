@@ -0,0 +1,245 @@
+//! Hashing to `G2` with the `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite of RFC 9380.
+//!
+//! The square roots of the simplified SWU map are witnessed by a hint together with the choice
+//! between the two candidate `x` coordinates, which is sound as exactly one of them has a square
+//! on the right-hand side of the curve equation. The exceptional inputs of the map and of the
+//! incomplete additions are only hit with negligible probability and make the proof fail.
+
+use serde::{Deserialize, Serialize};
+
+use super::curve::G2AffineVariable;
+use super::fp::{Fp2Variable, FpVariable};
+use super::native::{iso_coefficients, sswu_constants, Fp2, NativeField};
+use crate::frontend::hint::simple::hint::Hint;
+use crate::prelude::*;
+
+/// The length in bytes of the output of `expand_message_xmd` for two elements of `Fp2`.
+const EXPANDED_MESSAGE_LENGTH: usize = 256;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// The `expand_message_xmd` function of RFC 9380 with SHA-256, for a constant tag `dst`.
+    pub fn bls12_381_expand_message_xmd(
+        &mut self,
+        message: &[ByteVariable],
+        dst: &[u8],
+        len_in_bytes: usize,
+    ) -> Vec<ByteVariable> {
+        let ell = len_in_bytes.div_ceil(32);
+        assert!(ell <= 255 && dst.len() <= 255);
+        let mut dst_prime = dst.to_vec();
+        dst_prime.push(dst.len() as u8);
+        let dst_prime = dst_prime
+            .into_iter()
+            .map(|byte| self.constant::<ByteVariable>(byte))
+            .collect::<Vec<_>>();
+
+        let mut b0_input = (0..64)
+            .map(|_| self.constant::<ByteVariable>(0))
+            .collect::<Vec<_>>();
+        b0_input.extend_from_slice(message);
+        for byte in (len_in_bytes as u16).to_be_bytes().into_iter().chain([0]) {
+            b0_input.push(self.constant::<ByteVariable>(byte));
+        }
+        b0_input.extend_from_slice(&dst_prime);
+        let b0 = self.curta_sha256(&b0_input).as_bytes();
+
+        let mut blocks: Vec<[ByteVariable; 32]> = Vec::new();
+        for i in 1..=ell {
+            let mut input = match blocks.last() {
+                None => b0.to_vec(),
+                Some(previous) => b0
+                    .iter()
+                    .zip(previous.iter())
+                    .map(|(a, b)| self.xor(*a, *b))
+                    .collect(),
+            };
+            input.push(self.constant::<ByteVariable>(i as u8));
+            input.extend_from_slice(&dst_prime);
+            blocks.push(self.curta_sha256(&input).as_bytes());
+        }
+        let mut output = blocks.concat();
+        output.truncate(len_in_bytes);
+        output
+    }
+
+    /// Hashes a message to two elements of `Fp2`, each from 128 bytes of `expand_message_xmd`.
+    pub fn bls12_381_hash_to_field_fp2(
+        &mut self,
+        message: &[ByteVariable],
+        dst: &[u8],
+    ) -> [Fp2Variable; 2] {
+        let bytes = self.bls12_381_expand_message_xmd(message, dst, EXPANDED_MESSAGE_LENGTH);
+        let elements = bytes
+            .chunks(64)
+            .map(|chunk| self.fp_reduce_be_bytes(chunk))
+            .collect::<Vec<FpVariable>>();
+        [
+            Fp2Variable {
+                c0: elements[0].clone(),
+                c1: elements[1].clone(),
+            },
+            Fp2Variable {
+                c0: elements[2].clone(),
+                c1: elements[3].clone(),
+            },
+        ]
+    }
+
+    /// The right-hand side `x^3 + A' x + B'` of the equation of the curve of the SWU map.
+    fn bls12_381_sswu_rhs(&mut self, x: &Fp2Variable) -> Fp2Variable {
+        let (a, b, _) = sswu_constants();
+        let a = self.fp2_constant(&a);
+        let b = self.fp2_constant(&b);
+        let x_squared = self.fp2_square(x);
+        let x_squared = self.fp2_add(&x_squared, &a);
+        let rhs = self.fp2_poly_mul(&x_squared, x);
+        let b = self.fp2_to_poly(&b);
+        let rhs = self.fp2_poly_add(&rhs, &b);
+        self.fp2_reduce(&rhs)
+    }
+
+    /// The simplified SWU map of RFC 9380 to the curve isogenous to the twist.
+    pub fn bls12_381_sswu_map(&mut self, u: &Fp2Variable) -> G2AffineVariable {
+        let (a, b, z) = sswu_constants();
+        let z = self.fp2_constant(&z);
+        let u_squared = self.fp2_square(u);
+        let z_u2 = self.fp2_mul(&z, &u_squared);
+        let z_u2_squared = self.fp2_square(&z_u2);
+        let tv = self.fp2_add(&z_u2_squared, &z_u2);
+
+        // `x1 = (-B' / A') * (1 + 1 / tv)`, where `tv` is only zero for a negligible set of `u`.
+        let c = self.fp2_constant(&(-b * a.inverse()));
+        let tv_inverse = self.fp2_inverse(&tv);
+        let c_tv_inverse = self.fp2_poly_mul(&c, &tv_inverse);
+        let c_poly = self.fp2_to_poly(&c);
+        let x1 = self.fp2_poly_add(&c_poly, &c_tv_inverse);
+        let x1 = self.fp2_reduce(&x1);
+        let gx1 = self.bls12_381_sswu_rhs(&x1);
+        let x2 = self.fp2_mul(&z_u2, &x1);
+        let gx2 = self.bls12_381_sswu_rhs(&x2);
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(u);
+        input_stream.write(&gx1);
+        input_stream.write(&gx2);
+        let output_stream = self.hint(input_stream, SswuSqrtHint);
+        let gx1_is_square = output_stream.read::<BoolVariable>(self);
+        let y = output_stream.read::<Fp2Variable>(self);
+
+        let x = self.select(gx1_is_square, x1, x2);
+        let gx = self.select(gx1_is_square, gx1, gx2);
+        let y_squared = self.fp2_square(&y);
+        self.assert_is_equal(y_squared, gx);
+        let y_sign = self.fp2_sgn0(&y);
+        let u_sign = self.fp2_sgn0(u);
+        self.assert_is_equal(y_sign, u_sign);
+        G2AffineVariable { x, y }
+    }
+
+    fn bls12_381_evaluate(&mut self, polynomial: &[Fp2], x: &Fp2Variable) -> Fp2Variable {
+        let mut coefficients = polynomial.iter().rev();
+        let leading = coefficients.next().expect("empty polynomial");
+        let mut result = self.fp2_constant(leading);
+        for c in coefficients {
+            let product = self.fp2_poly_mul(&result, x);
+            let c = self.fp2_constant(c);
+            let c = self.fp2_to_poly(&c);
+            let sum = self.fp2_poly_add(&product, &c);
+            result = self.fp2_reduce(&sum);
+        }
+        result
+    }
+
+    /// Maps a point of the curve of the simplified SWU map to the twist with the 3-isogeny.
+    pub fn bls12_381_iso_map(&mut self, point: &G2AffineVariable) -> G2AffineVariable {
+        let [x_num, x_den, y_num, y_den] = iso_coefficients();
+        let x_num = self.bls12_381_evaluate(&x_num, &point.x);
+        let x_den = self.bls12_381_evaluate(&x_den, &point.x);
+        let y_num = self.bls12_381_evaluate(&y_num, &point.x);
+        let y_den = self.bls12_381_evaluate(&y_den, &point.x);
+        let x = self.fp2_div(&x_num, &x_den);
+        let y = self.fp2_div(&y_num, &y_den);
+        let y = self.fp2_mul(&point.y, &y);
+        G2AffineVariable { x, y }
+    }
+
+    /// Multiplies a point of the twist by the effective cofactor of RFC 9380 with the endomorphism
+    /// `psi`, as `[x^2 - x - 1] P + [x - 1] psi(P) + psi^2(2P)`.
+    pub fn bls12_381_clear_cofactor(&mut self, point: &G2AffineVariable) -> G2AffineVariable {
+        let t1 = self.bls12_381_g2_mul_by_x(point);
+        let t2 = self.bls12_381_g2_psi(point);
+        let t3 = self.bls12_381_g2_double(point);
+        let t3 = self.bls12_381_g2_psi(&t3);
+        let t3 = self.bls12_381_g2_psi(&t3);
+        let neg_t2 = self.bls12_381_g2_neg(&t2);
+        let t3 = self.bls12_381_g2_add(&t3, &neg_t2);
+        let t2 = self.bls12_381_g2_add(&t1, &t2);
+        let t2 = self.bls12_381_g2_mul_by_x(&t2);
+        let t3 = self.bls12_381_g2_add(&t3, &t2);
+        let neg_t1 = self.bls12_381_g2_neg(&t1);
+        let t3 = self.bls12_381_g2_add(&t3, &neg_t1);
+        let neg_point = self.bls12_381_g2_neg(point);
+        self.bls12_381_g2_add(&t3, &neg_point)
+    }
+
+    /// Hashes a message to a point of `G2` with the constant domain separation tag `dst`.
+    pub fn bls12_381_hash_to_g2(
+        &mut self,
+        message: &[ByteVariable],
+        dst: &[u8],
+    ) -> G2AffineVariable {
+        let [u0, u1] = self.bls12_381_hash_to_field_fp2(message, dst);
+        let q0 = self.bls12_381_sswu_map(&u0);
+        let q0 = self.bls12_381_iso_map(&q0);
+        let q1 = self.bls12_381_sswu_map(&u1);
+        let q1 = self.bls12_381_iso_map(&q1);
+        let sum = self.bls12_381_g2_add(&q0, &q1);
+        self.bls12_381_clear_cofactor(&sum)
+    }
+}
+
+/// Computes whether `g(x1)` is a square and a square root of `g(x1)` or `g(x2)` with the sign of
+/// `u`, for the simplified SWU map.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SswuSqrtHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for SswuSqrtHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let u = Fp2::from(input_stream.read_value::<Fp2Variable>());
+        let gx1 = Fp2::from(input_stream.read_value::<Fp2Variable>());
+        let gx2 = Fp2::from(input_stream.read_value::<Fp2Variable>());
+        let (gx1_is_square, y) = match gx1.sqrt() {
+            Some(y) => (true, y),
+            // One of `g(x1)` and `g(x2) = Z^3 u^6 g(x1)` is a square.
+            None => (false, gx2.sqrt().expect("neither candidate is a square")),
+        };
+        let y = if y.sgn0() == u.sgn0() { y } else { -y };
+        output_stream.write_value::<BoolVariable>(gx1_is_square);
+        output_stream.write_value::<Fp2Variable>(y.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::ecc::bls12_381::native::{hash_to_g2, G2Affine};
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_bls12_381_hash_to_g2() {
+        let dst = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+        let mut builder = DefaultBuilder::new();
+        let message = builder.read::<BytesVariable<3>>();
+        let point = builder.bls12_381_hash_to_g2(&message.0, dst);
+        builder.write(point);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<BytesVariable<3>>(*b"abc");
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let point = G2Affine::from(output.read::<G2AffineVariable>());
+        assert_eq!(point, hash_to_g2(b"abc", dst));
+    }
+}
@@ -0,0 +1,234 @@
+//! Verification of the KZG proofs of EIP-4844 blobs, as done by the point evaluation precompile.
+//!
+//! A proof `pi` that the polynomial committed to by `C` evaluates to `y` at `z` is checked with
+//! the pairing equation `e(C - [y] G1 + [z] pi, G2) = e(pi, [tau] G2)`, where `[tau] G2` comes from
+//! the trusted setup of the KZG ceremony. The commitment and the proof are checked to lie in `G1`,
+//! and must not be the point at infinity for the proof to be valid.
+
+use ethers::types::U256;
+use num_bigint::BigUint;
+
+use super::curve::G1AffineVariable;
+use super::native::{fr_modulus, hash_to_g1_by_increment, Fp, Fp2, G1Affine, G2Affine, OFFSET_DST};
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::*;
+
+/// The version byte of the versioned hash of a KZG commitment.
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// The number of field elements of a blob.
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+
+/// The number of bits of the scalars `z` and `y`, which are 32-byte big endian integers.
+const SCALAR_BITS: usize = 256;
+
+fn fp_from_hex(hex: &str) -> Fp {
+    Fp::new(BigUint::parse_bytes(hex.as_bytes(), 16).unwrap())
+}
+
+/// The point `[tau] G2` of the trusted setup of the KZG ceremony used by EIP-4844.
+pub fn kzg_setup_g2() -> G2Affine {
+    G2Affine::new(
+        Fp2::new(
+            fp_from_hex("185cbfee53492714734429b7b38608e23926c911cceceac9a36851477ba4c60b087041de621000edc98edada20c1def2"),
+            fp_from_hex("15bfd7dd8cdeb128843bc287230af38926187075cbfbefa81009a2ce615ac53d2914e5870cb452d2afaaab24f3499f72"),
+        ),
+        Fp2::new(
+            fp_from_hex("014353bdb96b626dd7d5ee8599d1fca2131569490e28de18e82451a496a9c9794ce26d105941f383ee689bfbbb832a99"),
+            fp_from_hex("1666c54b0a32529503432fcae0181b4bef79de09fc63671fda5ed1ba9bfa07899495346f3d7ac9cd23048ef30d0a154f"),
+        ),
+    )
+}
+
+/// A point of `G1` hashed to the curve, whose discrete logarithm is unknown, which the sum of the
+/// scalar multiplications starts from so that the incomplete additions never involve the point at
+/// infinity: commitments or proofs crafted to hit an exceptional case would need a relation to the
+/// offset.
+fn kzg_offset() -> G1Affine {
+    hash_to_g1_by_increment(b"kzg offset", OFFSET_DST)
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// The versioned hash of a commitment, `sha256(commitment)` with its first byte replaced by
+    /// the version.
+    pub fn kzg_to_versioned_hash(&mut self, commitment: &BytesVariable<48>) -> Bytes32Variable {
+        let mut hash = self.curta_sha256(&commitment.0);
+        hash.0 .0[0] = self.constant::<ByteVariable>(VERSIONED_HASH_VERSION_KZG);
+        hash
+    }
+
+    /// Checks that a 32-byte big endian scalar is an element of the scalar field, and returns its
+    /// bits in big endian order.
+    fn kzg_scalar_bits(&mut self, scalar: Bytes32Variable) -> Vec<BoolVariable> {
+        let value = U256Variable::decode(self, &scalar.as_bytes());
+        let modulus = U256::from_big_endian(&fr_modulus().to_bytes_be());
+        let modulus = self.constant::<U256Variable>(modulus);
+        let in_field = self.lt(value, modulus);
+        let true_v = self._true();
+        self.assert_is_equal(in_field, true_v);
+        scalar
+            .as_bytes()
+            .iter()
+            .flat_map(|byte| byte.as_be_bits())
+            .collect()
+    }
+
+    /// Checks that `proof` proves that the polynomial committed to by `commitment` evaluates to
+    /// `y` at `z`, against the given point `[tau] G2` of a trusted setup.
+    pub fn kzg_verify_proof_with_setup(
+        &mut self,
+        tau_g2: &G2Affine,
+        commitment: &G1AffineVariable,
+        z: Bytes32Variable,
+        y: Bytes32Variable,
+        proof: &G1AffineVariable,
+    ) {
+        self.bls12_381_g1_assert_in_subgroup(commitment);
+        self.bls12_381_g1_assert_in_subgroup(proof);
+        let z_bits = self.kzg_scalar_bits(z);
+        let y_bits = self.kzg_scalar_bits(y);
+
+        // Compute `C - [y] G1 + [z] pi` from the offset, by double-and-add for `[z] pi` and by
+        // adding the constant multiples of `-G1` for `[y] G1`.
+        let offset = kzg_offset();
+        let mut sum = self.bls12_381_g1_constant(&offset);
+        for bit in z_bits.iter() {
+            sum = self.bls12_381_g1_double(&sum);
+            let with_proof = self.bls12_381_g1_add(&sum, proof);
+            sum = self.select(*bit, with_proof, sum);
+        }
+        let mut power = G1Affine::generator().negate();
+        for bit in y_bits.iter().rev() {
+            let multiple = self.bls12_381_g1_constant(&power);
+            let with_multiple = self.bls12_381_g1_add(&sum, &multiple);
+            sum = self.select(*bit, with_multiple, sum);
+            power = power.double();
+        }
+        sum = self.bls12_381_g1_add(&sum, commitment);
+        let shifted_offset = offset
+            .scalar_mul(&(BigUint::from(1u32) << SCALAR_BITS))
+            .expect("shifted offset is the point at infinity");
+        let neg_shifted_offset = self.bls12_381_g1_constant(&shifted_offset.negate());
+        let lhs = self.bls12_381_g1_add(&sum, &neg_shifted_offset);
+
+        let generator = self.bls12_381_g2_constant(&G2Affine::generator());
+        let tau_g2 = self.bls12_381_g2_constant(tau_g2);
+        let neg_proof = self.bls12_381_g1_neg(proof);
+        self.bls12_381_pairing_check(&[(lhs, generator), (neg_proof, tau_g2)]);
+    }
+
+    /// Checks that `proof` proves that the polynomial committed to by `commitment` evaluates to
+    /// `y` at `z`, against the trusted setup of EIP-4844.
+    pub fn kzg_verify_proof(
+        &mut self,
+        commitment: &G1AffineVariable,
+        z: Bytes32Variable,
+        y: Bytes32Variable,
+        proof: &G1AffineVariable,
+    ) {
+        self.kzg_verify_proof_with_setup(&kzg_setup_g2(), commitment, z, y, proof);
+    }
+
+    /// Checks the input of the point evaluation precompile: the commitment must have the given
+    /// versioned hash and open to `y` at `z`. Returns the decompressed commitment.
+    pub fn kzg_point_evaluation(
+        &mut self,
+        versioned_hash: Bytes32Variable,
+        z: Bytes32Variable,
+        y: Bytes32Variable,
+        commitment: &BytesVariable<48>,
+        proof: &BytesVariable<48>,
+    ) -> G1AffineVariable {
+        let expected_hash = self.kzg_to_versioned_hash(commitment);
+        self.assert_is_equal(versioned_hash, expected_hash);
+        let commitment = self.bls12_381_g1_decompress(&commitment.0);
+        let proof = self.bls12_381_g1_decompress(&proof.0);
+        self.kzg_verify_proof(&commitment, z, y, &proof);
+        commitment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::*;
+    use crate::utils::hash::sha256;
+
+    fn scalar(seed: &[u8]) -> BigUint {
+        BigUint::from_bytes_be(&sha256(seed)) % fr_modulus()
+    }
+
+    fn scalar_bytes(value: &BigUint) -> H256 {
+        let bytes = value.to_bytes_be();
+        H256::from_slice(&[vec![0u8; 32 - bytes.len()], bytes].concat())
+    }
+
+    fn verify(claimed_y: BigUint) {
+        // Commit to a polynomial `f` through `f(tau)` and open it at `z` with the quotient
+        // `(f(tau) - f(z)) / (tau - z)`.
+        let r = fr_modulus();
+        let tau = scalar(b"tau");
+        let f_tau = scalar(b"f(tau)");
+        let z = scalar(b"z");
+        let y = scalar(b"f(z)");
+        let quotient = (&f_tau + &r - &y) * (&tau + &r - &z).modpow(&(&r - 2u32), &r) % &r;
+        let commitment = G1Affine::generator().scalar_mul(&f_tau).unwrap();
+        let proof = G1Affine::generator().scalar_mul(&quotient).unwrap();
+        let tau_g2 = G2Affine::generator().scalar_mul(&tau).unwrap();
+
+        let mut builder = DefaultBuilder::new();
+        let commitment_bytes = builder.read::<BytesVariable<48>>();
+        let z_variable = builder.read::<Bytes32Variable>();
+        let y_variable = builder.read::<Bytes32Variable>();
+        let proof_bytes = builder.read::<BytesVariable<48>>();
+        let versioned_hash = builder.kzg_to_versioned_hash(&commitment_bytes);
+        let commitment_point = builder.bls12_381_g1_decompress(&commitment_bytes.0);
+        let proof_point = builder.bls12_381_g1_decompress(&proof_bytes.0);
+        builder.kzg_verify_proof_with_setup(
+            &tau_g2,
+            &commitment_point,
+            z_variable,
+            y_variable,
+            &proof_point,
+        );
+        builder.write(versioned_hash);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<BytesVariable<48>>(commitment.compress());
+        input.write::<Bytes32Variable>(scalar_bytes(&z));
+        input.write::<Bytes32Variable>(scalar_bytes(&claimed_y));
+        input.write::<BytesVariable<48>>(proof.compress());
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let mut expected_hash = sha256(&commitment.compress());
+        expected_hash[0] = VERSIONED_HASH_VERSION_KZG;
+        assert_eq!(output.read::<Bytes32Variable>(), H256::from(expected_hash));
+    }
+
+    #[test]
+    fn test_kzg_setup_g2() {
+        let tau_g2 = kzg_setup_g2();
+        assert!(tau_g2.is_on_curve());
+        assert!(tau_g2.scalar_mul(&fr_modulus()).is_none());
+        assert_eq!(
+            tau_g2.compress().to_vec(),
+            bytes!("0xb5bfd7dd8cdeb128843bc287230af38926187075cbfbefa81009a2ce615ac53d2914e5870cb452d2afaaab24f3499f72185cbfee53492714734429b7b38608e23926c911cceceac9a36851477ba4c60b087041de621000edc98edada20c1def2")
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_kzg_verify_proof() {
+        verify(scalar(b"f(z)"));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    #[should_panic]
+    fn test_kzg_verify_proof_wrong_evaluation() {
+        verify(scalar(b"f(z)") + 1u32);
+    }
+}
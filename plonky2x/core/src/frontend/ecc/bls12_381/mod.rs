@@ -0,0 +1,13 @@
+//! Arithmetic over BLS12-381, its optimal ate pairing and hashing to `G2`, for verifying the
+//! aggregate signatures of the Ethereum sync committee and the KZG proofs of blobs in a circuit.
+//!
+//! Elements of the base field are represented with 24 limbs of 16 bits and reduced lazily, with
+//! the arithmetic of the `tower` module.
+
+pub mod curve;
+pub mod fp;
+pub mod hash_to_curve;
+pub mod kzg;
+pub mod native;
+pub mod pairing;
+pub mod signature;
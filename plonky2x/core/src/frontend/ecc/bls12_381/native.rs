@@ -0,0 +1,744 @@
+//! Native arithmetic over BLS12-381, used for the constants and the hints of the circuit gadgets.
+//!
+//! Points are serialized in the compressed format of the Zcash specification, which is the one of
+//! Ethereum public keys and signatures, and messages are hashed to `G2` with the
+//! `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite of RFC 9380.
+
+use core::ops::{Add, Mul, Neg, Sub};
+
+use num::{One, Zero};
+use num_bigint::BigUint;
+
+pub use crate::frontend::ecc::bn254::native::{Affine, NativeField};
+use crate::utils::hash::sha256;
+
+/// The absolute value of the parameter `x = -0xd201000000010000` of the curve, with
+/// `r = x^4 - x^2 + 1` and `p = (x - 1)^2 * r / 3 + x`.
+pub const BLS_X: u64 = 0xd201000000010000;
+
+/// The flags in the most significant bits of the first byte of a compressed point.
+pub const COMPRESSION_FLAG: u8 = 0x80;
+pub const INFINITY_FLAG: u8 = 0x40;
+pub const SIGN_FLAG: u8 = 0x20;
+
+/// The domain separation tag of `hash_to_g1_by_increment` for the offsets of the incomplete
+/// additions of the gadgets.
+pub const OFFSET_DST: &[u8] = b"PLONKY2X-BLS12381G1_XMD:SHA-256_OFFSET_";
+
+fn from_hex(hex: &str) -> BigUint {
+    BigUint::parse_bytes(hex.as_bytes(), 16).unwrap()
+}
+
+/// The modulus of the base field.
+pub fn fp_modulus() -> BigUint {
+    from_hex(
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab",
+    )
+}
+
+/// The order of the groups `G1`, `G2` and `GT`, which is the modulus of the scalar field.
+pub fn fr_modulus() -> BigUint {
+    from_hex("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001")
+}
+
+/// An element of the base field `Fp`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fp(pub BigUint);
+
+impl Fp {
+    pub fn new(value: BigUint) -> Self {
+        Self(value % fp_modulus())
+    }
+
+    fn from_hex(hex: &str) -> Self {
+        Self::new(from_hex(hex))
+    }
+
+    /// Whether the element is greater than its negation, which gives the sign of a compressed
+    /// point.
+    pub fn is_lexicographically_largest(&self) -> bool {
+        self.0 > (fp_modulus() - 1u32) / 2u32
+    }
+
+    /// The sign of RFC 9380, which is the parity of the element.
+    pub fn sgn0(&self) -> bool {
+        self.0.bit(0)
+    }
+
+    /// A square root of the element, if it is a square.
+    pub fn sqrt(&self) -> Option<Self> {
+        // As `p = 3 mod 4`, a square root of a square `a` is `a^((p + 1) / 4)`.
+        let root = self.pow(&((fp_modulus() + 1u32) / 4u32));
+        (root.square() == *self).then_some(root)
+    }
+}
+
+impl Add for Fp {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Fp {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.0 + fp_modulus() - rhs.0)
+    }
+}
+
+impl Mul for Fp {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.0 * rhs.0)
+    }
+}
+
+impl Neg for Fp {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(fp_modulus() - self.0)
+    }
+}
+
+impl NativeField for Fp {
+    fn zero() -> Self {
+        Self(BigUint::zero())
+    }
+
+    fn one() -> Self {
+        Self(BigUint::one())
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Self::new(BigUint::from(value))
+    }
+
+    fn inverse(&self) -> Self {
+        assert!(!self.is_zero(), "zero has no inverse");
+        let p = fp_modulus();
+        Self(self.0.modpow(&(&p - 2u32), &p))
+    }
+}
+
+/// An element of `Fp2 = Fp[u]/(u^2 + 1)`, as `c0 + c1 * u`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fp2 {
+    pub c0: Fp,
+    pub c1: Fp,
+}
+
+impl Fp2 {
+    pub fn new(c0: Fp, c1: Fp) -> Self {
+        Self { c0, c1 }
+    }
+
+    fn from_hex(c0: &str, c1: &str) -> Self {
+        Self::new(Fp::from_hex(c0), Fp::from_hex(c1))
+    }
+
+    /// The non-residue `1 + u` used to build `Fp12` and the twist of `G2`.
+    pub fn xi() -> Self {
+        Self::new(Fp::one(), Fp::one())
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.c0.clone(), -self.c1.clone())
+    }
+
+    pub fn mul_by_fp(&self, other: &Fp) -> Self {
+        Self::new(
+            self.c0.clone() * other.clone(),
+            self.c1.clone() * other.clone(),
+        )
+    }
+
+    /// Whether the element is greater than its negation, comparing `c1` first.
+    pub fn is_lexicographically_largest(&self) -> bool {
+        if self.c1.is_zero() {
+            self.c0.is_lexicographically_largest()
+        } else {
+            self.c1.is_lexicographically_largest()
+        }
+    }
+
+    /// The sign of RFC 9380.
+    pub fn sgn0(&self) -> bool {
+        self.c0.sgn0() || (self.c0.is_zero() && self.c1.sgn0())
+    }
+
+    /// A square root of the element, if it is a square, with algorithm 9 of "Square root
+    /// computation over even extension fields" by Adj and Rodríguez-Henríquez.
+    pub fn sqrt(&self) -> Option<Self> {
+        let p = fp_modulus();
+        let a1 = self.pow(&((&p - 3u32) / 4u32));
+        let alpha = a1.square() * self.clone();
+        let a0 = alpha.pow(&p) * alpha.clone();
+        if a0 == -Self::one() {
+            return None;
+        }
+        let x0 = a1 * self.clone();
+        let root = if alpha == -Self::one() {
+            Self::new(Fp::zero(), Fp::one()) * x0
+        } else {
+            (Self::one() + alpha).pow(&((p - 1u32) / 2u32)) * x0
+        };
+        Some(root)
+    }
+}
+
+impl Add for Fp2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.c0 + rhs.c0, self.c1 + rhs.c1)
+    }
+}
+
+impl Sub for Fp2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.c0 - rhs.c0, self.c1 - rhs.c1)
+    }
+}
+
+impl Mul for Fp2 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.c0.clone() * rhs.c0.clone() - self.c1.clone() * rhs.c1.clone(),
+            self.c0 * rhs.c1 + self.c1 * rhs.c0,
+        )
+    }
+}
+
+impl Neg for Fp2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.c0, -self.c1)
+    }
+}
+
+impl NativeField for Fp2 {
+    fn zero() -> Self {
+        Self::new(Fp::zero(), Fp::zero())
+    }
+
+    fn one() -> Self {
+        Self::new(Fp::one(), Fp::zero())
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Self::new(Fp::from_u64(value), Fp::zero())
+    }
+
+    fn inverse(&self) -> Self {
+        let norm = (self.c0.square() + self.c1.square()).inverse();
+        self.conjugate().mul_by_fp(&norm)
+    }
+}
+
+/// The coefficients `xi^(i * (p^k - 1) / 6)` of the `p^k`-th power Frobenius map, for `i < 6`.
+pub fn frobenius_coefficients(k: u32) -> Vec<Fp2> {
+    let exponent = (fp_modulus().pow(k) - 1u32) / 6u32;
+    let gamma = Fp2::xi().pow(&exponent);
+    (0..6u32).map(|i| gamma.pow(&BigUint::from(i))).collect()
+}
+
+/// An element of `Fp12 = Fp2[w]/(w^6 - xi)`, as its coefficients in `1, w, ..., w^5`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fp12(pub [Fp2; 6]);
+
+impl Fp12 {
+    /// The `p^6`-th power, which is the inverse of unitary elements.
+    pub fn conjugate(&self) -> Self {
+        let mut result = self.clone();
+        for i in [1, 3, 5] {
+            result.0[i] = -result.0[i].clone();
+        }
+        result
+    }
+
+    /// The `p^k`-th power.
+    pub fn frobenius(&self, k: u32) -> Self {
+        let gammas = frobenius_coefficients(k);
+        let mut result = self.clone();
+        for (c, gamma) in result.0.iter_mut().zip(gammas) {
+            if k % 2 == 1 {
+                *c = c.conjugate();
+            }
+            *c = c.clone() * gamma;
+        }
+        result
+    }
+}
+
+impl Add for Fp12 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut result = self;
+        for (a, b) in result.0.iter_mut().zip(rhs.0) {
+            *a = a.clone() + b;
+        }
+        result
+    }
+}
+
+impl Sub for Fp12 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+impl Mul for Fp12 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let mut result = Self::zero();
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in rhs.0.iter().enumerate() {
+                let product = a.clone() * b.clone();
+                if i + j < 6 {
+                    result.0[i + j] = result.0[i + j].clone() + product;
+                } else {
+                    result.0[i + j - 6] = result.0[i + j - 6].clone() + Fp2::xi() * product;
+                }
+            }
+        }
+        result
+    }
+}
+
+impl Neg for Fp12 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.map(|c| -c))
+    }
+}
+
+impl NativeField for Fp12 {
+    fn zero() -> Self {
+        Self(core::array::from_fn(|_| Fp2::zero()))
+    }
+
+    fn one() -> Self {
+        let mut result = Self::zero();
+        result.0[0] = Fp2::one();
+        result
+    }
+
+    fn from_u64(value: u64) -> Self {
+        let mut result = Self::zero();
+        result.0[0] = Fp2::from_u64(value);
+        result
+    }
+
+    fn inverse(&self) -> Self {
+        // `self * conj(self)` lies in `Fp6 = Fp2[v]/(v^3 - xi)` with `v = w^2`, where the inverse
+        // has a closed form.
+        let g = self.clone() * self.conjugate();
+        let (a0, a1, a2) = (g.0[0].clone(), g.0[2].clone(), g.0[4].clone());
+        let xi = Fp2::xi();
+        let t0 = a0.square() - xi.clone() * a1.clone() * a2.clone();
+        let t1 = xi.clone() * a2.square() - a0.clone() * a1.clone();
+        let t2 = a1.square() - a0.clone() * a2.clone();
+        let norm = a0 * t0.clone() + xi * (a2 * t1.clone() + a1 * t2.clone());
+        let norm_inverse = norm.inverse();
+
+        let mut g_inverse = Self::zero();
+        g_inverse.0[0] = t0 * norm_inverse.clone();
+        g_inverse.0[2] = t1 * norm_inverse.clone();
+        g_inverse.0[4] = t2 * norm_inverse;
+        self.conjugate() * g_inverse
+    }
+}
+
+/// A point of `G1`, on `y^2 = x^3 + 4` over `Fp`.
+pub type G1Affine = Affine<Fp>;
+
+/// A point of `G2`, on the twist `y^2 = x^3 + 4 * xi` over `Fp2`.
+pub type G2Affine = Affine<Fp2>;
+
+/// Sets the flags of a compressed point, whose `x` coordinate is already in `bytes`.
+fn set_flags(bytes: &mut [u8], is_lexicographically_largest: bool) {
+    bytes[0] |= COMPRESSION_FLAG;
+    if is_lexicographically_largest {
+        bytes[0] |= SIGN_FLAG;
+    }
+}
+
+fn fp_to_be_bytes(value: &Fp) -> Vec<u8> {
+    let bytes = value.0.to_bytes_be();
+    let mut padded = vec![0u8; 48 - bytes.len()];
+    padded.extend(bytes);
+    padded
+}
+
+impl G1Affine {
+    pub fn generator() -> Self {
+        Self::new(
+            Fp::from_hex("17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"),
+            Fp::from_hex("08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1"),
+        )
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        self.y.square() == self.x.square() * self.x.clone() + Fp::from_u64(4)
+    }
+
+    pub fn compress(&self) -> [u8; 48] {
+        let mut bytes: [u8; 48] = fp_to_be_bytes(&self.x).try_into().unwrap();
+        set_flags(&mut bytes, self.y.is_lexicographically_largest());
+        bytes
+    }
+}
+
+impl G2Affine {
+    pub fn generator() -> Self {
+        Self::new(
+            Fp2::from_hex(
+                "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8",
+                "13e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e",
+            ),
+            Fp2::from_hex(
+                "0ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801",
+                "0606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be",
+            ),
+        )
+    }
+
+    /// The coefficient `4 * xi` of the twist.
+    pub fn twist_b() -> Fp2 {
+        Fp2::from_u64(4) * Fp2::xi()
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        self.y.square() == self.x.square() * self.x.clone() + Self::twist_b()
+    }
+
+    /// The coefficients of the endomorphism `psi`, which are `xi^(-(p - 1) / 3)` and
+    /// `xi^(-(p - 1) / 2)` as the twist is a multiplicative one.
+    pub fn psi_coefficients() -> (Fp2, Fp2) {
+        let gammas = frobenius_coefficients(1);
+        (gammas[2].inverse(), gammas[3].inverse())
+    }
+
+    /// The endomorphism `psi` obtained by untwisting, applying the Frobenius map and twisting back.
+    pub fn psi(&self) -> Self {
+        let (gamma_x, gamma_y) = Self::psi_coefficients();
+        Self::new(self.x.conjugate() * gamma_x, self.y.conjugate() * gamma_y)
+    }
+
+    /// The point multiplied by the negative parameter `x` of the curve, `None` for the point at
+    /// infinity.
+    pub fn mul_by_x(&self) -> Option<Self> {
+        self.scalar_mul(&BigUint::from(BLS_X))
+            .map(|point| point.negate())
+    }
+
+    pub fn compress(&self) -> [u8; 96] {
+        let mut bytes = fp_to_be_bytes(&self.x.c1);
+        bytes.extend(fp_to_be_bytes(&self.x.c0));
+        let mut bytes: [u8; 96] = bytes.try_into().unwrap();
+        set_flags(&mut bytes, self.y.is_lexicographically_largest());
+        bytes
+    }
+}
+
+/// Adds two points, either of which may be the point at infinity.
+fn add_points(a: Option<G2Affine>, b: Option<G2Affine>) -> Option<G2Affine> {
+    match (a, b) {
+        (Some(a), Some(b)) => a.checked_add(&b),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// The `expand_message_xmd` function of RFC 9380 with SHA-256.
+pub fn expand_message_xmd(message: &[u8], dst: &[u8], len_in_bytes: usize) -> Vec<u8> {
+    let ell = len_in_bytes.div_ceil(32);
+    assert!(ell <= 255 && dst.len() <= 255);
+    let mut dst_prime = dst.to_vec();
+    dst_prime.push(dst.len() as u8);
+
+    let mut b0_input = vec![0u8; 64];
+    b0_input.extend(message);
+    b0_input.extend((len_in_bytes as u16).to_be_bytes());
+    b0_input.push(0);
+    b0_input.extend(&dst_prime);
+    let b0 = sha256(&b0_input);
+
+    let mut blocks: Vec<[u8; 32]> = Vec::new();
+    for i in 1..=ell {
+        let mut input = match blocks.last() {
+            None => b0.to_vec(),
+            Some(previous) => b0.iter().zip(previous).map(|(a, b)| a ^ b).collect(),
+        };
+        input.push(i as u8);
+        input.extend(&dst_prime);
+        blocks.push(sha256(&input));
+    }
+    let mut output = blocks.concat();
+    output.truncate(len_in_bytes);
+    output
+}
+
+/// Hashes a message to two elements of `Fp2`, each from 128 bytes of `expand_message_xmd`.
+pub fn hash_to_field_fp2(message: &[u8], dst: &[u8]) -> [Fp2; 2] {
+    let bytes = expand_message_xmd(message, dst, 256);
+    let element = |i: usize| Fp::new(BigUint::from_bytes_be(&bytes[64 * i..64 * (i + 1)]));
+    [
+        Fp2::new(element(0), element(1)),
+        Fp2::new(element(2), element(3)),
+    ]
+}
+
+/// The coefficients `A' = 240 * u` and `B' = 1012 * (1 + u)` of the curve `y^2 = x^3 + A' x + B'`
+/// isogenous to the twist, and the non-square `Z = -(2 + u)` of the simplified SWU map.
+pub fn sswu_constants() -> (Fp2, Fp2, Fp2) {
+    (
+        Fp2::new(Fp::zero(), Fp::from_u64(240)),
+        Fp2::new(Fp::from_u64(1012), Fp::from_u64(1012)),
+        -Fp2::new(Fp::from_u64(2), Fp::one()),
+    )
+}
+
+/// The simplified SWU map of RFC 9380 to the curve isogenous to the twist.
+pub fn sswu_map(u: &Fp2) -> Affine<Fp2> {
+    let (a, b, z) = sswu_constants();
+    let z_u2 = z.clone() * u.square();
+    let tv = z_u2.square() + z_u2.clone();
+    let x1 = if tv.is_zero() {
+        b.clone() * (z * a.clone()).inverse()
+    } else {
+        -b.clone() * a.inverse() * (Fp2::one() + tv.inverse())
+    };
+    let curve = |x: &Fp2| x.square() * x.clone() + a.clone() * x.clone() + b.clone();
+    let (x, y) = match curve(&x1).sqrt() {
+        Some(y) => (x1, y),
+        None => {
+            let x2 = z_u2 * x1;
+            let y = curve(&x2).sqrt().unwrap();
+            (x2, y)
+        }
+    };
+    let y = if y.sgn0() == u.sgn0() { y } else { -y };
+    Affine::new(x, y)
+}
+
+/// The coefficients, constant term first, of the polynomials `x_num`, `x_den`, `y_num` and `y_den`
+/// of the 3-isogeny of RFC 9380 from the curve of the simplified SWU map to the twist, as the
+/// coordinates `(c0, c1)` in hexadecimal.
+const ISO_COEFFICIENTS: [&[(&str, &str)]; 4] = [
+    &[
+        (
+            "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97d6",
+            "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97d6",
+        ),
+        (
+            "0",
+            "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71a",
+        ),
+        (
+            "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71e",
+            "8ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063fcd104635a790520c0a395554e5c6aaaa9354ffffffffe38d",
+        ),
+        (
+            "171d6541fa38ccfaed6dea691f5fb614cb14b4e7f4e810aa22d6108f142b85757098e38d0f671c7188e2aaaaaaaa5ed1",
+            "0",
+        ),
+    ],
+    &[
+        (
+            "0",
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa63",
+        ),
+        (
+            "c",
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa9f",
+        ),
+        ("1", "0"),
+    ],
+    &[
+        (
+            "1530477c7ab4113b59a4c18b076d11930f7da5d4a07f649bf54439d87d27e500fc8c25ebf8c92f6812cfc71c71c6d706",
+            "1530477c7ab4113b59a4c18b076d11930f7da5d4a07f649bf54439d87d27e500fc8c25ebf8c92f6812cfc71c71c6d706",
+        ),
+        (
+            "0",
+            "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97be",
+        ),
+        (
+            "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71c",
+            "8ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063fcd104635a790520c0a395554e5c6aaaa9354ffffffffe38f",
+        ),
+        (
+            "124c9ad43b6cf79bfbf7043de3811ad0761b0f37a1e26286b0e977c69aa274524e79097a56dc4bd9e1b371c71c718b10",
+            "0",
+        ),
+    ],
+    &[
+        (
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa8fb",
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa8fb",
+        ),
+        (
+            "0",
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa9d3",
+        ),
+        (
+            "12",
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa99",
+        ),
+        ("1", "0"),
+    ],
+];
+
+/// The polynomials `x_num`, `x_den`, `y_num` and `y_den` of the 3-isogeny to the twist.
+pub fn iso_coefficients() -> [Vec<Fp2>; 4] {
+    ISO_COEFFICIENTS.map(|coefficients| {
+        coefficients
+            .iter()
+            .map(|(c0, c1)| Fp2::from_hex(c0, c1))
+            .collect()
+    })
+}
+
+fn evaluate(polynomial: &[Fp2], x: &Fp2) -> Fp2 {
+    polynomial
+        .iter()
+        .rev()
+        .fold(Fp2::zero(), |acc, c| acc * x.clone() + c.clone())
+}
+
+/// Maps a point of the curve of the simplified SWU map to the twist.
+pub fn iso_map(point: &Affine<Fp2>) -> G2Affine {
+    let [x_num, x_den, y_num, y_den] = iso_coefficients();
+    let x = evaluate(&x_num, &point.x) * evaluate(&x_den, &point.x).inverse();
+    let y = point.y.clone() * evaluate(&y_num, &point.x) * evaluate(&y_den, &point.x).inverse();
+    Affine::new(x, y)
+}
+
+/// Multiplies a point of the twist by the effective cofactor of RFC 9380 with the endomorphism
+/// `psi`, as `[x^2 - x - 1] P + [x - 1] psi(P) + psi^2(2P)`.
+pub fn clear_cofactor(point: &G2Affine) -> Option<G2Affine> {
+    let psi = |point: Option<G2Affine>| point.map(|point| point.psi());
+    let mul_by_x = |point: Option<G2Affine>| point.and_then(|point| point.mul_by_x());
+    let neg = |point: Option<G2Affine>| point.map(|point| point.negate());
+
+    let p = Some(point.clone());
+    let t1 = mul_by_x(p.clone());
+    let t2 = psi(p.clone());
+    let t3 = psi(psi(p.clone().map(|point| point.double())));
+    let t3 = add_points(t3, neg(t2.clone()));
+    let t2 = mul_by_x(add_points(t1.clone(), t2));
+    let t3 = add_points(add_points(t3, t2), neg(t1));
+    add_points(t3, neg(p))
+}
+
+/// Hashes a message to a point of `G2` with the domain separation tag `dst`.
+pub fn hash_to_g2(message: &[u8], dst: &[u8]) -> G2Affine {
+    let [u0, u1] = hash_to_field_fp2(message, dst);
+    let q0 = iso_map(&sswu_map(&u0));
+    let q1 = iso_map(&sswu_map(&u1));
+    let sum = q0
+        .checked_add(&q1)
+        .expect("sum of the maps is the point at infinity");
+    clear_cofactor(&sum).expect("hash is the point at infinity")
+}
+
+/// Hashes a message to a point of `G1` by try-and-increment: the first `x = H(message || i)`
+/// with `x^3 + 4` a square, multiplied by the effective cofactor `1 - x` of RFC 9380.
+///
+/// Unlike `hash_to_g2`, the map is not constant-time, so it is only meant to derive public points
+/// whose discrete logarithm must be unknown, such as the offsets of the incomplete additions.
+pub fn hash_to_g1_by_increment(message: &[u8], dst: &[u8]) -> G1Affine {
+    let cofactor = BigUint::from(BLS_X) + 1u32;
+    (0u32..)
+        .find_map(|i| {
+            let input = [message, &i.to_be_bytes()].concat();
+            let x = Fp::new(BigUint::from_bytes_be(&expand_message_xmd(&input, dst, 64)));
+            let y = (x.square() * x.clone() + Fp::from_u64(4)).sqrt()?;
+            G1Affine::new(x, y).scalar_mul(&cofactor)
+        })
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::bytes;
+
+    #[test]
+    fn test_native_bls12_381() {
+        let r = fr_modulus();
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+        assert!(g1.is_on_curve());
+        assert!(g2.is_on_curve());
+        assert_eq!(g1.scalar_mul(&r), None);
+        assert_eq!(g2.scalar_mul(&r), None);
+        assert_eq!(g2.psi(), g2.mul_by_x().unwrap());
+
+        let g1_compressed: [u8; 48] = bytes!("97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb");
+        assert_eq!(g1.compress(), g1_compressed);
+        let g2_compressed: [u8; 96] = bytes!("93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8");
+        assert_eq!(g2.compress(), g2_compressed);
+
+        let element = Fp2::new(Fp::from_u64(12345), -Fp::from_u64(678));
+        let square = element.square();
+        let root = square.sqrt().unwrap();
+        assert!(root == element || root == -element);
+        let (_, _, z) = sswu_constants();
+        assert_eq!(z.sqrt(), None);
+    }
+
+    #[test]
+    fn test_expand_message_xmd() {
+        // A test vector of RFC 9380.
+        let output = expand_message_xmd(b"", b"QUUX-V01-CS02-with-expander-SHA256-128", 32);
+        let expected: Vec<u8> =
+            bytes!("68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235");
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_hash_to_g1_by_increment() {
+        let point = hash_to_g1_by_increment(b"message", b"plonky2x-test");
+        assert!(point.is_on_curve());
+        assert_eq!(point.scalar_mul(&fr_modulus()), None);
+        assert_ne!(
+            point,
+            hash_to_g1_by_increment(b"message", b"plonky2x-other")
+        );
+    }
+
+    #[test]
+    fn test_hash_to_g2() {
+        // A test vector of RFC 9380.
+        let point = hash_to_g2(b"", b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_");
+        let expected = G2Affine::new(
+            Fp2::from_hex(
+                "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a",
+                "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d",
+            ),
+            Fp2::from_hex(
+                "0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+                "12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6",
+            ),
+        );
+        assert_eq!(point, expected);
+        assert_eq!(point.scalar_mul(&fr_modulus()), None);
+    }
+}
@@ -0,0 +1,166 @@
+//! The optimal ate pairing over BLS12-381.
+//!
+//! The Miller loop runs over the bits of `|x|` with the `G2` points in affine coordinates on the
+//! twist, and evaluates each line at the `G1` point as a sparse element of `Fp12`. As `x` is
+//! negative, the output of the loop is conjugated.
+
+use num_bigint::BigUint;
+
+use super::curve::{G1AffineVariable, G2AffineVariable};
+use super::fp::{Fp12Coefficient, Fp12Variable, Fp2Poly, FpVariable};
+use super::native::BLS_X;
+use crate::prelude::*;
+
+#[derive(Debug, Clone)]
+struct MillerPair {
+    x_p: FpVariable,
+    y_p: FpVariable,
+    t: G2AffineVariable,
+    q: G2AffineVariable,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Evaluates the line of the next step at the `G1` point of the pair, advancing its `G2` point
+    /// by doubling it or by adding `Q`.
+    fn bls12_381_line(
+        &mut self,
+        pair: &mut MillerPair,
+        is_double: bool,
+    ) -> Vec<(usize, Fp12Coefficient)> {
+        let (next, slope) = if is_double {
+            self.bls12_381_g2_double_with_slope(&pair.t)
+        } else {
+            self.bls12_381_g2_add_with_chord(&pair.t, &pair.q)
+        };
+        let slope_x = self.fp2_poly_mul(&slope, &pair.t.x);
+        let y = self.fp2_to_poly(&pair.t.y);
+        let c0 = self.fp2_poly_sub(&slope_x, &y);
+        let c0 = self.fp2_reduce(&c0);
+        pair.t = next;
+
+        let slope_x_p = self.fp2_poly_mul_fp(&slope, &pair.x_p);
+        let c2 = self.fp2_poly_sub(&Fp2Poly::zero(), &slope_x_p);
+        let c2 = self.fp2_reduce(&c2);
+        vec![
+            (0, Fp12Coefficient::Fp2(c0)),
+            (2, Fp12Coefficient::Fp2(c2)),
+            (3, Fp12Coefficient::Fp(pair.y_p.clone())),
+        ]
+    }
+
+    /// The product of the Miller loops of the given pairs.
+    pub fn bls12_381_miller_loop(
+        &mut self,
+        pairs: &[(G1AffineVariable, G2AffineVariable)],
+    ) -> Fp12Variable {
+        let mut states = pairs
+            .iter()
+            .map(|(p, q)| MillerPair {
+                x_p: p.x.clone(),
+                y_p: p.y.clone(),
+                t: q.clone(),
+                q: q.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let x = BigUint::from(BLS_X);
+        let mut f: Option<Fp12Variable> = None;
+        for i in (0..x.bits() - 1).rev() {
+            if let Some(value) = &f {
+                f = Some(self.fp12_square(value));
+            }
+            for pair in states.iter_mut() {
+                let line = self.bls12_381_line(pair, true);
+                f = Some(self.fp12_mul_by_line(f, line));
+            }
+            if x.bit(i) {
+                for pair in states.iter_mut() {
+                    let line = self.bls12_381_line(pair, false);
+                    f = Some(self.fp12_mul_by_line(f, line));
+                }
+            }
+        }
+        let f = f.expect("no pairs");
+        self.fp12_conjugate(&f)
+    }
+
+    /// Raises an element of the cyclotomic subgroup to the power of the curve parameter `x`,
+    /// which is negative, so that the result is the conjugate of the power `|x|`.
+    pub fn fp12_pow_bls_x(&mut self, a: &Fp12Variable) -> Fp12Variable {
+        let power = self.fp12_pow(a, &BigUint::from(BLS_X));
+        self.fp12_conjugate(&power)
+    }
+
+    /// Raises the output of a Miller loop to the power `3 * (p^12 - 1) / r`, which maps it to
+    /// one exactly when the power `(p^12 - 1) / r` does, as `r` is prime to 3.
+    pub fn bls12_381_final_exponentiation(&mut self, f: &Fp12Variable) -> Fp12Variable {
+        // The easy part, `f^((p^6 - 1) * (p^2 + 1))`, maps `f` to the cyclotomic subgroup.
+        let inverse = self.fp12_inverse(f);
+        let conjugate = self.fp12_conjugate(f);
+        let f = self.fp12_mul(&conjugate, &inverse);
+        let frobenius = self.fp12_frobenius(&f, 2);
+        let f = self.fp12_mul(&frobenius, &f);
+
+        // The hard part, `f^(3 * (p^4 - p^2 + 1) / r)`, with the decomposition
+        // `3 * (p^4 - p^2 + 1) / r = l0 + l1 * p + l2 * p^2 + l3 * p^3` of Hayashida et al., where
+        // `l3 = (x - 1)^2`, `l2 = l3 * x`, `l1 = l2 * x - l3` and `l0 = l1 * x + 3`.
+        let f_conjugate = self.fp12_conjugate(&f);
+        let a = self.fp12_pow_bls_x(&f);
+        let a = self.fp12_mul(&a, &f_conjugate);
+        let a_conjugate = self.fp12_conjugate(&a);
+        let l3 = self.fp12_pow_bls_x(&a);
+        let l3 = self.fp12_mul(&l3, &a_conjugate);
+        let l2 = self.fp12_pow_bls_x(&l3);
+        let l3_conjugate = self.fp12_conjugate(&l3);
+        let l1 = self.fp12_pow_bls_x(&l2);
+        let l1 = self.fp12_mul(&l1, &l3_conjugate);
+        let f_squared = self.fp12_square(&f);
+        let f_cubed = self.fp12_mul(&f_squared, &f);
+        let l0 = self.fp12_pow_bls_x(&l1);
+        let l0 = self.fp12_mul(&l0, &f_cubed);
+
+        let l1 = self.fp12_frobenius(&l1, 1);
+        let l2 = self.fp12_frobenius(&l2, 2);
+        let l3 = self.fp12_frobenius(&l3, 3);
+        let result = self.fp12_mul(&l0, &l1);
+        let result = self.fp12_mul(&result, &l2);
+        self.fp12_mul(&result, &l3)
+    }
+
+    /// Checks that `prod_i e(P_i, Q_i) = 1` for the given pairs `(P_i, Q_i)`.
+    pub fn bls12_381_pairing_check(&mut self, pairs: &[(G1AffineVariable, G2AffineVariable)]) {
+        let f = self.bls12_381_miller_loop(pairs);
+        let f = self.bls12_381_final_exponentiation(&f);
+        self.fp12_assert_is_one(&f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::ecc::bls12_381::native::{G1Affine, G2Affine};
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_bls12_381_pairing_check() {
+        let mut builder = DefaultBuilder::new();
+        let p = builder.read::<G1AffineVariable>();
+        let q = builder.read::<G2AffineVariable>();
+        let r = builder.read::<G1AffineVariable>();
+        let s = builder.read::<G2AffineVariable>();
+        builder.bls12_381_pairing_check(&[(p, q), (r, s)]);
+        let circuit = builder.build();
+
+        // `e([6] G1, [5] G2) * e(-[10] G1, [3] G2) = 1`.
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+        let mul = |n: u32| BigUint::from(n);
+        let mut input = circuit.input();
+        input.write::<G1AffineVariable>(g1.scalar_mul(&mul(6)).unwrap().into());
+        input.write::<G2AffineVariable>(g2.scalar_mul(&mul(5)).unwrap().into());
+        input.write::<G1AffineVariable>(g1.scalar_mul(&mul(10)).unwrap().negate().into());
+        input.write::<G2AffineVariable>(g2.scalar_mul(&mul(3)).unwrap().into());
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
@@ -0,0 +1,135 @@
+//! Verification of aggregate BLS signatures as used by the Ethereum sync committee, with public
+//! keys in `G1` and signatures in `G2`.
+//!
+//! `bls12_381_fast_aggregate_verify` follows `FastAggregateVerify` of the proof-of-possession
+//! scheme: the keys of the participants are added and the aggregate signature is checked with a
+//! single pairing check. The keys are assumed to be valid points of `G1`, as the beacon chain
+//! checks them when they are deposited, while the signature is checked to lie in `G2`.
+
+use super::curve::{G1AffineVariable, G2AffineVariable};
+use super::native::{hash_to_g1_by_increment, G1Affine, OFFSET_DST};
+use crate::prelude::*;
+
+/// The domain separation tag of the proof-of-possession scheme, used by the beacon chain.
+pub const BLS_SIGNATURE_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// A point of `G1` hashed to the curve, whose discrete logarithm is unknown, which the sum of the
+/// keys starts from so that the incomplete additions never involve the point at infinity: keys
+/// crafted to hit an exceptional case would need a relation to the offset.
+fn aggregation_offset() -> G1Affine {
+    hash_to_g1_by_increment(b"aggregation offset", OFFSET_DST)
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Adds the keys whose participation bit is set, of which there must be at least one for the
+    /// proof to be valid.
+    pub fn bls12_381_aggregate_pubkeys(
+        &mut self,
+        pubkeys: &[G1AffineVariable],
+        participation: &[BoolVariable],
+    ) -> G1AffineVariable {
+        assert_eq!(pubkeys.len(), participation.len());
+        let offset = aggregation_offset();
+        let mut sum = self.bls12_381_g1_constant(&offset);
+        for (pubkey, bit) in pubkeys.iter().zip(participation.iter()) {
+            let with_pubkey = self.bls12_381_g1_add(&sum, pubkey);
+            sum = self.select(*bit, with_pubkey, sum);
+        }
+        let neg_offset = self.bls12_381_g1_constant(&offset.negate());
+        self.bls12_381_g1_add(&sum, &neg_offset)
+    }
+
+    /// Checks that `signature` is an aggregate signature of `message` by the keys whose
+    /// participation bit is set, as `e(sum_i pk_i, H(m)) = e(G1, signature)`.
+    pub fn bls12_381_fast_aggregate_verify(
+        &mut self,
+        pubkeys: &[G1AffineVariable],
+        participation: &[BoolVariable],
+        message: &[ByteVariable],
+        signature: &G2AffineVariable,
+    ) {
+        self.bls12_381_g2_assert_on_curve(signature);
+        self.bls12_381_g2_assert_in_subgroup(signature);
+        let aggregate_pubkey = self.bls12_381_aggregate_pubkeys(pubkeys, participation);
+        let message_point = self.bls12_381_hash_to_g2(message, BLS_SIGNATURE_DST);
+        let neg_generator = self.bls12_381_g1_constant(&G1Affine::generator().negate());
+        self.bls12_381_pairing_check(&[
+            (aggregate_pubkey, message_point),
+            (neg_generator, signature.clone()),
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+    use crate::frontend::ecc::bls12_381::native::{fr_modulus, hash_to_g2, G2Affine};
+    use crate::utils::hash::sha256;
+
+    const NUM_PUBKEYS: usize = 4;
+
+    fn signing_keys() -> Vec<BigUint> {
+        (0..NUM_PUBKEYS as u32)
+            .map(|i| BigUint::from_bytes_be(&sha256(&i.to_be_bytes())) % fr_modulus())
+            .collect()
+    }
+
+    fn aggregate_signature(message: &[u8], participation: &[bool]) -> G2Affine {
+        let point = hash_to_g2(message, BLS_SIGNATURE_DST);
+        let secret = signing_keys()
+            .into_iter()
+            .zip(participation)
+            .filter(|(_, bit)| **bit)
+            .fold(BigUint::from(0u32), |acc, (secret, _)| acc + secret);
+        point.scalar_mul(&(secret % fr_modulus())).unwrap()
+    }
+
+    fn verify(signature: G2Affine) {
+        let mut builder = DefaultBuilder::new();
+        let pubkeys = (0..NUM_PUBKEYS)
+            .map(|_| {
+                let bytes = builder.read::<BytesVariable<48>>();
+                builder.bls12_381_g1_decompress(&bytes.0)
+            })
+            .collect::<Vec<_>>();
+        let participation = builder.read::<ArrayVariable<BoolVariable, NUM_PUBKEYS>>();
+        let message = builder.read::<Bytes32Variable>();
+        let signature_bytes = builder.read::<BytesVariable<96>>();
+        let signature_point = builder.bls12_381_g2_decompress(&signature_bytes.0);
+        builder.bls12_381_fast_aggregate_verify(
+            &pubkeys,
+            participation.as_slice(),
+            &message.as_bytes(),
+            &signature_point,
+        );
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        for secret in signing_keys() {
+            let pubkey = G1Affine::generator().scalar_mul(&secret).unwrap();
+            input.write::<BytesVariable<48>>(pubkey.compress());
+        }
+        input.write::<ArrayVariable<BoolVariable, NUM_PUBKEYS>>(vec![true, false, true, true]);
+        input.write::<Bytes32Variable>(sha256(b"sync committee").into());
+        input.write::<BytesVariable<96>>(signature.compress());
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_bls12_381_fast_aggregate_verify() {
+        let message = sha256(b"sync committee");
+        verify(aggregate_signature(&message, &[true, false, true, true]));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    #[should_panic]
+    fn test_bls12_381_fast_aggregate_verify_wrong_participation() {
+        let message = sha256(b"sync committee");
+        verify(aggregate_signature(&message, &[true, true, true, true]));
+    }
+}
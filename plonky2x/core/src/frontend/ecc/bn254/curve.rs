@@ -0,0 +1,230 @@
+//! Arithmetic over the groups `G1` and `G2` of BN254, in affine coordinates.
+//!
+//! Additions use incomplete formulas and constrain the points to have distinct `x` coordinates, so
+//! the rare inputs hitting the exceptional cases make the proof fail instead of being accepted.
+
+use num::One;
+use num_bigint::BigUint;
+
+use super::fq::{Fq2Value, Fq2Variable, FqVariable};
+use super::native::{frobenius_coefficients, Fq, Fq2, G1Affine, G2Affine, NativeField, BN_X};
+use crate::prelude::*;
+
+/// A point of `G1`.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(G1AffineValue)]
+pub struct G1AffineVariable {
+    pub x: FqVariable,
+    pub y: FqVariable,
+}
+
+/// A point of the twist `G2`.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(G2AffineValue)]
+pub struct G2AffineVariable {
+    pub x: Fq2Variable,
+    pub y: Fq2Variable,
+}
+
+impl<F: RichField> From<G1Affine> for G1AffineValue<F> {
+    fn from(point: G1Affine) -> Self {
+        Self {
+            x: point.x.0,
+            y: point.y.0,
+        }
+    }
+}
+
+impl<F: RichField> From<G2Affine> for G2AffineValue<F> {
+    fn from(point: G2Affine) -> Self {
+        Self {
+            x: Fq2Value::from(point.x),
+            y: Fq2Value::from(point.y),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    pub fn g1_constant(&mut self, point: &G1Affine) -> G1AffineVariable {
+        G1AffineVariable {
+            x: self.fp_constant(&point.x),
+            y: self.fp_constant(&point.y),
+        }
+    }
+
+    pub fn g1_assert_on_curve(&mut self, point: &G1AffineVariable) {
+        let x_squared = self.fp_mul(&point.x, &point.x);
+        let x_cubed = self.fp_poly_mul(&x_squared, &point.x);
+        let b = self.fp_constant(&Fq::from_u64(3));
+        let b = self.fp_to_poly(&b);
+        let rhs = self.fp_poly_add(&x_cubed, &b);
+        let lhs = self.fp_poly_mul(&point.y, &point.y);
+        let difference = self.fp_poly_sub(&lhs, &rhs);
+        let difference = self.fp_reduce(&difference);
+        let zero = self.fp_constant(&Fq::zero());
+        self.assert_is_equal(difference, zero);
+    }
+
+    pub fn g1_neg(&mut self, point: &G1AffineVariable) -> G1AffineVariable {
+        G1AffineVariable {
+            x: point.x.clone(),
+            y: self.fp_neg(&point.y),
+        }
+    }
+
+    /// Adds two points with distinct `x` coordinates.
+    pub fn g1_add(&mut self, a: &G1AffineVariable, b: &G1AffineVariable) -> G1AffineVariable {
+        let dy = self.fp_sub(&b.y, &a.y);
+        let dx = self.fp_sub(&b.x, &a.x);
+        let slope = self.fp_div(&dy, &dx);
+
+        let slope_squared = self.fp_poly_mul(&slope, &slope);
+        let x_sum = self.fp_to_poly(&a.x);
+        let b_x = self.fp_to_poly(&b.x);
+        let x_sum = self.fp_poly_add(&x_sum, &b_x);
+        let x = self.fp_poly_sub(&slope_squared, &x_sum);
+        let x = self.fp_reduce(&x);
+
+        let dx = self.fp_sub(&a.x, &x);
+        let y = self.fp_poly_mul(&slope, &dx);
+        let a_y = self.fp_to_poly(&a.y);
+        let y = self.fp_poly_sub(&y, &a_y);
+        let y = self.fp_reduce(&y);
+        G1AffineVariable { x, y }
+    }
+
+    /// Computes `base + sum_i scalars[i] * points[i]` for constant points and scalars given as
+    /// little-endian bits, with one conditional addition of a precomputed multiple per bit.
+    pub fn g1_linear_combination(
+        &mut self,
+        base: &G1Affine,
+        points: &[G1Affine],
+        scalars: &[Vec<BoolVariable>],
+    ) -> G1AffineVariable {
+        assert_eq!(points.len(), scalars.len());
+        let mut result = self.g1_constant(base);
+        for (point, bits) in points.iter().zip(scalars.iter()) {
+            let mut multiple = point.clone();
+            for bit in bits.iter() {
+                let addend = self.g1_constant(&multiple);
+                let sum = self.g1_add(&result, &addend);
+                result = self.select(*bit, sum, result);
+                multiple = multiple.double();
+            }
+        }
+        result
+    }
+
+    pub fn g2_constant(&mut self, point: &G2Affine) -> G2AffineVariable {
+        G2AffineVariable {
+            x: self.fp2_constant(&point.x),
+            y: self.fp2_constant(&point.y),
+        }
+    }
+
+    pub fn g2_assert_on_curve(&mut self, point: &G2AffineVariable) {
+        let x_squared = self.fp2_mul(&point.x, &point.x);
+        let x_cubed = self.fp2_poly_mul(&x_squared, &point.x);
+        let b = self.fp2_constant(&G2Affine::twist_b());
+        let b = self.fp2_to_poly(&b);
+        let rhs = self.fp2_poly_add(&x_cubed, &b);
+        let lhs = self.fp2_poly_mul(&point.y, &point.y);
+        let difference = self.fp2_poly_sub(&lhs, &rhs);
+        let difference = self.fp2_reduce(&difference);
+        let zero = self.fp2_constant(&Fq2::zero());
+        self.assert_is_equal(difference, zero);
+    }
+
+    /// Checks that a point of the twist lies in the subgroup `G2` of order `r`, using that
+    /// `psi(Q) = [6x^2] Q` exactly for points of `G2`.
+    pub fn g2_assert_in_subgroup(&mut self, point: &G2AffineVariable) {
+        let x = BigUint::from(BN_X);
+        let multiple = self.g2_scalar_mul(point, &(BigUint::from(6u32) * &x * &x));
+        let frobenius = self.g2_frobenius(point);
+        self.assert_is_equal(multiple, frobenius);
+    }
+
+    pub fn g2_neg(&mut self, point: &G2AffineVariable) -> G2AffineVariable {
+        G2AffineVariable {
+            x: point.x.clone(),
+            y: self.fp2_neg(&point.y),
+        }
+    }
+
+    /// The endomorphism `psi`, obtained by untwisting, applying the Frobenius map and twisting back.
+    pub fn g2_frobenius(&mut self, point: &G2AffineVariable) -> G2AffineVariable {
+        let gammas = frobenius_coefficients(1);
+        let x = self.fp2_conjugate(&point.x);
+        let gamma_x = self.fp2_constant(&gammas[2]);
+        let y = self.fp2_conjugate(&point.y);
+        let gamma_y = self.fp2_constant(&gammas[3]);
+        G2AffineVariable {
+            x: self.fp2_mul(&x, &gamma_x),
+            y: self.fp2_mul(&y, &gamma_y),
+        }
+    }
+
+    /// The third point on the line of the given slope through `a` and `b`, negated.
+    fn g2_add_with_slope(
+        &mut self,
+        a: &G2AffineVariable,
+        b: &G2AffineVariable,
+        slope: &Fq2Variable,
+    ) -> G2AffineVariable {
+        let slope_squared = self.fp2_poly_mul(slope, slope);
+        let a_x = self.fp2_to_poly(&a.x);
+        let b_x = self.fp2_to_poly(&b.x);
+        let x_sum = self.fp2_poly_add(&a_x, &b_x);
+        let x = self.fp2_poly_sub(&slope_squared, &x_sum);
+        let x = self.fp2_reduce(&x);
+
+        let dx = self.fp2_sub(&a.x, &x);
+        let y = self.fp2_poly_mul(slope, &dx);
+        let a_y = self.fp2_to_poly(&a.y);
+        let y = self.fp2_poly_sub(&y, &a_y);
+        let y = self.fp2_reduce(&y);
+        G2AffineVariable { x, y }
+    }
+
+    /// Doubles a point, also returning the slope of the tangent.
+    pub fn g2_double_with_slope(
+        &mut self,
+        point: &G2AffineVariable,
+    ) -> (G2AffineVariable, Fq2Variable) {
+        let x_squared = self.fp2_poly_mul(&point.x, &point.x);
+        let numerator = self.fp2_poly_scale(&x_squared, 3);
+        let numerator = self.fp2_reduce(&numerator);
+        let denominator = self.fp2_add(&point.y, &point.y);
+        let slope = self.fp2_div(&numerator, &denominator);
+        (self.g2_add_with_slope(point, point, &slope), slope)
+    }
+
+    /// Adds two points with distinct `x` coordinates, also returning the slope of the chord.
+    pub fn g2_add_with_chord(
+        &mut self,
+        a: &G2AffineVariable,
+        b: &G2AffineVariable,
+    ) -> (G2AffineVariable, Fq2Variable) {
+        let dy = self.fp2_sub(&b.y, &a.y);
+        let dx = self.fp2_sub(&b.x, &a.x);
+        let slope = self.fp2_div(&dy, &dx);
+        (self.g2_add_with_slope(a, b, &slope), slope)
+    }
+
+    /// Multiplies a point by a constant scalar greater than one, by double-and-add.
+    pub fn g2_scalar_mul(
+        &mut self,
+        point: &G2AffineVariable,
+        scalar: &BigUint,
+    ) -> G2AffineVariable {
+        assert!(*scalar > BigUint::one());
+        let mut result = point.clone();
+        for i in (0..scalar.bits() - 1).rev() {
+            result = self.g2_double_with_slope(&result).0;
+            if scalar.bit(i) {
+                result = self.g2_add_with_chord(&result, point).0;
+            }
+        }
+        result
+    }
+}
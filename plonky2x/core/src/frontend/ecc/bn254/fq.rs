@@ -0,0 +1,80 @@
+//! The BN254 base field `Fq` and its tower `Fq2 = Fq[u]/(u^2 + 1)` and `Fq12 = Fq2[w]/(w^6 - xi)`
+//! with `xi = 9 + u`, as instances of the non-native arithmetic of the `tower` module.
+
+use num_bigint::BigUint;
+
+use super::native::{fq_modulus, frobenius_coefficients, Fq, Fq12, Fq2};
+use crate::frontend::ecc::tower::fp::{FpPoly, FpVariable};
+use crate::frontend::ecc::tower::fp12::{Fp12Coefficient, Fp12Variable};
+use crate::frontend::ecc::tower::fp2::{Fp2Poly, Fp2Value, Fp2Variable};
+use crate::frontend::ecc::tower::{TowerField, TowerFp12, TowerFp2};
+use crate::prelude::*;
+
+/// An element of the BN254 base field.
+pub type FqVariable = FpVariable<Fq>;
+pub type FqPoly = FpPoly<Fq>;
+pub type Fq2Variable = Fp2Variable<Fq>;
+pub type Fq2Value<F> = Fp2Value<Fq, F>;
+pub type Fq2Poly = Fp2Poly<Fq>;
+pub type Fq12Variable = Fp12Variable<Fq>;
+pub type Fq12Coefficient = Fp12Coefficient<Fq>;
+
+impl TowerField for Fq {
+    const NB_LIMBS: usize = 16;
+    const XI_C0: u64 = 9;
+
+    type Fp2 = Fq2;
+    type Fp12 = Fq12;
+
+    fn modulus() -> BigUint {
+        fq_modulus()
+    }
+
+    fn from_biguint(value: BigUint) -> Self {
+        Fq::new(value)
+    }
+
+    fn as_biguint(&self) -> &BigUint {
+        &self.0
+    }
+
+    fn frobenius_coefficients(k: u32) -> Vec<Fq2> {
+        frobenius_coefficients(k)
+    }
+}
+
+impl TowerFp2 for Fq2 {
+    type Base = Fq;
+
+    fn from_coefficients(c0: Fq, c1: Fq) -> Self {
+        Fq2::new(c0, c1)
+    }
+
+    fn coefficients(&self) -> [Fq; 2] {
+        [self.c0.clone(), self.c1.clone()]
+    }
+}
+
+impl TowerFp12 for Fq12 {
+    type Base = Fq;
+
+    fn from_coefficients(coefficients: [Fq2; 6]) -> Self {
+        Fq12(coefficients)
+    }
+
+    fn coefficients(&self) -> [Fq2; 6] {
+        self.0.clone()
+    }
+}
+
+impl<F: RichField> From<Fq2> for Fq2Value<F> {
+    fn from(value: Fq2) -> Self {
+        Self::from_native(&value)
+    }
+}
+
+impl<F: RichField> From<Fq2Value<F>> for Fq2 {
+    fn from(value: Fq2Value<F>) -> Self {
+        value.to_native()
+    }
+}
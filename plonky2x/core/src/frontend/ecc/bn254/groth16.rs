@@ -0,0 +1,323 @@
+//! Verification of Groth16 proofs over BN254, in the format produced by snarkjs.
+//!
+//! The verifying key is a constant of the circuit, while the proof and the public inputs are
+//! variables. A proof `(A, B, C)` is accepted for inputs `x_1, ..., x_n` if
+//! `e(A, B) = e(alpha, beta) * e(IC_0 + sum_i x_i * IC_i, gamma) * e(C, delta)`.
+
+use anyhow::{anyhow, ensure, Result};
+use ethers::types::U256;
+use num_bigint::BigUint;
+use serde_json::Value;
+
+use super::curve::{G1AffineValue, G1AffineVariable, G2AffineValue, G2AffineVariable};
+use super::native::{fq_modulus, fr_modulus, Fq, Fq2, G1Affine, G2Affine};
+use super::pairing::G2Operand;
+use crate::prelude::*;
+
+/// The number of bits of the scalar field modulus `r`.
+const SCALAR_BITS: usize = 254;
+
+/// A Groth16 verifying key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Groth16VerifyingKey {
+    pub alpha: G1Affine,
+    pub beta: G2Affine,
+    pub gamma: G2Affine,
+    pub delta: G2Affine,
+    /// The points `IC_0, ..., IC_n` for `n` public inputs.
+    pub ic: Vec<G1Affine>,
+}
+
+/// A Groth16 proof.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Groth16Proof {
+    pub a: G1Affine,
+    pub b: G2Affine,
+    pub c: G1Affine,
+}
+
+fn parse_fq(value: &Value) -> Result<Fq> {
+    let string = value
+        .as_str()
+        .ok_or_else(|| anyhow!("expected a decimal string, got {}", value))?;
+    let value = BigUint::parse_bytes(string.as_bytes(), 10)
+        .ok_or_else(|| anyhow!("invalid field element {}", string))?;
+    ensure!(
+        value < fq_modulus(),
+        "field element {} is not reduced",
+        value
+    );
+    Ok(Fq::new(value))
+}
+
+fn parse_coordinates(value: &Value) -> Result<&Vec<Value>> {
+    let coordinates = value
+        .as_array()
+        .ok_or_else(|| anyhow!("expected a point, got {}", value))?;
+    ensure!(
+        coordinates.len() == 3,
+        "expected projective coordinates, got {}",
+        value
+    );
+    Ok(coordinates)
+}
+
+fn parse_g1(value: &Value) -> Result<G1Affine> {
+    let coordinates = parse_coordinates(value)?;
+    ensure!(
+        parse_fq(&coordinates[2])? == Fq::new(1u32.into()),
+        "point {} is not normalized",
+        value
+    );
+    let point = G1Affine::new(parse_fq(&coordinates[0])?, parse_fq(&coordinates[1])?);
+    ensure!(point.is_on_curve(), "point {} is not on G1", value);
+    Ok(point)
+}
+
+fn parse_fq2(value: &Value) -> Result<Fq2> {
+    match value.as_array().map(Vec::as_slice) {
+        Some([c0, c1]) => Ok(Fq2::new(parse_fq(c0)?, parse_fq(c1)?)),
+        _ => Err(anyhow!("expected an element of Fq2, got {}", value)),
+    }
+}
+
+fn parse_g2(value: &Value) -> Result<G2Affine> {
+    let coordinates = parse_coordinates(value)?;
+    ensure!(
+        parse_fq2(&coordinates[2])? == Fq2::new(Fq::new(1u32.into()), Fq::new(0u32.into())),
+        "point {} is not normalized",
+        value
+    );
+    let point = G2Affine::new(parse_fq2(&coordinates[0])?, parse_fq2(&coordinates[1])?);
+    ensure!(point.is_on_curve(), "point {} is not on the twist", value);
+    ensure!(
+        point.scalar_mul(&fr_modulus()).is_none(),
+        "point {} is not in G2",
+        value
+    );
+    Ok(point)
+}
+
+fn field<'a>(json: &'a Value, name: &str) -> Result<&'a Value> {
+    json.get(name)
+        .ok_or_else(|| anyhow!("missing field {}", name))
+}
+
+impl Groth16VerifyingKey {
+    /// Parses a verifying key exported by `snarkjs zkey export verificationkey`.
+    pub fn from_snarkjs_json(json: &str) -> Result<Self> {
+        let json: Value = serde_json::from_str(json)?;
+        let ic = field(&json, "IC")?
+            .as_array()
+            .ok_or_else(|| anyhow!("IC is not an array"))?
+            .iter()
+            .map(parse_g1)
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(!ic.is_empty(), "IC is empty");
+        if let Some(nb_public) = json.get("nPublic") {
+            ensure!(
+                nb_public.as_u64() == Some(ic.len() as u64 - 1),
+                "nPublic does not match the length of IC"
+            );
+        }
+        Ok(Self {
+            alpha: parse_g1(field(&json, "vk_alpha_1")?)?,
+            beta: parse_g2(field(&json, "vk_beta_2")?)?,
+            gamma: parse_g2(field(&json, "vk_gamma_2")?)?,
+            delta: parse_g2(field(&json, "vk_delta_2")?)?,
+            ic,
+        })
+    }
+
+    /// The number of public inputs.
+    pub fn nb_public_inputs(&self) -> usize {
+        self.ic.len() - 1
+    }
+}
+
+impl Groth16Proof {
+    /// Parses a proof produced by `snarkjs groth16 prove`.
+    pub fn from_snarkjs_json(json: &str) -> Result<Self> {
+        let json: Value = serde_json::from_str(json)?;
+        Ok(Self {
+            a: parse_g1(field(&json, "pi_a")?)?,
+            b: parse_g2(field(&json, "pi_b")?)?,
+            c: parse_g1(field(&json, "pi_c")?)?,
+        })
+    }
+}
+
+/// A Groth16 proof in a circuit.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(Groth16ProofValue)]
+pub struct Groth16ProofVariable {
+    pub a: G1AffineVariable,
+    pub b: G2AffineVariable,
+    pub c: G1AffineVariable,
+}
+
+impl<F: RichField> From<Groth16Proof> for Groth16ProofValue<F> {
+    fn from(proof: Groth16Proof) -> Self {
+        Self {
+            a: G1AffineValue::from(proof.a),
+            b: G2AffineValue::from(proof.b),
+            c: G1AffineValue::from(proof.c),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Verifies a Groth16 proof for the given public inputs, which must be canonical elements of
+    /// the scalar field.
+    ///
+    /// The points of the proof are checked to lie on the curve, and `B` to lie in `G2`. Points at
+    /// infinity and the exceptional cases of the incomplete additions are not supported, which
+    /// only rejects proofs that an honest prover produces with negligible probability.
+    pub fn groth16_verify(
+        &mut self,
+        vk: &Groth16VerifyingKey,
+        proof: &Groth16ProofVariable,
+        public_inputs: &[U256Variable],
+    ) {
+        assert_eq!(
+            public_inputs.len(),
+            vk.nb_public_inputs(),
+            "wrong number of public inputs"
+        );
+        self.g1_assert_on_curve(&proof.a);
+        self.g1_assert_on_curve(&proof.c);
+        self.g2_assert_on_curve(&proof.b);
+        self.g2_assert_in_subgroup(&proof.b);
+
+        let modulus = U256::from_dec_str(&fr_modulus().to_string()).unwrap();
+        let modulus = self.constant::<U256Variable>(modulus);
+        let true_variable = self._true();
+        let scalars = public_inputs
+            .iter()
+            .map(|input| {
+                let is_canonical = self.lt(*input, modulus);
+                self.assert_is_equal(is_canonical, true_variable);
+                let mut bits = input.to_le_bits(self);
+                bits.truncate(SCALAR_BITS);
+                bits
+            })
+            .collect::<Vec<_>>();
+        let vk_x = self.g1_linear_combination(&vk.ic[0], &vk.ic[1..], &scalars);
+
+        let neg_a = self.g1_neg(&proof.a);
+        let alpha = self.g1_constant(&vk.alpha);
+        self.bn254_pairing_check(&[
+            (neg_a, G2Operand::Variable(proof.b.clone())),
+            (alpha, G2Operand::Constant(vk.beta.clone())),
+            (vk_x, G2Operand::Constant(vk.gamma.clone())),
+            (proof.c.clone(), G2Operand::Constant(vk.delta.clone())),
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fq_json(x: &Fq) -> String {
+        format!("\"{}\"", x.0)
+    }
+
+    fn g1_json(point: &G1Affine) -> String {
+        format!("[{}, {}, \"1\"]", fq_json(&point.x), fq_json(&point.y))
+    }
+
+    fn g2_json(point: &G2Affine) -> String {
+        let fq2_json = |x: &Fq2| format!("[{}, {}]", fq_json(&x.c0), fq_json(&x.c1));
+        format!(
+            "[{}, {}, [\"1\", \"0\"]]",
+            fq2_json(&point.x),
+            fq2_json(&point.y)
+        )
+    }
+
+    fn g1_mul(scalar: u64) -> G1Affine {
+        G1Affine::generator()
+            .scalar_mul(&BigUint::from(scalar))
+            .unwrap()
+    }
+
+    fn g2_mul(scalar: u64) -> G2Affine {
+        G2Affine::generator()
+            .scalar_mul(&BigUint::from(scalar))
+            .unwrap()
+    }
+
+    /// A verifying key with known discrete logarithms, so that proofs can be forged for testing.
+    fn trapdoor_key() -> Groth16VerifyingKey {
+        Groth16VerifyingKey {
+            alpha: g1_mul(3),
+            beta: g2_mul(5),
+            gamma: g2_mul(7),
+            delta: g2_mul(11),
+            ic: vec![g1_mul(13), g1_mul(17), g1_mul(19)],
+        }
+    }
+
+    /// Forges a proof with `A = [a] G1` and `B = [b] G2` for the trapdoor key.
+    fn forge_proof(inputs: &[u64], a: u64, b: u64) -> Groth16Proof {
+        let r = fr_modulus();
+        let x = 13 + 17 * BigUint::from(inputs[0]) + 19 * BigUint::from(inputs[1]);
+        let delta_inverse = BigUint::from(11u32).modpow(&(&r - 2u32), &r);
+        let ab = BigUint::from(a) * b;
+        let c = ((&ab + &r * 100u32 - 3u32 * 5u32 - x * 7u32) % &r) * delta_inverse % &r;
+        Groth16Proof {
+            a: g1_mul(a),
+            b: g2_mul(b),
+            c: G1Affine::generator().scalar_mul(&c).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_groth16_snarkjs_json() {
+        let vk = trapdoor_key();
+        let ic = vk.ic.iter().map(g1_json).collect::<Vec<_>>().join(", ");
+        let json = format!(
+            r#"{{"protocol": "groth16", "curve": "bn128", "nPublic": 2, "vk_alpha_1": {}, "vk_beta_2": {}, "vk_gamma_2": {}, "vk_delta_2": {}, "IC": [{}]}}"#,
+            g1_json(&vk.alpha),
+            g2_json(&vk.beta),
+            g2_json(&vk.gamma),
+            g2_json(&vk.delta),
+            ic
+        );
+        assert_eq!(Groth16VerifyingKey::from_snarkjs_json(&json).unwrap(), vk);
+
+        let proof = forge_proof(&[1, 2], 23, 29);
+        let json = format!(
+            r#"{{"pi_a": {}, "pi_b": {}, "pi_c": {}, "protocol": "groth16", "curve": "bn128"}}"#,
+            g1_json(&proof.a),
+            g2_json(&proof.b),
+            g1_json(&proof.c)
+        );
+        assert_eq!(Groth16Proof::from_snarkjs_json(&json).unwrap(), proof);
+
+        let off_curve = json.replacen(&fq_json(&proof.a.y), "\"1\"", 1);
+        assert!(Groth16Proof::from_snarkjs_json(&off_curve).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_groth16_verify() {
+        let vk = trapdoor_key();
+        let mut builder = DefaultBuilder::new();
+        let proof = builder.read::<Groth16ProofVariable>();
+        let inputs = [
+            builder.read::<U256Variable>(),
+            builder.read::<U256Variable>(),
+        ];
+        builder.groth16_verify(&vk, &proof, &inputs);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Groth16ProofVariable>(forge_proof(&[1, 2], 23, 29).into());
+        input.write::<U256Variable>(U256::from(1));
+        input.write::<U256Variable>(U256::from(2));
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
@@ -0,0 +1,12 @@
+//! Arithmetic over BN254 and its optimal ate pairing, for verifying Groth16 proofs and the inputs
+//! of the `ecPairing` precompile of the EVM in a circuit.
+//!
+//! Elements of the base field are represented with 16 limbs of 16 bits and reduced lazily, with
+//! the arithmetic of the `tower` module.
+
+pub mod curve;
+pub mod fq;
+pub mod groth16;
+pub mod native;
+pub mod pairing;
+pub mod precompile;
@@ -0,0 +1,479 @@
+//! Native arithmetic over BN254, used for the constants and the hints of the circuit gadgets.
+
+use core::fmt::Debug;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use num::{One, Zero};
+use num_bigint::BigUint;
+
+/// The parameter `x` of the curve, with `p = 36x^4 + 36x^3 + 24x^2 + 6x + 1`.
+pub const BN_X: u64 = 4965661367192848881;
+
+/// The modulus of the base field.
+pub fn fq_modulus() -> BigUint {
+    BigUint::parse_bytes(
+        b"21888242871839275222246405745257275088696311157297823662689037894645226208583",
+        10,
+    )
+    .unwrap()
+}
+
+/// The order of the groups `G1`, `G2` and `GT`, which is the modulus of the scalar field.
+pub fn fr_modulus() -> BigUint {
+    BigUint::parse_bytes(
+        b"21888242871839275222246405745257275088548364400416034343698204186575808495617",
+        10,
+    )
+    .unwrap()
+}
+
+/// The field operations needed for curve arithmetic.
+pub trait NativeField:
+    Debug
+    + Clone
+    + PartialEq
+    + Eq
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    fn zero() -> Self;
+
+    fn one() -> Self;
+
+    fn from_u64(value: u64) -> Self;
+
+    /// The multiplicative inverse, which panics if the element is zero.
+    fn inverse(&self) -> Self;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    fn square(&self) -> Self {
+        self.clone() * self.clone()
+    }
+
+    fn pow(&self, exponent: &BigUint) -> Self {
+        let mut result = Self::one();
+        for i in (0..exponent.bits()).rev() {
+            result = result.square();
+            if exponent.bit(i) {
+                result = result * self.clone();
+            }
+        }
+        result
+    }
+}
+
+/// An element of the base field `Fq`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fq(pub BigUint);
+
+impl Fq {
+    pub fn new(value: BigUint) -> Self {
+        Self(value % fq_modulus())
+    }
+}
+
+impl Add for Fq {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Fq {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.0 + fq_modulus() - rhs.0)
+    }
+}
+
+impl Mul for Fq {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.0 * rhs.0)
+    }
+}
+
+impl Neg for Fq {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(fq_modulus() - self.0)
+    }
+}
+
+impl NativeField for Fq {
+    fn zero() -> Self {
+        Self(BigUint::zero())
+    }
+
+    fn one() -> Self {
+        Self(BigUint::one())
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Self::new(BigUint::from(value))
+    }
+
+    fn inverse(&self) -> Self {
+        assert!(!self.is_zero(), "zero has no inverse");
+        let p = fq_modulus();
+        Self(self.0.modpow(&(&p - 2u32), &p))
+    }
+}
+
+/// An element of `Fq2 = Fq[u]/(u^2 + 1)`, as `c0 + c1 * u`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fq2 {
+    pub c0: Fq,
+    pub c1: Fq,
+}
+
+impl Fq2 {
+    pub fn new(c0: Fq, c1: Fq) -> Self {
+        Self { c0, c1 }
+    }
+
+    /// The non-residue `9 + u` used to build `Fq12` and the twist of `G2`.
+    pub fn xi() -> Self {
+        Self::new(Fq::from_u64(9), Fq::one())
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.c0.clone(), -self.c1.clone())
+    }
+
+    pub fn mul_by_fq(&self, other: &Fq) -> Self {
+        Self::new(
+            self.c0.clone() * other.clone(),
+            self.c1.clone() * other.clone(),
+        )
+    }
+}
+
+impl Add for Fq2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.c0 + rhs.c0, self.c1 + rhs.c1)
+    }
+}
+
+impl Sub for Fq2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.c0 - rhs.c0, self.c1 - rhs.c1)
+    }
+}
+
+impl Mul for Fq2 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.c0.clone() * rhs.c0.clone() - self.c1.clone() * rhs.c1.clone(),
+            self.c0 * rhs.c1 + self.c1 * rhs.c0,
+        )
+    }
+}
+
+impl Neg for Fq2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.c0, -self.c1)
+    }
+}
+
+impl NativeField for Fq2 {
+    fn zero() -> Self {
+        Self::new(Fq::zero(), Fq::zero())
+    }
+
+    fn one() -> Self {
+        Self::new(Fq::one(), Fq::zero())
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Self::new(Fq::from_u64(value), Fq::zero())
+    }
+
+    fn inverse(&self) -> Self {
+        let norm = (self.c0.square() + self.c1.square()).inverse();
+        self.conjugate().mul_by_fq(&norm)
+    }
+}
+
+/// The coefficients `xi^(i * (p^k - 1) / 6)` of the `p^k`-th power Frobenius map, for `i < 6`.
+pub fn frobenius_coefficients(k: u32) -> Vec<Fq2> {
+    let exponent = (fq_modulus().pow(k) - 1u32) / 6u32;
+    let gamma = Fq2::xi().pow(&exponent);
+    (0..6u32).map(|i| gamma.pow(&BigUint::from(i))).collect()
+}
+
+/// An element of `Fq12 = Fq2[w]/(w^6 - xi)`, as its coefficients in `1, w, ..., w^5`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fq12(pub [Fq2; 6]);
+
+impl Fq12 {
+    /// The `p^6`-th power, which is the inverse of unitary elements.
+    pub fn conjugate(&self) -> Self {
+        let mut result = self.clone();
+        for i in [1, 3, 5] {
+            result.0[i] = -result.0[i].clone();
+        }
+        result
+    }
+
+    /// The `p^k`-th power.
+    pub fn frobenius(&self, k: u32) -> Self {
+        let gammas = frobenius_coefficients(k);
+        let mut result = self.clone();
+        for (c, gamma) in result.0.iter_mut().zip(gammas) {
+            if k % 2 == 1 {
+                *c = c.conjugate();
+            }
+            *c = c.clone() * gamma;
+        }
+        result
+    }
+}
+
+impl Add for Fq12 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut result = self;
+        for (a, b) in result.0.iter_mut().zip(rhs.0) {
+            *a = a.clone() + b;
+        }
+        result
+    }
+}
+
+impl Sub for Fq12 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+impl Mul for Fq12 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let mut result = Self::zero();
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in rhs.0.iter().enumerate() {
+                let product = a.clone() * b.clone();
+                if i + j < 6 {
+                    result.0[i + j] = result.0[i + j].clone() + product;
+                } else {
+                    result.0[i + j - 6] = result.0[i + j - 6].clone() + Fq2::xi() * product;
+                }
+            }
+        }
+        result
+    }
+}
+
+impl Neg for Fq12 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.map(|c| -c))
+    }
+}
+
+impl NativeField for Fq12 {
+    fn zero() -> Self {
+        Self(core::array::from_fn(|_| Fq2::zero()))
+    }
+
+    fn one() -> Self {
+        let mut result = Self::zero();
+        result.0[0] = Fq2::one();
+        result
+    }
+
+    fn from_u64(value: u64) -> Self {
+        let mut result = Self::zero();
+        result.0[0] = Fq2::from_u64(value);
+        result
+    }
+
+    fn inverse(&self) -> Self {
+        // `self * conj(self)` lies in `Fq6 = Fq2[v]/(v^3 - xi)` with `v = w^2`, where the inverse
+        // has a closed form.
+        let g = self.clone() * self.conjugate();
+        let (a0, a1, a2) = (g.0[0].clone(), g.0[2].clone(), g.0[4].clone());
+        let xi = Fq2::xi();
+        let t0 = a0.square() - xi.clone() * a1.clone() * a2.clone();
+        let t1 = xi.clone() * a2.square() - a0.clone() * a1.clone();
+        let t2 = a1.square() - a0.clone() * a2.clone();
+        let norm = a0 * t0.clone() + xi * (a2 * t1.clone() + a1 * t2.clone());
+        let norm_inverse = norm.inverse();
+
+        let mut g_inverse = Self::zero();
+        g_inverse.0[0] = t0 * norm_inverse.clone();
+        g_inverse.0[2] = t1 * norm_inverse.clone();
+        g_inverse.0[4] = t2 * norm_inverse;
+        self.conjugate() * g_inverse
+    }
+}
+
+/// A point in affine coordinates on a short Weierstrass curve `y^2 = x^3 + b`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Affine<F> {
+    pub x: F,
+    pub y: F,
+}
+
+/// A point of `G1`, on `y^2 = x^3 + 3` over `Fq`.
+pub type G1Affine = Affine<Fq>;
+
+/// A point of `G2`, on the twist `y^2 = x^3 + 3 / xi` over `Fq2`.
+pub type G2Affine = Affine<Fq2>;
+
+impl<F: NativeField> Affine<F> {
+    pub fn new(x: F, y: F) -> Self {
+        Self { x, y }
+    }
+
+    pub fn negate(&self) -> Self {
+        Self::new(self.x.clone(), -self.y.clone())
+    }
+
+    /// The slope of the tangent at `self`.
+    pub fn tangent_slope(&self) -> F {
+        let numerator = F::from_u64(3) * self.x.square();
+        let denominator = self.y.clone() + self.y.clone();
+        numerator * denominator.inverse()
+    }
+
+    /// The slope of the line through `self` and `other`, which must have distinct `x`.
+    pub fn chord_slope(&self, other: &Self) -> F {
+        (other.y.clone() - self.y.clone()) * (other.x.clone() - self.x.clone()).inverse()
+    }
+
+    /// The third point on the line with the given slope through `self` and `other`, negated.
+    fn add_with_slope(&self, other: &Self, slope: &F) -> Self {
+        let x = slope.square() - self.x.clone() - other.x.clone();
+        let y = slope.clone() * (self.x.clone() - x.clone()) - self.y.clone();
+        Self::new(x, y)
+    }
+
+    pub fn double(&self) -> Self {
+        self.add_with_slope(self, &self.tangent_slope())
+    }
+
+    /// Adds two points, returning `None` for the point at infinity.
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        if self.x != other.x {
+            Some(self.add_with_slope(other, &self.chord_slope(other)))
+        } else if self.y == other.y && !self.y.is_zero() {
+            Some(self.double())
+        } else {
+            None
+        }
+    }
+
+    /// Multiplies the point by a scalar, returning `None` for the point at infinity.
+    pub fn scalar_mul(&self, scalar: &BigUint) -> Option<Self> {
+        let mut result: Option<Self> = None;
+        for i in (0..scalar.bits()).rev() {
+            result = result.and_then(|r| r.checked_add(&r));
+            if scalar.bit(i) {
+                result = match result {
+                    Some(r) => r.checked_add(self),
+                    None => Some(self.clone()),
+                };
+            }
+        }
+        result
+    }
+}
+
+impl G1Affine {
+    pub fn generator() -> Self {
+        Self::new(Fq::from_u64(1), Fq::from_u64(2))
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        self.y.square() == self.x.square() * self.x.clone() + Fq::from_u64(3)
+    }
+}
+
+impl G2Affine {
+    pub fn generator() -> Self {
+        let fq = |s: &[u8]| Fq::new(BigUint::parse_bytes(s, 10).unwrap());
+        Self::new(
+            Fq2::new(
+                fq(b"10857046999023057135944570762232829481370756359578518086990519993285655852781"),
+                fq(b"11559732032986387107991004021392285783925812861821192530917403151452391805634"),
+            ),
+            Fq2::new(
+                fq(b"8495653923123431417604973247489272438418190587263600148770280649306958101930"),
+                fq(b"4082367875863433681332203403145435568316851327593401208105741076214120093531"),
+            ),
+        )
+    }
+
+    /// The coefficient `3 / xi` of the twist.
+    pub fn twist_b() -> Fq2 {
+        Fq2::from_u64(3) * Fq2::xi().inverse()
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        self.y.square() == self.x.square() * self.x.clone() + Self::twist_b()
+    }
+
+    /// The endomorphism `psi` obtained by untwisting, applying the Frobenius map and twisting back.
+    pub fn frobenius(&self) -> Self {
+        let gammas = frobenius_coefficients(1);
+        Self::new(
+            self.x.conjugate() * gammas[2].clone(),
+            self.y.conjugate() * gammas[3].clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_bn254() {
+        let r = fr_modulus();
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+        assert!(g1.is_on_curve());
+        assert!(g2.is_on_curve());
+        assert_eq!(g1.scalar_mul(&r), None);
+        assert_eq!(g2.scalar_mul(&r), None);
+
+        let x = BigUint::from(BN_X);
+        assert_eq!(
+            g2.frobenius(),
+            g2.scalar_mul(&(BigUint::from(6u32) * &x * &x)).unwrap()
+        );
+
+        let mut f = Fq12::zero();
+        for (i, c) in f.0.iter_mut().enumerate() {
+            *c = Fq2::new(Fq::from_u64(i as u64 + 1), Fq::from_u64(7 * i as u64 + 3));
+        }
+        assert_eq!(f.clone() * f.inverse(), Fq12::one());
+        assert_eq!(f.frobenius(1).frobenius(1), f.frobenius(2));
+        assert_eq!(f.frobenius(2).frobenius(1), f.frobenius(3));
+        assert_eq!(f.frobenius(3).frobenius(3), f.conjugate());
+    }
+}
@@ -0,0 +1,276 @@
+//! The optimal ate pairing over BN254.
+//!
+//! The Miller loop runs over `6x + 2` in non-adjacent form with the `G2` points in affine
+//! coordinates on the twist, and evaluates each line at the `G1` point as a sparse element of
+//! `Fq12`. Lines through constant `G2` points are computed natively when building the circuit, so
+//! only their evaluation at the `G1` point is constrained.
+
+use itertools::Itertools;
+use num_bigint::BigUint;
+
+use super::curve::{G1AffineVariable, G2AffineVariable};
+use super::fq::{Fq12Coefficient, Fq12Variable, Fq2Poly, FqVariable};
+use super::native::{Fq2, G2Affine, BN_X};
+use crate::prelude::*;
+
+/// The loop count `6x + 2` of the optimal ate pairing in non-adjacent form, least significant
+/// digit first.
+fn ate_loop_naf() -> Vec<i8> {
+    let mut n = 6 * BN_X as u128 + 2;
+    let mut digits = Vec::new();
+    while n > 0 {
+        let digit = match n % 4 {
+            1 => 1,
+            3 => -1,
+            _ => 0,
+        };
+        n = if digit == -1 {
+            n + 1
+        } else {
+            n - digit as u128
+        } / 2;
+        digits.push(digit);
+    }
+    digits
+}
+
+/// The steps of the Miller loop, after the most significant digit.
+#[derive(Debug, Clone, Copy)]
+enum LineStep {
+    Double,
+    AddQ,
+    SubQ,
+    /// Adds `psi(Q)`.
+    AddQ1,
+    /// Adds `-psi^2(Q)`.
+    SubQ2,
+}
+
+fn miller_loop_steps() -> Vec<LineStep> {
+    let naf = ate_loop_naf();
+    let mut steps = Vec::new();
+    for digit in naf[..naf.len() - 1].iter().rev() {
+        steps.push(LineStep::Double);
+        match digit {
+            1 => steps.push(LineStep::AddQ),
+            -1 => steps.push(LineStep::SubQ),
+            _ => {}
+        }
+    }
+    steps.push(LineStep::AddQ1);
+    steps.push(LineStep::SubQ2);
+    steps
+}
+
+/// The `G2` argument of a pairing.
+#[derive(Debug, Clone)]
+pub enum G2Operand {
+    /// A constant point, such as a point of a verifying key.
+    Constant(G2Affine),
+    Variable(G2AffineVariable),
+}
+
+/// Computes the coefficients of the lines through a constant point that do not depend on the
+/// `G1` argument: the slope and `slope * x_T - y_T` at each step of the Miller loop.
+fn constant_lines(q: &G2Affine) -> Vec<(Fq2, Fq2)> {
+    let q1 = q.frobenius();
+    let neg_q2 = q1.frobenius().negate();
+    let mut t = q.clone();
+    let mut lines = Vec::new();
+    for step in miller_loop_steps() {
+        let other = match step {
+            LineStep::Double => None,
+            LineStep::AddQ => Some(q.clone()),
+            LineStep::SubQ => Some(q.negate()),
+            LineStep::AddQ1 => Some(q1.clone()),
+            LineStep::SubQ2 => Some(neg_q2.clone()),
+        };
+        let (slope, next) = match other {
+            None => (t.tangent_slope(), t.double()),
+            Some(other) => (t.chord_slope(&other), t.checked_add(&other).unwrap()),
+        };
+        lines.push((slope.clone(), slope * t.x.clone() - t.y.clone()));
+        t = next;
+    }
+    lines
+}
+
+#[derive(Debug, Clone)]
+enum MillerState {
+    Constant(std::vec::IntoIter<(Fq2, Fq2)>),
+    Variable {
+        t: G2AffineVariable,
+        q: G2AffineVariable,
+        neg_q: G2AffineVariable,
+        q1: G2AffineVariable,
+        neg_q2: G2AffineVariable,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct MillerPair {
+    x_p: FqVariable,
+    y_p: FqVariable,
+    state: MillerState,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Evaluates the line of the next step at the `G1` point of the pair, advancing its `G2` point.
+    fn bn254_line(
+        &mut self,
+        pair: &mut MillerPair,
+        step: LineStep,
+    ) -> Vec<(usize, Fq12Coefficient)> {
+        let (slope, c3) = match &mut pair.state {
+            MillerState::Constant(lines) => {
+                let (slope, c3) = lines.next().expect("missing line");
+                (self.fp2_constant(&slope), self.fp2_constant(&c3))
+            }
+            MillerState::Variable {
+                t,
+                q,
+                neg_q,
+                q1,
+                neg_q2,
+            } => {
+                let (next, slope) = match step {
+                    LineStep::Double => self.g2_double_with_slope(t),
+                    LineStep::AddQ => self.g2_add_with_chord(t, q),
+                    LineStep::SubQ => self.g2_add_with_chord(t, neg_q),
+                    LineStep::AddQ1 => self.g2_add_with_chord(t, q1),
+                    LineStep::SubQ2 => self.g2_add_with_chord(t, neg_q2),
+                };
+                let slope_x = self.fp2_poly_mul(&slope, &t.x);
+                let y = self.fp2_to_poly(&t.y);
+                let c3 = self.fp2_poly_sub(&slope_x, &y);
+                let c3 = self.fp2_reduce(&c3);
+                *t = next;
+                (slope, c3)
+            }
+        };
+        let slope_x_p = self.fp2_poly_mul_fp(&slope, &pair.x_p);
+        let c1 = self.fp2_poly_sub(&Fq2Poly::zero(), &slope_x_p);
+        let c1 = self.fp2_reduce(&c1);
+        vec![
+            (0, Fq12Coefficient::Fp(pair.y_p.clone())),
+            (1, Fq12Coefficient::Fp2(c1)),
+            (3, Fq12Coefficient::Fp2(c3)),
+        ]
+    }
+
+    /// The product of the Miller loops of the given pairs.
+    pub fn bn254_miller_loop(&mut self, pairs: &[(G1AffineVariable, G2Operand)]) -> Fq12Variable {
+        let mut states = Vec::new();
+        for (p, q) in pairs.iter() {
+            let state = match q {
+                G2Operand::Constant(q) => MillerState::Constant(constant_lines(q).into_iter()),
+                G2Operand::Variable(q) => {
+                    let q1 = self.g2_frobenius(q);
+                    let q2 = self.g2_frobenius(&q1);
+                    MillerState::Variable {
+                        t: q.clone(),
+                        q: q.clone(),
+                        neg_q: self.g2_neg(q),
+                        q1,
+                        neg_q2: self.g2_neg(&q2),
+                    }
+                }
+            };
+            states.push(MillerPair {
+                x_p: p.x.clone(),
+                y_p: p.y.clone(),
+                state,
+            });
+        }
+
+        let mut f: Option<Fq12Variable> = None;
+        for step in miller_loop_steps() {
+            if let (LineStep::Double, Some(value)) = (step, &f) {
+                f = Some(self.fp12_square(value));
+            }
+            for pair in states.iter_mut() {
+                let line = self.bn254_line(pair, step);
+                f = Some(self.fp12_mul_by_line(f, line));
+            }
+        }
+        f.expect("no pairs")
+    }
+
+    /// Raises an element to the power of the curve parameter `x`.
+    pub fn fq12_pow_bn_x(&mut self, a: &Fq12Variable) -> Fq12Variable {
+        self.fp12_pow(a, &BigUint::from(BN_X))
+    }
+
+    /// Raises the output of a Miller loop to the power `(p^12 - 1) / r`.
+    pub fn bn254_final_exponentiation(&mut self, f: &Fq12Variable) -> Fq12Variable {
+        // The easy part, `f^((p^6 - 1) * (p^2 + 1))`, maps `f` to the cyclotomic subgroup.
+        let inverse = self.fp12_inverse(f);
+        let conjugate = self.fp12_conjugate(f);
+        let f = self.fp12_mul(&conjugate, &inverse);
+        let frobenius = self.fp12_frobenius(&f, 2);
+        let f = self.fp12_mul(&frobenius, &f);
+
+        // The hard part, `f^((p^4 - p^2 + 1) / r)`, with the addition chain of Scott et al.
+        let fx = self.fq12_pow_bn_x(&f);
+        let fx2 = self.fq12_pow_bn_x(&fx);
+        let fx3 = self.fq12_pow_bn_x(&fx2);
+
+        let fp = self.fp12_frobenius(&f, 1);
+        let fp2 = self.fp12_frobenius(&f, 2);
+        let fp3 = self.fp12_frobenius(&f, 3);
+        let y0 = self.fp12_mul(&fp, &fp2);
+        let y0 = self.fp12_mul(&y0, &fp3);
+        let y1 = self.fp12_conjugate(&f);
+        let y2 = self.fp12_frobenius(&fx2, 2);
+        let y3 = self.fp12_frobenius(&fx, 1);
+        let y3 = self.fp12_conjugate(&y3);
+        let y4 = self.fp12_frobenius(&fx2, 1);
+        let y4 = self.fp12_mul(&fx, &y4);
+        let y4 = self.fp12_conjugate(&y4);
+        let y5 = self.fp12_conjugate(&fx2);
+        let y6 = self.fp12_frobenius(&fx3, 1);
+        let y6 = self.fp12_mul(&fx3, &y6);
+        let y6 = self.fp12_conjugate(&y6);
+
+        let t0 = self.fp12_square(&y6);
+        let t0 = self.fp12_mul(&t0, &y4);
+        let t0 = self.fp12_mul(&t0, &y5);
+        let t1 = self.fp12_mul(&y3, &y5);
+        let t1 = self.fp12_mul(&t1, &t0);
+        let t0 = self.fp12_mul(&t0, &y2);
+        let t1 = self.fp12_square(&t1);
+        let t1 = self.fp12_mul(&t1, &t0);
+        let t1 = self.fp12_square(&t1);
+        let t0 = self.fp12_mul(&t1, &y1);
+        let t1 = self.fp12_mul(&t1, &y0);
+        let t0 = self.fp12_square(&t0);
+        self.fp12_mul(&t1, &t0)
+    }
+
+    /// Checks that `prod_i e(P_i, Q_i) = 1` for the given pairs `(P_i, Q_i)`.
+    pub fn bn254_pairing_check(&mut self, pairs: &[(G1AffineVariable, G2Operand)]) {
+        let f = self.bn254_miller_loop(pairs);
+        let f = self.bn254_final_exponentiation(&f);
+        self.fp12_assert_is_one(&f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ate_loop_naf() {
+        let naf = ate_loop_naf();
+        let value = naf
+            .iter()
+            .rev()
+            .fold(0i128, |acc, digit| 2 * acc + *digit as i128);
+        assert_eq!(value, 6 * BN_X as i128 + 2);
+        assert!(naf.iter().tuple_windows().all(|(a, b)| *a == 0 || *b == 0));
+        assert_eq!(
+            constant_lines(&G2Affine::generator()).len(),
+            miller_loop_steps().len()
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! The `ecPairing` precompile of the EVM, as specified by EIP-197.
+//!
+//! The input is a sequence of pairs of a point of `G1` and a point of `G2`, each coordinate a
+//! 32-byte big-endian word, with the coefficients of each element of `Fq2` in the order `c1, c0`.
+//! The point at infinity of either group is encoded with zero coordinates, and a pair containing
+//! it contributes a factor of one to the product. Inputs for which the precompile fails, such as
+//! points off the curves or outside of `G2`, make the proof fail.
+
+use super::curve::{G1AffineVariable, G2AffineVariable};
+use super::fq::{Fq12Variable, Fq2Variable, FqVariable};
+use super::native::{Fq, Fq12, G1Affine, G2Affine, NativeField};
+use super::pairing::G2Operand;
+use crate::prelude::*;
+
+/// The number of words of a pair of the input.
+pub const EC_PAIRING_PAIR_WORDS: usize = 6;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    fn bn254_fq_from_word(&mut self, word: &Bytes32Variable) -> FqVariable {
+        self.fp_from_be_bytes(&word.as_bytes())
+    }
+
+    /// Decodes a point of `G1` from the words `x, y`, returning whether it is the point at
+    /// infinity, in which case the point returned is the generator.
+    pub fn bn254_g1_from_words(
+        &mut self,
+        words: &[Bytes32Variable; 2],
+    ) -> (G1AffineVariable, BoolVariable) {
+        let point = G1AffineVariable {
+            x: self.bn254_fq_from_word(&words[0]),
+            y: self.bn254_fq_from_word(&words[1]),
+        };
+        let zero = self.fp_constant(&Fq::zero());
+        let x_is_zero = self.is_equal(point.x.clone(), zero.clone());
+        let y_is_zero = self.is_equal(point.y.clone(), zero);
+        let is_infinity = self.and(x_is_zero, y_is_zero);
+
+        let generator = self.g1_constant(&G1Affine::generator());
+        let point = self.select(is_infinity, generator, point);
+        self.g1_assert_on_curve(&point);
+        (point, is_infinity)
+    }
+
+    /// Decodes a point of `G2` from the words `x.c1, x.c0, y.c1, y.c0` and checks that it lies in
+    /// `G2`, returning whether it is the point at infinity, in which case the point returned is
+    /// the generator.
+    pub fn bn254_g2_from_words(
+        &mut self,
+        words: &[Bytes32Variable; 4],
+    ) -> (G2AffineVariable, BoolVariable) {
+        let [x_c1, x_c0, y_c1, y_c0] = words.each_ref().map(|word| self.bn254_fq_from_word(word));
+        let point = G2AffineVariable {
+            x: Fq2Variable { c0: x_c0, c1: x_c1 },
+            y: Fq2Variable { c0: y_c0, c1: y_c1 },
+        };
+        let zero = self.fp_constant(&Fq::zero());
+        let zeros = [zero.clone(), zero.clone(), zero.clone(), zero];
+        let coordinates = [
+            point.x.c0.clone(),
+            point.x.c1.clone(),
+            point.y.c0.clone(),
+            point.y.c1.clone(),
+        ];
+        let is_infinity = self.is_equal(coordinates, zeros);
+
+        let generator = self.g2_constant(&G2Affine::generator());
+        let point = self.select(is_infinity, generator, point);
+        self.g2_assert_on_curve(&point);
+        self.g2_assert_in_subgroup(&point);
+        (point, is_infinity)
+    }
+
+    /// Computes the output of the `ecPairing` precompile for the given input words, which is
+    /// `true` if the product of the pairings of the pairs is one.
+    pub fn bn254_ec_pairing(&mut self, input: &[Bytes32Variable]) -> BoolVariable {
+        assert!(
+            input.len() % EC_PAIRING_PAIR_WORDS == 0,
+            "input is not a sequence of pairs"
+        );
+        let one = self.fp12_constant(&Fq12::one());
+        let mut product: Option<Fq12Variable> = None;
+        for words in input.chunks(EC_PAIRING_PAIR_WORDS) {
+            let (p, p_is_infinity) = self.bn254_g1_from_words(&[words[0], words[1]]);
+            let (q, q_is_infinity) =
+                self.bn254_g2_from_words(&[words[2], words[3], words[4], words[5]]);
+            let is_trivial = self.or(p_is_infinity, q_is_infinity);
+
+            // Each Miller loop is computed separately so that trivial pairs can be replaced by one.
+            let f = self.bn254_miller_loop(&[(p, G2Operand::Variable(q))]);
+            let f = Fq12Variable::new(core::array::from_fn(|i| {
+                self.select(is_trivial, one.0[i].clone(), f.0[i].clone())
+            }));
+            product = Some(match product {
+                Some(product) => self.fp12_mul(&product, &f),
+                None => f,
+            });
+        }
+
+        match product {
+            Some(product) => {
+                let result = self.bn254_final_exponentiation(&product);
+                self.is_equal(result.0, one.0)
+            }
+            None => self._true(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    fn word(value: &Fq) -> H256 {
+        let bytes = value.0.to_bytes_be();
+        let mut word = [0u8; 32];
+        word[32 - bytes.len()..].copy_from_slice(&bytes);
+        H256(word)
+    }
+
+    fn pair_words(p: Option<G1Affine>, q: Option<G2Affine>) -> Vec<H256> {
+        let mut words = match p {
+            Some(p) => vec![word(&p.x), word(&p.y)],
+            None => vec![H256::zero(); 2],
+        };
+        match q {
+            Some(q) => words.extend([q.x.c1, q.x.c0, q.y.c1, q.y.c0].iter().map(word)),
+            None => words.extend([H256::zero(); 4]),
+        }
+        words
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_bn254_ec_pairing() {
+        const NUM_WORDS: usize = 3 * EC_PAIRING_PAIR_WORDS;
+        let mut builder = DefaultBuilder::new();
+        let input = builder.read::<ArrayVariable<Bytes32Variable, NUM_WORDS>>();
+        let output = builder.bn254_ec_pairing(input.as_slice());
+        builder.write(output);
+        let circuit = builder.build();
+
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+        let mul = |n: u32| BigUint::from(n);
+        let a = g1.scalar_mul(&mul(6)).unwrap();
+        let b = g2.scalar_mul(&mul(5)).unwrap();
+        let c = g1.scalar_mul(&mul(10)).unwrap().negate();
+        let d = g2.scalar_mul(&mul(3)).unwrap();
+
+        // `e(6 G1, 5 G2) * e(-10 G1, 3 G2) * e(0, G2) = 1`, while changing `d` breaks the
+        // relation.
+        for (d, expected) in [(d.clone(), true), (d.double(), false)] {
+            let mut words = pair_words(Some(a.clone()), Some(b.clone()));
+            words.extend(pair_words(Some(c.clone()), Some(d)));
+            words.extend(pair_words(None, Some(g2.clone())));
+            let mut input = circuit.input();
+            input.write::<ArrayVariable<Bytes32Variable, NUM_WORDS>>(words);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<BoolVariable>(), expected);
+        }
+    }
+}
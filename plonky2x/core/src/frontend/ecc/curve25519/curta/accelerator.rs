@@ -1,8 +1,15 @@
-use super::request::{EcOpRequest, EcOpResponse};
+use std::collections::HashMap;
+
+use super::request::{EcOpRequest, EcOpRequestType, EcOpResponse};
 use super::Curve;
+use crate::prelude::Variable;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct EcOpAccelerator {
     pub ec_op_requests: Vec<EcOpRequest<Curve>>,
     pub ec_op_responses: Vec<EcOpResponse<Curve>>,
+    /// The responses of the requests so far, by request key. Identical requests from different
+    /// call sites, such as the decompression of a public key verified several times, share a
+    /// single row of the table.
+    pub cached_responses: HashMap<(EcOpRequestType, Vec<Variable>), EcOpResponse<Curve>>,
 }
@@ -3,9 +3,9 @@ use starkyx::chip::ec::EllipticCurve;
 
 use crate::frontend::curta::ec::point::{AffinePointVariable, CompressedEdwardsYVariable};
 use crate::frontend::curta::field::variable::FieldVariable;
-use crate::prelude::U256Variable;
+use crate::prelude::{CircuitVariable, U256Variable, Variable};
 
-#[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EcOpRequestType {
     Add,
     ScalarMul,
@@ -44,4 +44,18 @@ impl<E: EllipticCurve> EcOpRequest<E> {
             EcOpRequest::IsValid(_) => EcOpRequestType::IsValid,
         }
     }
+
+    /// The type of the request and its input variables. Requests with equal keys have equal
+    /// responses.
+    pub fn key(&self) -> (EcOpRequestType, Vec<Variable>) {
+        let variables = match self {
+            EcOpRequest::Add(a, b) => [a.variables(), b.variables()].concat(),
+            EcOpRequest::ScalarMul(scalar, point) => {
+                [scalar.variables(), point.variables()].concat()
+            }
+            EcOpRequest::Decompress(compressed_point) => compressed_point.variables(),
+            EcOpRequest::IsValid(point) => point.variables(),
+        };
+        (self.req_type(), variables)
+    }
 }
@@ -58,10 +58,15 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     /// Add an EC operation request to the accelerator.
     fn add_ec_25519_ops_request(&mut self, request: EcOpRequest<Ed25519>) -> EcOpResponse<Ed25519> {
         if self.ec_25519_ops_accelerator.is_none() {
-            self.ec_25519_ops_accelerator = Some(EcOpAccelerator {
-                ec_op_requests: Vec::new(),
-                ec_op_responses: Vec::new(),
-            });
+            self.ec_25519_ops_accelerator = Some(EcOpAccelerator::default());
+        }
+
+        let cached_response = self
+            .ec_25519_ops_accelerator
+            .as_ref()
+            .and_then(|accelerator| accelerator.cached_responses.get(&request.key()));
+        if let Some(response) = cached_response {
+            return response.clone();
         }
 
         let response = match request.req_type() {
@@ -81,9 +86,36 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             .as_mut()
             .expect("sha256 accelerator should exist");
 
+        accelerator
+            .cached_responses
+            .insert(request.key(), response.clone());
         accelerator.ec_op_requests.push(request);
         accelerator.ec_op_responses.push(response.clone());
 
         response
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+
+    #[test]
+    fn test_ec_25519_ops_shared_requests() {
+        let mut builder = DefaultBuilder::new();
+        let compressed_point = builder.read::<CompressedEdwardsYVariable>();
+        let scalar = builder.read::<U256Variable>();
+
+        let a = builder.curta_25519_decompress(compressed_point.clone());
+        let b = builder.curta_25519_decompress(compressed_point);
+        builder.assert_is_equal(a.clone(), b.clone());
+        let c = builder.curta_25519_scalar_mul(scalar, a);
+        let d = builder.curta_25519_scalar_mul(scalar, b);
+        builder.assert_is_equal(c, d);
+
+        let accelerator = builder.ec_25519_ops_accelerator.as_ref().unwrap();
+        assert_eq!(accelerator.ec_op_requests.len(), 2);
+        assert_eq!(accelerator.ec_op_responses.len(), 2);
+    }
+}
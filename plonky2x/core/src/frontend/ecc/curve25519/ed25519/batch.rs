@@ -0,0 +1,309 @@
+//! Batch verification of ed25519 signatures.
+//!
+//! Rather than checking `s_i * B = R_i + h_i * A_i` for every signature, the batch checks a random
+//! linear combination of the equations,
+//!
+//! `8 * (sum_i z_i * s_i) * B = 8 * (sum_i z_i * R_i + sum_i (z_i * h_i) * A_i)`,
+//!
+//! where the 128-bit coefficients `z_i` are derived from the hashes of all the signatures. The left
+//! side is a single scalar multiplication of the base point, and the right side is a single
+//! multi-scalar multiplication whose doublings are shared by all the points.
+//!
+//! The equation is multiplied by the cofactor, so, as the batch verification of ZIP-215 used by
+//! Tendermint, it accepts signatures whose `R` or `A` have a small order component, which the
+//! cofactorless `curta_eddsa_verify_sigs` rejects.
+
+use ethers::types::U256;
+use itertools::Itertools;
+use num::{One, Zero};
+use num_bigint::BigUint;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use starkyx::chip::ec::edwards::ed25519::params::{Ed25519, Ed25519Parameters, Ed25519ScalarField};
+use starkyx::chip::ec::edwards::EdwardsParameters;
+use starkyx::chip::ec::point::AffinePoint;
+use starkyx::chip::field::parameters::FieldParameters;
+
+use super::eddsa::EDDSASignatureVariable;
+use crate::frontend::curta::ec::point::{AffinePointVariable, CompressedEdwardsYVariable};
+use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
+use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::U32Target;
+use crate::prelude::*;
+
+/// The number of bits of the digits of the multi-scalar multiplication.
+const WINDOW_BITS: usize = 4;
+
+/// The number of bits of the coefficients of the linear combination.
+const COEFFICIENT_BITS: usize = 128;
+
+fn ed25519_scalar_modulus() -> BigUint {
+    BigUint::from_bytes_le(&Ed25519ScalarField::modulus().to_bytes_le())
+}
+
+fn to_biguint_target(a: U256Variable) -> BigUintTarget {
+    BigUintTarget {
+        limbs: a.limbs.iter().map(|limb| U32Target::from(*limb)).collect(),
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Reduces an integer of at most `256 + 32 * nb_quotient_limbs` bits modulo the order of the
+    /// group.
+    fn ed25519_scalar_reduce(
+        &mut self,
+        value: &BigUintTarget,
+        nb_quotient_limbs: usize,
+    ) -> U256Variable {
+        let modulus = self.api.constant_biguint(&ed25519_scalar_modulus());
+        let (_, rem) = self
+            .api
+            ._div_rem_biguint(value, &modulus, nb_quotient_limbs);
+        U256Variable {
+            limbs: core::array::from_fn(|i| rem.limbs[i].into()),
+        }
+    }
+
+    /// Multiplies a point by the cofactor 8.
+    fn ed25519_mul_by_cofactor(
+        &mut self,
+        point: AffinePointVariable<Ed25519>,
+    ) -> AffinePointVariable<Ed25519> {
+        let mut result = point;
+        for _ in 0..3 {
+            result = self.curta_25519_add(result.clone(), result);
+        }
+        result
+    }
+
+    /// Computes `sum_i scalars[i] * points[i]`, where the scalars are given by their little endian
+    /// bits, with additions of the complete Edwards law only. The doublings are shared by all the
+    /// points, and each window of `WINDOW_BITS` bits of a scalar adds one entry of a table of the
+    /// small multiples of its point, so shorter scalars need fewer additions.
+    fn ed25519_msm(
+        &mut self,
+        points: &[AffinePointVariable<Ed25519>],
+        scalars: &[Vec<BoolVariable>],
+    ) -> AffinePointVariable<Ed25519> {
+        assert_eq!(points.len(), scalars.len());
+        assert!(scalars.iter().all(|bits| bits.len() % WINDOW_BITS == 0));
+
+        let identity =
+            AffinePointVariable::constant(self, AffinePoint::new(BigUint::zero(), BigUint::one()));
+        let tables = points
+            .iter()
+            .map(|point| {
+                let mut table = vec![identity.clone(), point.clone()];
+                for _ in 2..1 << WINDOW_BITS {
+                    let entry = self.curta_25519_add(table.last().unwrap().clone(), point.clone());
+                    table.push(entry);
+                }
+                table
+            })
+            .collect_vec();
+
+        let nb_windows = scalars.iter().map(|bits| bits.len()).max().unwrap_or(0) / WINDOW_BITS;
+        let mut result = identity;
+        for window in (0..nb_windows).rev() {
+            if window + 1 < nb_windows {
+                for _ in 0..WINDOW_BITS {
+                    result = self.curta_25519_add(result.clone(), result);
+                }
+            }
+            for (table, bits) in tables.iter().zip(scalars.iter()) {
+                if bits.len() <= window * WINDOW_BITS {
+                    continue;
+                }
+                let digit = &bits[window * WINDOW_BITS..(window + 1) * WINDOW_BITS];
+                let mut entries = table.clone();
+                for bit in digit {
+                    entries = entries
+                        .chunks_exact(2)
+                        .map(|pair| self.select(*bit, pair[1].clone(), pair[0].clone()))
+                        .collect();
+                }
+                result = self.curta_25519_add(result, entries.pop().unwrap());
+            }
+        }
+        result
+    }
+
+    /// Verifies a set of eddsa signatures with a single multi-scalar multiplication, which is much
+    /// cheaper than `curta_eddsa_verify_sigs` for large sets. If message_byte_lengths is None, then
+    /// all the messages should have the length of MAX_MSG_LENGTH_BYTES.
+    ///
+    /// Unlike `curta_eddsa_verify_sigs`, the verification is cofactored, see the module
+    /// documentation.
+    pub fn ed25519_verify_batch<
+        // Maximum length of a signed message in bytes.
+        const MAX_MSG_LENGTH_BYTES: usize,
+        const NUM_SIGS: usize,
+    >(
+        &mut self,
+        messages: ArrayVariable<BytesVariable<MAX_MSG_LENGTH_BYTES>, NUM_SIGS>,
+        message_byte_lengths: Option<ArrayVariable<U32Variable, NUM_SIGS>>,
+        signatures: ArrayVariable<EDDSASignatureVariable, NUM_SIGS>,
+        pubkeys: ArrayVariable<CompressedEdwardsYVariable, NUM_SIGS>,
+    ) where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        assert!(NUM_SIGS > 0);
+
+        let scalar_modulus = self.constant::<U256Variable>(U256::from_little_endian(
+            &ed25519_scalar_modulus().to_bytes_le(),
+        ));
+        let true_variable = self._true();
+        let challenges = (0..NUM_SIGS)
+            .map(|i| {
+                // Assert that s is less than the scalar modulus.
+                let s_lt_scalar_mod = self.lt(signatures[i].s, scalar_modulus);
+                self.assert_is_equal(s_lt_scalar_mod, true_variable);
+                self.curta_eddsa_challenge(
+                    &signatures[i],
+                    &pubkeys[i],
+                    &messages[i],
+                    message_byte_lengths.as_ref().map(|msg_lens| msg_lens[i]),
+                )
+            })
+            .collect_vec();
+
+        // The challenges bind the points and the messages, so hashing them with the `s` of the
+        // signatures commits to the whole batch.
+        let transcript = challenges
+            .iter()
+            .zip(signatures.as_vec().iter())
+            .flat_map(|(h, signature)| [h.variables(), signature.s.variables()].concat())
+            .collect_vec();
+        let seed = self.poseidon_hash(&transcript);
+
+        let mut points = Vec::new();
+        let mut scalars = Vec::new();
+        let mut s_sum = self.zero::<U256Variable>();
+        for i in 0..NUM_SIGS {
+            let index = self.constant::<Variable>(L::Field::from_canonical_usize(i));
+            let mut coefficient_input = seed.variables();
+            coefficient_input.push(index);
+            let coefficient_hash = self.poseidon_hash(&coefficient_input);
+            let coefficient_bits = coefficient_hash.elements.as_vec()[..COEFFICIENT_BITS / 64]
+                .iter()
+                .flat_map(|element| self.to_le_bits(*element))
+                .collect_vec();
+            let mut coefficient_limbs = coefficient_bits
+                .chunks_exact(32)
+                .map(|bits| U32Variable::from_le_bits(bits, self))
+                .collect_vec();
+            coefficient_limbs.resize(8, self.zero());
+            let coefficient = U256Variable {
+                limbs: coefficient_limbs.try_into().unwrap(),
+            };
+
+            let z_s = self.api.mul_biguint(
+                &to_biguint_target(coefficient),
+                &to_biguint_target(signatures[i].s),
+            );
+            let z_s = self.ed25519_scalar_reduce(&z_s, 8);
+            let s_sum_unreduced = self
+                .api
+                .add_biguint(&to_biguint_target(s_sum), &to_biguint_target(z_s));
+            s_sum = self.ed25519_scalar_reduce(&s_sum_unreduced, 1);
+
+            let z_h = self.api.mul_biguint(
+                &to_biguint_target(coefficient),
+                &to_biguint_target(challenges[i]),
+            );
+            let z_h = self.ed25519_scalar_reduce(&z_h, 8);
+
+            let sigr_affine = self.curta_25519_decompress(signatures[i].r.clone());
+            self.curta_25519_is_valid(sigr_affine.clone());
+            points.push(sigr_affine);
+            scalars.push(coefficient_bits);
+
+            let pubkey_affine = self.curta_25519_decompress(pubkeys[i].clone());
+            self.curta_25519_is_valid(pubkey_affine.clone());
+            points.push(pubkey_affine);
+            scalars.push(self.to_le_bits(z_h));
+        }
+
+        let (generator_x, generator_y) = Ed25519Parameters::generator();
+        let generator_var =
+            AffinePointVariable::constant(self, AffinePoint::new(generator_x, generator_y));
+        let lhs = self.curta_25519_scalar_mul(s_sum, generator_var);
+        let lhs = self.ed25519_mul_by_cofactor(lhs);
+        let rhs = self.ed25519_msm(&points, &scalars);
+        let rhs = self.ed25519_mul_by_cofactor(rhs);
+        self.assert_is_equal(lhs, rhs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::edwards::CompressedEdwardsY;
+    use ed25519_dalek::{Signer, SigningKey};
+    use ethers::types::U256;
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use crate::frontend::curta::ec::point::CompressedEdwardsYVariable;
+    use crate::frontend::ecc::curve25519::ed25519::eddsa::{
+        EDDSASignatureVariable, EDDSASignatureVariableValue,
+    };
+    use crate::prelude::{ArrayVariable, BytesVariable, DefaultBuilder};
+    use crate::utils;
+
+    const MAX_MSG_LEN_BYTES: usize = 64;
+    const NUM_SIGS: usize = 4;
+
+    fn test_ed25519_verify_batch(tamper: bool) {
+        utils::setup_logger();
+
+        let mut builder = DefaultBuilder::new();
+        let pkeys = builder.read::<ArrayVariable<CompressedEdwardsYVariable, NUM_SIGS>>();
+        let signatures = builder.read::<ArrayVariable<EDDSASignatureVariable, NUM_SIGS>>();
+        let messages = builder.read::<ArrayVariable<BytesVariable<MAX_MSG_LEN_BYTES>, NUM_SIGS>>();
+        builder.ed25519_verify_batch(messages, None, signatures, pkeys);
+        let circuit = builder.build();
+
+        let mut test_messages = Vec::new();
+        let mut test_pub_keys = Vec::new();
+        let mut test_signatures = Vec::new();
+        let mut csprng = OsRng;
+        for i in 0..NUM_SIGS {
+            let mut test_message = [0u8; MAX_MSG_LEN_BYTES];
+            rand::thread_rng().fill(&mut test_message[..]);
+
+            let test_signing_key = SigningKey::generate(&mut csprng);
+            let test_signature = test_signing_key.sign(&test_message);
+
+            if tamper && i == NUM_SIGS - 1 {
+                test_message[0] = test_message[0].wrapping_add(1);
+            }
+            test_messages.push(test_message);
+            test_pub_keys.push(CompressedEdwardsY(
+                test_signing_key.verifying_key().to_bytes(),
+            ));
+            test_signatures.push(EDDSASignatureVariableValue {
+                r: CompressedEdwardsY(*test_signature.r_bytes()),
+                s: U256::from_little_endian(test_signature.s_bytes()),
+            });
+        }
+
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<CompressedEdwardsYVariable, NUM_SIGS>>(test_pub_keys);
+        input.write::<ArrayVariable<EDDSASignatureVariable, NUM_SIGS>>(test_signatures);
+        input.write::<ArrayVariable<BytesVariable<MAX_MSG_LEN_BYTES>, NUM_SIGS>>(test_messages);
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ed25519_verify_batch_valid() {
+        test_ed25519_verify_batch(false);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ed25519_verify_batch_failure() {
+        test_ed25519_verify_batch(true);
+    }
+}
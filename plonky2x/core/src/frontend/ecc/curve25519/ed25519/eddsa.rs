@@ -42,7 +42,7 @@ pub const DUMMY_SIGNATURE: [u8; 64] = [
 ];
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
-    fn get_dummy_variables<const MAX_MSG_BYTE_LENGTH: usize>(
+    pub(crate) fn get_dummy_variables<const MAX_MSG_BYTE_LENGTH: usize>(
         &mut self,
     ) -> (
         CompressedEdwardsYVariable,
@@ -126,6 +126,47 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         self.curta_eddsa_verify_sigs(msg_array, Some(msg_len_vec), sig_array, pub_key_array);
     }
 
+    /// Computes the challenge `H(R || A || M)` of a signature, reduced modulo the order of the
+    /// group. If message_byte_length is None, then the message has the length of
+    /// MAX_MSG_LENGTH_BYTES.
+    pub(crate) fn curta_eddsa_challenge<const MAX_MSG_LENGTH_BYTES: usize>(
+        &mut self,
+        signature: &EDDSASignatureVariable,
+        pubkey: &CompressedEdwardsYVariable,
+        message: &BytesVariable<MAX_MSG_LENGTH_BYTES>,
+        message_byte_length: Option<U32Variable>,
+    ) -> U256Variable {
+        // Create a new BytesVariable that will contain the message to be hashed.
+        // The hashed message is a concatenation of sigR, pk, and msg.
+        let mut message_bytes = Vec::new();
+        message_bytes.extend(signature.r.0.as_bytes());
+        message_bytes.extend(pubkey.0.as_bytes());
+        message_bytes.extend(message.0);
+
+        let digest: BytesVariable<64>;
+        if let Some(msg_len) = message_byte_length {
+            let const_64 = U32Variable::constant(self, 64);
+            let message_to_hash_len = self.add(msg_len, const_64);
+            digest = self.curta_sha512_variable(&message_bytes, message_to_hash_len);
+        } else {
+            digest = self.curta_sha512(&message_bytes);
+        }
+
+        let scalar_modulus_value =
+            U512::from_little_endian(&Ed25519ScalarField::modulus().to_bytes_le());
+        let scalar_modulus = self.constant::<U512Variable>(scalar_modulus_value);
+        let h_limbs = biguint_from_bytes_variable(self, digest)
+            .limbs
+            .into_iter()
+            .map(|x| x.target)
+            .collect::<Vec<_>>();
+        let h_int = U512Variable::from_targets(&h_limbs);
+        let h_scalar_512_limbs = self.rem(h_int, scalar_modulus).limbs;
+        U256Variable {
+            limbs: array![i => h_scalar_512_limbs[i]; 8],
+        }
+    }
+
     /// This function will verify a set of eddsa signatures. If message_byte_lengths is None, then
     /// all the messages should have the length of MAX_MSG_LENGTH_BYTES.
     pub fn curta_eddsa_verify_sigs<
@@ -151,40 +192,17 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let generator_affine = AffinePoint::new(generator_x, generator_y);
         let generator_var = AffinePointVariable::constant(self, generator_affine);
 
-        let scalar_modulus_value =
-            U512::from_little_endian(&Ed25519ScalarField::modulus().to_bytes_le());
-        let scalar_modulus = self.constant::<U512Variable>(scalar_modulus_value);
         let scalar_mod_256_value =
             U256::from_little_endian(&Ed25519ScalarField::modulus().to_bytes_le());
         let scalar_mod_256 = self.constant::<U256Variable>(scalar_mod_256_value);
 
         for i in 0..NUM_SIGS {
-            // Create a new BytesVariable that will contain the message to be hashed.
-            // The hashed message is a concatenation of sigR, pk, and msg.
-            let mut message_bytes = Vec::new();
-            message_bytes.extend(signatures[i].r.0.as_bytes());
-            message_bytes.extend(pubkeys[i].0.as_bytes());
-            message_bytes.extend(messages[i].0);
-
-            let digest: BytesVariable<64>;
-            if let Some(ref msg_lens) = message_byte_lengths {
-                let const_64 = U32Variable::constant(self, 64);
-                let message_to_hash_len = self.add(msg_lens[i], const_64);
-                digest = self.curta_sha512_variable(&message_bytes, message_to_hash_len);
-            } else {
-                digest = self.curta_sha512(&message_bytes);
-            }
-
-            let h_limbs = biguint_from_bytes_variable(self, digest)
-                .limbs
-                .into_iter()
-                .map(|x| x.target)
-                .collect::<Vec<_>>();
-            let h_int = U512Variable::from_targets(&h_limbs);
-            let h_scalar_512_limbs = self.rem(h_int, scalar_modulus).limbs;
-            let h_scalar = U256Variable {
-                limbs: array![i => h_scalar_512_limbs[i]; 8],
-            };
+            let h_scalar = self.curta_eddsa_challenge(
+                &signatures[i],
+                &pubkeys[i],
+                &messages[i],
+                message_byte_lengths.as_ref().map(|msg_lens| msg_lens[i]),
+            );
 
             let s = signatures[i].s;
             // Assert that s is less than the scalar modulus.
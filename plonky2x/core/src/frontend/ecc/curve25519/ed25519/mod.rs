@@ -1,3 +1,4 @@
+pub mod batch;
 pub mod eddsa;
 
 pub use starkyx::chip::ec::edwards::ed25519::params::Ed25519Parameters;
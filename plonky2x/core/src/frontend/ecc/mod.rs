@@ -1 +1,7 @@
+pub mod bls12_381;
+pub mod bn254;
 pub mod curve25519;
+pub mod nonnative;
+pub mod p256;
+pub mod secp256k1;
+pub mod tower;
@@ -0,0 +1,208 @@
+//! Non-native arithmetic modulo a prime of at most 256 bits, for the fields of `secp256k1` and
+//! `p256`. The base fields of the pairing-friendly curves use the lazily reduced limbs of the
+//! `tower` module instead.
+//!
+//! An element is a `U256Variable` less than the modulus. Results are reduced with the constrained
+//! division of `CircuitBuilderBiguint`, with a quotient only as wide as the operation needs, and
+//! inverses are witnessed by a hint and checked with a multiplication.
+
+use ethers::types::U256;
+use num::{One, Zero};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
+use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::{CircuitBuilderU32, U32Target};
+use crate::prelude::*;
+
+pub fn biguint_to_u256(value: &BigUint) -> U256 {
+    U256::from_little_endian(&value.to_bytes_le())
+}
+
+pub fn u256_to_biguint(value: U256) -> BigUint {
+    let mut bytes = [0u8; 32];
+    value.to_little_endian(&mut bytes);
+    BigUint::from_bytes_le(&bytes)
+}
+
+/// The inverse of a non-zero element modulo a prime.
+pub fn inverse_mod(value: &BigUint, modulus: &BigUint) -> BigUint {
+    assert!(!(value % modulus).is_zero(), "zero has no inverse");
+    value.modpow(&(modulus - 2u32), modulus)
+}
+
+fn to_biguint_target(a: U256Variable) -> BigUintTarget {
+    BigUintTarget {
+        limbs: a.limbs.iter().map(|limb| U32Target::from(*limb)).collect(),
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    pub fn nonnative_constant(&mut self, value: &BigUint) -> U256Variable {
+        self.constant::<U256Variable>(biguint_to_u256(value))
+    }
+
+    /// Reduces an integer less than `bound`.
+    fn nonnative_reduce(
+        &mut self,
+        modulus: &BigUint,
+        value: &BigUintTarget,
+        bound: &BigUint,
+    ) -> U256Variable {
+        assert!(
+            modulus.bits() <= 256,
+            "modulus does not fit in a U256Variable"
+        );
+        let max_quotient = (bound - 1u32) / modulus;
+        let nb_quotient_limbs = max_quotient.bits().div_ceil(32).max(1) as usize;
+        let modulus = self.api.constant_biguint(modulus);
+        let (_, rem) = self
+            .api
+            ._div_rem_biguint(value, &modulus, nb_quotient_limbs);
+        let zero = self.api.zero_u32();
+        U256Variable {
+            limbs: core::array::from_fn(|i| rem.limbs.get(i).copied().unwrap_or(zero).into()),
+        }
+    }
+
+    /// Asserts that an integer is less than the modulus, so that it is a canonical element.
+    pub fn nonnative_assert_reduced(&mut self, modulus: &BigUint, a: U256Variable) {
+        let modulus_variable = self.nonnative_constant(modulus);
+        let is_reduced = self.lt(a, modulus_variable);
+        let true_variable = self._true();
+        self.assert_is_equal(is_reduced, true_variable);
+    }
+
+    /// Reduces an arbitrary 256-bit integer, such as a hash.
+    pub fn nonnative_from_u256(&mut self, modulus: &BigUint, value: U256Variable) -> U256Variable {
+        let bound = BigUint::one() << 256;
+        self.nonnative_reduce(modulus, &to_biguint_target(value), &bound)
+    }
+
+    pub fn nonnative_add(
+        &mut self,
+        modulus: &BigUint,
+        a: U256Variable,
+        b: U256Variable,
+    ) -> U256Variable {
+        let sum = self
+            .api
+            .add_biguint(&to_biguint_target(a), &to_biguint_target(b));
+        self.nonnative_reduce(modulus, &sum, &(modulus * 2u32))
+    }
+
+    pub fn nonnative_neg(&mut self, modulus: &BigUint, a: U256Variable) -> U256Variable {
+        let modulus_target = self.api.constant_biguint(modulus);
+        // The difference is at most the modulus, which reduces to zero.
+        let difference = self.api.sub_biguint(&modulus_target, &to_biguint_target(a));
+        self.nonnative_reduce(modulus, &difference, &(modulus + 1u32))
+    }
+
+    pub fn nonnative_sub(
+        &mut self,
+        modulus: &BigUint,
+        a: U256Variable,
+        b: U256Variable,
+    ) -> U256Variable {
+        let neg_b = self.nonnative_neg(modulus, b);
+        self.nonnative_add(modulus, a, neg_b)
+    }
+
+    pub fn nonnative_mul(
+        &mut self,
+        modulus: &BigUint,
+        a: U256Variable,
+        b: U256Variable,
+    ) -> U256Variable {
+        let product = self
+            .api
+            .mul_biguint(&to_biguint_target(a), &to_biguint_target(b));
+        self.nonnative_reduce(modulus, &product, &(modulus * modulus))
+    }
+
+    /// The inverse of an element, which must be non-zero for the proof to be valid.
+    pub fn nonnative_inverse(&mut self, modulus: &BigUint, a: U256Variable) -> U256Variable {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        let hint = NonNativeInverseHint {
+            modulus: biguint_to_u256(modulus),
+        };
+        let output_stream = self.hint(input_stream, hint);
+        let inverse = output_stream.read::<U256Variable>(self);
+        self.nonnative_assert_reduced(modulus, inverse);
+
+        let product = self.nonnative_mul(modulus, a, inverse);
+        let one = self.constant::<U256Variable>(U256::one());
+        self.assert_is_equal(product, one);
+        inverse
+    }
+
+    pub fn nonnative_div(
+        &mut self,
+        modulus: &BigUint,
+        a: U256Variable,
+        b: U256Variable,
+    ) -> U256Variable {
+        let inverse = self.nonnative_inverse(modulus, b);
+        self.nonnative_mul(modulus, a, inverse)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NonNativeInverseHint {
+    modulus: U256,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for NonNativeInverseHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let a = u256_to_biguint(input_stream.read_value::<U256Variable>());
+        let modulus = u256_to_biguint(self.modulus);
+        let inverse = if (&a % &modulus).is_zero() {
+            a
+        } else {
+            inverse_mod(&a, &modulus)
+        };
+        output_stream.write_value::<U256Variable>(biguint_to_u256(&inverse));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nonnative_arithmetic_small_modulus() {
+        // A modulus narrower than a `U256Variable`, whose remainders are padded with zero limbs.
+        let p = BigUint::from(0xffff_ffff_ffff_ffc5u64);
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U256Variable>();
+        let b = builder.read::<U256Variable>();
+        let a = builder.nonnative_from_u256(&p, a);
+        let b = builder.nonnative_from_u256(&p, b);
+        let sum = builder.nonnative_add(&p, a, b);
+        let difference = builder.nonnative_sub(&p, a, b);
+        let product = builder.nonnative_mul(&p, a, b);
+        let quotient = builder.nonnative_div(&p, a, b);
+        builder.write(sum);
+        builder.write(difference);
+        builder.write(product);
+        builder.write(quotient);
+        let circuit = builder.build();
+
+        let a = (BigUint::one() << 256) - 1u32;
+        let b = BigUint::from(0x1234_5678_9abc_def0u64) << 100;
+        let mut input = circuit.input();
+        input.write::<U256Variable>(biguint_to_u256(&a));
+        input.write::<U256Variable>(biguint_to_u256(&b));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let (a, b) = (a % &p, b % &p);
+        let mut read = || u256_to_biguint(output.read::<U256Variable>());
+        assert_eq!(read(), (&a + &b) % &p);
+        assert_eq!(read(), (&a + &p - &b) % &p);
+        assert_eq!(read(), (&a * &b) % &p);
+        assert_eq!(read(), (&a * inverse_mod(&b, &p)) % &p);
+    }
+}
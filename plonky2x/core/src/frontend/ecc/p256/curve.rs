@@ -0,0 +1,187 @@
+//! Affine arithmetic over P-256.
+//!
+//! As for secp256k1, additions use incomplete formulas and constrain the points to have distinct
+//! `x` coordinates, and multi-scalar multiplications start from a fixed offset point that is
+//! subtracted at the end, with window tables whose entries are all shifted by another fixed point.
+
+use itertools::Itertools;
+use num::One;
+use num_bigint::BigUint;
+
+use super::native::{p256_a, p256_b, p256_base_modulus, P256Point};
+use crate::frontend::ecc::nonnative::biguint_to_u256;
+use crate::prelude::*;
+use crate::utils::hash::sha256;
+
+/// The number of bits of the scalars covered by each table lookup of a multi-scalar
+/// multiplication.
+const WINDOW_BITS: usize = 4;
+
+/// An affine point of P-256, other than the point at infinity.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(P256AffineValue)]
+#[value_derive(PartialEq, Eq)]
+pub struct P256AffineVariable {
+    pub x: U256Variable,
+    pub y: U256Variable,
+}
+
+impl<F: RichField> From<P256Point> for P256AffineValue<F> {
+    fn from(point: P256Point) -> Self {
+        let (x, y) = point.coordinates();
+        Self {
+            x: biguint_to_u256(&x),
+            y: biguint_to_u256(&y),
+        }
+    }
+}
+
+/// The starting point of multi-scalar multiplications, with no known relation to the generator.
+fn offset_point() -> P256Point {
+    let scalar = BigUint::from_bytes_be(&sha256(b"plonky2x p256 msm offset"));
+    P256Point::generator().mul(&scalar)
+}
+
+/// The point added to all the entries of the window tables, so that none of them is the point at
+/// infinity.
+fn table_offset_point() -> P256Point {
+    let scalar = BigUint::from_bytes_be(&sha256(b"plonky2x p256 msm table offset"));
+    P256Point::generator().mul(&scalar)
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    pub fn p256_point_constant(&mut self, point: &P256Point) -> P256AffineVariable {
+        self.constant::<P256AffineVariable>(point.clone().into())
+    }
+
+    pub fn p256_assert_on_curve(&mut self, point: &P256AffineVariable) {
+        let p = p256_base_modulus();
+        let x_squared = self.nonnative_mul(&p, point.x, point.x);
+        let a = self.nonnative_constant(&p256_a());
+        let x_squared_plus_a = self.nonnative_add(&p, x_squared, a);
+        let x_cubed_plus_ax = self.nonnative_mul(&p, x_squared_plus_a, point.x);
+        let b = self.nonnative_constant(&p256_b());
+        let rhs = self.nonnative_add(&p, x_cubed_plus_ax, b);
+        let lhs = self.nonnative_mul(&p, point.y, point.y);
+        self.assert_is_equal(lhs, rhs);
+    }
+
+    pub fn p256_point_neg(&mut self, point: &P256AffineVariable) -> P256AffineVariable {
+        P256AffineVariable {
+            x: point.x,
+            y: self.nonnative_neg(&p256_base_modulus(), point.y),
+        }
+    }
+
+    /// The third point on the line of the given slope through `a` and `b`, negated.
+    fn p256_add_with_slope(
+        &mut self,
+        a: &P256AffineVariable,
+        b: &P256AffineVariable,
+        slope: U256Variable,
+    ) -> P256AffineVariable {
+        let p = p256_base_modulus();
+        let slope_squared = self.nonnative_mul(&p, slope, slope);
+        let x = self.nonnative_sub(&p, slope_squared, a.x);
+        let x = self.nonnative_sub(&p, x, b.x);
+        let dx = self.nonnative_sub(&p, a.x, x);
+        let y = self.nonnative_mul(&p, slope, dx);
+        let y = self.nonnative_sub(&p, y, a.y);
+        P256AffineVariable { x, y }
+    }
+
+    /// Adds two points with distinct `x` coordinates.
+    pub fn p256_point_add(
+        &mut self,
+        a: &P256AffineVariable,
+        b: &P256AffineVariable,
+    ) -> P256AffineVariable {
+        let p = p256_base_modulus();
+        let dy = self.nonnative_sub(&p, b.y, a.y);
+        let dx = self.nonnative_sub(&p, b.x, a.x);
+        let slope = self.nonnative_div(&p, dy, dx);
+        self.p256_add_with_slope(a, b, slope)
+    }
+
+    pub fn p256_point_double(&mut self, point: &P256AffineVariable) -> P256AffineVariable {
+        let p = p256_base_modulus();
+        let x_squared = self.nonnative_mul(&p, point.x, point.x);
+        let three = self.nonnative_constant(&BigUint::from(3u32));
+        let three_x_squared = self.nonnative_mul(&p, x_squared, three);
+        let a = self.nonnative_constant(&p256_a());
+        let numerator = self.nonnative_add(&p, three_x_squared, a);
+        let denominator = self.nonnative_add(&p, point.y, point.y);
+        let slope = self.nonnative_div(&p, numerator, denominator);
+        self.p256_add_with_slope(point, point, slope)
+    }
+
+    /// Selects `table[index]`, where `index` is given by its little endian bits.
+    fn p256_select_table(
+        &mut self,
+        table: &[P256AffineVariable],
+        index_bits: &[BoolVariable],
+    ) -> P256AffineVariable {
+        assert_eq!(table.len(), 1 << index_bits.len());
+        let mut entries = table.to_vec();
+        for bit in index_bits {
+            entries = entries
+                .chunks_exact(2)
+                .map(|pair| self.select(*bit, pair[1].clone(), pair[0].clone()))
+                .collect();
+        }
+        entries.pop().unwrap()
+    }
+
+    /// Computes `sum_i scalars[i] * points[i]` with one doubling per bit shared by all the points,
+    /// and one addition per window of `WINDOW_BITS` bits and point. The result must not be the
+    /// point at infinity for the proof to be valid.
+    pub fn p256_msm(
+        &mut self,
+        points: &[P256AffineVariable],
+        scalars: &[U256Variable],
+    ) -> P256AffineVariable {
+        assert_eq!(points.len(), scalars.len());
+        let bits = scalars
+            .iter()
+            .map(|scalar| self.to_le_bits(*scalar))
+            .collect::<Vec<_>>();
+
+        // The entry `d` of the table of a point `P` is `d * P + T` for the table offset `T`.
+        let table_offset = table_offset_point();
+        let tables = points
+            .iter()
+            .map(|point| {
+                let mut table = vec![self.p256_point_constant(&table_offset)];
+                for _ in 1..1 << WINDOW_BITS {
+                    let entry = self.p256_point_add(table.last().unwrap(), point);
+                    table.push(entry);
+                }
+                table
+            })
+            .collect_vec();
+
+        let offset = offset_point();
+        let mut result = self.p256_point_constant(&offset);
+        let nb_windows = 256 / WINDOW_BITS;
+        for window in (0..nb_windows).rev() {
+            for _ in 0..WINDOW_BITS {
+                result = self.p256_point_double(&result);
+            }
+            for (table, bits) in tables.iter().zip(bits.iter()) {
+                let digit = &bits[window * WINDOW_BITS..(window + 1) * WINDOW_BITS];
+                let entry = self.p256_select_table(table, digit);
+                result = self.p256_point_add(&result, &entry);
+            }
+        }
+
+        // The offset has been doubled once per bit, and each window of each point has added the
+        // table offset with the weight of the window, that is `sum_j 2^(WINDOW_BITS * j)`.
+        let window_weights = ((BigUint::one() << 256) - 1u32) / ((1u32 << WINDOW_BITS) - 1);
+        let correction = offset
+            .mul(&(BigUint::one() << 256))
+            .add(&table_offset.mul(&(window_weights * points.len())))
+            .negate();
+        let correction = self.p256_point_constant(&correction);
+        self.p256_point_add(&result, &correction)
+    }
+}
@@ -0,0 +1,150 @@
+//! ECDSA verification over P-256, and verification of the WebAuthn assertions signed by passkeys.
+//!
+//! A WebAuthn assertion is an ECDSA signature with SHA-256 of `authenticatorData ||
+//! sha256(clientDataJSON)`. Checking the contents of either part, such as the challenge or the
+//! flags, is left to the caller, which has them as bytes.
+
+use super::curve::P256AffineVariable;
+use super::native::{p256_scalar_modulus, P256Point};
+use crate::frontend::ecc::secp256k1::ecdsa::EcdsaSignatureVariable;
+use crate::prelude::*;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Checks an ECDSA signature of the 32-byte `message_hash` under `key`.
+    pub fn p256_ecdsa_verify(
+        &mut self,
+        key: &P256AffineVariable,
+        message_hash: Bytes32Variable,
+        signature: &EcdsaSignatureVariable,
+    ) {
+        let n = p256_scalar_modulus();
+        let true_variable = self._true();
+        let zero = self.zero::<U256Variable>();
+        let n_variable = self.nonnative_constant(&n);
+        for scalar in [signature.r, signature.s] {
+            let is_reduced = self.lt(scalar, n_variable);
+            self.assert_is_equal(is_reduced, true_variable);
+            let is_positive = self.lt(zero, scalar);
+            self.assert_is_equal(is_positive, true_variable);
+        }
+        self.p256_assert_on_curve(key);
+
+        // The nonce point `R = (z / s) * G + (r / s) * Q` must have `x` equal to `r` modulo the
+        // group order.
+        let z = message_hash.as_u256(self);
+        let z = self.nonnative_from_u256(&n, z);
+        let s_inverse = self.nonnative_inverse(&n, signature.s);
+        let u1 = self.nonnative_mul(&n, z, s_inverse);
+        let u2 = self.nonnative_mul(&n, signature.r, s_inverse);
+        let generator = self.p256_point_constant(&P256Point::generator());
+        let nonce = self.p256_msm(&[generator, key.clone()], &[u1, u2]);
+        let nonce_x = self.nonnative_from_u256(&n, nonce.x);
+        self.assert_is_equal(nonce_x, signature.r);
+    }
+
+    /// Checks a WebAuthn assertion signature over `authenticator_data` and `client_data_json`
+    /// under the credential key `key`.
+    pub fn p256_webauthn_verify(
+        &mut self,
+        key: &P256AffineVariable,
+        authenticator_data: &[ByteVariable],
+        client_data_json: &[ByteVariable],
+        signature: &EcdsaSignatureVariable,
+    ) {
+        let client_data_hash = self.curta_sha256(client_data_json);
+        let mut message = authenticator_data.to_vec();
+        message.extend(client_data_hash.as_bytes());
+        let message_hash = self.curta_sha256(&message);
+        self.p256_ecdsa_verify(key, message_hash, signature);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+    use num_bigint::BigUint;
+
+    use super::*;
+    use crate::frontend::ecc::nonnative::biguint_to_u256;
+    use crate::frontend::ecc::p256::native::p256_ecdsa_sign;
+    use crate::frontend::ecc::secp256k1::ecdsa::EcdsaSignature;
+    use crate::utils::bytes;
+    use crate::utils::hash::sha256;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_p256_ecdsa_verify() {
+        let mut builder = DefaultBuilder::new();
+        let key = builder.read::<P256AffineVariable>();
+        let message_hash = builder.read::<Bytes32Variable>();
+        let signature = builder.read::<EcdsaSignatureVariable>();
+        builder.p256_ecdsa_verify(&key, message_hash, &signature);
+        let circuit = builder.build();
+
+        let secret = BigUint::from(0x5eed_u32) << 180;
+        let key = P256Point::generator().mul(&secret);
+        let message_hash = sha256(b"plonky2x p256 ecdsa");
+        let (r, s) = p256_ecdsa_sign(&secret, &BigUint::from(0xc0ffee_u32), &message_hash);
+
+        let mut input = circuit.input();
+        input.write::<P256AffineVariable>(key.into());
+        input.write::<Bytes32Variable>(H256(message_hash));
+        input.write::<EcdsaSignatureVariable>(EcdsaSignature {
+            r: biguint_to_u256(&r),
+            s: biguint_to_u256(&s),
+        });
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_p256_webauthn_verify() {
+        let authenticator_data: [u8; 37] =
+            bytes!("49960de5880e8c687434170f6476605b8fe4aeb9a28632c7995cf3ba831d97630500000000");
+        let client_data_json =
+            br#"{"type":"webauthn.get","challenge":"cGx1bmt5Mng","origin":"https://example.com"}"#;
+
+        let mut builder = DefaultBuilder::new();
+        let key = builder.read::<P256AffineVariable>();
+        let authenticator_data_variable = builder.read::<BytesVariable<37>>();
+        let client_data_json_variable = builder.read::<BytesVariable<80>>();
+        let signature = builder.read::<EcdsaSignatureVariable>();
+        builder.p256_webauthn_verify(
+            &key,
+            &authenticator_data_variable.0,
+            &client_data_json_variable.0,
+            &signature,
+        );
+        let circuit = builder.build();
+
+        // An assertion signed by an external P-256 implementation with the key of the examples of
+        // RFC 6979.
+        let secret = BigUint::parse_bytes(
+            b"c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721",
+            16,
+        )
+        .unwrap();
+        let r = BigUint::parse_bytes(
+            b"eccf0d588a8bb6a6370cda7167ea844d86c95bb75407a8acd8256d7566163a44",
+            16,
+        )
+        .unwrap();
+        let s = BigUint::parse_bytes(
+            b"1daf0534bc5bf404431165859e8e52b086636cb45ece07e929eb80f9458e44e1",
+            16,
+        )
+        .unwrap();
+
+        let mut input = circuit.input();
+        input.write::<P256AffineVariable>(P256Point::generator().mul(&secret).into());
+        input.write::<BytesVariable<37>>(authenticator_data);
+        input.write::<BytesVariable<80>>(*client_data_json);
+        input.write::<EcdsaSignatureVariable>(EcdsaSignature {
+            r: biguint_to_u256(&r),
+            s: biguint_to_u256(&s),
+        });
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
@@ -0,0 +1,10 @@
+//! Arithmetic over NIST P-256, with verification of ECDSA signatures and of the WebAuthn
+//! assertions of passkeys.
+//!
+//! Elements of both fields are `U256Variable`s handled by the non-native arithmetic of
+//! `frontend::ecc::nonnative`, as for secp256k1, with the doubling formula accounting for the
+//! coefficient `a = -3` of the curve.
+
+pub mod curve;
+pub mod ecdsa;
+pub mod native;
@@ -0,0 +1,200 @@
+//! Native arithmetic over NIST P-256, used for the constants of the circuit gadgets and to produce
+//! test vectors.
+
+use num::Zero;
+use num_bigint::BigUint;
+
+use crate::frontend::ecc::nonnative::inverse_mod;
+
+/// The modulus of the base field.
+pub fn p256_base_modulus() -> BigUint {
+    BigUint::parse_bytes(
+        b"ffffffff00000001000000000000000000000000ffffffffffffffffffffffff",
+        16,
+    )
+    .unwrap()
+}
+
+/// The order of the group, which is the modulus of the scalar field.
+pub fn p256_scalar_modulus() -> BigUint {
+    BigUint::parse_bytes(
+        b"ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551",
+        16,
+    )
+    .unwrap()
+}
+
+/// The coefficient `a = -3` of the curve `y^2 = x^3 + a * x + b`.
+pub fn p256_a() -> BigUint {
+    p256_base_modulus() - 3u32
+}
+
+/// The coefficient `b` of the curve `y^2 = x^3 + a * x + b`.
+pub fn p256_b() -> BigUint {
+    BigUint::parse_bytes(
+        b"5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b",
+        16,
+    )
+    .unwrap()
+}
+
+/// An affine point of P-256, or the point at infinity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum P256Point {
+    Infinity,
+    Affine { x: BigUint, y: BigUint },
+}
+
+impl P256Point {
+    pub fn generator() -> Self {
+        Self::Affine {
+            x: BigUint::parse_bytes(
+                b"6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296",
+                16,
+            )
+            .unwrap(),
+            y: BigUint::parse_bytes(
+                b"4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5",
+                16,
+            )
+            .unwrap(),
+        }
+    }
+
+    pub fn coordinates(&self) -> (BigUint, BigUint) {
+        match self {
+            Self::Infinity => panic!("the point at infinity has no coordinates"),
+            Self::Affine { x, y } => (x.clone(), y.clone()),
+        }
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        match self {
+            Self::Infinity => true,
+            Self::Affine { x, y } => {
+                let p = p256_base_modulus();
+                (x * x * x + p256_a() * x + p256_b()) % &p == y * y % &p
+            }
+        }
+    }
+
+    pub fn negate(&self) -> Self {
+        match self {
+            Self::Infinity => Self::Infinity,
+            Self::Affine { x, y } => Self::Affine {
+                x: x.clone(),
+                y: (p256_base_modulus() - y) % p256_base_modulus(),
+            },
+        }
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let p = p256_base_modulus();
+        let (x1, y1, x2, y2) = match (self, other) {
+            (Self::Infinity, _) => return other.clone(),
+            (_, Self::Infinity) => return self.clone(),
+            (Self::Affine { x: x1, y: y1 }, Self::Affine { x: x2, y: y2 }) => (x1, y1, x2, y2),
+        };
+        let slope = if x1 == x2 {
+            if (y1 + y2) % &p == BigUint::zero() {
+                return Self::Infinity;
+            }
+            let numerator = BigUint::from(3u32) * x1 * x1 + p256_a();
+            numerator * inverse_mod(&(BigUint::from(2u32) * y1), &p) % &p
+        } else {
+            (y2 + &p - y1) * inverse_mod(&(x2 + &p - x1), &p) % &p
+        };
+        let x = (&slope * &slope + BigUint::from(2u32) * &p - x1 - x2) % &p;
+        let y = (slope * (x1 + &p - &x) + &p - y1) % &p;
+        Self::Affine { x, y }
+    }
+
+    pub fn mul(&self, scalar: &BigUint) -> Self {
+        let mut result = Self::Infinity;
+        for i in (0..scalar.bits()).rev() {
+            result = result.add(&result);
+            if scalar.bit(i) {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+}
+
+/// Signs a 32-byte message hash under the key `secret * G` with ECDSA, returning `(r, s)`.
+pub fn p256_ecdsa_sign(
+    secret: &BigUint,
+    nonce: &BigUint,
+    message_hash: &[u8; 32],
+) -> (BigUint, BigUint) {
+    let n = p256_scalar_modulus();
+    let z = BigUint::from_bytes_be(message_hash) % &n;
+    let r = P256Point::generator().mul(nonce).coordinates().0 % &n;
+    let s = inverse_mod(nonce, &n) * (z + &r * secret) % &n;
+    (r, s)
+}
+
+/// Checks an ECDSA signature `(r, s)` of a 32-byte message hash under `key`.
+pub fn p256_ecdsa_verify(
+    key: &P256Point,
+    message_hash: &[u8; 32],
+    r: &BigUint,
+    s: &BigUint,
+) -> bool {
+    let n = p256_scalar_modulus();
+    if r.is_zero() || s.is_zero() || r >= &n || s >= &n || !key.is_on_curve() {
+        return false;
+    }
+    let z = BigUint::from_bytes_be(message_hash) % &n;
+    let s_inverse = inverse_mod(s, &n);
+    let nonce = P256Point::generator()
+        .mul(&(z * &s_inverse % &n))
+        .add(&key.mul(&(r * &s_inverse % &n)));
+    match nonce {
+        P256Point::Infinity => false,
+        P256Point::Affine { x, .. } => &(x % &n) == r,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hash::sha256;
+
+    #[test]
+    fn test_p256_native() {
+        // The key of the P-256 examples of RFC 6979.
+        let secret = BigUint::parse_bytes(
+            b"c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721",
+            16,
+        )
+        .unwrap();
+        let key = P256Point::generator().mul(&secret);
+        assert!(key.is_on_curve());
+        assert_eq!(
+            key.coordinates().0,
+            BigUint::parse_bytes(
+                b"60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6",
+                16,
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            key.coordinates().1,
+            BigUint::parse_bytes(
+                b"7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299",
+                16,
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            P256Point::generator().mul(&p256_scalar_modulus()),
+            P256Point::Infinity
+        );
+
+        let message_hash = sha256(b"plonky2x p256");
+        let (r, s) = p256_ecdsa_sign(&secret, &BigUint::from(0xc0ffee_u32), &message_hash);
+        assert!(p256_ecdsa_verify(&key, &message_hash, &r, &s));
+        assert!(!p256_ecdsa_verify(&key.negate(), &message_hash, &r, &s));
+    }
+}
@@ -0,0 +1,172 @@
+//! Non-hardened BIP-32 derivation of child public keys, so that a circuit can prove that a key or
+//! an address descends from an extended public key without revealing the derivation path.
+//!
+//! The child of `(K, c)` at index `i < 2^31` is `(IL * G + K, IR)` where
+//! `IL || IR = HMAC-SHA512(c, serP(K) || ser32(i))`. Hardened indices need the private key and are
+//! rejected.
+
+use ethers::types::H256;
+use num_bigint::BigUint;
+
+use super::curve::{Secp256k1AffineValue, Secp256k1AffineVariable};
+use super::native::{secp256k1_scalar_modulus, Secp256k1Point};
+use crate::frontend::eth::vars::AddressVariable;
+use crate::prelude::*;
+use crate::utils::hash::sha512;
+
+/// An extended public key, without the metadata of its serialization.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(ExtendedPublicKey)]
+pub struct ExtendedPublicKeyVariable {
+    pub key: Secp256k1AffineVariable,
+    pub chain_code: Bytes32Variable,
+}
+
+fn hmac_sha512(key: &[u8], message: &[u8]) -> [u8; 64] {
+    let mut padded_key = key.to_vec();
+    padded_key.resize(128, 0);
+    let inner = padded_key
+        .iter()
+        .map(|byte| byte ^ 0x36)
+        .collect::<Vec<_>>();
+    let inner_digest = sha512(&[&inner[..], message].concat());
+    let outer = padded_key
+        .iter()
+        .map(|byte| byte ^ 0x5c)
+        .collect::<Vec<_>>();
+    sha512(&[&outer[..], &inner_digest[..]].concat())
+}
+
+/// Derives the child of an extended public key at a non-hardened index.
+pub fn bip32_derive_child_native(
+    key: &Secp256k1Point,
+    chain_code: &[u8; 32],
+    index: u32,
+) -> (Secp256k1Point, [u8; 32]) {
+    assert!(index < 1 << 31, "hardened derivation needs the private key");
+    let mut message = key.compressed();
+    message.extend(index.to_be_bytes());
+    let mac = hmac_sha512(chain_code, &message);
+    let tweak = BigUint::from_bytes_be(&mac[..32]);
+    assert!(tweak < secp256k1_scalar_modulus(), "invalid child index");
+    let child = Secp256k1Point::generator().mul(&tweak).add(key);
+    (child, mac[32..].try_into().unwrap())
+}
+
+impl<F: RichField> ExtendedPublicKey<F> {
+    pub fn new(key: Secp256k1Point, chain_code: [u8; 32]) -> Self {
+        Self {
+            key: Secp256k1AffineValue::from(key),
+            chain_code: H256(chain_code),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Derives the child of `parent` at a non-hardened `index`.
+    pub fn bip32_derive_child(
+        &mut self,
+        parent: &ExtendedPublicKeyVariable,
+        index: U32Variable,
+    ) -> ExtendedPublicKeyVariable {
+        let false_variable = self._false();
+        let is_hardened = self.to_le_bits(index)[31];
+        self.assert_is_equal(is_hardened, false_variable);
+
+        let mut message = self.secp256k1_compress(&parent.key);
+        message.extend(index.encode(self));
+        let mac = self.hmac_sha512(&parent.chain_code.as_bytes(), &message);
+        let tweak = Bytes32Variable::from(<[ByteVariable; 32]>::try_from(&mac.0[..32]).unwrap());
+        let chain_code =
+            Bytes32Variable::from(<[ByteVariable; 32]>::try_from(&mac.0[32..]).unwrap());
+
+        // BIP-32 skips the indices whose tweak is not a scalar, which happens with probability
+        // about 2^-127, so they are simply rejected.
+        let tweak = tweak.as_u256(self);
+        let n = self.secp256k1_constant(&secp256k1_scalar_modulus());
+        let is_scalar = self.lt(tweak, n);
+        let true_variable = self._true();
+        self.assert_is_equal(is_scalar, true_variable);
+
+        let generator = self.secp256k1_point_constant(&Secp256k1Point::generator());
+        let tweak_point = self.secp256k1_msm(&[generator], &[tweak]);
+        let key = self.secp256k1_point_add(&tweak_point, &parent.key);
+        ExtendedPublicKeyVariable { key, chain_code }
+    }
+
+    /// Derives the descendant of `root` along a path of non-hardened indices, checking that the
+    /// root key is on the curve.
+    pub fn bip32_derive_path(
+        &mut self,
+        root: &ExtendedPublicKeyVariable,
+        path: &[U32Variable],
+    ) -> ExtendedPublicKeyVariable {
+        self.secp256k1_assert_on_curve(&root.key);
+        path.iter().fold(root.clone(), |key, index| {
+            self.bip32_derive_child(&key, *index)
+        })
+    }
+
+    /// The Ethereum address of a key, the last 20 bytes of the keccak256 hash of its coordinates.
+    pub fn secp256k1_eth_address(&mut self, key: &Secp256k1AffineVariable) -> AddressVariable {
+        let mut coordinates = key.x.encode(self);
+        coordinates.extend(key.y.encode(self));
+        let hash = self.keccak256(&coordinates);
+        AddressVariable(BytesVariable(hash.as_bytes()[12..].try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::bytes;
+
+    // The master key and its child `m/0` from test vector 2 of BIP-32.
+    const PARENT_KEY: &str = "03cbcaa9c98c877a26977d00825c956a238e8dddfbd322cce4f74b0b5bd6ace4a7";
+    const PARENT_CHAIN_CODE: &str =
+        "60499f801b896d83179a4374aeb7822aaeaceaa0db1f85ee3e904c4defbd9689";
+    const CHILD_KEY: &str = "02fc9e5af0ac8d9b3cecfe2a888e2117ba3d089d8585886c9c826b6b22a98d12ea";
+    const CHILD_CHAIN_CODE: &str =
+        "f0909affaa7ee7abe5dd4e100598d4dc53cd709d5a5c2cac40e7412f232f7c9c";
+
+    fn parent() -> (Secp256k1Point, [u8; 32]) {
+        let key: [u8; 33] = bytes!(PARENT_KEY);
+        (
+            Secp256k1Point::from_compressed(&key),
+            bytes!(PARENT_CHAIN_CODE),
+        )
+    }
+
+    #[test]
+    fn test_bip32_derive_child_native() {
+        let (key, chain_code) = parent();
+        let (child, child_chain_code) = bip32_derive_child_native(&key, &chain_code, 0);
+        let expected_key: [u8; 33] = bytes!(CHILD_KEY);
+        let expected_chain_code: [u8; 32] = bytes!(CHILD_CHAIN_CODE);
+        assert_eq!(child.compressed(), expected_key);
+        assert_eq!(child_chain_code, expected_chain_code);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_bip32_derive_path() {
+        let mut builder = DefaultBuilder::new();
+        let root = builder.read::<ExtendedPublicKeyVariable>();
+        let index = builder.read::<U32Variable>();
+        let child = builder.bip32_derive_path(&root, &[index]);
+        builder.write(child);
+        let circuit = builder.build();
+
+        let (key, chain_code) = parent();
+        let mut input = circuit.input();
+        input.write::<ExtendedPublicKeyVariable>(ExtendedPublicKey::new(key.clone(), chain_code));
+        input.write::<U32Variable>(0);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let child = output.read::<ExtendedPublicKeyVariable>();
+        let (expected_key, expected_chain_code) = bip32_derive_child_native(&key, &chain_code, 0);
+        assert_eq!(child.key, expected_key.into());
+        assert_eq!(child.chain_code, H256(expected_chain_code));
+    }
+}
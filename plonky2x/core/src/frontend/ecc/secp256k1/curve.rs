@@ -0,0 +1,276 @@
+//! Affine arithmetic over secp256k1.
+//!
+//! Additions use incomplete formulas and constrain the points to have distinct `x` coordinates,
+//! and multi-scalar multiplications start from a fixed offset point that is subtracted at the end,
+//! so the rare inputs hitting the exceptional cases make the proof fail instead of being accepted.
+//! The same holds for the tables of the windowed multiplications, whose entries are all shifted by
+//! another fixed point.
+
+use itertools::Itertools;
+use num::One;
+use num_bigint::BigUint;
+
+use super::field::{biguint_to_u256, Secp256k1Field};
+use super::native::Secp256k1Point;
+use crate::prelude::*;
+use crate::utils::hash::sha256;
+
+const BASE: Secp256k1Field = Secp256k1Field::Base;
+
+/// The number of bits of the scalars covered by each table lookup of a multi-scalar
+/// multiplication.
+const WINDOW_BITS: usize = 4;
+
+/// An affine point of secp256k1, other than the point at infinity.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(Secp256k1AffineValue)]
+#[value_derive(PartialEq, Eq)]
+pub struct Secp256k1AffineVariable {
+    pub x: U256Variable,
+    pub y: U256Variable,
+}
+
+impl<F: RichField> From<Secp256k1Point> for Secp256k1AffineValue<F> {
+    fn from(point: Secp256k1Point) -> Self {
+        let (x, y) = point.coordinates();
+        Self {
+            x: biguint_to_u256(&x),
+            y: biguint_to_u256(&y),
+        }
+    }
+}
+
+/// The starting point of multi-scalar multiplications, with no known relation to the generator.
+fn offset_point() -> Secp256k1Point {
+    let scalar = BigUint::from_bytes_be(&sha256(b"plonky2x secp256k1 msm offset"));
+    Secp256k1Point::generator().mul(&scalar)
+}
+
+/// The point added to all the entries of the window tables, so that none of them is the point at
+/// infinity.
+fn table_offset_point() -> Secp256k1Point {
+    let scalar = BigUint::from_bytes_be(&sha256(b"plonky2x secp256k1 msm table offset"));
+    Secp256k1Point::generator().mul(&scalar)
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    pub fn secp256k1_point_constant(&mut self, point: &Secp256k1Point) -> Secp256k1AffineVariable {
+        self.constant::<Secp256k1AffineVariable>(point.clone().into())
+    }
+
+    /// Asserts that the coordinates are canonical elements and satisfy `y^2 = x^3 + 7`, as
+    /// non-canonical coordinates would change the parity of `y` in the compressed encoding.
+    pub fn secp256k1_assert_on_curve(&mut self, point: &Secp256k1AffineVariable) {
+        self.secp256k1_assert_reduced(BASE, point.x);
+        self.secp256k1_assert_reduced(BASE, point.y);
+        let x_squared = self.secp256k1_mul(BASE, point.x, point.x);
+        let x_cubed = self.secp256k1_mul(BASE, x_squared, point.x);
+        let b = self.secp256k1_constant(&BigUint::from(7u32));
+        let rhs = self.secp256k1_add(BASE, x_cubed, b);
+        let lhs = self.secp256k1_mul(BASE, point.y, point.y);
+        self.assert_is_equal(lhs, rhs);
+    }
+
+    pub fn secp256k1_has_even_y(&mut self, point: &Secp256k1AffineVariable) -> BoolVariable {
+        let is_odd = self.to_le_bits(point.y.limbs[0])[0];
+        self.not(is_odd)
+    }
+
+    /// The 33-byte SEC1 compressed encoding of a point.
+    pub fn secp256k1_compress(&mut self, point: &Secp256k1AffineVariable) -> Vec<ByteVariable> {
+        let is_odd = self.to_le_bits(point.y.limbs[0])[0];
+        let zero = self._false();
+        let one = self._true();
+        let mut bytes = vec![ByteVariable([
+            zero, zero, zero, zero, zero, zero, one, is_odd,
+        ])];
+        bytes.extend(point.x.encode(self));
+        bytes
+    }
+
+    pub fn secp256k1_point_neg(
+        &mut self,
+        point: &Secp256k1AffineVariable,
+    ) -> Secp256k1AffineVariable {
+        Secp256k1AffineVariable {
+            x: point.x,
+            y: self.secp256k1_neg(BASE, point.y),
+        }
+    }
+
+    /// The third point on the line of the given slope through `a` and `b`, negated.
+    fn secp256k1_add_with_slope(
+        &mut self,
+        a: &Secp256k1AffineVariable,
+        b: &Secp256k1AffineVariable,
+        slope: U256Variable,
+    ) -> Secp256k1AffineVariable {
+        let slope_squared = self.secp256k1_mul(BASE, slope, slope);
+        let x = self.secp256k1_sub(BASE, slope_squared, a.x);
+        let x = self.secp256k1_sub(BASE, x, b.x);
+        let dx = self.secp256k1_sub(BASE, a.x, x);
+        let y = self.secp256k1_mul(BASE, slope, dx);
+        let y = self.secp256k1_sub(BASE, y, a.y);
+        Secp256k1AffineVariable { x, y }
+    }
+
+    /// Adds two points with distinct `x` coordinates.
+    pub fn secp256k1_point_add(
+        &mut self,
+        a: &Secp256k1AffineVariable,
+        b: &Secp256k1AffineVariable,
+    ) -> Secp256k1AffineVariable {
+        let dy = self.secp256k1_sub(BASE, b.y, a.y);
+        let dx = self.secp256k1_sub(BASE, b.x, a.x);
+        let slope = self.secp256k1_div(BASE, dy, dx);
+        self.secp256k1_add_with_slope(a, b, slope)
+    }
+
+    pub fn secp256k1_point_double(
+        &mut self,
+        point: &Secp256k1AffineVariable,
+    ) -> Secp256k1AffineVariable {
+        let x_squared = self.secp256k1_mul(BASE, point.x, point.x);
+        let three = self.secp256k1_constant(&BigUint::from(3u32));
+        let numerator = self.secp256k1_mul(BASE, x_squared, three);
+        let denominator = self.secp256k1_add(BASE, point.y, point.y);
+        let slope = self.secp256k1_div(BASE, numerator, denominator);
+        self.secp256k1_add_with_slope(point, point, slope)
+    }
+
+    /// Selects `table[index]`, where `index` is given by its little endian bits.
+    fn secp256k1_select_table(
+        &mut self,
+        table: &[Secp256k1AffineVariable],
+        index_bits: &[BoolVariable],
+    ) -> Secp256k1AffineVariable {
+        assert_eq!(table.len(), 1 << index_bits.len());
+        let mut entries = table.to_vec();
+        for bit in index_bits {
+            entries = entries
+                .chunks_exact(2)
+                .map(|pair| self.select(*bit, pair[1].clone(), pair[0].clone()))
+                .collect();
+        }
+        entries.pop().unwrap()
+    }
+
+    /// Computes `sum_i scalars[i] * points[i]` with one doubling per bit shared by all the points,
+    /// and one addition per window of `WINDOW_BITS` bits and point, from a table of the small
+    /// multiples of each point. The result must not be the point at infinity for the proof to be
+    /// valid.
+    pub fn secp256k1_msm(
+        &mut self,
+        points: &[Secp256k1AffineVariable],
+        scalars: &[U256Variable],
+    ) -> Secp256k1AffineVariable {
+        assert_eq!(points.len(), scalars.len());
+        let bits = scalars
+            .iter()
+            .map(|scalar| self.to_le_bits(*scalar))
+            .collect::<Vec<_>>();
+
+        // The entry `d` of the table of a point `P` is `d * P + T` for the table offset `T`.
+        let table_offset = table_offset_point();
+        let tables = points
+            .iter()
+            .map(|point| {
+                let mut table = vec![self.secp256k1_point_constant(&table_offset)];
+                for _ in 1..1 << WINDOW_BITS {
+                    let entry = self.secp256k1_point_add(table.last().unwrap(), point);
+                    table.push(entry);
+                }
+                table
+            })
+            .collect_vec();
+
+        let offset = offset_point();
+        let mut result = self.secp256k1_point_constant(&offset);
+        let nb_windows = 256 / WINDOW_BITS;
+        for window in (0..nb_windows).rev() {
+            for _ in 0..WINDOW_BITS {
+                result = self.secp256k1_point_double(&result);
+            }
+            for (table, bits) in tables.iter().zip(bits.iter()) {
+                let digit = &bits[window * WINDOW_BITS..(window + 1) * WINDOW_BITS];
+                let entry = self.secp256k1_select_table(table, digit);
+                result = self.secp256k1_point_add(&result, &entry);
+            }
+        }
+
+        // The offset has been doubled once per bit, and each window of each point has added the
+        // table offset with the weight of the window, that is `sum_j 2^(WINDOW_BITS * j)`.
+        let window_weights = ((BigUint::one() << 256) - 1u32) / ((1u32 << WINDOW_BITS) - 1);
+        let correction = offset
+            .mul(&(BigUint::one() << 256))
+            .add(&table_offset.mul(&(window_weights * points.len())))
+            .negate();
+        let correction = self.secp256k1_point_constant(&correction);
+        self.secp256k1_point_add(&result, &correction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::ecc::secp256k1::native::secp256k1_base_modulus;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_secp256k1_msm() {
+        let mut builder = DefaultBuilder::new();
+        let points = (0..2)
+            .map(|_| builder.read::<Secp256k1AffineVariable>())
+            .collect::<Vec<_>>();
+        let scalars = (0..2)
+            .map(|_| builder.read::<U256Variable>())
+            .collect::<Vec<_>>();
+        for point in points.iter() {
+            builder.secp256k1_assert_on_curve(point);
+        }
+        let result = builder.secp256k1_msm(&points, &scalars);
+        builder.write(result);
+        let circuit = builder.build();
+
+        let g = Secp256k1Point::generator();
+        let native_points = [
+            g.mul(&BigUint::from(17u32)),
+            g.mul(&BigUint::from(1u32 << 31)),
+        ];
+        let native_scalars = [BigUint::from(u64::MAX) << 100, BigUint::from(12345u32)];
+        let mut input = circuit.input();
+        for point in native_points.iter() {
+            input.write::<Secp256k1AffineVariable>(point.clone().into());
+        }
+        for scalar in native_scalars.iter() {
+            input.write::<U256Variable>(biguint_to_u256(scalar));
+        }
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let expected = native_points[0]
+            .mul(&native_scalars[0])
+            .add(&native_points[1].mul(&native_scalars[1]));
+        let result = output.read::<Secp256k1AffineVariable>();
+        assert_eq!(result, expected.into());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_secp256k1_assert_on_curve_non_canonical() {
+        let mut builder = DefaultBuilder::new();
+        let point = builder.read::<Secp256k1AffineVariable>();
+        builder.secp256k1_assert_on_curve(&point);
+        let circuit = builder.build();
+
+        // The point `(1, sqrt(8))` with its `x` coordinate shifted by the modulus.
+        let p = secp256k1_base_modulus();
+        let y = BigUint::from(8u32).modpow(&((&p + 1u32) / 4u32), &p);
+        let mut input = circuit.input();
+        input.write::<Secp256k1AffineVariable>(Secp256k1AffineValue {
+            x: biguint_to_u256(&(p + 1u32)),
+            y: biguint_to_u256(&y),
+        });
+        circuit.prove(&input);
+    }
+}
@@ -0,0 +1,205 @@
+//! ECDSA verification over secp256k1, as used by Ethereum transactions and Bitcoin, and the
+//! `ecrecover` precompile of the EVM.
+//!
+//! `ecdsa_verify` takes the public key as an input, so the recovery id `v` is not needed. The low
+//! `s` rule of EIP-2 is not enforced by either gadget, as it is not by the precompile.
+
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+
+use super::curve::Secp256k1AffineVariable;
+use super::field::{u256_to_biguint, Secp256k1Field};
+use super::native::{ecdsa_recover, secp256k1_scalar_modulus, Secp256k1Point};
+use crate::frontend::eth::vars::AddressVariable;
+use crate::frontend::hint::simple::hint::Hint;
+use crate::prelude::*;
+
+const SCALAR: Secp256k1Field = Secp256k1Field::Scalar;
+
+/// An ECDSA signature, two non-zero scalars.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(EcdsaSignature)]
+pub struct EcdsaSignatureVariable {
+    pub r: U256Variable,
+    pub s: U256Variable,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Checks that `r` and `s` are non-zero scalars and computes the nonce point
+    /// `R = (z / s) * G + (r / s) * Q` of a signature of the hash `z` under the key `Q`.
+    fn ecdsa_nonce(
+        &mut self,
+        key: &Secp256k1AffineVariable,
+        message_hash: Bytes32Variable,
+        signature: &EcdsaSignatureVariable,
+    ) -> Secp256k1AffineVariable {
+        let true_variable = self._true();
+        let zero = self.zero::<U256Variable>();
+        let n = self.secp256k1_constant(&secp256k1_scalar_modulus());
+        for scalar in [signature.r, signature.s] {
+            let is_reduced = self.lt(scalar, n);
+            self.assert_is_equal(is_reduced, true_variable);
+            let is_positive = self.lt(zero, scalar);
+            self.assert_is_equal(is_positive, true_variable);
+        }
+        self.secp256k1_assert_on_curve(key);
+
+        let z = message_hash.as_u256(self);
+        let z = self.secp256k1_from_u256(SCALAR, z);
+        let s_inverse = self.secp256k1_inverse(SCALAR, signature.s);
+        let u1 = self.secp256k1_mul(SCALAR, z, s_inverse);
+        let u2 = self.secp256k1_mul(SCALAR, signature.r, s_inverse);
+
+        let generator = self.secp256k1_point_constant(&Secp256k1Point::generator());
+        self.secp256k1_msm(&[generator, key.clone()], &[u1, u2])
+    }
+
+    /// Checks an ECDSA signature of the 32-byte `message_hash` under `key`.
+    pub fn ecdsa_verify(
+        &mut self,
+        key: &Secp256k1AffineVariable,
+        message_hash: Bytes32Variable,
+        signature: &EcdsaSignatureVariable,
+    ) {
+        // The `x` coordinate of the nonce must be `r` modulo the group order.
+        let nonce = self.ecdsa_nonce(key, message_hash, signature);
+        let nonce_x = self.secp256k1_from_u256(SCALAR, nonce.x);
+        self.assert_is_equal(nonce_x, signature.r);
+    }
+
+    /// Recovers the signer of `message_hash` as the `ecrecover` precompile does, from the 32-byte
+    /// words `v`, `r` and `s` of its input, and returns the address of the precompile's output.
+    ///
+    /// The key is witnessed and checked to produce the nonce point `(r, y)` whose parity of `y` is
+    /// given by `v`, which determines it uniquely. The inputs for which the precompile returns no
+    /// output, such as a `v` other than 27 and 28, make the proof fail.
+    pub fn ecrecover(
+        &mut self,
+        message_hash: Bytes32Variable,
+        v: U256Variable,
+        signature: &EcdsaSignatureVariable,
+    ) -> AddressVariable {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&message_hash);
+        input_stream.write(&v);
+        input_stream.write(signature);
+        let output_stream = self.hint(input_stream, EcrecoverHint);
+        let key = output_stream.read::<Secp256k1AffineVariable>(self);
+
+        let v_27 = self.constant::<U256Variable>(U256::from(27));
+        let v_28 = self.constant::<U256Variable>(U256::from(28));
+        let is_even = self.is_equal(v, v_27);
+        let is_odd = self.is_equal(v, v_28);
+        let is_valid = self.or(is_even, is_odd);
+        let true_variable = self._true();
+        self.assert_is_equal(is_valid, true_variable);
+
+        // The nonce point is `(r, y)` itself, not a point whose `x` coordinate reduces to `r`.
+        let nonce = self.ecdsa_nonce(&key, message_hash, signature);
+        self.assert_is_equal(nonce.x, signature.r);
+        let has_even_y = self.secp256k1_has_even_y(&nonce);
+        self.assert_is_equal(has_even_y, is_even);
+
+        self.secp256k1_eth_address(&key)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcrecoverHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for EcrecoverHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let message_hash = input_stream.read_value::<Bytes32Variable>();
+        let v = input_stream.read_value::<U256Variable>();
+        let signature = input_stream.read_value::<EcdsaSignatureVariable>();
+        let key = ecdsa_recover(
+            &message_hash.0,
+            v == U256::from(28),
+            &u256_to_biguint(signature.r),
+            &u256_to_biguint(signature.s),
+        );
+        // The constraints of the gadget cannot be satisfied for invalid inputs, so any point is
+        // as good as another.
+        let key = key.unwrap_or_else(Secp256k1Point::generator);
+        output_stream.write_value::<Secp256k1AffineVariable>(key.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{H160, H256};
+    use num::BigUint;
+
+    use super::*;
+    use crate::frontend::ecc::secp256k1::field::biguint_to_u256;
+    use crate::frontend::ecc::secp256k1::native::ecdsa_sign;
+    use crate::utils::bytes;
+    use crate::utils::hash::sha256;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ecdsa_verify() {
+        let mut builder = DefaultBuilder::new();
+        let key = builder.read::<Secp256k1AffineVariable>();
+        let message_hash = builder.read::<Bytes32Variable>();
+        let signature = builder.read::<EcdsaSignatureVariable>();
+        builder.ecdsa_verify(&key, message_hash, &signature);
+        let circuit = builder.build();
+
+        let secret = BigUint::from(0x5eed_u32) << 180;
+        let key = Secp256k1Point::generator().mul(&secret);
+        let message_hash = sha256(b"plonky2x ecdsa");
+        let (r, s) = ecdsa_sign(&secret, &BigUint::from(0xc0ffee_u32), &message_hash);
+
+        let mut input = circuit.input();
+        input.write::<Secp256k1AffineVariable>(key.into());
+        input.write::<Bytes32Variable>(H256(message_hash));
+        input.write::<EcdsaSignatureVariable>(EcdsaSignature {
+            r: biguint_to_u256(&r),
+            s: biguint_to_u256(&s),
+        });
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ecrecover() {
+        let mut builder = DefaultBuilder::new();
+        let message_hash = builder.read::<Bytes32Variable>();
+        let v = builder.read::<U256Variable>();
+        let signature = builder.read::<EcdsaSignatureVariable>();
+        let address = builder.ecrecover(message_hash, v, &signature);
+        builder.write(address);
+        let circuit = builder.build();
+
+        // The key with secret 1, whose address is well known.
+        let secret = BigUint::from(1u32);
+        let nonce = BigUint::from(0xc0ffee_u32);
+        let message_hash = sha256(b"plonky2x ecrecover");
+        let (r, s) = ecdsa_sign(&secret, &nonce, &message_hash);
+        let v = if Secp256k1Point::generator().mul(&nonce).has_even_y() {
+            27
+        } else {
+            28
+        };
+        let key = ecdsa_recover(&message_hash, v == 28, &r, &s).unwrap();
+        assert_eq!(key, Secp256k1Point::generator());
+
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(H256(message_hash));
+        input.write::<U256Variable>(U256::from(v));
+        input.write::<EcdsaSignatureVariable>(EcdsaSignature {
+            r: biguint_to_u256(&r),
+            s: biguint_to_u256(&s),
+        });
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let address = output.read::<AddressVariable>();
+        assert_eq!(
+            address,
+            H160(bytes!("7e5f4552091a69125d5dfcb7b8c2659029395bdf"))
+        );
+    }
+}
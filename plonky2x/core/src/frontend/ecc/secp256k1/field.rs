@@ -0,0 +1,148 @@
+//! Arithmetic modulo the secp256k1 base field and group order, on top of the non-native
+//! arithmetic of `frontend::ecc::nonnative`.
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::native::{secp256k1_base_modulus, secp256k1_scalar_modulus};
+pub use crate::frontend::ecc::nonnative::{biguint_to_u256, u256_to_biguint};
+use crate::prelude::*;
+
+/// One of the two fields of secp256k1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Secp256k1Field {
+    /// The field of the coordinates of the points.
+    Base,
+    /// The field of the scalars, of order the order of the group.
+    Scalar,
+}
+
+impl Secp256k1Field {
+    pub fn modulus(&self) -> BigUint {
+        match self {
+            Self::Base => secp256k1_base_modulus(),
+            Self::Scalar => secp256k1_scalar_modulus(),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    pub fn secp256k1_constant(&mut self, value: &BigUint) -> U256Variable {
+        self.nonnative_constant(value)
+    }
+
+    /// Reduces an arbitrary 256-bit integer, such as a hash.
+    pub fn secp256k1_from_u256(
+        &mut self,
+        field: Secp256k1Field,
+        value: U256Variable,
+    ) -> U256Variable {
+        self.nonnative_from_u256(&field.modulus(), value)
+    }
+
+    pub fn secp256k1_add(
+        &mut self,
+        field: Secp256k1Field,
+        a: U256Variable,
+        b: U256Variable,
+    ) -> U256Variable {
+        self.nonnative_add(&field.modulus(), a, b)
+    }
+
+    /// Asserts that an integer is a canonical element, less than the modulus.
+    pub fn secp256k1_assert_reduced(&mut self, field: Secp256k1Field, a: U256Variable) {
+        self.nonnative_assert_reduced(&field.modulus(), a)
+    }
+
+    /// The negation of an element, which is checked to be canonical as the subtraction from the
+    /// modulus would wrap otherwise.
+    pub fn secp256k1_neg(&mut self, field: Secp256k1Field, a: U256Variable) -> U256Variable {
+        self.secp256k1_assert_reduced(field, a);
+        self.nonnative_neg(&field.modulus(), a)
+    }
+
+    pub fn secp256k1_sub(
+        &mut self,
+        field: Secp256k1Field,
+        a: U256Variable,
+        b: U256Variable,
+    ) -> U256Variable {
+        self.nonnative_sub(&field.modulus(), a, b)
+    }
+
+    pub fn secp256k1_mul(
+        &mut self,
+        field: Secp256k1Field,
+        a: U256Variable,
+        b: U256Variable,
+    ) -> U256Variable {
+        self.nonnative_mul(&field.modulus(), a, b)
+    }
+
+    /// The inverse of an element, which must be non-zero for the proof to be valid.
+    pub fn secp256k1_inverse(&mut self, field: Secp256k1Field, a: U256Variable) -> U256Variable {
+        self.nonnative_inverse(&field.modulus(), a)
+    }
+
+    pub fn secp256k1_div(
+        &mut self,
+        field: Secp256k1Field,
+        a: U256Variable,
+        b: U256Variable,
+    ) -> U256Variable {
+        self.nonnative_div(&field.modulus(), a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::ecc::nonnative::inverse_mod;
+
+    #[test]
+    fn test_secp256k1_field_arithmetic() {
+        let field = Secp256k1Field::Base;
+        let p = field.modulus();
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U256Variable>();
+        let b = builder.read::<U256Variable>();
+        let sum = builder.secp256k1_add(field, a, b);
+        let difference = builder.secp256k1_sub(field, a, b);
+        let product = builder.secp256k1_mul(field, a, b);
+        let quotient = builder.secp256k1_div(field, a, b);
+        builder.write(sum);
+        builder.write(difference);
+        builder.write(product);
+        builder.write(quotient);
+        let circuit = builder.build();
+
+        let a = &p - 5u32;
+        let b = BigUint::from(0x1234_5678_9abc_def0u64) << 150;
+        let mut input = circuit.input();
+        input.write::<U256Variable>(biguint_to_u256(&a));
+        input.write::<U256Variable>(biguint_to_u256(&b));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let mut read = || u256_to_biguint(output.read::<U256Variable>());
+        assert_eq!(read(), (&a + &b) % &p);
+        assert_eq!(read(), (&a + &p - &b) % &p);
+        assert_eq!(read(), (&a * &b) % &p);
+        assert_eq!(read(), (&a * inverse_mod(&b, &p)) % &p);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_secp256k1_neg_non_canonical() {
+        let field = Secp256k1Field::Base;
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U256Variable>();
+        let negation = builder.secp256k1_neg(field, a);
+        builder.write(negation);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<U256Variable>(biguint_to_u256(&(field.modulus() + 1u32)));
+        circuit.prove(&input);
+    }
+}
@@ -0,0 +1,13 @@
+//! Arithmetic over secp256k1, with verification of ECDSA signatures and of MuSig2 aggregated
+//! Schnorr signatures, and BIP-32 derivation of public keys.
+//!
+//! Elements of both fields are `U256Variable`s reduced modulo the field, multiplied with the
+//! biguint gadgets and reduced by a constrained division, since the curve is only needed for a few
+//! scalar multiplications per signature or derivation.
+
+pub mod bip32;
+pub mod curve;
+pub mod ecdsa;
+pub mod field;
+pub mod musig;
+pub mod native;
@@ -0,0 +1,222 @@
+//! MuSig2 key aggregation (BIP-327) and BIP-340 Schnorr verification, so that a circuit can check
+//! the attestation of a committee with a single aggregated signature.
+//!
+//! Keys are given in the order of the committee, since the aggregation coefficients depend on it;
+//! committees sorting their keys with `KeySort` must be passed in sorted order.
+
+use ethers::types::U256;
+use itertools::Itertools;
+
+use super::curve::Secp256k1AffineVariable;
+use super::field::Secp256k1Field;
+use super::native::{secp256k1_base_modulus, secp256k1_scalar_modulus, Secp256k1Point};
+use crate::prelude::*;
+use crate::utils::hash::sha256;
+
+const SCALAR: Secp256k1Field = Secp256k1Field::Scalar;
+
+/// A BIP-340 signature: the `x` coordinate of the nonce point and the scalar `s`.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(SchnorrSignature)]
+pub struct SchnorrSignatureVariable {
+    pub r: U256Variable,
+    pub s: U256Variable,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// The tagged hash of BIP-340, `sha256(sha256(tag) || sha256(tag) || message)`.
+    pub fn secp256k1_tagged_hash(
+        &mut self,
+        tag: &str,
+        message: &[ByteVariable],
+    ) -> Bytes32Variable {
+        let tag_hash = sha256(tag.as_bytes());
+        let mut input = tag_hash
+            .iter()
+            .chain(tag_hash.iter())
+            .map(|byte| self.constant::<ByteVariable>(*byte))
+            .collect_vec();
+        input.extend_from_slice(message);
+        self.curta_sha256(&input)
+    }
+
+    /// The MuSig2 key aggregation coefficients of the keys, in order.
+    pub fn musig2_key_agg_coefficients(
+        &mut self,
+        keys: &[Secp256k1AffineVariable],
+    ) -> Vec<U256Variable> {
+        assert!(!keys.is_empty(), "no keys to aggregate");
+        let encoded = keys
+            .iter()
+            .map(|key| self.secp256k1_compress(key))
+            .collect::<Vec<_>>();
+        let list_hash = self.secp256k1_tagged_hash("KeyAgg list", &encoded.concat());
+
+        // The second key is the first one differing from the first key, if any, and its
+        // coefficient is one.
+        let mut second_key = keys[0].clone();
+        let mut has_second_key = self._false();
+        for key in keys[1..].iter().rev() {
+            let is_first_key = self.is_equal(key.clone(), keys[0].clone());
+            let differs = self.not(is_first_key);
+            second_key = self.select(differs, key.clone(), second_key);
+            has_second_key = self.or(has_second_key, differs);
+        }
+
+        let one = self.constant::<U256Variable>(U256::one());
+        let mut coefficients = Vec::with_capacity(keys.len());
+        for (key, encoded_key) in keys.iter().zip(encoded) {
+            let mut input = list_hash.as_bytes().to_vec();
+            input.extend(encoded_key);
+            let hash = self.secp256k1_tagged_hash("KeyAgg coefficient", &input);
+            let hash = hash.as_u256(self);
+            let coefficient = self.secp256k1_from_u256(SCALAR, hash);
+            let is_second_key = self.is_equal(key.clone(), second_key.clone());
+            let is_second_key = self.and(is_second_key, has_second_key);
+            coefficients.push(self.select(is_second_key, one, coefficient));
+        }
+        coefficients
+    }
+
+    /// The MuSig2 aggregate of the keys, `sum_i a_i * P_i`, checking that each key is on the
+    /// curve.
+    pub fn musig2_aggregate_keys(
+        &mut self,
+        keys: &[Secp256k1AffineVariable],
+    ) -> Secp256k1AffineVariable {
+        for key in keys.iter() {
+            self.secp256k1_assert_on_curve(key);
+        }
+        let coefficients = self.musig2_key_agg_coefficients(keys);
+        self.secp256k1_msm(keys, &coefficients)
+    }
+
+    /// Checks a BIP-340 signature of `message` under the x-only key of `key`, that is the point
+    /// with the same `x` coordinate and an even `y` coordinate.
+    pub fn schnorr_verify(
+        &mut self,
+        key: &Secp256k1AffineVariable,
+        message: Bytes32Variable,
+        signature: &SchnorrSignatureVariable,
+    ) {
+        let true_variable = self._true();
+        let p = self.secp256k1_constant(&secp256k1_base_modulus());
+        let r_is_reduced = self.lt(signature.r, p);
+        self.assert_is_equal(r_is_reduced, true_variable);
+        let n = self.secp256k1_constant(&secp256k1_scalar_modulus());
+        let s_is_reduced = self.lt(signature.s, n);
+        self.assert_is_equal(s_is_reduced, true_variable);
+
+        let has_even_y = self.secp256k1_has_even_y(key);
+        let neg_key = self.secp256k1_point_neg(key);
+        let key = self.select(has_even_y, key.clone(), neg_key);
+
+        let mut input = signature.r.encode(self);
+        input.extend(key.x.encode(self));
+        input.extend(message.as_bytes());
+        let challenge = self.secp256k1_tagged_hash("BIP0340/challenge", &input);
+        let challenge = challenge.as_u256(self);
+        let challenge = self.secp256k1_from_u256(SCALAR, challenge);
+        let neg_challenge = self.secp256k1_neg(SCALAR, challenge);
+
+        // R = s * G - e * P must have an even `y` coordinate and `x` coordinate `r`.
+        let generator = self.secp256k1_point_constant(&Secp256k1Point::generator());
+        let nonce = self.secp256k1_msm(&[generator, key], &[signature.s, neg_challenge]);
+        self.assert_is_equal(nonce.x, signature.r);
+        let has_even_y = self.secp256k1_has_even_y(&nonce);
+        self.assert_is_equal(has_even_y, true_variable);
+    }
+
+    /// Checks a MuSig2 signature of `message` by the committee with the given keys.
+    pub fn musig2_verify(
+        &mut self,
+        keys: &[Secp256k1AffineVariable],
+        message: Bytes32Variable,
+        signature: &SchnorrSignatureVariable,
+    ) {
+        let aggregate_key = self.musig2_aggregate_keys(keys);
+        self.schnorr_verify(&aggregate_key, message, signature);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+    use num::BigUint;
+
+    use super::*;
+    use crate::frontend::ecc::secp256k1::field::biguint_to_u256;
+    use crate::frontend::ecc::secp256k1::native::{
+        musig2_aggregate_keys, musig2_key_agg_coefficients, schnorr_challenge, schnorr_sign,
+    };
+
+    fn committee() -> (Vec<BigUint>, Vec<Secp256k1Point>) {
+        let secrets = [0x1111u64, 0x2222, 0x1111]
+            .iter()
+            .map(|secret| BigUint::from(*secret) << 200)
+            .collect::<Vec<_>>();
+        let keys = secrets
+            .iter()
+            .map(|secret| Secp256k1Point::generator().mul(secret))
+            .collect();
+        (secrets, keys)
+    }
+
+    #[test]
+    fn test_musig2_native() {
+        let (secrets, keys) = committee();
+        let coefficients = musig2_key_agg_coefficients(&keys);
+        assert_eq!(coefficients[1], BigUint::from(1u32));
+        assert_eq!(coefficients[0], coefficients[2]);
+
+        let n = secp256k1_scalar_modulus();
+        let secret = secrets
+            .iter()
+            .zip(coefficients.iter())
+            .fold(BigUint::from(0u32), |sum, (x, a)| (sum + x * a) % &n);
+        let key = musig2_aggregate_keys(&keys);
+        assert_eq!(Secp256k1Point::generator().mul(&secret), key);
+
+        let message = [7u8; 32];
+        let (r, s) = schnorr_sign(&secret, &BigUint::from(0xabcdefu32), &message);
+        let key = if key.has_even_y() { key } else { key.negate() };
+        let e = schnorr_challenge(&r, &key.coordinates().0, &message);
+        let nonce = Secp256k1Point::generator().mul(&s).add(&key.mul(&(&n - e)));
+        assert_eq!(nonce.coordinates().0, r);
+        assert!(nonce.has_even_y());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_musig2_verify() {
+        let mut builder = DefaultBuilder::new();
+        let keys = (0..3)
+            .map(|_| builder.read::<Secp256k1AffineVariable>())
+            .collect::<Vec<_>>();
+        let message = builder.read::<Bytes32Variable>();
+        let signature = builder.read::<SchnorrSignatureVariable>();
+        builder.musig2_verify(&keys, message, &signature);
+        let circuit = builder.build();
+
+        let (secrets, keys) = committee();
+        let n = secp256k1_scalar_modulus();
+        let secret = secrets
+            .iter()
+            .zip(musig2_key_agg_coefficients(&keys))
+            .fold(BigUint::from(0u32), |sum, (x, a)| (sum + x * a) % &n);
+        let message = [7u8; 32];
+        let (r, s) = schnorr_sign(&secret, &BigUint::from(0xabcdefu32), &message);
+
+        let mut input = circuit.input();
+        for key in keys {
+            input.write::<Secp256k1AffineVariable>(key.into());
+        }
+        input.write::<Bytes32Variable>(H256(message));
+        input.write::<SchnorrSignatureVariable>(SchnorrSignature {
+            r: biguint_to_u256(&r),
+            s: biguint_to_u256(&s),
+        });
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
@@ -0,0 +1,252 @@
+//! Native arithmetic over secp256k1, used for the constants and the hints of the circuit gadgets
+//! and to produce test vectors.
+
+use num::{One, Zero};
+use num_bigint::BigUint;
+
+pub use crate::frontend::ecc::nonnative::inverse_mod;
+use crate::utils::hash::sha256;
+
+/// The modulus of the base field.
+pub fn secp256k1_base_modulus() -> BigUint {
+    BigUint::parse_bytes(
+        b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+        16,
+    )
+    .unwrap()
+}
+
+/// The order of the group, which is the modulus of the scalar field.
+pub fn secp256k1_scalar_modulus() -> BigUint {
+    BigUint::parse_bytes(
+        b"fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        16,
+    )
+    .unwrap()
+}
+
+/// The tagged hash of BIP-340, `sha256(sha256(tag) || sha256(tag) || message)`.
+pub fn tagged_hash(tag: &str, message: &[u8]) -> [u8; 32] {
+    let tag_hash = sha256(tag.as_bytes());
+    sha256(&[&tag_hash[..], &tag_hash[..], message].concat())
+}
+
+/// An affine point of secp256k1, or the point at infinity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Secp256k1Point {
+    Infinity,
+    Affine { x: BigUint, y: BigUint },
+}
+
+impl Secp256k1Point {
+    pub fn generator() -> Self {
+        Self::Affine {
+            x: BigUint::parse_bytes(
+                b"79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+                16,
+            )
+            .unwrap(),
+            y: BigUint::parse_bytes(
+                b"483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+                16,
+            )
+            .unwrap(),
+        }
+    }
+
+    pub fn coordinates(&self) -> (BigUint, BigUint) {
+        match self {
+            Self::Infinity => panic!("the point at infinity has no coordinates"),
+            Self::Affine { x, y } => (x.clone(), y.clone()),
+        }
+    }
+
+    pub fn has_even_y(&self) -> bool {
+        !self.coordinates().1.bit(0)
+    }
+
+    pub fn negate(&self) -> Self {
+        match self {
+            Self::Infinity => Self::Infinity,
+            Self::Affine { x, y } => Self::Affine {
+                x: x.clone(),
+                y: (secp256k1_base_modulus() - y) % secp256k1_base_modulus(),
+            },
+        }
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let p = secp256k1_base_modulus();
+        let (x1, y1, x2, y2) = match (self, other) {
+            (Self::Infinity, _) => return other.clone(),
+            (_, Self::Infinity) => return self.clone(),
+            (Self::Affine { x: x1, y: y1 }, Self::Affine { x: x2, y: y2 }) => (x1, y1, x2, y2),
+        };
+        let slope = if x1 == x2 {
+            if (y1 + y2) % &p == BigUint::zero() {
+                return Self::Infinity;
+            }
+            BigUint::from(3u32) * x1 * x1 * inverse_mod(&(BigUint::from(2u32) * y1), &p) % &p
+        } else {
+            (y2 + &p - y1) * inverse_mod(&(x2 + &p - x1), &p) % &p
+        };
+        let x = (&slope * &slope + BigUint::from(2u32) * &p - x1 - x2) % &p;
+        let y = (slope * (x1 + &p - &x) + &p - y1) % &p;
+        Self::Affine { x, y }
+    }
+
+    pub fn mul(&self, scalar: &BigUint) -> Self {
+        let mut result = Self::Infinity;
+        for i in (0..scalar.bits()).rev() {
+            result = result.add(&result);
+            if scalar.bit(i) {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+
+    /// The 33-byte SEC1 compressed encoding of the point.
+    pub fn compressed(&self) -> Vec<u8> {
+        let (x, _) = self.coordinates();
+        let mut bytes = vec![if self.has_even_y() { 2 } else { 3 }];
+        bytes.extend(to_be_bytes32(&x));
+        bytes
+    }
+
+    /// Decodes a 33-byte SEC1 compressed point.
+    pub fn from_compressed(bytes: &[u8]) -> Self {
+        assert!(bytes.len() == 33 && (bytes[0] == 2 || bytes[0] == 3));
+        let p = secp256k1_base_modulus();
+        let x = BigUint::from_bytes_be(&bytes[1..]);
+        let rhs = (&x * &x * &x + 7u32) % &p;
+        // Square roots are `rhs^((p + 1) / 4)` since `p = 3 mod 4`.
+        let mut y = rhs.modpow(&((&p + 1u32) >> 2), &p);
+        assert_eq!(&y * &y % &p, rhs, "not a point of the curve");
+        if y.bit(0) != (bytes[0] == 3) {
+            y = &p - y;
+        }
+        Self::Affine { x, y }
+    }
+}
+
+pub fn to_be_bytes32(value: &BigUint) -> [u8; 32] {
+    let bytes = value.to_bytes_be();
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    padded
+}
+
+/// The MuSig2 key aggregation coefficients of BIP-327 for the keys in the given order.
+pub fn musig2_key_agg_coefficients(keys: &[Secp256k1Point]) -> Vec<BigUint> {
+    let n = secp256k1_scalar_modulus();
+    let encoded = keys.iter().map(|key| key.compressed()).collect::<Vec<_>>();
+    let list_hash = tagged_hash("KeyAgg list", &encoded.concat());
+    let second_key = encoded.iter().find(|key| **key != encoded[0]);
+    encoded
+        .iter()
+        .map(|key| {
+            if Some(key) == second_key {
+                BigUint::one()
+            } else {
+                let hash = tagged_hash("KeyAgg coefficient", &[&list_hash[..], key].concat());
+                BigUint::from_bytes_be(&hash) % &n
+            }
+        })
+        .collect()
+}
+
+/// The MuSig2 aggregate of the given keys.
+pub fn musig2_aggregate_keys(keys: &[Secp256k1Point]) -> Secp256k1Point {
+    musig2_key_agg_coefficients(keys)
+        .iter()
+        .zip(keys)
+        .fold(Secp256k1Point::Infinity, |sum, (coefficient, key)| {
+            sum.add(&key.mul(coefficient))
+        })
+}
+
+/// The BIP-340 challenge of a signature with nonce `r` under a key with x coordinate `key_x`.
+pub fn schnorr_challenge(r: &BigUint, key_x: &BigUint, message: &[u8; 32]) -> BigUint {
+    let hash = tagged_hash(
+        "BIP0340/challenge",
+        &[
+            &to_be_bytes32(r)[..],
+            &to_be_bytes32(key_x)[..],
+            &message[..],
+        ]
+        .concat(),
+    );
+    BigUint::from_bytes_be(&hash) % secp256k1_scalar_modulus()
+}
+
+/// Signs a message under the key `secret * G` as in BIP-340, returning `(r, s)`.
+///
+/// The signature of a MuSig2 session is indistinguishable from the one made with the aggregated
+/// secret `sum_i a_i * x_i`, so this is enough to produce test vectors for the aggregated keys.
+pub fn schnorr_sign(secret: &BigUint, nonce: &BigUint, message: &[u8; 32]) -> (BigUint, BigUint) {
+    let n = secp256k1_scalar_modulus();
+    let key = Secp256k1Point::generator().mul(secret);
+    let secret = if key.has_even_y() {
+        secret % &n
+    } else {
+        &n - secret % &n
+    };
+    let nonce_point = Secp256k1Point::generator().mul(nonce);
+    let nonce = if nonce_point.has_even_y() {
+        nonce % &n
+    } else {
+        &n - nonce % &n
+    };
+    let r = nonce_point.coordinates().0;
+    let e = schnorr_challenge(&r, &key.coordinates().0, message);
+    (r, (nonce + e * secret) % n)
+}
+
+/// Signs a 32-byte message hash under the key `secret * G` with ECDSA, returning `(r, s)`.
+pub fn ecdsa_sign(
+    secret: &BigUint,
+    nonce: &BigUint,
+    message_hash: &[u8; 32],
+) -> (BigUint, BigUint) {
+    let n = secp256k1_scalar_modulus();
+    let z = BigUint::from_bytes_be(message_hash) % &n;
+    let r = Secp256k1Point::generator().mul(nonce).coordinates().0 % &n;
+    let s = inverse_mod(nonce, &n) * (z + &r * secret) % &n;
+    (r, s)
+}
+
+/// Recovers the key of an ECDSA signature as the `ecrecover` precompile of the EVM does, where
+/// `y_is_odd` is the recovery id. Returns `None` for the inputs the precompile rejects.
+pub fn ecdsa_recover(
+    message_hash: &[u8; 32],
+    y_is_odd: bool,
+    r: &BigUint,
+    s: &BigUint,
+) -> Option<Secp256k1Point> {
+    let n = secp256k1_scalar_modulus();
+    if r.is_zero() || s.is_zero() || r >= &n || s >= &n {
+        return None;
+    }
+    let p = secp256k1_base_modulus();
+    let rhs = (r * r * r + 7u32) % &p;
+    let mut y = rhs.modpow(&((&p + 1u32) >> 2), &p);
+    if &y * &y % &p != rhs {
+        return None;
+    }
+    if y.bit(0) != y_is_odd {
+        y = &p - y;
+    }
+    let nonce = Secp256k1Point::Affine { x: r.clone(), y };
+
+    // Q = r^-1 * (s * R - z * G)
+    let z = BigUint::from_bytes_be(message_hash) % &n;
+    let r_inverse = inverse_mod(r, &n);
+    let key = nonce
+        .mul(&(s * &r_inverse % &n))
+        .add(&Secp256k1Point::generator().mul(&((&n - z) * &r_inverse % &n)));
+    match key {
+        Secp256k1Point::Infinity => None,
+        key => Some(key),
+    }
+}
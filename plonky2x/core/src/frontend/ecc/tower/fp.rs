@@ -0,0 +1,517 @@
+//! Non-native arithmetic over the base field.
+//!
+//! An element is an `FpVariable`, whose limbs of 16 bits are range checked and whose value is less
+//! than the modulus. Sums and products of elements are first accumulated as an `FpPoly` with limbs
+//! that may be negative or exceed 16 bits, and reduced once: the quotient and remainder modulo `p`
+//! are witnessed by a hint and the identity `value = q * p + r` is checked limb by limb,
+//! propagating range checked carries.
+
+use core::marker::PhantomData;
+
+use itertools::Itertools;
+use num::{Integer, One, Zero};
+use num_bigint::BigUint;
+use plonky2::field::types::{Field64, PrimeField64};
+use serde::{Deserialize, Serialize};
+
+use super::{inverse_or_zero, TowerField};
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::uint::num::u32::gadgets::multiple_comparison::list_lte_circuit;
+use crate::prelude::*;
+
+const LIMB_BITS: usize = 16;
+
+/// The largest magnitude of a limb of an `FpPoly` that can be reduced, in bits.
+const MAX_LIMB_BITS: u32 = 46;
+
+/// Carries are range checked to `[-2^CARRY_BITS, 2^CARRY_BITS)`, which holds for limbs of at most
+/// `MAX_LIMB_BITS` bits. Together with the bound on the limbs, this keeps every limb identity far
+/// from wrapping around the Goldilocks modulus.
+const CARRY_BITS: usize = 32;
+
+/// Splits an integer into little-endian limbs of `LIMB_BITS` bits.
+fn to_limbs(value: &BigUint) -> Vec<u64> {
+    value
+        .iter_u32_digits()
+        .flat_map(|digit| [(digit & 0xffff) as u64, (digit >> 16) as u64])
+        .collect()
+}
+
+/// Whether the integer with the given limbs of `LIMB_BITS` bits is at most `bound`.
+fn limbs_lte<L: PlonkParameters<D>, const D: usize>(
+    builder: &mut CircuitBuilder<L, D>,
+    limbs: &[Variable],
+    bound: &BigUint,
+) -> BoolVariable {
+    let mut bound_limbs = to_limbs(bound);
+    bound_limbs.resize(limbs.len(), 0);
+    let bound_limbs = bound_limbs
+        .into_iter()
+        .map(|limb| builder.api.constant(L::Field::from_canonical_u64(limb)))
+        .collect_vec();
+    let lte = list_lte_circuit(
+        &mut builder.api,
+        limbs.iter().map(|limb| limb.0).collect(),
+        bound_limbs,
+        LIMB_BITS,
+    );
+    lte.into()
+}
+
+/// An element of the base field `P`.
+#[derive(Debug, Clone)]
+pub struct FpVariable<P: TowerField> {
+    limbs: Vec<Variable>,
+    _marker: PhantomData<P>,
+}
+
+impl<P: TowerField> CircuitVariable for FpVariable<P> {
+    type ValueType<F: RichField> = BigUint;
+
+    fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Self::from_variables_unsafe(
+            &(0..P::NB_LIMBS)
+                .map(|_| builder.init_unsafe())
+                .collect_vec(),
+        )
+    }
+
+    fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        for limb in self.limbs.iter() {
+            builder.api.range_check(limb.0, LIMB_BITS);
+        }
+        let is_reduced = limbs_lte(builder, &self.limbs, &(P::modulus() - 1u32));
+        let true_variable = builder._true();
+        builder.assert_is_equal(is_reduced, true_variable);
+    }
+
+    fn nb_elements() -> usize {
+        P::NB_LIMBS
+    }
+
+    fn elements<F: RichField>(value: Self::ValueType<F>) -> Vec<F> {
+        let mut limbs = to_limbs(&value);
+        assert!(
+            limbs.len() <= P::NB_LIMBS,
+            "value does not fit in the limbs"
+        );
+        limbs.resize(P::NB_LIMBS, 0);
+        limbs.into_iter().map(F::from_canonical_u64).collect()
+    }
+
+    fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F> {
+        elements.iter().rev().fold(BigUint::zero(), |acc, limb| {
+            (acc << LIMB_BITS) + limb.as_canonical_u64()
+        })
+    }
+
+    fn variables(&self) -> Vec<Variable> {
+        self.limbs.clone()
+    }
+
+    fn from_variables_unsafe(variables: &[Variable]) -> Self {
+        assert_eq!(variables.len(), P::NB_LIMBS);
+        Self {
+            limbs: variables.to_vec(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// An integer `sum_i limbs[i] * 2^(16 * i)`, obtained by adding and multiplying field elements
+/// without reducing. Limbs are field elements standing for small signed integers.
+#[derive(Debug, Clone)]
+pub struct FpPoly<P: TowerField> {
+    limbs: Vec<Target>,
+    /// A bound on the magnitude of each limb.
+    limb_bound: u128,
+    /// Bounds `-min <= value <= max` on the integer.
+    min: BigUint,
+    max: BigUint,
+    _marker: PhantomData<P>,
+}
+
+impl<P: TowerField> FpPoly<P> {
+    pub fn zero() -> Self {
+        Self {
+            limbs: Vec::new(),
+            limb_bound: 0,
+            min: BigUint::zero(),
+            max: BigUint::zero(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    pub fn fp_constant<P: TowerField>(&mut self, value: &P) -> FpVariable<P> {
+        self.constant::<FpVariable<P>>(value.as_biguint().clone())
+    }
+
+    pub fn fp_to_poly<P: TowerField>(&mut self, a: &FpVariable<P>) -> FpPoly<P> {
+        FpPoly {
+            limbs: a.limbs.iter().map(|v| v.0).collect(),
+            limb_bound: (1 << LIMB_BITS) - 1,
+            min: BigUint::zero(),
+            max: P::modulus() - 1u32,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The product of two elements, as an unreduced integer.
+    pub fn fp_poly_mul<P: TowerField>(
+        &mut self,
+        a: &FpVariable<P>,
+        b: &FpVariable<P>,
+    ) -> FpPoly<P> {
+        let zero = self.api.zero();
+        let mut limbs = vec![zero; 2 * P::NB_LIMBS - 1];
+        for (i, x) in a.limbs.iter().enumerate() {
+            for (j, y) in b.limbs.iter().enumerate() {
+                limbs[i + j] =
+                    self.api
+                        .arithmetic(L::Field::ONE, L::Field::ONE, x.0, y.0, limbs[i + j]);
+            }
+        }
+        let p = P::modulus();
+        FpPoly {
+            limbs,
+            limb_bound: (P::NB_LIMBS as u128) * ((1 << LIMB_BITS) - 1) * ((1 << LIMB_BITS) - 1),
+            min: BigUint::zero(),
+            max: (&p - 1u32) * (&p - 1u32),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn fp_poly_add<P: TowerField>(&mut self, a: &FpPoly<P>, b: &FpPoly<P>) -> FpPoly<P> {
+        let zero = self.api.zero();
+        let limbs = (0..a.limbs.len().max(b.limbs.len()))
+            .map(|i| {
+                let x = a.limbs.get(i).copied().unwrap_or(zero);
+                let y = b.limbs.get(i).copied().unwrap_or(zero);
+                self.api.add(x, y)
+            })
+            .collect();
+        FpPoly {
+            limbs,
+            limb_bound: a.limb_bound + b.limb_bound,
+            min: &a.min + &b.min,
+            max: &a.max + &b.max,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn fp_poly_sub<P: TowerField>(&mut self, a: &FpPoly<P>, b: &FpPoly<P>) -> FpPoly<P> {
+        let zero = self.api.zero();
+        let limbs = (0..a.limbs.len().max(b.limbs.len()))
+            .map(|i| {
+                let x = a.limbs.get(i).copied().unwrap_or(zero);
+                let y = b.limbs.get(i).copied().unwrap_or(zero);
+                self.api.sub(x, y)
+            })
+            .collect();
+        FpPoly {
+            limbs,
+            limb_bound: a.limb_bound + b.limb_bound,
+            min: &a.min + &b.max,
+            max: &a.max + &b.min,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Multiplies an unreduced integer by a small constant.
+    pub fn fp_poly_scale<P: TowerField>(&mut self, a: &FpPoly<P>, c: u64) -> FpPoly<P> {
+        let limbs = a
+            .limbs
+            .iter()
+            .map(|limb| self.api.mul_const(L::Field::from_canonical_u64(c), *limb))
+            .collect();
+        FpPoly {
+            limbs,
+            limb_bound: a.limb_bound * c as u128,
+            min: &a.min * c,
+            max: &a.max * c,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Reduces an integer modulo `p`.
+    pub fn fp_reduce<P: TowerField>(&mut self, a: &FpPoly<P>) -> FpVariable<P> {
+        let p = P::modulus();
+
+        // Shift the integer by a multiple of `p` so that it is non-negative.
+        let offset = a.min.div_ceil(&p) * &p;
+        let max = &a.max + &offset;
+        let offset_limbs = to_limbs(&offset);
+        let zero = self.api.zero();
+        let mut limbs = a.limbs.clone();
+        limbs.resize(limbs.len().max(offset_limbs.len()), zero);
+        for (limb, c) in limbs.iter_mut().zip(offset_limbs.iter()) {
+            *limb = self.api.add_const(*limb, L::Field::from_canonical_u64(*c));
+        }
+        let limb_bound = a.limb_bound + (1 << LIMB_BITS);
+        assert!(
+            limb_bound < 1 << MAX_LIMB_BITS,
+            "limbs of {} bits are too large to be reduced",
+            128 - limb_bound.leading_zeros()
+        );
+
+        let nb_quotient_limbs = ((&max / &p).bits() as usize).div_ceil(LIMB_BITS).max(1);
+        let mut input_stream = VariableStream::new();
+        input_stream.write_slice(&limbs.iter().map(|t| Variable(*t)).collect_vec());
+        let hint = FpReduceHint::<P> {
+            nb_limbs: limbs.len(),
+            nb_quotient_limbs,
+            _marker: PhantomData,
+        };
+        let output_stream = self.hint(input_stream, hint);
+        let quotient = output_stream.read_exact(self, nb_quotient_limbs);
+        for q in quotient.iter() {
+            self.api.range_check(q.0, LIMB_BITS);
+        }
+        let remainder = output_stream.read::<FpVariable<P>>(self);
+
+        // Check that `value - q * p - r = 0`, limb by limb.
+        let nb_diff_limbs = limbs.len().max(nb_quotient_limbs + P::NB_LIMBS - 1);
+        let mut diff = limbs;
+        diff.resize(nb_diff_limbs, zero);
+        for (i, q) in quotient.iter().enumerate() {
+            for (j, c) in to_limbs(&p).into_iter().enumerate() {
+                let c = -L::Field::from_canonical_u64(c);
+                diff[i + j] = self.api.mul_const_add(c, q.0, diff[i + j]);
+            }
+        }
+        for (i, r) in remainder.limbs.iter().enumerate() {
+            diff[i] = self.api.sub(diff[i], r.0);
+        }
+
+        let limb_base_inverse = L::Field::from_canonical_u64(1 << LIMB_BITS).inverse();
+        let carry_offset = L::Field::from_canonical_u64(1 << CARRY_BITS);
+        let mut carry = zero;
+        for (i, limb) in diff.into_iter().enumerate() {
+            let sum = self.api.add(limb, carry);
+            if i == nb_diff_limbs - 1 {
+                self.api.assert_zero(sum);
+            } else {
+                carry = self.api.mul_const(limb_base_inverse, sum);
+                let shifted = self.api.add_const(carry, carry_offset);
+                self.api.range_check(shifted, CARRY_BITS + 1);
+            }
+        }
+
+        remainder
+    }
+
+    pub fn fp_add<P: TowerField>(&mut self, a: &FpVariable<P>, b: &FpVariable<P>) -> FpVariable<P> {
+        let a = self.fp_to_poly(a);
+        let b = self.fp_to_poly(b);
+        let sum = self.fp_poly_add(&a, &b);
+        self.fp_reduce(&sum)
+    }
+
+    pub fn fp_sub<P: TowerField>(&mut self, a: &FpVariable<P>, b: &FpVariable<P>) -> FpVariable<P> {
+        let a = self.fp_to_poly(a);
+        let b = self.fp_to_poly(b);
+        let difference = self.fp_poly_sub(&a, &b);
+        self.fp_reduce(&difference)
+    }
+
+    pub fn fp_neg<P: TowerField>(&mut self, a: &FpVariable<P>) -> FpVariable<P> {
+        let a = self.fp_to_poly(a);
+        let negation = self.fp_poly_sub(&FpPoly::zero(), &a);
+        self.fp_reduce(&negation)
+    }
+
+    pub fn fp_mul<P: TowerField>(&mut self, a: &FpVariable<P>, b: &FpVariable<P>) -> FpVariable<P> {
+        let product = self.fp_poly_mul(a, b);
+        self.fp_reduce(&product)
+    }
+
+    /// The inverse of an element, which must be non-zero for the proof to be valid.
+    pub fn fp_inverse<P: TowerField>(&mut self, a: &FpVariable<P>) -> FpVariable<P> {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(a);
+        let output_stream = self.hint(input_stream, FpInverseHint::<P>(PhantomData));
+        let inverse = output_stream.read::<FpVariable<P>>(self);
+
+        let product = self.fp_mul(a, &inverse);
+        let one = self.fp_constant(&P::one());
+        self.assert_is_equal(product, one);
+        inverse
+    }
+
+    pub fn fp_div<P: TowerField>(&mut self, a: &FpVariable<P>, b: &FpVariable<P>) -> FpVariable<P> {
+        let inverse = self.fp_inverse(b);
+        self.fp_mul(a, &inverse)
+    }
+
+    pub fn fp_is_zero<P: TowerField>(&mut self, a: &FpVariable<P>) -> BoolVariable {
+        let zero = self.fp_constant(&P::zero());
+        self.is_equal(a.clone(), zero)
+    }
+
+    /// The parity of an element, which is its sign in RFC 9380.
+    pub fn fp_is_odd<P: TowerField>(&mut self, a: &FpVariable<P>) -> BoolVariable {
+        let bits = self.api.split_le(a.limbs[0].0, LIMB_BITS);
+        bits[0].into()
+    }
+
+    /// Whether the element is greater than its negation, which gives the sign of a compressed
+    /// point.
+    pub fn fp_is_lexicographically_largest<P: TowerField>(
+        &mut self,
+        a: &FpVariable<P>,
+    ) -> BoolVariable {
+        let half = (P::modulus() - 1u32) / 2u32;
+        let is_at_most_half = limbs_lte(self, &a.limbs, &half);
+        self.not(is_at_most_half)
+    }
+
+    /// The element whose big-endian encoding of `2 * NB_LIMBS` bytes is `bytes`, which must be
+    /// less than the modulus for the proof to be valid.
+    pub fn fp_from_be_bytes<P: TowerField>(&mut self, bytes: &[ByteVariable]) -> FpVariable<P> {
+        assert_eq!(bytes.len(), 2 * P::NB_LIMBS);
+        let limbs = self.be_bytes_to_limbs(bytes);
+        FpVariable::from_variables(self, &limbs)
+    }
+
+    /// Reduces the integer whose big-endian encoding is `bytes` modulo `p`.
+    pub fn fp_reduce_be_bytes<P: TowerField>(&mut self, bytes: &[ByteVariable]) -> FpVariable<P> {
+        let limbs = self.be_bytes_to_limbs(bytes);
+        let poly = FpPoly {
+            limbs: limbs.iter().map(|v| v.0).collect(),
+            limb_bound: (1 << LIMB_BITS) - 1,
+            min: BigUint::zero(),
+            max: (BigUint::one() << (8 * bytes.len())) - 1u32,
+            _marker: PhantomData,
+        };
+        self.fp_reduce(&poly)
+    }
+
+    /// The little-endian limbs of `LIMB_BITS` bits of a big-endian encoding of an even length.
+    fn be_bytes_to_limbs(&mut self, bytes: &[ByteVariable]) -> Vec<Variable> {
+        assert!(bytes.len() % 2 == 0);
+        let base = L::Field::from_canonical_u64(1 << 8);
+        bytes
+            .chunks(2)
+            .rev()
+            .map(|pair| {
+                let high = pair[0].to_variable(self);
+                let low = pair[1].to_variable(self);
+                Variable(self.api.mul_const_add(base, high.0, low.0))
+            })
+            .collect()
+    }
+}
+
+/// Computes the quotient and remainder modulo `p` of an integer with signed limbs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpReduceHint<P: TowerField> {
+    nb_limbs: usize,
+    nb_quotient_limbs: usize,
+    _marker: PhantomData<P>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize, P: TowerField> Hint<L, D> for FpReduceHint<P> {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let limbs = input_stream.read_exact(self.nb_limbs).to_vec();
+        let mut positive = BigUint::zero();
+        let mut negative = BigUint::zero();
+        for (i, limb) in limbs.iter().enumerate() {
+            let limb = limb.as_canonical_u64();
+            if limb < L::Field::ORDER / 2 {
+                positive += BigUint::from(limb) << (LIMB_BITS * i);
+            } else {
+                negative += BigUint::from(L::Field::ORDER - limb) << (LIMB_BITS * i);
+            }
+        }
+        assert!(positive >= negative, "integer to reduce is negative");
+
+        let (quotient, remainder) = (positive - negative).div_rem(&P::modulus());
+        let mut quotient_limbs = to_limbs(&quotient);
+        assert!(quotient_limbs.len() <= self.nb_quotient_limbs);
+        quotient_limbs.resize(self.nb_quotient_limbs, 0);
+        output_stream.write_slice(
+            &quotient_limbs
+                .into_iter()
+                .map(L::Field::from_canonical_u64)
+                .collect_vec(),
+        );
+        output_stream.write_value::<FpVariable<P>>(remainder);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpInverseHint<P: TowerField>(PhantomData<P>);
+
+impl<L: PlonkParameters<D>, const D: usize, P: TowerField> Hint<L, D> for FpInverseHint<P> {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let a = P::from_biguint(input_stream.read_value::<FpVariable<P>>());
+        let inverse = inverse_or_zero(a);
+        output_stream.write_value::<FpVariable<P>>(inverse.as_biguint().clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::ecc::bls12_381::native::Fp;
+    use crate::frontend::ecc::bn254::native::Fq;
+
+    fn test_fp_arithmetic<P: TowerField>() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<FpVariable<P>>();
+        let b = builder.read::<FpVariable<P>>();
+        let sum = builder.fp_add(&a, &b);
+        builder.write(sum);
+        let difference = builder.fp_sub(&a, &b);
+        builder.write(difference);
+        let negation = builder.fp_neg(&a);
+        builder.write(negation);
+        let product = builder.fp_mul(&a, &b);
+        builder.write(product);
+        let quotient = builder.fp_div(&a, &b);
+        builder.write(quotient);
+        let is_largest = builder.fp_is_lexicographically_largest(&a);
+        builder.write(is_largest);
+        let circuit = builder.build();
+
+        let p = P::modulus();
+        let a = P::from_biguint(&p - 12345u32);
+        let b = P::from_biguint(BigUint::one() << (p.bits() - 2));
+        let mut input = circuit.input();
+        input.write::<FpVariable<P>>(a.as_biguint().clone());
+        input.write::<FpVariable<P>>(b.as_biguint().clone());
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let expected = [
+            a.clone() + b.clone(),
+            a.clone() - b.clone(),
+            -a.clone(),
+            a.clone() * b.clone(),
+            a * b.inverse(),
+        ];
+        for value in expected {
+            assert_eq!(&output.read::<FpVariable<P>>(), value.as_biguint());
+        }
+        assert!(output.read::<BoolVariable>());
+    }
+
+    #[test]
+    fn test_fp_arithmetic_bn254() {
+        test_fp_arithmetic::<Fq>();
+    }
+
+    #[test]
+    fn test_fp_arithmetic_bls12_381() {
+        test_fp_arithmetic::<Fp>();
+    }
+}
@@ -0,0 +1,265 @@
+//! Arithmetic over `Fp12 = Fp2[w]/(w^6 - xi)`, the target group of the pairing.
+
+use core::marker::PhantomData;
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::fp::FpVariable;
+use super::fp2::{Fp2Poly, Fp2Value, Fp2Variable};
+use super::{inverse_or_zero, TowerField, TowerFp12};
+use crate::frontend::ecc::bn254::native::NativeField;
+use crate::frontend::hint::simple::hint::Hint;
+use crate::prelude::*;
+
+/// An element of `Fp12`, as its coefficients in `1, w, ..., w^5`.
+#[derive(Debug, Clone)]
+pub struct Fp12Variable<P: TowerField>(pub [Fp2Variable<P>; 6]);
+
+impl<P: TowerField> Fp12Variable<P> {
+    pub fn new(coefficients: [Fp2Variable<P>; 6]) -> Self {
+        Self(coefficients)
+    }
+}
+
+/// A coefficient of a sparse element of `Fp12`.
+#[derive(Debug, Clone)]
+pub enum Fp12Coefficient<P: TowerField> {
+    Fp(FpVariable<P>),
+    Fp2(Fp2Variable<P>),
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    pub fn fp12_constant<E: TowerFp12>(&mut self, value: &E) -> Fp12Variable<E::Base> {
+        let coefficients = value.coefficients();
+        Fp12Variable(core::array::from_fn(|i| {
+            self.fp2_constant(&coefficients[i])
+        }))
+    }
+
+    /// Multiplies `a` by the sparse element `sum_j b_j * w^j`, reducing each coefficient once.
+    pub fn fp12_mul_sparse<P: TowerField>(
+        &mut self,
+        a: &Fp12Variable<P>,
+        b: &[(usize, Fp12Coefficient<P>)],
+    ) -> Fp12Variable<P> {
+        let mut direct = core::array::from_fn::<_, 6, _>(|_| Fp2Poly::zero());
+        let mut wrapped = core::array::from_fn::<_, 6, _>(|_| Fp2Poly::zero());
+        for (i, x) in a.0.iter().enumerate() {
+            for (j, y) in b.iter() {
+                let product = match y {
+                    Fp12Coefficient::Fp(y) => self.fp2_poly_mul_fp(x, y),
+                    Fp12Coefficient::Fp2(y) => self.fp2_poly_mul(x, y),
+                };
+                // Terms of degree at least 6 wrap around as `w^6 = xi`.
+                let k = i + j;
+                if k < 6 {
+                    direct[k] = self.fp2_poly_add(&direct[k], &product);
+                } else {
+                    wrapped[k - 6] = self.fp2_poly_add(&wrapped[k - 6], &product);
+                }
+            }
+        }
+        Fp12Variable(core::array::from_fn(|k| {
+            let wrapped = self.fp2_poly_mul_by_xi(&wrapped[k]);
+            let sum = self.fp2_poly_add(&direct[k], &wrapped);
+            self.fp2_reduce(&sum)
+        }))
+    }
+
+    /// Multiplies `f` by the sparse evaluation of a line of the Miller loop, or sets `f` to it if
+    /// there is no `f` yet.
+    pub fn fp12_mul_by_line<P: TowerField>(
+        &mut self,
+        f: Option<Fp12Variable<P>>,
+        line: Vec<(usize, Fp12Coefficient<P>)>,
+    ) -> Fp12Variable<P> {
+        match f {
+            Some(f) => self.fp12_mul_sparse(&f, &line),
+            None => {
+                let zero = self.fp2_constant(&<P::Fp2 as NativeField>::zero());
+                let mut result = Fp12Variable(core::array::from_fn(|_| zero.clone()));
+                for (j, c) in line {
+                    result.0[j] = match c {
+                        Fp12Coefficient::Fp(c0) => Fp2Variable {
+                            c0,
+                            c1: self.fp_constant(&P::zero()),
+                        },
+                        Fp12Coefficient::Fp2(c) => c,
+                    };
+                }
+                result
+            }
+        }
+    }
+
+    pub fn fp12_mul<P: TowerField>(
+        &mut self,
+        a: &Fp12Variable<P>,
+        b: &Fp12Variable<P>,
+    ) -> Fp12Variable<P> {
+        let b =
+            b.0.iter()
+                .cloned()
+                .map(Fp12Coefficient::Fp2)
+                .enumerate()
+                .collect::<Vec<_>>();
+        self.fp12_mul_sparse(a, &b)
+    }
+
+    pub fn fp12_square<P: TowerField>(&mut self, a: &Fp12Variable<P>) -> Fp12Variable<P> {
+        self.fp12_mul(a, a)
+    }
+
+    /// The `p^6`-th power, which is the inverse of elements in the cyclotomic subgroup.
+    pub fn fp12_conjugate<P: TowerField>(&mut self, a: &Fp12Variable<P>) -> Fp12Variable<P> {
+        let mut result = a.clone();
+        for i in [1, 3, 5] {
+            result.0[i] = self.fp2_neg(&a.0[i]);
+        }
+        result
+    }
+
+    /// The `p^k`-th power.
+    pub fn fp12_frobenius<P: TowerField>(
+        &mut self,
+        a: &Fp12Variable<P>,
+        k: u32,
+    ) -> Fp12Variable<P> {
+        let gammas = P::frobenius_coefficients(k);
+        let mut result = a.clone();
+        for (i, gamma) in gammas.iter().enumerate() {
+            if k % 2 == 1 {
+                result.0[i] = self.fp2_conjugate(&result.0[i]);
+            }
+            if i > 0 {
+                let gamma = self.fp2_constant(gamma);
+                result.0[i] = self.fp2_mul(&result.0[i], &gamma);
+            }
+        }
+        result
+    }
+
+    /// The inverse of an element, which must be non-zero for the proof to be valid.
+    pub fn fp12_inverse<P: TowerField>(&mut self, a: &Fp12Variable<P>) -> Fp12Variable<P> {
+        let mut input_stream = VariableStream::new();
+        input_stream.write_slice(&a.0);
+        let output_stream = self.hint(input_stream, Fp12InverseHint::<P>(PhantomData));
+        let inverse = Fp12Variable(core::array::from_fn(|_| {
+            output_stream.read::<Fp2Variable<P>>(self)
+        }));
+
+        let product = self.fp12_mul(a, &inverse);
+        self.fp12_assert_is_one(&product);
+        inverse
+    }
+
+    /// Raises an element to a constant power by square and multiply.
+    pub fn fp12_pow<P: TowerField>(
+        &mut self,
+        a: &Fp12Variable<P>,
+        exponent: &BigUint,
+    ) -> Fp12Variable<P> {
+        assert!(exponent.bits() > 0, "the exponent is zero");
+        let mut result = a.clone();
+        for i in (0..exponent.bits() - 1).rev() {
+            result = self.fp12_square(&result);
+            if exponent.bit(i) {
+                result = self.fp12_mul(&result, a);
+            }
+        }
+        result
+    }
+
+    pub fn fp12_assert_is_equal<P: TowerField>(
+        &mut self,
+        a: &Fp12Variable<P>,
+        b: &Fp12Variable<P>,
+    ) {
+        for (x, y) in a.0.iter().zip(b.0.iter()) {
+            self.assert_is_equal(x.clone(), y.clone());
+        }
+    }
+
+    pub fn fp12_assert_is_one<P: TowerField>(&mut self, a: &Fp12Variable<P>) {
+        let one = self.fp12_constant(&<P::Fp12 as NativeField>::one());
+        self.fp12_assert_is_equal(a, &one);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Fp12InverseHint<P: TowerField>(PhantomData<P>);
+
+impl<L: PlonkParameters<D>, const D: usize, P: TowerField> Hint<L, D> for Fp12InverseHint<P> {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let a = <P::Fp12 as TowerFp12>::from_coefficients(core::array::from_fn(|_| {
+            input_stream.read_value::<Fp2Variable<P>>().to_native()
+        }));
+        let inverse = inverse_or_zero(a);
+        for c in TowerFp12::coefficients(&inverse) {
+            output_stream.write_value::<Fp2Variable<P>>(Fp2Value::from_native(&c));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::ecc::bls12_381::native::Fp;
+    use crate::frontend::ecc::bn254::native::Fq;
+    use crate::frontend::ecc::tower::TowerFp2;
+
+    fn test_fp12_arithmetic<P: TowerField>() {
+        let mut builder = DefaultBuilder::new();
+        let a = Fp12Variable::<P>::new(core::array::from_fn(|_| builder.read::<Fp2Variable<P>>()));
+        let b = Fp12Variable::<P>::new(core::array::from_fn(|_| builder.read::<Fp2Variable<P>>()));
+        let product = builder.fp12_mul(&a, &b);
+        let frobenius = builder.fp12_frobenius(&a, 1);
+        let inverse = builder.fp12_inverse(&a);
+        for c in product.0.into_iter().chain(frobenius.0).chain(inverse.0) {
+            builder.write(c);
+        }
+        let circuit = builder.build();
+
+        let element = |seed: u64| {
+            <P::Fp12 as TowerFp12>::from_coefficients(core::array::from_fn(|i| {
+                let i = i as u64;
+                let c0 = P::from_u64(seed * i + 1);
+                <P::Fp2 as TowerFp2>::from_coefficients(c0, -P::from_u64(seed + i))
+            }))
+        };
+        let (a, b) = (element(3), element(11));
+        let mut input = circuit.input();
+        for c in TowerFp12::coefficients(&a)
+            .iter()
+            .chain(TowerFp12::coefficients(&b).iter())
+        {
+            input.write::<Fp2Variable<P>>(Fp2Value::from_native(c));
+        }
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        // The Frobenius map is the `p`-th power.
+        let expected = [
+            a.clone() * b,
+            NativeField::pow(&a, &P::modulus()),
+            NativeField::inverse(&a),
+        ];
+        for value in expected {
+            for c in TowerFp12::coefficients(&value) {
+                assert_eq!(output.read::<Fp2Variable<P>>().to_native(), c);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fp12_arithmetic_bn254() {
+        test_fp12_arithmetic::<Fq>();
+    }
+
+    #[test]
+    fn test_fp12_arithmetic_bls12_381() {
+        test_fp12_arithmetic::<Fp>();
+    }
+}
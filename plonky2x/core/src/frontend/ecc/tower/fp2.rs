@@ -0,0 +1,250 @@
+//! Arithmetic over `Fp2 = Fp[u]/(u^2 + 1)`.
+
+use core::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+
+use super::fp::{FpPoly, FpVariable};
+use super::{inverse_or_zero, TowerField, TowerFp2};
+use crate::frontend::ecc::bn254::native::NativeField;
+use crate::frontend::hint::simple::hint::Hint;
+use crate::prelude::*;
+
+/// An element `c0 + c1 * u` of `Fp2`.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(Fp2Value)]
+pub struct Fp2Variable<P: TowerField> {
+    pub c0: FpVariable<P>,
+    pub c1: FpVariable<P>,
+}
+
+/// An unreduced element of `Fp2`.
+#[derive(Debug, Clone)]
+pub struct Fp2Poly<P: TowerField> {
+    pub c0: FpPoly<P>,
+    pub c1: FpPoly<P>,
+}
+
+impl<P: TowerField> Fp2Poly<P> {
+    pub fn zero() -> Self {
+        Self {
+            c0: FpPoly::zero(),
+            c1: FpPoly::zero(),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    pub fn fp2_constant<E: TowerFp2>(&mut self, value: &E) -> Fp2Variable<E::Base> {
+        let [c0, c1] = value.coefficients();
+        Fp2Variable {
+            c0: self.fp_constant(&c0),
+            c1: self.fp_constant(&c1),
+        }
+    }
+
+    pub fn fp2_to_poly<P: TowerField>(&mut self, a: &Fp2Variable<P>) -> Fp2Poly<P> {
+        Fp2Poly {
+            c0: self.fp_to_poly(&a.c0),
+            c1: self.fp_to_poly(&a.c1),
+        }
+    }
+
+    pub fn fp2_poly_add<P: TowerField>(&mut self, a: &Fp2Poly<P>, b: &Fp2Poly<P>) -> Fp2Poly<P> {
+        Fp2Poly {
+            c0: self.fp_poly_add(&a.c0, &b.c0),
+            c1: self.fp_poly_add(&a.c1, &b.c1),
+        }
+    }
+
+    pub fn fp2_poly_sub<P: TowerField>(&mut self, a: &Fp2Poly<P>, b: &Fp2Poly<P>) -> Fp2Poly<P> {
+        Fp2Poly {
+            c0: self.fp_poly_sub(&a.c0, &b.c0),
+            c1: self.fp_poly_sub(&a.c1, &b.c1),
+        }
+    }
+
+    pub fn fp2_poly_scale<P: TowerField>(&mut self, a: &Fp2Poly<P>, c: u64) -> Fp2Poly<P> {
+        Fp2Poly {
+            c0: self.fp_poly_scale(&a.c0, c),
+            c1: self.fp_poly_scale(&a.c1, c),
+        }
+    }
+
+    /// Multiplies an unreduced element by `xi = c + u`.
+    pub fn fp2_poly_mul_by_xi<P: TowerField>(&mut self, a: &Fp2Poly<P>) -> Fp2Poly<P> {
+        let scaled = if P::XI_C0 == 1 {
+            a.clone()
+        } else {
+            self.fp2_poly_scale(a, P::XI_C0)
+        };
+        Fp2Poly {
+            c0: self.fp_poly_sub(&scaled.c0, &a.c1),
+            c1: self.fp_poly_add(&scaled.c1, &a.c0),
+        }
+    }
+
+    /// The product of two elements, as an unreduced element.
+    pub fn fp2_poly_mul<P: TowerField>(
+        &mut self,
+        a: &Fp2Variable<P>,
+        b: &Fp2Variable<P>,
+    ) -> Fp2Poly<P> {
+        let a0b0 = self.fp_poly_mul(&a.c0, &b.c0);
+        let a1b1 = self.fp_poly_mul(&a.c1, &b.c1);
+        let a0b1 = self.fp_poly_mul(&a.c0, &b.c1);
+        let a1b0 = self.fp_poly_mul(&a.c1, &b.c0);
+        Fp2Poly {
+            c0: self.fp_poly_sub(&a0b0, &a1b1),
+            c1: self.fp_poly_add(&a0b1, &a1b0),
+        }
+    }
+
+    /// The product of an element of `Fp2` and an element of `Fp`, as an unreduced element.
+    pub fn fp2_poly_mul_fp<P: TowerField>(
+        &mut self,
+        a: &Fp2Variable<P>,
+        b: &FpVariable<P>,
+    ) -> Fp2Poly<P> {
+        Fp2Poly {
+            c0: self.fp_poly_mul(&a.c0, b),
+            c1: self.fp_poly_mul(&a.c1, b),
+        }
+    }
+
+    pub fn fp2_reduce<P: TowerField>(&mut self, a: &Fp2Poly<P>) -> Fp2Variable<P> {
+        Fp2Variable {
+            c0: self.fp_reduce(&a.c0),
+            c1: self.fp_reduce(&a.c1),
+        }
+    }
+
+    pub fn fp2_add<P: TowerField>(
+        &mut self,
+        a: &Fp2Variable<P>,
+        b: &Fp2Variable<P>,
+    ) -> Fp2Variable<P> {
+        Fp2Variable {
+            c0: self.fp_add(&a.c0, &b.c0),
+            c1: self.fp_add(&a.c1, &b.c1),
+        }
+    }
+
+    pub fn fp2_sub<P: TowerField>(
+        &mut self,
+        a: &Fp2Variable<P>,
+        b: &Fp2Variable<P>,
+    ) -> Fp2Variable<P> {
+        Fp2Variable {
+            c0: self.fp_sub(&a.c0, &b.c0),
+            c1: self.fp_sub(&a.c1, &b.c1),
+        }
+    }
+
+    pub fn fp2_neg<P: TowerField>(&mut self, a: &Fp2Variable<P>) -> Fp2Variable<P> {
+        Fp2Variable {
+            c0: self.fp_neg(&a.c0),
+            c1: self.fp_neg(&a.c1),
+        }
+    }
+
+    pub fn fp2_conjugate<P: TowerField>(&mut self, a: &Fp2Variable<P>) -> Fp2Variable<P> {
+        Fp2Variable {
+            c0: a.c0.clone(),
+            c1: self.fp_neg(&a.c1),
+        }
+    }
+
+    pub fn fp2_mul<P: TowerField>(
+        &mut self,
+        a: &Fp2Variable<P>,
+        b: &Fp2Variable<P>,
+    ) -> Fp2Variable<P> {
+        let product = self.fp2_poly_mul(a, b);
+        self.fp2_reduce(&product)
+    }
+
+    pub fn fp2_mul_fp<P: TowerField>(
+        &mut self,
+        a: &Fp2Variable<P>,
+        b: &FpVariable<P>,
+    ) -> Fp2Variable<P> {
+        let product = self.fp2_poly_mul_fp(a, b);
+        self.fp2_reduce(&product)
+    }
+
+    pub fn fp2_square<P: TowerField>(&mut self, a: &Fp2Variable<P>) -> Fp2Variable<P> {
+        self.fp2_mul(a, a)
+    }
+
+    /// The inverse of an element, which must be non-zero for the proof to be valid.
+    pub fn fp2_inverse<P: TowerField>(&mut self, a: &Fp2Variable<P>) -> Fp2Variable<P> {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(a);
+        let output_stream = self.hint(input_stream, Fp2InverseHint::<P>(PhantomData));
+        let inverse = output_stream.read::<Fp2Variable<P>>(self);
+
+        let product = self.fp2_mul(a, &inverse);
+        let one = self.fp2_constant(&<P::Fp2 as NativeField>::one());
+        self.assert_is_equal(product, one);
+        inverse
+    }
+
+    pub fn fp2_div<P: TowerField>(
+        &mut self,
+        a: &Fp2Variable<P>,
+        b: &Fp2Variable<P>,
+    ) -> Fp2Variable<P> {
+        let inverse = self.fp2_inverse(b);
+        self.fp2_mul(a, &inverse)
+    }
+
+    /// The sign of RFC 9380, the parity of `c0`, or of `c1` if `c0` is zero.
+    pub fn fp2_sgn0<P: TowerField>(&mut self, a: &Fp2Variable<P>) -> BoolVariable {
+        let c0_is_odd = self.fp_is_odd(&a.c0);
+        let c0_is_zero = self.fp_is_zero(&a.c0);
+        let c1_is_odd = self.fp_is_odd(&a.c1);
+        let c1_sign = self.and(c0_is_zero, c1_is_odd);
+        self.or(c0_is_odd, c1_sign)
+    }
+
+    /// Whether the element is greater than its negation, comparing `c1` first.
+    pub fn fp2_is_lexicographically_largest<P: TowerField>(
+        &mut self,
+        a: &Fp2Variable<P>,
+    ) -> BoolVariable {
+        let c1_is_zero = self.fp_is_zero(&a.c1);
+        let c0_is_largest = self.fp_is_lexicographically_largest(&a.c0);
+        let c1_is_largest = self.fp_is_lexicographically_largest(&a.c1);
+        self.select(c1_is_zero, c0_is_largest, c1_is_largest)
+    }
+}
+
+impl<P: TowerField, F: RichField> Fp2Value<P, F> {
+    pub fn from_native(value: &P::Fp2) -> Self {
+        let [c0, c1] = TowerFp2::coefficients(value);
+        Self {
+            c0: c0.as_biguint().clone(),
+            c1: c1.as_biguint().clone(),
+        }
+    }
+
+    pub fn to_native(&self) -> P::Fp2 {
+        <P::Fp2 as TowerFp2>::from_coefficients(
+            P::from_biguint(self.c0.clone()),
+            P::from_biguint(self.c1.clone()),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Fp2InverseHint<P: TowerField>(PhantomData<P>);
+
+impl<L: PlonkParameters<D>, const D: usize, P: TowerField> Hint<L, D> for Fp2InverseHint<P> {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let a = input_stream.read_value::<Fp2Variable<P>>().to_native();
+        let inverse = inverse_or_zero(a);
+        output_stream.write_value::<Fp2Variable<P>>(Fp2Value::from_native(&inverse));
+    }
+}
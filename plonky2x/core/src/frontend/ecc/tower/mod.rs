@@ -0,0 +1,67 @@
+//! Non-native arithmetic over the base field `Fp` of a pairing-friendly curve and over the tower
+//! `Fp2 = Fp[u]/(u^2 + 1)` and `Fp12 = Fp2[w]/(w^6 - xi)` with `xi = c + u`, shared by the `bn254`
+//! and `bls12_381` modules.
+//!
+//! Elements of the base field are represented with limbs of 16 bits, and products are reduced
+//! lazily: sums of products are accumulated without carries and reduced once, with the quotient
+//! and remainder given by a hint and the integer identity checked limb by limb. A curve only
+//! provides its native fields through [`TowerField`].
+
+use num_bigint::BigUint;
+
+use super::bn254::native::NativeField;
+
+pub mod fp;
+pub mod fp12;
+pub mod fp2;
+
+/// The native base field of a curve, with the parameters of its representation in a circuit and
+/// of its tower of extensions.
+pub trait TowerField: NativeField + Send + Sync + 'static {
+    /// The number of limbs of 16 bits of an element.
+    const NB_LIMBS: usize;
+
+    /// The integer `c` of the non-residue `xi = c + u`.
+    const XI_C0: u64;
+
+    type Fp2: TowerFp2<Base = Self>;
+    type Fp12: TowerFp12<Base = Self>;
+
+    fn modulus() -> BigUint;
+
+    /// The element of an integer, reduced modulo `p`.
+    fn from_biguint(value: BigUint) -> Self;
+
+    fn as_biguint(&self) -> &BigUint;
+
+    /// The coefficients `xi^(i * (p^k - 1) / 6)` of the `p^k`-th power Frobenius map, for `i < 6`.
+    fn frobenius_coefficients(k: u32) -> Vec<Self::Fp2>;
+}
+
+/// The native `Fp2` of a curve.
+pub trait TowerFp2: NativeField + Send + Sync + 'static {
+    type Base: TowerField<Fp2 = Self>;
+
+    fn from_coefficients(c0: Self::Base, c1: Self::Base) -> Self;
+
+    fn coefficients(&self) -> [Self::Base; 2];
+}
+
+/// The native `Fp12` of a curve.
+pub trait TowerFp12: NativeField + Send + Sync + 'static {
+    type Base: TowerField<Fp12 = Self>;
+
+    fn from_coefficients(coefficients: [<Self::Base as TowerField>::Fp2; 6]) -> Self;
+
+    fn coefficients(&self) -> [<Self::Base as TowerField>::Fp2; 6];
+}
+
+/// The inverse computed by the inverse hints, which is zero for zero so that the check of the
+/// product fails instead of the hint.
+fn inverse_or_zero<E: NativeField>(a: E) -> E {
+    if a.is_zero() {
+        a
+    } else {
+        a.inverse()
+    }
+}
@@ -0,0 +1,497 @@
+//! Constrained `abi.encode` and `abi.encodePacked` of static values, such as the commitments to
+//! the outputs of a circuit that a contract recomputes.
+//!
+//! Every static value takes one 32-byte word in `abi.encode`: unsigned integers and addresses are
+//! padded with zeros on the left, signed integers are sign-extended, and fixed-size byte arrays
+//! are padded with zeros on the right. Static tuples and arrays are the concatenation of the
+//! encodings of their elements. In `abi.encodePacked`, values take their own size, except for the
+//! elements of arrays, which are still padded.
+//!
+//! Decoding checks that the padding is the one that `abi.encode` writes, so that a word has a
+//! single valid decoding.
+
+use super::vars::AddressVariable;
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::*;
+
+/// The size of a word of `abi.encode`.
+pub const ABI_WORD_LEN: usize = 32;
+
+/// How a value shorter than a word is laid out in its word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbiPadding {
+    /// Zeros before the value, for unsigned integers, booleans and addresses.
+    Left,
+    /// Copies of the sign bit before the value, for signed integers.
+    SignExtended,
+    /// Zeros after the value, for fixed-size byte arrays.
+    Right,
+}
+
+/// A static value of the Solidity ABI.
+pub trait AbiVariable: Sized {
+    /// The number of bytes of `abi.encodePacked(value)`.
+    const PACKED_LEN: usize;
+
+    /// The number of bytes of `abi.encode(value)`, a multiple of `ABI_WORD_LEN`.
+    const ENCODED_LEN: usize;
+
+    /// Serializes the value as `abi.encode(value)`.
+    fn abi_encode<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable>;
+
+    /// Deserializes a value from `abi.encode(value)`, checking the padding of its words.
+    fn abi_decode<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self;
+
+    /// Serializes the value as `abi.encodePacked(value)`.
+    fn abi_encode_packed<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable>;
+
+    /// Deserializes a value from `abi.encodePacked(value)`.
+    fn abi_decode_packed<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self;
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns `abi.encode(value)`.
+    pub fn abi_encode<V: AbiVariable>(&mut self, value: &V) -> Vec<ByteVariable> {
+        value.abi_encode(self)
+    }
+
+    /// Decodes `abi.encode(value)`, which must be `V::ENCODED_LEN` bytes long.
+    pub fn abi_decode<V: AbiVariable>(&mut self, bytes: &[ByteVariable]) -> V {
+        assert_eq!(bytes.len(), V::ENCODED_LEN, "wrong abi encoding length");
+        V::abi_decode(self, bytes)
+    }
+
+    /// Returns `abi.encodePacked(value)`.
+    pub fn abi_encode_packed<V: AbiVariable>(&mut self, value: &V) -> Vec<ByteVariable> {
+        value.abi_encode_packed(self)
+    }
+
+    /// Decodes `abi.encodePacked(value)`, which must be `V::PACKED_LEN` bytes long.
+    pub fn abi_decode_packed<V: AbiVariable>(&mut self, bytes: &[ByteVariable]) -> V {
+        assert_eq!(
+            bytes.len(),
+            V::PACKED_LEN,
+            "wrong packed abi encoding length"
+        );
+        V::abi_decode_packed(self, bytes)
+    }
+
+    /// The bytes written before or after `value` in its word.
+    fn abi_padding_byte(&mut self, first: ByteVariable, padding: AbiPadding) -> ByteVariable {
+        let zero = self.constant::<ByteVariable>(0);
+        if padding == AbiPadding::SignExtended {
+            let ones = self.constant::<ByteVariable>(0xff);
+            self.select(first.as_be_bits()[0], ones, zero)
+        } else {
+            zero
+        }
+    }
+
+    /// Pads the bytes of a value to a word.
+    pub fn abi_pad_word(
+        &mut self,
+        bytes: &[ByteVariable],
+        padding: AbiPadding,
+    ) -> Vec<ByteVariable> {
+        assert!(
+            !bytes.is_empty() && bytes.len() <= ABI_WORD_LEN,
+            "values of the abi take between one byte and a word"
+        );
+        let padding_byte = self.abi_padding_byte(bytes[0], padding);
+        let mut word = vec![padding_byte; ABI_WORD_LEN - bytes.len()];
+        if padding == AbiPadding::Right {
+            word.splice(0..0, bytes.iter().copied());
+        } else {
+            word.extend_from_slice(bytes);
+        }
+        word
+    }
+
+    /// Returns the `len` bytes of a value from its word, asserting that the other bytes are the
+    /// padding of `abi.encode`.
+    pub fn abi_unpad_word(
+        &mut self,
+        word: &[ByteVariable],
+        len: usize,
+        padding: AbiPadding,
+    ) -> Vec<ByteVariable> {
+        assert_eq!(
+            word.len(),
+            ABI_WORD_LEN,
+            "words of the abi are 32 bytes long"
+        );
+        assert!(
+            len > 0 && len <= ABI_WORD_LEN,
+            "values of the abi take between one byte and a word"
+        );
+        let (value, padding_bytes) = if padding == AbiPadding::Right {
+            (&word[..len], &word[len..])
+        } else {
+            let (padding_bytes, value) = word.split_at(ABI_WORD_LEN - len);
+            (value, padding_bytes)
+        };
+        let padding_byte = self.abi_padding_byte(value[0], padding);
+        for byte in padding_bytes.iter() {
+            self.assert_is_equal(*byte, padding_byte);
+        }
+        value.to_vec()
+    }
+}
+
+macro_rules! impl_abi_word {
+    ($($ty:ty => $padding:expr, $len:expr;)*) => {
+        $(
+            impl AbiVariable for $ty {
+                const PACKED_LEN: usize = $len;
+                const ENCODED_LEN: usize = ABI_WORD_LEN;
+
+                fn abi_encode<L: PlonkParameters<D>, const D: usize>(
+                    &self,
+                    builder: &mut CircuitBuilder<L, D>,
+                ) -> Vec<ByteVariable> {
+                    let bytes = self.encode(builder);
+                    builder.abi_pad_word(&bytes, $padding)
+                }
+
+                fn abi_decode<L: PlonkParameters<D>, const D: usize>(
+                    builder: &mut CircuitBuilder<L, D>,
+                    bytes: &[ByteVariable],
+                ) -> Self {
+                    let bytes = builder.abi_unpad_word(bytes, $len, $padding);
+                    <$ty as EvmVariable>::decode(builder, &bytes)
+                }
+
+                fn abi_encode_packed<L: PlonkParameters<D>, const D: usize>(
+                    &self,
+                    builder: &mut CircuitBuilder<L, D>,
+                ) -> Vec<ByteVariable> {
+                    self.encode(builder)
+                }
+
+                fn abi_decode_packed<L: PlonkParameters<D>, const D: usize>(
+                    builder: &mut CircuitBuilder<L, D>,
+                    bytes: &[ByteVariable],
+                ) -> Self {
+                    <$ty as EvmVariable>::decode(builder, bytes)
+                }
+            }
+        )*
+    };
+}
+
+// A `ByteVariable` is a `uint8`, and a `BytesVariable<1>` a `bytes1`.
+impl_abi_word! {
+    ByteVariable => AbiPadding::Left, 1;
+    U32Variable => AbiPadding::Left, 4;
+    U64Variable => AbiPadding::Left, 8;
+    U128Variable => AbiPadding::Left, 16;
+    U256Variable => AbiPadding::Left, 32;
+    I64Variable => AbiPadding::SignExtended, 8;
+    I256Variable => AbiPadding::SignExtended, 32;
+    AddressVariable => AbiPadding::Left, 20;
+    Bytes32Variable => AbiPadding::Right, 32;
+}
+
+impl<const N: usize> AbiVariable for BytesVariable<N> {
+    const PACKED_LEN: usize = N;
+    const ENCODED_LEN: usize = ABI_WORD_LEN;
+
+    fn abi_encode<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        builder.abi_pad_word(&self.0, AbiPadding::Right)
+    }
+
+    fn abi_decode<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self {
+        let bytes = builder.abi_unpad_word(bytes, N, AbiPadding::Right);
+        BytesVariable(bytes.try_into().unwrap())
+    }
+
+    fn abi_encode_packed<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        _builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        self.0.to_vec()
+    }
+
+    fn abi_decode_packed<L: PlonkParameters<D>, const D: usize>(
+        _builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self {
+        BytesVariable(bytes.try_into().unwrap())
+    }
+}
+
+impl AbiVariable for BoolVariable {
+    const PACKED_LEN: usize = 1;
+    const ENCODED_LEN: usize = ABI_WORD_LEN;
+
+    fn abi_encode<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        let byte = self.abi_encode_packed(builder);
+        builder.abi_pad_word(&byte, AbiPadding::Left)
+    }
+
+    fn abi_decode<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self {
+        let byte = builder.abi_unpad_word(bytes, 1, AbiPadding::Left);
+        Self::abi_decode_packed(builder, &byte)
+    }
+
+    fn abi_encode_packed<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        let false_variable = builder._false();
+        let mut bits = [false_variable; 8];
+        bits[7] = *self;
+        vec![ByteVariable::from_be_bits(bits)]
+    }
+
+    /// Decodes a byte that must be zero or one.
+    fn abi_decode_packed<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self {
+        let bits = bytes[0].as_be_bits();
+        let false_variable = builder._false();
+        for bit in bits[..7].iter() {
+            builder.assert_is_equal(*bit, false_variable);
+        }
+        bits[7]
+    }
+}
+
+/// The elements of an array are padded to words in both encodings.
+impl<V: AbiVariable + CircuitVariable, const N: usize> AbiVariable for ArrayVariable<V, N> {
+    const PACKED_LEN: usize = N * V::ENCODED_LEN;
+    const ENCODED_LEN: usize = N * V::ENCODED_LEN;
+
+    fn abi_encode<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        self.as_slice()
+            .iter()
+            .flat_map(|element| element.abi_encode(builder))
+            .collect()
+    }
+
+    fn abi_decode<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self {
+        let elements = bytes
+            .chunks_exact(V::ENCODED_LEN)
+            .map(|chunk| V::abi_decode(builder, chunk))
+            .collect::<Vec<_>>();
+        ArrayVariable::new(elements)
+    }
+
+    fn abi_encode_packed<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        self.abi_encode(builder)
+    }
+
+    fn abi_decode_packed<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self {
+        Self::abi_decode(builder, bytes)
+    }
+}
+
+macro_rules! impl_abi_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: AbiVariable),+> AbiVariable for ($($name,)+) {
+            const PACKED_LEN: usize = 0 $(+ $name::PACKED_LEN)+;
+            const ENCODED_LEN: usize = 0 $(+ $name::ENCODED_LEN)+;
+
+            fn abi_encode<L: PlonkParameters<D>, const D: usize>(
+                &self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> Vec<ByteVariable> {
+                let mut bytes = Vec::with_capacity(Self::ENCODED_LEN);
+                $(bytes.extend(self.$index.abi_encode(builder));)+
+                bytes
+            }
+
+            #[allow(unused_assignments)]
+            fn abi_decode<L: PlonkParameters<D>, const D: usize>(
+                builder: &mut CircuitBuilder<L, D>,
+                bytes: &[ByteVariable],
+            ) -> Self {
+                let mut rest = bytes;
+                ($({
+                    let (head, tail) = rest.split_at($name::ENCODED_LEN);
+                    rest = tail;
+                    $name::abi_decode(builder, head)
+                },)+)
+            }
+
+            fn abi_encode_packed<L: PlonkParameters<D>, const D: usize>(
+                &self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> Vec<ByteVariable> {
+                let mut bytes = Vec::with_capacity(Self::PACKED_LEN);
+                $(bytes.extend(self.$index.abi_encode_packed(builder));)+
+                bytes
+            }
+
+            #[allow(unused_assignments)]
+            fn abi_decode_packed<L: PlonkParameters<D>, const D: usize>(
+                builder: &mut CircuitBuilder<L, D>,
+                bytes: &[ByteVariable],
+            ) -> Self {
+                let mut rest = bytes;
+                ($({
+                    let (head, tail) = rest.split_at($name::PACKED_LEN);
+                    rest = tail;
+                    $name::abi_decode_packed(builder, head)
+                },)+)
+            }
+        }
+    };
+}
+
+impl_abi_tuple!(A 0);
+impl_abi_tuple!(A 0, B 1);
+impl_abi_tuple!(A 0, B 1, C 2);
+impl_abi_tuple!(A 0, B 1, C 2, E 3);
+impl_abi_tuple!(A 0, B 1, C 2, E 3, F 4);
+impl_abi_tuple!(A 0, B 1, C 2, E 3, F 4, G 5);
+impl_abi_tuple!(A 0, B 1, C 2, E 3, F 4, G 5, H 6);
+impl_abi_tuple!(A 0, B 1, C 2, E 3, F 4, G 5, H 6, I 7);
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{encode, Token};
+    use ethers::types::{H256, I256, U256};
+
+    use super::*;
+
+    type Values = (
+        U256Variable,
+        AddressVariable,
+        Bytes32Variable,
+        U32Variable,
+        I64Variable,
+        BoolVariable,
+        BytesVariable<4>,
+    );
+
+    #[test]
+    fn test_abi_encode_static_tuple() {
+        const ENCODED_LEN: usize = 7 * ABI_WORD_LEN;
+        const PACKED_LEN: usize = 32 + 20 + 32 + 4 + 8 + 1 + 4;
+
+        let mut builder = DefaultBuilder::new();
+        let values: Values = (
+            builder.read::<U256Variable>(),
+            builder.read::<AddressVariable>(),
+            builder.read::<Bytes32Variable>(),
+            builder.read::<U32Variable>(),
+            builder.read::<I64Variable>(),
+            builder.read::<BoolVariable>(),
+            builder.read::<BytesVariable<4>>(),
+        );
+        let encoded = builder.abi_encode(&values);
+        let packed = builder.abi_encode_packed(&values);
+        let decoded = builder.abi_decode::<Values>(&encoded);
+        let decoded_packed = builder.abi_decode_packed::<Values>(&packed);
+        builder.write(ArrayVariable::<ByteVariable, ENCODED_LEN>::new(encoded));
+        builder.write(ArrayVariable::<ByteVariable, PACKED_LEN>::new(packed));
+        builder.write(decoded.1);
+        builder.write(decoded.4);
+        builder.write(decoded_packed.4);
+        builder.write(decoded_packed.5);
+        let circuit = builder.build();
+
+        let amount = U256::exp10(18) * U256::from(3);
+        let recipient = address!("0xde0b295669a9fd93d5f28d9ec85e40f4cb697bae");
+        let root = bytes32!("0x4cfa7808badb1e62513ba42486f21240f696a9ffc6d598273d226cc5b30bfe28");
+        let nonce = 42u32;
+        let delta = -1234i64;
+        let selector = [0xa9, 0x05, 0x9c, 0xbb];
+        let mut input = circuit.input();
+        input.write::<U256Variable>(amount);
+        input.write::<AddressVariable>(recipient);
+        input.write::<Bytes32Variable>(root);
+        input.write::<U32Variable>(nonce);
+        input.write::<I64Variable>(delta);
+        input.write::<BoolVariable>(true);
+        input.write::<BytesVariable<4>>(selector);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let expected = encode(&[
+            Token::Uint(amount),
+            Token::Address(recipient),
+            Token::FixedBytes(root.as_bytes().to_vec()),
+            Token::Uint(U256::from(nonce)),
+            Token::Int(I256::from(delta).into_raw()),
+            Token::Bool(true),
+            Token::FixedBytes(selector.to_vec()),
+        ]);
+        assert_eq!(
+            output.read::<ArrayVariable<ByteVariable, ENCODED_LEN>>(),
+            expected
+        );
+
+        let mut expected_packed = [0u8; 32];
+        amount.to_big_endian(&mut expected_packed);
+        let mut expected_packed = expected_packed.to_vec();
+        expected_packed.extend_from_slice(recipient.as_bytes());
+        expected_packed.extend_from_slice(root.as_bytes());
+        expected_packed.extend_from_slice(&nonce.to_be_bytes());
+        expected_packed.extend_from_slice(&delta.to_be_bytes());
+        expected_packed.push(1);
+        expected_packed.extend_from_slice(&selector);
+        assert_eq!(
+            output.read::<ArrayVariable<ByteVariable, PACKED_LEN>>(),
+            expected_packed
+        );
+
+        assert_eq!(output.read::<AddressVariable>(), recipient);
+        assert_eq!(output.read::<I64Variable>(), delta);
+        assert_eq!(output.read::<I64Variable>(), delta);
+        assert!(output.read::<BoolVariable>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_abi_decode_dirty_padding() {
+        let mut builder = DefaultBuilder::new();
+        let word = builder.read::<Bytes32Variable>();
+        let address = builder.abi_decode::<AddressVariable>(&word.0 .0);
+        builder.write(address);
+        let circuit = builder.mock_build();
+
+        // An address word whose padding is not zero, as written by a careless encoder.
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(H256::repeat_byte(0x11));
+        circuit.mock_prove(&input);
+    }
+}
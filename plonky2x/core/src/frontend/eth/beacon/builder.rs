@@ -1,40 +1,39 @@
-use array_macro::array;
-use ethers::types::{H256, U256};
-
+use super::fork::{
+    BeaconFork, BALANCES_FIELD, BLOCK_ROOTS_FIELD, HISTORICAL_SUMMARIES_FIELD,
+    NEXT_SYNC_COMMITTEE_FIELD, PENDING_CONSOLIDATIONS_FIELD, PENDING_DEPOSITS_FIELD,
+    STATE_ROOT_GINDEX, VALIDATORS_FIELD,
+};
 use super::generators::{
     BeaconAllWithdrawalsHint, BeaconBalanceBatchWitnessHint, BeaconBalanceGenerator,
-    BeaconBalanceWitnessHint, BeaconBalancesGenerator, BeaconBlockRootsHint, BeaconGraffitiHint,
-    BeaconHeaderHint, BeaconHeadersFromOffsetRangeHint, BeaconHistoricalBlockHint,
-    BeaconPartialBalancesHint, BeaconPartialValidatorsHint, BeaconValidatorBatchHint,
-    BeaconValidatorGenerator, BeaconValidatorSubtreeHint, BeaconValidatorSubtreePoseidonHint,
-    BeaconValidatorSubtreesHint, BeaconValidatorsHint, BeaconWithdrawalGenerator,
-    BeaconWithdrawalsGenerator, CompressedBeaconValidatorBatchHint, CLOSE_SLOT_BLOCK_ROOT_DEPTH,
-    FAR_SLOT_BLOCK_ROOT_DEPTH, FAR_SLOT_HISTORICAL_SUMMARY_DEPTH,
+    BeaconBalanceSumHint, BeaconBalanceWitnessHint, BeaconBalancesGenerator, BeaconBlockRootsHint,
+    BeaconGraffitiHint, BeaconHeaderHint, BeaconHeadersFromOffsetRangeHint,
+    BeaconHistoricalBlockHint, BeaconPartialBalancesHint, BeaconPartialValidatorsHint,
+    BeaconPendingConsolidationHint, BeaconPendingConsolidationsHint, BeaconPendingDepositHint,
+    BeaconPendingDepositsHint, BeaconValidatorBatchHint, BeaconValidatorGenerator,
+    BeaconValidatorSubtreeHint, BeaconValidatorSubtreePoseidonHint, BeaconValidatorSubtreesHint,
+    BeaconValidatorsHint, BeaconWithdrawalGenerator, BeaconWithdrawalsGenerator,
+    CompressedBeaconValidatorBatchHint, BALANCE_PROOF_DEPTH, FAR_SLOT_BLOCK_ROOT_DEPTH,
+    HISTORICAL_ROOTS_LIMIT_LOG2, PENDING_CONSOLIDATIONS_LIMIT_LOG2, PENDING_DEPOSITS_LIMIT_LOG2,
 };
 use super::vars::{
-    BeaconBalancesVariable, BeaconHeaderVariable, BeaconValidatorVariable,
-    BeaconValidatorsVariable, BeaconWithdrawalVariable, BeaconWithdrawalsVariable,
-    CompressedBeaconValidatorVariable,
+    BeaconBalancesVariable, BeaconHeaderVariable, BeaconPendingConsolidationVariable,
+    BeaconPendingConsolidationsVariable, BeaconPendingDepositVariable,
+    BeaconPendingDepositsVariable, BeaconValidatorVariable, BeaconValidatorsVariable,
+    BeaconWithdrawalVariable, BeaconWithdrawalsVariable, CompressedBeaconValidatorVariable,
+    SyncCommitteeVariable,
 };
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::ecc::bls12_381::curve::G1AffineVariable;
 use crate::frontend::eth::vars::BLSPubkeyVariable;
 use crate::frontend::uint::uint64::U64Variable;
 use crate::frontend::vars::{
-    Bytes32Variable, CircuitVariable, EvmVariable, SSZVariable, VariableStream,
+    Bytes32Variable, CircuitVariable, EvmVariable, OrderedBytesVariable, OutputVariableStream,
+    SSZVariable, VariableStream,
 };
 use crate::prelude::{ArrayVariable, BoolVariable, ByteVariable, BytesVariable, U256Variable};
 use crate::utils::eth::concat_g_indices;
 
-/// The gindex for blockRoot -> validatorsRoot.
-const VALIDATORS_ROOT_GINDEX: u64 = 363;
-
-/// The gindex for blockRoot -> stateRoot.
-const STATE_ROOT_GINDEX: u64 = 11;
-
-/// The gindex for blockRoot -> balancesRoot.
-const BALANCES_ROOT_GINDEX: u64 = 364;
-
 /// The gindex for blockRoot -> withdrawalsRoot.
 const WITHDRAWALS_ROOT_GINDEX: u64 = 3230;
 
@@ -47,36 +46,15 @@ const BALANCE_BASE_GINDEX: u64 = 549755813888;
 /// The gindex for withdrawalsRoot -> withdrawals[i].
 const WITHDRAWAL_BASE_GINDEX: u64 = 32;
 
-/// The gindex for blockRoot -> state -> state.historicalSummaries[0].
-const HISTORICAL_SUMMARIES_BASE_GINDEX: u64 = 12717129728;
-
 /// The gindex for state.historicalSummaries[i] -> block_summary/block_roots -> block_roots[0].
 const HISTORICAL_SUMMARY_BLOCK_ROOT_GINDEX: u64 = 16384;
 
-/// The gindex for blockRoot -> state -> state.block_roots[0].
-const CLOSE_SLOT_BLOCK_ROOT_GINDEX: u64 = 2924544;
-
 /// The log2 of the validator registry limit.
 const VALIDATOR_REGISTRY_LIMIT_LOG2: usize = 40;
 
-/// The depth of the proof from the blockRoot -> balancesRoot.
-const BALANCES_PROOF_DEPTH: usize = 8;
-
-/// The depth of the proof from the blockRoot -> blockRoots.
-const BLOCK_ROOTS_PROOF_DEPTH: usize = 8;
-
 /// The depth of the proof from blockRoot -> graffiti.
 const GRAFFITI_PROOF_DEPTH: usize = 7;
 
-/// The gindex for stateRoot -> validators;
-const VALIDATORS_GINDEX: usize = 43;
-
-/// The gindex for stateRoot -> balances;
-const BALANCES_GINDEX: usize = 44;
-
-/// The gindex for blockRoot -> blockRoots.
-const BLOCK_ROOTS_GINDEX: usize = 357;
-
 /// The gindex for blockRoot -> graffiti.
 const GRAFFITI_GINDEX: usize = 194;
 
@@ -89,6 +67,9 @@ const SLOTS_PER_HISTORICAL_ROOT: usize = 8192;
 /// Beacon chain constant CAPELLA_FORK_EPOCH (mainnet specific).
 const CAPELLA_FORK_EPOCH: u64 = 194048;
 
+/// Beacon chain constant COMPOUNDING_WITHDRAWAL_PREFIX.
+const COMPOUNDING_WITHDRAWAL_PREFIX: u8 = 0x02;
+
 /// Beacon chain constant MAX_WITHDRAWALS_PER_PAYLOAD.
 const MAX_WITHDRAWALS_PER_PAYLOAD: usize = 16;
 
@@ -99,20 +80,22 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         block_root: Bytes32Variable,
     ) -> BeaconValidatorsVariable {
         let b_log2 = (B as f64).log2().ceil() as usize;
-        let hint = BeaconPartialValidatorsHint::<B> {};
+        let depth = self.beacon_fork.block_root_field_depth();
+        let hint = BeaconPartialValidatorsHint::<B> { depth };
         let mut input_stream = VariableStream::new();
         input_stream.write(&block_root);
 
         let output_stream = self.async_hint(input_stream, hint);
         let partial_validators_root = output_stream.read::<Bytes32Variable>(self);
-        let nb_branches = BALANCES_PROOF_DEPTH + (VALIDATOR_REGISTRY_LIMIT_LOG2 + 1 - b_log2);
+        let nb_branches = depth + (VALIDATOR_REGISTRY_LIMIT_LOG2 + 1 - b_log2);
         let mut proof = Vec::new();
         for _ in 0..nb_branches {
             proof.push(output_stream.read::<Bytes32Variable>(self));
         }
 
-        let gindex = VALIDATORS_GINDEX * (2usize.pow(41 - b_log2 as u32));
-        let gindex = concat_g_indices(&[STATE_ROOT_GINDEX as usize, gindex]);
+        let gindex = self.beacon_fork.state_field_gindex(VALIDATORS_FIELD)
+            * (2usize.pow(41 - b_log2 as u32));
+        let gindex = concat_g_indices(&[STATE_ROOT_GINDEX, gindex]);
         self.ssz_verify_proof_const(block_root, partial_validators_root, &proof, gindex as u64);
         BeaconValidatorsVariable {
             block_root,
@@ -127,12 +110,16 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     ) -> BeaconValidatorsVariable {
         let mut input_stream = VariableStream::new();
         input_stream.write(&block_root);
-        let hint = BeaconValidatorsHint::new();
+        let depth = self.beacon_fork.block_root_field_depth();
+        let hint = BeaconValidatorsHint::with_depth(depth);
         let output_stream = self.async_hint(input_stream, hint);
 
         let validators_root = output_stream.read::<Bytes32Variable>(self);
-        let proof = array![_ => output_stream.read::<Bytes32Variable>(self); 8];
-        self.ssz_verify_proof_const(block_root, validators_root, &proof, VALIDATORS_ROOT_GINDEX);
+        let proof = (0..depth)
+            .map(|_| output_stream.read::<Bytes32Variable>(self))
+            .collect::<Vec<_>>();
+        let gindex = self.beacon_fork.block_root_field_gindex(VALIDATORS_FIELD);
+        self.ssz_verify_proof_const(block_root, validators_root, &proof, gindex);
         BeaconValidatorsVariable {
             block_root,
             validators_root,
@@ -231,16 +218,30 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         (generator.validator_idx, generator.validator)
     }
 
+    /// Whether a validator has compounding (0x02) withdrawal credentials, which from Electra raise
+    /// its maximum effective balance from 32 to 2048 ETH.
+    pub fn beacon_has_compounding_withdrawal_credential(
+        &mut self,
+        validator: &BeaconValidatorVariable,
+    ) -> BoolVariable {
+        let prefix = self.constant::<ByteVariable>(COMPOUNDING_WITHDRAWAL_PREFIX);
+        self.is_equal(validator.withdrawal_credentials.0 .0[0], prefix)
+    }
+
     /// Get the balances for a given block root.
     pub fn beacon_get_balances(&mut self, block_root: Bytes32Variable) -> BeaconBalancesVariable {
-        let generator =
-            BeaconBalancesGenerator::new(self, self.beacon_client.clone().unwrap(), block_root);
+        let generator = BeaconBalancesGenerator::new(
+            self,
+            self.beacon_client.clone().unwrap(),
+            block_root,
+            self.beacon_fork.block_root_field_depth(),
+        );
         self.add_simple_generator(generator.clone());
         self.ssz_verify_proof_const(
             block_root,
             generator.balances_root,
             &generator.proof,
-            BALANCES_ROOT_GINDEX,
+            self.beacon_fork.block_root_field_gindex(BALANCES_FIELD),
         );
         BeaconBalancesVariable {
             block_root,
@@ -254,20 +255,22 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         block_root: Bytes32Variable,
     ) -> BeaconBalancesVariable {
         let b_log2 = (B as f64).log2().ceil() as usize;
-        let hint = BeaconPartialBalancesHint::<B> {};
+        let depth = self.beacon_fork.block_root_field_depth();
+        let hint = BeaconPartialBalancesHint::<B> { depth };
         let mut input_stream = VariableStream::new();
         input_stream.write(&block_root);
 
         let output_stream = self.async_hint(input_stream, hint);
         let partial_balances_root = output_stream.read::<Bytes32Variable>(self);
-        let nb_branches = BALANCES_PROOF_DEPTH + (VALIDATOR_REGISTRY_LIMIT_LOG2 + 1 - b_log2);
+        let nb_branches = depth + (VALIDATOR_REGISTRY_LIMIT_LOG2 + 1 - b_log2);
         let mut proof = Vec::new();
         for _ in 0..nb_branches {
             proof.push(output_stream.read::<Bytes32Variable>(self));
         }
 
-        let gindex = BALANCES_GINDEX * (2usize.pow(41 - b_log2 as u32));
-        let gindex = concat_g_indices(&[STATE_ROOT_GINDEX as usize, gindex]);
+        let gindex =
+            self.beacon_fork.state_field_gindex(BALANCES_FIELD) * (2usize.pow(41 - b_log2 as u32));
+        let gindex = concat_g_indices(&[STATE_ROOT_GINDEX, gindex]);
         self.ssz_verify_proof_const(block_root, partial_balances_root, &proof, gindex as u64);
         BeaconBalancesVariable {
             block_root,
@@ -422,6 +425,74 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         generator.balance
     }
 
+    /// Proves the sum of the balances of the validators at the first `nb_indices` of `indices`
+    /// against the balances root. These indices must be strictly increasing, so that no validator
+    /// is counted twice, and the remaining indices are ignored.
+    pub fn beacon_sum_balances<const N: usize>(
+        &mut self,
+        balances: BeaconBalancesVariable,
+        indices: ArrayVariable<U64Variable, N>,
+        nb_indices: U64Variable,
+    ) -> U64Variable {
+        let max_indices = self.constant::<U64Variable>(N as u64);
+        let in_bounds = self.lte(nb_indices, max_indices);
+        let true_v = self._true();
+        self.assert_is_equal(in_bounds, true_v);
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&balances.block_root);
+        input_stream.write(&nb_indices);
+        input_stream.write(&indices);
+        let output_stream = self.hint(input_stream, BeaconBalanceSumHint::<N> {});
+
+        let base_gindex = self.constant::<U64Variable>(BALANCE_BASE_GINDEX);
+        let four = self.constant::<U64Variable>(4);
+        let mut sum = self.constant::<U64Variable>(0);
+        let mut enabled = self._true();
+        for i in 0..N {
+            let i_v = self.constant::<U64Variable>(i as u64);
+            let is_end = self.is_equal(nb_indices, i_v);
+            let not_end = self.not(is_end);
+            enabled = self.and(enabled, not_end);
+            let disabled = self.not(enabled);
+
+            let index = indices[i];
+            if i > 0 {
+                let increasing = self.lt(indices[i - 1], index);
+                let holds = self.or(disabled, increasing);
+                self.assert_is_equal(holds, true_v);
+            }
+
+            // Four balances are packed in each leaf of the balances tree.
+            let leaf = output_stream.read::<Bytes32Variable>(self);
+            let proof = (0..BALANCE_PROOF_DEPTH)
+                .map(|_| output_stream.read::<Bytes32Variable>(self))
+                .collect::<Vec<_>>();
+            let offset = self.div(index, four);
+            let gindex = self.add(base_gindex, offset);
+            let root = self.ssz_restore_merkle_root(leaf, &proof, gindex);
+            let is_root = self.is_equal(root, balances.root);
+            let holds = self.or(disabled, is_root);
+            self.assert_is_equal(holds, true_v);
+
+            let position = self.rem(index, four);
+            let bits = self.to_le_bits(position);
+            let first_half = BytesVariable::<16>(leaf.0 .0[..16].try_into().unwrap());
+            let second_half = BytesVariable::<16>(leaf.0 .0[16..].try_into().unwrap());
+            let half = self.select(bits[1], second_half, first_half);
+            let first_quarter = BytesVariable::<8>(half.0[..8].try_into().unwrap());
+            let second_quarter = BytesVariable::<8>(half.0[8..].try_into().unwrap());
+            let quarter = self.select(bits[0], second_quarter, first_quarter);
+            let balance =
+                U64Variable::from_little_endian(&OrderedBytesVariable::new(quarter), self);
+
+            let zero = self.constant::<U64Variable>(0);
+            let balance = self.select(enabled, balance, zero);
+            sum = self.add(sum, balance);
+        }
+        sum
+    }
+
     /// Get the withdrawals for a given block root.
     pub fn beacon_get_withdrawals(
         &mut self,
@@ -464,14 +535,8 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             .collect::<Vec<_>>();
         let items_root = self.ssz_hash_leafs(&leafs);
 
-        // SSZ lists encoded as [items_root, list_length]
-        // List length is u256 LE
-        let mut list_length = vec![0u8; 32];
-        U256::from(16).to_little_endian(&mut list_length);
-        let list_length_array: [u8; 32] = list_length.try_into().unwrap();
-        let list_length_variable = self.constant::<Bytes32Variable>(H256::from(list_length_array));
-
-        let reconstructed_root = self.ssz_hash_leafs(&[items_root, list_length_variable]);
+        let list_length = self.constant::<U64Variable>(MAX_WITHDRAWALS_PER_PAYLOAD as u64);
+        let reconstructed_root = self.ssz_mix_in_length(items_root, list_length);
         self.assert_is_equal(withdrawals_variable.withdrawals_root, reconstructed_root);
 
         withdrawals
@@ -520,16 +585,23 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let mut hint_input = VariableStream::new();
         hint_input.write(&block_root);
         hint_input.write(&target_slot);
-        let hint_output = self.async_hint(hint_input, BeaconHistoricalBlockHint {});
+        let hint = BeaconHistoricalBlockHint {
+            depth: self.beacon_fork.block_root_field_depth(),
+        };
+        let close_slot_block_root_depth = hint.close_slot_block_root_depth();
+        let far_slot_historical_summary_depth = hint.far_slot_historical_summary_depth();
+        let hint_output = self.async_hint(hint_input, hint);
 
         let target_block_root = hint_output.read::<Bytes32Variable>(self);
-        let close_slot_block_root_proof =
-            hint_output.read::<ArrayVariable<Bytes32Variable, CLOSE_SLOT_BLOCK_ROOT_DEPTH>>(self);
+        let close_slot_block_root_proof = (0..close_slot_block_root_depth)
+            .map(|_| hint_output.read::<Bytes32Variable>(self))
+            .collect::<Vec<_>>();
         let far_slot_block_root_proof =
             hint_output.read::<ArrayVariable<Bytes32Variable, FAR_SLOT_BLOCK_ROOT_DEPTH>>(self);
         let far_slot_historical_summary_root = hint_output.read::<Bytes32Variable>(self);
-        let far_slot_historical_summary_proof = hint_output
-            .read::<ArrayVariable<Bytes32Variable, FAR_SLOT_HISTORICAL_SUMMARY_DEPTH>>(self);
+        let far_slot_historical_summary_proof = (0..far_slot_historical_summary_depth)
+            .map(|_| hint_output.read::<Bytes32Variable>(self))
+            .collect::<Vec<_>>();
 
         // Use close slot logic if (source - target) < 8192
         let source_sub_target = self.sub(source_slot, target_slot);
@@ -541,13 +613,15 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let block_roots_array_index = self.rem(target_slot, slots_per_historical);
 
         // Close slot logic
+        // The gindex for blockRoot -> state -> state.block_roots[0].
+        let block_roots_gindex = self.beacon_fork.block_root_field_gindex(BLOCK_ROOTS_FIELD);
         let mut close_slot_block_root_gindex =
-            self.constant::<U64Variable>(CLOSE_SLOT_BLOCK_ROOT_GINDEX);
+            self.constant::<U64Variable>(block_roots_gindex * SLOTS_PER_HISTORICAL_ROOT as u64);
         close_slot_block_root_gindex =
             self.add(close_slot_block_root_gindex, block_roots_array_index);
         let restored_close_slot_block_root = self.ssz_restore_merkle_root(
             target_block_root,
-            &close_slot_block_root_proof.as_vec(),
+            &close_slot_block_root_proof,
             close_slot_block_root_gindex,
         );
         let valid_close_slot = self.is_equal(restored_close_slot_block_root, block_root);
@@ -556,13 +630,19 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let capella_slot = self.constant::<U64Variable>(CAPELLA_FORK_EPOCH * SLOTS_PER_EPOCH);
         let slots_since_capella = self.sub(target_slot, capella_slot);
         let historical_summary_array_index = self.div(slots_since_capella, slots_per_historical);
-        let mut historical_summary_gindex =
-            self.constant::<U64Variable>(HISTORICAL_SUMMARIES_BASE_GINDEX);
+        // The gindex for blockRoot -> state -> state.historical_summaries[0], past the length
+        // mix-in.
+        let historical_summaries_gindex = self
+            .beacon_fork
+            .block_root_field_gindex(HISTORICAL_SUMMARIES_FIELD);
+        let mut historical_summary_gindex = self.constant::<U64Variable>(
+            (historical_summaries_gindex * 2) << HISTORICAL_ROOTS_LIMIT_LOG2,
+        );
         historical_summary_gindex =
             self.add(historical_summary_gindex, historical_summary_array_index);
         let restored_far_slot_block_root = self.ssz_restore_merkle_root(
             far_slot_historical_summary_root,
-            &far_slot_historical_summary_proof.as_vec(),
+            &far_slot_historical_summary_proof,
             historical_summary_gindex,
         );
         let valid_far_slot_block_root = self.is_equal(restored_far_slot_block_root, block_root);
@@ -595,16 +675,19 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     ) -> ArrayVariable<Bytes32Variable, SLOTS_PER_HISTORICAL_ROOT> {
         let mut input = VariableStream::new();
         input.write(&block_root);
-        let output = self.hint(input, BeaconBlockRootsHint {});
+        let depth = self.beacon_fork.block_root_field_depth();
+        let output = self.hint(input, BeaconBlockRootsHint { depth });
         let block_roots_root = output.read::<Bytes32Variable>(self);
-        let proof = output.read::<ArrayVariable<Bytes32Variable, BLOCK_ROOTS_PROOF_DEPTH>>(self);
+        let proof = (0..depth)
+            .map(|_| output.read::<Bytes32Variable>(self))
+            .collect::<Vec<_>>();
         let block_roots =
             output.read::<ArrayVariable<Bytes32Variable, SLOTS_PER_HISTORICAL_ROOT>>(self);
         self.ssz_verify_proof_const(
             block_root,
             block_roots_root,
-            proof.as_slice(),
-            BLOCK_ROOTS_GINDEX as u64,
+            &proof,
+            self.beacon_fork.block_root_field_gindex(BLOCK_ROOTS_FIELD),
         );
         let root = self.ssz_hash_leafs(block_roots.as_slice());
         self.assert_is_equal(root, block_roots_root);
@@ -626,6 +709,110 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         graffiti
     }
 
+    /// Reads a field root and its proof from blockRoot from the output of a hint, and verifies it
+    /// against the Electra layout of the beacon state.
+    fn beacon_verify_electra_field(
+        &mut self,
+        block_root: Bytes32Variable,
+        output: &OutputVariableStream<L, D>,
+        field: usize,
+    ) -> Bytes32Variable {
+        assert_eq!(
+            self.beacon_fork,
+            BeaconFork::Electra,
+            "the field only exists in the beacon state from Electra"
+        );
+        let root = output.read::<Bytes32Variable>(self);
+        let proof = (0..self.beacon_fork.block_root_field_depth())
+            .map(|_| output.read::<Bytes32Variable>(self))
+            .collect::<Vec<_>>();
+        let gindex = self.beacon_fork.block_root_field_gindex(field);
+        self.ssz_verify_proof_const(block_root, root, &proof, gindex);
+        root
+    }
+
+    /// Get the pending deposits for a given block root. Requires the Electra fork.
+    pub fn beacon_get_pending_deposits(
+        &mut self,
+        block_root: Bytes32Variable,
+    ) -> BeaconPendingDepositsVariable {
+        let mut input = VariableStream::new();
+        input.write(&block_root);
+        let output = self.hint(input, BeaconPendingDepositsHint {});
+        let pending_deposits_root =
+            self.beacon_verify_electra_field(block_root, &output, PENDING_DEPOSITS_FIELD);
+        BeaconPendingDepositsVariable {
+            block_root,
+            pending_deposits_root,
+        }
+    }
+
+    /// Get a pending deposit from a given index, which must be within the queue.
+    pub fn beacon_get_pending_deposit(
+        &mut self,
+        deposits: BeaconPendingDepositsVariable,
+        idx: U64Variable,
+    ) -> BeaconPendingDepositVariable {
+        let mut input = VariableStream::new();
+        input.write(&deposits.block_root);
+        input.write(&idx);
+        let output = self.hint(input, BeaconPendingDepositHint {});
+        let deposit = output.read::<BeaconPendingDepositVariable>(self);
+        let proof = output
+            .read::<ArrayVariable<Bytes32Variable, { PENDING_DEPOSITS_LIMIT_LOG2 + 1 }>>(self);
+        let leaf = deposit.hash_tree_root(self);
+        self.ssz_verify_list_proof::<PENDING_DEPOSITS_LIMIT_LOG2>(
+            deposits.pending_deposits_root,
+            leaf,
+            proof.as_slice(),
+            idx,
+            1,
+        );
+        deposit
+    }
+
+    /// Get the pending consolidations for a given block root. Requires the Electra fork.
+    pub fn beacon_get_pending_consolidations(
+        &mut self,
+        block_root: Bytes32Variable,
+    ) -> BeaconPendingConsolidationsVariable {
+        let mut input = VariableStream::new();
+        input.write(&block_root);
+        let output = self.hint(input, BeaconPendingConsolidationsHint {});
+        let pending_consolidations_root =
+            self.beacon_verify_electra_field(block_root, &output, PENDING_CONSOLIDATIONS_FIELD);
+        BeaconPendingConsolidationsVariable {
+            block_root,
+            pending_consolidations_root,
+        }
+    }
+
+    /// Get a pending consolidation from a given index, which must be within the queue.
+    pub fn beacon_get_pending_consolidation(
+        &mut self,
+        consolidations: BeaconPendingConsolidationsVariable,
+        idx: U64Variable,
+    ) -> BeaconPendingConsolidationVariable {
+        let mut input = VariableStream::new();
+        input.write(&consolidations.block_root);
+        input.write(&idx);
+        let output = self.hint(input, BeaconPendingConsolidationHint {});
+        let consolidation = output.read::<BeaconPendingConsolidationVariable>(self);
+        let proof = output
+            .read::<ArrayVariable<Bytes32Variable, { PENDING_CONSOLIDATIONS_LIMIT_LOG2 + 1 }>>(
+                self,
+            );
+        let leaf = consolidation.hash_tree_root(self);
+        self.ssz_verify_list_proof::<PENDING_CONSOLIDATIONS_LIMIT_LOG2>(
+            consolidations.pending_consolidations_root,
+            leaf,
+            proof.as_slice(),
+            idx,
+            1,
+        );
+        consolidation
+    }
+
     pub fn beacon_witness_headers_from_offset_range<const B: usize>(
         &mut self,
         end_block_root: Bytes32Variable,
@@ -640,6 +827,53 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         output.read::<ArrayVariable<Bytes32Variable, B>>(self)
     }
 
+    /// Verifies the next sync committee against the state root of a finalized header, and that
+    /// its aggregate pubkey is the sum of its pubkeys. The branch is the proof of
+    /// stateRoot -> state.next_sync_committee for the fork configured on the builder.
+    ///
+    /// Returns the decompressed pubkeys of the committee, against which the sync aggregates of the
+    /// next period are verified with `bls12_381_fast_aggregate_verify`.
+    pub fn beacon_verify_next_sync_committee<const SIZE: usize>(
+        &mut self,
+        finalized_header: &BeaconHeaderVariable,
+        committee: &SyncCommitteeVariable<SIZE>,
+        branch: &[Bytes32Variable],
+    ) -> Vec<G1AffineVariable> {
+        assert_eq!(
+            branch.len(),
+            self.beacon_fork.state_tree_depth(),
+            "unexpected branch length"
+        );
+        let committee_root = committee.hash_tree_root(self);
+        let gindex = self
+            .beacon_fork
+            .state_field_gindex(NEXT_SYNC_COMMITTEE_FIELD);
+        self.ssz_verify_proof_const(
+            finalized_header.state_root,
+            committee_root,
+            branch,
+            gindex as u64,
+        );
+
+        let pubkeys = committee
+            .pubkeys
+            .as_slice()
+            .iter()
+            .map(|pubkey| self.bls12_381_g1_decompress(&pubkey.0 .0))
+            .collect::<Vec<_>>();
+        let participation = vec![self._true(); SIZE];
+        let sum = self.bls12_381_aggregate_pubkeys(&pubkeys, &participation);
+        let aggregate_pubkey = self.bls12_381_g1_decompress(&committee.aggregate_pubkey.0 .0);
+        let true_v = self._true();
+        for (a, b) in [(&sum.x, &aggregate_pubkey.x), (&sum.y, &aggregate_pubkey.y)] {
+            let difference = self.fp_sub(a, b);
+            let is_equal = self.fp_is_zero(&difference);
+            self.assert_is_equal(is_equal, true_v);
+        }
+
+        pubkeys
+    }
+
     /// Verify a simple serialize (ssz) merkle proof with a dynamic index.
     #[allow(unused_variables)]
     pub fn ssz_verify_proof(
@@ -715,20 +949,56 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         hash
     }
 
-    pub fn ssz_hash_tree_root<V: SSZVariable>(&mut self, variable: V) -> Bytes32Variable {
-        variable.hash_tree_root(self)
-    }
+    /// Verifies a proof of the chunk at `index` of an SSZ `List` whose items tree has depth
+    /// `DEPTH`, against the root of the list (i.e. including the length mix-in), and returns the
+    /// length of the list.
+    ///
+    /// The branch is the one returned by the beacon API for the chunk's gindex in the list: the
+    /// `DEPTH` siblings in the items tree followed by the length chunk. The chunk must hold items
+    /// that exist, i.e. `index * items_per_chunk < length`, where `items_per_chunk` is 1 for
+    /// lists of containers and `32 / size` for lists of basic types of `size` bytes.
+    pub fn ssz_verify_list_proof<const DEPTH: usize>(
+        &mut self,
+        list_root: Bytes32Variable,
+        leaf: Bytes32Variable,
+        branch: &[Bytes32Variable],
+        index: U64Variable,
+        items_per_chunk: u64,
+    ) -> U64Variable {
+        assert_eq!(
+            branch.len(),
+            DEPTH + 1,
+            "the branch must contain the length chunk"
+        );
 
-    pub fn ssz_hash_leafs(&mut self, leafs: &[Bytes32Variable]) -> Bytes32Variable {
-        let mut leafs = leafs.to_vec();
-        while leafs.len() != 1 {
-            let mut tmp = Vec::new();
-            for i in 0..leafs.len() / 2 {
-                tmp.push(self.curta_sha256_pair(leafs[i * 2], leafs[i * 2 + 1]));
-            }
-            leafs = tmp;
+        // The index must fit in the items tree.
+        let false_v = self._false();
+        let index_bits = self.to_le_bits(index);
+        for bit in index_bits[DEPTH..].iter() {
+            self.assert_is_equal(*bit, false_v);
+        }
+        let items_root = self.ssz_restore_merkle_root(leaf, &branch[..DEPTH], index);
+
+        // The length chunk is a little endian uint256, of which only the low 8 bytes can be set.
+        let length_chunk = branch[DEPTH];
+        let zero = self.constant::<ByteVariable>(0);
+        for byte in length_chunk.0 .0[8..].iter() {
+            self.assert_is_equal(*byte, zero);
         }
-        leafs[0]
+        let length_bytes = BytesVariable::<8>(length_chunk.0 .0[..8].try_into().unwrap());
+        let length =
+            U64Variable::from_little_endian(&OrderedBytesVariable::new(length_bytes), self);
+
+        let expected_root = self.curta_sha256_pair(items_root, length_chunk);
+        self.assert_is_equal(list_root, expected_root);
+
+        let items_per_chunk = self.constant::<U64Variable>(items_per_chunk);
+        let first_item = self.mul(index, items_per_chunk);
+        let in_bounds = self.lt(first_item, length);
+        let true_v = self._true();
+        self.assert_is_equal(in_bounds, true_v);
+
+        length
     }
 }
 
@@ -736,14 +1006,23 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 pub(crate) mod tests {
     use std::env;
 
+    use ethers::types::H256;
     use log::debug;
+    use num_bigint::BigUint;
 
     use crate::backend::circuit::DefaultParameters;
     use crate::frontend::builder::CircuitBuilder;
+    use crate::frontend::ecc::bls12_381::native::{fr_modulus, G1Affine};
+    use crate::frontend::eth::beacon::fork::{BeaconFork, NEXT_SYNC_COMMITTEE_FIELD};
+    use crate::frontend::eth::beacon::vars::{
+        BeaconHeaderValue, BeaconHeaderVariable, SyncCommitteeValue, SyncCommitteeVariable,
+    };
     use crate::frontend::eth::vars::BLSPubkeyVariable;
     use crate::frontend::uint::uint64::U64Variable;
     use crate::frontend::vars::Bytes32Variable;
+    use crate::prelude::ArrayVariable;
     use crate::utils::eth::beacon::BeaconClient;
+    use crate::utils::hash::sha256;
     use crate::utils::{bytes, bytes32};
 
     type L = DefaultParameters;
@@ -983,6 +1262,106 @@ pub(crate) mod tests {
         circuit.test_default_serializers();
     }
 
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_beacon_sum_balances() {
+        env_logger::try_init().unwrap_or_default();
+        dotenv::dotenv().ok();
+
+        let consensus_rpc = env::var("CONSENSUS_RPC_URL").unwrap();
+        let client = BeaconClient::new(consensus_rpc);
+        let latest_block_root = client.get_finalized_block_root().unwrap();
+        let indices = [1u64, 7, 12, 0];
+        let nb_indices = 3;
+        let expected_sum: u64 = indices[..nb_indices]
+            .iter()
+            .map(|index| {
+                client
+                    .get_validator_balance_v2(latest_block_root.clone(), *index)
+                    .unwrap()
+                    .balance
+            })
+            .sum();
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        builder.set_beacon_client(client);
+
+        let block_root = builder.constant::<Bytes32Variable>(bytes32!(latest_block_root));
+        let balances = builder.beacon_get_balances(block_root);
+        let indices = builder.constant::<ArrayVariable<U64Variable, 4>>(indices.to_vec());
+        let nb_indices = builder.constant::<U64Variable>(nb_indices as u64);
+        let sum = builder.beacon_sum_balances(balances, indices, nb_indices);
+        builder.write(sum);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<U64Variable>(), expected_sum);
+        circuit.test_default_serializers();
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_beacon_verify_next_sync_committee() {
+        const SIZE: usize = 4;
+        let pair = |left: [u8; 32], right: [u8; 32]| sha256(&[left, right].concat());
+        let pubkey_root = |pubkey: &[u8; 48]| sha256(&[&pubkey[..], &[0u8; 16]].concat());
+
+        let secrets = (0..SIZE as u32)
+            .map(|i| BigUint::from_bytes_be(&sha256(&i.to_be_bytes())) % fr_modulus())
+            .collect::<Vec<_>>();
+        let pubkeys = secrets
+            .iter()
+            .map(|secret| G1Affine::generator().scalar_mul(secret).unwrap().compress())
+            .collect::<Vec<_>>();
+        let aggregate_secret = secrets.iter().sum::<BigUint>() % fr_modulus();
+        let aggregate_pubkey = G1Affine::generator()
+            .scalar_mul(&aggregate_secret)
+            .unwrap()
+            .compress();
+
+        let roots = pubkeys.iter().map(pubkey_root).collect::<Vec<_>>();
+        let pubkeys_root = pair(pair(roots[0], roots[1]), pair(roots[2], roots[3]));
+        let committee_root = pair(pubkeys_root, pubkey_root(&aggregate_pubkey));
+
+        // Place the committee at the gindex of next_sync_committee in a Deneb state.
+        let gindex = BeaconFork::Deneb.state_field_gindex(NEXT_SYNC_COMMITTEE_FIELD);
+        let branch = (0..5u8).map(|i| sha256(&[i])).collect::<Vec<_>>();
+        let mut state_root = committee_root;
+        for (i, sibling) in branch.iter().enumerate() {
+            state_root = if (gindex >> i) & 1 == 1 {
+                pair(*sibling, state_root)
+            } else {
+                pair(state_root, *sibling)
+            };
+        }
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let header = builder.constant::<BeaconHeaderVariable>(BeaconHeaderValue {
+            slot: 8192,
+            proposer_index: 7,
+            parent_root: H256::repeat_byte(1),
+            state_root: state_root.into(),
+            body_root: H256::repeat_byte(2),
+        });
+        let committee = builder.read::<SyncCommitteeVariable<SIZE>>();
+        let branch = branch
+            .iter()
+            .map(|node| builder.constant::<Bytes32Variable>((*node).into()))
+            .collect::<Vec<_>>();
+        builder.beacon_verify_next_sync_committee(&header, &committee, &branch);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<SyncCommitteeVariable<SIZE>>(SyncCommitteeValue {
+            pubkeys,
+            aggregate_pubkey,
+        });
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
     #[test]
     #[cfg_attr(feature = "ci", ignore)]
     fn test_beacon_get_withdrawals() {
@@ -1275,4 +1654,41 @@ pub(crate) mod tests {
         circuit.verify(&proof, &input, &output);
         circuit.test_default_serializers();
     }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ssz_verify_list_proof() {
+        env_logger::try_init().unwrap_or_default();
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+
+        let leaf = builder.constant::<Bytes32Variable>(bytes32!(
+            "0xa1b2c3d4e5f60718291a2b3c4d5e6f708192a2b3c4d5e6f7a1b2c3d4e5f60718"
+        ));
+        let index = builder.constant::<U64Variable>(2);
+        let branch = vec![
+            builder.constant::<Bytes32Variable>(bytes32!(
+                "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+            )),
+            builder.constant::<Bytes32Variable>(bytes32!(
+                "0xfedcba0987654321fedcba0987654321fedcba0987654321fedcba0987654321"
+            )),
+            // The length mix-in, for a list of 3 items.
+            builder.constant::<Bytes32Variable>(bytes32!(
+                "0x0300000000000000000000000000000000000000000000000000000000000000"
+            )),
+        ];
+        let list_root = builder.constant::<Bytes32Variable>(bytes32!(
+            "0x63642ec2ac7bd297352c3038a3a3b861e606c0f8b932892a6922db15a63d30ed"
+        ));
+
+        let length = builder.ssz_verify_list_proof::<2>(list_root, leaf, &branch, index, 1);
+        builder.write(length);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<U64Variable>(), 3);
+    }
 }
@@ -0,0 +1,122 @@
+//! The layouts of the beacon state across forks.
+//!
+//! Electra grows the beacon state from 28 to 37 fields, which adds a level to its merkle tree and
+//! moves every field to a new generalized index. Gadgets that prove fields of the state look up
+//! their gindices through the fork configured on the builder.
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::eth::concat_g_indices;
+
+/// The gindex for blockRoot -> stateRoot.
+pub(crate) const STATE_ROOT_GINDEX: usize = 11;
+
+/// The depth of the proof from blockRoot -> stateRoot.
+const STATE_ROOT_PROOF_DEPTH: usize = 3;
+
+/// The index of `block_roots` in the beacon state.
+pub(crate) const BLOCK_ROOTS_FIELD: usize = 5;
+
+/// The index of `validators` in the beacon state.
+pub(crate) const VALIDATORS_FIELD: usize = 11;
+
+/// The index of `balances` in the beacon state.
+pub(crate) const BALANCES_FIELD: usize = 12;
+
+/// The index of `next_sync_committee` in the beacon state.
+pub(crate) const NEXT_SYNC_COMMITTEE_FIELD: usize = 23;
+
+/// The index of `historical_summaries` in the beacon state.
+pub(crate) const HISTORICAL_SUMMARIES_FIELD: usize = 27;
+
+/// The index of `pending_deposits` in the Electra beacon state.
+pub(crate) const PENDING_DEPOSITS_FIELD: usize = 34;
+
+/// The index of `pending_consolidations` in the Electra beacon state.
+pub(crate) const PENDING_CONSOLIDATIONS_FIELD: usize = 36;
+
+/// Beacon chain constant ELECTRA_FORK_EPOCH (mainnet specific).
+pub const ELECTRA_FORK_EPOCH: u64 = 364032;
+
+/// A fork of the beacon chain with a distinct beacon state layout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BeaconFork {
+    #[default]
+    Deneb,
+    Electra,
+}
+
+impl BeaconFork {
+    /// The fork active at the given epoch on mainnet.
+    pub fn at_epoch(epoch: u64) -> Self {
+        if epoch >= ELECTRA_FORK_EPOCH {
+            BeaconFork::Electra
+        } else {
+            BeaconFork::Deneb
+        }
+    }
+
+    /// The depth of the merkle tree of the beacon state.
+    pub fn state_tree_depth(&self) -> usize {
+        match self {
+            BeaconFork::Deneb => 5,
+            BeaconFork::Electra => 6,
+        }
+    }
+
+    /// The gindex for stateRoot -> state.fields[field].
+    pub fn state_field_gindex(&self, field: usize) -> usize {
+        assert!(field < 1 << self.state_tree_depth(), "field out of range");
+        (1 << self.state_tree_depth()) + field
+    }
+
+    /// The gindex for blockRoot -> state.fields[field].
+    pub fn block_root_field_gindex(&self, field: usize) -> u64 {
+        concat_g_indices(&[STATE_ROOT_GINDEX, self.state_field_gindex(field)]) as u64
+    }
+
+    /// The depth of the proof from blockRoot -> state.fields[field].
+    pub fn block_root_field_depth(&self) -> usize {
+        STATE_ROOT_PROOF_DEPTH + self.state_tree_depth()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beacon_fork_gindices() {
+        let deneb = BeaconFork::Deneb;
+        assert_eq!(deneb.state_field_gindex(VALIDATORS_FIELD), 43);
+        assert_eq!(deneb.block_root_field_gindex(VALIDATORS_FIELD), 363);
+        assert_eq!(deneb.block_root_field_gindex(BALANCES_FIELD), 364);
+        assert_eq!(deneb.block_root_field_gindex(BLOCK_ROOTS_FIELD), 357);
+        assert_eq!(
+            deneb.block_root_field_gindex(HISTORICAL_SUMMARIES_FIELD),
+            379
+        );
+        assert_eq!(deneb.state_field_gindex(NEXT_SYNC_COMMITTEE_FIELD), 55);
+        assert_eq!(deneb.block_root_field_depth(), 8);
+
+        let electra = BeaconFork::Electra;
+        assert_eq!(electra.state_field_gindex(VALIDATORS_FIELD), 75);
+        assert_eq!(electra.block_root_field_gindex(VALIDATORS_FIELD), 715);
+        assert_eq!(electra.block_root_field_gindex(BALANCES_FIELD), 716);
+        assert_eq!(electra.block_root_field_gindex(BLOCK_ROOTS_FIELD), 709);
+        assert_eq!(
+            electra.block_root_field_gindex(HISTORICAL_SUMMARIES_FIELD),
+            731
+        );
+        assert_eq!(electra.block_root_field_gindex(PENDING_DEPOSITS_FIELD), 738);
+        assert_eq!(
+            electra.block_root_field_gindex(PENDING_CONSOLIDATIONS_FIELD),
+            740
+        );
+        assert_eq!(electra.state_field_gindex(NEXT_SYNC_COMMITTEE_FIELD), 87);
+        assert_eq!(electra.block_root_field_depth(), 9);
+
+        assert_eq!(BeaconFork::at_epoch(ELECTRA_FORK_EPOCH - 1), deneb);
+        assert_eq!(BeaconFork::at_epoch(ELECTRA_FORK_EPOCH), electra);
+    }
+}
@@ -0,0 +1,49 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::uint::uint64::U64Variable;
+use crate::frontend::vars::ValueStream;
+use crate::prelude::{ArrayVariable, Bytes32Variable, PlonkParameters};
+use crate::utils::eth::beacon::BeaconClient;
+use crate::utils::{bytes32, hex};
+
+/// The depth of the proof from balancesRoot -> balances[i], including the length mix-in.
+pub const BALANCE_PROOF_DEPTH: usize = 39;
+
+/// Witnesses the balance leaves and their proofs against the balances root for the first
+/// `nb_indices` of `N` validator indices. The leaves and proofs of the remaining indices are
+/// zeros, so the padding of the indices does not have to be existing validators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeaconBalanceSumHint<const N: usize> {}
+
+impl<L: PlonkParameters<D>, const D: usize, const N: usize> Hint<L, D> for BeaconBalanceSumHint<N> {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let client = BeaconClient::new(env::var("CONSENSUS_RPC_URL").unwrap());
+        let block_root = input_stream.read_value::<Bytes32Variable>();
+        let nb_indices = input_stream.read_value::<U64Variable>();
+        let indices = input_stream.read_value::<ArrayVariable<U64Variable, N>>();
+
+        for (i, index) in indices.into_iter().enumerate() {
+            if (i as u64) < nb_indices {
+                let response = client
+                    .get_validator_balance_v2(hex!(block_root), index)
+                    .unwrap();
+                assert_eq!(
+                    response.proof.len(),
+                    BALANCE_PROOF_DEPTH,
+                    "unexpected proof length"
+                );
+                output_stream.write_value::<Bytes32Variable>(bytes32!(response.balance_leaf));
+                for node in response.proof.iter() {
+                    output_stream.write_value::<Bytes32Variable>(bytes32!(node));
+                }
+            } else {
+                for _ in 0..BALANCE_PROOF_DEPTH + 1 {
+                    output_stream.write_value::<Bytes32Variable>(Default::default());
+                }
+            }
+        }
+    }
+}
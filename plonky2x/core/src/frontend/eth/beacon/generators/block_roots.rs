@@ -11,10 +11,12 @@ use crate::utils::eth::beacon::BeaconClient;
 use crate::utils::{bytes32, hex};
 
 const NB_BLOCK_ROOTS: usize = 8192;
-const DEPTH: usize = 8;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BeaconBlockRootsHint;
+pub struct BeaconBlockRootsHint {
+    /// The depth of the proof from blockRoot -> blockRoots, which depends on the fork.
+    pub depth: usize,
+}
 
 impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for BeaconBlockRootsHint {
     fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
@@ -22,9 +24,10 @@ impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for BeaconBlockRootsHint
         let header_root = input_stream.read_value::<Bytes32Variable>();
         let response = client.get_block_roots(hex!(header_root)).unwrap();
         output_stream.write_value::<Bytes32Variable>(bytes32!(response.block_roots_root));
-        output_stream.write_value::<ArrayVariable<Bytes32Variable, DEPTH>>(
-            response.proof.iter().map(|p| bytes32!(p)).collect_vec(),
-        );
+        assert_eq!(response.proof.len(), self.depth, "unexpected proof length");
+        for node in response.proof.iter() {
+            output_stream.write_value::<Bytes32Variable>(bytes32!(node));
+        }
         output_stream.write_value::<ArrayVariable<Bytes32Variable, NB_BLOCK_ROOTS>>(
             response
                 .block_roots
@@ -11,12 +11,43 @@ use crate::prelude::{ArrayVariable, ValueStream};
 use crate::utils::eth::beacon::BeaconClient;
 use crate::utils::{bytes32, hex};
 
-pub const CLOSE_SLOT_BLOCK_ROOT_DEPTH: usize = 21;
-pub const FAR_SLOT_HISTORICAL_SUMMARY_DEPTH: usize = 33;
+/// The log2 of Beacon chain constant SLOTS_PER_HISTORICAL_ROOT.
+pub const SLOTS_PER_HISTORICAL_ROOT_LOG2: usize = 13;
+
+/// The log2 of Beacon chain constant HISTORICAL_ROOTS_LIMIT.
+pub const HISTORICAL_ROOTS_LIMIT_LOG2: usize = 24;
+
 pub const FAR_SLOT_BLOCK_ROOT_DEPTH: usize = 14;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BeaconHistoricalBlockHint {}
+pub struct BeaconHistoricalBlockHint {
+    /// The depth of the proof from blockRoot -> state.fields[field], which depends on the fork.
+    pub depth: usize,
+}
+
+impl BeaconHistoricalBlockHint {
+    /// The depth of the proof from blockRoot -> state.block_roots[i].
+    pub fn close_slot_block_root_depth(&self) -> usize {
+        self.depth + SLOTS_PER_HISTORICAL_ROOT_LOG2
+    }
+
+    /// The depth of the proof from blockRoot -> state.historical_summaries[i], including the
+    /// length mix-in.
+    pub fn far_slot_historical_summary_depth(&self) -> usize {
+        self.depth + 1 + HISTORICAL_ROOTS_LIMIT_LOG2
+    }
+}
+
+fn write_proof<L: PlonkParameters<D>, const D: usize>(
+    output_stream: &mut ValueStream<L, D>,
+    proof: &[String],
+    depth: usize,
+) {
+    assert_eq!(proof.len(), depth, "unexpected proof length");
+    for node in proof.iter() {
+        output_stream.write_value::<Bytes32Variable>(bytes32!(node));
+    }
+}
 
 #[async_trait]
 impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for BeaconHistoricalBlockHint {
@@ -35,12 +66,10 @@ impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for BeaconHistorical
             .expect("failed to get historical block root");
 
         output_stream.write_value::<Bytes32Variable>(bytes32!(result.target_block_root));
-        output_stream.write_value::<ArrayVariable<Bytes32Variable, CLOSE_SLOT_BLOCK_ROOT_DEPTH>>(
-            result
-                .close_slot_block_root_proof
-                .iter()
-                .map(|x| bytes32!(*x))
-                .collect(),
+        write_proof(
+            output_stream,
+            &result.close_slot_block_root_proof,
+            self.close_slot_block_root_depth(),
         );
         output_stream.write_value::<ArrayVariable<Bytes32Variable, FAR_SLOT_BLOCK_ROOT_DEPTH>>(
             result
@@ -51,13 +80,10 @@ impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for BeaconHistorical
         );
         output_stream
             .write_value::<Bytes32Variable>(bytes32!(result.far_slot_historical_summary_root));
-        output_stream
-            .write_value::<ArrayVariable<Bytes32Variable, FAR_SLOT_HISTORICAL_SUMMARY_DEPTH>>(
-                result
-                    .far_slot_historical_summary_proof
-                    .iter()
-                    .map(|x| bytes32!(*x))
-                    .collect(),
-            );
+        write_proof(
+            output_stream,
+            &result.far_slot_historical_summary_proof,
+            self.far_slot_historical_summary_depth(),
+        );
     }
 }
@@ -1,5 +1,6 @@
 mod all_withdrawals;
 mod balance;
+mod balance_sum;
 mod balance_witness;
 mod balances;
 mod block_roots;
@@ -9,6 +10,7 @@ mod headers;
 mod historical;
 mod partial_balances;
 mod partial_validators;
+mod pending;
 mod validator;
 mod validator_subtree;
 mod validator_witness;
@@ -17,6 +19,7 @@ mod withdrawal;
 mod withdrawals;
 pub use all_withdrawals::BeaconAllWithdrawalsHint;
 pub use balance::BeaconBalanceGenerator;
+pub use balance_sum::{BeaconBalanceSumHint, BALANCE_PROOF_DEPTH};
 pub use balance_witness::{BeaconBalanceBatchWitnessHint, BeaconBalanceWitnessHint};
 pub use balances::BeaconBalancesGenerator;
 pub use block_roots::BeaconBlockRootsHint;
@@ -24,11 +27,14 @@ pub use graffiti::BeaconGraffitiHint;
 pub use header::BeaconHeaderHint;
 pub use headers::BeaconHeadersFromOffsetRangeHint;
 pub use historical::{
-    BeaconHistoricalBlockHint, CLOSE_SLOT_BLOCK_ROOT_DEPTH, FAR_SLOT_BLOCK_ROOT_DEPTH,
-    FAR_SLOT_HISTORICAL_SUMMARY_DEPTH,
+    BeaconHistoricalBlockHint, FAR_SLOT_BLOCK_ROOT_DEPTH, HISTORICAL_ROOTS_LIMIT_LOG2,
 };
 pub use partial_balances::BeaconPartialBalancesHint;
 pub use partial_validators::BeaconPartialValidatorsHint;
+pub use pending::{
+    BeaconPendingConsolidationHint, BeaconPendingConsolidationsHint, BeaconPendingDepositHint,
+    BeaconPendingDepositsHint, PENDING_CONSOLIDATIONS_LIMIT_LOG2, PENDING_DEPOSITS_LIMIT_LOG2,
+};
 pub use validator::BeaconValidatorGenerator;
 pub use validator_subtree::{
     BeaconValidatorSubtreeHint, BeaconValidatorSubtreePoseidonHint, BeaconValidatorSubtreesHint,
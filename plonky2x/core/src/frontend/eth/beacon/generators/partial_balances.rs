@@ -10,10 +10,12 @@ use crate::utils::eth::beacon::BeaconClient;
 use crate::utils::{bytes32, hex};
 
 const VALIDATOR_REGISTRY_LIMIT_LOG2: usize = 40;
-const DEPTH: usize = 8;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BeaconPartialBalancesHint<const B: usize> {}
+pub struct BeaconPartialBalancesHint<const B: usize> {
+    /// The depth of the proof from blockRoot -> stateRoot -> balances, which depends on the fork.
+    pub depth: usize,
+}
 
 #[async_trait]
 impl<L: PlonkParameters<D>, const D: usize, const B: usize> AsyncHint<L, D>
@@ -32,7 +34,7 @@ impl<L: PlonkParameters<D>, const D: usize, const B: usize> AsyncHint<L, D>
             .unwrap();
         output_stream.write_value::<Bytes32Variable>(bytes32!(response.partial_balances_root));
         let nb_branches =
-            DEPTH + (VALIDATOR_REGISTRY_LIMIT_LOG2 + 1 - ((B as f64).log2().ceil() as usize));
+            self.depth + (VALIDATOR_REGISTRY_LIMIT_LOG2 + 1 - ((B as f64).log2().ceil() as usize));
         assert_eq!(response.proof.len(), nb_branches);
         for i in 0..nb_branches {
             output_stream.write_value::<Bytes32Variable>(bytes32!(response.proof[i]));
@@ -0,0 +1,118 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::eth::beacon::fork::BeaconFork;
+use crate::frontend::eth::beacon::vars::{
+    BeaconPendingConsolidationValue, BeaconPendingConsolidationVariable, BeaconPendingDepositValue,
+    BeaconPendingDepositVariable,
+};
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::uint::uint64::U64Variable;
+use crate::frontend::vars::{Bytes32Variable, ValueStream};
+use crate::utils::eth::beacon::BeaconClient;
+use crate::utils::{bytes, bytes32, hex};
+
+/// The log2 of Beacon chain constant PENDING_DEPOSITS_LIMIT.
+pub const PENDING_DEPOSITS_LIMIT_LOG2: usize = 27;
+
+/// The log2 of Beacon chain constant PENDING_CONSOLIDATIONS_LIMIT.
+pub const PENDING_CONSOLIDATIONS_LIMIT_LOG2: usize = 18;
+
+fn write_proof<L: PlonkParameters<D>, const D: usize>(
+    output_stream: &mut ValueStream<L, D>,
+    proof: &[String],
+    depth: usize,
+) {
+    assert_eq!(proof.len(), depth, "unexpected proof length");
+    for node in proof.iter() {
+        output_stream.write_value::<Bytes32Variable>(bytes32!(node));
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeaconPendingDepositsHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for BeaconPendingDepositsHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let client = BeaconClient::new(env::var("CONSENSUS_RPC_URL").unwrap());
+        let block_root = input_stream.read_value::<Bytes32Variable>();
+        let response = client.get_pending_deposits_root(hex!(block_root)).unwrap();
+        output_stream.write_value::<Bytes32Variable>(bytes32!(response.pending_deposits_root));
+        write_proof(
+            output_stream,
+            &response.proof,
+            BeaconFork::Electra.block_root_field_depth(),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeaconPendingDepositHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for BeaconPendingDepositHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let client = BeaconClient::new(env::var("CONSENSUS_RPC_URL").unwrap());
+        let block_root = input_stream.read_value::<Bytes32Variable>();
+        let idx = input_stream.read_value::<U64Variable>();
+        let response = client.get_pending_deposit(hex!(block_root), idx).unwrap();
+        output_stream.write_value::<BeaconPendingDepositVariable>(BeaconPendingDepositValue {
+            pubkey: bytes!(response.pending_deposit.pubkey),
+            withdrawal_credentials: bytes32!(response.pending_deposit.withdrawal_credentials),
+            amount: response.pending_deposit.amount,
+            signature: bytes!(response.pending_deposit.signature),
+            slot: response.pending_deposit.slot,
+        });
+        write_proof(
+            output_stream,
+            &response.proof,
+            PENDING_DEPOSITS_LIMIT_LOG2 + 1,
+        );
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeaconPendingConsolidationsHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for BeaconPendingConsolidationsHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let client = BeaconClient::new(env::var("CONSENSUS_RPC_URL").unwrap());
+        let block_root = input_stream.read_value::<Bytes32Variable>();
+        let response = client
+            .get_pending_consolidations_root(hex!(block_root))
+            .unwrap();
+        output_stream
+            .write_value::<Bytes32Variable>(bytes32!(response.pending_consolidations_root));
+        write_proof(
+            output_stream,
+            &response.proof,
+            BeaconFork::Electra.block_root_field_depth(),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeaconPendingConsolidationHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for BeaconPendingConsolidationHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let client = BeaconClient::new(env::var("CONSENSUS_RPC_URL").unwrap());
+        let block_root = input_stream.read_value::<Bytes32Variable>();
+        let idx = input_stream.read_value::<U64Variable>();
+        let response = client
+            .get_pending_consolidation(hex!(block_root), idx)
+            .unwrap();
+        output_stream.write_value::<BeaconPendingConsolidationVariable>(
+            BeaconPendingConsolidationValue {
+                source_index: response.pending_consolidation.source_index,
+                target_index: response.pending_consolidation.target_index,
+            },
+        );
+        write_proof(
+            output_stream,
+            &response.proof,
+            PENDING_CONSOLIDATIONS_LIMIT_LOG2 + 1,
+        );
+    }
+}
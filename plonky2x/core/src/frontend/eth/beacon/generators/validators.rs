@@ -19,11 +19,18 @@ use crate::utils::{bytes32, hex};
 pub(crate) const DEPTH: usize = 8;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BeaconValidatorsHint {}
+pub struct BeaconValidatorsHint {
+    /// The depth of the proof from blockRoot -> validatorsRoot, which depends on the fork.
+    pub depth: usize,
+}
 
 impl BeaconValidatorsHint {
     pub fn new() -> Self {
-        Self {}
+        Self { depth: DEPTH }
+    }
+
+    pub fn with_depth(depth: usize) -> Self {
+        Self { depth }
     }
 }
 
@@ -50,7 +57,8 @@ impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for BeaconValidators
         output_stream.write_value::<Bytes32Variable>(bytes32!(result.validators_root));
 
         // write proof
-        for i in 0..DEPTH {
+        assert_eq!(result.proof.len(), self.depth);
+        for i in 0..self.depth {
             output_stream.write_value::<Bytes32Variable>(bytes32!(result.proof[i]));
         }
     }
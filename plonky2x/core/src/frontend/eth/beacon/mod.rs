@@ -1,3 +1,4 @@
 pub mod builder;
+pub mod fork;
 pub mod generators;
 pub mod vars;
@@ -1,6 +1,8 @@
 mod balances;
 mod compressed_validator;
 mod header;
+mod pending;
+mod sync_committee;
 mod validator;
 mod validators;
 mod withdrawal;
@@ -9,6 +11,8 @@ mod withdrawals;
 pub use balances::*;
 pub use compressed_validator::*;
 pub use header::*;
+pub use pending::*;
+pub use sync_committee::*;
 pub use validator::*;
 pub use validators::*;
 pub use withdrawal::*;
@@ -0,0 +1,158 @@
+use std::fmt::Debug;
+
+use plonky2::hash::hash_types::RichField;
+use plonky2x_derive::CircuitVariable;
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::eth::vars::BLSPubkeyVariable;
+use crate::frontend::uint::uint64::U64Variable;
+use crate::frontend::vars::{
+    Bytes32Variable, BytesVariable, CircuitVariable, EvmVariable, SSZVariable,
+};
+use crate::prelude::{ByteVariable, Variable};
+
+/// A deposit queued in the Electra beacon state, the `PendingDeposit` container of the
+/// consensus specs.
+#[derive(Debug, Copy, Clone, CircuitVariable)]
+#[value_name(BeaconPendingDepositValue)]
+pub struct BeaconPendingDepositVariable {
+    pub pubkey: BLSPubkeyVariable,
+    pub withdrawal_credentials: Bytes32Variable,
+    pub amount: U64Variable,
+    pub signature: BytesVariable<96>,
+    pub slot: U64Variable,
+}
+
+/// A consolidation of a source validator into a target validator, queued in the Electra beacon
+/// state.
+#[derive(Debug, Copy, Clone, CircuitVariable)]
+#[value_name(BeaconPendingConsolidationValue)]
+pub struct BeaconPendingConsolidationVariable {
+    pub source_index: U64Variable,
+    pub target_index: U64Variable,
+}
+
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(BeaconPendingDepositsValue)]
+pub struct BeaconPendingDepositsVariable {
+    pub block_root: Bytes32Variable,
+    pub pending_deposits_root: Bytes32Variable,
+}
+
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(BeaconPendingConsolidationsValue)]
+pub struct BeaconPendingConsolidationsVariable {
+    pub block_root: Bytes32Variable,
+    pub pending_consolidations_root: Bytes32Variable,
+}
+
+/// The root of a container of two uint64 fields.
+fn hash_u64_pair<L: PlonkParameters<D>, const D: usize>(
+    builder: &mut CircuitBuilder<L, D>,
+    a: U64Variable,
+    b: U64Variable,
+) -> Bytes32Variable {
+    let zero = builder.constant::<ByteVariable>(0);
+
+    let mut a_bytes = a.encode(builder);
+    a_bytes.reverse();
+    a_bytes.extend([zero; 24]);
+
+    let mut b_bytes = b.encode(builder);
+    b_bytes.reverse();
+    b_bytes.extend([zero; 24]);
+
+    let mut input = Vec::new();
+    input.extend(a_bytes);
+    input.extend(b_bytes);
+    builder.curta_sha256(&input)
+}
+
+impl SSZVariable for BeaconPendingDepositVariable {
+    fn hash_tree_root<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Bytes32Variable {
+        let field_roots = [
+            self.pubkey.hash_tree_root(builder),
+            self.withdrawal_credentials.hash_tree_root(builder),
+            self.amount.hash_tree_root(builder),
+            builder.ssz_basic_vector_root(&self.signature.0),
+            self.slot.hash_tree_root(builder),
+        ];
+        builder.ssz_container_root(&field_roots)
+    }
+}
+
+impl SSZVariable for BeaconPendingConsolidationVariable {
+    fn hash_tree_root<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Bytes32Variable {
+        hash_u64_pair(builder, self.source_index, self.target_index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ethers::types::H256;
+
+    use super::*;
+    use crate::prelude::DefaultParameters;
+    use crate::utils::hash::sha256;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    fn hash_pair(a: &[u8], b: &[u8]) -> [u8; 32] {
+        sha256(&[a, b].concat())
+    }
+
+    fn u64_leaf(value: u64) -> [u8; 32] {
+        let mut leaf = [0u8; 32];
+        leaf[..8].copy_from_slice(&value.to_le_bytes());
+        leaf
+    }
+
+    #[test]
+    fn test_beacon_pending_deposit_hash_tree_root() {
+        let deposit = BeaconPendingDepositValue::<<L as PlonkParameters<D>>::Field> {
+            pubkey: core::array::from_fn(|i| i as u8),
+            withdrawal_credentials: H256::from_low_u64_be(0x0200_0000_1234),
+            amount: 32_000_000_000,
+            signature: core::array::from_fn(|i| (255 - i) as u8),
+            slot: 11_649_024,
+        };
+
+        // The `PendingDeposit` container, merkleized natively with its five fields padded to 8.
+        let zero = [0u8; 32];
+        let pubkey_root = hash_pair(
+            &deposit.pubkey[..32],
+            &[&deposit.pubkey[32..], &[0u8; 16]].concat(),
+        );
+        let signature_root = hash_pair(
+            &hash_pair(&deposit.signature[..32], &deposit.signature[32..64]),
+            &hash_pair(&deposit.signature[64..], &zero),
+        );
+        let expected = hash_pair(
+            &hash_pair(
+                &hash_pair(&pubkey_root, deposit.withdrawal_credentials.as_bytes()),
+                &hash_pair(&u64_leaf(deposit.amount), &signature_root),
+            ),
+            &hash_pair(
+                &hash_pair(&u64_leaf(deposit.slot), &zero),
+                &hash_pair(&zero, &zero),
+            ),
+        );
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let deposit = builder.constant::<BeaconPendingDepositVariable>(deposit);
+        let hash_tree_root = deposit.hash_tree_root(&mut builder);
+        let expected = builder.constant::<Bytes32Variable>(H256(expected));
+        builder.assert_is_equal(hash_tree_root, expected);
+        let circuit = builder.mock_build();
+        let input = circuit.input();
+        circuit.mock_prove(&input);
+    }
+}
@@ -0,0 +1,32 @@
+use std::fmt::Debug;
+
+use plonky2::hash::hash_types::RichField;
+use plonky2x_derive::CircuitVariable;
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::eth::vars::BLSPubkeyVariable;
+use crate::frontend::vars::{Bytes32Variable, CircuitVariable, SSZVariable};
+use crate::prelude::{ArrayVariable, Variable};
+
+/// Beacon chain constant SYNC_COMMITTEE_SIZE.
+pub const SYNC_COMMITTEE_SIZE: usize = 512;
+
+/// A sync committee of `SIZE` validators, which is `SYNC_COMMITTEE_SIZE` on the beacon chain.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(SyncCommitteeValue)]
+pub struct SyncCommitteeVariable<const SIZE: usize> {
+    pub pubkeys: ArrayVariable<BLSPubkeyVariable, SIZE>,
+    pub aggregate_pubkey: BLSPubkeyVariable,
+}
+
+impl<const SIZE: usize> SSZVariable for SyncCommitteeVariable<SIZE> {
+    fn hash_tree_root<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Bytes32Variable {
+        let pubkeys_root = builder.ssz_vector_root(self.pubkeys.as_slice());
+        let aggregate_pubkey_root = self.aggregate_pubkey.hash_tree_root(builder);
+        builder.ssz_container_root(&[pubkeys_root, aggregate_pubkey_root])
+    }
+}
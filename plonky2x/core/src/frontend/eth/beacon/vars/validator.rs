@@ -4,9 +4,10 @@ use plonky2::hash::hash_types::RichField;
 
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
-use crate::frontend::eth::vars::BLSPubkeyVariable;
+use crate::frontend::eth::vars::{AddressVariable, BLSPubkeyVariable};
 use crate::frontend::vars::{
-    BoolVariable, Bytes32Variable, CircuitVariable, EvmVariable, SSZVariable, U256Variable,
+    BoolVariable, Bytes32Variable, BytesVariable, CircuitVariable, EvmVariable, SSZVariable,
+    U256Variable,
 };
 use crate::prelude::{ByteVariable, Variable};
 use crate::utils::eth::beacon::BeaconValidator;
@@ -165,6 +166,137 @@ impl CircuitVariable for BeaconValidatorVariable {
     }
 }
 
+/// The withdrawal credentials prefix of validators that withdraw to a BLS key.
+pub const BLS_WITHDRAWAL_PREFIX: u8 = 0x00;
+
+/// The withdrawal credentials prefix of validators that withdraw to an execution address.
+pub const ETH1_ADDRESS_WITHDRAWAL_PREFIX: u8 = 0x01;
+
+/// The withdrawal credentials prefix of validators that withdraw to an execution address and
+/// compound their rewards (EIP-7251).
+pub const COMPOUNDING_WITHDRAWAL_PREFIX: u8 = 0x02;
+
+impl BeaconValidatorVariable {
+    /// The first byte of the withdrawal credentials, which determines their type.
+    pub fn withdrawal_credentials_prefix(&self) -> ByteVariable {
+        self.withdrawal_credentials.0 .0[0]
+    }
+
+    /// The execution address in the withdrawal credentials. Only meaningful if the validator has
+    /// execution withdrawal credentials.
+    pub fn withdrawal_address(&self) -> AddressVariable {
+        AddressVariable(BytesVariable(
+            self.withdrawal_credentials.0 .0[12..].try_into().unwrap(),
+        ))
+    }
+
+    /// Whether the withdrawal credentials are a hash of a BLS withdrawal key (prefix 0x00).
+    pub fn has_bls_withdrawal_credentials<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BoolVariable {
+        let prefix = builder.constant::<ByteVariable>(BLS_WITHDRAWAL_PREFIX);
+        builder.is_equal(self.withdrawal_credentials_prefix(), prefix)
+    }
+
+    /// Whether the withdrawal credentials are well-formed credentials with the given
+    /// execution-address prefix, i.e. `prefix || 0x00 * 11 || address`.
+    fn has_address_withdrawal_credentials<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        prefix: u8,
+    ) -> BoolVariable {
+        let prefix = builder.constant::<ByteVariable>(prefix);
+        let mut result = builder.is_equal(self.withdrawal_credentials_prefix(), prefix);
+        let zero = builder.constant::<ByteVariable>(0);
+        for byte in self.withdrawal_credentials.0 .0[1..12].iter() {
+            let is_zero = builder.is_equal(*byte, zero);
+            result = builder.and(result, is_zero);
+        }
+        result
+    }
+
+    /// Whether the validator withdraws to an execution address (prefix 0x01).
+    pub fn has_eth1_withdrawal_credentials<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BoolVariable {
+        self.has_address_withdrawal_credentials(builder, ETH1_ADDRESS_WITHDRAWAL_PREFIX)
+    }
+
+    /// Whether the validator withdraws to an execution address with compounding (prefix 0x02).
+    pub fn has_compounding_withdrawal_credentials<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BoolVariable {
+        self.has_address_withdrawal_credentials(builder, COMPOUNDING_WITHDRAWAL_PREFIX)
+    }
+
+    /// Whether the validator withdraws to an execution address (prefix 0x01 or 0x02), in which
+    /// case `withdrawal_address` is the address.
+    pub fn has_execution_withdrawal_credentials<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BoolVariable {
+        let eth1 = self.has_eth1_withdrawal_credentials(builder);
+        let compounding = self.has_compounding_withdrawal_credentials(builder);
+        builder.or(eth1, compounding)
+    }
+
+    /// Whether the validator is active at `epoch`, i.e. `activation_epoch <= epoch < exit_epoch`.
+    pub fn is_active<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        epoch: U256Variable,
+    ) -> BoolVariable {
+        let activated = builder.lte(self.activation_epoch, epoch);
+        let not_exited = builder.lt(epoch, self.exit_epoch);
+        builder.and(activated, not_exited)
+    }
+
+    /// Whether the validator has exited at `epoch`, i.e. `exit_epoch <= epoch`.
+    pub fn is_exited<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        epoch: U256Variable,
+    ) -> BoolVariable {
+        builder.lte(self.exit_epoch, epoch)
+    }
+
+    /// Whether the validator can withdraw its balance at `epoch`, i.e.
+    /// `withdrawable_epoch <= epoch`.
+    pub fn is_withdrawable<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        epoch: U256Variable,
+    ) -> BoolVariable {
+        builder.lte(self.withdrawable_epoch, epoch)
+    }
+
+    /// Whether the validator can be slashed at `epoch`, i.e. it is not slashed yet and
+    /// `activation_epoch <= epoch < withdrawable_epoch`.
+    pub fn is_slashable<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        epoch: U256Variable,
+    ) -> BoolVariable {
+        let not_slashed = builder.not(self.slashed);
+        let activated = builder.lte(self.activation_epoch, epoch);
+        let not_withdrawable = builder.lt(epoch, self.withdrawable_epoch);
+        let result = builder.and(not_slashed, activated);
+        builder.and(result, not_withdrawable)
+    }
+
+    /// Asserts that the validator has not been slashed.
+    pub fn assert_not_slashed<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        let false_v = builder._false();
+        builder.assert_is_equal(self.slashed, false_v);
+    }
+}
+
 impl SSZVariable for BeaconValidatorVariable {
     fn hash_tree_root<L: PlonkParameters<D>, const D: usize>(
         &self,
@@ -222,9 +354,10 @@ pub(crate) mod tests {
     use crate::backend::circuit::DefaultParameters;
     use crate::frontend::builder::CircuitBuilder;
     use crate::frontend::eth::beacon::vars::BeaconValidatorVariable;
-    use crate::frontend::vars::{Bytes32Variable, SSZVariable};
-    use crate::utils::bytes32;
+    use crate::frontend::eth::vars::AddressVariable;
+    use crate::frontend::vars::{BoolVariable, Bytes32Variable, SSZVariable, U256Variable};
     use crate::utils::eth::beacon::BeaconValidator;
+    use crate::utils::{address, bytes32};
 
     type L = DefaultParameters;
     const D: usize = 2;
@@ -288,4 +421,58 @@ pub(crate) mod tests {
         let (proof, output) = circuit.prove(&input);
         circuit.verify(&proof, &input, &output);
     }
+
+    #[test]
+    fn test_validator_field_helpers() {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let validator = BeaconValidator {
+            pubkey: "0x933ad9491b62059dd065b560d256d8957a8c402cc6e8d8ee7290ae11e8f7329267a8811c397529dac52ae1342ba58c95".to_string(),
+            withdrawal_credentials: "0x0100000000000000000000000d369bb49efa5100fd3b86a9f828c55da04d2d50".to_string(),
+            effective_balance: 32000000000,
+            slashed: false,
+            activation_eligibility_epoch: "5".to_string(),
+            activation_epoch: "10".to_string(),
+            exit_epoch: "20".to_string(),
+            withdrawable_epoch: "276".to_string(),
+        };
+        let v = builder.constant::<BeaconValidatorVariable>(validator);
+        v.assert_not_slashed(&mut builder);
+
+        let before = builder.constant::<U256Variable>(9.into());
+        let during = builder.constant::<U256Variable>(10.into());
+        let after = builder.constant::<U256Variable>(20.into());
+
+        let checks = vec![
+            v.has_bls_withdrawal_credentials(&mut builder),
+            v.has_eth1_withdrawal_credentials(&mut builder),
+            v.has_compounding_withdrawal_credentials(&mut builder),
+            v.has_execution_withdrawal_credentials(&mut builder),
+            v.is_active(&mut builder, before),
+            v.is_active(&mut builder, during),
+            v.is_active(&mut builder, after),
+            v.is_exited(&mut builder, after),
+            v.is_withdrawable(&mut builder, after),
+            v.is_slashable(&mut builder, after),
+        ];
+        for check in checks {
+            builder.write(check);
+        }
+        builder.write(v.withdrawal_address());
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let expected = [
+            false, true, false, true, false, true, false, true, false, true,
+        ];
+        for value in expected {
+            assert_eq!(output.read::<BoolVariable>(), value);
+        }
+        assert_eq!(
+            output.read::<AddressVariable>(),
+            address!("0x0d369bb49efa5100fd3b86a9f828c55da04d2d50")
+        );
+    }
 }
@@ -9,7 +9,7 @@ use crate::frontend::eth::vars::AddressVariable;
 use crate::frontend::uint::uint256::U256Variable;
 use crate::frontend::uint::uint64::U64Variable;
 use crate::frontend::vars::{Bytes32Variable, CircuitVariable, EvmVariable, SSZVariable};
-use crate::prelude::{ByteVariable, Variable};
+use crate::prelude::{ByteVariable, BytesVariable, Variable};
 
 #[derive(Debug, Copy, Clone, CircuitVariable)]
 #[value_name(BeaconWithdrawalValue)]
@@ -25,35 +25,18 @@ impl SSZVariable for BeaconWithdrawalVariable {
         &self,
         builder: &mut CircuitBuilder<L, D>,
     ) -> Bytes32Variable {
+        // The address is an SSZ `Bytes20`, right padded to a chunk.
         let zero = builder.constant::<ByteVariable>(0);
-
-        let mut index_bytes = self.index.encode(builder);
-        index_bytes.reverse();
-        index_bytes.extend([zero; 24]);
-
-        let mut validator_index_bytes = self.validator_index.encode(builder);
-        validator_index_bytes.reverse();
-        validator_index_bytes.extend([zero; 24]);
-
         let mut address_bytes = self.address.encode(builder);
         address_bytes.extend([zero; 12]);
-
-        let mut amount_bytes = self.amount.encode(builder);
-        amount_bytes.reverse();
-
-        let mut ab_input = Vec::new();
-        ab_input.extend(index_bytes);
-        ab_input.extend(validator_index_bytes);
-        let ab = builder.curta_sha256(&ab_input);
-
-        let mut cd_input = Vec::new();
-        cd_input.extend(address_bytes);
-        cd_input.extend(amount_bytes);
-        let cd = builder.curta_sha256(&cd_input);
-
-        let mut input = Vec::new();
-        input.extend(ab.0 .0);
-        input.extend(cd.0 .0);
-        builder.curta_sha256(&input)
+        let address_root = Bytes32Variable(BytesVariable::<32>(address_bytes.try_into().unwrap()));
+
+        let field_roots = [
+            self.index.hash_tree_root(builder),
+            self.validator_index.hash_tree_root(builder),
+            address_root,
+            self.amount.hash_tree_root(builder),
+        ];
+        builder.ssz_container_root(&field_roots)
     }
 }
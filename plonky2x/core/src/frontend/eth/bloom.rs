@@ -0,0 +1,152 @@
+//! Membership checks against the logs bloom of a block header.
+//!
+//! The logs bloom is a 2048-bit filter. Every logged address and topic sets three bits, taken
+//! from the first six bytes of its keccak256 hash. A log whose address and topics do not all have
+//! their bits set cannot be in the block, so a bloom check proves the exclusion of a log without
+//! any receipt proof.
+
+use ethers::utils::keccak256;
+use itertools::Itertools;
+use plonky2::iop::target::{BoolTarget, Target};
+
+use super::vars::AddressVariable;
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::vars::{
+    BitDecomposition, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitVariable,
+    Variable,
+};
+
+/// The number of bytes of a logs bloom.
+pub const LOGS_BLOOM_SIZE: usize = 256;
+
+/// Returns the positions, counted from the least significant bit of the bloom, of the three bits
+/// set by `item`.
+pub fn logs_bloom_bit_indices(item: &[u8]) -> [usize; 3] {
+    let hash = keccak256(item);
+    [0, 1, 2].map(|i| (((hash[2 * i] as usize) << 8) | hash[2 * i + 1] as usize) & 2047)
+}
+
+/// Adds `item` to `bloom`.
+pub fn logs_bloom_accrue(bloom: &mut [u8; LOGS_BLOOM_SIZE], item: &[u8]) {
+    for index in logs_bloom_bit_indices(item) {
+        bloom[LOGS_BLOOM_SIZE - 1 - index / 8] |= 1 << (index % 8);
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns whether `item`, a logged address or topic, may be in `bloom`. A false result proves
+    /// that no log of the block contains `item`.
+    pub fn logs_bloom_contains(
+        &mut self,
+        bloom: &BytesVariable<LOGS_BLOOM_SIZE>,
+        item: &[ByteVariable],
+    ) -> BoolVariable {
+        // Pack the bloom into 64 words of 32 bits, so that a bit is selected with two small
+        // random accesses instead of a scan of all 2048 bits.
+        let bloom_bits = bloom.to_le_bits(self);
+        let words = bloom_bits
+            .chunks_exact(32)
+            .map(|chunk| {
+                self.api
+                    .le_sum(chunk.iter().map(|b| BoolTarget::new_unsafe(b.variable.0)))
+            })
+            .collect_vec();
+
+        let hash = self.keccak256(item);
+        let mut result = self._true();
+        for i in 0..3 {
+            // The bit index is the low 11 bits of the big endian pair `hash[2i] || hash[2i + 1]`.
+            let index_bits = hash.0 .0[2 * i + 1]
+                .as_le_bits()
+                .into_iter()
+                .chain(hash.0 .0[2 * i].as_le_bits().into_iter().take(3))
+                .map(|b| BoolTarget::new_unsafe(b.variable.0))
+                .collect_vec();
+            let bit_in_word = self.api.le_sum(index_bits[..5].iter());
+            let word_index = self.api.le_sum(index_bits[5..].iter());
+
+            let word = self.api.random_access(word_index, words.clone());
+            let word_bits = self
+                .api
+                .split_le(word, 32)
+                .into_iter()
+                .map(|b| b.target)
+                .collect::<Vec<Target>>();
+            let bit = self.api.random_access(bit_in_word, word_bits);
+            // The selected target is one of the bloom bits, so it is boolean.
+            let bit = BoolVariable::from_variables_unsafe(&[Variable(bit)]);
+            result = self.and(result, bit);
+        }
+        result
+    }
+
+    /// Returns whether a log emitted by `address` with `topics` may be in `bloom`. A false
+    /// result proves that the block contains no such log.
+    pub fn logs_bloom_may_contain_log(
+        &mut self,
+        bloom: &BytesVariable<LOGS_BLOOM_SIZE>,
+        address: AddressVariable,
+        topics: &[Bytes32Variable],
+    ) -> BoolVariable {
+        let mut result = self.logs_bloom_contains(bloom, &address.0 .0);
+        for topic in topics.iter() {
+            let contains_topic = self.logs_bloom_contains(bloom, &topic.0 .0);
+            result = self.and(result, contains_topic);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_logs_bloom_contains() {
+        let address = address!("0x55032650b14df07b85bF18A3a3eC8E0Af2e028d5");
+        let topic = bytes32!("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+        let other_topic =
+            bytes32!("0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+
+        let mut bloom = [0u8; LOGS_BLOOM_SIZE];
+        logs_bloom_accrue(&mut bloom, address.as_bytes());
+        logs_bloom_accrue(&mut bloom, topic.as_bytes());
+
+        let mut builder = DefaultBuilder::new();
+        let bloom_variable = builder.read::<BytesVariable<LOGS_BLOOM_SIZE>>();
+        let address_variable = builder.read::<AddressVariable>();
+        let topic_variable = builder.read::<Bytes32Variable>();
+        let other_topic_variable = builder.read::<Bytes32Variable>();
+
+        let contains = builder.logs_bloom_may_contain_log(
+            &bloom_variable,
+            address_variable,
+            &[topic_variable],
+        );
+        builder.write(contains);
+        let contains = builder.logs_bloom_may_contain_log(
+            &bloom_variable,
+            address_variable,
+            &[topic_variable, other_topic_variable],
+        );
+        builder.write(contains);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<BytesVariable<LOGS_BLOOM_SIZE>>(bloom);
+        input.write::<AddressVariable>(address);
+        input.write::<Bytes32Variable>(topic);
+        input.write::<Bytes32Variable>(other_topic);
+
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        assert!(output.read::<BoolVariable>());
+        let other_topic_in_bloom = logs_bloom_bit_indices(other_topic.as_bytes())
+            .iter()
+            .all(|index| (bloom[LOGS_BLOOM_SIZE - 1 - index / 8] >> (index % 8)) & 1 == 1);
+        assert_eq!(output.read::<BoolVariable>(), other_topic_in_bloom);
+    }
+}
@@ -0,0 +1,111 @@
+//! Reading the parent beacon block root of an execution block, as stored by the EIP-4788 beacon
+//! roots contract.
+//!
+//! At the start of every block, the contract stores the timestamp of the block in the slot
+//! `timestamp % HISTORY_BUFFER_LENGTH` and the parent beacon block root in the slot
+//! `timestamp % HISTORY_BUFFER_LENGTH + HISTORY_BUFFER_LENGTH` of its ring buffers. Proving these
+//! slots against the state root of a block connects it to a beacon block root, from which the
+//! beacon state can then be proven.
+
+use super::storage::vars::EthHeaderVariable;
+use super::vars::AddressVariable;
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::uint::uint256::U256Variable;
+use crate::frontend::vars::{Bytes32Variable, BytesVariable, EvmVariable};
+use crate::utils::address;
+
+/// The address of the beacon roots contract.
+pub const BEACON_ROOTS_ADDRESS: &str = "0x000F3df6D732807Ef1319fB7B8bB8522d0Beac02";
+
+/// The length of the ring buffers of the beacon roots contract.
+pub const HISTORY_BUFFER_LENGTH: u64 = 8191;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Reads the parent beacon block root of the block `block_hash` with header `header` from the
+    /// storage of the beacon roots contract, verifying the storage proofs against the state root
+    /// of the header. The timestamp stored alongside the root is checked to be the timestamp of
+    /// the header, so the root was not overwritten by a later block. The caller is responsible for
+    /// checking that `header` is the header of the block `block_hash`.
+    ///
+    /// The proofs have the same bounds as in `eth_get_storage_at`.
+    pub fn eth_get_parent_beacon_block_root<
+        const ENCODING_LEN: usize,
+        const ACCOUNT_PROOF_LEN: usize,
+        const STORAGE_PROOF_LEN: usize,
+    >(
+        &mut self,
+        block_hash: Bytes32Variable,
+        header: &EthHeaderVariable,
+    ) -> Bytes32Variable {
+        let contract = self.constant::<AddressVariable>(address!(BEACON_ROOTS_ADDRESS));
+        let buffer_length = self.constant::<U256Variable>(HISTORY_BUFFER_LENGTH.into());
+        let timestamp_index = self.rem(header.time, buffer_length);
+        let root_index = self.add(timestamp_index, buffer_length);
+
+        let timestamp_slot = Bytes32Variable(BytesVariable(
+            timestamp_index.encode(self).try_into().unwrap(),
+        ));
+        let timestamp = self
+            .eth_get_storage_at::<ENCODING_LEN, ACCOUNT_PROOF_LEN, STORAGE_PROOF_LEN>(
+                block_hash,
+                header.root,
+                contract,
+                timestamp_slot,
+            );
+        let expected_timestamp =
+            Bytes32Variable(BytesVariable(header.time.encode(self).try_into().unwrap()));
+        self.assert_is_equal(timestamp, expected_timestamp);
+
+        let root_slot = Bytes32Variable(BytesVariable(root_index.encode(self).try_into().unwrap()));
+        self.eth_get_storage_at::<ENCODING_LEN, ACCOUNT_PROOF_LEN, STORAGE_PROOF_LEN>(
+            block_hash,
+            header.root,
+            contract,
+            root_slot,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use ethers::providers::{Http, Middleware, Provider};
+    use ethers::types::BlockNumber;
+    use tokio::runtime::Runtime;
+
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+    use crate::utils;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_eth_get_parent_beacon_block_root() {
+        utils::setup_logger();
+        dotenv::dotenv().ok();
+        let rpc_url = env::var("RPC_1").unwrap();
+        let provider = Provider::<Http>::try_from(rpc_url).unwrap();
+        let block = Runtime::new()
+            .unwrap()
+            .block_on(provider.get_block(BlockNumber::Finalized))
+            .unwrap()
+            .unwrap();
+
+        let mut builder = DefaultBuilder::new();
+        builder.set_execution_client(provider);
+        let block_hash = builder.evm_read::<Bytes32Variable>();
+        let header = builder.eth_get_block_by_hash_witness(block_hash);
+        let root = builder.eth_get_parent_beacon_block_root::<600, 16, 16>(block_hash, &header);
+        builder.evm_write(root);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.evm_write::<Bytes32Variable>(block.hash.unwrap());
+        let (_witness, mut output) = circuit.mock_prove(&input);
+        assert_eq!(
+            output.evm_read::<Bytes32Variable>(),
+            block.parent_beacon_block_root.unwrap(),
+        );
+    }
+}
@@ -0,0 +1,439 @@
+//! Hashing of EIP-712 typed structured data.
+//!
+//! A schema lists the struct types of the typed data, in the format of the `types` field of an
+//! `eth_signTypedData_v4` request. The encoding of each type and its type hash are computed when
+//! building the circuit, so only the encoding of the values is constrained. The message signed
+//! by a wallet is `keccak256(0x19 || 0x01 || domainSeparator || hashStruct(message))`, where the
+//! domain separator is the struct hash of the `EIP712Domain` struct.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, ensure, Result};
+use ethers::utils::keccak256;
+use serde_json::Value;
+
+use super::vars::AddressVariable;
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::uint::uint256::U256Variable;
+use crate::frontend::vars::{BoolVariable, ByteVariable, Bytes32Variable, EvmVariable};
+
+/// The name of the struct type of domain separators.
+pub const EIP712_DOMAIN_TYPE: &str = "EIP712Domain";
+
+/// A member of an EIP-712 struct type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eip712Member {
+    pub name: String,
+    pub type_name: String,
+}
+
+/// The struct types of some EIP-712 typed data.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Eip712Schema {
+    types: BTreeMap<String, Vec<Eip712Member>>,
+}
+
+/// The type of a member, with arrays resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MemberType<'a> {
+    Atomic(&'a str),
+    Dynamic,
+    Struct(&'a str),
+    Array(&'a str),
+}
+
+impl Eip712Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a struct type with the given `(name, type)` members.
+    pub fn with_type(mut self, name: &str, members: &[(&str, &str)]) -> Self {
+        let members = members
+            .iter()
+            .map(|(name, type_name)| Eip712Member {
+                name: name.to_string(),
+                type_name: type_name.to_string(),
+            })
+            .collect();
+        self.types.insert(name.to_string(), members);
+        self
+    }
+
+    /// Parses the `types` field of an `eth_signTypedData_v4` request, or a whole request.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let json: Value = serde_json::from_str(json)?;
+        let types = json
+            .get("types")
+            .unwrap_or(&json)
+            .as_object()
+            .ok_or_else(|| anyhow!("expected an object of types"))?;
+        let mut schema = Self::new();
+        for (name, members) in types {
+            let members = members
+                .as_array()
+                .ok_or_else(|| anyhow!("members of {} are not an array", name))?
+                .iter()
+                .map(|member| {
+                    let field = |key: &str| {
+                        member
+                            .get(key)
+                            .and_then(Value::as_str)
+                            .map(str::to_string)
+                            .ok_or_else(|| anyhow!("member of {} has no {}", name, key))
+                    };
+                    Ok(Eip712Member {
+                        name: field("name")?,
+                        type_name: field("type")?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            schema.types.insert(name.clone(), members);
+        }
+        for name in schema.types.keys() {
+            schema.referenced_types(name)?;
+        }
+        Ok(schema)
+    }
+
+    pub fn members(&self, name: &str) -> Result<&[Eip712Member]> {
+        self.types
+            .get(name)
+            .map(Vec::as_slice)
+            .ok_or_else(|| anyhow!("unknown struct type {}", name))
+    }
+
+    fn member_type<'a>(&self, type_name: &'a str) -> Result<MemberType<'a>> {
+        if let Some(item) = type_name.strip_suffix(']') {
+            let (item, length) = item
+                .rsplit_once('[')
+                .ok_or_else(|| anyhow!("invalid array type {}", type_name))?;
+            ensure!(
+                length.is_empty() || length.parse::<usize>().is_ok(),
+                "invalid array length in {}",
+                type_name
+            );
+            self.member_type(item)?;
+            return Ok(MemberType::Array(item));
+        }
+        if type_name == "bytes" || type_name == "string" {
+            return Ok(MemberType::Dynamic);
+        }
+        if self.types.contains_key(type_name) {
+            return Ok(MemberType::Struct(type_name));
+        }
+        let is_sized = |prefix: &str, range: core::ops::RangeInclusive<usize>, step: usize| {
+            type_name
+                .strip_prefix(prefix)
+                .and_then(|size| size.parse::<usize>().ok())
+                .map_or(false, |size| range.contains(&size) && size % step == 0)
+        };
+        ensure!(
+            type_name == "address"
+                || type_name == "bool"
+                || is_sized("uint", 8..=256, 8)
+                || is_sized("int", 8..=256, 8)
+                || is_sized("bytes", 1..=32, 1),
+            "unknown type {}",
+            type_name
+        );
+        Ok(MemberType::Atomic(type_name))
+    }
+
+    /// The struct types referenced by `name`, including itself.
+    fn referenced_types(&self, name: &str) -> Result<BTreeSet<String>> {
+        let mut types = BTreeSet::new();
+        let mut stack = vec![name.to_string()];
+        while let Some(name) = stack.pop() {
+            if !types.insert(name.clone()) {
+                continue;
+            }
+            for member in self.members(&name)? {
+                let mut member_type = self.member_type(&member.type_name)?;
+                while let MemberType::Array(item) = member_type {
+                    member_type = self.member_type(item)?;
+                }
+                if let MemberType::Struct(dependency) = member_type {
+                    stack.push(dependency.to_string());
+                }
+            }
+        }
+        Ok(types)
+    }
+
+    /// The encoding of a struct type, followed by the encodings of the struct types it references
+    /// in alphabetical order.
+    pub fn encode_type(&self, name: &str) -> Result<String> {
+        let mut dependencies = self.referenced_types(name)?;
+        dependencies.remove(name);
+        let encode = |name: &str| -> Result<String> {
+            let members = self
+                .members(name)?
+                .iter()
+                .map(|member| format!("{} {}", member.type_name, member.name))
+                .collect::<Vec<_>>();
+            Ok(format!("{}({})", name, members.join(",")))
+        };
+        let mut encoding = encode(name)?;
+        for dependency in dependencies {
+            encoding.push_str(&encode(&dependency)?);
+        }
+        Ok(encoding)
+    }
+
+    /// The type hash `keccak256(encodeType(name))` of a struct type.
+    pub fn type_hash(&self, name: &str) -> Result<[u8; 32]> {
+        Ok(keccak256(self.encode_type(name)?))
+    }
+}
+
+/// The value of a member of an EIP-712 struct in a circuit.
+#[derive(Debug, Clone)]
+pub enum Eip712Variable {
+    /// A `uintN`, or an `intN` in two's complement.
+    Uint(U256Variable),
+    Address(AddressVariable),
+    Bool(BoolVariable),
+    /// A `bytesN`, given as its `N` bytes.
+    FixedBytes(Vec<ByteVariable>),
+    /// A `bytes` or a `string`, given as its bytes.
+    Bytes(Vec<ByteVariable>),
+    /// A struct, given as the values of its members in order.
+    Struct(Vec<Eip712Variable>),
+    Array(Vec<Eip712Variable>),
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Encodes a value of the given type as one 32-byte word, following `encodeData`.
+    fn eip712_encode_value(
+        &mut self,
+        schema: &Eip712Schema,
+        type_name: &str,
+        value: &Eip712Variable,
+    ) -> Vec<ByteVariable> {
+        let zero = self.constant::<ByteVariable>(0);
+        let member_type = schema.member_type(type_name).unwrap();
+        match (member_type, value) {
+            (MemberType::Atomic(t), Eip712Variable::Uint(value))
+                if t.starts_with("uint") || t.starts_with("int") =>
+            {
+                value.encode(self)
+            }
+            (MemberType::Atomic("address"), Eip712Variable::Address(value)) => {
+                let mut word = vec![zero; 12];
+                word.extend(value.encode(self));
+                word
+            }
+            (MemberType::Atomic("bool"), Eip712Variable::Bool(value)) => {
+                let mut byte = [self._false(); 8];
+                byte[7] = *value;
+                let mut word = vec![zero; 31];
+                word.push(ByteVariable(byte));
+                word
+            }
+            (MemberType::Atomic(t), Eip712Variable::FixedBytes(bytes))
+                if t.starts_with("bytes") =>
+            {
+                assert_eq!(
+                    t["bytes".len()..].parse::<usize>().unwrap(),
+                    bytes.len(),
+                    "wrong length for {}",
+                    t
+                );
+                let mut word = bytes.clone();
+                word.resize(32, zero);
+                word
+            }
+            (MemberType::Dynamic, Eip712Variable::Bytes(bytes)) => {
+                self.keccak256(bytes).0 .0.to_vec()
+            }
+            (MemberType::Struct(name), Eip712Variable::Struct(values)) => {
+                self.eip712_hash_struct(schema, name, values).0 .0.to_vec()
+            }
+            (MemberType::Array(item), Eip712Variable::Array(values)) => {
+                if let Some(length) = type_name
+                    .strip_suffix(']')
+                    .and_then(|t| t.rsplit_once('['))
+                    .and_then(|(_, length)| length.parse::<usize>().ok())
+                {
+                    assert_eq!(values.len(), length, "wrong length for {}", type_name);
+                }
+                let encoding = values
+                    .iter()
+                    .flat_map(|value| self.eip712_encode_value(schema, item, value))
+                    .collect::<Vec<_>>();
+                self.keccak256(&encoding).0 .0.to_vec()
+            }
+            _ => panic!("value {:?} does not have type {}", value, type_name),
+        }
+    }
+
+    /// Computes `hashStruct` of a struct of the given type, from the values of its members.
+    pub fn eip712_hash_struct(
+        &mut self,
+        schema: &Eip712Schema,
+        name: &str,
+        values: &[Eip712Variable],
+    ) -> Bytes32Variable {
+        let members = schema.members(name).unwrap();
+        assert_eq!(
+            members.len(),
+            values.len(),
+            "wrong number of members for {}",
+            name
+        );
+        let type_hash = self.constant::<Bytes32Variable>(schema.type_hash(name).unwrap().into());
+        let mut encoding = type_hash.0 .0.to_vec();
+        for (member, value) in members.iter().zip(values.iter()) {
+            encoding.extend(self.eip712_encode_value(schema, &member.type_name, value));
+        }
+        self.keccak256(&encoding)
+    }
+
+    /// Computes the domain separator from the values of the members of `EIP712Domain`.
+    pub fn eip712_domain_separator(
+        &mut self,
+        schema: &Eip712Schema,
+        values: &[Eip712Variable],
+    ) -> Bytes32Variable {
+        self.eip712_hash_struct(schema, EIP712_DOMAIN_TYPE, values)
+    }
+
+    /// Computes the digest signed for a message with the given struct hash.
+    pub fn eip712_signing_hash(
+        &mut self,
+        domain_separator: Bytes32Variable,
+        struct_hash: Bytes32Variable,
+    ) -> Bytes32Variable {
+        let mut encoding = vec![
+            self.constant::<ByteVariable>(0x19),
+            self.constant::<ByteVariable>(0x01),
+        ];
+        encoding.extend(domain_separator.0 .0);
+        encoding.extend(struct_hash.0 .0);
+        self.keccak256(&encoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::*;
+    use crate::backend::circuit::DefaultParameters;
+    use crate::utils::{address, bytes32};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    const MAIL_TYPES: &str = r#"{
+        "EIP712Domain": [
+            {"name": "name", "type": "string"},
+            {"name": "version", "type": "string"},
+            {"name": "chainId", "type": "uint256"},
+            {"name": "verifyingContract", "type": "address"}
+        ],
+        "Person": [
+            {"name": "name", "type": "string"},
+            {"name": "wallet", "type": "address"}
+        ],
+        "Mail": [
+            {"name": "from", "type": "Person"},
+            {"name": "to", "type": "Person"},
+            {"name": "contents", "type": "string"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_eip712_schema() {
+        let schema = Eip712Schema::from_json(MAIL_TYPES).unwrap();
+        assert_eq!(
+            schema.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            H256::from(schema.type_hash("Mail").unwrap()),
+            bytes32!("0xa0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2")
+        );
+        assert_eq!(
+            schema,
+            Eip712Schema::new()
+                .with_type(
+                    EIP712_DOMAIN_TYPE,
+                    &[
+                        ("name", "string"),
+                        ("version", "string"),
+                        ("chainId", "uint256"),
+                        ("verifyingContract", "address")
+                    ]
+                )
+                .with_type("Person", &[("name", "string"), ("wallet", "address")])
+                .with_type(
+                    "Mail",
+                    &[("from", "Person"), ("to", "Person"), ("contents", "string")]
+                )
+        );
+        assert!(
+            Eip712Schema::from_json(r#"{"Mail": [{"name": "to", "type": "Person"}]}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_eip712_signing_hash() {
+        let schema = Eip712Schema::from_json(MAIL_TYPES).unwrap();
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let string = |builder: &mut CircuitBuilder<L, D>, s: &str| {
+            Eip712Variable::Bytes(
+                s.bytes()
+                    .map(|b| builder.constant::<ByteVariable>(b))
+                    .collect(),
+            )
+        };
+
+        let domain = [
+            string(&mut builder, "Ether Mail"),
+            string(&mut builder, "1"),
+            Eip712Variable::Uint(builder.read::<U256Variable>()),
+            Eip712Variable::Address(builder.read::<AddressVariable>()),
+        ];
+        let from = Eip712Variable::Struct(vec![
+            string(&mut builder, "Cow"),
+            Eip712Variable::Address(builder.read::<AddressVariable>()),
+        ]);
+        let to = Eip712Variable::Struct(vec![
+            string(&mut builder, "Bob"),
+            Eip712Variable::Address(builder.read::<AddressVariable>()),
+        ]);
+        let mail = [from, to, string(&mut builder, "Hello, Bob!")];
+
+        let domain_separator = builder.eip712_domain_separator(&schema, &domain);
+        let struct_hash = builder.eip712_hash_struct(&schema, "Mail", &mail);
+        let signing_hash = builder.eip712_signing_hash(domain_separator, struct_hash);
+        builder.write(domain_separator);
+        builder.write(struct_hash);
+        builder.write(signing_hash);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<U256Variable>(1.into());
+        input.write::<AddressVariable>(address!("0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"));
+        input.write::<AddressVariable>(address!("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"));
+        input.write::<AddressVariable>(address!("0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            bytes32!("0xf2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+        );
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            bytes32!("0xc52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e")
+        );
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            bytes32!("0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+        );
+    }
+}
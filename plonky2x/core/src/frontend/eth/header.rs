@@ -0,0 +1,263 @@
+//! Verification of runs of consecutive execution block headers.
+//!
+//! A header is the RLP encoding of the list of its fields, and the hash of a block is the
+//! keccak256 of its header. Only the fields shared by the headers of every fork are decoded: the
+//! parent hash, the block number and the timestamp, which are the first, ninth and twelfth fields.
+
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::*;
+
+/// The number of fields of the longest supported headers, which gain fields with forks.
+const MAX_HEADER_FIELDS: usize = 24;
+
+/// The positions of the decoded fields of a header.
+const PARENT_HASH: usize = 0;
+const NUMBER: usize = 8;
+const TIMESTAMP: usize = 11;
+
+/// The bytes read past the end of a header: the payload of a word and its length bytes.
+const HEADER_PADDING_LEN: usize = 35;
+
+/// An RLP encoded header, padded with zeros to `MAX_LEN` bytes, and its length.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(EthRlpHeader)]
+pub struct EthRlpHeaderVariable<const MAX_LEN: usize> {
+    pub encoding: ArrayVariable<ByteVariable, MAX_LEN>,
+    pub len: Variable,
+}
+
+/// The decoded fields of a header.
+#[derive(Debug, Clone, Copy)]
+struct DecodedHeader {
+    hash: Bytes32Variable,
+    parent_hash: Bytes32Variable,
+    number: U256Variable,
+    timestamp: U256Variable,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the string item of a header at `offset` as a 32-byte big-endian word, checked to be
+    /// at most 32 bytes long if `enabled` is set.
+    fn header_word(
+        &mut self,
+        encoding: &[ByteVariable],
+        offset: Variable,
+        enabled: BoolVariable,
+    ) -> U256Variable {
+        let zero = self.zero::<Variable>();
+        let item = self.rlp_decode_string(encoding, offset, enabled);
+        let bytes = self.rlp_item_bytes::<32>(encoding, &item, enabled);
+        let thirty_two = self.mpt_constant(32);
+        let shift = self.sub(thirty_two, item.len);
+        let shift = self.select(enabled, shift, zero);
+        let word = ArrayVariable::<ByteVariable, 32>::new(bytes.to_vec());
+        let word = self.rotate_right(word, shift);
+        U256Variable::decode(self, &word.as_vec())
+    }
+
+    /// Hashes a header and decodes its fields, which are checked to be well formed if `enabled` is
+    /// set.
+    fn decode_header<const MAX_LEN: usize>(
+        &mut self,
+        header: &EthRlpHeaderVariable<MAX_LEN>,
+        enabled: BoolVariable,
+    ) -> DecodedHeader {
+        let zero = self.zero::<Variable>();
+        let zero_byte = self.constant::<ByteVariable>(0);
+        let mut encoding = header.encoding.as_vec();
+        encoding.extend(vec![zero_byte; HEADER_PADDING_LEN]);
+
+        // The header must be a single list, whose fields include the decoded ones.
+        let fields = self.rlp_decode_list(&encoding, zero, enabled);
+        let is_whole = self.is_equal(fields.encoding_len, header.len);
+        self.mpt_assert_if(enabled, is_whole);
+        let (items, nb_items) =
+            self.rlp_decode_list_items::<MAX_HEADER_FIELDS>(&encoding, &fields, enabled);
+        for nb_fields in 0..=TIMESTAMP {
+            let nb_fields = self.mpt_constant(nb_fields);
+            let is_short = self.is_equal(nb_items, nb_fields);
+            let is_long_enough = self.not(is_short);
+            self.mpt_assert_if(enabled, is_long_enough);
+        }
+
+        let parent_hash = self.rlp_decode_string(&encoding, items[PARENT_HASH].offset, enabled);
+        let thirty_two = self.mpt_constant(32);
+        let is_hash = self.is_equal(parent_hash.len, thirty_two);
+        self.mpt_assert_if(enabled, is_hash);
+        let parent_hash = self.rlp_item_bytes::<32>(&encoding, &parent_hash, enabled);
+        let number = self.header_word(&encoding, items[NUMBER].offset, enabled);
+        let timestamp = self.header_word(&encoding, items[TIMESTAMP].offset, enabled);
+
+        // The length of an enabled header is the one of its list, which is at most 2^16 + 2.
+        let len = self.select(enabled, header.len, zero);
+        let len = U32Variable::from_variables_unsafe(&[len]);
+        let hash = self.keccak256_variable(&header.encoding.as_vec(), len);
+
+        DecodedHeader {
+            hash,
+            parent_hash: Bytes32Variable(BytesVariable(parent_hash)),
+            number,
+            timestamp,
+        }
+    }
+
+    /// Verifies that the first `n` of `headers`, with `n` between 1 and `MAX_HEADERS`, are the
+    /// headers of consecutive blocks: each header is a child of the previous one by its parent
+    /// hash, and has the next block number and a later timestamp. Returns the hashes of the first
+    /// and the last block of the run. The remaining headers are ignored.
+    pub fn verify_header_chain<const MAX_HEADERS: usize, const MAX_LEN: usize>(
+        &mut self,
+        headers: &ArrayVariable<EthRlpHeaderVariable<MAX_LEN>, MAX_HEADERS>,
+        n: Variable,
+    ) -> (Bytes32Variable, Bytes32Variable) {
+        assert!(MAX_HEADERS > 0, "the chain must have at least one header");
+        let true_variable = self._true();
+        let false_variable = self._false();
+        let one = self.one::<U256Variable>();
+
+        let mut enabled = true_variable;
+        let mut n_found = false_variable;
+        let mut previous: Option<DecodedHeader> = None;
+        let mut last_hash = self.constant::<Bytes32Variable>(Default::default());
+        let mut first_hash = last_hash;
+        for (i, header) in headers.as_vec().iter().enumerate() {
+            // The headers are enabled up to the `n`-th one, which must not be the first.
+            let index = self.mpt_constant(i);
+            let at_n = self.is_equal(index, n);
+            if i == 0 {
+                let not_at_n = self.not(at_n);
+                self.assert_is_equal(not_at_n, true_variable);
+            }
+            n_found = self.or(n_found, at_n);
+            enabled = self.select(at_n, false_variable, enabled);
+
+            let decoded = self.decode_header(header, enabled);
+            match previous {
+                None => first_hash = decoded.hash,
+                Some(parent) => {
+                    let is_child = self.is_equal(decoded.parent_hash, parent.hash);
+                    self.mpt_assert_if(enabled, is_child);
+                    let next_number = self.add(parent.number, one);
+                    let is_next = self.is_equal(decoded.number, next_number);
+                    self.mpt_assert_if(enabled, is_next);
+                    let is_later = self.gt(decoded.timestamp, parent.timestamp);
+                    self.mpt_assert_if(enabled, is_later);
+                }
+            }
+            last_hash = self.select(enabled, decoded.hash, last_hash);
+            previous = Some(decoded);
+        }
+        let max_headers = self.mpt_constant(MAX_HEADERS);
+        let at_max = self.is_equal(max_headers, n);
+        n_found = self.or(n_found, at_max);
+        self.assert_is_equal(n_found, true_variable);
+
+        (first_hash, last_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+    use ethers::utils::keccak256;
+    use ethers::utils::rlp::RlpStream;
+
+    use super::*;
+
+    const MAX_HEADERS: usize = 4;
+    const MAX_LEN: usize = 640;
+
+    /// Encodes a London header with the given parent hash, number and timestamp.
+    fn encode_header(parent_hash: H256, number: u64, timestamp: u64) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(16);
+        stream.append(&parent_hash);
+        stream.append(&H256::from(keccak256([0xc0])));
+        stream.append(&vec![0x11u8; 20]);
+        for seed in [b"state", b"txs..", b"rcpts"] {
+            stream.append(&H256::from(keccak256(seed)));
+        }
+        stream.append(&vec![0u8; 256]);
+        stream.append(&0u64);
+        stream.append(&number);
+        stream.append(&30_000_000u64);
+        stream.append(&12_345_678u64);
+        stream.append(&timestamp);
+        stream.append(&b"plonky2x".to_vec());
+        stream.append(&H256::from(keccak256(number.to_be_bytes())));
+        stream.append(&vec![0u8; 8]);
+        stream.append(&7u64);
+        stream.out().to_vec()
+    }
+
+    fn verify(headers: &[Vec<u8>], n: usize) -> (H256, H256) {
+        let mut builder = DefaultBuilder::new();
+        let headers_variable =
+            builder.read::<ArrayVariable<EthRlpHeaderVariable<MAX_LEN>, MAX_HEADERS>>();
+        let n_variable = builder.read::<Variable>();
+        let (first, last) = builder.verify_header_chain(&headers_variable, n_variable);
+        builder.write(first);
+        builder.write(last);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        let values = (0..MAX_HEADERS)
+            .map(|i| {
+                let header = headers.get(i).cloned().unwrap_or_default();
+                let len = header.len();
+                let mut encoding = header;
+                encoding.resize(MAX_LEN, 0);
+                EthRlpHeader {
+                    encoding,
+                    len: GoldilocksField::from_canonical_usize(len),
+                }
+            })
+            .collect::<Vec<_>>();
+        input.write::<ArrayVariable<EthRlpHeaderVariable<MAX_LEN>, MAX_HEADERS>>(values);
+        input.write::<Variable>(GoldilocksField::from_canonical_usize(n));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        (
+            output.read::<Bytes32Variable>(),
+            output.read::<Bytes32Variable>(),
+        )
+    }
+
+    fn chain(parent_hash: H256, number: u64, timestamps: &[u64]) -> Vec<Vec<u8>> {
+        let mut parent_hash = parent_hash;
+        timestamps
+            .iter()
+            .enumerate()
+            .map(|(i, timestamp)| {
+                let header = encode_header(parent_hash, number + i as u64, *timestamp);
+                parent_hash = H256::from(keccak256(&header));
+                header
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_header_chain() {
+        let headers = chain(
+            H256::repeat_byte(0x42),
+            18_000_000,
+            &[1_700_000_000, 1_700_000_012],
+        );
+        let (first, last) = verify(&headers, 2);
+        assert_eq!(first, H256::from(keccak256(&headers[0])));
+        assert_eq!(last, H256::from(keccak256(&headers[1])));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    #[should_panic]
+    fn test_verify_header_chain_broken_link() {
+        let mut headers = chain(H256::repeat_byte(0x42), 18_000_000, &[1_700_000_000]);
+        headers.push(encode_header(
+            H256::repeat_byte(0x43),
+            18_000_001,
+            1_700_000_012,
+        ));
+        verify(&headers, 2);
+    }
+}
@@ -1,4 +1,9 @@
+pub mod abi;
 pub mod beacon;
+pub mod bloom;
+pub mod eip4788;
+pub mod eip712;
+pub mod header;
 pub mod mpt;
 pub mod rlp;
 pub mod storage;
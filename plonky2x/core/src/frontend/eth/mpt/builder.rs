@@ -9,25 +9,35 @@ use crate::prelude::{
 pub fn transform_proof_to_padded<const ENCODING_LEN: usize, const PROOF_LEN: usize>(
     storage_proof: Vec<Vec<u8>>,
 ) -> (Vec<Vec<u8>>, Vec<usize>) {
-    if storage_proof.len() > PROOF_LEN {
+    pad_proof_nodes(storage_proof, ENCODING_LEN, PROOF_LEN)
+}
+
+/// Pads a proof to `proof_len` nodes of `encoding_len` bytes, returning the padded nodes and their
+/// original lengths. The same as `transform_proof_to_padded`, for sizes known at runtime.
+pub fn pad_proof_nodes(
+    storage_proof: Vec<Vec<u8>>,
+    encoding_len: usize,
+    proof_len: usize,
+) -> (Vec<Vec<u8>>, Vec<usize>) {
+    if storage_proof.len() > proof_len {
         panic!(
-            "Proof is too long, has {} elements, but PROOF_LEN is {}",
+            "Proof is too long, has {} elements, but proof_len is {}",
             storage_proof.len(),
-            PROOF_LEN
+            proof_len
         );
     }
 
-    let mut padded_elements = vec![vec![0u8; ENCODING_LEN]; PROOF_LEN];
-    let mut lengths = vec![0usize; PROOF_LEN];
+    let mut padded_elements = vec![vec![0u8; encoding_len]; proof_len];
+    let mut lengths = vec![0usize; proof_len];
 
     for (i, inner_vec) in storage_proof.into_iter().enumerate() {
         // Check inner length
-        if inner_vec.len() > ENCODING_LEN {
+        if inner_vec.len() > encoding_len {
             panic!(
-                "Proof element {} is too long, has {} elements, but ENCODING_LEN is {}",
+                "Proof element {} is too long, has {} elements, but encoding_len is {}",
                 i,
                 inner_vec.len(),
-                ENCODING_LEN
+                encoding_len
             );
         }
         lengths[i] = inner_vec.len();
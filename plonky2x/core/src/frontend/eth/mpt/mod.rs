@@ -1,5 +1,9 @@
 pub mod builder;
 pub mod generators;
+pub mod node;
+pub mod proof;
+pub mod receipt;
 pub mod reference;
 pub mod rlc;
+pub mod transaction;
 pub mod utils;
@@ -0,0 +1,177 @@
+//! Constrained decoding of the RLP encoding of Merkle-Patricia trie nodes.
+//!
+//! A node is a list of 17 items for a branch node, or of 2 items for an extension or leaf node.
+//! The items are walked from the header of the list with the decoding of `rlp::item`, and the walk
+//! must end exactly at the end of the encoding. Only string items are supported, as in the state,
+//! storage and receipt tries: a node embedded in its parent, which is a list, makes the proof fail.
+
+use plonky2::field::types::Field;
+
+use crate::frontend::eth::rlp::item::RLPItemPosition;
+use crate::prelude::{BoolVariable, ByteVariable, CircuitBuilder, PlonkParameters, Variable};
+
+/// The length of the longest node of the state and storage tries, a branch node with 16 hashes,
+/// whose encoding is a 3-byte list header, 16 items of 33 bytes and an empty value.
+pub const MAX_MPT_NODE_LEN: usize = 3 + 16 * 33 + 1;
+
+/// The number of items of a branch node.
+pub const BRANCH_NODE_ITEMS: usize = 17;
+
+/// The position of the payload of an item in an RLP encoding.
+#[derive(Debug, Clone, Copy)]
+pub struct MptItem {
+    pub start: Variable,
+    pub len: Variable,
+}
+
+impl From<RLPItemPosition> for MptItem {
+    fn from(item: RLPItemPosition) -> Self {
+        Self {
+            start: item.start,
+            len: item.len,
+        }
+    }
+}
+
+/// A decoded node, with the positions of its first two items if it is not a branch node.
+#[derive(Debug, Clone)]
+pub struct MptNode {
+    pub is_branch: BoolVariable,
+    pub items: [MptItem; BRANCH_NODE_ITEMS],
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    pub(crate) fn mpt_constant(&mut self, value: usize) -> Variable {
+        self.constant::<Variable>(L::Field::from_canonical_usize(value))
+    }
+
+    /// Asserts that `condition` holds if `enabled` is set.
+    pub(crate) fn mpt_assert_if(&mut self, enabled: BoolVariable, condition: BoolVariable) {
+        let disabled = self.not(enabled);
+        let holds = self.or(disabled, condition);
+        let true_variable = self._true();
+        self.assert_is_equal(holds, true_variable);
+    }
+
+    /// Reads the string item of the encoding starting at `offset`, returning its payload and the
+    /// length of its encoding.
+    pub(crate) fn mpt_decode_item(
+        &mut self,
+        encoding: &[ByteVariable],
+        offset: Variable,
+        enabled: BoolVariable,
+    ) -> (MptItem, Variable) {
+        let item = self.rlp_decode_string(encoding, offset, enabled);
+        (MptItem::from(item), item.encoding_len)
+    }
+
+    /// Reads the header of the list item of the encoding starting at `offset`, returning its
+    /// payload and the length of its encoding.
+    pub(crate) fn mpt_decode_list(
+        &mut self,
+        encoding: &[ByteVariable],
+        offset: Variable,
+        enabled: BoolVariable,
+    ) -> (MptItem, Variable) {
+        let item = self.rlp_decode_list(encoding, offset, enabled);
+        (MptItem::from(item), item.encoding_len)
+    }
+
+    /// Decodes the node whose RLP encoding is the first `len` bytes of `encoding`. The encoding
+    /// must be followed by enough bytes for the reads past the end of the shortest items, which
+    /// callers provide by padding it with zeros.
+    ///
+    /// The decoding is only checked if `enabled` is set, so that the unused nodes of a fixed-size
+    /// proof can be anything.
+    pub fn mpt_decode_node(
+        &mut self,
+        encoding: &[ByteVariable],
+        len: Variable,
+        enabled: BoolVariable,
+    ) -> MptNode {
+        let zero = self.zero::<Variable>();
+        let (payload, encoding_len) = self.mpt_decode_list(encoding, zero, enabled);
+        let has_len = self.is_equal(encoding_len, len);
+        self.mpt_assert_if(enabled, has_len);
+
+        // The items of a node with two items end at the end of the encoding, and otherwise the
+        // node must be a branch node whose 17 items do.
+        let mut offset = payload.start;
+        let mut is_branch = self._false();
+        let mut branch_enabled = enabled;
+        let mut items = Vec::with_capacity(BRANCH_NODE_ITEMS);
+        for i in 0..BRANCH_NODE_ITEMS {
+            let item_enabled = if i < 2 { enabled } else { branch_enabled };
+            let (item, item_encoding_len) = self.mpt_decode_item(encoding, offset, item_enabled);
+            items.push(item);
+            offset = self.add(offset, item_encoding_len);
+            if i == 1 {
+                let ends = self.is_equal(offset, len);
+                is_branch = self.not(ends);
+                branch_enabled = self.and(enabled, is_branch);
+            }
+        }
+        let ends = self.is_equal(offset, len);
+        self.mpt_assert_if(branch_enabled, ends);
+
+        MptNode {
+            is_branch,
+            items: items.try_into().unwrap(),
+        }
+    }
+
+    /// Returns the payload of `item` padded with zeros to `LEN` bytes, checking that it is at most
+    /// `LEN` bytes long if `enabled` is set.
+    pub fn mpt_item_bytes<const LEN: usize>(
+        &mut self,
+        encoding: &[ByteVariable],
+        item: MptItem,
+        enabled: BoolVariable,
+    ) -> [ByteVariable; LEN] {
+        self.rlp_bytes::<LEN>(encoding, item.start, item.len, enabled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{ArrayVariable, DefaultBuilder, GoldilocksField};
+    use crate::utils::bytes;
+
+    #[test]
+    fn test_mpt_decode_node() {
+        const ENCODING_LEN: usize = 64;
+
+        // An extension node with the path `6f` pointing to a node with the given hash.
+        let encoding: Vec<u8> =
+            bytes!("0xe482006fa0188d1100731419827900267bf4e6ea6d428fa5a67656e021485d1f6c89e69be6");
+        let mut padded = encoding.clone();
+        padded.resize(ENCODING_LEN, 0);
+
+        let mut builder = DefaultBuilder::new();
+        let encoding_variable = builder.read::<ArrayVariable<ByteVariable, ENCODING_LEN>>();
+        let len = builder.read::<Variable>();
+        let mut extended = encoding_variable.as_vec();
+        extended.extend([builder.constant::<ByteVariable>(0); 33]);
+        let enabled = builder._true();
+        let node = builder.mpt_decode_node(&extended, len, enabled);
+        let path = builder.mpt_item_bytes::<33>(&extended, node.items[0], enabled);
+        let child = builder.mpt_item_bytes::<32>(&extended, node.items[1], enabled);
+        builder.write(node.is_branch);
+        builder.write(path);
+        builder.write(child);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<ByteVariable, ENCODING_LEN>>(padded);
+        input.write::<Variable>(GoldilocksField::from_canonical_usize(encoding.len()));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        assert!(!output.read::<BoolVariable>());
+        let mut expected_path = [0u8; 33];
+        expected_path[..2].copy_from_slice(&encoding[2..4]);
+        assert_eq!(output.read::<[ByteVariable; 33]>(), expected_path);
+        assert_eq!(output.read::<[ByteVariable; 32]>()[..], encoding[5..]);
+    }
+}
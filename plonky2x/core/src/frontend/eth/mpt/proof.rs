@@ -0,0 +1,450 @@
+//! Verification of Merkle-Patricia trie proofs, as returned by `eth_getProof`, against the root of
+//! the trie.
+//!
+//! The nodes of a proof are hashed with the constrained keccak256 gadget, decoded with
+//! `mpt_decode_node`, and followed from the root along the nibbles of the hashed key: a branch
+//! node consumes one nibble, and the path of an extension or leaf node must match the next nibbles
+//! of the key. Only inclusion proofs are supported, so the proof of a key absent from the trie,
+//! such as an empty storage slot, makes the proof fail.
+
+use plonky2::field::types::Field;
+
+use super::node::MptItem;
+use crate::frontend::eth::storage::vars::EthAccountVariable;
+use crate::frontend::eth::vars::AddressVariable;
+use crate::frontend::vars::{EvmVariable, Nibbles};
+use crate::prelude::{
+    ArrayVariable, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder,
+    PlonkParameters, U256Variable, U32Variable, Variable,
+};
+
+/// The number of nibbles of a key of the state and storage tries, and the maximum for the other
+/// tries.
+pub(crate) const KEY_NIBBLES: usize = 64;
+
+/// The length of the longest path of an extension or leaf node, one byte of flags followed by up to
+/// 32 bytes of nibbles.
+const MAX_PATH_LEN: usize = 33;
+
+/// The length of the longest storage value in the storage trie, the RLP encoding of a 32-byte word.
+pub const MAX_STORAGE_VALUE_LEN: usize = 33;
+
+/// The length of the longest account in the state trie, the RLP encoding of the list of an 8-byte
+/// nonce, a 32-byte balance, the storage root and the code hash.
+pub const MAX_ACCOUNT_VALUE_LEN: usize = 2 + 9 + 33 + 33 + 33;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the value of the leaf of the trie with root `root` at the hashed key `key`, padded
+    /// with zeros to `VALUE_LEN` bytes, and its length.
+    ///
+    /// The nodes of the proof are the first `len_nodes[i]` bytes of `proof[i]`, from the root to
+    /// the leaf, and the nodes after the leaf are ignored.
+    pub fn mpt_get<const ENCODING_LEN: usize, const PROOF_LEN: usize, const VALUE_LEN: usize>(
+        &mut self,
+        key: Bytes32Variable,
+        proof: &ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: &ArrayVariable<U32Variable, PROOF_LEN>,
+        root: Bytes32Variable,
+    ) -> (ArrayVariable<ByteVariable, VALUE_LEN>, Variable) {
+        let key_nibbles = ArrayVariable::new(key.as_bytes().to_vec().to_nibbles(self));
+        let key_len = self.constant::<Variable>(L::Field::from_canonical_usize(KEY_NIBBLES));
+        self.mpt_get_nibbles(&key_nibbles, key_len, proof, len_nodes, root)
+    }
+
+    /// The implementation of `mpt_get` for keys of `key_len` nibbles, padded to `KEY_NIBBLES`
+    /// nibbles, such as the unhashed keys of the transaction and receipt tries.
+    pub(crate) fn mpt_get_nibbles<
+        const ENCODING_LEN: usize,
+        const PROOF_LEN: usize,
+        const VALUE_LEN: usize,
+    >(
+        &mut self,
+        key_nibbles: &ArrayVariable<ByteVariable, KEY_NIBBLES>,
+        key_len: Variable,
+        proof: &ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: &ArrayVariable<U32Variable, PROOF_LEN>,
+        root: Bytes32Variable,
+    ) -> (ArrayVariable<ByteVariable, VALUE_LEN>, Variable) {
+        assert!(
+            VALUE_LEN >= 32,
+            "values must be at least as long as node hashes"
+        );
+        let zero_byte = self.constant::<ByteVariable>(0);
+        let zero = self.zero::<Variable>();
+        let one = self.one::<Variable>();
+        let two = self.constant::<Variable>(L::Field::from_canonical_usize(2));
+
+        let mut expected_hash = root;
+        let mut key_idx = zero;
+        let mut finished = self._false();
+        let mut value = [zero_byte; VALUE_LEN];
+        let mut value_len = zero;
+        for i in 0..PROOF_LEN {
+            let active = self.not(finished);
+            let hash = self.keccak256_variable(proof[i].as_slice(), len_nodes[i]);
+            let hash_matches = self.is_equal(hash, expected_hash);
+            self.mpt_assert_if(active, hash_matches);
+
+            // Every read of the items stays within the padding when the node is valid.
+            let mut encoding = proof[i].as_vec();
+            encoding.extend(vec![zero_byte; VALUE_LEN.max(MAX_PATH_LEN)]);
+            let node = self.mpt_decode_node(&encoding, len_nodes[i].variable, active);
+            let not_branch = self.not(node.is_branch);
+            let is_active_branch = self.and(active, node.is_branch);
+            let is_active_short = self.and(active, not_branch);
+
+            // The path of an extension or leaf node is its flags, whose low bit tells whether the
+            // number of nibbles is odd, followed by the nibbles, starting in the low nibble of the
+            // flags byte if the number is odd.
+            let path =
+                self.mpt_item_bytes::<MAX_PATH_LEN>(&encoding, node.items[0], is_active_short);
+            let path_nibbles = path.to_vec().to_nibbles(self);
+            let flags = path_nibbles[0].as_be_bits();
+            let flags_high = self.or(flags[4], flags[5]);
+            let flags_valid = self.not(flags_high);
+            self.mpt_assert_if(is_active_short, flags_valid);
+            let is_odd = flags[7];
+            let is_leaf_path = flags[6];
+            let is_even = self.not(is_odd);
+            let is_even_short = self.and(is_active_short, is_even);
+            let padding_is_zero = self.is_equal(path_nibbles[1], zero_byte);
+            self.mpt_assert_if(is_even_short, padding_is_zero);
+            let path_is_empty = self.is_equal(node.items[0].len, zero);
+            let path_is_present = self.not(path_is_empty);
+            self.mpt_assert_if(is_active_short, path_is_present);
+
+            let path_bytes_len = self.sub(node.items[0].len, one);
+            let path_len = self.mul(path_bytes_len, two);
+            let path_len = self.add(path_len, is_odd.variable);
+            let short_key_idx = self.select(is_active_short, key_idx, zero);
+            let remaining_key = self.rotate_left(key_nibbles.clone(), short_key_idx);
+            let false_variable = self._false();
+            let mut within_path = self._true();
+            let mut len_found = self._false();
+            for j in 0..=KEY_NIBBLES {
+                let index = self.constant::<Variable>(L::Field::from_canonical_usize(j));
+                let at_len = self.is_equal(index, path_len);
+                len_found = self.or(len_found, at_len);
+                within_path = self.select(at_len, false_variable, within_path);
+                if j < KEY_NIBBLES {
+                    let nibble = self.select(is_odd, path_nibbles[j + 1], path_nibbles[j + 2]);
+                    let matches = self.is_equal(nibble, remaining_key[j]);
+                    let check = self.and(is_active_short, within_path);
+                    self.mpt_assert_if(check, matches);
+                }
+            }
+            self.mpt_assert_if(is_active_short, len_found);
+
+            // A branch node continues with the item of the next nibble of the key, and the other
+            // nodes with their second item.
+            let branch_key_idx = self.select(is_active_branch, key_idx, zero);
+            let branch_nibble = self.select_slice_index(key_nibbles.as_slice(), branch_key_idx);
+            let branch_nibble = branch_nibble.to_variable(self);
+            let starts = node.items[..16]
+                .iter()
+                .map(|item| item.start)
+                .collect::<Vec<_>>();
+            let lens = node.items[..16]
+                .iter()
+                .map(|item| item.len)
+                .collect::<Vec<_>>();
+            let branch_item = MptItem {
+                start: self.select_slice_index(&starts, branch_nibble),
+                len: self.select_slice_index(&lens, branch_nibble),
+            };
+            let next_item = MptItem {
+                start: self.select(node.is_branch, branch_item.start, node.items[1].start),
+                len: self.select(node.is_branch, branch_item.len, node.items[1].len),
+            };
+            let next = self.mpt_item_bytes::<VALUE_LEN>(&encoding, next_item, active);
+
+            let is_leaf = self.and(is_active_short, is_leaf_path);
+            let not_leaf = self.not(is_leaf);
+            let is_hash = self.and(active, not_leaf);
+            let thirty_two = self.constant::<Variable>(L::Field::from_canonical_usize(32));
+            let next_is_hash = self.is_equal(next_item.len, thirty_two);
+            self.mpt_assert_if(is_hash, next_is_hash);
+            expected_hash = Bytes32Variable(BytesVariable(next[..32].try_into().unwrap()));
+
+            let consumed = self.select(node.is_branch, one, path_len);
+            let next_key_idx = self.add(key_idx, consumed);
+            let consumes_key = self.is_equal(next_key_idx, key_len);
+            self.mpt_assert_if(is_leaf, consumes_key);
+            // The key index stays in `0..=key_len`, as the paths of extension nodes fit in the
+            // remaining nibbles of the key.
+            key_idx = self.select(active, next_key_idx, key_idx);
+            let remaining = self.sub(key_len, key_idx);
+            self.api.range_check(remaining.0, 7);
+
+            for (value_byte, next_byte) in value.iter_mut().zip(next.iter()) {
+                *value_byte = self.select(is_leaf, *next_byte, *value_byte);
+            }
+            value_len = self.select(is_leaf, next_item.len, value_len);
+            finished = self.or(finished, is_leaf);
+        }
+        let true_variable = self._true();
+        self.assert_is_equal(finished, true_variable);
+
+        (ArrayVariable::new(value.to_vec()), value_len)
+    }
+
+    /// Returns the nibbles of `rlp(tx_index)`, the key of a transaction in the transaction and
+    /// receipt tries, padded with zeros, and their number. The index must be less than 2^16.
+    pub(crate) fn mpt_transaction_key(
+        &mut self,
+        tx_index: Variable,
+    ) -> (ArrayVariable<ByteVariable, KEY_NIBBLES>, Variable) {
+        let bits = self
+            .api
+            .split_le(tx_index.0, 16)
+            .into_iter()
+            .map(BoolVariable::from)
+            .collect::<Vec<_>>();
+        let byte = |bits: &[BoolVariable]| {
+            let mut be_bits: [BoolVariable; 8] = bits.try_into().unwrap();
+            be_bits.reverse();
+            ByteVariable::from_be_bits(be_bits)
+        };
+        let low = byte(&bits[..8]);
+        let high = byte(&bits[8..]);
+
+        // Indices below 0x80 are a single byte, except zero which is the empty string 0x80, and
+        // larger indices are one or two bytes prefixed with 0x81 or 0x82.
+        let mut high_is_set = self._false();
+        for bit in bits[8..].iter() {
+            high_is_set = self.or(high_is_set, *bit);
+        }
+        let fits_byte = self.not(high_is_set);
+        let below_0x80 = self.not(bits[7]);
+        let is_single = self.and(fits_byte, below_0x80);
+        let zero = self.zero::<Variable>();
+        let is_zero = self.is_equal(tx_index, zero);
+
+        let const_0x80 = self.constant::<ByteVariable>(0x80);
+        let const_0x81 = self.constant::<ByteVariable>(0x81);
+        let const_0x82 = self.constant::<ByteVariable>(0x82);
+        let prefix = self.select(fits_byte, const_0x81, const_0x82);
+        let first = self.select(is_single, low, prefix);
+        let first = self.select(is_zero, const_0x80, first);
+        let second = self.select(fits_byte, low, high);
+        let mut nibbles = vec![first, second, low].to_nibbles(self);
+        nibbles.resize(KEY_NIBBLES, self.constant::<ByteVariable>(0));
+
+        let two = self.constant::<Variable>(L::Field::from_canonical_usize(2));
+        let four = self.constant::<Variable>(L::Field::from_canonical_usize(4));
+        let six = self.constant::<Variable>(L::Field::from_canonical_usize(6));
+        let len = self.select(fits_byte, four, six);
+        let len = self.select(is_single, two, len);
+        (ArrayVariable::new(nibbles), len)
+    }
+
+    /// Returns the item of an RLP list at `offset` as a 32-byte big-endian word, such as a nonce, a
+    /// balance or a storage value, and the length of its encoding.
+    pub(crate) fn mpt_rlp_word(
+        &mut self,
+        encoding: &[ByteVariable],
+        offset: Variable,
+    ) -> ([ByteVariable; 32], Variable) {
+        let one = self.one::<Variable>();
+        let true_variable = self._true();
+        let prefix = self.select_slice_index(encoding, offset);
+        let prefix_variable = prefix.to_variable(self);
+
+        // A byte below 0x80 is its own encoding, and longer words are prefixed by 0x80 plus their
+        // length, which is at most 32.
+        let is_single = self.not(prefix.as_be_bits()[0]);
+        let const_0x80 = self.constant::<Variable>(L::Field::from_canonical_usize(0x80));
+        let len = self.sub(prefix_variable, const_0x80);
+        let len = self.select(is_single, one, len);
+        let start = self.add(offset, one);
+        let start = self.select(is_single, offset, start);
+        let bytes = self.mpt_item_bytes::<32>(encoding, MptItem { start, len }, true_variable);
+
+        // Right-align the word, whose bytes past its length are zero.
+        let thirty_two = self.constant::<Variable>(L::Field::from_canonical_usize(32));
+        let shift = self.sub(thirty_two, len);
+        let word = ArrayVariable::<ByteVariable, 32>::new(bytes.to_vec());
+        let word = self.rotate_right(word, shift);
+        let encoding_len = self.add(start, len);
+        let encoding_len = self.sub(encoding_len, offset);
+        (word.as_slice().try_into().unwrap(), encoding_len)
+    }
+
+    /// Verifies a proof of the storage slot `slot` in the storage trie with root `storage_root`,
+    /// returning the value of the slot.
+    pub fn mpt_verify_storage_proof<const ENCODING_LEN: usize, const PROOF_LEN: usize>(
+        &mut self,
+        storage_root: Bytes32Variable,
+        slot: Bytes32Variable,
+        proof: &ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: &ArrayVariable<U32Variable, PROOF_LEN>,
+    ) -> Bytes32Variable {
+        let key = self.keccak256(&slot.as_bytes());
+        let (value, value_len) = self.mpt_get::<ENCODING_LEN, PROOF_LEN, MAX_STORAGE_VALUE_LEN>(
+            key,
+            proof,
+            len_nodes,
+            storage_root,
+        );
+
+        // The leaf holds the RLP encoding of the word without its leading zeros.
+        let zero = self.zero::<Variable>();
+        let mut encoding = value.as_vec();
+        encoding.extend(vec![self.constant::<ByteVariable>(0); 32]);
+        let (word, word_encoding_len) = self.mpt_rlp_word(&encoding, zero);
+        let has_len = self.is_equal(word_encoding_len, value_len);
+        let true_variable = self._true();
+        self.assert_is_equal(has_len, true_variable);
+        Bytes32Variable(BytesVariable(word))
+    }
+
+    /// Verifies a proof of the account `address` in the state trie with root `state_root`,
+    /// returning the account.
+    pub fn mpt_verify_account_proof<const ENCODING_LEN: usize, const PROOF_LEN: usize>(
+        &mut self,
+        state_root: Bytes32Variable,
+        address: AddressVariable,
+        proof: &ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: &ArrayVariable<U32Variable, PROOF_LEN>,
+    ) -> EthAccountVariable {
+        let key = self.keccak256(&address.0 .0);
+        let (value, value_len) = self.mpt_get::<ENCODING_LEN, PROOF_LEN, MAX_ACCOUNT_VALUE_LEN>(
+            key, proof, len_nodes, state_root,
+        );
+        let mut encoding = value.as_vec();
+        encoding.extend(vec![self.constant::<ByteVariable>(0); 32]);
+        let true_variable = self._true();
+        let one = self.one::<Variable>();
+
+        // The account is the list `[nonce, balance, storage_root, code_hash]`, whose header is one
+        // byte, or 0xf8 followed by the length for lists of more than 55 bytes.
+        let f8 = self.constant::<ByteVariable>(0xf8);
+        let is_long = self.is_equal(encoding[0], f8);
+        let two = self.constant::<Variable>(L::Field::from_canonical_usize(2));
+        let header_len = self.select(is_long, two, one);
+        let (nonce, nonce_encoding_len) = self.mpt_rlp_word(&encoding, header_len);
+        let offset = self.add(header_len, nonce_encoding_len);
+        let (balance, balance_encoding_len) = self.mpt_rlp_word(&encoding, offset);
+        let offset = self.add(offset, balance_encoding_len);
+
+        let hash_prefix = self.constant::<ByteVariable>(0xa0);
+        let mut hashes = Vec::new();
+        let mut offset = offset;
+        for _ in 0..2 {
+            let prefix = self.select_slice_index(&encoding, offset);
+            let is_hash = self.is_equal(prefix, hash_prefix);
+            self.assert_is_equal(is_hash, true_variable);
+            let start = self.add(offset, one);
+            let thirty_two = self.constant::<Variable>(L::Field::from_canonical_usize(32));
+            let item = MptItem {
+                start,
+                len: thirty_two,
+            };
+            let hash = self.mpt_item_bytes::<32>(&encoding, item, true_variable);
+            hashes.push(Bytes32Variable(BytesVariable(hash)));
+            offset = self.add(start, thirty_two);
+        }
+        let has_len = self.is_equal(offset, value_len);
+        self.assert_is_equal(has_len, true_variable);
+
+        EthAccountVariable {
+            balance: U256Variable::decode(self, &balance),
+            code_hash: hashes[1],
+            nonce: U256Variable::decode(self, &nonce),
+            storage_hash: hashes[0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{H256, U256};
+
+    use super::super::builder::transform_proof_to_padded;
+    use super::super::utils::{read_fixture, EIP1186ProofResponse};
+    use super::*;
+    use crate::frontend::eth::utils::u256_to_h256_be;
+    use crate::prelude::DefaultBuilder;
+    use crate::utils::bytes32;
+
+    const ENCODING_LEN: usize = 600;
+    const PROOF_LEN: usize = 16;
+
+    fn padded_proof(proof: &[ethers::types::Bytes]) -> (Vec<Vec<u8>>, Vec<u32>) {
+        let proof = proof.iter().map(|node| node.to_vec()).collect::<Vec<_>>();
+        let (proof, lengths) = transform_proof_to_padded::<ENCODING_LEN, PROOF_LEN>(proof);
+        (proof, lengths.into_iter().map(|len| len as u32).collect())
+    }
+
+    fn prove_storage(fixture: &EIP1186ProofResponse, slot: H256) -> H256 {
+        let mut builder = DefaultBuilder::new();
+        let storage_root = builder.read::<Bytes32Variable>();
+        let slot_variable = builder.read::<Bytes32Variable>();
+        let proof =
+            builder.read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>();
+        let len_nodes = builder.read::<ArrayVariable<U32Variable, PROOF_LEN>>();
+        let value =
+            builder.mpt_verify_storage_proof(storage_root, slot_variable, &proof, &len_nodes);
+        builder.write(value);
+        let circuit = builder.mock_build();
+
+        let (proof, lengths) = padded_proof(&fixture.storage_proof[0].proof);
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(fixture.storage_hash);
+        input.write::<Bytes32Variable>(slot);
+        input.write::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>(proof);
+        input.write::<ArrayVariable<U32Variable, PROOF_LEN>>(lengths);
+        let (_witness, mut output) = circuit.mock_prove(&input);
+        output.read::<Bytes32Variable>()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_mpt_verify_storage_proof() {
+        let fixture = read_fixture("./src/frontend/eth/mpt/fixtures/example.json");
+        let value = prove_storage(&fixture, fixture.storage_proof[0].key);
+        assert_eq!(value, u256_to_h256_be(fixture.storage_proof[0].value));
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_mpt_verify_storage_proof_wrong_slot() {
+        let fixture = read_fixture("./src/frontend/eth/mpt/fixtures/example.json");
+        prove_storage(&fixture, H256::zero());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_mpt_verify_account_proof() {
+        let fixture = read_fixture("./src/frontend/eth/mpt/fixtures/example.json");
+        // The state root of block 17880427, at which the fixture was fetched.
+        let state_root =
+            bytes32!("0xff90251f501c864f21d696c811af4c3aa987006916bd0e31a6c06cc612e7632e");
+
+        let mut builder = DefaultBuilder::new();
+        let state_root_variable = builder.read::<Bytes32Variable>();
+        let address = builder.read::<AddressVariable>();
+        let proof =
+            builder.read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>();
+        let len_nodes = builder.read::<ArrayVariable<U32Variable, PROOF_LEN>>();
+        let account =
+            builder.mpt_verify_account_proof(state_root_variable, address, &proof, &len_nodes);
+        builder.write(account);
+        let circuit = builder.mock_build();
+
+        let (proof, lengths) = padded_proof(&fixture.account_proof);
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(state_root);
+        input.write::<AddressVariable>(fixture.address);
+        input.write::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>(proof);
+        input.write::<ArrayVariable<U32Variable, PROOF_LEN>>(lengths);
+        let (_witness, mut output) = circuit.mock_prove(&input);
+
+        let account = output.read::<EthAccountVariable>();
+        assert_eq!(account.nonce, U256::from(fixture.nonce.as_u64()));
+        assert_eq!(account.balance, fixture.balance);
+        assert_eq!(account.storage_hash, fixture.storage_hash);
+        assert_eq!(account.code_hash, fixture.code_hash);
+    }
+}
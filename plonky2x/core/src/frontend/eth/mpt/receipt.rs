@@ -0,0 +1,335 @@
+//! Verification of transaction receipts against the `receiptsRoot` of a block, and decoding of
+//! their logs.
+//!
+//! The receipt trie maps the RLP encoding of the index of each transaction in the block, which is
+//! not hashed, to its receipt. A receipt is `rlp([status, cumulativeGasUsed, logsBloom, logs])`,
+//! prefixed by the transaction type for typed transactions, and each log is
+//! `rlp([address, topics, data])`.
+
+use ethers::types::{H160, H256};
+
+use crate::frontend::eth::vars::AddressVariable;
+use crate::prelude::*;
+
+/// The maximum number of topics of a log, for the `LOG0` to `LOG4` opcodes.
+pub const MAX_LOG_TOPICS: usize = 4;
+
+/// A log of a receipt, whose topics past `nb_topics` are zero and whose data is padded with zeros
+/// to `DATA_LEN` bytes.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(ReceiptLog)]
+#[value_derive(PartialEq, Eq)]
+pub struct ReceiptLogVariable<const DATA_LEN: usize> {
+    pub address: AddressVariable,
+    pub nb_topics: Variable,
+    pub topics: ArrayVariable<Bytes32Variable, MAX_LOG_TOPICS>,
+    pub data: ArrayVariable<ByteVariable, DATA_LEN>,
+    pub data_len: Variable,
+}
+
+impl<const DATA_LEN: usize, F: RichField> ReceiptLog<DATA_LEN, F> {
+    pub fn new(address: H160, topics: &[H256], data: &[u8]) -> Self {
+        assert!(topics.len() <= MAX_LOG_TOPICS, "too many topics");
+        assert!(data.len() <= DATA_LEN, "data is too long");
+        let mut padded_topics = topics.to_vec();
+        padded_topics.resize(MAX_LOG_TOPICS, H256::zero());
+        let mut padded_data = data.to_vec();
+        padded_data.resize(DATA_LEN, 0);
+        Self {
+            address,
+            nb_topics: F::from_canonical_usize(topics.len()),
+            topics: padded_topics,
+            data: padded_data,
+            data_len: F::from_canonical_usize(data.len()),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Verifies a proof of the receipt of the transaction at `tx_index` in the receipt trie with
+    /// root `receipts_root`, returning the receipt padded with zeros to `RECEIPT_LEN` bytes and
+    /// its length.
+    pub fn mpt_verify_receipt_proof<
+        const ENCODING_LEN: usize,
+        const PROOF_LEN: usize,
+        const RECEIPT_LEN: usize,
+    >(
+        &mut self,
+        receipts_root: Bytes32Variable,
+        tx_index: Variable,
+        proof: &ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: &ArrayVariable<U32Variable, PROOF_LEN>,
+    ) -> (ArrayVariable<ByteVariable, RECEIPT_LEN>, Variable) {
+        let (key_nibbles, key_len) = self.mpt_transaction_key(tx_index);
+        self.mpt_get_nibbles(&key_nibbles, key_len, proof, len_nodes, receipts_root)
+    }
+
+    /// Decodes the log at `log_index` of the receipt whose encoding is the first `receipt_len`
+    /// bytes of `receipt`. The index must be less than `MAX_LOGS`, and the data of the log must be
+    /// at most `DATA_LEN` bytes long.
+    pub fn mpt_receipt_log<
+        const RECEIPT_LEN: usize,
+        const MAX_LOGS: usize,
+        const DATA_LEN: usize,
+    >(
+        &mut self,
+        receipt: &ArrayVariable<ByteVariable, RECEIPT_LEN>,
+        receipt_len: Variable,
+        log_index: Variable,
+    ) -> ReceiptLogVariable<DATA_LEN> {
+        let zero = self.zero::<Variable>();
+        let zero_byte = self.constant::<ByteVariable>(0);
+        let true_variable = self._true();
+        let false_variable = self._false();
+
+        // Every read of a valid receipt stays within the padding, including the data of a log
+        // at its end and the lengths that may follow a prefix.
+        let mut encoding = receipt.as_vec();
+        encoding.extend(vec![zero_byte; DATA_LEN + 2]);
+
+        // Typed receipts start with the transaction type, which is below 0x80, and legacy ones
+        // directly with the list of fields.
+        let is_typed = self.not(encoding[0].as_be_bits()[0]);
+        let fields_offset = is_typed.variable;
+        let (fields, fields_encoding_len) =
+            self.mpt_decode_list(&encoding, fields_offset, true_variable);
+        let receipt_end = self.add(fields_offset, fields_encoding_len);
+        self.assert_is_equal(receipt_end, receipt_len);
+
+        // Skip the status, the cumulative gas used and the logs bloom.
+        let mut offset = fields.start;
+        for _ in 0..3 {
+            let (_, item_encoding_len) = self.mpt_decode_item(&encoding, offset, true_variable);
+            offset = self.add(offset, item_encoding_len);
+        }
+        let (logs, logs_encoding_len) = self.mpt_decode_list(&encoding, offset, true_variable);
+        let logs_end = self.add(offset, logs_encoding_len);
+        self.assert_is_equal(logs_end, receipt_end);
+
+        // Walk the logs up to the selected one. The offsets of the logs after it are not
+        // meaningful, so they are replaced by zero to keep the reads within the encoding.
+        let mut log_offset = logs.start;
+        let mut selected_offset = zero;
+        let mut found = self._false();
+        for k in 0..MAX_LOGS {
+            let enabled = self.not(found);
+            let index = self.constant::<Variable>(L::Field::from_canonical_usize(k));
+            let is_selected = self.is_equal(index, log_index);
+            selected_offset = self.select(is_selected, log_offset, selected_offset);
+            found = self.or(found, is_selected);
+            let read_offset = self.select(enabled, log_offset, zero);
+            let (_, log_encoding_len) = self.mpt_decode_list(&encoding, read_offset, enabled);
+            log_offset = self.add(log_offset, log_encoding_len);
+        }
+        self.assert_is_equal(found, true_variable);
+
+        // The logs are contiguous from the start of the list, so the selected log and all the
+        // logs before it are in the list if it ends before the end of the list.
+        let (log, log_encoding_len) =
+            self.mpt_decode_list(&encoding, selected_offset, true_variable);
+        let log_end = self.add(selected_offset, log_encoding_len);
+        let remaining = self.sub(logs_end, log_end);
+        self.api.range_check(remaining.0, 32);
+
+        let (address, address_encoding_len) =
+            self.mpt_decode_item(&encoding, log.start, true_variable);
+        let twenty = self.constant::<Variable>(L::Field::from_canonical_usize(20));
+        self.assert_is_equal(address.len, twenty);
+        let address = self.mpt_item_bytes::<20>(&encoding, address, true_variable);
+        let offset = self.add(log.start, address_encoding_len);
+
+        // The topics are a list of 33-byte items, the prefix 0xa0 followed by the topic.
+        let (topics, topics_encoding_len) = self.mpt_decode_list(&encoding, offset, true_variable);
+        let thirty_two = self.constant::<Variable>(L::Field::from_canonical_usize(32));
+        let topic_encoding_len = self.constant::<Variable>(L::Field::from_canonical_usize(33));
+        let mut nb_topics = zero;
+        let mut nb_topics_found = self._false();
+        let mut is_present = self._true();
+        let mut topic_values = Vec::with_capacity(MAX_LOG_TOPICS);
+        for t in 0..=MAX_LOG_TOPICS {
+            let t_variable = self.constant::<Variable>(L::Field::from_canonical_usize(t));
+            let topics_len = self.constant::<Variable>(L::Field::from_canonical_usize(33 * t));
+            let is_nb_topics = self.is_equal(topics.len, topics_len);
+            nb_topics = self.select(is_nb_topics, t_variable, nb_topics);
+            nb_topics_found = self.or(nb_topics_found, is_nb_topics);
+            is_present = self.select(is_nb_topics, false_variable, is_present);
+            if t == MAX_LOG_TOPICS {
+                break;
+            }
+
+            let topic_offset = self.mul(t_variable, topic_encoding_len);
+            let topic_offset = self.add(topics.start, topic_offset);
+            let read_offset = self.select(is_present, topic_offset, zero);
+            let (topic, _) = self.mpt_decode_item(&encoding, read_offset, is_present);
+            let is_word = self.is_equal(topic.len, thirty_two);
+            self.mpt_assert_if(is_present, is_word);
+            let topic = self.mpt_item_bytes::<32>(&encoding, topic, is_present);
+            let topic = topic.map(|byte| self.select(is_present, byte, zero_byte));
+            topic_values.push(Bytes32Variable(BytesVariable(topic)));
+        }
+        self.assert_is_equal(nb_topics_found, true_variable);
+        let offset = self.add(offset, topics_encoding_len);
+
+        let (data, data_encoding_len) = self.mpt_decode_item(&encoding, offset, true_variable);
+        let data_end = self.add(offset, data_encoding_len);
+        self.assert_is_equal(data_end, log_end);
+        let data_bytes = self.mpt_item_bytes::<DATA_LEN>(&encoding, data, true_variable);
+
+        ReceiptLogVariable {
+            address: AddressVariable(BytesVariable(address)),
+            nb_topics,
+            topics: ArrayVariable::new(topic_values),
+            data: ArrayVariable::new(data_bytes.to_vec()),
+            data_len: data.len,
+        }
+    }
+
+    /// Verifies a proof of the receipt of the transaction at `tx_index` in the receipt trie with
+    /// root `receipts_root`, returning its log at `log_index`.
+    pub fn mpt_verify_receipt_log<
+        const ENCODING_LEN: usize,
+        const PROOF_LEN: usize,
+        const RECEIPT_LEN: usize,
+        const MAX_LOGS: usize,
+        const DATA_LEN: usize,
+    >(
+        &mut self,
+        receipts_root: Bytes32Variable,
+        tx_index: Variable,
+        log_index: Variable,
+        proof: &ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: &ArrayVariable<U32Variable, PROOF_LEN>,
+    ) -> ReceiptLogVariable<DATA_LEN> {
+        let (receipt, receipt_len) = self
+            .mpt_verify_receipt_proof::<ENCODING_LEN, PROOF_LEN, RECEIPT_LEN>(
+                receipts_root,
+                tx_index,
+                proof,
+                len_nodes,
+            );
+        self.mpt_receipt_log::<RECEIPT_LEN, MAX_LOGS, DATA_LEN>(&receipt, receipt_len, log_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::utils::keccak256;
+    use ethers::utils::rlp::RlpStream;
+
+    use super::super::builder::transform_proof_to_padded;
+    use super::*;
+    use crate::utils::{address, bytes32};
+
+    type Log = (H160, Vec<H256>, Vec<u8>);
+
+    fn encode_receipt(tx_type: Option<u8>, logs: &[Log]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&1u8);
+        stream.append(&21000u64);
+        stream.append(&vec![0u8; 256]);
+        stream.begin_list(logs.len());
+        for (address, topics, data) in logs {
+            stream.begin_list(3);
+            stream.append(address);
+            stream.append_list::<H256, H256>(topics);
+            stream.append(data);
+        }
+        let mut encoding = tx_type.map(|tx_type| vec![tx_type]).unwrap_or_default();
+        encoding.extend_from_slice(&stream.out());
+        encoding
+    }
+
+    fn encode_leaf(path: u8, value: &[u8]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&vec![path]);
+        stream.append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_mpt_verify_receipt_log() {
+        const ENCODING_LEN: usize = 640;
+        const PROOF_LEN: usize = 4;
+        const RECEIPT_LEN: usize = 576;
+        const MAX_LOGS: usize = 4;
+        const DATA_LEN: usize = 64;
+
+        let token = address!("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+        let transfer =
+            bytes32!("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+        let from = bytes32!("0x00000000000000000000000055032650b14df07b85bf18a3a3ec8e0af2e028d5");
+        let to = bytes32!("0x000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        let logs_0: Vec<Log> = vec![
+            (token, vec![transfer], vec![0xde, 0xad, 0xbe, 0xef]),
+            (token, vec![transfer, from, to], (0..40).collect()),
+        ];
+        let logs_1: Vec<Log> = vec![(
+            address!("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+            vec![],
+            vec![],
+        )];
+
+        // A trie of two receipts with the keys `rlp(0) = 0x80` and `rlp(1) = 0x01`, whose
+        // leaves hang from the children 8 and 0 of the root branch node with the odd paths `0`
+        // and `1`.
+        let leaf_0 = encode_leaf(0x30, &encode_receipt(Some(2), &logs_0));
+        let leaf_1 = encode_leaf(0x31, &encode_receipt(None, &logs_1));
+        let mut stream = RlpStream::new_list(17);
+        for i in 0..17 {
+            match i {
+                0 => stream.append(&keccak256(&leaf_1).to_vec()),
+                8 => stream.append(&keccak256(&leaf_0).to_vec()),
+                _ => stream.append_empty_data(),
+            };
+        }
+        let root_node = stream.out().to_vec();
+        let receipts_root = H256(keccak256(&root_node));
+
+        let mut builder = DefaultBuilder::new();
+        let receipts_root_variable = builder.read::<Bytes32Variable>();
+        let tx_index = builder.read::<Variable>();
+        let log_index = builder.read::<Variable>();
+        let proof =
+            builder.read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>();
+        let len_nodes = builder.read::<ArrayVariable<U32Variable, PROOF_LEN>>();
+        let log = builder
+            .mpt_verify_receipt_log::<ENCODING_LEN, PROOF_LEN, RECEIPT_LEN, MAX_LOGS, DATA_LEN>(
+                receipts_root_variable,
+                tx_index,
+                log_index,
+                &proof,
+                &len_nodes,
+            );
+        builder.write(log);
+        let circuit = builder.mock_build();
+
+        for (tx, leaf, log_index, log) in [
+            (0, &leaf_0, 1, &logs_0[1]),
+            (0, &leaf_0, 0, &logs_0[0]),
+            (1, &leaf_1, 0, &logs_1[0]),
+        ] {
+            let (proof, lengths) = transform_proof_to_padded::<ENCODING_LEN, PROOF_LEN>(vec![
+                root_node.clone(),
+                leaf.clone(),
+            ]);
+            let mut input = circuit.input();
+            input.write::<Bytes32Variable>(receipts_root);
+            input.write::<Variable>(GoldilocksField::from_canonical_usize(tx));
+            input.write::<Variable>(GoldilocksField::from_canonical_usize(log_index));
+            input.write::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>(
+                proof,
+            );
+            input.write::<ArrayVariable<U32Variable, PROOF_LEN>>(
+                lengths.into_iter().map(|len| len as u32).collect(),
+            );
+            let (_witness, mut output) = circuit.mock_prove(&input);
+
+            let (address, topics, data) = log;
+            assert_eq!(
+                output.read::<ReceiptLogVariable<DATA_LEN>>(),
+                ReceiptLog::new(*address, topics, data)
+            );
+        }
+    }
+}
@@ -0,0 +1,528 @@
+//! Verification of transactions against the `transactionsRoot` of a block, and decoding of their
+//! envelopes.
+//!
+//! The transaction trie maps the RLP encoding of the index of each transaction in the block to
+//! the transaction. A legacy transaction is
+//! `rlp([nonce, gasPrice, gas, to, value, data, v, r, s])`, and a typed transaction is its
+//! EIP-2718 type followed by the list of the fields of the type, of which access list (1), fee
+//! market (2) and blob (3) transactions are supported.
+//!
+//! The sender is recovered from the signature of the signing hash, the keccak256 of the encoding
+//! with the list of fields truncated before the signature. For a legacy transaction whose `v` is
+//! neither 27 nor 28, the truncated list is extended with `[chainId, 0, 0]` as specified by
+//! EIP-155, with a chain id less than 2^31.
+
+use ethers::types::U256;
+
+use crate::frontend::ecc::secp256k1::ecdsa::EcdsaSignatureVariable;
+use crate::frontend::eth::vars::AddressVariable;
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::*;
+
+/// The number of fields of the longest supported transactions, blob transactions.
+const MAX_TRANSACTION_FIELDS: usize = 14;
+
+/// The number of fields of legacy, access list, fee market and blob transactions, and the
+/// positions of the decoded fields among them.
+const NB_FIELDS: [usize; 4] = [9, 11, 12, 14];
+const NONCE: [usize; 4] = [0, 1, 1, 1];
+const TO: [usize; 4] = [3, 4, 5, 5];
+const VALUE: [usize; 4] = [4, 5, 6, 6];
+const DATA: [usize; 4] = [5, 6, 7, 7];
+
+/// The position of `v`, or of `yParity` for typed transactions, which are followed by `r` and `s`.
+const SIGNATURE: [usize; 4] = [6, 8, 9, 11];
+
+/// The length of the longest encoding of the items `[chainId, 0, 0]` of EIP-155.
+const MAX_EIP155_SUFFIX_LEN: usize = 7;
+
+/// A transaction, whose recipient is zero for contract creations and whose data is padded with
+/// zeros to `DATA_LEN` bytes. The type of legacy transactions is zero.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(EthTransaction)]
+#[value_derive(PartialEq, Eq)]
+pub struct EthTransactionVariable<const DATA_LEN: usize> {
+    pub tx_type: Variable,
+    pub sender: AddressVariable,
+    pub nonce: U256Variable,
+    pub is_create: BoolVariable,
+    pub to: AddressVariable,
+    pub value: U256Variable,
+    pub data: ArrayVariable<ByteVariable, DATA_LEN>,
+    pub data_len: Variable,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Verifies a proof of the transaction at `tx_index` in the transaction trie with root
+    /// `transactions_root`, returning the transaction padded with zeros to `TX_LEN` bytes and its
+    /// length.
+    pub fn mpt_verify_transaction_proof<
+        const ENCODING_LEN: usize,
+        const PROOF_LEN: usize,
+        const TX_LEN: usize,
+    >(
+        &mut self,
+        transactions_root: Bytes32Variable,
+        tx_index: Variable,
+        proof: &ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: &ArrayVariable<U32Variable, PROOF_LEN>,
+    ) -> (ArrayVariable<ByteVariable, TX_LEN>, Variable) {
+        let (key_nibbles, key_len) = self.mpt_transaction_key(tx_index);
+        self.mpt_get_nibbles(&key_nibbles, key_len, proof, len_nodes, transactions_root)
+    }
+
+    /// Returns the value of `values` for the type whose flag is set among `type_flags`.
+    fn mpt_select_by_type<V: CircuitVariable>(
+        &mut self,
+        type_flags: &[BoolVariable; 4],
+        values: [V; 4],
+    ) -> V {
+        let mut values = values.into_iter();
+        let mut value = values.next().unwrap();
+        for (flag, candidate) in type_flags[1..].iter().zip(values) {
+            value = self.select(*flag, candidate, value);
+        }
+        value
+    }
+
+    /// Decodes the transaction whose encoding is the first `transaction_len` bytes of
+    /// `transaction`, of at least 32 bytes, and recovers its sender. The data of the transaction
+    /// must be at most `DATA_LEN` bytes long.
+    pub fn mpt_decode_transaction<const TX_LEN: usize, const DATA_LEN: usize>(
+        &mut self,
+        transaction: &ArrayVariable<ByteVariable, TX_LEN>,
+        transaction_len: Variable,
+    ) -> EthTransactionVariable<DATA_LEN> {
+        let zero = self.zero::<Variable>();
+        let zero_byte = self.constant::<ByteVariable>(0);
+        let true_variable = self._true();
+        let false_variable = self._false();
+
+        // The words and the data read from a field may extend past the end of the encoding.
+        let mut encoding = transaction.as_vec();
+        encoding.extend(vec![zero_byte; TX_LEN.max(DATA_LEN) + 2]);
+
+        // A typed transaction starts with its type, which is below 0x80, and a legacy one with the
+        // header of its list of fields.
+        let first = encoding[0];
+        let is_typed = self.not(first.as_be_bits()[0]);
+        let is_legacy = self.not(is_typed);
+        let first_variable = first.to_variable(self);
+        let tx_type = self.select(is_typed, first_variable, zero);
+        let mut type_flags = [is_legacy; 4];
+        let mut is_supported = is_legacy;
+        for (i, flag) in type_flags.iter_mut().enumerate().skip(1) {
+            let i = self.mpt_constant(i);
+            let has_type = self.is_equal(tx_type, i);
+            *flag = self.and(is_typed, has_type);
+            is_supported = self.or(is_supported, *flag);
+        }
+        self.assert_is_equal(is_supported, true_variable);
+
+        let fields_offset = is_typed.variable;
+        let fields = self.rlp_decode_list(&encoding, fields_offset, true_variable);
+        let end = self.add(fields_offset, fields.encoding_len);
+        self.assert_is_equal(end, transaction_len);
+
+        // The list must hold exactly the fields of the type.
+        let (items, nb_items) =
+            self.rlp_decode_list_items::<MAX_TRANSACTION_FIELDS>(&encoding, &fields, true_variable);
+        let nb_fields = NB_FIELDS.map(|nb_fields| self.mpt_constant(nb_fields));
+        let nb_fields = self.mpt_select_by_type(&type_flags, nb_fields);
+        self.assert_is_equal(nb_items, nb_fields);
+        let offsets = items.map(|item| item.offset);
+        let field_offset = |builder: &mut Self, positions: [usize; 4], shift: usize| {
+            let offsets = positions.map(|position| offsets[position + shift]);
+            builder.mpt_select_by_type(&type_flags, offsets)
+        };
+
+        let nonce_offset = field_offset(self, NONCE, 0);
+        let (nonce, _) = self.mpt_rlp_word(&encoding, nonce_offset);
+        let nonce = U256Variable::decode(self, &nonce);
+
+        // The recipient is empty for contract creations.
+        let to_offset = field_offset(self, TO, 0);
+        let to = self.rlp_decode_string(&encoding, to_offset, true_variable);
+        let is_create = self.is_equal(to.len, zero);
+        let twenty = self.mpt_constant(20);
+        let is_call = self.is_equal(to.len, twenty);
+        let is_valid_to = self.or(is_create, is_call);
+        self.assert_is_equal(is_valid_to, true_variable);
+        let to = self.rlp_item_bytes::<20>(&encoding, &to, true_variable);
+
+        let value_offset = field_offset(self, VALUE, 0);
+        let (value, _) = self.mpt_rlp_word(&encoding, value_offset);
+        let value = U256Variable::decode(self, &value);
+
+        let data_offset = field_offset(self, DATA, 0);
+        let data = self.rlp_decode_string(&encoding, data_offset, true_variable);
+        let data_bytes = self.rlp_item_bytes::<DATA_LEN>(&encoding, &data, true_variable);
+
+        // The parity of the signature is `yParity` for typed transactions, and one minus the low
+        // bit of `v` for legacy ones, whose `v` is 27 or 28, or `2 * chainId + 35 + parity`.
+        let v_offset = field_offset(self, SIGNATURE, 0);
+        let r_offset = field_offset(self, SIGNATURE, 1);
+        let s_offset = field_offset(self, SIGNATURE, 2);
+        let (v_word, _) = self.mpt_rlp_word(&encoding, v_offset);
+        let (r, _) = self.mpt_rlp_word(&encoding, r_offset);
+        let (s, _) = self.mpt_rlp_word(&encoding, s_offset);
+        for byte in v_word[..28].iter() {
+            self.assert_is_equal(*byte, zero_byte);
+        }
+        let v = U32Variable::decode(self, &v_word[28..]);
+        let one_u32 = self.constant::<U32Variable>(1);
+        let is_parity = self.lte(v, one_u32);
+        self.mpt_assert_if(is_typed, is_parity);
+        let low_bit = v_word[31].as_be_bits()[7];
+        let not_low_bit = self.not(low_bit);
+        let parity = self.select(is_typed, low_bit, not_low_bit);
+
+        let v_27 = self.constant::<U32Variable>(27);
+        let v_28 = self.constant::<U32Variable>(28);
+        let is_27 = self.is_equal(v, v_27);
+        let is_28 = self.is_equal(v, v_28);
+        let is_unprotected = self.or(is_27, is_28);
+        let is_protected = self.not(is_unprotected);
+        let is_eip155 = self.and(is_legacy, is_protected);
+
+        // A `v` below 35 would make the chain id wrap around the field, past the range check.
+        let const_35 = self.mpt_constant(35);
+        let chain_id = self.sub(v.variable, const_35);
+        let chain_id = self.sub(chain_id, parity.variable);
+        let inverse_of_two = self.constant::<Variable>(L::Field::TWO.inverse());
+        let chain_id = self.mul(chain_id, inverse_of_two);
+        let chain_id = self.select(is_eip155, chain_id, zero);
+        self.api.range_check(chain_id.0, 31);
+        let chain_id = U32Variable::from_variables_unsafe(&[chain_id]);
+        let (suffix, suffix_len) = self.mpt_eip155_suffix(chain_id);
+        let suffix_len = self.select(is_eip155, suffix_len, zero);
+
+        // The signed message is the type of a typed transaction, the header of the signed list,
+        // the fields before the signature and the suffix of EIP-155. Its prefix is no longer than
+        // the one of the transaction, so the fields are read from the encoding with the prefix in
+        // front of them, which is then overwritten.
+        let body_len = self.sub(v_offset, fields.start);
+        let payload_len = self.add(body_len, suffix_len);
+        let (header, header_len) = self.mpt_list_header(payload_len);
+        let prefix_len = self.add(is_typed.variable, header_len);
+        let body_end = self.add(prefix_len, body_len);
+        let message_len = self.add(body_end, suffix_len);
+        let message_start = self.sub(fields.start, prefix_len);
+        let mut message =
+            self.rlp_bytes::<TX_LEN>(&encoding, message_start, body_end, true_variable);
+
+        let mut within_prefix = true_variable;
+        for (i, byte) in message.iter_mut().take(4).enumerate() {
+            let legacy_prefix = header.get(i).copied().unwrap_or(zero_byte);
+            let typed_prefix = if i == 0 { first } else { header[i - 1] };
+            let prefix_byte = self.select(is_typed, typed_prefix, legacy_prefix);
+            let index = self.mpt_constant(i);
+            let at_prefix_end = self.is_equal(index, prefix_len);
+            within_prefix = self.select(at_prefix_end, false_variable, within_prefix);
+            *byte = self.select(within_prefix, prefix_byte, *byte);
+        }
+
+        let mut padded_suffix = suffix.to_vec();
+        padded_suffix.resize(TX_LEN, zero_byte);
+        let padded_suffix = ArrayVariable::<ByteVariable, TX_LEN>::new(padded_suffix);
+        let shifted_suffix = self.rotate_right(padded_suffix, body_end);
+        let mut within_suffix = false_variable;
+        for (i, byte) in message.iter_mut().enumerate() {
+            let index = self.mpt_constant(i);
+            let at_suffix_start = self.is_equal(index, body_end);
+            let at_suffix_end = self.is_equal(index, message_len);
+            within_suffix = self.select(at_suffix_start, true_variable, within_suffix);
+            within_suffix = self.select(at_suffix_end, false_variable, within_suffix);
+            *byte = self.select(within_suffix, shifted_suffix[i], *byte);
+        }
+
+        // The message is shorter than the transaction, whose length fits in 32 bits.
+        let message_len = U32Variable::from_variables_unsafe(&[message_len]);
+        let message_hash = self.keccak256_variable(&message, message_len);
+        let v_27 = self.constant::<U256Variable>(U256::from(27));
+        let v_28 = self.constant::<U256Variable>(U256::from(28));
+        let v = self.select(parity, v_28, v_27);
+        let signature = EcdsaSignatureVariable {
+            r: U256Variable::decode(self, &r),
+            s: U256Variable::decode(self, &s),
+        };
+        let sender = self.ecrecover(message_hash, v, &signature);
+
+        EthTransactionVariable {
+            tx_type,
+            sender,
+            nonce,
+            is_create,
+            to: AddressVariable(BytesVariable(to)),
+            value,
+            data: ArrayVariable::new(data_bytes.to_vec()),
+            data_len: data.len,
+        }
+    }
+
+    /// Returns the header of a list whose payload is `payload_len` bytes long, less than 2^16,
+    /// padded with zeros to 3 bytes, and the length of the header.
+    fn mpt_list_header(&mut self, payload_len: Variable) -> ([ByteVariable; 3], Variable) {
+        let zero_byte = self.constant::<ByteVariable>(0);
+        let payload_len = U32Variable::from_variables_unsafe(&[payload_len]);
+        let bytes = payload_len.encode(self);
+        for byte in bytes[..2].iter() {
+            self.assert_is_equal(*byte, zero_byte);
+        }
+        let (high, low) = (bytes[2], bytes[3]);
+
+        let const_55 = self.constant::<U32Variable>(55);
+        let const_255 = self.constant::<U32Variable>(255);
+        let is_short = self.lte(payload_len, const_55);
+        let is_one_byte = self.lte(payload_len, const_255);
+        let const_0xc0 = self.mpt_constant(0xc0);
+        let short_prefix = self.add(payload_len.variable, const_0xc0);
+        let short_prefix = self.select(is_short, short_prefix, const_0xc0);
+        let short_prefix = ByteVariable::from_variable(self, short_prefix);
+        let one_byte_prefix = self.constant::<ByteVariable>(0xf8);
+        let two_byte_prefix = self.constant::<ByteVariable>(0xf9);
+
+        let long_prefix = self.select(is_one_byte, one_byte_prefix, two_byte_prefix);
+        let second = self.select(is_one_byte, low, high);
+        let third = self.select(is_one_byte, zero_byte, low);
+        let header = [
+            self.select(is_short, short_prefix, long_prefix),
+            self.select(is_short, zero_byte, second),
+            self.select(is_short, zero_byte, third),
+        ];
+        let one = self.one::<Variable>();
+        let two = self.mpt_constant(2);
+        let three = self.mpt_constant(3);
+        let header_len = self.select(is_one_byte, two, three);
+        let header_len = self.select(is_short, one, header_len);
+        (header, header_len)
+    }
+
+    /// Returns the encoding of the items `[chainId, 0, 0]`, padded with zeros to
+    /// `MAX_EIP155_SUFFIX_LEN` bytes, and its length.
+    fn mpt_eip155_suffix(
+        &mut self,
+        chain_id: U32Variable,
+    ) -> ([ByteVariable; MAX_EIP155_SUFFIX_LEN], Variable) {
+        let zero_byte = self.constant::<ByteVariable>(0);
+        let empty = self.constant::<ByteVariable>(0x80);
+        let bytes = chain_id.encode(self);
+
+        // A chain id is its length followed by its significant bytes, except for a single byte
+        // below 0x80, which is its own encoding, and zero, which is the empty string.
+        let mut suffix = [zero_byte; MAX_EIP155_SUFFIX_LEN];
+        suffix[..3].copy_from_slice(&[empty; 3]);
+        let mut suffix_len = self.mpt_constant(3);
+        let mut leading_zeros = self._true();
+        for i in 0..4 {
+            let is_zero = self.is_equal(bytes[i], zero_byte);
+            let is_nonzero = self.not(is_zero);
+            let is_first = self.and(leading_zeros, is_nonzero);
+            leading_zeros = self.and(leading_zeros, is_zero);
+
+            let nb_bytes = 4 - i;
+            let mut candidate = vec![self.constant::<ByteVariable>(0x80 + nb_bytes as u8)];
+            candidate.extend_from_slice(&bytes[i..]);
+            candidate.extend([empty; 2]);
+            candidate.resize(MAX_EIP155_SUFFIX_LEN, zero_byte);
+            let mut candidate_len = self.mpt_constant(nb_bytes + 3);
+            if nb_bytes == 1 {
+                let is_small = self.not(bytes[i].as_be_bits()[0]);
+                let mut small_candidate = vec![bytes[i], empty, empty];
+                small_candidate.resize(MAX_EIP155_SUFFIX_LEN, zero_byte);
+                for (byte, small_byte) in candidate.iter_mut().zip(small_candidate) {
+                    *byte = self.select(is_small, small_byte, *byte);
+                }
+                let small_candidate_len = self.mpt_constant(3);
+                candidate_len = self.select(is_small, small_candidate_len, candidate_len);
+            }
+            for (byte, candidate_byte) in suffix.iter_mut().zip(candidate) {
+                *byte = self.select(is_first, candidate_byte, *byte);
+            }
+            suffix_len = self.select(is_first, candidate_len, suffix_len);
+        }
+        (suffix, suffix_len)
+    }
+
+    /// Verifies a proof of the transaction at `tx_index` in the transaction trie with root
+    /// `transactions_root`, and decodes it.
+    pub fn mpt_verify_transaction<
+        const ENCODING_LEN: usize,
+        const PROOF_LEN: usize,
+        const TX_LEN: usize,
+        const DATA_LEN: usize,
+    >(
+        &mut self,
+        transactions_root: Bytes32Variable,
+        tx_index: Variable,
+        proof: &ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: &ArrayVariable<U32Variable, PROOF_LEN>,
+    ) -> EthTransactionVariable<DATA_LEN> {
+        let (transaction, transaction_len) = self
+            .mpt_verify_transaction_proof::<ENCODING_LEN, PROOF_LEN, TX_LEN>(
+                transactions_root,
+                tx_index,
+                proof,
+                len_nodes,
+            );
+        self.mpt_decode_transaction::<TX_LEN, DATA_LEN>(&transaction, transaction_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::types::transaction::eip2930::{AccessList, AccessListItem};
+    use ethers::types::{
+        Eip1559TransactionRequest, Eip2930TransactionRequest, TransactionRequest, H160, H256,
+    };
+    use ethers::utils::keccak256;
+    use ethers::utils::rlp::RlpStream;
+
+    use super::super::builder::transform_proof_to_padded;
+    use super::*;
+    use crate::utils::address;
+
+    fn encode_leaf(path: u8, value: &[u8]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&vec![path]);
+        stream.append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    fn encode_branch(children: &[(usize, &[u8])]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(17);
+        for i in 0..17 {
+            match children.iter().find(|(index, _)| *index == i) {
+                Some((_, child)) => stream.append(&keccak256(child).to_vec()),
+                None => stream.append_empty_data(),
+            };
+        }
+        stream.out().to_vec()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_mpt_verify_transaction() {
+        const ENCODING_LEN: usize = 320;
+        const PROOF_LEN: usize = 4;
+        const TX_LEN: usize = 256;
+        const DATA_LEN: usize = 64;
+
+        let wallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse::<LocalWallet>()
+            .unwrap()
+            .with_chain_id(1u64);
+        let recipient = address!("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        let legacy = TypedTransaction::Legacy(
+            TransactionRequest::new()
+                .nonce(7)
+                .gas_price(20_000_000_000u64)
+                .gas(60_000)
+                .to(recipient)
+                .value(1_000_000_000_000_000_000u64)
+                .data((0..40).collect::<Vec<u8>>())
+                .chain_id(1),
+        );
+        let fee_market = TypedTransaction::Eip1559(
+            Eip1559TransactionRequest::new()
+                .nonce(300)
+                .max_priority_fee_per_gas(1_000_000_000u64)
+                .max_fee_per_gas(30_000_000_000u64)
+                .gas(50_000)
+                .to(recipient)
+                .value(12_345)
+                .data(vec![0xd0, 0xe3, 0x0d, 0xb0])
+                .chain_id(1),
+        );
+        let access_list = TypedTransaction::Eip2930(Eip2930TransactionRequest::new(
+            TransactionRequest::new()
+                .nonce(0)
+                .gas_price(10_000_000_000u64)
+                .gas(1_000_000)
+                .data(vec![0x60; DATA_LEN])
+                .chain_id(1),
+            AccessList(vec![AccessListItem {
+                address: recipient,
+                storage_keys: vec![H256::zero()],
+            }]),
+        ));
+        let transactions = [legacy, fee_market, access_list];
+        let encodings = transactions
+            .iter()
+            .map(|tx| {
+                let signature = wallet.sign_transaction_sync(tx).unwrap();
+                tx.rlp_signed(&signature).to_vec()
+            })
+            .collect::<Vec<_>>();
+
+        // A trie of three transactions with the keys `rlp(0) = 0x80`, `rlp(1) = 0x01` and
+        // `rlp(2) = 0x02`. The first hangs from the child 8 of the root branch node with the odd
+        // path `0`, and the others from a branch node at the child 0 with empty paths.
+        let leaf_0 = encode_leaf(0x30, &encodings[0]);
+        let leaf_1 = encode_leaf(0x20, &encodings[1]);
+        let leaf_2 = encode_leaf(0x20, &encodings[2]);
+        let inner_node = encode_branch(&[(1, &leaf_1), (2, &leaf_2)]);
+        let root_node = encode_branch(&[(0, &inner_node), (8, &leaf_0)]);
+        let transactions_root = H256(keccak256(&root_node));
+
+        let mut builder = DefaultBuilder::new();
+        let transactions_root_variable = builder.read::<Bytes32Variable>();
+        let tx_index = builder.read::<Variable>();
+        let proof =
+            builder.read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>();
+        let len_nodes = builder.read::<ArrayVariable<U32Variable, PROOF_LEN>>();
+        let transaction = builder
+            .mpt_verify_transaction::<ENCODING_LEN, PROOF_LEN, TX_LEN, DATA_LEN>(
+                transactions_root_variable,
+                tx_index,
+                &proof,
+                &len_nodes,
+            );
+        builder.write(transaction);
+        let circuit = builder.mock_build();
+
+        let proofs = [
+            vec![root_node.clone(), leaf_0],
+            vec![root_node.clone(), inner_node.clone(), leaf_1],
+            vec![root_node, inner_node, leaf_2],
+        ];
+        for (i, (tx, nodes)) in transactions.iter().zip(proofs).enumerate() {
+            let (proof, lengths) = transform_proof_to_padded::<ENCODING_LEN, PROOF_LEN>(nodes);
+            let mut input = circuit.input();
+            input.write::<Bytes32Variable>(transactions_root);
+            input.write::<Variable>(GoldilocksField::from_canonical_usize(i));
+            input.write::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>(
+                proof,
+            );
+            input.write::<ArrayVariable<U32Variable, PROOF_LEN>>(
+                lengths.into_iter().map(|len| len as u32).collect(),
+            );
+            let (_witness, mut output) = circuit.mock_prove(&input);
+
+            let tx_type = match tx {
+                TypedTransaction::Legacy(_) => 0,
+                TypedTransaction::Eip2930(_) => 1,
+                TypedTransaction::Eip1559(_) => 2,
+            };
+            let data = tx.data().unwrap().to_vec();
+            let mut padded_data = data.clone();
+            padded_data.resize(DATA_LEN, 0);
+            let to = tx.to().map(|to| *to.as_address().unwrap());
+            assert_eq!(
+                output.read::<EthTransactionVariable<DATA_LEN>>(),
+                EthTransaction {
+                    tx_type: GoldilocksField::from_canonical_usize(tx_type),
+                    sender: wallet.address(),
+                    nonce: *tx.nonce().unwrap(),
+                    is_create: to.is_none(),
+                    to: to.unwrap_or(H160::zero()),
+                    value: tx.value().copied().unwrap_or_default(),
+                    data: padded_data,
+                    data_len: GoldilocksField::from_canonical_usize(data.len()),
+                }
+            );
+        }
+    }
+}
@@ -26,6 +26,8 @@
 //!
 //! 1 and 1' are in this file, decoder.rs. 2 and 2' are in utils.rs. Finally, 3 and 3' are in
 //! builder.rs.
+//!
+//! The constrained decoding of items of any kind at variable offsets is in item.rs.
 
 use crate::utils::stream::Stream;
 
@@ -0,0 +1,300 @@
+//! Constrained decoding of RLP items at variable offsets.
+//!
+//! An encoding is a slice of bytes, which callers pad with zeros past its end so that the reads of
+//! the prefix and the length bytes of the last items stay within it. An item is decoded from the
+//! offset of its prefix into its position in the encoding: where its payload starts, how long it
+//! is, how long the whole encoding of the item is, and whether it is a list. The items of a list
+//! are then decoded one after the other from the start of its payload.
+//!
+//! Lengths of up to two bytes are supported, so payloads must be shorter than 2^16 bytes.
+
+use plonky2::field::types::Field;
+
+use crate::prelude::{BoolVariable, ByteVariable, CircuitBuilder, PlonkParameters, Variable};
+
+/// The position of an item in an RLP encoding.
+#[derive(Debug, Clone, Copy)]
+pub struct RLPItemPosition {
+    /// The offset of the prefix of the item.
+    pub offset: Variable,
+    /// The offset of the payload, which is the prefix itself for a single byte below 0x80.
+    pub start: Variable,
+    /// The length of the payload.
+    pub len: Variable,
+    /// The length of the encoding of the item, from its prefix to the end of its payload.
+    pub encoding_len: Variable,
+    pub is_list: BoolVariable,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    fn rlp_constant(&mut self, value: usize) -> Variable {
+        self.constant::<Variable>(L::Field::from_canonical_usize(value))
+    }
+
+    fn rlp_assert_if(&mut self, enabled: BoolVariable, condition: BoolVariable) {
+        let disabled = self.not(enabled);
+        let holds = self.or(disabled, condition);
+        let true_variable = self._true();
+        self.assert_is_equal(holds, true_variable);
+    }
+
+    /// Decodes the string or list item whose prefix is at `offset`. The prefix must be valid if
+    /// `enabled` is set, and the position is meaningless otherwise.
+    pub fn rlp_decode_item(
+        &mut self,
+        encoding: &[ByteVariable],
+        offset: Variable,
+        enabled: BoolVariable,
+    ) -> RLPItemPosition {
+        let one = self.one::<Variable>();
+        let two = self.rlp_constant(2);
+        let three = self.rlp_constant(3);
+        let const_256 = self.rlp_constant(256);
+        let offset_plus_one = self.add(offset, one);
+        let offset_plus_two = self.add(offset, two);
+        let prefix = self.select_slice_index(encoding, offset);
+        let first_len_byte = self.select_slice_index(encoding, offset_plus_one);
+        let second_len_byte = self.select_slice_index(encoding, offset_plus_two);
+        let first_len_byte = first_len_byte.to_variable(self);
+        let second_len_byte = second_len_byte.to_variable(self);
+
+        // A byte below 0x80 is its own encoding. Above, the prefix of a string or a list is 0x80
+        // or 0xc0 plus the length of a payload of up to 55 bytes, or 0xb7 or 0xf7 plus the number
+        // of bytes of the length of a longer payload.
+        let bits = prefix.as_be_bits();
+        let is_single = self.not(bits[0]);
+        let is_list = self.and(bits[0], bits[1]);
+        let is_long = self.and(bits[2], bits[3]);
+        let is_long = self.and(is_long, bits[4]);
+        let is_short = self.not(is_long);
+        let is_short = self.and(bits[0], is_short);
+        let not_bit_5 = self.not(bits[5]);
+        let not_bit_6 = self.not(bits[6]);
+        let has_length_bytes = self.and(is_long, not_bit_5);
+        let has_length_bytes = self.and(has_length_bytes, not_bit_6);
+        let not_bit_7 = self.not(bits[7]);
+        let is_one_byte = self.and(has_length_bytes, not_bit_7);
+        let is_two_byte = self.and(has_length_bytes, bits[7]);
+        let is_valid = self.or(is_single, is_short);
+        let is_valid = self.or(is_valid, is_one_byte);
+        let is_valid = self.or(is_valid, is_two_byte);
+        self.rlp_assert_if(enabled, is_valid);
+
+        let prefix = prefix.to_variable(self);
+        let const_0x80 = self.rlp_constant(0x80);
+        let const_0xc0 = self.rlp_constant(0xc0);
+        let base = self.select(is_list, const_0xc0, const_0x80);
+        let short_len = self.sub(prefix, base);
+        let two_byte_len = self.mul(first_len_byte, const_256);
+        let two_byte_len = self.add(two_byte_len, second_len_byte);
+        let len = self.select(is_one_byte, first_len_byte, two_byte_len);
+        let len = self.select(is_short, short_len, len);
+        let len = self.select(is_single, one, len);
+        let header_len = self.select(is_one_byte, two, three);
+        let header_len = self.select(is_short, one, header_len);
+        let zero = self.zero::<Variable>();
+        let header_len = self.select(is_single, zero, header_len);
+
+        RLPItemPosition {
+            offset,
+            start: self.add(offset, header_len),
+            len,
+            encoding_len: self.add(header_len, len),
+            is_list,
+        }
+    }
+
+    /// Decodes the string item whose prefix is at `offset`, checked if `enabled` is set.
+    pub fn rlp_decode_string(
+        &mut self,
+        encoding: &[ByteVariable],
+        offset: Variable,
+        enabled: BoolVariable,
+    ) -> RLPItemPosition {
+        let item = self.rlp_decode_item(encoding, offset, enabled);
+        let is_string = self.not(item.is_list);
+        self.rlp_assert_if(enabled, is_string);
+        item
+    }
+
+    /// Decodes the list item whose prefix is at `offset`, checked if `enabled` is set.
+    pub fn rlp_decode_list(
+        &mut self,
+        encoding: &[ByteVariable],
+        offset: Variable,
+        enabled: BoolVariable,
+    ) -> RLPItemPosition {
+        let item = self.rlp_decode_item(encoding, offset, enabled);
+        self.rlp_assert_if(enabled, item.is_list);
+        item
+    }
+
+    /// Decodes the items of `list`, of which there must be at most `MAX_ITEMS` ending exactly at
+    /// the end of its payload if `enabled` is set. Returns the positions of the items and their
+    /// number. The positions past the number of items are decoded from the start of the encoding.
+    pub fn rlp_decode_list_items<const MAX_ITEMS: usize>(
+        &mut self,
+        encoding: &[ByteVariable],
+        list: &RLPItemPosition,
+        enabled: BoolVariable,
+    ) -> ([RLPItemPosition; MAX_ITEMS], Variable) {
+        let zero = self.zero::<Variable>();
+        let end = self.add(list.start, list.len);
+        let mut offset = list.start;
+        let mut nb_items = zero;
+        let mut found = self._false();
+        let mut items = Vec::with_capacity(MAX_ITEMS);
+        for i in 0..=MAX_ITEMS {
+            let at_end = self.is_equal(offset, end);
+            let is_first_end = self.not(found);
+            let is_first_end = self.and(is_first_end, at_end);
+            let index = self.rlp_constant(i);
+            nb_items = self.select(is_first_end, index, nb_items);
+            found = self.or(found, at_end);
+            if i == MAX_ITEMS {
+                break;
+            }
+
+            let is_present = self.not(found);
+            let item_enabled = self.and(enabled, is_present);
+            let read_offset = self.select(is_present, offset, zero);
+            let item = self.rlp_decode_item(encoding, read_offset, item_enabled);
+            items.push(item);
+            offset = self.add(offset, item.encoding_len);
+            offset = self.select(is_present, offset, end);
+        }
+        self.rlp_assert_if(enabled, found);
+        (items.try_into().unwrap(), nb_items)
+    }
+
+    /// Returns the `len` bytes of the encoding at `start` padded with zeros to `LEN` bytes,
+    /// checking that `len` is at most `LEN` if `enabled` is set.
+    pub fn rlp_bytes<const LEN: usize>(
+        &mut self,
+        encoding: &[ByteVariable],
+        start: Variable,
+        len: Variable,
+        enabled: BoolVariable,
+    ) -> [ByteVariable; LEN] {
+        let zero = self.constant::<ByteVariable>(0);
+        let false_variable = self._false();
+        let mut within_item = self._true();
+        let mut len_found = self._false();
+        let mut bytes = [zero; LEN];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            let index = self.rlp_constant(i);
+            let at_len = self.is_equal(index, len);
+            len_found = self.or(len_found, at_len);
+            within_item = self.select(at_len, false_variable, within_item);
+            let position = self.add(start, index);
+            let encoding_byte = self.select_slice_index(encoding, position);
+            *byte = self.select(within_item, encoding_byte, zero);
+        }
+        let index = self.rlp_constant(LEN);
+        let at_len = self.is_equal(index, len);
+        len_found = self.or(len_found, at_len);
+        self.rlp_assert_if(enabled, len_found);
+        bytes
+    }
+
+    /// Returns the payload of `item` padded with zeros to `LEN` bytes, checking that it is at most
+    /// `LEN` bytes long if `enabled` is set.
+    pub fn rlp_item_bytes<const LEN: usize>(
+        &mut self,
+        encoding: &[ByteVariable],
+        item: &RLPItemPosition,
+        enabled: BoolVariable,
+    ) -> [ByteVariable; LEN] {
+        self.rlp_bytes::<LEN>(encoding, item.start, item.len, enabled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::utils::rlp::RlpStream;
+
+    use super::*;
+    use crate::prelude::{ArrayVariable, DefaultBuilder, GoldilocksField};
+
+    #[test]
+    fn test_rlp_decode_list_items() {
+        type F = GoldilocksField;
+        const ENCODING_LEN: usize = 96;
+        const MAX_ITEMS: usize = 6;
+
+        // The list `["cat", ["dog", []], 0x0f, long]`, with a long string of 60 bytes, whose items
+        // are a short string, a nested list, a single byte and a long string.
+        let long = vec![0xaa; 60];
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&"cat");
+        stream.begin_list(2);
+        stream.append(&"dog");
+        stream.begin_list(0);
+        stream.append(&0x0fu8);
+        stream.append(&long);
+        let encoding = stream.out().to_vec();
+        let mut padded = encoding.clone();
+        padded.resize(ENCODING_LEN, 0);
+
+        let mut builder = DefaultBuilder::new();
+        let encoding_variable = builder.read::<ArrayVariable<ByteVariable, ENCODING_LEN>>();
+        let mut extended = encoding_variable.as_vec();
+        extended.extend([builder.constant::<ByteVariable>(0); 64]);
+        let zero = builder.zero::<Variable>();
+        let enabled = builder._true();
+        let list = builder.rlp_decode_list(&extended, zero, enabled);
+        let (items, nb_items) =
+            builder.rlp_decode_list_items::<MAX_ITEMS>(&extended, &list, enabled);
+        let (nested_items, nb_nested_items) =
+            builder.rlp_decode_list_items::<MAX_ITEMS>(&extended, &items[1], enabled);
+        builder.write(list.encoding_len);
+        builder.write(nb_items);
+        for item in items[..4].iter() {
+            builder.write(item.offset);
+            builder.write(item.start);
+            builder.write(item.len);
+            builder.write(item.is_list);
+        }
+        builder.write(nb_nested_items);
+        builder.write(nested_items[1].is_list);
+        let cat = builder.rlp_item_bytes::<4>(&extended, &items[0], enabled);
+        let long_bytes = builder.rlp_item_bytes::<64>(&extended, &items[3], enabled);
+        builder.write(ArrayVariable::<ByteVariable, 4>::new(cat.to_vec()));
+        builder.write(ArrayVariable::<ByteVariable, 64>::new(long_bytes.to_vec()));
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<ByteVariable, ENCODING_LEN>>(padded);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        assert_eq!(
+            output.read::<Variable>(),
+            F::from_canonical_usize(encoding.len())
+        );
+        assert_eq!(output.read::<Variable>(), F::from_canonical_usize(4));
+        for (offset, start, len, is_list) in [
+            (2, 3, 3, false),
+            (6, 7, 5, true),
+            (12, 12, 1, false),
+            (13, 15, 60, false),
+        ] {
+            assert_eq!(output.read::<Variable>(), F::from_canonical_usize(offset));
+            assert_eq!(output.read::<Variable>(), F::from_canonical_usize(start));
+            assert_eq!(output.read::<Variable>(), F::from_canonical_usize(len));
+            assert_eq!(output.read::<BoolVariable>(), is_list);
+        }
+        assert_eq!(output.read::<Variable>(), F::from_canonical_usize(2));
+        assert!(output.read::<BoolVariable>());
+        assert_eq!(
+            output.read::<ArrayVariable<ByteVariable, 4>>(),
+            b"cat\0".to_vec()
+        );
+        let mut expected_long = long;
+        expected_long.resize(64, 0);
+        assert_eq!(
+            output.read::<ArrayVariable<ByteVariable, 64>>(),
+            expected_long
+        );
+    }
+}
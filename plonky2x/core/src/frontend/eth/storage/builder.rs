@@ -1,14 +1,18 @@
 use ethers::types::Address;
 
 use super::generators::{
-    EthBlockGenerator, EthLogGenerator, EthStorageKeyGenerator, EthStorageProofHint,
+    EthAccountStorageProofHint, EthBlockGenerator, EthLogGenerator, EthStorageKeyGenerator,
+    EthStorageProofBatchHint, EthStorageProofHint,
 };
 use super::vars::{EthAccountVariable, EthHeaderVariable, EthLogVariable};
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::eth::vars::AddressVariable;
 use crate::frontend::uint::uint256::U256Variable;
-use crate::frontend::vars::{Bytes32Variable, VariableStream};
+use crate::frontend::vars::{
+    ArrayVariable, ByteVariable, Bytes32Variable, BytesVariable, EvmVariable, U32Variable,
+    VariableStream,
+};
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     pub fn get_storage_key_at_witness(
@@ -22,6 +26,37 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         value
     }
 
+    /// Computes the storage slot of `mapping[key]` for a Solidity mapping stored at `base_slot`,
+    /// i.e. `keccak256(abi.encode(key, base_slot))`.
+    ///
+    /// The key is left-padded to 32 bytes, which is how Solidity lays out value-type keys such as
+    /// addresses, integers, booleans and `bytes32`. Keys of type `bytesN` with `N < 32` are
+    /// right-padded by Solidity and must be passed as a right-padded `Bytes32Variable`.
+    pub fn mapping_slot<K: EvmVariable>(
+        &mut self,
+        base_slot: U256Variable,
+        key: K,
+    ) -> Bytes32Variable {
+        let key = key.encode(self);
+        assert!(key.len() <= 32, "mapping keys must fit in a word");
+        let zero = self.constant::<ByteVariable>(0);
+        let mut preimage = vec![zero; 32 - key.len()];
+        preimage.extend(key);
+        preimage.extend(base_slot.encode(self));
+        self.keccak256(&preimage)
+    }
+
+    /// Computes the storage slot of `array[index]` for a dynamic Solidity array stored at
+    /// `base_slot`, i.e. `keccak256(base_slot) + index`. For elements that span several slots,
+    /// `index` must be scaled by the number of slots per element.
+    pub fn array_slot(&mut self, base_slot: U256Variable, index: U256Variable) -> Bytes32Variable {
+        let base_slot_bytes = base_slot.encode(self);
+        let data_start = self.keccak256(&base_slot_bytes);
+        let data_start = U256Variable::decode(self, &data_start.0 .0);
+        let slot = self.add(data_start, index);
+        Bytes32Variable(BytesVariable(slot.encode(self).try_into().unwrap()))
+    }
+
     pub fn eth_get_storage_at_witness(
         &mut self,
         block_hash: Bytes32Variable,
@@ -39,6 +74,81 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         output_stream.read::<Bytes32Variable>(self)
     }
 
+    /// Reads the storage value of `(address, storage_key)` at the block with state root
+    /// `state_root`, verifying the account proof and the storage proof returned by `eth_getProof`
+    /// in the circuit. The caller is responsible for checking that `state_root` is the state root
+    /// of the block `block_hash`, from which the proofs are fetched.
+    ///
+    /// The proofs must have at most `ACCOUNT_PROOF_LEN` and `STORAGE_PROOF_LEN` nodes of at most
+    /// `ENCODING_LEN` bytes, and the slot must be non-empty.
+    pub fn eth_get_storage_at<
+        const ENCODING_LEN: usize,
+        const ACCOUNT_PROOF_LEN: usize,
+        const STORAGE_PROOF_LEN: usize,
+    >(
+        &mut self,
+        block_hash: Bytes32Variable,
+        state_root: Bytes32Variable,
+        address: AddressVariable,
+        storage_key: Bytes32Variable,
+    ) -> Bytes32Variable {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&block_hash);
+        input_stream.write(&address);
+        input_stream.write(&storage_key);
+
+        let hint = EthAccountStorageProofHint::new(
+            self,
+            ENCODING_LEN,
+            ACCOUNT_PROOF_LEN,
+            STORAGE_PROOF_LEN,
+        );
+        let output_stream = self.async_hint(input_stream, hint);
+        let account_proof = output_stream
+            .read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, ACCOUNT_PROOF_LEN>>(
+                self,
+            );
+        let account_len_nodes =
+            output_stream.read::<ArrayVariable<U32Variable, ACCOUNT_PROOF_LEN>>(self);
+        let storage_proof = output_stream
+            .read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, STORAGE_PROOF_LEN>>(
+                self,
+            );
+        let storage_len_nodes =
+            output_stream.read::<ArrayVariable<U32Variable, STORAGE_PROOF_LEN>>(self);
+
+        let account =
+            self.mpt_verify_account_proof(state_root, address, &account_proof, &account_len_nodes);
+        self.mpt_verify_storage_proof(
+            account.storage_hash,
+            storage_key,
+            &storage_proof,
+            &storage_len_nodes,
+        )
+    }
+
+    /// Reads the storage values of many `(address, storage_key)` slots at the same block. The
+    /// values are fetched together, with one `eth_getProof` request per distinct address.
+    pub fn eth_get_storage_batch_witness(
+        &mut self,
+        block_hash: Bytes32Variable,
+        slots: &[(AddressVariable, Bytes32Variable)],
+    ) -> Vec<Bytes32Variable> {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&block_hash);
+        for (address, storage_key) in slots.iter() {
+            input_stream.write(address);
+            input_stream.write(storage_key);
+        }
+
+        let hint = EthStorageProofBatchHint::new(self, slots.len());
+        let output_stream = self.async_hint(input_stream, hint);
+
+        (0..slots.len())
+            .map(|_| output_stream.read::<Bytes32Variable>(self))
+            .collect()
+    }
+
     pub fn eth_get_block_by_hash_witness(
         &mut self,
         block_hash: Bytes32Variable,
@@ -75,7 +185,8 @@ mod tests {
     use std::env;
 
     use ethers::providers::{Http, Provider};
-    use ethers::types::{U256, U64};
+    use ethers::types::{H256, U256, U64};
+    use ethers::utils::keccak256;
     use log::debug;
 
     use super::*;
@@ -156,6 +267,45 @@ mod tests {
         circuit.verify(&proof, &input, &output);
     }
 
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_eth_get_storage_at() {
+        utils::setup_logger();
+        dotenv::dotenv().ok();
+        let rpc_url = env::var("RPC_1").unwrap();
+        let provider = Provider::<Http>::try_from(rpc_url).unwrap();
+
+        let mut builder = DefaultBuilder::new();
+        builder.set_execution_client(provider);
+        let block_hash = builder.evm_read::<Bytes32Variable>();
+        let state_root = builder.evm_read::<Bytes32Variable>();
+        let address = builder.evm_read::<AddressVariable>();
+        let location = builder.evm_read::<Bytes32Variable>();
+        let value =
+            builder.eth_get_storage_at::<600, 16, 16>(block_hash, state_root, address, location);
+        builder.evm_write(value);
+        let circuit = builder.mock_build();
+
+        // These values are taken from Ethereum block https://etherscan.io/block/17880427
+        let mut input = circuit.input();
+        input.evm_write::<Bytes32Variable>(bytes32!(
+            "0x281dc31bb78779a1ede7bf0f4d2bc5f07ddebc9f9d1155e413d8804384604bbe"
+        ));
+        input.evm_write::<Bytes32Variable>(bytes32!(
+            "0xff90251f501c864f21d696c811af4c3aa987006916bd0e31a6c06cc612e7632e"
+        ));
+        input.evm_write::<AddressVariable>(address!("0x55032650b14df07b85bF18A3a3eC8E0Af2e028d5"));
+        input.evm_write::<Bytes32Variable>(bytes32!(
+            "0xad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5"
+        ));
+
+        let (_witness, mut output) = circuit.mock_prove(&input);
+        assert_eq!(
+            output.evm_read::<Bytes32Variable>(),
+            bytes32!("0x0000000000000000000000dd4bc51496dc93a0c47008e820e0d80745476f2201"),
+        );
+    }
+
     #[test]
     #[cfg_attr(feature = "ci", ignore)]
     #[allow(non_snake_case)]
@@ -217,6 +367,66 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_eth_get_storage_batch_witness() {
+        utils::setup_logger();
+        dotenv::dotenv().ok();
+        let rpc_url = env::var("RPC_1").unwrap();
+        let provider = Provider::<Http>::try_from(rpc_url).unwrap();
+
+        let num_slots = 10;
+
+        // This is the circuit definition
+        let mut builder = DefaultBuilder::new();
+        builder.set_execution_client(provider);
+        let block_hash = builder.evm_read::<Bytes32Variable>();
+        let slots = (0..num_slots)
+            .map(|_| {
+                let address = builder.evm_read::<AddressVariable>();
+                let location = builder.evm_read::<Bytes32Variable>();
+                (address, location)
+            })
+            .collect::<Vec<_>>();
+        let values = builder.eth_get_storage_batch_witness(block_hash, &slots);
+        for value in values {
+            builder.evm_write(value);
+        }
+
+        // Build your circuit.
+        let circuit = builder.build();
+
+        // Write to the circuit input.
+        // These values are taken from Ethereum block https://etherscan.io/block/17880427
+        let mut input = circuit.input();
+        input.evm_write::<Bytes32Variable>(bytes32!(
+            "0x281dc31bb78779a1ede7bf0f4d2bc5f07ddebc9f9d1155e413d8804384604bbe"
+        ));
+        for _ in 0..num_slots {
+            input.evm_write::<AddressVariable>(address!(
+                "0x55032650b14df07b85bF18A3a3eC8E0Af2e028d5"
+            ));
+            input.evm_write::<Bytes32Variable>(bytes32!(
+                "0xad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5"
+            ));
+        }
+
+        // Generate a proof.
+        let (proof, mut output) = circuit.prove(&input);
+
+        // Verify proof.
+        circuit.verify(&proof, &input, &output);
+
+        // Read output.
+        for _ in 0..num_slots {
+            let circuit_value = output.evm_read::<Bytes32Variable>();
+            assert_eq!(
+                circuit_value,
+                bytes32!("0x0000000000000000000000dd4bc51496dc93a0c47008e820e0d80745476f2201"),
+            );
+        }
+    }
+
     #[test]
     #[cfg_attr(feature = "ci", ignore)]
     #[allow(non_snake_case)]
@@ -417,4 +627,48 @@ mod tests {
             .serialize(&gate_serializer, &hint_serializer)
             .unwrap();
     }
+
+    #[test]
+    fn test_mapping_and_array_slots() {
+        let mut builder = DefaultBuilder::new();
+        let base_slot = builder.read::<U256Variable>();
+        let key = builder.read::<Bytes32Variable>();
+        let holder = builder.read::<AddressVariable>();
+        let index = builder.read::<U256Variable>();
+
+        let slot = builder.mapping_slot(base_slot, key);
+        builder.write(slot);
+        let slot = builder.mapping_slot(base_slot, holder);
+        builder.write(slot);
+        let slot = builder.array_slot(base_slot, index);
+        builder.write(slot);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        let map_key =
+            bytes32!("0x281dc31bb78779a1ede7bf0f4d2bc5f07ddebc9f9d1155e413d8804384604bbe");
+        let holder = address!("0x55032650b14df07b85bF18A3a3eC8E0Af2e028d5");
+        input.write::<U256Variable>(U256::from(3));
+        input.write::<Bytes32Variable>(map_key);
+        input.write::<AddressVariable>(holder);
+        input.write::<U256Variable>(U256::from(5));
+
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            get_map_storage_location(3, map_key)
+        );
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            get_map_storage_location(3, H256::from(holder))
+        );
+        let mut base_slot = [0u8; 32];
+        base_slot[31] = 3;
+        let data_start = U256::from(keccak256(base_slot));
+        let mut expected = [0u8; 32];
+        (data_start + 5).to_big_endian(&mut expected);
+        assert_eq!(output.read::<Bytes32Variable>(), H256::from(expected));
+    }
 }
@@ -3,5 +3,6 @@ mod storage;
 
 pub use block::EthBlockGenerator;
 pub use storage::{
-    EthLogGenerator, EthStorageKeyGenerator, EthStorageProofGenerator, EthStorageProofHint,
+    EthAccountStorageProofHint, EthLogGenerator, EthStorageKeyGenerator, EthStorageProofBatchHint,
+    EthStorageProofGenerator, EthStorageProofHint,
 };
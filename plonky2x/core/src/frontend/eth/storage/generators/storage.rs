@@ -3,7 +3,7 @@ use core::marker::PhantomData;
 
 use async_trait::async_trait;
 use ethers::providers::Middleware;
-use ethers::types::{EIP1186ProofResponse, TransactionReceipt};
+use ethers::types::TransactionReceipt;
 use futures::executor;
 use log::debug;
 use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
@@ -17,14 +17,17 @@ use tokio::runtime::Runtime;
 
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::eth::mpt::builder::pad_proof_nodes;
 use crate::frontend::eth::storage::utils::get_map_storage_location;
 use crate::frontend::eth::storage::vars::{EthLog, EthLogVariable};
-use crate::frontend::eth::utils::u256_to_h256_be;
 use crate::frontend::eth::vars::AddressVariable;
 use crate::frontend::hint::asynchronous::hint::AsyncHint;
 use crate::frontend::uint::uint256::U256Variable;
-use crate::frontend::vars::{Bytes32Variable, CircuitVariable, ValueStream};
+use crate::frontend::vars::{
+    ByteVariable, Bytes32Variable, CircuitVariable, U32Variable, ValueStream,
+};
 use crate::utils::eth::get_provider;
+use crate::utils::eth::storage::{get_storage_proof, get_storage_values};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EthStorageProofHint<L: PlonkParameters<D>, const D: usize> {
@@ -53,13 +56,130 @@ impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for EthStorageProofH
         let address = input_stream.read_value::<AddressVariable>();
         let location = input_stream.read_value::<Bytes32Variable>();
 
-        let provider = get_provider(self.chain_id);
-        let result = provider
-            .get_proof(address, vec![location], Some(block_hash.into()))
+        let values = get_storage_values(self.chain_id, block_hash, &[(address, location)])
+            .await
+            .expect("Failed to get proof");
+        output_stream.write_value::<Bytes32Variable>(values[0]);
+    }
+}
+
+/// A hint that fetches many storage slots at the same block.
+///
+/// Slots belonging to the same address are fetched with a single `eth_getProof` request and
+/// slots that were already fetched by other hints are served from a shared cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EthStorageProofBatchHint<L: PlonkParameters<D>, const D: usize> {
+    chain_id: u64,
+    nb_slots: usize,
+    _phantom: PhantomData<L>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> EthStorageProofBatchHint<L, D> {
+    pub fn new(builder: &CircuitBuilder<L, D>, nb_slots: usize) -> EthStorageProofBatchHint<L, D> {
+        let chain_id = builder.get_chain_id();
+        EthStorageProofBatchHint {
+            chain_id,
+            nb_slots,
+            _phantom: PhantomData::<L>,
+        }
+    }
+}
+
+#[async_trait]
+impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for EthStorageProofBatchHint<L, D> {
+    async fn hint(
+        &self,
+        input_stream: &mut ValueStream<L, D>,
+        output_stream: &mut ValueStream<L, D>,
+    ) {
+        let block_hash = input_stream.read_value::<Bytes32Variable>();
+        let slots = (0..self.nb_slots)
+            .map(|_| {
+                let address = input_stream.read_value::<AddressVariable>();
+                let location = input_stream.read_value::<Bytes32Variable>();
+                (address, location)
+            })
+            .collect::<Vec<_>>();
+
+        let values = get_storage_values(self.chain_id, block_hash, &slots)
+            .await
+            .expect("Failed to get proofs");
+        for value in values {
+            output_stream.write_value::<Bytes32Variable>(value);
+        }
+    }
+}
+
+/// A hint that fetches the account proof and the storage proof of a slot, for the circuit to
+/// verify against the state root. Each proof is written as `proof_len` nodes padded with zeros to
+/// `encoding_len` bytes, followed by the lengths of the nodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EthAccountStorageProofHint<L: PlonkParameters<D>, const D: usize> {
+    chain_id: u64,
+    encoding_len: usize,
+    account_proof_len: usize,
+    storage_proof_len: usize,
+    _phantom: PhantomData<L>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> EthAccountStorageProofHint<L, D> {
+    pub fn new(
+        builder: &CircuitBuilder<L, D>,
+        encoding_len: usize,
+        account_proof_len: usize,
+        storage_proof_len: usize,
+    ) -> EthAccountStorageProofHint<L, D> {
+        let chain_id = builder.get_chain_id();
+        EthAccountStorageProofHint {
+            chain_id,
+            encoding_len,
+            account_proof_len,
+            storage_proof_len,
+            _phantom: PhantomData::<L>,
+        }
+    }
+
+    fn write_proof(
+        &self,
+        proof: &[ethers::types::Bytes],
+        proof_len: usize,
+        output_stream: &mut ValueStream<L, D>,
+    ) {
+        let proof = proof.iter().map(|node| node.to_vec()).collect::<Vec<_>>();
+        let (nodes, lengths) = pad_proof_nodes(proof, self.encoding_len, proof_len);
+        for byte in nodes.into_iter().flatten() {
+            output_stream.write_value::<ByteVariable>(byte);
+        }
+        for len in lengths {
+            output_stream.write_value::<U32Variable>(len as u32);
+        }
+    }
+}
+
+#[async_trait]
+impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for EthAccountStorageProofHint<L, D> {
+    async fn hint(
+        &self,
+        input_stream: &mut ValueStream<L, D>,
+        output_stream: &mut ValueStream<L, D>,
+    ) {
+        let block_hash = input_stream.read_value::<Bytes32Variable>();
+        let address = input_stream.read_value::<AddressVariable>();
+        let location = input_stream.read_value::<Bytes32Variable>();
+
+        let response = get_storage_proof(self.chain_id, block_hash, address, location)
             .await
             .expect("Failed to get proof");
-        let value = u256_to_h256_be(result.storage_proof[0].value);
-        output_stream.write_value::<Bytes32Variable>(value);
+        self.write_proof(
+            &response.account_proof,
+            self.account_proof_len,
+            output_stream,
+        );
+        self.write_proof(
+            &response.storage_proof[0].proof,
+            self.storage_proof_len,
+            output_stream,
+        );
     }
 }
 
@@ -120,21 +240,17 @@ impl<L: PlonkParameters<D>, const D: usize> SimpleGenerator<L::Field, D>
         let address = self.address.get(witness);
         let location = self.storage_key.get(witness);
         let block_hash = self.block_hash.get(witness);
-        let provider = get_provider(self.chain_id);
-        let result: EIP1186ProofResponse = executor::block_on(async {
+        let values = executor::block_on(async {
             debug!(
                 "querying proof {:?} {:?} {:?}",
                 address, location, block_hash
             );
-            let proof = provider
-                .get_proof(address, vec![location], Some(block_hash.into()))
+            get_storage_values(self.chain_id, block_hash, &[(address, location)])
                 .await
-                .expect("Failed to get proof");
-            debug!("got proof {:?}", proof.storage_proof[0].value);
-            proof
+                .expect("Failed to get proof")
         });
-        let value = u256_to_h256_be(result.storage_proof[0].value);
-        self.value.set(buffer, value);
+        debug!("got proof {:?}", values[0]);
+        self.value.set(buffer, values[0]);
     }
 
     #[allow(unused_variables)]
@@ -1,4 +1,5 @@
 pub mod builder;
 pub mod generators;
+pub mod packed;
 pub mod utils;
 pub mod vars;
@@ -0,0 +1,147 @@
+//! Decoding of storage slots holding several packed Solidity variables.
+//!
+//! Solidity packs consecutive state variables smaller than 32 bytes into a single slot, starting
+//! from the low-order end of the word. For example, `bool a; address b; uint64 c;` is stored as
+//! `0x00..00 || c || b || a`.
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::vars::{BoolVariable, ByteVariable, Bytes32Variable, EvmVariable};
+
+/// The sizes, in bytes and in declaration order, of the variables packed into a storage slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedSlotLayout {
+    sizes: Vec<usize>,
+}
+
+impl PackedSlotLayout {
+    pub fn new(sizes: &[usize]) -> Self {
+        assert!(
+            sizes.iter().all(|size| *size > 0),
+            "variables cannot be empty"
+        );
+        assert!(
+            sizes.iter().sum::<usize>() <= 32,
+            "variables do not fit in a single slot"
+        );
+        Self {
+            sizes: sizes.to_vec(),
+        }
+    }
+
+    /// The number of variables in the slot.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// The size in bytes of the `i`-th variable.
+    pub fn size(&self, i: usize) -> usize {
+        self.sizes[i]
+    }
+
+    /// The offset in bytes of the `i`-th variable from the low-order end of the slot.
+    pub fn offset(&self, i: usize) -> usize {
+        self.sizes[..i].iter().sum()
+    }
+
+    /// The range of the `i`-th variable in the big endian bytes of the slot.
+    fn range(&self, i: usize) -> std::ops::Range<usize> {
+        let end = 32 - self.offset(i);
+        end - self.size(i)..end
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Splits a storage word into the big endian bytes of each variable in `layout`.
+    ///
+    /// Masking and shifting a packed slot only selects bytes of the word, so the outputs are
+    /// fully constrained by the word itself.
+    pub fn unpack_storage_slot(
+        &mut self,
+        word: Bytes32Variable,
+        layout: &PackedSlotLayout,
+    ) -> Vec<Vec<ByteVariable>> {
+        (0..layout.len())
+            .map(|i| word.0 .0[layout.range(i)].to_vec())
+            .collect()
+    }
+
+    /// Decodes the `i`-th variable of `layout` from a storage word, e.g. an `AddressVariable` or
+    /// a `U64Variable`. The size of the variable in `layout` must match the size of `V`.
+    pub fn unpack_storage_field<V: EvmVariable>(
+        &mut self,
+        word: Bytes32Variable,
+        layout: &PackedSlotLayout,
+        i: usize,
+    ) -> V {
+        V::decode(self, &word.0 .0[layout.range(i)])
+    }
+
+    /// Decodes the `i`-th variable of `layout`, which must be a `bool`, from a storage word.
+    /// Solidity stores booleans as a full byte, so the other bits of the byte are constrained to
+    /// be zero.
+    pub fn unpack_storage_bool(
+        &mut self,
+        word: Bytes32Variable,
+        layout: &PackedSlotLayout,
+        i: usize,
+    ) -> BoolVariable {
+        assert_eq!(layout.size(i), 1, "booleans take a single byte");
+        let bits = word.0 .0[layout.range(i).start].as_be_bits();
+        let false_v = self._false();
+        for bit in bits[..7].iter() {
+            self.assert_is_equal(*bit, false_v);
+        }
+        bits[7]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::eth::vars::AddressVariable;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_unpack_storage_slot() {
+        let mut builder = DefaultBuilder::new();
+        let word = builder.read::<Bytes32Variable>();
+
+        // bool a; address b; uint64 c;
+        let layout = PackedSlotLayout::new(&[1, 20, 8]);
+        let a = builder.unpack_storage_bool(word, &layout, 0);
+        let b = builder.unpack_storage_field::<AddressVariable>(word, &layout, 1);
+        let c = builder.unpack_storage_field::<U64Variable>(word, &layout, 2);
+        builder.write(a);
+        builder.write(b);
+        builder.write(c);
+
+        // uint128 lo; uint128 hi;
+        let layout = PackedSlotLayout::new(&[16, 16]);
+        let lo = builder.unpack_storage_field::<U128Variable>(word, &layout, 0);
+        let hi = builder.unpack_storage_field::<U128Variable>(word, &layout, 1);
+        builder.write(lo);
+        builder.write(hi);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(bytes32!(
+            "0x00000000000000000000070d369bb49efa5100fd3b86a9f828c55da04d2d5001"
+        ));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        assert!(output.read::<BoolVariable>());
+        assert_eq!(
+            output.read::<AddressVariable>(),
+            address!("0x0d369bb49efa5100fd3b86a9f828c55da04d2d50")
+        );
+        assert_eq!(output.read::<U64Variable>(), 7);
+        assert_eq!(
+            output.read::<U128Variable>(),
+            0xfa5100fd3b86a9f828c55da04d2d5001u128.into()
+        );
+        assert_eq!(output.read::<U128Variable>(), 0x070d369bb49eu128.into());
+    }
+}
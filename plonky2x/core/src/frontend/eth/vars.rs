@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 
 use ethers::types::H160;
+use ethers::utils::to_checksum;
 use plonky2::hash::hash_types::RichField;
 
 use crate::backend::circuit::PlonkParameters;
@@ -9,6 +10,7 @@ use crate::frontend::vars::{
     ByteVariable, BytesVariable, CircuitVariable, EvmVariable, SSZVariable,
 };
 use crate::prelude::{Bytes32Variable, Variable};
+use crate::value::EvmValue;
 
 #[derive(Debug, Clone, Copy)]
 pub struct BLSPubkeyVariable(pub BytesVariable<48>);
@@ -50,6 +52,15 @@ impl CircuitVariable for BLSPubkeyVariable {
     }
 }
 
+impl SSZVariable for BLSPubkeyVariable {
+    fn hash_tree_root<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Bytes32Variable {
+        builder.ssz_basic_vector_root(&self.0 .0)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct AddressVariable(pub BytesVariable<20>);
 
@@ -74,6 +85,11 @@ impl CircuitVariable for AddressVariable {
         H160::from_slice(&BytesVariable::<20>::from_elements(elements))
     }
 
+    /// Formats the address with its EIP-55 checksum.
+    fn format_value<F: RichField>(value: &Self::ValueType<F>) -> String {
+        to_checksum(value, None)
+    }
+
     fn variables(&self) -> Vec<Variable> {
         self.0.variables()
     }
@@ -106,11 +122,11 @@ impl EvmVariable for AddressVariable {
     }
 
     fn encode_value<F: RichField>(value: Self::ValueType<F>) -> Vec<u8> {
-        value.as_bytes().to_vec()
+        value.encode()
     }
 
     fn decode_value<F: RichField>(bytes: &[u8]) -> Self::ValueType<F> {
-        H160::from_slice(bytes)
+        H160::decode(bytes)
     }
 }
 
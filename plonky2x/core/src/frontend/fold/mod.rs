@@ -12,7 +12,7 @@ use crate::frontend::fold::util::common_data_for_recursion;
 use crate::prelude::{CircuitBuilder, CircuitVariable, PlonkParameters, U32Variable};
 
 pub mod generator;
-mod util;
+pub(crate) mod util;
 
 pub trait FoldBuilderMethods<L: PlonkParameters<D>, const D: usize> {
     fn fold<Definition, Ctx, Element, Accumulator, Serializer>(
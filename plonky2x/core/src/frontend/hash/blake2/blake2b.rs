@@ -0,0 +1,250 @@
+//! Implementation of unkeyed BLAKE2b-512 with the compression function in the circuit.
+//! reference: https://datatracker.ietf.org/doc/html/rfc7693
+
+use array_macro::array;
+use itertools::Itertools;
+
+use crate::frontend::hash::common::xor2_arr;
+use crate::prelude::*;
+
+/// The block size of BLAKE2b in bytes.
+pub const BLAKE2B_BLOCK_BYTES: usize = 128;
+
+/// The length of a BLAKE2b-512 digest in bytes.
+pub const BLAKE2B_512_DIGEST_BYTES: usize = 64;
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// A 64-bit word as big endian bits.
+type Word = [BoolVariable; 64];
+
+fn constant_word<L: PlonkParameters<D>, const D: usize>(
+    value: u64,
+    builder: &mut CircuitBuilder<L, D>,
+) -> Word {
+    array![i => builder.constant::<BoolVariable>((value >> (63 - i)) & 1 == 1); 64]
+}
+
+/// Reads a word from 8 little endian bytes.
+fn word_from_le_bytes(bytes: &[ByteVariable]) -> Word {
+    bytes
+        .iter()
+        .rev()
+        .flat_map(|byte| byte.as_be_bits())
+        .collect_vec()
+        .try_into()
+        .unwrap()
+}
+
+/// Writes a word as 8 little endian bytes.
+fn word_to_le_bytes(word: &Word) -> Vec<ByteVariable> {
+    word.chunks_exact(8)
+        .rev()
+        .map(|bits| ByteVariable::from_be_bits(bits.try_into().unwrap()))
+        .collect()
+}
+
+/// The mixing function G, applied to the words `a`, `b`, `c` and `d` of `v` with the message
+/// words `x` and `y`.
+#[allow(clippy::too_many_arguments)]
+fn mix<L: PlonkParameters<D>, const D: usize>(
+    v: &mut [Word; 16],
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    x: Word,
+    y: Word,
+    builder: &mut CircuitBuilder<L, D>,
+) {
+    v[a] = builder.add_many_arr(&[v[a], v[b], x]);
+    let d_xor_a = xor2_arr(v[d], v[a], builder);
+    v[d] = builder._right_rotate(d_xor_a, 32);
+    v[c] = builder.add_arr(v[c], v[d]);
+    let b_xor_c = xor2_arr(v[b], v[c], builder);
+    v[b] = builder._right_rotate(b_xor_c, 24);
+    v[a] = builder.add_many_arr(&[v[a], v[b], y]);
+    let d_xor_a = xor2_arr(v[d], v[a], builder);
+    v[d] = builder._right_rotate(d_xor_a, 16);
+    v[c] = builder.add_arr(v[c], v[d]);
+    let b_xor_c = xor2_arr(v[b], v[c], builder);
+    v[b] = builder._right_rotate(b_xor_c, 63);
+}
+
+/// An incremental BLAKE2b-512 hasher, which absorbs its input in any number of `update` calls.
+///
+/// The number of bytes and the block structure are fixed when building the circuit, so the
+/// counter and the finalization flag of every compression are constants.
+#[derive(Debug, Clone)]
+pub struct Blake2bHasher {
+    h: [Word; 8],
+    buffer: Vec<ByteVariable>,
+    counter: u128,
+}
+
+impl Blake2bHasher {
+    pub fn new<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>) -> Self {
+        // The parameter block of an unkeyed hash of `BLAKE2B_512_DIGEST_BYTES` bytes.
+        let mut iv = IV;
+        iv[0] ^= 0x01010000 ^ BLAKE2B_512_DIGEST_BYTES as u64;
+        Self {
+            h: iv.map(|word| constant_word(word, builder)),
+            buffer: Vec::new(),
+            counter: 0,
+        }
+    }
+
+    /// Absorbs `input`. Full blocks are only compressed once more input follows, since the last
+    /// block is compressed differently.
+    pub fn update<L: PlonkParameters<D>, const D: usize>(
+        &mut self,
+        input: &[ByteVariable],
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        self.buffer.extend_from_slice(input);
+        while self.buffer.len() > BLAKE2B_BLOCK_BYTES {
+            let block = self.buffer.drain(..BLAKE2B_BLOCK_BYTES).collect_vec();
+            self.counter += BLAKE2B_BLOCK_BYTES as u128;
+            self.compress(&block, false, builder);
+        }
+    }
+
+    /// Compresses the last block and returns the digest.
+    pub fn finalize<L: PlonkParameters<D>, const D: usize>(
+        mut self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BytesVariable<BLAKE2B_512_DIGEST_BYTES> {
+        let mut block = std::mem::take(&mut self.buffer);
+        self.counter += block.len() as u128;
+        let zero = builder.constant::<ByteVariable>(0);
+        block.resize(BLAKE2B_BLOCK_BYTES, zero);
+        self.compress(&block, true, builder);
+
+        let bytes = self.h.iter().flat_map(word_to_le_bytes).collect_vec();
+        BytesVariable(bytes.try_into().unwrap())
+    }
+
+    /// The compression function F.
+    fn compress<L: PlonkParameters<D>, const D: usize>(
+        &mut self,
+        block: &[ByteVariable],
+        is_last: bool,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        let m = block.chunks_exact(8).map(word_from_le_bytes).collect_vec();
+
+        let mut local_iv = IV;
+        local_iv[4] ^= self.counter as u64;
+        local_iv[5] ^= (self.counter >> 64) as u64;
+        if is_last {
+            local_iv[6] = !local_iv[6];
+        }
+        let mut v: [Word; 16] = [self.h, local_iv.map(|word| constant_word(word, builder))]
+            .concat()
+            .try_into()
+            .unwrap();
+
+        for round in 0..12 {
+            let s = SIGMA[round % 10];
+            mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]], builder);
+            mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]], builder);
+            mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]], builder);
+            mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]], builder);
+            mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]], builder);
+            mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]], builder);
+            mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]], builder);
+            mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]], builder);
+        }
+
+        for i in 0..8 {
+            let halves = xor2_arr(v[i], v[i + 8], builder);
+            self.h[i] = xor2_arr(self.h[i], halves, builder);
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Computes the BLAKE2b-512 digest of `input`.
+    pub fn blake2b_512(
+        &mut self,
+        input: &[ByteVariable],
+    ) -> BytesVariable<BLAKE2B_512_DIGEST_BYTES> {
+        let mut hasher = Blake2bHasher::new(self);
+        hasher.update(input, self);
+        hasher.finalize(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto::blake2b::Blake2b;
+    use crypto::digest::Digest;
+
+    use super::*;
+
+    fn expected_digest(message: &[u8]) -> [u8; BLAKE2B_512_DIGEST_BYTES] {
+        let mut hasher = Blake2b::new(BLAKE2B_512_DIGEST_BYTES);
+        hasher.input(message);
+        let mut digest = [0u8; BLAKE2B_512_DIGEST_BYTES];
+        hasher.result(&mut digest);
+        digest
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_blake2b_512() {
+        let message = (0..200).map(|i| i as u8).collect::<Vec<_>>();
+
+        let mut builder = DefaultBuilder::new();
+        let input = message
+            .iter()
+            .map(|b| builder.constant::<ByteVariable>(*b))
+            .collect::<Vec<_>>();
+
+        let empty_digest = builder.blake2b_512(&[]);
+        let expected = builder.constant::<BytesVariable<64>>(expected_digest(&[]));
+        builder.assert_is_equal(empty_digest, expected);
+
+        // Exactly one block, whose compression is the final one.
+        let block_digest = builder.blake2b_512(&input[..BLAKE2B_BLOCK_BYTES]);
+        let expected =
+            builder.constant::<BytesVariable<64>>(expected_digest(&message[..BLAKE2B_BLOCK_BYTES]));
+        builder.assert_is_equal(block_digest, expected);
+
+        // Streaming the message in pieces which do not follow the block boundaries.
+        let mut hasher = Blake2bHasher::new(&mut builder);
+        for piece in input.chunks(57) {
+            hasher.update(piece, &mut builder);
+        }
+        let streamed_digest = hasher.finalize(&mut builder);
+        let expected = builder.constant::<BytesVariable<64>>(expected_digest(&message));
+        builder.assert_is_equal(streamed_digest, expected);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
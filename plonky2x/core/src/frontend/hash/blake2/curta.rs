@@ -188,10 +188,16 @@ impl<L: PlonkParameters<D>, const D: usize> Hash<L, D, 96, true, 4> for BLAKE2B
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     pub fn curta_blake2b(&mut self, input: &[ByteVariable]) -> Bytes32Variable {
         if self.blake2b_accelerator.is_none() {
-            self.blake2b_accelerator = Some(BLAKE2BAccelerator {
-                hash_requests: Vec::new(),
-                hash_responses: Vec::new(),
-            });
+            self.blake2b_accelerator = Some(BLAKE2BAccelerator::new());
+        }
+
+        let request = HashRequest::Fixed(input.to_vec());
+        let cached_digest = self
+            .blake2b_accelerator
+            .as_ref()
+            .and_then(|accelerator| accelerator.cached_digest(&request));
+        if let Some(digest) = cached_digest {
+            return Bytes32Variable::from_variables_unsafe(digest);
         }
 
         let digest = self.init::<Bytes32Variable>();
@@ -201,8 +207,9 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             .as_mut()
             .expect("blake2b accelerator should exist");
         accelerator
-            .hash_requests
-            .push(HashRequest::Fixed(input.to_vec()));
+            .digests
+            .insert(request.key(), digest.variables());
+        accelerator.hash_requests.push(request);
         accelerator.hash_responses.push(digest_array);
 
         digest
@@ -222,10 +229,16 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 
         let last_chunk = self.compute_blake2b_last_chunk_index(length);
         if self.blake2b_accelerator.is_none() {
-            self.blake2b_accelerator = Some(BLAKE2BAccelerator {
-                hash_requests: Vec::new(),
-                hash_responses: Vec::new(),
-            });
+            self.blake2b_accelerator = Some(BLAKE2BAccelerator::new());
+        }
+
+        let request = HashRequest::Variable(input.to_vec(), length, last_chunk);
+        let cached_digest = self
+            .blake2b_accelerator
+            .as_ref()
+            .and_then(|accelerator| accelerator.cached_digest(&request));
+        if let Some(digest) = cached_digest {
+            return Bytes32Variable::from_variables_unsafe(digest);
         }
 
         let digest = self.init::<Bytes32Variable>();
@@ -236,8 +249,9 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             .as_mut()
             .expect("blake2b accelerator should exist");
         accelerator
-            .hash_requests
-            .push(HashRequest::Variable(input.to_vec(), length, last_chunk));
+            .digests
+            .insert(request.key(), digest.variables());
+        accelerator.hash_requests.push(request);
         accelerator.hash_responses.push(digest_array);
 
         digest
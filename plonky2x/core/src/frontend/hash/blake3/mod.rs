@@ -0,0 +1,391 @@
+//! Implementation of the BLAKE3 hash function, with access to its chunk and parent chaining values
+//! so that a single chunk can be verified against the root of a large input.
+//! reference: https://github.com/BLAKE3-team/BLAKE3-specs/blob/master/blake3.pdf
+
+use array_macro::array;
+use itertools::Itertools;
+
+use crate::frontend::hash::common::xor2_arr;
+use crate::prelude::*;
+
+/// The block size of BLAKE3 in bytes.
+pub const BLAKE3_BLOCK_BYTES: usize = 64;
+
+/// The chunk size of BLAKE3 in bytes. Every chunk is a leaf of the hash tree.
+pub const BLAKE3_CHUNK_BYTES: usize = 1024;
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+/// A 32-bit word as big endian bits.
+type Word = [BoolVariable; 32];
+
+/// A chaining value of 8 words.
+type ChainingValue = [Word; 8];
+
+fn constant_word<L: PlonkParameters<D>, const D: usize>(
+    value: u32,
+    builder: &mut CircuitBuilder<L, D>,
+) -> Word {
+    array![i => builder.constant::<BoolVariable>((value >> (31 - i)) & 1 == 1); 32]
+}
+
+/// Reads a word from 4 little endian bytes.
+fn word_from_le_bytes(bytes: &[ByteVariable]) -> Word {
+    bytes
+        .iter()
+        .rev()
+        .flat_map(|byte| byte.as_be_bits())
+        .collect_vec()
+        .try_into()
+        .unwrap()
+}
+
+/// Writes a word as 4 little endian bytes.
+fn word_to_le_bytes(word: &Word) -> Vec<ByteVariable> {
+    word.chunks_exact(8)
+        .rev()
+        .map(|bits| ByteVariable::from_be_bits(bits.try_into().unwrap()))
+        .collect()
+}
+
+fn chaining_value_from_bytes(bytes: Bytes32Variable) -> ChainingValue {
+    bytes
+        .as_bytes()
+        .chunks_exact(4)
+        .map(word_from_le_bytes)
+        .collect_vec()
+        .try_into()
+        .unwrap()
+}
+
+fn chaining_value_to_bytes(chaining_value: &ChainingValue) -> Bytes32Variable {
+    let bytes = chaining_value
+        .iter()
+        .flat_map(word_to_le_bytes)
+        .collect_vec();
+    Bytes32Variable::from(bytes.as_slice())
+}
+
+/// The mixing function G, applied to the words `a`, `b`, `c` and `d` of `v` with the message
+/// words `x` and `y`.
+#[allow(clippy::too_many_arguments)]
+fn mix<L: PlonkParameters<D>, const D: usize>(
+    v: &mut [Word; 16],
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    x: Word,
+    y: Word,
+    builder: &mut CircuitBuilder<L, D>,
+) {
+    v[a] = builder.add_many_arr(&[v[a], v[b], x]);
+    let d_xor_a = xor2_arr(v[d], v[a], builder);
+    v[d] = builder._right_rotate(d_xor_a, 16);
+    v[c] = builder.add_arr(v[c], v[d]);
+    let b_xor_c = xor2_arr(v[b], v[c], builder);
+    v[b] = builder._right_rotate(b_xor_c, 12);
+    v[a] = builder.add_many_arr(&[v[a], v[b], y]);
+    let d_xor_a = xor2_arr(v[d], v[a], builder);
+    v[d] = builder._right_rotate(d_xor_a, 8);
+    v[c] = builder.add_arr(v[c], v[d]);
+    let b_xor_c = xor2_arr(v[b], v[c], builder);
+    v[b] = builder._right_rotate(b_xor_c, 7);
+}
+
+/// The compression function, truncated to the 8 words of the chaining value.
+fn compress<L: PlonkParameters<D>, const D: usize>(
+    chaining_value: &ChainingValue,
+    block: &[Word; 16],
+    counter: [Word; 2],
+    block_len: u32,
+    flags: u32,
+    builder: &mut CircuitBuilder<L, D>,
+) -> ChainingValue {
+    let iv = array![i => constant_word(IV[i], builder); 4];
+    let block_len = constant_word(block_len, builder);
+    let flags = constant_word(flags, builder);
+    let mut v = chaining_value.to_vec();
+    v.extend(iv);
+    v.extend(counter);
+    v.extend([block_len, flags]);
+    let mut v: [Word; 16] = v.try_into().unwrap();
+
+    let mut m = *block;
+    for round in 0..7 {
+        mix(&mut v, 0, 4, 8, 12, m[0], m[1], builder);
+        mix(&mut v, 1, 5, 9, 13, m[2], m[3], builder);
+        mix(&mut v, 2, 6, 10, 14, m[4], m[5], builder);
+        mix(&mut v, 3, 7, 11, 15, m[6], m[7], builder);
+        mix(&mut v, 0, 5, 10, 15, m[8], m[9], builder);
+        mix(&mut v, 1, 6, 11, 12, m[10], m[11], builder);
+        mix(&mut v, 2, 7, 8, 13, m[12], m[13], builder);
+        mix(&mut v, 3, 4, 9, 14, m[14], m[15], builder);
+        if round < 6 {
+            m = array![i => m[MSG_PERMUTATION[i]]; 16];
+        }
+    }
+
+    array![i => xor2_arr(v[i], v[i + 8], builder); 8]
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the chaining value of a chunk of at most `BLAKE3_CHUNK_BYTES` bytes, whose index is
+    /// given by the two words of `counter`.
+    fn blake3_chunk(
+        &mut self,
+        chunk: &[ByteVariable],
+        counter: [Word; 2],
+        is_root: bool,
+    ) -> ChainingValue {
+        assert!(
+            chunk.len() <= BLAKE3_CHUNK_BYTES,
+            "a chunk has at most {} bytes",
+            BLAKE3_CHUNK_BYTES
+        );
+        let zero = self.constant::<ByteVariable>(0);
+        let blocks = if chunk.is_empty() {
+            vec![vec![]]
+        } else {
+            chunk
+                .chunks(BLAKE3_BLOCK_BYTES)
+                .map(|block| block.to_vec())
+                .collect_vec()
+        };
+
+        let mut chaining_value = IV.map(|word| constant_word(word, self));
+        for (i, mut block) in blocks.iter().cloned().enumerate() {
+            let block_len = block.len() as u32;
+            block.resize(BLAKE3_BLOCK_BYTES, zero);
+            let words = block
+                .chunks_exact(4)
+                .map(word_from_le_bytes)
+                .collect_vec()
+                .try_into()
+                .unwrap();
+
+            let mut flags = 0;
+            if i == 0 {
+                flags |= CHUNK_START;
+            }
+            if i == blocks.len() - 1 {
+                flags |= CHUNK_END;
+                if is_root {
+                    flags |= ROOT;
+                }
+            }
+            chaining_value = compress(&chaining_value, &words, counter, block_len, flags, self);
+        }
+        chaining_value
+    }
+
+    fn blake3_parent(
+        &mut self,
+        left: &ChainingValue,
+        right: &ChainingValue,
+        is_root: bool,
+    ) -> ChainingValue {
+        let block = [*left, *right].concat().try_into().unwrap();
+        let key = IV.map(|word| constant_word(word, self));
+        let counter = [constant_word(0, self), constant_word(0, self)];
+        let flags = if is_root { PARENT | ROOT } else { PARENT };
+        compress(
+            &key,
+            &block,
+            counter,
+            BLAKE3_BLOCK_BYTES as u32,
+            flags,
+            self,
+        )
+    }
+
+    /// Returns the chaining value of the subtree over `input`, whose first chunk has the index
+    /// `first_chunk`. The left subtree holds the largest power of two of chunks that leaves at
+    /// least one chunk to the right subtree.
+    fn blake3_subtree(
+        &mut self,
+        input: &[ByteVariable],
+        first_chunk: u64,
+        is_root: bool,
+    ) -> ChainingValue {
+        if input.len() <= BLAKE3_CHUNK_BYTES {
+            let counter = [
+                constant_word(first_chunk as u32, self),
+                constant_word((first_chunk >> 32) as u32, self),
+            ];
+            return self.blake3_chunk(input, counter, is_root);
+        }
+        let nb_chunks = (input.len() + BLAKE3_CHUNK_BYTES - 1) / BLAKE3_CHUNK_BYTES;
+        let nb_left_chunks = (nb_chunks / 2 + nb_chunks % 2).next_power_of_two();
+        let (left, right) = input.split_at(nb_left_chunks * BLAKE3_CHUNK_BYTES);
+        let left = self.blake3_subtree(left, first_chunk, false);
+        let right = self.blake3_subtree(right, first_chunk + nb_left_chunks as u64, false);
+        self.blake3_parent(&left, &right, is_root)
+    }
+
+    /// Computes the BLAKE3 digest of `input`.
+    pub fn blake3(&mut self, input: &[ByteVariable]) -> Bytes32Variable {
+        let root = self.blake3_subtree(input, 0, true);
+        chaining_value_to_bytes(&root)
+    }
+
+    /// Returns the chaining value of the chunk at index `chunk_index` of an input longer than a
+    /// chunk, i.e. the leaf of the hash tree.
+    pub fn blake3_chunk_chaining_value(
+        &mut self,
+        chunk: &[ByteVariable],
+        chunk_index: U32Variable,
+    ) -> Bytes32Variable {
+        let counter = [
+            self.to_be_bits(chunk_index).try_into().unwrap(),
+            constant_word(0, self),
+        ];
+        let chaining_value = self.blake3_chunk(chunk, counter, false);
+        chaining_value_to_bytes(&chaining_value)
+    }
+
+    /// Returns the chaining value of the inner node of the hash tree with the given children, for
+    /// a node which is not the root.
+    pub fn blake3_parent_chaining_value(
+        &mut self,
+        left: Bytes32Variable,
+        right: Bytes32Variable,
+    ) -> Bytes32Variable {
+        let left = chaining_value_from_bytes(left);
+        let right = chaining_value_from_bytes(right);
+        let chaining_value = self.blake3_parent(&left, &right, false);
+        chaining_value_to_bytes(&chaining_value)
+    }
+
+    /// Fails unless `chunk` is the chunk at index `chunk_index` of an input with digest `root`,
+    /// given the chaining values of the siblings on its path from the leaf to the root.
+    ///
+    /// Only inputs of `2^siblings.len()` full chunks are supported, whose hash tree is a perfect
+    /// binary tree, so that the path is determined by the bits of `chunk_index`. This costs one
+    /// chunk and `siblings.len()` parent compressions instead of hashing the whole input.
+    pub fn blake3_verify_chunk(
+        &mut self,
+        chunk: &[ByteVariable],
+        chunk_index: U32Variable,
+        siblings: &[Bytes32Variable],
+        root: Bytes32Variable,
+    ) {
+        let depth = siblings.len();
+        if depth == 0 {
+            let digest = self.blake3(chunk);
+            self.assert_is_equal(digest, root);
+            return;
+        }
+        assert_eq!(
+            chunk.len(),
+            BLAKE3_CHUNK_BYTES,
+            "only full chunks can be verified"
+        );
+
+        // Decomposing the index also checks that `chunk_index < 2^depth`.
+        let index_bits = self.api.split_le(chunk_index.variable.0, depth);
+        let mut node = self.blake3_chunk_chaining_value(chunk, chunk_index);
+        for (level, (sibling, is_right)) in siblings.iter().zip(index_bits).enumerate() {
+            let is_right = BoolVariable::from(is_right);
+            let left = self.select(is_right, *sibling, node);
+            let right = self.select(is_right, node, *sibling);
+            let left = chaining_value_from_bytes(left);
+            let right = chaining_value_from_bytes(right);
+            let chaining_value = self.blake3_parent(&left, &right, level == depth - 1);
+            node = chaining_value_to_bytes(&chaining_value);
+        }
+        self.assert_is_equal(node, root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::utils::bytes32;
+
+    /// The input of the official test vectors, `i % 251` for every byte index `i`.
+    fn test_input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_blake3() {
+        let test_vectors = [
+            (
+                0,
+                bytes32!("0xaf1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"),
+            ),
+            (
+                1024,
+                bytes32!("0x42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7"),
+            ),
+            (
+                1025,
+                bytes32!("0xd00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444"),
+            ),
+        ];
+
+        let mut builder = DefaultBuilder::new();
+        for (len, expected) in test_vectors {
+            let input = test_input(len)
+                .into_iter()
+                .map(|b| builder.constant::<ByteVariable>(b))
+                .collect::<Vec<_>>();
+            let digest = builder.blake3(&input);
+            let expected = builder.constant::<Bytes32Variable>(expected);
+            builder.assert_is_equal(digest, expected);
+        }
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_blake3_verify_chunk() {
+        // An input of four chunks, whose chunk 2 is verified against its digest.
+        let root = bytes32!("0x015094013f57a5277b59d8475c0501042c0b642e531b0a1c8f58d2163229e969");
+        let data = test_input(4 * 1024);
+
+        let mut builder = DefaultBuilder::new();
+        let chunks = data
+            .chunks(1024)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|b| builder.constant::<ByteVariable>(*b))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let chunk_index = builder.read::<U32Variable>();
+
+        let index_0 = builder.constant::<U32Variable>(0);
+        let index_1 = builder.constant::<U32Variable>(1);
+        let index_3 = builder.constant::<U32Variable>(3);
+        let chunk_0 = builder.blake3_chunk_chaining_value(&chunks[0], index_0);
+        let chunk_1 = builder.blake3_chunk_chaining_value(&chunks[1], index_1);
+        let chunk_3 = builder.blake3_chunk_chaining_value(&chunks[3], index_3);
+        let left = builder.blake3_parent_chaining_value(chunk_0, chunk_1);
+
+        let root = builder.constant::<Bytes32Variable>(root);
+        builder.blake3_verify_chunk(&chunks[2], chunk_index, &[chunk_3, left], root);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<U32Variable>(2);
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
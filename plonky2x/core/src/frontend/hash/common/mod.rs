@@ -1,5 +1,7 @@
 use crate::frontend::builder::CircuitBuilder;
-use crate::prelude::{BoolVariable, CircuitVariable, PlonkParameters, U32Variable, Variable};
+use crate::prelude::{
+    BoolVariable, CircuitVariable, PlonkParameters, U32Variable, U64Variable, Variable,
+};
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     pub fn reshape(&self, arr: Vec<BoolVariable>) -> Vec<[BoolVariable; 32]> {
@@ -67,20 +69,60 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         b: [BoolVariable; S],
     ) -> [BoolVariable; S] {
         if S == 32 {
-            let a_u32 = U32Variable::from_be_bits(&a, self);
-            let b_u32 = U32Variable::from_be_bits(&b, self);
+            let a_u32: U32Variable = self.from_be_bits(&a);
+            let b_u32: U32Variable = self.from_be_bits(&b);
 
             let a_u64 = a_u32.to_u64(self);
             let b_u64 = b_u32.to_u64(self);
             let c_u64 = self.add(a_u64, b_u64);
             let c_u32 = c_u64.limbs[0];
 
-            c_u32.to_be_bits(self).to_vec().try_into().unwrap()
+            self.to_be_bits(c_u32).try_into().unwrap()
+        } else if S == 64 {
+            let a_u64: U64Variable = self.from_be_bits(&a);
+            let b_u64: U64Variable = self.from_be_bits(&b);
+            let c_u64 = self.add(a_u64, b_u64);
+
+            self.to_be_bits(c_u64).try_into().unwrap()
         } else {
             todo!();
         }
     }
 
+    /// Adds many words in one `U32AddManyGate` row, which is cheaper than chaining `add_arr`.
+    ///
+    /// Panics unless the words are 32 or 64 bits wide.
+    pub fn add_many_arr<const S: usize>(
+        &mut self,
+        arrs: &[[BoolVariable; S]],
+    ) -> [BoolVariable; S] {
+        if S == 32 {
+            let values = arrs
+                .iter()
+                .map(|arr| self.from_be_bits::<U32Variable>(arr))
+                .collect::<Vec<_>>();
+            let sum = self.add_many_u32(&values);
+
+            self.to_be_bits(sum).try_into().unwrap()
+        } else if S == 64 {
+            let values = arrs
+                .iter()
+                .map(|arr| self.from_be_bits::<U64Variable>(arr))
+                .collect::<Vec<_>>();
+            let mut sum = values[0];
+            for value in values[1..].iter() {
+                sum = self.add(sum, *value);
+            }
+
+            self.to_be_bits(sum).try_into().unwrap()
+        } else {
+            panic!(
+                "add_many_arr only supports words of 32 or 64 bits, got {} bits",
+                S
+            );
+        }
+    }
+
     pub fn zip_add<const S: usize>(
         &mut self,
         a: [[BoolVariable; S]; 8],
@@ -1,7 +1,34 @@
-use crate::frontend::hash::curta::request::HashRequest;
+use std::collections::HashMap;
+
+use crate::frontend::hash::curta::request::{HashRequest, HashRequestType};
+use crate::prelude::Variable;
 
 #[derive(Debug, Clone)]
 pub struct HashAccelerator<T, const S: usize> {
     pub hash_requests: Vec<HashRequest>,
     pub hash_responses: Vec<[T; S]>,
+    /// The digests of the requests so far, by request key. Identical requests from different call
+    /// sites reuse the digest of the first one, so every message is hashed once in the table.
+    pub digests: HashMap<(HashRequestType, Vec<Variable>), Vec<Variable>>,
+}
+
+impl<T, const S: usize> HashAccelerator<T, S> {
+    pub fn new() -> Self {
+        Self {
+            hash_requests: Vec::new(),
+            hash_responses: Vec::new(),
+            digests: HashMap::new(),
+        }
+    }
+
+    /// Returns the digest of an earlier request with the same key, if any.
+    pub fn cached_digest(&self, request: &HashRequest) -> Option<&[Variable]> {
+        self.digests.get(&request.key()).map(Vec::as_slice)
+    }
+}
+
+impl<T, const S: usize> Default for HashAccelerator<T, S> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
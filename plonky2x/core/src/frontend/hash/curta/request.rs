@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::prelude::*;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum HashRequestType {
     Fixed,
     Variable,
@@ -25,4 +25,16 @@ impl HashRequest {
             HashRequest::Variable(_, _, _) => HashRequestType::Variable,
         }
     }
+
+    /// The type of the request and the variables that determine its digest. Requests with equal
+    /// keys have equal digests.
+    pub fn key(&self) -> (HashRequestType, Vec<Variable>) {
+        let (input, length) = match self {
+            HashRequest::Fixed(input) => (input, None),
+            HashRequest::Variable(input, length, _) => (input, Some(length)),
+        };
+        let mut variables = input.iter().flat_map(|b| b.variables()).collect::<Vec<_>>();
+        variables.extend(length.iter().flat_map(|length| length.variables()));
+        (self.req_type(), variables)
+    }
 }
@@ -2,12 +2,17 @@
 
 use core::marker::PhantomData;
 
+use ethers::types::H256;
+
 use self::keccak256::Keccak256Generator;
+use self::pad::KECCAK256_RATE_BYTES;
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::vars::Bytes32Variable;
-use crate::prelude::{ByteVariable, CircuitBuilder, Variable};
+use crate::prelude::{ByteVariable, CircuitBuilder, U32Variable, Variable};
 
 pub mod keccak256;
+pub mod pad;
+mod permutation;
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     /// WARNING: DO NOT USE IN PRODUCTION, this is unconstrained!
@@ -40,11 +45,48 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         self.add_simple_generator(generator.clone());
         generator.output
     }
+
+    /// Computes the keccak256 digest of `input` with the Keccak-f[1600] permutation in the
+    /// circuit.
+    pub fn keccak256(&mut self, input: &[ByteVariable]) -> Bytes32Variable {
+        let padded = self.pad_message_keccak256(input);
+        let mut state = self.keccak_empty_state();
+        for block in padded.chunks_exact(KECCAK256_RATE_BYTES) {
+            state = self.keccak_absorb(state, block);
+        }
+        self.keccak_squeeze(&state)
+    }
+
+    /// Computes the keccak256 digest of the first `input_byte_length` bytes of `input`, which must
+    /// be at most `input.len()`.
+    ///
+    /// Every block that may hold the message is absorbed, and the digest is selected from the
+    /// state after the block holding the end of the padding, so the cost is that of hashing
+    /// `input.len()` bytes.
+    pub fn keccak256_variable(
+        &mut self,
+        input: &[ByteVariable],
+        input_byte_length: U32Variable,
+    ) -> Bytes32Variable {
+        let (padded, last_block) = self.pad_keccak256_variable_length(input, input_byte_length);
+        let mut state = self.keccak_empty_state();
+        let mut digest = self.constant::<Bytes32Variable>(H256::zero());
+        for (i, block) in padded.chunks_exact(KECCAK256_RATE_BYTES).enumerate() {
+            state = self.keccak_absorb(state, block);
+            let block_digest = self.keccak_squeeze(&state);
+            let block_index = self.constant::<U32Variable>(i as u32);
+            let is_last_block = self.is_equal(block_index, last_block);
+            digest = self.select(is_last_block, block_digest, digest);
+        }
+        digest
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
+    use ethers::utils::keccak256;
+
     use super::*;
     use crate::backend::circuit::DefaultParameters;
     use crate::utils::bytes32;
@@ -67,4 +109,35 @@ mod tests {
         let input = circuit.input();
         let (_, _) = circuit.prove(&input);
     }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_keccak256_constrained() {
+        env_logger::try_init().unwrap_or_default();
+
+        let message = (0..200).map(|i| i as u8).collect::<Vec<_>>();
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let input = message
+            .iter()
+            .map(|b| builder.constant::<ByteVariable>(*b))
+            .collect::<Vec<_>>();
+
+        let hash = builder.keccak256(&input[..32]);
+        let expected = builder.constant::<Bytes32Variable>(H256(keccak256(&message[..32])));
+        builder.assert_is_equal(hash, expected);
+
+        // Lengths on both sides of the end of the first block.
+        for length in [135, 136] {
+            let length_v = builder.constant::<U32Variable>(length as u32);
+            let hash = builder.keccak256_variable(&input, length_v);
+            let expected = builder.constant::<Bytes32Variable>(H256(keccak256(&message[..length])));
+            builder.assert_is_equal(hash, expected);
+        }
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
 }
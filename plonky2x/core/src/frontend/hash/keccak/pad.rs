@@ -0,0 +1,81 @@
+use crate::prelude::*;
+
+/// The number of bytes absorbed by each Keccak-f[1600] permutation of keccak256.
+pub const KECCAK256_RATE_BYTES: usize = 136;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Pad the given input according to the keccak256 spec: a 0x01 byte, zero bytes, and a final
+    /// 0x80 byte up to a multiple of the rate, where both marker bytes may coincide as 0x81.
+    pub(crate) fn pad_message_keccak256(&mut self, input: &[ByteVariable]) -> Vec<ByteVariable> {
+        let padded_len = (input.len() / KECCAK256_RATE_BYTES + 1) * KECCAK256_RATE_BYTES;
+        let mut padding = vec![0u8; padded_len - input.len()];
+        padding[0] = 0x01;
+        *padding.last_mut().unwrap() |= 0x80;
+
+        let mut padded = input.to_vec();
+        padded.extend(
+            padding
+                .into_iter()
+                .map(|b| self.constant::<ByteVariable>(b)),
+        );
+        padded
+    }
+
+    /// Pad the given variable length input according to the keccak256 spec, returning the padded
+    /// bytes and the index of the last block to absorb. The bytes after the last block are zero.
+    /// input_byte_length gives the real length of the input in bytes, which must be at most
+    /// `input.len()`.
+    pub(crate) fn pad_keccak256_variable_length(
+        &mut self,
+        input: &[ByteVariable],
+        input_byte_length: U32Variable,
+    ) -> (Vec<ByteVariable>, U32Variable) {
+        let true_t = self._true();
+        let max_len = self.constant::<U32Variable>(input.len() as u32);
+        let is_within_input = self.lte(input_byte_length, max_len);
+        self.assert_is_equal(is_within_input, true_t);
+
+        // The padding adds at least one byte, so the message ends in the block of its last byte
+        // of padding.
+        let rate = self.constant::<U32Variable>(KECCAK256_RATE_BYTES as u32);
+        let last_block = self.div(input_byte_length, rate);
+        let max_num_blocks = input.len() / KECCAK256_RATE_BYTES + 1;
+
+        let zero_byte = self.constant::<ByteVariable>(0x00);
+        let padding_start_byte = self.constant::<ByteVariable>(0x01);
+        let padding_end_byte = self.constant::<ByteVariable>(0x80);
+
+        let mut padded_bytes = Vec::new();
+        let mut message_byte_selector = true_t;
+        for i in 0..max_num_blocks {
+            let curr_block = self.constant::<U32Variable>(i as u32);
+            let is_last_block = self.is_equal(curr_block, last_block);
+
+            for j in 0..KECCAK256_RATE_BYTES {
+                let idx = i * KECCAK256_RATE_BYTES + j;
+                let idx_t = self.constant::<U32Variable>(idx as u32);
+                let is_first_padding_byte = self.is_equal(idx_t, input_byte_length);
+                let not_first_padding_byte = self.not(is_first_padding_byte);
+                message_byte_selector = self.and(message_byte_selector, not_first_padding_byte);
+
+                // The message byte, then 0x01, then zero bytes.
+                let mut byte = match input.get(idx) {
+                    Some(input_byte) => self.select(message_byte_selector, *input_byte, zero_byte),
+                    None => zero_byte,
+                };
+                byte = self.select(is_first_padding_byte, padding_start_byte, byte);
+
+                if j == KECCAK256_RATE_BYTES - 1 {
+                    // The message ends before the last byte of its last block, so `byte` is either
+                    // 0x00 or 0x01 here.
+                    let end_byte = self.or(byte, padding_end_byte);
+                    byte = self.select(is_last_block, end_byte, byte);
+                }
+
+                padded_bytes.push(byte);
+            }
+        }
+
+        (padded_bytes, last_block)
+    }
+}
@@ -0,0 +1,137 @@
+//! The Keccak-f[1600] permutation over a state of 25 lanes of 64 little-endian bits.
+
+use array_macro::array;
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::hash::keccak::pad::KECCAK256_RATE_BYTES;
+use crate::frontend::vars::{BoolVariable, ByteVariable, Bytes32Variable};
+
+pub type KeccakLane = [BoolVariable; 64];
+pub type KeccakState = [KeccakLane; 25];
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The rotation offset of the lane `x + 5 * y`.
+const ROTATION_OFFSETS: [usize; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+fn rotate_left(lane: KeccakLane, bits: usize) -> KeccakLane {
+    array![i => lane[(i + 64 - bits) % 64]; 64]
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    fn xor_lanes(&mut self, a: KeccakLane, b: KeccakLane) -> KeccakLane {
+        array![i => self.xor(a[i], b[i]); 64]
+    }
+
+    fn xor3_lanes(&mut self, a: KeccakLane, b: KeccakLane, c: KeccakLane) -> KeccakLane {
+        array![i => self.xor3(a[i].variable, b[i].variable, c[i].variable); 64]
+    }
+
+    /// Returns the state of a sponge that has not absorbed anything.
+    pub(crate) fn keccak_empty_state(&mut self) -> KeccakState {
+        let false_v = self._false();
+        [[false_v; 64]; 25]
+    }
+
+    /// Absorbs a block of `KECCAK256_RATE_BYTES` bytes into `state` and applies the permutation.
+    pub(crate) fn keccak_absorb(
+        &mut self,
+        mut state: KeccakState,
+        block: &[ByteVariable],
+    ) -> KeccakState {
+        assert_eq!(block.len(), KECCAK256_RATE_BYTES);
+        for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+            let bits = array![i => bytes[i / 8].as_le_bits()[i % 8]; 64];
+            *lane = self.xor_lanes(*lane, bits);
+        }
+        self.keccak_f1600(state)
+    }
+
+    /// Returns the keccak256 digest of the sponge, i.e. its first 32 bytes.
+    pub(crate) fn keccak_squeeze(&self, state: &KeccakState) -> Bytes32Variable {
+        let bytes = array![i => {
+            let mut bits = array![j => state[i / 8][8 * (i % 8) + j]; 8];
+            bits.reverse();
+            ByteVariable::from_be_bits(bits)
+        }; 32];
+        Bytes32Variable::from(bytes)
+    }
+
+    /// Applies the 24 rounds of Keccak-f[1600] to `state`.
+    pub(crate) fn keccak_f1600(&mut self, mut state: KeccakState) -> KeccakState {
+        for round_constant in ROUND_CONSTANTS {
+            // θ: xor every lane with the parities of two neighbouring columns.
+            let parities = array![x => {
+                let mut parity = self.xor3_lanes(state[x], state[x + 5], state[x + 10]);
+                parity = self.xor3_lanes(parity, state[x + 15], state[x + 20]);
+                parity
+            }; 5];
+            for x in 0..5 {
+                let d =
+                    self.xor_lanes(parities[(x + 4) % 5], rotate_left(parities[(x + 1) % 5], 1));
+                for y in 0..5 {
+                    state[x + 5 * y] = self.xor_lanes(state[x + 5 * y], d);
+                }
+            }
+
+            // ρ and π: rotate every lane and move the lane (x, y) to (y, 2x + 3y).
+            let mut permuted = state;
+            for x in 0..5 {
+                for y in 0..5 {
+                    permuted[y + 5 * ((2 * x + 3 * y) % 5)] =
+                        rotate_left(state[x + 5 * y], ROTATION_OFFSETS[x + 5 * y]);
+                }
+            }
+
+            // χ: xor every lane with `!next & next_next` along its row.
+            for x in 0..5 {
+                for y in 0..5 {
+                    let lane = permuted[x + 5 * y];
+                    let next = permuted[(x + 1) % 5 + 5 * y];
+                    let next_next = permuted[(x + 2) % 5 + 5 * y];
+                    state[x + 5 * y] = array![i => {
+                        let not_next = self.not(next[i]);
+                        let mask = self.and(not_next, next_next[i]);
+                        self.xor(lane[i], mask)
+                    }; 64];
+                }
+            }
+
+            // ι: xor the first lane with the round constant, which negates the bits set in it.
+            for (i, bit) in state[0].iter_mut().enumerate() {
+                if (round_constant >> i) & 1 == 1 {
+                    *bit = self.not(*bit);
+                }
+            }
+        }
+        state
+    }
+}
@@ -1,6 +1,8 @@
 pub mod blake2;
+pub mod blake3;
 pub mod common;
 pub mod curta;
 pub mod keccak;
 pub mod poseidon;
+pub mod ripemd160;
 pub mod sha;
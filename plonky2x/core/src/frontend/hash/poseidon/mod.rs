@@ -1,3 +1,4 @@
 //! An implementation of the Poseidon hash functions in a plonky2 circuit
 
+pub mod poseidon2;
 pub mod poseidon256;
@@ -0,0 +1,454 @@
+use alloc::{format, vec};
+use core::marker::PhantomData;
+
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::gates::gate::Gate;
+use plonky2::gates::util::StridedConstraintConsumer;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::plonk::vars::{EvaluationTargets, EvaluationVars, EvaluationVarsBase};
+use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
+
+use super::constants::INTERNAL_MATRIX_DIAG_M_1;
+use super::{
+    external_linear_layer, full_round_constants, internal_linear_layer, partial_round_constant,
+    sbox, HALF_N_FULL_ROUNDS, N_PARTIAL_ROUNDS, SPONGE_WIDTH,
+};
+
+/// A gate which computes the Poseidon2 permutation of its inputs, after swapping the first and
+/// the second four inputs if `swap` is set.
+///
+/// The wires have the same layout as the `PoseidonGate` of plonky2: the inputs of the S-boxes of
+/// every round but the first are stored, which bounds the degree of the constraints to 7.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Poseidon2Gate<F: RichField + Extendable<D>, const D: usize>(PhantomData<F>);
+
+impl<F: RichField + Extendable<D>, const D: usize> Poseidon2Gate<F, D> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+
+    /// The wire of the `i`-th input.
+    pub fn wire_input(i: usize) -> usize {
+        debug_assert!(i < SPONGE_WIDTH);
+        i
+    }
+
+    /// The wire of the `i`-th output.
+    pub fn wire_output(i: usize) -> usize {
+        debug_assert!(i < SPONGE_WIDTH);
+        SPONGE_WIDTH + i
+    }
+
+    /// If this is set to 1, the first four inputs will be swapped with the next four inputs.
+    pub const WIRE_SWAP: usize = 2 * SPONGE_WIDTH;
+
+    const START_DELTA: usize = 2 * SPONGE_WIDTH + 1;
+
+    /// A wire which stores `swap * (input[i + 4] - input[i])`, used to compute the swapped inputs.
+    fn wire_delta(i: usize) -> usize {
+        debug_assert!(i < 4);
+        Self::START_DELTA + i
+    }
+
+    const START_FULL_0: usize = Self::START_DELTA + 4;
+
+    /// The S-box input of the `i`-th element in the given full round before the partial rounds.
+    /// The inputs of the first round are linear in the inputs of the gate, so they are not stored.
+    fn wire_full_sbox_0(round: usize, i: usize) -> usize {
+        debug_assert!(
+            round != 0,
+            "first round S-box inputs are not stored as wires"
+        );
+        debug_assert!(round < HALF_N_FULL_ROUNDS);
+        debug_assert!(i < SPONGE_WIDTH);
+        Self::START_FULL_0 + SPONGE_WIDTH * (round - 1) + i
+    }
+
+    const START_PARTIAL: usize = Self::START_FULL_0 + SPONGE_WIDTH * (HALF_N_FULL_ROUNDS - 1);
+
+    /// The S-box input of the given partial round.
+    fn wire_partial_sbox(round: usize) -> usize {
+        debug_assert!(round < N_PARTIAL_ROUNDS);
+        Self::START_PARTIAL + round
+    }
+
+    const START_FULL_1: usize = Self::START_PARTIAL + N_PARTIAL_ROUNDS;
+
+    /// The S-box input of the `i`-th element in the given full round after the partial rounds.
+    fn wire_full_sbox_1(round: usize, i: usize) -> usize {
+        debug_assert!(round < HALF_N_FULL_ROUNDS);
+        debug_assert!(i < SPONGE_WIDTH);
+        Self::START_FULL_1 + SPONGE_WIDTH * round + i
+    }
+
+    fn end() -> usize {
+        Self::START_FULL_1 + SPONGE_WIDTH * HALF_N_FULL_ROUNDS
+    }
+
+    /// The constraints of the gate, evaluated natively in the base field or in its extension.
+    fn eval_generic<FE: Field>(wire: impl Fn(usize) -> FE) -> Vec<FE> {
+        let mut constraints = Vec::new();
+
+        // Assert that `swap` is binary and that the deltas are `swap * (rhs - lhs)`.
+        let swap = wire(Self::WIRE_SWAP);
+        constraints.push(swap * (swap - FE::ONE));
+        for i in 0..4 {
+            let lhs = wire(Self::wire_input(i));
+            let rhs = wire(Self::wire_input(i + 4));
+            constraints.push(swap * (rhs - lhs) - wire(Self::wire_delta(i)));
+        }
+
+        let mut state: [FE; SPONGE_WIDTH] = core::array::from_fn(|i| wire(Self::wire_input(i)));
+        for i in 0..4 {
+            let delta = wire(Self::wire_delta(i));
+            state[i] += delta;
+            state[i + 4] -= delta;
+        }
+
+        external_linear_layer(&mut state);
+
+        for round in 0..HALF_N_FULL_ROUNDS {
+            for (i, c) in full_round_constants::<FE>(round).into_iter().enumerate() {
+                state[i] += c;
+                if round != 0 {
+                    let sbox_in = wire(Self::wire_full_sbox_0(round, i));
+                    constraints.push(state[i] - sbox_in);
+                    state[i] = sbox_in;
+                }
+                state[i] = sbox(state[i]);
+            }
+            external_linear_layer(&mut state);
+        }
+
+        for round in 0..N_PARTIAL_ROUNDS {
+            let sbox_in = wire(Self::wire_partial_sbox(round));
+            constraints.push(state[0] + partial_round_constant(round) - sbox_in);
+            state[0] = sbox(sbox_in);
+            internal_linear_layer(&mut state);
+        }
+
+        for round in 0..HALF_N_FULL_ROUNDS {
+            let constants = full_round_constants::<FE>(HALF_N_FULL_ROUNDS + round);
+            for (i, c) in constants.into_iter().enumerate() {
+                let sbox_in = wire(Self::wire_full_sbox_1(round, i));
+                constraints.push(state[i] + c - sbox_in);
+                state[i] = sbox(sbox_in);
+            }
+            external_linear_layer(&mut state);
+        }
+
+        for (i, x) in state.into_iter().enumerate() {
+            constraints.push(x - wire(Self::wire_output(i)));
+        }
+
+        constraints
+    }
+}
+
+/// The external linear layer of `super::external_linear_layer` in the recursive circuit.
+fn external_linear_layer_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    state: &mut [ExtensionTarget<D>; SPONGE_WIDTH],
+) {
+    for x in state.chunks_exact_mut(4) {
+        let t0 = builder.add_extension(x[0], x[1]);
+        let t1 = builder.add_extension(x[2], x[3]);
+        let t2 = builder.add_extension(x[1], x[1]);
+        let t2 = builder.add_extension(t2, t1);
+        let t3 = builder.add_extension(x[3], x[3]);
+        let t3 = builder.add_extension(t3, t0);
+        let t4 = builder.add_extension(t1, t1);
+        let t4 = builder.add_extension(t4, t4);
+        let t4 = builder.add_extension(t4, t3);
+        let t5 = builder.add_extension(t0, t0);
+        let t5 = builder.add_extension(t5, t5);
+        let t5 = builder.add_extension(t5, t2);
+        x[0] = builder.add_extension(t3, t5);
+        x[1] = t5;
+        x[2] = builder.add_extension(t2, t4);
+        x[3] = t4;
+    }
+
+    let mut sums = [state[0], state[1], state[2], state[3]];
+    for x in state.chunks_exact(4).skip(1) {
+        for i in 0..4 {
+            sums[i] = builder.add_extension(sums[i], x[i]);
+        }
+    }
+    for (i, x) in state.iter_mut().enumerate() {
+        *x = builder.add_extension(*x, sums[i % 4]);
+    }
+}
+
+/// The internal linear layer of `super::internal_linear_layer` in the recursive circuit.
+fn internal_linear_layer_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    state: &mut [ExtensionTarget<D>; SPONGE_WIDTH],
+) {
+    let zero = builder.zero_extension();
+    let sum = state
+        .iter()
+        .fold(zero, |acc, x| builder.add_extension(acc, *x));
+    let one = builder.one_extension();
+    for (x, diag) in state.iter_mut().zip(INTERNAL_MATRIX_DIAG_M_1) {
+        *x = builder.arithmetic_extension(F::from_canonical_u64(diag), F::ONE, *x, one, sum);
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for Poseidon2Gate<F, D> {
+    fn id(&self) -> String {
+        format!("{self:?}<WIDTH={SPONGE_WIDTH}>")
+    }
+
+    fn serialize(
+        &self,
+        _dst: &mut Vec<u8>,
+        _common_data: &CommonCircuitData<F, D>,
+    ) -> IoResult<()> {
+        Ok(())
+    }
+
+    fn deserialize(_src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        Ok(Poseidon2Gate::new())
+    }
+
+    fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        Self::eval_generic(|i| vars.local_wires[i])
+    }
+
+    fn eval_unfiltered_base_one(
+        &self,
+        vars: EvaluationVarsBase<F>,
+        mut yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        yield_constr.many(Self::eval_generic(|i| vars.local_wires[i]));
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        let wire = |i: usize| vars.local_wires[i];
+        let mut constraints = Vec::with_capacity(self.num_constraints());
+
+        let swap = wire(Self::WIRE_SWAP);
+        constraints.push(builder.mul_sub_extension(swap, swap, swap));
+        for i in 0..4 {
+            let lhs = wire(Self::wire_input(i));
+            let rhs = wire(Self::wire_input(i + 4));
+            let diff = builder.sub_extension(rhs, lhs);
+            constraints.push(builder.mul_sub_extension(swap, diff, wire(Self::wire_delta(i))));
+        }
+
+        let mut state: [ExtensionTarget<D>; SPONGE_WIDTH] =
+            core::array::from_fn(|i| wire(Self::wire_input(i)));
+        for i in 0..4 {
+            let delta = wire(Self::wire_delta(i));
+            state[i] = builder.add_extension(state[i], delta);
+            state[i + 4] = builder.sub_extension(state[i + 4], delta);
+        }
+
+        external_linear_layer_circuit(builder, &mut state);
+
+        for round in 0..HALF_N_FULL_ROUNDS {
+            let constants = full_round_constants::<F::Extension>(round);
+            for (i, c) in constants.into_iter().enumerate() {
+                let c = builder.constant_extension(c);
+                state[i] = builder.add_extension(state[i], c);
+                if round != 0 {
+                    let sbox_in = wire(Self::wire_full_sbox_0(round, i));
+                    constraints.push(builder.sub_extension(state[i], sbox_in));
+                    state[i] = sbox_in;
+                }
+                state[i] = builder.exp_u64_extension(state[i], 7);
+            }
+            external_linear_layer_circuit(builder, &mut state);
+        }
+
+        for round in 0..N_PARTIAL_ROUNDS {
+            let c = builder.constant_extension(partial_round_constant(round));
+            let sbox_in = wire(Self::wire_partial_sbox(round));
+            let x = builder.add_extension(state[0], c);
+            constraints.push(builder.sub_extension(x, sbox_in));
+            state[0] = builder.exp_u64_extension(sbox_in, 7);
+            internal_linear_layer_circuit(builder, &mut state);
+        }
+
+        for round in 0..HALF_N_FULL_ROUNDS {
+            let constants = full_round_constants::<F::Extension>(HALF_N_FULL_ROUNDS + round);
+            for (i, c) in constants.into_iter().enumerate() {
+                let c = builder.constant_extension(c);
+                let sbox_in = wire(Self::wire_full_sbox_1(round, i));
+                let x = builder.add_extension(state[i], c);
+                constraints.push(builder.sub_extension(x, sbox_in));
+                state[i] = builder.exp_u64_extension(sbox_in, 7);
+            }
+            external_linear_layer_circuit(builder, &mut state);
+        }
+
+        for (i, x) in state.into_iter().enumerate() {
+            constraints.push(builder.sub_extension(x, wire(Self::wire_output(i))));
+        }
+
+        constraints
+    }
+
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F, D>> {
+        let gen = Poseidon2Generator::<F, D> {
+            row,
+            _phantom: PhantomData,
+        };
+        vec![WitnessGeneratorRef::new(gen.adapter())]
+    }
+
+    fn num_wires(&self) -> usize {
+        Self::end()
+    }
+
+    fn num_constants(&self) -> usize {
+        0
+    }
+
+    // The S-box `x^7` of a stored input.
+    fn degree(&self) -> usize {
+        7
+    }
+
+    // 1 for the swap, 4 for the deltas, 1 for each stored S-box input and 1 for each output.
+    fn num_constraints(&self) -> usize {
+        5 + (Self::end() - Self::START_FULL_0) + SPONGE_WIDTH
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Poseidon2Generator<F: RichField + Extendable<D>, const D: usize> {
+    row: usize,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Poseidon2Generator<F, D> {
+    pub fn id() -> String {
+        "Poseidon2Generator".to_string()
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for Poseidon2Generator<F, D>
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        (0..SPONGE_WIDTH)
+            .map(Poseidon2Gate::<F, D>::wire_input)
+            .chain(Some(Poseidon2Gate::<F, D>::WIRE_SWAP))
+            .map(|column| Target::wire(self.row, column))
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let local_wire = |column| Target::wire(self.row, column);
+
+        let mut state: [F; SPONGE_WIDTH] = core::array::from_fn(|i| {
+            witness.get_target(local_wire(Poseidon2Gate::<F, D>::wire_input(i)))
+        });
+        let swap = witness.get_target(local_wire(Poseidon2Gate::<F, D>::WIRE_SWAP));
+        debug_assert!(swap == F::ZERO || swap == F::ONE);
+
+        for i in 0..4 {
+            let delta = swap * (state[i + 4] - state[i]);
+            out_buffer.set_target(local_wire(Poseidon2Gate::<F, D>::wire_delta(i)), delta);
+        }
+        if swap == F::ONE {
+            for i in 0..4 {
+                state.swap(i, i + 4);
+            }
+        }
+
+        external_linear_layer(&mut state);
+
+        for round in 0..HALF_N_FULL_ROUNDS {
+            for (i, c) in full_round_constants::<F>(round).into_iter().enumerate() {
+                state[i] += c;
+                if round != 0 {
+                    out_buffer.set_target(
+                        local_wire(Poseidon2Gate::<F, D>::wire_full_sbox_0(round, i)),
+                        state[i],
+                    );
+                }
+                state[i] = sbox(state[i]);
+            }
+            external_linear_layer(&mut state);
+        }
+
+        for round in 0..N_PARTIAL_ROUNDS {
+            state[0] += partial_round_constant(round);
+            out_buffer.set_target(
+                local_wire(Poseidon2Gate::<F, D>::wire_partial_sbox(round)),
+                state[0],
+            );
+            state[0] = sbox(state[0]);
+            internal_linear_layer(&mut state);
+        }
+
+        for round in 0..HALF_N_FULL_ROUNDS {
+            let constants = full_round_constants::<F>(HALF_N_FULL_ROUNDS + round);
+            for (i, c) in constants.into_iter().enumerate() {
+                state[i] += c;
+                out_buffer.set_target(
+                    local_wire(Poseidon2Gate::<F, D>::wire_full_sbox_1(round, i)),
+                    state[i],
+                );
+                state[i] = sbox(state[i]);
+            }
+            external_linear_layer(&mut state);
+        }
+
+        for (i, x) in state.into_iter().enumerate() {
+            out_buffer.set_target(local_wire(Poseidon2Gate::<F, D>::wire_output(i)), x);
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_usize(self.row)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        Ok(Self {
+            row,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    use super::*;
+
+    #[test]
+    fn low_degree() {
+        test_low_degree::<GoldilocksField, _, 4>(Poseidon2Gate::<GoldilocksField, 4>::new())
+    }
+
+    #[test]
+    fn eval_fns() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_eval_fns::<F, C, _, D>(Poseidon2Gate::<F, D>::new()).unwrap();
+    }
+}
@@ -0,0 +1,346 @@
+//! Implementation of the Poseidon2 permutation over the Goldilocks field with a width of 12, and
+//! of a plonky2 hasher and config which use it in place of Poseidon.
+//! reference: https://eprint.iacr.org/2023/323
+//!
+//! The permutation has the same number of rounds as the Poseidon of plonky2, so `Poseidon2Gate`
+//! has the same wires and constraints as the `PoseidonGate`, but its linear layers only need
+//! additions and a diagonal matrix instead of a dense MDS matrix, which makes the gate cheaper to
+//! evaluate in a recursive verifier.
+
+mod constants;
+pub mod gate;
+
+use core::fmt::Debug;
+
+use plonky2::field::extension::quadratic::QuadraticExtension;
+use plonky2::field::extension::Extendable;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::hash::hashing::{compress, hash_n_to_hash_no_pad, PlonkyPermutation};
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder as CircuitAPI;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use serde::Serialize;
+
+use self::constants::{
+    FULL_ROUND_CONSTANTS_FIRST, FULL_ROUND_CONSTANTS_LAST, INTERNAL_MATRIX_DIAG_M_1,
+    PARTIAL_ROUND_CONSTANTS,
+};
+use self::gate::Poseidon2Gate;
+use super::poseidon256::PoseidonHashOutVariable;
+use crate::prelude::{CircuitBuilder, CircuitVariable, PlonkParameters, Variable};
+
+/// The width of the permutation.
+pub const SPONGE_WIDTH: usize = 12;
+
+/// The number of elements absorbed by each permutation of the sponge.
+pub const SPONGE_RATE: usize = 8;
+
+/// The number of full rounds on each side of the partial rounds.
+pub const HALF_N_FULL_ROUNDS: usize = 4;
+
+/// The number of partial rounds.
+pub const N_PARTIAL_ROUNDS: usize = 22;
+
+/// The S-box `x^7`.
+pub(crate) fn sbox<FE: Field>(x: FE) -> FE {
+    x.exp_u64(7)
+}
+
+/// The round constants of the given full round, from 0 to `2 * HALF_N_FULL_ROUNDS - 1`.
+pub(crate) fn full_round_constants<FE: Field>(round: usize) -> [FE; SPONGE_WIDTH] {
+    let constants = if round < HALF_N_FULL_ROUNDS {
+        FULL_ROUND_CONSTANTS_FIRST[round]
+    } else {
+        FULL_ROUND_CONSTANTS_LAST[round - HALF_N_FULL_ROUNDS]
+    };
+    constants.map(FE::from_canonical_u64)
+}
+
+/// The round constant of the given partial round.
+pub(crate) fn partial_round_constant<FE: Field>(round: usize) -> FE {
+    FE::from_canonical_u64(PARTIAL_ROUND_CONSTANTS[round])
+}
+
+/// The external linear layer, which applies the matrix `circ(2 * M4, M4, M4)` where `M4` is the
+/// 4x4 MDS matrix of the Poseidon2 paper, using only additions.
+pub(crate) fn external_linear_layer<FE: Field>(state: &mut [FE; SPONGE_WIDTH]) {
+    for x in state.chunks_exact_mut(4) {
+        let t0 = x[0] + x[1];
+        let t1 = x[2] + x[3];
+        let t2 = x[1].double() + t1;
+        let t3 = x[3].double() + t0;
+        let t4 = t1.double().double() + t3;
+        let t5 = t0.double().double() + t2;
+        x[0] = t3 + t5;
+        x[1] = t5;
+        x[2] = t2 + t4;
+        x[3] = t4;
+    }
+
+    let mut sums = [FE::ZERO; 4];
+    for x in state.chunks_exact(4) {
+        for i in 0..4 {
+            sums[i] += x[i];
+        }
+    }
+    for (i, x) in state.iter_mut().enumerate() {
+        *x += sums[i % 4];
+    }
+}
+
+/// The internal linear layer, which maps `x` to `x_i * INTERNAL_MATRIX_DIAG_M_1[i] + sum(x)`.
+pub(crate) fn internal_linear_layer<FE: Field>(state: &mut [FE; SPONGE_WIDTH]) {
+    let sum = state.iter().fold(FE::ZERO, |acc, x| acc + *x);
+    for (x, diag) in state.iter_mut().zip(INTERNAL_MATRIX_DIAG_M_1) {
+        *x = *x * FE::from_canonical_u64(diag) + sum;
+    }
+}
+
+/// Computes the Poseidon2 permutation of `input`.
+pub fn poseidon2<FE: Field>(input: [FE; SPONGE_WIDTH]) -> [FE; SPONGE_WIDTH] {
+    let mut state = input;
+    external_linear_layer(&mut state);
+
+    for round in 0..HALF_N_FULL_ROUNDS {
+        full_round(&mut state, round);
+    }
+    for round in 0..N_PARTIAL_ROUNDS {
+        state[0] = sbox(state[0] + partial_round_constant(round));
+        internal_linear_layer(&mut state);
+    }
+    for round in HALF_N_FULL_ROUNDS..2 * HALF_N_FULL_ROUNDS {
+        full_round(&mut state, round);
+    }
+
+    state
+}
+
+fn full_round<FE: Field>(state: &mut [FE; SPONGE_WIDTH], round: usize) {
+    for (x, c) in state.iter_mut().zip(full_round_constants::<FE>(round)) {
+        *x = sbox(*x + c);
+    }
+    external_linear_layer(state);
+}
+
+/// The permutation of the sponge states, which is only computed natively for field elements.
+pub trait Permuter: Sized {
+    fn permute(input: [Self; SPONGE_WIDTH]) -> [Self; SPONGE_WIDTH];
+}
+
+impl Permuter for GoldilocksField {
+    fn permute(input: [Self; SPONGE_WIDTH]) -> [Self; SPONGE_WIDTH] {
+        poseidon2(input)
+    }
+}
+
+impl Permuter for Target {
+    fn permute(_input: [Self; SPONGE_WIDTH]) -> [Self; SPONGE_WIDTH] {
+        panic!("Call `permute_swapped()` instead of `permute()`");
+    }
+}
+
+/// The Poseidon2 permutation as a plonky2 sponge state.
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct Poseidon2Permutation<T> {
+    state: [T; SPONGE_WIDTH],
+}
+
+impl<T: Eq> Eq for Poseidon2Permutation<T> {}
+
+impl<T> AsRef<[T]> for Poseidon2Permutation<T> {
+    fn as_ref(&self) -> &[T] {
+        &self.state
+    }
+}
+
+impl<T: Copy + Debug + Default + Eq + Permuter + Send + Sync> PlonkyPermutation<T>
+    for Poseidon2Permutation<T>
+{
+    const RATE: usize = SPONGE_RATE;
+    const WIDTH: usize = SPONGE_WIDTH;
+
+    fn new<I: IntoIterator<Item = T>>(elts: I) -> Self {
+        let mut perm = Self {
+            state: [T::default(); SPONGE_WIDTH],
+        };
+        perm.set_from_iter(elts, 0);
+        perm
+    }
+
+    fn set_elt(&mut self, elt: T, idx: usize) {
+        self.state[idx] = elt;
+    }
+
+    fn set_from_slice(&mut self, elts: &[T], start_idx: usize) {
+        self.state[start_idx..start_idx + elts.len()].copy_from_slice(elts);
+    }
+
+    fn set_from_iter<I: IntoIterator<Item = T>>(&mut self, elts: I, start_idx: usize) {
+        for (s, e) in self.state[start_idx..].iter_mut().zip(elts) {
+            *s = e;
+        }
+    }
+
+    fn permute(&mut self) {
+        self.state = T::permute(self.state);
+    }
+
+    fn squeeze(&self) -> &[T] {
+        &self.state[..Self::RATE]
+    }
+}
+
+/// The Poseidon2 hash function, with the same sponge construction as the `PoseidonHash` of
+/// plonky2.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Poseidon2Hash;
+
+impl Hasher<GoldilocksField> for Poseidon2Hash {
+    const HASH_SIZE: usize = 4 * 8;
+    type Hash = HashOut<GoldilocksField>;
+    type Permutation = Poseidon2Permutation<GoldilocksField>;
+
+    fn hash_no_pad(input: &[GoldilocksField]) -> Self::Hash {
+        hash_n_to_hash_no_pad::<GoldilocksField, Self::Permutation>(input)
+    }
+
+    fn two_to_one(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+        compress::<GoldilocksField, Self::Permutation>(left, right)
+    }
+}
+
+impl AlgebraicHasher<GoldilocksField> for Poseidon2Hash {
+    type AlgebraicPermutation = Poseidon2Permutation<Target>;
+
+    fn permute_swapped<const D: usize>(
+        inputs: Self::AlgebraicPermutation,
+        swap: BoolTarget,
+        builder: &mut CircuitAPI<GoldilocksField, D>,
+    ) -> Self::AlgebraicPermutation
+    where
+        GoldilocksField: RichField + Extendable<D>,
+    {
+        let gate = builder.add_gate(Poseidon2Gate::<GoldilocksField, D>::new(), vec![]);
+
+        let swap_wire = Target::wire(gate, Poseidon2Gate::<GoldilocksField, D>::WIRE_SWAP);
+        builder.connect(swap.target, swap_wire);
+
+        for (i, input) in inputs.as_ref().iter().enumerate() {
+            let in_wire = Target::wire(gate, Poseidon2Gate::<GoldilocksField, D>::wire_input(i));
+            builder.connect(*input, in_wire);
+        }
+
+        Self::AlgebraicPermutation::new(
+            (0..SPONGE_WIDTH)
+                .map(|i| Target::wire(gate, Poseidon2Gate::<GoldilocksField, D>::wire_output(i))),
+        )
+    }
+}
+
+/// Configuration using Poseidon2 over the Goldilocks field, both for the proofs and for the
+/// recursive verification of the inner proofs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub struct Poseidon2GoldilocksConfig;
+
+impl GenericConfig<2> for Poseidon2GoldilocksConfig {
+    type F = GoldilocksField;
+    type FE = QuadraticExtension<Self::F>;
+    type Hasher = Poseidon2Hash;
+    type InnerHasher = Poseidon2Hash;
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Computes the Poseidon2 hash of the given variables with no padding, whatever the hasher of
+    /// the config of the circuit.
+    pub fn poseidon2_hash(&mut self, variables: &[Variable]) -> PoseidonHashOutVariable
+    where
+        Poseidon2Hash: AlgebraicHasher<L::Field>,
+    {
+        let targets = variables.iter().map(|v| v.0).collect::<Vec<_>>();
+        PoseidonHashOutVariable::from_targets(
+            &self
+                .api
+                .hash_n_to_hash_no_pad::<Poseidon2Hash>(targets)
+                .elements,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Sample;
+
+    use super::*;
+    use crate::backend::circuit::Poseidon2Parameters;
+    use crate::prelude::DefaultBuilder;
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn test_poseidon2_permutation() {
+        let input = core::array::from_fn(|i| F::from_canonical_usize(i));
+        // Computed with an independent implementation of the same permutation and constants.
+        let expected = [
+            0xb36e78e10126f4f5,
+            0x703af60f4278bba3,
+            0x981edd6ce7c5f2ce,
+            0xc5fe5a4eebe117b5,
+            0x58c42fc766dff8b1,
+            0xa993a2dc2d6a4312,
+            0x26eb116a6c58845d,
+            0xa6fc76618fd6bcac,
+            0x537925250e7f44be,
+            0xf6ea37ffcd409af6,
+            0xc6e601d5af8658c3,
+            0x2426c9f16e1fee19,
+        ]
+        .map(F::from_canonical_u64);
+        assert_eq!(poseidon2(input), expected);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_poseidon2_hash() {
+        let inputs = F::rand_vec(20);
+        let expected = Poseidon2Hash::hash_no_pad(&inputs);
+
+        let mut builder = DefaultBuilder::new();
+        let variables = inputs
+            .iter()
+            .map(|x| builder.constant::<Variable>(*x))
+            .collect::<Vec<_>>();
+        let hash = builder.poseidon2_hash(&variables);
+        let expected = builder.constant::<PoseidonHashOutVariable>(expected);
+        builder.assert_is_equal(hash, expected);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_poseidon2_parameters() {
+        // The merkle caps and the challenges of the proof are computed with Poseidon2, and the
+        // builder's hash of the config is Poseidon2 as well.
+        let inputs = F::rand_vec(8);
+        let expected = Poseidon2Hash::hash_no_pad(&inputs);
+
+        let mut builder = CircuitBuilder::<Poseidon2Parameters, 2>::new();
+        let variables = inputs
+            .iter()
+            .map(|x| builder.constant::<Variable>(*x))
+            .collect::<Vec<_>>();
+        let hash = builder.poseidon_hash(&variables);
+        let expected = builder.constant::<PoseidonHashOutVariable>(expected);
+        builder.assert_is_equal(hash, expected);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
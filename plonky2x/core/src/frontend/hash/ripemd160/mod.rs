@@ -0,0 +1,254 @@
+//! Implementation of ripemd160
+//! reference: https://homes.esat.kuleuven.be/~bosselae/ripemd160/pdf/AB-9601/AB-9601.pdf
+
+use array_macro::array;
+use itertools::Itertools;
+
+use crate::frontend::hash::common::{and_arr, not_arr, xor2_arr, xor3_arr};
+use crate::prelude::*;
+
+/// The block size of RIPEMD-160 in bytes.
+pub const RIPEMD160_BLOCK_BYTES: usize = 64;
+
+const INITIAL_HASH: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+const K_LEFT: [u32; 5] = [0x00000000, 0x5A827999, 0x6ED9EBA1, 0x8F1BBCDC, 0xA953FD4E];
+const K_RIGHT: [u32; 5] = [0x50A28BE6, 0x5C4DD124, 0x6D703EF3, 0x7A6D76E9, 0x00000000];
+
+const R_LEFT: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5,
+    2, 14, 11, 8, 3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, 1, 9, 11, 10, 0, 8, 12, 4,
+    13, 3, 7, 15, 14, 5, 6, 2, 4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+const R_RIGHT: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, 6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12,
+    4, 9, 1, 2, 15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, 8, 6, 4, 1, 3, 11, 15, 0, 5,
+    12, 2, 13, 9, 7, 10, 14, 12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+const S_LEFT: [usize; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, 7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15,
+    9, 11, 7, 13, 12, 11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, 11, 12, 14, 15, 14,
+    15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, 9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+const S_RIGHT: [usize; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, 9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12,
+    7, 6, 15, 13, 11, 9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, 15, 5, 8, 11, 14, 14,
+    6, 14, 6, 9, 12, 9, 12, 5, 15, 8, 8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+/// A 32-bit word as big endian bits.
+type Word = [BoolVariable; 32];
+
+fn constant_word<L: PlonkParameters<D>, const D: usize>(
+    value: u32,
+    builder: &mut CircuitBuilder<L, D>,
+) -> Word {
+    array![i => builder.constant::<BoolVariable>((value >> (31 - i)) & 1 == 1); 32]
+}
+
+fn or_arr<L: PlonkParameters<D>, const D: usize>(
+    a: Word,
+    b: Word,
+    builder: &mut CircuitBuilder<L, D>,
+) -> Word {
+    array![i => builder.or(a[i], b[i]); 32]
+}
+
+fn rotate_left<L: PlonkParameters<D>, const D: usize>(
+    word: Word,
+    bits: usize,
+    builder: &CircuitBuilder<L, D>,
+) -> Word {
+    builder._right_rotate(word, 32 - bits)
+}
+
+/// The boolean function of the given round, from 0 to 4.
+fn round_function<L: PlonkParameters<D>, const D: usize>(
+    round: usize,
+    x: Word,
+    y: Word,
+    z: Word,
+    builder: &mut CircuitBuilder<L, D>,
+) -> Word {
+    match round {
+        0 => xor3_arr(x, y, z, builder),
+        1 => {
+            let x_and_y = and_arr(x, y, builder);
+            let not_x = not_arr(x, builder);
+            let not_x_and_z = and_arr(not_x, z, builder);
+            or_arr(x_and_y, not_x_and_z, builder)
+        }
+        2 => {
+            let not_y = not_arr(y, builder);
+            let x_or_not_y = or_arr(x, not_y, builder);
+            xor2_arr(x_or_not_y, z, builder)
+        }
+        3 => {
+            let x_and_z = and_arr(x, z, builder);
+            let not_z = not_arr(z, builder);
+            let y_and_not_z = and_arr(y, not_z, builder);
+            or_arr(x_and_z, y_and_not_z, builder)
+        }
+        4 => {
+            let not_z = not_arr(z, builder);
+            let y_or_not_z = or_arr(y, not_z, builder);
+            xor2_arr(x, y_or_not_z, builder)
+        }
+        _ => unreachable!(),
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Pad the given input according to the RIPEMD-160 spec, which is the SHA-256 padding with
+    /// the bit length in little endian order.
+    pub(crate) fn pad_message_ripemd160(&mut self, input: &[ByteVariable]) -> Vec<ByteVariable> {
+        let mut padding = vec![0x80u8];
+        while (input.len() + padding.len()) % RIPEMD160_BLOCK_BYTES != 56 {
+            padding.push(0);
+        }
+        padding.extend(((input.len() as u64) * 8).to_le_bytes());
+
+        let mut padded = input.to_vec();
+        padded.extend(
+            padding
+                .into_iter()
+                .map(|b| self.constant::<ByteVariable>(b)),
+        );
+        padded
+    }
+
+    /// Computes the RIPEMD-160 digest of `input`.
+    pub fn ripemd160(&mut self, input: &[ByteVariable]) -> BytesVariable<20> {
+        let padded = self.pad_message_ripemd160(input);
+        let mut h = INITIAL_HASH.map(|word| constant_word(word, self));
+
+        for block in padded.chunks_exact(RIPEMD160_BLOCK_BYTES) {
+            // The message words are read in little endian order.
+            let x = block
+                .chunks_exact(4)
+                .map(|bytes| {
+                    let bits = bytes
+                        .iter()
+                        .rev()
+                        .flat_map(|byte| byte.as_be_bits())
+                        .collect_vec();
+                    Word::try_from(bits).unwrap()
+                })
+                .collect_vec();
+
+            let [mut a, mut b, mut c, mut d, mut e] = h;
+            let [mut a_r, mut b_r, mut c_r, mut d_r, mut e_r] = h;
+            for j in 0..80 {
+                let round = j / 16;
+
+                let f = round_function(round, b, c, d, self);
+                let k = constant_word(K_LEFT[round], self);
+                let sum = self.add_many_arr(&[a, f, x[R_LEFT[j]], k]);
+                let t = rotate_left(sum, S_LEFT[j], self);
+                let t = self.add_arr(t, e);
+                a = e;
+                e = d;
+                d = rotate_left(c, 10, self);
+                c = b;
+                b = t;
+
+                let f = round_function(4 - round, b_r, c_r, d_r, self);
+                let k = constant_word(K_RIGHT[round], self);
+                let sum = self.add_many_arr(&[a_r, f, x[R_RIGHT[j]], k]);
+                let t = rotate_left(sum, S_RIGHT[j], self);
+                let t = self.add_arr(t, e_r);
+                a_r = e_r;
+                e_r = d_r;
+                d_r = rotate_left(c_r, 10, self);
+                c_r = b_r;
+                b_r = t;
+            }
+
+            h = [
+                self.add_many_arr(&[h[1], c, d_r]),
+                self.add_many_arr(&[h[2], d, e_r]),
+                self.add_many_arr(&[h[3], e, a_r]),
+                self.add_many_arr(&[h[4], a, b_r]),
+                self.add_many_arr(&[h[0], b, c_r]),
+            ];
+        }
+
+        // The digest is the words of the state in little endian order.
+        let bytes = h
+            .iter()
+            .flat_map(|word| {
+                word.chunks_exact(8)
+                    .rev()
+                    .map(|bits| ByteVariable::from_be_bits(bits.try_into().unwrap()))
+                    .collect_vec()
+            })
+            .collect_vec();
+        BytesVariable(bytes.try_into().unwrap())
+    }
+
+    /// Computes `ripemd160(sha256(input))`, the hash of public keys and scripts in Bitcoin
+    /// addresses.
+    pub fn hash160(&mut self, input: &[ByteVariable]) -> BytesVariable<20> {
+        let sha256_digest = self.curta_sha256(input);
+        self.ripemd160(&sha256_digest.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::utils::bytes;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ripemd160() {
+        // The test vectors of the specification, and a message whose padding needs a second block.
+        let test_vectors: Vec<(Vec<u8>, [u8; 20])> = vec![
+            (vec![], bytes!("9c1185a5c5e9fc54612808977ee8f548b2258d31")),
+            (
+                b"abc".to_vec(),
+                bytes!("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+            ),
+            (
+                (0..56).collect(),
+                bytes!("ebdd79cfd4fd9949ef8089673d2620427f487cfb"),
+            ),
+        ];
+
+        let mut builder = DefaultBuilder::new();
+        for (message, expected) in test_vectors {
+            let message = message
+                .iter()
+                .map(|b| builder.constant::<ByteVariable>(*b))
+                .collect::<Vec<_>>();
+            let digest = builder.ripemd160(&message);
+            let expected = builder.constant::<BytesVariable<20>>(expected);
+            builder.assert_is_equal(digest, expected);
+        }
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_hash160() {
+        // The compressed public key of the secp256k1 generator.
+        let mut builder = DefaultBuilder::new();
+        let public_key = builder.constant::<BytesVariable<33>>(bytes!(
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        ));
+        let digest = builder.hash160(&public_key.0);
+        let expected = builder
+            .constant::<BytesVariable<20>>(bytes!("751e76e8199196d454941c45d1b3a323f1433bd6"));
+        builder.assert_is_equal(digest, expected);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
@@ -0,0 +1,116 @@
+use itertools::Itertools;
+
+use super::pad::{SHA256_CHUNK_SIZE_BYTES, SHA256_INPUT_LENGTH_BYTE_SIZE};
+use crate::prelude::*;
+
+/// An incremental SHA-256 hasher, which is fed the message one 64-byte block at a time so that
+/// long messages never have to be held in a single input array.
+///
+/// The blocks are compressed as soon as they are absorbed, with the same compression function as
+/// `builder.sha256`, and `finalize` pads the remaining bytes with the length of the whole message.
+#[derive(Debug, Clone)]
+pub struct Sha256Accumulator {
+    state: [[BoolVariable; 32]; 8],
+    nb_blocks: usize,
+}
+
+impl Sha256Accumulator {
+    pub fn new<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>) -> Self {
+        Self {
+            state: builder.get_inital_hash(),
+            nb_blocks: 0,
+        }
+    }
+
+    /// Absorbs a full block of the message.
+    pub fn absorb_block<L: PlonkParameters<D>, const D: usize>(
+        &mut self,
+        block: &[ByteVariable],
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        assert_eq!(
+            block.len(),
+            SHA256_CHUNK_SIZE_BYTES,
+            "a block has {} bytes",
+            SHA256_CHUNK_SIZE_BYTES
+        );
+        let bits = block.iter().flat_map(|b| b.as_be_bits()).collect_vec();
+        self.state = builder.sha256_compress(self.state, &bits);
+        self.nb_blocks += 1;
+    }
+
+    /// Pads the last bytes of the message, which are fewer than a block, and returns the digest of
+    /// the whole message.
+    pub fn finalize<L: PlonkParameters<D>, const D: usize>(
+        mut self,
+        last_bytes: &[ByteVariable],
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Bytes32Variable {
+        assert!(
+            last_bytes.len() < SHA256_CHUNK_SIZE_BYTES,
+            "full blocks must be absorbed with `absorb_block`"
+        );
+        let message_len = self.nb_blocks * SHA256_CHUNK_SIZE_BYTES + last_bytes.len();
+
+        let mut padding = vec![0x80u8];
+        while (last_bytes.len() + padding.len()) % SHA256_CHUNK_SIZE_BYTES
+            != SHA256_CHUNK_SIZE_BYTES - SHA256_INPUT_LENGTH_BYTE_SIZE
+        {
+            padding.push(0);
+        }
+        padding.extend(((message_len as u64) * 8).to_be_bytes());
+
+        let mut padded = last_bytes.to_vec();
+        padded.extend(
+            padding
+                .into_iter()
+                .map(|b| builder.constant::<ByteVariable>(b)),
+        );
+        for block in padded.chunks_exact(SHA256_CHUNK_SIZE_BYTES) {
+            self.absorb_block(block, builder);
+        }
+
+        let bits = self
+            .state
+            .iter()
+            .flatten()
+            .map(|b| b.variable)
+            .collect_vec();
+        // Ok to use `from_variables_unsafe` as the state has 256 bits
+        Bytes32Variable::from_variables_unsafe(&bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hash::sha256;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_sha256_accumulator() {
+        // Two full blocks followed by a tail whose padding needs one more block.
+        let message = (0..188).map(|i| i as u8).collect::<Vec<_>>();
+
+        let mut builder = DefaultBuilder::new();
+        let message_variables = message
+            .iter()
+            .map(|b| builder.constant::<ByteVariable>(*b))
+            .collect::<Vec<_>>();
+        let (blocks, last_bytes) = message_variables.split_at(128);
+
+        let mut accumulator = Sha256Accumulator::new(&mut builder);
+        for block in blocks.chunks_exact(SHA256_CHUNK_SIZE_BYTES) {
+            accumulator.absorb_block(block, &mut builder);
+        }
+        let digest = accumulator.finalize(last_bytes, &mut builder);
+
+        let expected = builder.constant::<Bytes32Variable>(sha256(&message).into());
+        builder.assert_is_equal(digest, expected);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
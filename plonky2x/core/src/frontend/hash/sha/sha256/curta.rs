@@ -118,10 +118,16 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     /// Executes a SHA256 hash on the given input of fixed size.
     pub fn curta_sha256(&mut self, input: &[ByteVariable]) -> Bytes32Variable {
         if self.sha256_accelerator.is_none() {
-            self.sha256_accelerator = Some(SHA256Accelerator {
-                hash_requests: Vec::new(),
-                hash_responses: Vec::new(),
-            });
+            self.sha256_accelerator = Some(SHA256Accelerator::new());
+        }
+
+        let request = HashRequest::Fixed(input.to_vec());
+        let cached_digest = self
+            .sha256_accelerator
+            .as_ref()
+            .and_then(|accelerator| accelerator.cached_digest(&request));
+        if let Some(digest) = cached_digest {
+            return Bytes32Variable::from_variables_unsafe(digest);
         }
 
         let digest = self.init::<Bytes32Variable>();
@@ -131,8 +137,9 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             .as_mut()
             .expect("sha256 accelerator should exist");
         accelerator
-            .hash_requests
-            .push(HashRequest::Fixed(input.to_vec()));
+            .digests
+            .insert(request.key(), digest.variables());
+        accelerator.hash_requests.push(request);
         accelerator.hash_responses.push(digest_array);
 
         digest
@@ -161,10 +168,16 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 
         let last_chunk = self.compute_sha256_last_chunk(length);
         if self.sha256_accelerator.is_none() {
-            self.sha256_accelerator = Some(SHA256Accelerator {
-                hash_requests: Vec::new(),
-                hash_responses: Vec::new(),
-            });
+            self.sha256_accelerator = Some(SHA256Accelerator::new());
+        }
+
+        let request = HashRequest::Variable(input.to_vec(), length, last_chunk);
+        let cached_digest = self
+            .sha256_accelerator
+            .as_ref()
+            .and_then(|accelerator| accelerator.cached_digest(&request));
+        if let Some(digest) = cached_digest {
+            return Bytes32Variable::from_variables_unsafe(digest);
         }
 
         let digest = self.init::<Bytes32Variable>();
@@ -174,8 +187,9 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             .as_mut()
             .expect("sha256 accelerator should exist");
         accelerator
-            .hash_requests
-            .push(HashRequest::Variable(input.to_vec(), length, last_chunk));
+            .digests
+            .insert(request.key(), digest.variables());
+        accelerator.hash_requests.push(request);
         accelerator.hash_responses.push(digest_array);
 
         digest
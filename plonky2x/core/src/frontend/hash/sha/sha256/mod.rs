@@ -9,6 +9,7 @@ use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::hash::common::{and_arr, not_arr, xor2_arr, xor3_arr};
 use crate::frontend::vars::{BoolVariable, ByteVariable, Bytes32Variable, CircuitVariable};
 
+pub mod accumulator;
 pub mod curta;
 pub mod pad;
 
@@ -23,7 +24,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             .unwrap()
     }
 
-    fn get_inital_hash(&mut self) -> [[BoolVariable; 32]; 8] {
+    pub(crate) fn get_inital_hash(&mut self) -> [[BoolVariable; 32]; 8] {
         SHA256::INITIAL_HASH.map(|x| self.const_be_bits(x))
     }
 
@@ -31,101 +32,106 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         SHA256::ROUND_CONSTANTS.map(|x| self.const_be_bits(x))
     }
 
+    /// Returns the state `sha256_hash` after compressing the 512 bits of `chunk`.
+    pub(crate) fn sha256_compress(
+        &mut self,
+        sha256_hash: [[BoolVariable; 32]; 8],
+        chunk: &[BoolVariable],
+    ) -> [[BoolVariable; 32]; 8] {
+        assert_eq!(chunk.len(), 512);
+        let round_constants = self.get_round_constants();
+
+        let mut u: Vec<BoolVariable> = Vec::new();
+
+        for bit in chunk.iter() {
+            // 0 .. 16 chunk size * 32 bits7
+            u.push(*bit);
+        }
+        for _ in 512..64 * 32 {
+            // 16 * 8 ... 64 * 8 because of L
+            u.push(self._false());
+        }
+
+        let mut w = self.reshape(u);
+
+        for i in 16..64 {
+            let s0 = xor3_arr(
+                self._right_rotate(w[i - 15], 7),
+                self._right_rotate(w[i - 15], 18),
+                self._shr(w[i - 15], 3),
+                self,
+            );
+
+            let s1 = xor3_arr(
+                self._right_rotate(w[i - 2], 17),
+                self._right_rotate(w[i - 2], 19),
+                self._shr(w[i - 2], 10),
+                self,
+            );
+
+            w[i] = self.add_many_arr(&[w[i - 16], s0, w[i - 7], s1]);
+        }
+        let mut a = sha256_hash[0];
+        let mut b = sha256_hash[1];
+        let mut c = sha256_hash[2];
+        let mut d = sha256_hash[3];
+        let mut e = sha256_hash[4];
+        let mut f = sha256_hash[5];
+        let mut g = sha256_hash[6];
+        let mut h = sha256_hash[7];
+
+        for i in 0..64 {
+            let sum1 = xor3_arr(
+                self._right_rotate(e, 6),
+                self._right_rotate(e, 11),
+                self._right_rotate(e, 25),
+                self,
+            );
+            let ch = xor2_arr(
+                and_arr(e, f, self),
+                and_arr(not_arr(e, self), g, self),
+                self,
+            );
+            let final_temp1 = self.add_many_arr(&[h, sum1, ch, round_constants[i], w[i]]);
+
+            let sum0 = xor3_arr(
+                self._right_rotate(a, 2),
+                self._right_rotate(a, 13),
+                self._right_rotate(a, 22),
+                self,
+            );
+
+            let maj = xor3_arr(
+                and_arr(a, b, self),
+                and_arr(a, c, self),
+                and_arr(b, c, self),
+                self,
+            );
+            let final_temp2 = self.add_arr(sum0, maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = self.add_arr(d, final_temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = self.add_arr(final_temp1, final_temp2);
+        }
+
+        self.zip_add(sha256_hash, [a, b, c, d, e, f, g, h])
+    }
+
     fn process_padded_message(&mut self, msg_input: &[ByteVariable]) -> Vec<BoolVariable> {
         let msg_input_bits = msg_input
             .iter()
             .flat_map(|b| b.as_be_bits().to_vec())
             .collect_vec();
         let mut sha256_hash = self.get_inital_hash();
-        let round_constants = self.get_round_constants();
 
         // Process the input with 512 bit chunks aka 64 byte chunks
         for chunk in msg_input_bits.chunks_exact(512) {
-            let mut u: Vec<BoolVariable> = Vec::new();
-
-            for bit in chunk.iter() {
-                // 0 .. 16 chunk size * 32 bits7
-                u.push(*bit);
-            }
-            for _ in 512..64 * 32 {
-                // 16 * 8 ... 64 * 8 because of L
-                u.push(self._false());
-            }
-
-            let mut w = self.reshape(u);
-
-            for i in 16..64 {
-                let s0 = xor3_arr(
-                    self._right_rotate(w[i - 15], 7),
-                    self._right_rotate(w[i - 15], 18),
-                    self._shr(w[i - 15], 3),
-                    self,
-                );
-
-                let s1 = xor3_arr(
-                    self._right_rotate(w[i - 2], 17),
-                    self._right_rotate(w[i - 2], 19),
-                    self._shr(w[i - 2], 10),
-                    self,
-                );
-
-                let inter1 = self.add_arr(w[i - 16], s0);
-                let inter2 = self.add_arr(inter1, w[i - 7]);
-                w[i] = self.add_arr(s1, inter2);
-            }
-            let mut a = sha256_hash[0];
-            let mut b = sha256_hash[1];
-            let mut c = sha256_hash[2];
-            let mut d = sha256_hash[3];
-            let mut e = sha256_hash[4];
-            let mut f = sha256_hash[5];
-            let mut g = sha256_hash[6];
-            let mut h = sha256_hash[7];
-
-            for i in 0..64 {
-                let sum1 = xor3_arr(
-                    self._right_rotate(e, 6),
-                    self._right_rotate(e, 11),
-                    self._right_rotate(e, 25),
-                    self,
-                );
-                let ch = xor2_arr(
-                    and_arr(e, f, self),
-                    and_arr(not_arr(e, self), g, self),
-                    self,
-                );
-                let temp1 = self.add_arr(h, sum1);
-                let temp2 = self.add_arr(temp1, ch);
-                let temp3 = self.add_arr(temp2, round_constants[i]);
-                let temp4 = self.add_arr(temp3, w[i]);
-                let final_temp1 = temp4;
-
-                let sum0 = xor3_arr(
-                    self._right_rotate(a, 2),
-                    self._right_rotate(a, 13),
-                    self._right_rotate(a, 22),
-                    self,
-                );
-
-                let maj = xor3_arr(
-                    and_arr(a, b, self),
-                    and_arr(a, c, self),
-                    and_arr(b, c, self),
-                    self,
-                );
-                let final_temp2 = self.add_arr(sum0, maj);
-
-                h = g;
-                g = f;
-                f = e;
-                e = self.add_arr(d, final_temp1);
-                d = c;
-                c = b;
-                b = a;
-                a = self.add_arr(final_temp1, final_temp2);
-            }
-
-            sha256_hash = self.zip_add(sha256_hash, [a, b, c, d, e, f, g, h]);
+            sha256_hash = self.sha256_compress(sha256_hash, chunk);
         }
 
         sha256_hash.iter().flat_map(|x| x.to_vec()).collect()
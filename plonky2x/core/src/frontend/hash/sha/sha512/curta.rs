@@ -127,10 +127,16 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     /// Executes a SHA512 hash on the given input of fixed size.
     pub fn curta_sha512(&mut self, input: &[ByteVariable]) -> BytesVariable<64> {
         if self.sha512_accelerator.is_none() {
-            self.sha512_accelerator = Some(SHA512Accelerator {
-                hash_requests: Vec::new(),
-                hash_responses: Vec::new(),
-            });
+            self.sha512_accelerator = Some(SHA512Accelerator::new());
+        }
+
+        let request = HashRequest::Fixed(input.to_vec());
+        let cached_digest = self
+            .sha512_accelerator
+            .as_ref()
+            .and_then(|accelerator| accelerator.cached_digest(&request));
+        if let Some(digest) = cached_digest {
+            return BytesVariable::<64>::from_variables_unsafe(digest);
         }
 
         let digest = self.init::<BytesVariable<64>>();
@@ -140,8 +146,9 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             .as_mut()
             .expect("sha512 accelerator should exist");
         accelerator
-            .hash_requests
-            .push(HashRequest::Fixed(input.to_vec()));
+            .digests
+            .insert(request.key(), digest.variables());
+        accelerator.hash_requests.push(request);
         accelerator.hash_responses.push(digest_array);
 
         digest
@@ -162,10 +169,16 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let last_chunk = self.compute_sha512_last_chunk(length);
 
         if self.sha512_accelerator.is_none() {
-            self.sha512_accelerator = Some(SHA512Accelerator {
-                hash_requests: Vec::new(),
-                hash_responses: Vec::new(),
-            });
+            self.sha512_accelerator = Some(SHA512Accelerator::new());
+        }
+
+        let request = HashRequest::Variable(input.to_vec(), length, last_chunk);
+        let cached_digest = self
+            .sha512_accelerator
+            .as_ref()
+            .and_then(|accelerator| accelerator.cached_digest(&request));
+        if let Some(digest) = cached_digest {
+            return BytesVariable::<64>::from_variables_unsafe(digest);
         }
 
         let digest = self.init::<BytesVariable<64>>();
@@ -175,8 +188,9 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             .as_mut()
             .expect("sha512 accelerator should exist");
         accelerator
-            .hash_requests
-            .push(HashRequest::Variable(input.to_vec(), length, last_chunk));
+            .digests
+            .insert(request.key(), digest.variables());
+        accelerator.hash_requests.push(request);
         accelerator.hash_responses.push(digest_array);
 
         digest
@@ -229,6 +243,42 @@ mod tests {
         circuit.verify(&proof, &input, &output);
     }
 
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_sha512_curta_shared_requests() {
+        let mut builder = DefaultBuilder::new();
+        let message = b"plonky2"
+            .iter()
+            .map(|b| builder.constant::<ByteVariable>(*b))
+            .collect::<Vec<_>>();
+        let digest = builder.curta_sha512(&message);
+        let repeated_digest = builder.curta_sha512(&message);
+        let length = builder.constant::<U32Variable>(5);
+        let prefix_digest = builder.curta_sha512_variable(&message, length);
+        let repeated_prefix_digest = builder.curta_sha512_variable(&message, length);
+        assert_eq!(
+            builder
+                .sha512_accelerator
+                .as_ref()
+                .unwrap()
+                .hash_requests
+                .len(),
+            2
+        );
+
+        builder.assert_is_equal(digest, repeated_digest);
+        builder.assert_is_equal(prefix_digest, repeated_prefix_digest);
+        let expected_digest = builder.constant::<BytesVariable<64>>(bytes!(
+            "7c6159dd615db8c15bc76e23d36106e77464759979a0fcd1366e531f552cfa0852dbf5c832f00bb279cbc945b44a132bff3ed0028259813b6a07b57326e88c87"
+        ));
+        builder.assert_is_equal(digest, expected_digest);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
     #[test]
     #[cfg_attr(feature = "ci", ignore)]
     fn test_sha512_curta_empty() {
@@ -0,0 +1,68 @@
+use crate::prelude::*;
+
+/// The block size of SHA-512 in bytes.
+const BLOCK_BYTES: usize = 128;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Computes HMAC-SHA512 of `message` under a key of at most one block.
+    pub fn hmac_sha512(
+        &mut self,
+        key: &[ByteVariable],
+        message: &[ByteVariable],
+    ) -> BytesVariable<64> {
+        assert!(
+            key.len() <= BLOCK_BYTES,
+            "keys longer than a block are not supported"
+        );
+        let zero = self.constant::<ByteVariable>(0);
+        let mut padded_key = key.to_vec();
+        padded_key.resize(BLOCK_BYTES, zero);
+
+        let ipad = self.constant::<ByteVariable>(0x36);
+        let mut inner = padded_key
+            .iter()
+            .map(|byte| self.xor(*byte, ipad))
+            .collect::<Vec<_>>();
+        inner.extend_from_slice(message);
+        let inner_digest = self.curta_sha512(&inner);
+
+        let opad = self.constant::<ByteVariable>(0x5c);
+        let mut outer = padded_key
+            .iter()
+            .map(|byte| self.xor(*byte, opad))
+            .collect::<Vec<_>>();
+        outer.extend(inner_digest.0);
+        self.curta_sha512(&outer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::utils::bytes;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_hmac_sha512() {
+        // Test case 2 of RFC 4231.
+        let mut builder = DefaultBuilder::new();
+        let key = b"Jefe"
+            .iter()
+            .map(|b| builder.constant::<ByteVariable>(*b))
+            .collect::<Vec<_>>();
+        let message = b"what do ya want for nothing?"
+            .iter()
+            .map(|b| builder.constant::<ByteVariable>(*b))
+            .collect::<Vec<_>>();
+        let mac = builder.hmac_sha512(&key, &message);
+        let expected_mac = builder.constant::<BytesVariable<64>>(bytes!(
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        ));
+        builder.assert_is_equal(mac, expected_mac);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
@@ -1,2 +1,170 @@
+//! Implementation of sha512
+//! reference: https://datatracker.ietf.org/doc/html/rfc6234
+
+use ::starkyx::machine::hash::sha::algorithm::SHAPure;
+use ::starkyx::machine::hash::sha::sha512::SHA512;
+use itertools::Itertools;
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::hash::common::{and_arr, not_arr, xor2_arr, xor3_arr};
+use crate::frontend::vars::{BoolVariable, ByteVariable, BytesVariable, CircuitVariable};
+
 pub mod curta;
+pub mod hmac;
 pub mod pad;
+
+/// Implements SHA512 implementation for CircuitBuilder
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    fn const_be_bits_u64(&mut self, u: u64) -> [BoolVariable; 64] {
+        u.to_be_bytes()
+            .iter()
+            .flat_map(|b| self.constant::<ByteVariable>(*b).as_be_bits().to_vec())
+            .collect::<Vec<BoolVariable>>()
+            .try_into()
+            .unwrap()
+    }
+
+    fn get_sha512_initial_hash(&mut self) -> [[BoolVariable; 64]; 8] {
+        SHA512::INITIAL_HASH.map(|x| self.const_be_bits_u64(x))
+    }
+
+    fn get_sha512_round_constants(&mut self) -> [[BoolVariable; 64]; 80] {
+        SHA512::ROUND_CONSTANTS.map(|x| self.const_be_bits_u64(x))
+    }
+
+    fn process_sha512_padded_message(&mut self, msg_input: &[ByteVariable]) -> Vec<BoolVariable> {
+        let msg_input_bits = msg_input
+            .iter()
+            .flat_map(|b| b.as_be_bits().to_vec())
+            .collect_vec();
+        let mut sha512_hash = self.get_sha512_initial_hash();
+        let round_constants = self.get_sha512_round_constants();
+
+        // Process the input with 1024 bit chunks aka 128 byte chunks
+        for chunk in msg_input_bits.chunks_exact(1024) {
+            // The message schedule has 80 words of 64 bits, the first 16 being the chunk.
+            let mut w: Vec<[BoolVariable; 64]> = chunk
+                .chunks_exact(64)
+                .map(|word| word.try_into().unwrap())
+                .collect();
+            let zero_word = [self._false(); 64];
+            w.resize(80, zero_word);
+
+            for i in 16..80 {
+                let s0 = xor3_arr(
+                    self._right_rotate(w[i - 15], 1),
+                    self._right_rotate(w[i - 15], 8),
+                    self._shr(w[i - 15], 7),
+                    self,
+                );
+
+                let s1 = xor3_arr(
+                    self._right_rotate(w[i - 2], 19),
+                    self._right_rotate(w[i - 2], 61),
+                    self._shr(w[i - 2], 6),
+                    self,
+                );
+
+                w[i] = self.add_many_arr(&[w[i - 16], s0, w[i - 7], s1]);
+            }
+            let mut a = sha512_hash[0];
+            let mut b = sha512_hash[1];
+            let mut c = sha512_hash[2];
+            let mut d = sha512_hash[3];
+            let mut e = sha512_hash[4];
+            let mut f = sha512_hash[5];
+            let mut g = sha512_hash[6];
+            let mut h = sha512_hash[7];
+
+            for i in 0..80 {
+                let sum1 = xor3_arr(
+                    self._right_rotate(e, 14),
+                    self._right_rotate(e, 18),
+                    self._right_rotate(e, 41),
+                    self,
+                );
+                let ch = xor2_arr(
+                    and_arr(e, f, self),
+                    and_arr(not_arr(e, self), g, self),
+                    self,
+                );
+                let final_temp1 = self.add_many_arr(&[h, sum1, ch, round_constants[i], w[i]]);
+
+                let sum0 = xor3_arr(
+                    self._right_rotate(a, 28),
+                    self._right_rotate(a, 34),
+                    self._right_rotate(a, 39),
+                    self,
+                );
+
+                let maj = xor3_arr(
+                    and_arr(a, b, self),
+                    and_arr(a, c, self),
+                    and_arr(b, c, self),
+                    self,
+                );
+                let final_temp2 = self.add_arr(sum0, maj);
+
+                h = g;
+                g = f;
+                f = e;
+                e = self.add_arr(d, final_temp1);
+                d = c;
+                c = b;
+                b = a;
+                a = self.add_arr(final_temp1, final_temp2);
+            }
+
+            sha512_hash = self.zip_add(sha512_hash, [a, b, c, d, e, f, g, h]);
+        }
+
+        sha512_hash.iter().flat_map(|x| x.to_vec()).collect()
+    }
+
+    /// Computes the SHA-512 digest of `input` with the compression function in the circuit,
+    /// without the curta accelerator.
+    pub fn sha512(&mut self, input: &[ByteVariable]) -> BytesVariable<64> {
+        let padded = self.pad_message_sha512(input);
+        let bool_digest: Vec<BoolVariable> = self.process_sha512_padded_message(&padded);
+        assert_eq!(bool_digest.len(), 512);
+        // Ok to use `from_variables_unsafe` as we know `process_sha512_padded_message` returns 512
+        // bits
+        BytesVariable::<64>::from_variables_unsafe(
+            &bool_digest.iter().map(|b| b.variable).collect_vec(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::DefaultParameters;
+    use crate::utils::hash::sha512;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_sha512() {
+        // An empty message and a message whose padding spills into a second chunk.
+        let messages = [vec![], (0..120).map(|i| i as u8).collect::<Vec<_>>()];
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        for message in messages.iter() {
+            let message_variables = message
+                .iter()
+                .map(|b| builder.constant::<ByteVariable>(*b))
+                .collect::<Vec<_>>();
+            let result = builder.sha512(&message_variables);
+            let expected_digest = builder.constant::<BytesVariable<64>>(sha512(message));
+            builder.assert_is_equal(result, expected_digest);
+        }
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
@@ -133,4 +133,74 @@ mod tests {
         assert_eq!(byte_plus_two, 7u8);
         assert_eq!(c_plus_3, 4u8);
     }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct DivRem;
+
+    impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for DivRem {
+        fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let a = input_stream.read_value::<U32Variable>();
+            let b = input_stream.read_value::<U32Variable>();
+
+            output_stream.write_named_value::<U32Variable>("remainder", a % b);
+            output_stream.write_value::<BoolVariable>(a % b == 0);
+            output_stream.write_named_value::<U32Variable>("quotient", a / b);
+        }
+    }
+
+    #[test]
+    fn test_hint_named_fields() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U32Variable>();
+        let b = builder.read::<U32Variable>();
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        input_stream.write(&b);
+        let output_stream = builder.hint(input_stream, DivRem);
+        let quotient = output_stream.read_named::<U32Variable>(&mut builder, "quotient");
+        let divides = output_stream.read::<BoolVariable>(&mut builder);
+        let remainder = output_stream.read_named::<U32Variable>(&mut builder, "remainder");
+        builder.write(quotient);
+        builder.write(remainder);
+        builder.write(divides);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<U32Variable>(17);
+        input.write::<U32Variable>(5);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        assert_eq!(output.read::<U32Variable>(), 3);
+        assert_eq!(output.read::<U32Variable>(), 2);
+        assert!(!output.read::<BoolVariable>());
+    }
+
+    #[test]
+    #[should_panic(expected = "Hint output field `remainder` was not read")]
+    fn test_hint_unread_named_field() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U32Variable>();
+        let b = builder.read::<U32Variable>();
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        input_stream.write(&b);
+        let output_stream = builder.hint(input_stream, DivRem);
+        let quotient = output_stream.read_named::<U32Variable>(&mut builder, "quotient");
+        let divides = output_stream.read::<BoolVariable>(&mut builder);
+        builder.write(quotient);
+        builder.write(divides);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<U32Variable>(17);
+        input.write::<U32Variable>(5);
+        circuit.prove(&input);
+    }
 }
@@ -81,6 +81,58 @@ mod tests {
         }
     }
 
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Double;
+
+    impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for Double {
+        fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let a = input_stream.read_value::<ByteVariable>();
+
+            output_stream.write_value::<ByteVariable>(a * 2)
+        }
+    }
+
+    #[test]
+    fn test_hint_serialization_registration_order() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<ByteVariable>();
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        let output_stream = builder.hint(input_stream, AddSome { amount: 3 });
+        let b = output_stream.read::<ByteVariable>(&mut builder);
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&b);
+        let output_stream = builder.hint(input_stream, Double);
+        let c = output_stream.read::<ByteVariable>(&mut builder);
+        builder.write(c);
+        let circuit = builder.build();
+
+        // A circuit built with one registration order loads with another one.
+        let gate_serializer = GateRegistry::new();
+        let mut hint_serializer = HintRegistry::new();
+        hint_serializer.register_hint::<AddSome>();
+        hint_serializer.register_hint::<Double>();
+        let bytes = circuit
+            .serialize(&gate_serializer, &hint_serializer)
+            .unwrap();
+
+        let mut other_hint_serializer = HintRegistry::new();
+        other_hint_serializer.register_hint::<Double>();
+        other_hint_serializer.register_hint::<AddSome>();
+        let circuit =
+            CircuitBuild::deserialize(&bytes, &gate_serializer, &other_hint_serializer).unwrap();
+
+        let mut input = circuit.input();
+        input.write::<ByteVariable>(5u8);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<ByteVariable>(), 16u8);
+    }
+
     #[test]
     fn test_hint_serialization() {
         let mut builder = DefaultBuilder::new();
@@ -0,0 +1,177 @@
+//! Checkpoints of the proofs of a mapreduce, to resume a job which failed midway.
+//!
+//! Every proof of the tree is stored in the folder given by the env variable
+//! `MAPREDUCE_CHECKPOINT_DIR` as soon as its batch completes, either as the proof itself or as the
+//! `ProofId` of the remote proof. A map proof is keyed by the digest of its circuit and of its
+//! inputs, and a reduce proof by the digest of its circuit and of the keys of its children, so a
+//! rerun of the same job finds the proofs of the previous run and only proves the missing ones.
+
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use anyhow::{anyhow, Result};
+use plonky2::field::types::PrimeField64;
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::backend::circuit::{deserialize_proof, serialize_proof, PlonkParameters};
+use crate::backend::prover::{ProofId, ProofService};
+use crate::utils::hex;
+
+/// A checkpointed proof, generated locally or by the proof service.
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum CheckpointedProof<L: PlonkParameters<D>, const D: usize> {
+    Local(ProofWithPublicInputs<L::Field, L::Config, D>),
+    Remote(ProofId),
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CheckpointedProof<L, D> {
+    /// Returns the proof, downloading it from the proof service if it is remote.
+    pub fn materialize(self) -> Result<ProofWithPublicInputs<L::Field, L::Config, D>> {
+        match self {
+            CheckpointedProof::Local(proof) => Ok(proof),
+            CheckpointedProof::Remote(proof_id) => {
+                let response = ProofService::new_from_env().get::<L, D>(proof_id)?;
+                let result = response
+                    .result
+                    .ok_or_else(|| anyhow!("remote proof {:?} has no result", proof_id))?;
+                Ok(result.as_proof_and_output().0)
+            }
+        }
+    }
+}
+
+/// A folder of checkpointed proofs.
+#[derive(Debug, Clone)]
+pub struct MapReduceCheckpoint {
+    dir: PathBuf,
+}
+
+impl MapReduceCheckpoint {
+    /// Creates a checkpoint in the folder `dir`.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Creates a checkpoint in the folder given by `MAPREDUCE_CHECKPOINT_DIR`. Returns `None` if
+    /// checkpointing is not configured.
+    pub fn from_env() -> Option<Self> {
+        env::var("MAPREDUCE_CHECKPOINT_DIR").ok().map(Self::new)
+    }
+
+    /// The key of the proof of the map circuit `circuit_id` over the input `elements`.
+    pub fn leaf_key<F: PrimeField64>(circuit_id: &str, elements: &[F]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(circuit_id.as_bytes());
+        for element in elements {
+            hasher.update(element.to_canonical_u64().to_le_bytes());
+        }
+        hex!(hasher.finalize().to_vec())
+    }
+
+    /// The key of the proof of the reduce circuit `circuit_id` over the children with the given
+    /// keys.
+    pub fn node_key(circuit_id: &str, child_keys: &[String]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(circuit_id.as_bytes());
+        for key in child_keys {
+            hasher.update(key.as_bytes());
+        }
+        hex!(hasher.finalize().to_vec())
+    }
+
+    fn proof_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.proof", key))
+    }
+
+    fn remote_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.id", key))
+    }
+
+    /// Returns the checkpointed proof with the given key, if any.
+    pub fn load<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        key: &str,
+    ) -> Result<Option<CheckpointedProof<L, D>>> {
+        if let Ok(bytes) = fs::read(self.proof_path(key)) {
+            return Ok(Some(CheckpointedProof::Local(deserialize_proof(
+                &bytes, None,
+            )?)));
+        }
+        if let Ok(id) = fs::read_to_string(self.remote_path(key)) {
+            return Ok(Some(CheckpointedProof::Remote(ProofId(Uuid::parse_str(
+                id.trim(),
+            )?))));
+        }
+        Ok(None)
+    }
+
+    /// Stores a proof under the given key.
+    pub fn store<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        key: &str,
+        proof: &CheckpointedProof<L, D>,
+    ) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        match proof {
+            CheckpointedProof::Local(proof) => {
+                fs::write(self.proof_path(key), serialize_proof(proof)?)?
+            }
+            CheckpointedProof::Remote(proof_id) => {
+                fs::write(self.remote_path(key), proof_id.0.to_string())?
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Field;
+
+    use super::*;
+    use crate::backend::circuit::DefaultParameters;
+    use crate::prelude::GoldilocksField;
+    use crate::utils::test_circuits::{add_input, build_add_circuit};
+
+    const D: usize = 2;
+    type L = DefaultParameters;
+
+    #[test]
+    fn test_mapreduce_checkpoint() {
+        let dir = env::temp_dir().join("plonky2x-mapreduce-checkpoint");
+        let _ = fs::remove_dir_all(&dir);
+        let checkpoint = MapReduceCheckpoint::new(&dir);
+
+        let circuit = build_add_circuit();
+        let (proof, _) = circuit.prove(&add_input(&circuit, 3, 3));
+
+        let leaf = MapReduceCheckpoint::leaf_key(&circuit.id(), &[GoldilocksField::ONE]);
+        let other_leaf = MapReduceCheckpoint::leaf_key(&circuit.id(), &[GoldilocksField::TWO]);
+        assert_ne!(leaf, other_leaf);
+        let node = MapReduceCheckpoint::node_key(&circuit.id(), &[leaf.clone(), other_leaf]);
+        assert!(checkpoint.load::<L, D>(&leaf).unwrap().is_none());
+
+        checkpoint
+            .store::<L, D>(&leaf, &CheckpointedProof::Local(proof.clone()))
+            .unwrap();
+        let proof_id = ProofId(Uuid::from_u128(0x2a));
+        checkpoint
+            .store::<L, D>(&node, &CheckpointedProof::Remote(proof_id))
+            .unwrap();
+        match checkpoint.load::<L, D>(&leaf).unwrap() {
+            Some(CheckpointedProof::Local(loaded)) => assert_eq!(loaded, proof),
+            _ => panic!("expected a local proof"),
+        }
+        match checkpoint.load::<L, D>(&node).unwrap() {
+            Some(CheckpointedProof::Remote(loaded)) => assert_eq!(loaded, proof_id),
+            _ => panic!("expected a remote proof"),
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,19 +1,103 @@
 use core::marker::PhantomData;
+use std::env;
+use std::path::Path;
 
+use anyhow::{Context, Result};
 use array_macro::array;
-use log::debug;
+use log::{debug, info};
+use plonky2::field::types::PrimeField64;
+use plonky2::hash::hash_types::HashOut;
 use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
 use plonky2::iop::target::Target;
 use plonky2::iop::witness::{PartitionWitness, WitnessWrite};
 use plonky2::plonk::circuit_data::CommonCircuitData;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
-use plonky2::plonk::proof::ProofWithPublicInputsTarget;
+use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
 use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
 
-use super::{MapReduceInputVariable, MapReduceInputVariableValue};
-use crate::backend::circuit::{CircuitSerializer, PublicInput};
+use super::checkpoint::{CheckpointedProof, MapReduceCheckpoint};
+use super::{
+    MapReduceFoldStateVariable, MapReduceFoldStateVariableValue, MapReduceInputVariable,
+    MapReduceInputVariableValue,
+};
+use crate::backend::circuit::{
+    write_proof_archive, CircuitBuild, CircuitSerializer, DummyProofCache, ProofCompression,
+    PublicInput,
+};
 use crate::backend::prover::{EnvProver, ProverOutputs};
-use crate::prelude::{CircuitVariable, PlonkParameters};
+use crate::prelude::{CircuitVariable, PlonkParameters, U32Variable, Variable};
+
+/// Writes the proofs of a layer to one archive per circuit in the folder given by the env variable
+/// `MAPREDUCE_PROOF_DIR`, compressed as configured by `PROOF_COMPRESSION`. Proofs generated
+/// remotely are left on the proof service. Returns an error if the compression is malformed or the
+/// archive cannot be written.
+fn spill_proofs<L: PlonkParameters<D>, const D: usize>(
+    circuit_id: &str,
+    outputs: &ProverOutputs<L, D>,
+) -> Result<()> {
+    let (Ok(dir), ProverOutputs::Local(proofs, _)) = (env::var("MAPREDUCE_PROOF_DIR"), outputs)
+    else {
+        return Ok(());
+    };
+    let compression = ProofCompression::from_env()?;
+    let path = Path::new(&dir).join(format!("{}.proofs", circuit_id));
+    write_proof_archive(&path, proofs, compression)
+        .with_context(|| format!("failed to spill proofs to {}", path.display()))?;
+    debug!("spilled {} proofs to {}", proofs.len(), path.display());
+    Ok(())
+}
+
+/// The number of missing proofs of a layer that are proven between two checkpoints.
+const CHECKPOINT_BATCH_SIZE: usize = 256;
+
+/// Proves the `keys.len()` proofs of a layer of the circuit `circuit_id` which are not in the
+/// checkpoint, building the input of the `i`-th proof with `input_fn`. The proofs are proven in
+/// batches and stored in the checkpoint after each batch.
+fn prove_with_checkpoint<L: PlonkParameters<D>, S: CircuitSerializer, const D: usize>(
+    prover: &EnvProver,
+    checkpoint: &MapReduceCheckpoint,
+    circuit_id: &str,
+    keys: &[String],
+    mut input_fn: impl FnMut(usize) -> PublicInput<L, D>,
+) -> Result<Vec<CheckpointedProof<L, D>>>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+{
+    let mut proofs = keys
+        .iter()
+        .map(|key| checkpoint.load::<L, D>(key))
+        .collect::<Result<Vec<_>>>()?;
+    let missing = (0..keys.len())
+        .filter(|i| proofs[*i].is_none())
+        .collect::<Vec<_>>();
+    info!(
+        "resuming circuit {}: {} of {} proofs checkpointed",
+        circuit_id,
+        keys.len() - missing.len(),
+        keys.len()
+    );
+
+    for batch in missing.chunks(CHECKPOINT_BATCH_SIZE) {
+        let inputs = batch.iter().map(|i| input_fn(*i)).collect::<Vec<_>>();
+        let outputs = prover.batch_prove::<L, S, D>(circuit_id, &inputs)?;
+        spill_proofs(circuit_id, &outputs)?;
+        let outputs = match outputs {
+            ProverOutputs::Local(batch_proofs, _) => batch_proofs
+                .into_iter()
+                .map(CheckpointedProof::Local)
+                .collect(),
+            ProverOutputs::Remote(proof_ids) => proof_ids
+                .into_iter()
+                .map(CheckpointedProof::Remote)
+                .collect::<Vec<_>>(),
+        };
+        for (i, proof) in batch.iter().zip(outputs) {
+            checkpoint.store(&keys[*i], &proof)?;
+            proofs[*i] = Some(proof);
+        }
+    }
+    Ok(proofs.into_iter().map(Option::unwrap).collect())
+}
 
 #[derive(Debug, Clone)]
 pub struct MapReduceGenerator<L, Ctx, Input, Output, Serializer, const B: usize, const D: usize>
@@ -32,6 +116,9 @@ where
     /// The identifiers for the compiled reduce circuits.
     pub reduce_circuit_ids: Vec<String>,
 
+    /// The number of proofs reduced by each reduce circuit.
+    pub arity: usize,
+
     /// The global context for all circuits.
     pub ctx: Ctx,
 
@@ -59,6 +146,84 @@ where
     pub fn id() -> String {
         "MapReduceGenerator".to_string()
     }
+
+    /// Generates the final proof like `run_once`, reusing the proofs stored in the checkpoint by
+    /// a previous run and storing the new ones.
+    fn run_with_checkpoint(
+        &self,
+        checkpoint: &MapReduceCheckpoint,
+        ctx_value: Ctx::ValueType<L::Field>,
+    ) -> Result<ProofWithPublicInputs<L::Field, L::Config, D>> {
+        let prover = EnvProver::new();
+
+        // Prove the map layer, with each proof keyed by its inputs.
+        let map_inputs = (0..self.inputs.len() / B)
+            .map(|i| {
+                let mut map_input = PublicInput::Elements(Vec::new());
+                map_input.write::<MapReduceInputVariable<Ctx, Input, B>>(
+                    MapReduceInputVariableValue {
+                        ctx: ctx_value.clone(),
+                        inputs: self.inputs[i * B..(i + 1) * B].to_vec(),
+                    },
+                );
+                map_input
+            })
+            .collect::<Vec<_>>();
+        let mut keys = map_inputs
+            .iter()
+            .map(|input| match input {
+                PublicInput::Elements(elements) => {
+                    MapReduceCheckpoint::leaf_key(&self.map_circuit_id, elements)
+                }
+                _ => unreachable!("map inputs are elements"),
+            })
+            .collect::<Vec<_>>();
+        let mut proofs = prove_with_checkpoint::<L, Serializer, D>(
+            &prover,
+            checkpoint,
+            &self.map_circuit_id,
+            &keys,
+            |i| map_inputs[i].clone(),
+        )?;
+
+        // Prove each reduce layer, with each proof keyed by its children.
+        for circuit_id in self.reduce_circuit_ids.iter() {
+            keys = keys
+                .chunks(self.arity)
+                .map(|children| MapReduceCheckpoint::node_key(circuit_id, children))
+                .collect();
+            let children = proofs;
+            proofs = prove_with_checkpoint::<L, Serializer, D>(
+                &prover,
+                checkpoint,
+                circuit_id,
+                &keys,
+                |i| {
+                    let children = &children[i * self.arity..(i + 1) * self.arity];
+                    let proof_ids = children
+                        .iter()
+                        .map(|child| match child {
+                            CheckpointedProof::Remote(proof_id) => Some(*proof_id),
+                            CheckpointedProof::Local(_) => None,
+                        })
+                        .collect::<Option<Vec<_>>>();
+                    match proof_ids {
+                        Some(proof_ids) => PublicInput::RemoteRecursiveProofs(proof_ids),
+                        None => {
+                            let mut reduce_input =
+                                PublicInput::RecursiveProofs(Vec::new(), Vec::new());
+                            for child in children {
+                                reduce_input.proof_write(child.clone().materialize().unwrap());
+                            }
+                            reduce_input
+                        }
+                    }
+                },
+            )?;
+        }
+
+        proofs.remove(0).materialize()
+    }
 }
 
 impl<L, Ctx, Input, Output, Serializer, const B: usize, const D: usize> SimpleGenerator<L::Field, D>
@@ -88,11 +253,18 @@ where
         witness: &PartitionWitness<L::Field>,
         out_buffer: &mut GeneratedValues<L::Field>,
     ) {
+        // Resume from the checkpoint if one is configured.
+        let ctx_value = self.ctx.get(witness);
+        if let Some(checkpoint) = MapReduceCheckpoint::from_env() {
+            let proof = self.run_with_checkpoint(&checkpoint, ctx_value).unwrap();
+            out_buffer.set_proof_with_pis_target(&self.proof, &proof);
+            return;
+        }
+
         // Create the prover and the async runtime.
         let prover = EnvProver::new();
 
         // Calculate the inputs to the map.
-        let ctx_value = self.ctx.get(witness);
         let map_input_values = &self.inputs;
         let mut map_inputs = Vec::new();
         for i in 0..map_input_values.len() / B {
@@ -109,30 +281,30 @@ where
         let mut outputs = prover
             .batch_prove::<L, Serializer, D>(&self.map_circuit_id, &map_inputs)
             .unwrap();
+        spill_proofs(&self.map_circuit_id, &outputs).unwrap();
 
         // Process each reduce layer.
-        let nb_reduce_layers = ((self.inputs.len() / B) as f64).log2().ceil() as usize;
-        for i in 0..nb_reduce_layers {
+        for i in 0..self.reduce_circuit_ids.len() {
             // Calculate the inputs to the reduce layer.
             debug!("reduce time");
-            let nb_proofs = (self.inputs.len() / B) / (2usize.pow((i + 1) as u32));
+            let nb_proofs = (self.inputs.len() / B) / self.arity.pow((i + 1) as u32);
             let mut reduce_inputs = Vec::new();
             debug!("nb_proofs {}", nb_proofs);
             match outputs {
                 ProverOutputs::Local(proofs, _) => {
                     for j in 0..nb_proofs {
                         let mut reduce_input = PublicInput::RecursiveProofs(Vec::new(), Vec::new());
-                        reduce_input.proof_write(proofs[j * 2].clone());
-                        reduce_input.proof_write(proofs[j * 2 + 1].clone());
+                        for k in 0..self.arity {
+                            reduce_input.proof_write(proofs[j * self.arity + k].clone());
+                        }
                         reduce_inputs.push(reduce_input);
                     }
                 }
                 ProverOutputs::Remote(proof_ids) => {
                     for j in 0..nb_proofs {
-                        let reduce_input = PublicInput::<L, D>::RemoteRecursiveProofs(vec![
-                            proof_ids[j * 2],
-                            proof_ids[j * 2 + 1],
-                        ]);
+                        let reduce_input = PublicInput::<L, D>::RemoteRecursiveProofs(
+                            proof_ids[j * self.arity..(j + 1) * self.arity].to_vec(),
+                        );
                         reduce_inputs.push(reduce_input);
                     }
                 }
@@ -143,6 +315,7 @@ where
             outputs = prover
                 .batch_prove::<L, Serializer, D>(&self.reduce_circuit_ids[i], &reduce_inputs)
                 .unwrap();
+            spill_proofs(&self.reduce_circuit_ids[i], &outputs).unwrap();
         }
 
         // Set the proof target with the final proof.
@@ -161,6 +334,7 @@ where
             dst.write_usize(self.reduce_circuit_ids[i].len())?;
             dst.write_all(self.reduce_circuit_ids[i].as_bytes())?;
         }
+        dst.write_usize(self.arity)?;
 
         // Write context.
         dst.write_target_vec(&self.ctx.targets())?;
@@ -190,6 +364,7 @@ where
             src.read_exact(&mut reduce_circuit_id)?;
             reduce_circuit_ids.push(String::from_utf8(reduce_circuit_id).unwrap());
         }
+        let arity = src.read_usize()?;
 
         // Read context.
         let ctx = Ctx::from_targets(&src.read_target_vec()?);
@@ -208,6 +383,7 @@ where
         Ok(Self {
             map_circuit_id: String::from_utf8(map_circuit_id).unwrap(),
             reduce_circuit_ids,
+            arity,
             ctx,
             inputs,
             proof,
@@ -320,6 +496,7 @@ where
         let mut outputs = prover
             .batch_prove::<L, Serializer, D>(&self.map_circuit_id, &map_inputs)
             .unwrap();
+        spill_proofs(&self.map_circuit_id, &outputs).unwrap();
 
         // Process each reduce layer.
         let nb_reduce_layers = ((self.inputs.len() / B) as f64).log2().ceil() as usize;
@@ -354,6 +531,7 @@ where
             outputs = prover
                 .batch_prove::<L, Serializer, D>(&self.reduce_circuit_ids[i], &reduce_inputs)
                 .unwrap();
+            spill_proofs(&self.reduce_circuit_ids[i], &outputs).unwrap();
         }
 
         // Set the proof target with the final proof.
@@ -425,3 +603,408 @@ where
         })
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct MapReduceWithLenGenerator<
+    L,
+    Ctx,
+    Input,
+    Output,
+    Serializer,
+    const B: usize,
+    const D: usize,
+> where
+    L: PlonkParameters<D>,
+    <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    Ctx: CircuitVariable,
+    Input: CircuitVariable,
+    Output: CircuitVariable,
+    Serializer: CircuitSerializer,
+{
+    /// The identifier for the compiled map circuit.
+    pub map_circuit_id: String,
+
+    /// The identifiers for the compiled reduce circuits.
+    pub reduce_circuit_ids: Vec<String>,
+
+    /// The global context for all circuits.
+    pub ctx: Ctx,
+
+    /// The constant inputs to the map circuit.
+    pub inputs: Vec<Input::ValueType<L::Field>>,
+
+    /// The number of live inputs.
+    pub len: Variable,
+
+    /// The proof target for the final circuit proof.
+    pub proof: ProofWithPublicInputsTarget<D>,
+
+    /// Phantom data.
+    pub _phantom: PhantomData<(L, Output, Serializer)>,
+}
+
+impl<L, Ctx, Input, Output, Serializer, const B: usize, const D: usize>
+    MapReduceWithLenGenerator<L, Ctx, Input, Output, Serializer, B, D>
+where
+    L: PlonkParameters<D>,
+    <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    Ctx: CircuitVariable,
+    Input: CircuitVariable,
+    Output: CircuitVariable,
+    Serializer: CircuitSerializer,
+{
+    pub fn id() -> String {
+        "MapReduceWithLenGenerator".to_string()
+    }
+
+    /// Returns the dummy proof standing in for the subtrees without live inputs of the circuit
+    /// `circuit_id`, which was cached when the reduce circuits were built.
+    fn dummy_proof(&self, circuit_id: &str) -> ProofWithPublicInputs<L::Field, L::Config, D> {
+        let gate_serializer = Serializer::gate_registry::<L, D>();
+        let generator_serializer = Serializer::generator_registry::<L, D>();
+        let circuit = CircuitBuild::<L, D>::load(
+            &format!("./build/{}.circuit", circuit_id),
+            &gate_serializer,
+            &generator_serializer,
+        )
+        .unwrap();
+        DummyProofCache::new("./build")
+            .dummy_proof::<L, D>(&circuit.data.common, &gate_serializer)
+            .unwrap()
+            .proof
+    }
+}
+
+impl<L, Ctx, Input, Output, Serializer, const B: usize, const D: usize> SimpleGenerator<L::Field, D>
+    for MapReduceWithLenGenerator<L, Ctx, Input, Output, Serializer, B, D>
+where
+    L: PlonkParameters<D>,
+    <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    Ctx: CircuitVariable,
+    Input: CircuitVariable,
+    Output: CircuitVariable,
+    Serializer: CircuitSerializer,
+    <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        let mut targets = Vec::new();
+        targets.extend(self.ctx.targets());
+        targets.extend(self.len.targets());
+        targets
+    }
+
+    fn run_once(
+        &self,
+        witness: &PartitionWitness<L::Field>,
+        out_buffer: &mut GeneratedValues<L::Field>,
+    ) {
+        // Create the prover and the async runtime.
+        let prover = EnvProver::new();
+
+        // Calculate the inputs to the map for the live leaves only. The length is checked in the
+        // root circuit to be a multiple of `B` within the inputs.
+        let ctx_value = self.ctx.get(witness);
+        let len = self.len.get(witness).as_canonical_u64() as usize;
+        let map_input_values = &self.inputs;
+        let mut map_inputs = Vec::new();
+        for i in 0..len.div_ceil(B).min(map_input_values.len() / B) {
+            let mut map_input = PublicInput::Elements(Vec::new());
+            let input = array![j => map_input_values[i * B + j].clone(); B];
+            map_input.write::<MapReduceInputVariable<Ctx, Input, B>>(MapReduceInputVariableValue {
+                ctx: ctx_value.clone(),
+                inputs: input.to_vec(),
+            });
+            map_inputs.push(map_input)
+        }
+
+        // Generate the proofs for the map layer.
+        let outputs = prover
+            .batch_prove::<L, Serializer, D>(&self.map_circuit_id, &map_inputs)
+            .unwrap();
+        spill_proofs(&self.map_circuit_id, &outputs).unwrap();
+        let (mut proofs, _) = outputs.materialize().unwrap();
+
+        // Process each reduce layer, pairing the last live proof with a dummy proof of the child
+        // circuit if it has no sibling. The proofs are materialized so that the dummy proofs can
+        // be sent along with the live ones.
+        for i in 0..self.reduce_circuit_ids.len() {
+            let child_circuit_id = if i == 0 {
+                &self.map_circuit_id
+            } else {
+                &self.reduce_circuit_ids[i - 1]
+            };
+            let dummy_proof = self.dummy_proof(child_circuit_id);
+            let nb_proofs = proofs.len().div_ceil(2);
+            debug!("nb_proofs {}", nb_proofs);
+            let mut reduce_inputs = Vec::new();
+            for j in 0..nb_proofs {
+                let mut reduce_input = PublicInput::RecursiveProofs(Vec::new(), Vec::new());
+                reduce_input.proof_write(proofs[j * 2].clone());
+                reduce_input.proof_write(
+                    proofs
+                        .get(j * 2 + 1)
+                        .cloned()
+                        .unwrap_or_else(|| dummy_proof.clone()),
+                );
+                reduce_inputs.push(reduce_input);
+            }
+
+            // Generate the proofs for the reduce layer and update the proofs buffer.
+            let outputs = prover
+                .batch_prove::<L, Serializer, D>(&self.reduce_circuit_ids[i], &reduce_inputs)
+                .unwrap();
+            spill_proofs(&self.reduce_circuit_ids[i], &outputs).unwrap();
+            (proofs, _) = outputs.materialize().unwrap();
+        }
+
+        // Set the proof target with the final proof.
+        out_buffer.set_proof_with_pis_target(&self.proof, &proofs[0]);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _: &CommonCircuitData<L::Field, D>) -> IoResult<()> {
+        // Write map circuit.
+        dst.write_usize(self.map_circuit_id.len())?;
+        dst.write_all(self.map_circuit_id.as_bytes())?;
+
+        // Write vector of reduce circuits.
+        dst.write_usize(self.reduce_circuit_ids.len())?;
+        for i in 0..self.reduce_circuit_ids.len() {
+            dst.write_usize(self.reduce_circuit_ids[i].len())?;
+            dst.write_all(self.reduce_circuit_ids[i].as_bytes())?;
+        }
+
+        // Write context.
+        dst.write_target_vec(&self.ctx.targets())?;
+
+        // Write vector of input values.
+        dst.write_usize(self.inputs.len())?;
+        for i in 0..self.inputs.len() {
+            dst.write_field_vec::<L::Field>(&Input::elements::<L::Field>(self.inputs[i].clone()))?;
+        }
+
+        // Write length and proof target.
+        dst.write_target(self.len.0)?;
+        dst.write_target_proof_with_public_inputs(&self.proof)
+    }
+
+    fn deserialize(src: &mut Buffer, _: &CommonCircuitData<L::Field, D>) -> IoResult<Self> {
+        // Read map circuit.
+        let map_circuit_id_length = src.read_usize()?;
+        let mut map_circuit_id = vec![0u8; map_circuit_id_length];
+        src.read_exact(&mut map_circuit_id)?;
+
+        // Read vector of reduce circuits.
+        let mut reduce_circuit_ids = Vec::new();
+        let reduce_circuit_ids_len = src.read_usize()?;
+        for _ in 0..reduce_circuit_ids_len {
+            let reduce_circuit_id_length = src.read_usize()?;
+            let mut reduce_circuit_id = vec![0u8; reduce_circuit_id_length];
+            src.read_exact(&mut reduce_circuit_id)?;
+            reduce_circuit_ids.push(String::from_utf8(reduce_circuit_id).unwrap());
+        }
+
+        // Read context.
+        let ctx = Ctx::from_targets(&src.read_target_vec()?);
+
+        // Read vector of input values.
+        let mut inputs = Vec::new();
+        let inputs_len = src.read_usize()?;
+        for _ in 0..inputs_len {
+            let input_elements: Vec<L::Field> = src.read_field_vec(Input::nb_elements())?;
+            inputs.push(Input::from_elements::<L::Field>(&input_elements));
+        }
+
+        // Read length and proof.
+        let len = Variable(src.read_target()?);
+        let proof = src.read_target_proof_with_public_inputs()?;
+
+        Ok(Self {
+            map_circuit_id: String::from_utf8(map_circuit_id).unwrap(),
+            reduce_circuit_ids,
+            ctx,
+            inputs,
+            len,
+            proof,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MapReduceFoldGenerator<L, Ctx, Input, State, Serializer, const B: usize, const D: usize>
+where
+    L: PlonkParameters<D>,
+    <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    Ctx: CircuitVariable,
+    Input: CircuitVariable,
+    State: CircuitVariable,
+    Serializer: CircuitSerializer,
+{
+    /// The identifier for the compiled step circuit.
+    pub step_circuit_id: String,
+
+    /// The global context for all circuits.
+    pub ctx: Ctx,
+
+    /// The state before the first batch.
+    pub initial: State,
+
+    /// The constant inputs to the step circuit.
+    pub inputs: Vec<Input::ValueType<L::Field>>,
+
+    /// The proof target for the final circuit proof.
+    pub proof: ProofWithPublicInputsTarget<D>,
+
+    /// Phantom data.
+    pub _phantom: PhantomData<(L, Serializer)>,
+}
+
+impl<L, Ctx, Input, State, Serializer, const B: usize, const D: usize>
+    MapReduceFoldGenerator<L, Ctx, Input, State, Serializer, B, D>
+where
+    L: PlonkParameters<D>,
+    <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    Ctx: CircuitVariable,
+    Input: CircuitVariable,
+    State: CircuitVariable,
+    Serializer: CircuitSerializer,
+{
+    pub fn id() -> String {
+        "MapReduceFoldGenerator".to_string()
+    }
+}
+
+impl<L, Ctx, Input, State, Serializer, const B: usize, const D: usize> SimpleGenerator<L::Field, D>
+    for MapReduceFoldGenerator<L, Ctx, Input, State, Serializer, B, D>
+where
+    L: PlonkParameters<D>,
+    <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    Ctx: CircuitVariable,
+    Input: CircuitVariable,
+    State: CircuitVariable,
+    Serializer: CircuitSerializer,
+    <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        let mut targets = Vec::new();
+        targets.extend(self.ctx.targets());
+        targets.extend(self.initial.targets());
+        targets
+    }
+
+    fn run_once(
+        &self,
+        witness: &PartitionWitness<L::Field>,
+        out_buffer: &mut GeneratedValues<L::Field>,
+    ) {
+        let gate_serializer = Serializer::gate_registry::<L, D>();
+        let generator_serializer = Serializer::generator_registry::<L, D>();
+        let circuit = CircuitBuild::<L, D>::load(
+            &format!("./build/{}.circuit", self.step_circuit_id),
+            &gate_serializer,
+            &generator_serializer,
+        )
+        .unwrap();
+
+        // The first step verifies the base proof of the cyclic circuit in place of a previous
+        // proof.
+        let mut proof = DummyProofCache::new("./build")
+            .cyclic_base_proof::<L, D>(
+                &circuit.data.common,
+                &circuit.data.verifier_only,
+                &gate_serializer,
+            )
+            .unwrap();
+
+        // Prove each batch in order, threading the state through the proofs.
+        let ctx_value = self.ctx.get(witness);
+        let initial_value = self.initial.get(witness);
+        let mut prev = MapReduceFoldStateVariableValue::<State, L::Field> {
+            state: initial_value.clone(),
+            acc: HashOut::ZERO,
+        };
+        for i in 0..self.inputs.len() / B {
+            debug!("proving fold step {}", i);
+            let mut input = circuit.input();
+            input.write::<MapReduceInputVariable<Ctx, Input, B>>(MapReduceInputVariableValue {
+                ctx: ctx_value.clone(),
+                inputs: self.inputs[i * B..(i + 1) * B].to_vec(),
+            });
+            input.write::<MapReduceFoldStateVariable<State>>(prev);
+            input.write::<State>(initial_value.clone());
+            input.write::<U32Variable>(i as u32);
+            input.data_write(circuit.data.verifier_data());
+            input.proof_write(proof);
+            let (step_proof, mut output) = circuit.prove(&input);
+            prev = output.read::<MapReduceFoldStateVariable<State>>();
+            proof = step_proof;
+        }
+
+        // Set the proof target with the final proof.
+        out_buffer.set_proof_with_pis_target(&self.proof, &proof);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _: &CommonCircuitData<L::Field, D>) -> IoResult<()> {
+        // Write step circuit.
+        dst.write_usize(self.step_circuit_id.len())?;
+        dst.write_all(self.step_circuit_id.as_bytes())?;
+
+        // Write context and initial state.
+        dst.write_target_vec(&self.ctx.targets())?;
+        dst.write_target_vec(&self.initial.targets())?;
+
+        // Write vector of input values.
+        dst.write_usize(self.inputs.len())?;
+        for i in 0..self.inputs.len() {
+            dst.write_field_vec::<L::Field>(&Input::elements::<L::Field>(self.inputs[i].clone()))?;
+        }
+
+        // Write proof target.
+        dst.write_target_proof_with_public_inputs(&self.proof)
+    }
+
+    fn deserialize(src: &mut Buffer, _: &CommonCircuitData<L::Field, D>) -> IoResult<Self> {
+        // Read step circuit.
+        let step_circuit_id_length = src.read_usize()?;
+        let mut step_circuit_id = vec![0u8; step_circuit_id_length];
+        src.read_exact(&mut step_circuit_id)?;
+
+        // Read context and initial state.
+        let ctx = Ctx::from_targets(&src.read_target_vec()?);
+        let initial = State::from_targets(&src.read_target_vec()?);
+
+        // Read vector of input values.
+        let mut inputs = Vec::new();
+        let inputs_len = src.read_usize()?;
+        for _ in 0..inputs_len {
+            let input_elements: Vec<L::Field> = src.read_field_vec(Input::nb_elements())?;
+            inputs.push(Input::from_elements::<L::Field>(&input_elements));
+        }
+
+        // Read proof.
+        let proof = src.read_target_proof_with_public_inputs()?;
+
+        Ok(Self {
+            step_circuit_id: String::from_utf8(step_circuit_id).unwrap(),
+            ctx,
+            initial,
+            inputs,
+            proof,
+            _phantom: PhantomData,
+        })
+    }
+}
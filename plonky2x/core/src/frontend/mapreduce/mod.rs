@@ -15,6 +15,7 @@
 //! Under the hood, we compute each map in a seperate proof and perform the reductions by generating
 //! a proof for each reduction between two proofs until we have a single proof.
 
+pub mod checkpoint;
 pub mod generator;
 
 use core::fmt::Debug;
@@ -22,18 +23,25 @@ use core::marker::PhantomData;
 
 use itertools::Itertools;
 use log::debug;
-use plonky2::hash::hash_types::RichField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::iop::target::BoolTarget;
+use plonky2::plonk::circuit_data::{CommonCircuitData, VerifierOnlyCircuitData};
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 use plonky2x_derive::CircuitVariable;
 
-use self::generator::MapReduceGenerator;
+use self::generator::{MapReduceFoldGenerator, MapReduceGenerator, MapReduceWithLenGenerator};
 use super::hash::poseidon::poseidon256::PoseidonHashOutVariable;
-use crate::backend::circuit::{CircuitBuild, CircuitSerializer};
+use crate::backend::circuit::{CircuitBuild, CircuitSerializer, DummyProofCache};
 use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::fold::util::common_data_for_recursion;
 use crate::frontend::mapreduce::generator::MapReduceDynamicGenerator;
 use crate::frontend::vars::CircuitVariable;
-use crate::prelude::{ArrayVariable, PlonkParameters, Variable};
-use crate::utils::poseidon::{mapreduce_merkle_tree_root, MapReducePoseidonBuilderMethods};
+use crate::prelude::{ArrayVariable, BoolVariable, PlonkParameters, U32Variable, Variable};
+use crate::utils::poseidon::{
+    mapreduce_hash_chain, mapreduce_merkle_tree_leaves, mapreduce_merkle_tree_root_with_arity,
+    mapreduce_nb_layers, MapReducePoseidonBuilderMethods,
+};
 use crate::utils::proof::ProofWithPublicInputsTargetUtils;
 
 /// The input to the map or reduce circuit.
@@ -65,6 +73,33 @@ struct MapReduceOutputVariable<Ctx: CircuitVariable, Output: CircuitVariable> {
     acc: PoseidonHashOutVariable,
 }
 
+/// The output of the map or reduce circuit of a mapreduce over a runtime number of inputs.
+#[derive(Debug, Clone, CircuitVariable)]
+struct MapReduceWithLenOutputVariable<Ctx: CircuitVariable, Output: CircuitVariable> {
+    /// The context stores a variable passed in from the root circuit.
+    ctx: Ctx,
+
+    // The output is the result of the map or reduce function over the live inputs.
+    output: Output,
+
+    // The accumulator is the hash of the live inputs up to this point.
+    acc: PoseidonHashOutVariable,
+
+    // Whether the proof covers live inputs. It is set in every map and reduce proof, and zero in
+    // the dummy proofs which stand in for subtrees without live inputs.
+    live: BoolVariable,
+}
+
+/// The state of a fold after a batch of inputs.
+#[derive(Debug, Clone, CircuitVariable)]
+struct MapReduceFoldStateVariable<State: CircuitVariable> {
+    // The state is the result of the step function over the batches up to this point.
+    state: State,
+
+    // The accumulator is the hash chain of the inputs up to this point.
+    acc: PoseidonHashOutVariable,
+}
+
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     fn build_map<Ctx, Input, Output, MapFn, const B: usize>(
         &mut self,
@@ -108,7 +143,9 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         builder.build()
     }
 
-    fn build_reduce<Ctx, Output, ReduceFn>(
+    /// Builds a reduce circuit which verifies `R` proofs of the child circuit and folds their
+    /// outputs from left to right with the reduce function.
+    fn build_reduce<Ctx, Output, ReduceFn, const R: usize>(
         &mut self,
         child_circuit: &CircuitBuild<L, D>,
         reduce_fn: &ReduceFn,
@@ -124,38 +161,268 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 
         // Read and verify the child proofs.
         let verifier_data = builder.constant_verifier_data::<L>(&child_circuit.data);
-        let proof_left = builder.proof_read(&child_circuit.data.common);
-        builder.verify_proof::<L>(&proof_left, &verifier_data, &child_circuit.data.common);
-        let proof_right = builder.proof_read(&child_circuit.data.common);
-        builder.verify_proof::<L>(&proof_right, &verifier_data, &child_circuit.data.common);
+        let mut inputs = Vec::new();
+        for _ in 0..R {
+            let proof = builder.proof_read(&child_circuit.data.common);
+            builder.verify_proof::<L>(&proof, &verifier_data, &child_circuit.data.common);
+            inputs.push(proof.read_end_from_pis::<MapReduceOutputVariable<Ctx, Output>>());
+        }
 
         // Assert that the contexts match.
-        let input_left = proof_left.read_end_from_pis::<MapReduceOutputVariable<Ctx, Output>>();
-        let input_right = proof_right.read_end_from_pis::<MapReduceOutputVariable<Ctx, Output>>();
-        builder.assert_is_equal(input_left.clone().ctx, input_right.clone().ctx);
+        let ctx = inputs[0].clone().ctx;
+        for input in inputs.iter().skip(1) {
+            builder.assert_is_equal(ctx.clone(), input.clone().ctx);
+        }
 
         // Apply the reduce function.
-        let output = reduce_fn(
+        let mut output = inputs[0].clone().output;
+        for input in inputs.iter().skip(1) {
+            output = reduce_fn(ctx.clone(), output, input.clone().output, &mut builder);
+        }
+
+        // Compute the accumulator hash for the inputs.
+        let acc_variables = inputs
+            .iter()
+            .flat_map(|input| input.acc.variables())
+            .collect_vec();
+        let acc = builder.poseidon_hash(&acc_variables);
+
+        // Write result.
+        let result = MapReduceOutputVariable { ctx, acc, output };
+        builder.proof_write(result);
+
+        builder.build()
+    }
+
+    fn build_map_with_len<Ctx, Input, Output, MapFn, const B: usize>(
+        &mut self,
+        map_fn: &MapFn,
+    ) -> CircuitBuild<L, D>
+    where
+        Ctx: CircuitVariable,
+        Input: CircuitVariable,
+        Output: CircuitVariable,
+        MapFn: Fn(Ctx, ArrayVariable<Input, B>, &mut CircuitBuilder<L, D>) -> Output,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        builder.beacon_client = self.beacon_client.clone();
+        builder.execution_client = self.execution_client.clone();
+
+        // Read the inputs and apply the map function.
+        let data = builder.read::<MapReduceInputVariable<Ctx, Input, B>>();
+        let output = map_fn(data.clone().ctx, data.clone().inputs, &mut builder);
+
+        // Compute the leaf hash for the input.
+        let input_variables = data
+            .clone()
+            .inputs
+            .as_vec()
+            .iter()
+            .flat_map(|i| i.variables())
+            .collect_vec();
+        let acc = builder.poseidon_hash(&input_variables);
+
+        // Write result.
+        let live = builder._true();
+        let result = MapReduceWithLenOutputVariable {
+            ctx: data.ctx,
+            output,
+            acc,
+            live,
+        };
+        builder.write(result);
+        builder.build()
+    }
+
+    /// Builds a reduce circuit whose right child is either a proof of the child circuit or, when
+    /// it covers no live inputs, a dummy proof of the same shape with the verifier data `dummy`.
+    /// The reduction is skipped for a dummy right child, so that it acts as the identity.
+    fn build_reduce_with_len<Ctx, Output, ReduceFn>(
+        &mut self,
+        child_circuit: &CircuitBuild<L, D>,
+        dummy: &VerifierOnlyCircuitData<L::Config, D>,
+        reduce_fn: &ReduceFn,
+    ) -> CircuitBuild<L, D>
+    where
+        Ctx: CircuitVariable,
+        Output: CircuitVariable,
+        ReduceFn: Fn(Ctx, Output, Output, &mut CircuitBuilder<L, D>) -> Output,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let common_data = &child_circuit.data.common;
+
+        // The left child is always live, while the right child is verified against the dummy
+        // verifier data when it is not.
+        let verifier_data = builder.constant_verifier_data::<L>(&child_circuit.data);
+        let dummy_verifier_data = builder
+            .constant_verifier_only_data::<L>(common_data.config.fri_config.cap_height, dummy);
+        let proof_left = builder.proof_read(common_data);
+        builder.verify_proof::<L>(&proof_left, &verifier_data, common_data);
+        let proof_right = builder.proof_read(common_data);
+        let input_left =
+            proof_left.read_end_from_pis::<MapReduceWithLenOutputVariable<Ctx, Output>>();
+        let input_right =
+            proof_right.read_end_from_pis::<MapReduceWithLenOutputVariable<Ctx, Output>>();
+        let right_live = BoolTarget::new_unsafe(input_right.live.variable.0);
+        builder.api.assert_bool(right_live);
+        builder.api.conditionally_verify_proof::<L::Config>(
+            right_live,
+            &proof_right,
+            &verifier_data,
+            &proof_right,
+            &dummy_verifier_data,
+            common_data,
+        );
+
+        // Assert that the contexts match if the right child is live.
+        let right_ctx = builder.select(
+            input_right.live,
+            input_right.clone().ctx,
+            input_left.clone().ctx,
+        );
+        builder.assert_is_equal(input_left.clone().ctx, right_ctx);
+
+        // Apply the reduce function if the right child is live.
+        let reduced = reduce_fn(
             input_left.clone().ctx,
             input_left.clone().output,
             input_right.clone().output,
             &mut builder,
         );
+        let output = builder.select(input_right.live, reduced, input_left.clone().output);
 
-        // Compute the accumulator hash for the inputs.
-        let acc = builder.poseidon_hash_pair(input_left.clone().acc, input_right.clone().acc);
+        // Compute the accumulator hash for the live inputs.
+        let empty = builder.constant::<PoseidonHashOutVariable>(HashOut::ZERO);
+        let acc_right = builder.select(input_right.live, input_right.clone().acc, empty);
+        let acc = builder.poseidon_hash_pair(input_left.clone().acc, acc_right);
 
         // Write result.
-        let result = MapReduceOutputVariable {
+        let live = builder._true();
+        let result = MapReduceWithLenOutputVariable {
             ctx: input_left.clone().ctx,
-            acc,
             output,
+            acc,
+            live,
         };
         builder.proof_write(result);
 
         builder.build()
     }
 
+    /// Builds the cyclic step circuit of a fold, which applies the step function to the state
+    /// proven by its previous proof and the next batch of inputs, or to the initial state for
+    /// the first batch. The circuit takes in the context, the inputs, the previous state and
+    /// accumulator, the initial state and the index of the batch, and outputs the next state and
+    /// accumulator.
+    fn build_fold_step<Ctx, Input, State, StepFn, const B: usize>(
+        &mut self,
+        step_fn: &StepFn,
+        common_data: Option<CommonCircuitData<L::Field, D>>,
+    ) -> CircuitBuild<L, D>
+    where
+        Ctx: CircuitVariable,
+        Input: CircuitVariable,
+        State: CircuitVariable,
+        StepFn: Fn(Ctx, State, ArrayVariable<Input, B>, &mut CircuitBuilder<L, D>) -> State,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        builder.beacon_client = self.beacon_client.clone();
+        builder.execution_client = self.execution_client.clone();
+        builder.use_cyclic_recursion();
+
+        // Read the inputs.
+        let data = builder.read::<MapReduceInputVariable<Ctx, Input, B>>();
+        let prev = builder.read::<MapReduceFoldStateVariable<State>>();
+        let initial = builder.read::<State>();
+        let index = builder.read::<U32Variable>();
+
+        // The first batch starts from the initial state and the empty accumulator.
+        let zero = builder.constant::<U32Variable>(0);
+        let is_first = builder.is_equal(index, zero);
+        let state = builder.select(is_first, initial.clone(), prev.clone().state);
+        let empty = builder.constant::<PoseidonHashOutVariable>(HashOut::ZERO);
+        let acc = builder.select(is_first, empty, prev.clone().acc);
+
+        // Apply the step function and absorb the leaf hash of the inputs.
+        let state = step_fn(data.clone().ctx, state, data.clone().inputs, &mut builder);
+        let input_variables = data
+            .clone()
+            .inputs
+            .as_vec()
+            .iter()
+            .flat_map(|i| i.variables())
+            .collect_vec();
+        let leaf = builder.poseidon_hash(&input_variables);
+        let acc = builder.poseidon_hash_pair(acc, leaf);
+        builder.write(MapReduceFoldStateVariable { state, acc });
+        builder.close_cyclic_io();
+
+        // Read the previous proof, whose public inputs follow the layout above.
+        let is_retry = common_data.is_some();
+        let mut common_data = common_data.unwrap_or_else(common_data_for_recursion::<L, D>);
+        common_data.num_public_inputs = builder.api.num_public_inputs();
+        let inner_proof = builder.proof_read(&common_data);
+        let inner_pis = &inner_proof.public_inputs;
+        let mut ptr = 0;
+        let inner_data = MapReduceInputVariable::<Ctx, Input, B>::from_targets(
+            &inner_pis[ptr..ptr + MapReduceInputVariable::<Ctx, Input, B>::nb_elements()],
+        );
+        ptr += MapReduceInputVariable::<Ctx, Input, B>::nb_elements();
+        ptr += MapReduceFoldStateVariable::<State>::nb_elements();
+        let inner_initial = State::from_targets(&inner_pis[ptr..ptr + State::nb_elements()]);
+        ptr += State::nb_elements();
+        let inner_index =
+            U32Variable::from_targets(&inner_pis[ptr..ptr + U32Variable::nb_elements()]);
+        ptr += U32Variable::nb_elements();
+        let inner_output = MapReduceFoldStateVariable::<State>::from_targets(
+            &inner_pis[ptr..ptr + MapReduceFoldStateVariable::<State>::nb_elements()],
+        );
+
+        // Unless this is the first batch, the previous proof must be for the previous batch with
+        // the same context and initial state, and output the previous state and accumulator.
+        let one = builder.constant::<U32Variable>(1);
+        let inner_index_plus_one = builder.add(inner_index, one);
+        let checks = [
+            builder.is_equal(inner_data.ctx, data.ctx),
+            builder.is_equal(inner_initial, initial),
+            builder.is_equal(inner_output, prev),
+            builder.is_equal(inner_index_plus_one, index),
+        ];
+        let mut is_valid = builder._true();
+        for check in checks {
+            is_valid = builder.and(is_valid, check);
+        }
+        let is_valid = builder.or(is_valid, is_first);
+        let true_variable = builder._true();
+        builder.assert_is_equal(is_valid, true_variable);
+
+        // Verify the previous proof, or a dummy proof for the first batch.
+        let not_first = builder.not(is_first);
+        builder
+            .api
+            .conditionally_verify_cyclic_proof_or_dummy::<L::Config>(
+                BoolTarget::new_unsafe(not_first.variable.0),
+                &inner_proof,
+                &common_data,
+            )
+            .expect("failed to verify the previous fold proof");
+
+        // The dummy common data only has the shape of a recursive circuit, so rebuild the circuit
+        // with its own common data if they differ.
+        let (build, success) = builder.try_build();
+        if !success && !is_retry {
+            self.build_fold_step(step_fn, Some(build.data.common))
+        } else {
+            build
+        }
+    }
+
     pub fn mapreduce<Ctx, Input, Output, Serializer, const B: usize, MapFn, ReduceFn>(
         &mut self,
         ctx: Ctx,
@@ -163,6 +430,43 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         map_fn: MapFn,
         reduce_fn: ReduceFn,
     ) -> Output
+    where
+        Ctx: CircuitVariable,
+        Input: CircuitVariable,
+        Output: CircuitVariable,
+        Serializer: CircuitSerializer,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+        <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
+        MapFn: Fn(Ctx, ArrayVariable<Input, B>, &mut CircuitBuilder<L, D>) -> Output,
+        ReduceFn: Fn(Ctx, Output, Output, &mut CircuitBuilder<L, D>) -> Output,
+    {
+        self.mapreduce_with_arity::<Ctx, Input, Output, Serializer, B, 2, MapFn, ReduceFn>(
+            ctx, inputs, map_fn, reduce_fn,
+        )
+    }
+
+    /// Computes a mapreduce whose reduce circuits each reduce `R` proofs instead of two. A larger
+    /// arity gives bigger reduce circuits but fewer layers of recursion, and so a lower latency
+    /// when the proofs of a layer are generated in parallel. The reduce function is applied from
+    /// left to right to the outputs of the `R` proofs, and `inputs.len() / B` must be a power of
+    /// `R`.
+    pub fn mapreduce_with_arity<
+        Ctx,
+        Input,
+        Output,
+        Serializer,
+        const B: usize,
+        const R: usize,
+        MapFn,
+        ReduceFn,
+    >(
+        &mut self,
+        ctx: Ctx,
+        inputs: Vec<Input::ValueType<L::Field>>,
+        map_fn: MapFn,
+        reduce_fn: ReduceFn,
+    ) -> Output
     where
         Ctx: CircuitVariable,
         Input: CircuitVariable,
@@ -176,16 +480,18 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     {
         // Sanity checks.
         assert_eq!(inputs.len() % B, 0, "inputs length must be a multiple of B");
-        assert!(
-            (inputs.len() / B).is_power_of_two(),
-            "inputs.len() / B must be a power of two"
-        );
+        let nb_reduce_layers = mapreduce_nb_layers(inputs.len() / B, R)
+            .expect("inputs.len() / B must be a power of the arity R");
 
         // Compute the expected inputs accumulator.
         let expected_acc =
-            self.constant::<PoseidonHashOutVariable>(mapreduce_merkle_tree_root::<L, Input, B, D>(
-                &inputs,
-            ));
+            self.constant::<PoseidonHashOutVariable>(mapreduce_merkle_tree_root_with_arity::<
+                L,
+                Input,
+                B,
+                R,
+                D,
+            >(&inputs));
 
         // The gate and witness generator serializers.
         let gate_serializer = Serializer::gate_registry::<L, D>();
@@ -201,9 +507,8 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let map_circuit_path = format!("./build/{}.circuit", map_circuit_id);
         map_circuit.save(&map_circuit_path, &gate_serializer, &generator_serializer);
 
-        // For each reduce layer, we build a reduce circuit which reduces two input proofs
+        // For each reduce layer, we build a reduce circuit which reduces R input proofs
         // to an output O.
-        let nb_reduce_layers = ((inputs.len() / B) as f64).log2().ceil() as usize;
         let mut reduce_circuits = Vec::new();
         for i in 0..nb_reduce_layers {
             let child_circuit = if i == 0 {
@@ -212,7 +517,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
                 &reduce_circuits[i - 1]
             };
             let reduce_circuit =
-                self.build_reduce::<Ctx, Output, ReduceFn>(child_circuit, &reduce_fn);
+                self.build_reduce::<Ctx, Output, ReduceFn, R>(child_circuit, &reduce_fn);
             let reduce_circuit_id = reduce_circuit.id();
             let reduce_circuit_path = format!("./build/{}.circuit", reduce_circuit_id);
             reduce_circuit.save(
@@ -231,6 +536,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let generator = MapReduceGenerator::<L, Ctx, Input, Output, Serializer, B, D> {
             map_circuit_id,
             reduce_circuit_ids,
+            arity: R,
             ctx: ctx.clone(),
             inputs: inputs.clone(),
             proof: final_proof.clone(),
@@ -309,7 +615,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
                 &reduce_circuits[i - 1]
             };
             let reduce_circuit =
-                self.build_reduce::<Ctx, Output, ReduceFn>(child_circuit, &reduce_fn);
+                self.build_reduce::<Ctx, Output, ReduceFn, 2>(child_circuit, &reduce_fn);
             let reduce_circuit_id = reduce_circuit.id();
             let reduce_circuit_path = format!("./build/{}.circuit", reduce_circuit_id);
             reduce_circuit.save(
@@ -353,6 +659,257 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         // Return the output.
         output.output
     }
+    /// Computes a mapreduce over the first `len` of `inputs`, where `len` is only known at proving
+    /// time. Only the map proofs over these inputs are generated, and the subtrees beyond them are
+    /// filled with dummy proofs which the reduce circuits treat as the identity, so the proving
+    /// cost follows `len` rather than `inputs.len()`.
+    ///
+    /// The length must be a multiple of `B` between `B` and `inputs.len()`, and `inputs.len() / B`
+    /// must be a power of two as in `mapreduce`.
+    pub fn mapreduce_with_len<Ctx, Input, Output, Serializer, const B: usize, MapFn, ReduceFn>(
+        &mut self,
+        ctx: Ctx,
+        inputs: Vec<Input::ValueType<L::Field>>,
+        len: Variable,
+        map_fn: MapFn,
+        reduce_fn: ReduceFn,
+    ) -> Output
+    where
+        Ctx: CircuitVariable,
+        Input: CircuitVariable,
+        Output: CircuitVariable,
+        Serializer: CircuitSerializer,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+        <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
+        MapFn: Fn(Ctx, ArrayVariable<Input, B>, &mut CircuitBuilder<L, D>) -> Output,
+        ReduceFn: Fn(Ctx, Output, Output, &mut CircuitBuilder<L, D>) -> Output,
+    {
+        // Sanity checks.
+        assert_eq!(inputs.len() % B, 0, "inputs length must be a multiple of B");
+        assert!(
+            (inputs.len() / B).is_power_of_two(),
+            "inputs.len() / B must be a power of two"
+        );
+
+        // A leaf is live if it starts before `len`, which must be at the end of a leaf.
+        let true_variable = self._true();
+        let false_variable = self._false();
+        let mut at_end = false_variable;
+        let mut live = Vec::new();
+        for i in 0..=inputs.len() / B {
+            let end = self.constant::<Variable>(L::Field::from_canonical_usize(i * B));
+            let is_end = self.is_equal(len, end);
+            at_end = self.or(at_end, is_end);
+            live.push(self.not(at_end));
+        }
+        self.assert_is_equal(at_end, true_variable);
+        self.assert_is_equal(live[0], true_variable);
+
+        // Compute the expected inputs accumulator, in which the subtrees without live leaves are
+        // replaced by the empty hash.
+        let empty = self.constant::<PoseidonHashOutVariable>(HashOut::ZERO);
+        let mut nodes = mapreduce_merkle_tree_leaves::<L, Input, B, D>(&inputs)
+            .into_iter()
+            .zip(live)
+            .map(|(leaf, live)| (self.constant::<PoseidonHashOutVariable>(leaf), live))
+            .collect_vec();
+        while nodes.len() != 1 {
+            let mut tmp = Vec::new();
+            for i in 0..nodes.len() / 2 {
+                let (left, left_live) = nodes[i * 2].clone();
+                let (right, right_live) = nodes[i * 2 + 1].clone();
+                let right = self.select(right_live, right, empty.clone());
+                tmp.push((self.poseidon_hash_pair(left, right), left_live));
+            }
+            nodes = tmp;
+        }
+        let expected_acc = nodes[0].0.clone();
+
+        // The gate and witness generator serializers.
+        let gate_serializer = Serializer::gate_registry::<L, D>();
+        let generator_serializer = Serializer::generator_registry::<L, D>();
+        let dummy_cache = DummyProofCache::new("./build");
+
+        // Build a map circuit which maps from I -> O using the closure `m`.
+        let map_circuit = self.build_map_with_len(&map_fn);
+        debug!("succesfully built map circuit: id={}", map_circuit.id());
+
+        // Save map circuit and map circuit input target to build folder.
+        let map_circuit_id = map_circuit.id();
+        let map_circuit_path = format!("./build/{}.circuit", map_circuit_id);
+        map_circuit.save(&map_circuit_path, &gate_serializer, &generator_serializer);
+
+        // For each reduce layer, we build a reduce circuit which reduces two input proofs
+        // to an output O, the right one of which may be a dummy proof of the child circuit.
+        let nb_reduce_layers = ((inputs.len() / B) as f64).log2().ceil() as usize;
+        let mut reduce_circuits = Vec::new();
+        for i in 0..nb_reduce_layers {
+            let child_circuit = if i == 0 {
+                &map_circuit
+            } else {
+                &reduce_circuits[i - 1]
+            };
+            let dummy = dummy_cache
+                .dummy_proof::<L, D>(&child_circuit.data.common, &gate_serializer)
+                .unwrap();
+            let reduce_circuit = self.build_reduce_with_len::<Ctx, Output, ReduceFn>(
+                child_circuit,
+                &dummy.verifier_data,
+                &reduce_fn,
+            );
+            let reduce_circuit_id = reduce_circuit.id();
+            let reduce_circuit_path = format!("./build/{}.circuit", reduce_circuit_id);
+            reduce_circuit.save(
+                &reduce_circuit_path,
+                &gate_serializer,
+                &generator_serializer,
+            );
+            reduce_circuits.push(reduce_circuit);
+            debug!("succesfully built reduce circuit: id={}", reduce_circuit_id);
+        }
+
+        // Create generator to generate map and reduce proofs for each layer.
+        let reduce_circuit_ids = reduce_circuits.iter().map(|c| c.id()).collect_vec();
+        let final_circuit = &reduce_circuits[reduce_circuits.len() - 1];
+        let final_proof = self.add_virtual_proof_with_pis(&final_circuit.data.common);
+        let generator = MapReduceWithLenGenerator::<L, Ctx, Input, Output, Serializer, B, D> {
+            map_circuit_id,
+            reduce_circuit_ids,
+            ctx: ctx.clone(),
+            inputs: inputs.clone(),
+            len,
+            proof: final_proof.clone(),
+            _phantom: PhantomData,
+        };
+        self.add_simple_generator(generator);
+
+        // Verify the final proof.
+        let final_verifier_data = self.constant_verifier_data::<L>(&final_circuit.data);
+        self.verify_proof::<L>(
+            &final_proof,
+            &final_verifier_data,
+            &final_circuit.data.common,
+        );
+
+        // Verify the inputs accumulator.
+        let output = final_proof.read_end_from_pis::<MapReduceWithLenOutputVariable<Ctx, Output>>();
+        self.assert_is_equal(output.acc, expected_acc);
+
+        // Verify the context.
+        self.assert_is_equal(output.ctx, ctx);
+
+        // Return the output.
+        output.output
+    }
+    /// Folds `inputs` in order, in batches of `B`, starting from `initial`: the step function is
+    /// applied to the state after the previous batch and the next batch of inputs. Unlike
+    /// `mapreduce`, the step does not need to be associative, as the batches are proven by a chain
+    /// of cyclic recursive proofs rather than a tree, which makes it suited to running totals or
+    /// to updates of the frontier of a merkle tree. Returns the state after the last batch.
+    pub fn mapreduce_fold<Ctx, Input, State, Serializer, const B: usize, StepFn>(
+        &mut self,
+        ctx: Ctx,
+        initial: State,
+        inputs: Vec<Input::ValueType<L::Field>>,
+        step_fn: StepFn,
+    ) -> State
+    where
+        Ctx: CircuitVariable,
+        Input: CircuitVariable,
+        State: CircuitVariable,
+        Serializer: CircuitSerializer,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+        <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
+        StepFn: Fn(Ctx, State, ArrayVariable<Input, B>, &mut CircuitBuilder<L, D>) -> State,
+    {
+        // Sanity checks.
+        assert_eq!(inputs.len() % B, 0, "inputs length must be a multiple of B");
+        assert!(!inputs.is_empty(), "inputs must not be empty");
+
+        // Compute the expected inputs accumulator.
+        let expected_acc =
+            self.constant::<PoseidonHashOutVariable>(mapreduce_hash_chain::<L, Input, B, D>(
+                &inputs,
+            ));
+
+        // Build the step circuit and save it to the build folder.
+        let gate_serializer = Serializer::gate_registry::<L, D>();
+        let generator_serializer = Serializer::generator_registry::<L, D>();
+        let step_circuit = self.build_fold_step::<Ctx, Input, State, StepFn, B>(&step_fn, None);
+        let step_circuit_id = step_circuit.id();
+        let step_circuit_path = format!("./build/{}.circuit", step_circuit_id);
+        step_circuit.save(&step_circuit_path, &gate_serializer, &generator_serializer);
+        debug!(
+            "succesfully built fold step circuit: id={}",
+            step_circuit_id
+        );
+
+        // Create generator to generate the chain of step proofs.
+        let final_proof = self.add_virtual_proof_with_pis(&step_circuit.data.common);
+        let generator = MapReduceFoldGenerator::<L, Ctx, Input, State, Serializer, B, D> {
+            step_circuit_id,
+            ctx: ctx.clone(),
+            initial: initial.clone(),
+            inputs: inputs.clone(),
+            proof: final_proof.clone(),
+            _phantom: PhantomData,
+        };
+        self.add_simple_generator(generator);
+
+        // Verify the final proof.
+        let final_verifier_data = self.constant_verifier_data::<L>(&step_circuit.data);
+        self.verify_proof::<L>(
+            &final_proof,
+            &final_verifier_data,
+            &step_circuit.data.common,
+        );
+
+        // Decode the public inputs of the final proof, which end with the verifier data the chain
+        // was verified against.
+        let pis = &final_proof.public_inputs;
+        let mut ptr = 0;
+        let data = MapReduceInputVariable::<Ctx, Input, B>::from_targets(
+            &pis[ptr..ptr + MapReduceInputVariable::<Ctx, Input, B>::nb_elements()],
+        );
+        ptr += MapReduceInputVariable::<Ctx, Input, B>::nb_elements();
+        ptr += MapReduceFoldStateVariable::<State>::nb_elements();
+        let proof_initial = State::from_targets(&pis[ptr..ptr + State::nb_elements()]);
+        ptr += State::nb_elements();
+        let index = U32Variable::from_targets(&pis[ptr..ptr + U32Variable::nb_elements()]);
+        ptr += U32Variable::nb_elements();
+        let output = MapReduceFoldStateVariable::<State>::from_targets(
+            &pis[ptr..ptr + MapReduceFoldStateVariable::<State>::nb_elements()],
+        );
+        ptr += MapReduceFoldStateVariable::<State>::nb_elements();
+        let verifier_targets = final_verifier_data
+            .circuit_digest
+            .elements
+            .iter()
+            .chain(
+                final_verifier_data
+                    .constants_sigmas_cap
+                    .0
+                    .iter()
+                    .flat_map(|hash| hash.elements.iter()),
+            )
+            .collect_vec();
+        for (i, target) in verifier_targets.into_iter().enumerate() {
+            self.api.connect(pis[ptr + i], *target);
+        }
+
+        // Verify the context, the initial state, the number of batches and the inputs
+        // accumulator.
+        self.assert_is_equal(data.ctx, ctx);
+        self.assert_is_equal(proof_initial, initial);
+        let last_index = self.constant::<U32Variable>((inputs.len() / B - 1) as u32);
+        self.assert_is_equal(index, last_index);
+        self.assert_is_equal(output.acc, expected_acc);
+
+        // Return the final state.
+        output.state
+    }
 }
 
 #[cfg(test)]
@@ -405,4 +962,94 @@ pub(crate) mod tests {
         let result = output.read::<Variable>();
         println!("{}", result);
     }
+
+    #[test]
+    fn test_mapreduce_with_arity_circuit() {
+        env_logger::try_init().unwrap_or_default();
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let ctx = builder.constant::<Variable>(F::from_canonical_u64(8));
+        let inputs = (0..4).map(F::from_canonical_u64).collect::<Vec<_>>();
+
+        // A single reduce layer reduces the four map proofs.
+        let output = builder
+            .mapreduce_with_arity::<Variable, Variable, Variable, DefaultSerializer, 1, 4, _, _>(
+                ctx,
+                inputs,
+                |_, inputs, builder| {
+                    let constant = builder.constant::<Variable>(F::ONE);
+                    builder.add(inputs[0], constant)
+                },
+                |_, left, right, builder| builder.add(left, right),
+            );
+        builder.write(output);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<Variable>(), F::from_canonical_u64(10));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_mapreduce_with_len_circuit() {
+        env_logger::try_init().unwrap_or_default();
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let ctx = builder.constant::<Variable>(F::from_canonical_u64(8));
+        let len = builder.read::<Variable>();
+        let inputs = (0..4).map(F::from_canonical_u64).collect::<Vec<_>>();
+
+        let output = builder
+            .mapreduce_with_len::<Variable, Variable, Variable, DefaultSerializer, 1, _, _>(
+                ctx,
+                inputs,
+                len,
+                |_, inputs, builder| {
+                    let constant = builder.constant::<Variable>(F::ONE);
+                    builder.add(inputs[0], constant)
+                },
+                |_, left, right, builder| builder.add(left, right),
+            );
+        builder.write(output);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<Variable>(F::from_canonical_u64(3));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<Variable>(), F::from_canonical_u64(6));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_mapreduce_fold_circuit() {
+        env_logger::try_init().unwrap_or_default();
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let ctx = builder.constant::<Variable>(F::from_canonical_u64(2));
+        let initial = builder.read::<Variable>();
+        let inputs = (1..4).map(F::from_canonical_u64).collect::<Vec<_>>();
+
+        // The step is not associative, so the result depends on the order of the inputs.
+        let output = builder
+            .mapreduce_fold::<Variable, Variable, Variable, DefaultSerializer, 1, _>(
+                ctx,
+                initial,
+                inputs,
+                |ctx, state, inputs, builder| {
+                    let doubled = builder.mul(state, ctx);
+                    builder.add(doubled, inputs[0])
+                },
+            );
+        builder.write(output);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<Variable>(F::ZERO);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<Variable>(), F::from_canonical_u64(11));
+    }
 }
@@ -0,0 +1,389 @@
+//! A Poseidon accumulator of a growing set of `U256` elements, such as nullifiers.
+//!
+//! The set is stored as a sorted linked list in the leaves of a Poseidon Merkle tree of fixed
+//! depth: each leaf holds an element and the next larger element of the set, or zero for the
+//! largest one. Leaf `0` holds the sentinel element zero, which is reserved. The membership of an
+//! element is proven by opening its leaf, and its non-membership by opening the leaf of the
+//! largest smaller element, whose successor must be larger. Insertions update that leaf and fill
+//! the next empty leaf, so the accumulator state (the root and the number of leaves) can be
+//! carried across the steps of a cyclic circuit.
+
+use std::collections::HashMap;
+
+use ethers::types::U256;
+use plonky2::field::types::PrimeField64;
+use plonky2::hash::hash_types::HashOut;
+use plonky2::hash::hashing::hash_n_to_hash_no_pad;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+
+use crate::frontend::hash::poseidon::poseidon256::PoseidonHashOutVariable;
+use crate::prelude::*;
+
+/// A leaf of the accumulator.
+#[derive(Clone, Debug, CircuitVariable)]
+#[value_name(AccumulatorLeaf)]
+pub struct AccumulatorLeafVariable {
+    pub value: U256Variable,
+    /// The next larger element of the set, or zero if `value` is the largest.
+    pub next: U256Variable,
+}
+
+/// The state of an accumulator.
+#[derive(Clone, Debug, CircuitVariable)]
+#[value_name(AccumulatorState)]
+pub struct AccumulatorStateVariable {
+    pub root: PoseidonHashOutVariable,
+    /// The number of leaves in use, which is also the index of the next empty leaf.
+    pub size: Variable,
+}
+
+/// An opening of a leaf of an accumulator.
+#[derive(Clone, Debug, CircuitVariable)]
+#[value_name(AccumulatorProof)]
+pub struct AccumulatorProofVariable<const DEPTH: usize> {
+    pub leaf: AccumulatorLeafVariable,
+    pub index: Variable,
+    pub siblings: ArrayVariable<PoseidonHashOutVariable, DEPTH>,
+}
+
+/// The witness of an insertion: the non-membership proof of the inserted element, and the
+/// siblings of the next empty leaf once the predecessor of the element is updated.
+#[derive(Clone, Debug, CircuitVariable)]
+#[value_name(AccumulatorInsertion)]
+pub struct AccumulatorInsertionVariable<const DEPTH: usize> {
+    pub low: AccumulatorProofVariable<DEPTH>,
+    pub siblings: ArrayVariable<PoseidonHashOutVariable, DEPTH>,
+}
+
+fn hash_elements<L: PlonkParameters<D>, const D: usize>(elements: &[L::Field]) -> HashOut<L::Field>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    hash_n_to_hash_no_pad::<
+        L::Field,
+        <<<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher as Hasher<
+            <L as PlonkParameters<D>>::Field,
+        >>::Permutation,
+    >(elements)
+}
+
+/// The accumulator outside of the circuit, which generates the witnesses of the gadgets.
+#[derive(Debug, Clone)]
+pub struct PoseidonAccumulator<L: PlonkParameters<D>, const D: usize, const DEPTH: usize> {
+    leaves: Vec<AccumulatorLeaf<L::Field>>,
+    /// The hashes of the non-empty subtrees at each level, starting from the leaves.
+    nodes: Vec<HashMap<usize, HashOut<L::Field>>>,
+    /// The hashes of the empty subtrees at each level.
+    empty: Vec<HashOut<L::Field>>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize, const DEPTH: usize> Default
+    for PoseidonAccumulator<L, D, DEPTH>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, const DEPTH: usize> PoseidonAccumulator<L, D, DEPTH>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    /// Creates an accumulator holding only the sentinel element.
+    pub fn new() -> Self {
+        let mut empty = vec![HashOut {
+            elements: [L::Field::ZERO; 4],
+        }];
+        for level in 0..DEPTH {
+            empty.push(Self::hash_pair(&empty[level], &empty[level]));
+        }
+        let mut accumulator = Self {
+            leaves: Vec::new(),
+            nodes: vec![HashMap::new(); DEPTH + 1],
+            empty,
+        };
+        accumulator.set_leaf(
+            0,
+            AccumulatorLeaf {
+                value: U256::zero(),
+                next: U256::zero(),
+            },
+        );
+        accumulator
+    }
+
+    fn hash_pair(left: &HashOut<L::Field>, right: &HashOut<L::Field>) -> HashOut<L::Field> {
+        let mut elements = left.elements.to_vec();
+        elements.extend(right.elements);
+        hash_elements::<L, D>(&elements)
+    }
+
+    fn leaf_hash(leaf: &AccumulatorLeaf<L::Field>) -> HashOut<L::Field> {
+        hash_elements::<L, D>(&AccumulatorLeafVariable::elements(leaf.clone()))
+    }
+
+    fn node(&self, level: usize, index: usize) -> HashOut<L::Field> {
+        self.nodes[level]
+            .get(&index)
+            .copied()
+            .unwrap_or(self.empty[level])
+    }
+
+    fn set_leaf(&mut self, index: usize, leaf: AccumulatorLeaf<L::Field>) {
+        assert!(index < 1 << DEPTH, "accumulator is full");
+        let mut hash = Self::leaf_hash(&leaf);
+        if index == self.leaves.len() {
+            self.leaves.push(leaf);
+        } else {
+            self.leaves[index] = leaf;
+        }
+        let mut index = index;
+        for level in 0..DEPTH {
+            self.nodes[level].insert(index, hash);
+            let sibling = self.node(level, index ^ 1);
+            hash = if index % 2 == 0 {
+                Self::hash_pair(&hash, &sibling)
+            } else {
+                Self::hash_pair(&sibling, &hash)
+            };
+            index /= 2;
+        }
+        self.nodes[DEPTH].insert(0, hash);
+    }
+
+    fn siblings(&self, index: usize) -> Vec<HashOut<L::Field>> {
+        (0..DEPTH)
+            .map(|level| self.node(level, (index >> level) ^ 1))
+            .collect()
+    }
+
+    fn proof(&self, index: usize) -> AccumulatorProof<DEPTH, L::Field> {
+        AccumulatorProof {
+            leaf: self.leaves[index].clone(),
+            index: L::Field::from_canonical_usize(index),
+            siblings: self.siblings(index),
+        }
+    }
+
+    pub fn state(&self) -> AccumulatorState<L::Field> {
+        AccumulatorState {
+            root: self.node(DEPTH, 0),
+            size: L::Field::from_canonical_usize(self.leaves.len()),
+        }
+    }
+
+    pub fn contains(&self, value: U256) -> bool {
+        self.leaves.iter().any(|leaf| leaf.value == value)
+    }
+
+    /// Returns the opening of the leaf of `value`, if it is in the set.
+    pub fn membership_proof(&self, value: U256) -> Option<AccumulatorProof<DEPTH, L::Field>> {
+        let index = self.leaves.iter().position(|leaf| leaf.value == value)?;
+        Some(self.proof(index))
+    }
+
+    /// Returns the opening of the leaf of the largest element smaller than `value`, if `value` is
+    /// not in the set.
+    pub fn non_membership_proof(&self, value: U256) -> Option<AccumulatorProof<DEPTH, L::Field>> {
+        let index = self
+            .leaves
+            .iter()
+            .position(|leaf| leaf.value < value && (leaf.next.is_zero() || value < leaf.next))?;
+        Some(self.proof(index))
+    }
+
+    /// Inserts `value` into the set, returning the witness of the insertion, or `None` if it is
+    /// already in the set.
+    pub fn insert(&mut self, value: U256) -> Option<AccumulatorInsertion<DEPTH, L::Field>> {
+        let low = self.non_membership_proof(value)?;
+        let low_index = low.index.to_canonical_u64() as usize;
+        let index = self.leaves.len();
+        self.set_leaf(
+            low_index,
+            AccumulatorLeaf {
+                value: low.leaf.value,
+                next: value,
+            },
+        );
+        let siblings = self.siblings(index);
+        self.set_leaf(
+            index,
+            AccumulatorLeaf {
+                value,
+                next: low.leaf.next,
+            },
+        );
+        Some(AccumulatorInsertion { low, siblings })
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    /// The state of an accumulator holding only the sentinel element.
+    pub fn accumulator_empty<const DEPTH: usize>(&mut self) -> AccumulatorStateVariable {
+        let state = PoseidonAccumulator::<L, D, DEPTH>::new().state();
+        self.constant::<AccumulatorStateVariable>(state)
+    }
+
+    /// Computes the root of a tree from a leaf and its siblings.
+    fn accumulator_root<const DEPTH: usize>(
+        &mut self,
+        leaf_hash: PoseidonHashOutVariable,
+        index: Variable,
+        siblings: &ArrayVariable<PoseidonHashOutVariable, DEPTH>,
+    ) -> PoseidonHashOutVariable {
+        // Splitting the index into `DEPTH` bits also checks that it is in range.
+        let bits = self.api.split_le(index.0, DEPTH);
+        let mut node = leaf_hash;
+        for (bit, sibling) in bits.into_iter().zip(siblings.as_vec()) {
+            let bit = BoolVariable::from(bit);
+            let left = self.select(bit, sibling.clone(), node.clone());
+            let right = self.select(bit, node, sibling);
+            node = self.poseidon_hash_pair(left, right);
+        }
+        node
+    }
+
+    fn accumulator_leaf_hash(&mut self, leaf: &AccumulatorLeafVariable) -> PoseidonHashOutVariable {
+        self.poseidon_hash(&leaf.variables())
+    }
+
+    /// Checks that the proof opens a leaf of the accumulator.
+    fn accumulator_assert_opens<const DEPTH: usize>(
+        &mut self,
+        state: &AccumulatorStateVariable,
+        proof: &AccumulatorProofVariable<DEPTH>,
+    ) {
+        let leaf_hash = self.accumulator_leaf_hash(&proof.leaf);
+        let root = self.accumulator_root(leaf_hash, proof.index, &proof.siblings);
+        self.assert_is_equal(root, state.root.clone());
+    }
+
+    /// Checks that `value` is in the set.
+    pub fn accumulator_assert_contains<const DEPTH: usize>(
+        &mut self,
+        state: &AccumulatorStateVariable,
+        value: U256Variable,
+        proof: &AccumulatorProofVariable<DEPTH>,
+    ) {
+        self.accumulator_assert_opens(state, proof);
+        self.assert_is_equal(proof.leaf.value, value);
+    }
+
+    /// Checks that `value` is not in the set, given the opening of the leaf of the largest smaller
+    /// element.
+    pub fn accumulator_assert_not_contains<const DEPTH: usize>(
+        &mut self,
+        state: &AccumulatorStateVariable,
+        value: U256Variable,
+        proof: &AccumulatorProofVariable<DEPTH>,
+    ) {
+        self.accumulator_assert_opens(state, proof);
+        let true_variable = self._true();
+        let is_after_low = self.lt(proof.leaf.value, value);
+        self.assert_is_equal(is_after_low, true_variable);
+
+        let zero = self.constant::<U256Variable>(U256::zero());
+        let is_last = self.is_equal(proof.leaf.next, zero);
+        let is_before_next = self.lt(value, proof.leaf.next);
+        let is_in_gap = self.or(is_last, is_before_next);
+        self.assert_is_equal(is_in_gap, true_variable);
+    }
+
+    /// Inserts `value`, which must not be in the set, and returns the new state.
+    pub fn accumulator_insert<const DEPTH: usize>(
+        &mut self,
+        state: &AccumulatorStateVariable,
+        value: U256Variable,
+        insertion: &AccumulatorInsertionVariable<DEPTH>,
+    ) -> AccumulatorStateVariable {
+        let low = &insertion.low;
+        self.accumulator_assert_not_contains(state, value, low);
+
+        // Point the predecessor of `value` to it, with the same siblings as before.
+        let updated_low = AccumulatorLeafVariable {
+            value: low.leaf.value,
+            next: value,
+        };
+        let updated_low_hash = self.accumulator_leaf_hash(&updated_low);
+        let root = self.accumulator_root(updated_low_hash, low.index, &low.siblings);
+
+        // Open the next empty leaf in the updated tree, and fill it with `value`.
+        let empty = self.constant::<PoseidonHashOutVariable>(HashOut {
+            elements: [L::Field::ZERO; 4],
+        });
+        let empty_root = self.accumulator_root(empty, state.size, &insertion.siblings);
+        self.assert_is_equal(empty_root, root);
+
+        let leaf = AccumulatorLeafVariable {
+            value,
+            next: low.leaf.next,
+        };
+        let leaf_hash = self.accumulator_leaf_hash(&leaf);
+        let one: Variable = self.one();
+        AccumulatorStateVariable {
+            root: self.accumulator_root(leaf_hash, state.size, &insertion.siblings),
+            size: self.add(state.size, one),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+    const DEPTH: usize = 4;
+
+    #[test]
+    fn test_accumulator() {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let mut state = builder.accumulator_empty::<DEPTH>();
+        for _ in 0..3 {
+            let value = builder.read::<U256Variable>();
+            let insertion = builder.read::<AccumulatorInsertionVariable<DEPTH>>();
+            state = builder.accumulator_insert(&state, value, &insertion);
+        }
+        let member = builder.read::<U256Variable>();
+        let proof = builder.read::<AccumulatorProofVariable<DEPTH>>();
+        builder.accumulator_assert_contains(&state, member, &proof);
+        let non_member = builder.read::<U256Variable>();
+        let proof = builder.read::<AccumulatorProofVariable<DEPTH>>();
+        builder.accumulator_assert_not_contains(&state, non_member, &proof);
+        builder.write(state);
+        let circuit = builder.build();
+
+        let mut accumulator = PoseidonAccumulator::<L, D, DEPTH>::new();
+        let mut input = circuit.input();
+        for value in [50u64, 10, 30] {
+            let value = U256::from(value);
+            input.write::<U256Variable>(value);
+            input.write::<AccumulatorInsertionVariable<DEPTH>>(accumulator.insert(value).unwrap());
+        }
+        assert!(accumulator.insert(U256::from(30)).is_none());
+        assert!(accumulator.non_membership_proof(U256::from(10)).is_none());
+        input.write::<U256Variable>(U256::from(30));
+        input.write::<AccumulatorProofVariable<DEPTH>>(
+            accumulator.membership_proof(U256::from(30)).unwrap(),
+        );
+        input.write::<U256Variable>(U256::from(40));
+        input.write::<AccumulatorProofVariable<DEPTH>>(
+            accumulator.non_membership_proof(U256::from(40)).unwrap(),
+        );
+
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        let state = output.read::<AccumulatorStateVariable>();
+        assert_eq!(state.root, accumulator.state().root);
+        assert_eq!(state.size, accumulator.state().size);
+    }
+}
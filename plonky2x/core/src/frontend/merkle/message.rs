@@ -0,0 +1,230 @@
+//! A keccak commitment to an ordered batch of messages, as sent across a bridge.
+//!
+//! Each message is hashed into a leaf, and the leaves fill a binary tree of fixed depth from the
+//! left, with zero leaves after the last message. Inner nodes are `keccak256(left || right)` and
+//! the commitment is `keccak256(tree_root || count)` with the number of messages as a big-endian
+//! `uint256`, so it equals `keccak256(abi.encodePacked(treeRoot, count))` on the contract side and
+//! batches differing only by trailing empty messages have different commitments.
+
+use ethers::types::H256;
+use ethers::utils::keccak256;
+
+use crate::prelude::*;
+
+/// A message of at most `MAX_LEN` bytes, of which the first `length` are used.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(MessageValue)]
+pub struct MessageVariable<const MAX_LEN: usize> {
+    pub data: ArrayVariable<ByteVariable, MAX_LEN>,
+    pub length: Variable,
+}
+
+/// An opening of the leaf at `index` of a batch of `count` messages.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(MessageInclusionProof)]
+pub struct MessageInclusionProofVariable<const DEPTH: usize> {
+    pub index: U64Variable,
+    pub siblings: ArrayVariable<Bytes32Variable, DEPTH>,
+    pub count: U64Variable,
+}
+
+impl<const MAX_LEN: usize, F: RichField> MessageValue<MAX_LEN, F> {
+    pub fn new(message: &[u8]) -> Self {
+        assert!(message.len() <= MAX_LEN, "message is too long");
+        let mut data = message.to_vec();
+        data.resize(MAX_LEN, 0);
+        Self {
+            data,
+            length: F::from_canonical_usize(message.len()),
+        }
+    }
+}
+
+/// The native counterpart of the commitment, used to compute the witnesses of the inclusion
+/// proofs.
+#[derive(Debug, Clone)]
+pub struct MessageBatch<const DEPTH: usize> {
+    leaves: Vec<H256>,
+}
+
+impl<const DEPTH: usize> MessageBatch<DEPTH> {
+    pub fn new<M: AsRef<[u8]>>(messages: &[M]) -> Self {
+        assert!(messages.len() <= 1 << DEPTH, "too many messages");
+        Self {
+            leaves: messages
+                .iter()
+                .map(|message| H256(keccak256(message.as_ref())))
+                .collect(),
+        }
+    }
+
+    fn hash_pair(left: &H256, right: &H256) -> H256 {
+        H256(keccak256([left.as_bytes(), right.as_bytes()].concat()))
+    }
+
+    /// The nodes of the tree by level, from the leaves to the root.
+    fn levels(&self) -> Vec<Vec<H256>> {
+        let mut level = self.leaves.clone();
+        level.resize(1 << DEPTH, H256::zero());
+        let mut levels = vec![level];
+        for _ in 0..DEPTH {
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| Self::hash_pair(&pair[0], &pair[1]))
+                .collect();
+            levels.push(level);
+        }
+        levels
+    }
+
+    pub fn leaf(&self, index: usize) -> H256 {
+        self.leaves[index]
+    }
+
+    pub fn commitment(&self) -> H256 {
+        let tree_root = self.levels()[DEPTH][0];
+        let mut count = [0u8; 32];
+        count[24..].copy_from_slice(&(self.leaves.len() as u64).to_be_bytes());
+        H256(keccak256([tree_root.as_bytes(), &count].concat()))
+    }
+
+    pub fn inclusion_proof<F: RichField>(&self, index: usize) -> MessageInclusionProof<DEPTH, F> {
+        assert!(index < self.leaves.len(), "index out of range");
+        let levels = self.levels();
+        MessageInclusionProof {
+            index: index as u64,
+            siblings: (0..DEPTH)
+                .map(|level| levels[level][(index >> level) ^ 1])
+                .collect(),
+            count: self.leaves.len() as u64,
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// The leaf of a message, `keccak256(data[..length])`. The length must be at most `MAX_LEN`.
+    pub fn message_leaf<const MAX_LEN: usize>(
+        &mut self,
+        message: &MessageVariable<MAX_LEN>,
+    ) -> Bytes32Variable {
+        let length = U32Variable::from_variables(self, &[message.length]);
+        self.keccak256_variable(message.data.as_slice(), length)
+    }
+
+    /// Mixes the number of messages into the root of the tree.
+    fn message_mix_in_count(
+        &mut self,
+        tree_root: Bytes32Variable,
+        count: U64Variable,
+    ) -> Bytes32Variable {
+        let zero = self.constant::<ByteVariable>(0);
+        let mut preimage = tree_root.as_bytes().to_vec();
+        preimage.extend(vec![zero; 24]);
+        preimage.extend(count.encode(self));
+        self.keccak256(&preimage)
+    }
+
+    /// Computes the commitment to the first `count` of the given messages in a tree of depth
+    /// `DEPTH`. The messages at or after `count` are replaced by zero leaves.
+    pub fn message_batch_commitment<const DEPTH: usize, const MAX_LEN: usize>(
+        &mut self,
+        messages: &[MessageVariable<MAX_LEN>],
+        count: U64Variable,
+    ) -> Bytes32Variable {
+        assert!(messages.len() <= 1 << DEPTH, "too many messages");
+        let max_count = self.constant::<U64Variable>(messages.len() as u64);
+        let in_range = self.lte(count, max_count);
+        let true_variable = self._true();
+        self.assert_is_equal(in_range, true_variable);
+
+        let zero = self.constant::<Bytes32Variable>(H256::zero());
+        let mut level = Vec::with_capacity(1 << DEPTH);
+        for (i, message) in messages.iter().enumerate() {
+            let leaf = self.message_leaf(message);
+            let i = self.constant::<U64Variable>(i as u64);
+            let is_used = self.lt(i, count);
+            level.push(self.select(is_used, leaf, zero));
+        }
+        level.resize(1 << DEPTH, zero);
+        for _ in 0..DEPTH {
+            level = level
+                .chunks(2)
+                .map(|pair| self.keccak256(&[pair[0].as_bytes(), pair[1].as_bytes()].concat()))
+                .collect();
+        }
+        self.message_mix_in_count(level[0], count)
+    }
+
+    /// Checks that `leaf` is the leaf of the message at `proof.index` of the batch committed to
+    /// by `commitment`.
+    pub fn message_batch_assert_includes<const DEPTH: usize>(
+        &mut self,
+        commitment: Bytes32Variable,
+        leaf: Bytes32Variable,
+        proof: &MessageInclusionProofVariable<DEPTH>,
+    ) {
+        let true_variable = self._true();
+        let in_batch = self.lt(proof.index, proof.count);
+        self.assert_is_equal(in_batch, true_variable);
+
+        let false_variable = self._false();
+        let bits = self.to_le_bits(proof.index);
+        for bit in bits[DEPTH..].iter() {
+            self.assert_is_equal(*bit, false_variable);
+        }
+        let mut node = leaf;
+        for (bit, sibling) in bits.into_iter().zip(proof.siblings.as_vec()) {
+            let left = self.select(bit, sibling, node);
+            let right = self.select(bit, node, sibling);
+            node = self.keccak256(&[left.as_bytes(), right.as_bytes()].concat());
+        }
+        let expected = self.message_mix_in_count(node, proof.count);
+        self.assert_is_equal(expected, commitment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+    const DEPTH: usize = 2;
+    const MAX_LEN: usize = 40;
+
+    #[test]
+    fn test_message_batch() {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let messages = (0..3)
+            .map(|_| builder.read::<MessageVariable<MAX_LEN>>())
+            .collect::<Vec<_>>();
+        let count = builder.read::<U64Variable>();
+        let commitment = builder.message_batch_commitment::<DEPTH, MAX_LEN>(&messages, count);
+        let proof = builder.read::<MessageInclusionProofVariable<DEPTH>>();
+        let leaf = builder.message_leaf(&messages[1]);
+        builder.message_batch_assert_includes(commitment, leaf, &proof);
+        builder.write(commitment);
+        let circuit = builder.build();
+
+        let contents: [&[u8]; 3] = [b"", b"hello", &[7u8; MAX_LEN]];
+        // Only the first two messages are committed to.
+        let batch = MessageBatch::<DEPTH>::new(&contents[..2]);
+        let mut input = circuit.input();
+        for message in contents {
+            input.write::<MessageVariable<MAX_LEN>>(MessageValue::new(message));
+        }
+        input.write::<U64Variable>(2);
+        input.write::<MessageInclusionProofVariable<DEPTH>>(batch.inclusion_proof(1));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        assert_eq!(output.read::<Bytes32Variable>(), batch.commitment());
+        assert_eq!(batch.leaf(1), H256(keccak256(b"hello")));
+        assert_ne!(
+            batch.commitment(),
+            MessageBatch::<DEPTH>::new(&[b"".as_slice(), b"hello", b""]).commitment()
+        );
+    }
+}
@@ -1,3 +1,5 @@
+pub mod accumulator;
+pub mod message;
 pub mod simple;
 pub mod tendermint;
 pub mod tree;
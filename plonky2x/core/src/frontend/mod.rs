@@ -1,3 +1,4 @@
+pub mod btc;
 pub mod builder;
 pub mod curta;
 pub mod ecc;
@@ -10,5 +11,11 @@ pub mod mapreduce;
 pub mod merkle;
 pub mod ops;
 pub mod recursion;
+pub mod rsa;
+pub mod ssz;
+#[cfg(feature = "starky")]
+pub mod starky;
+pub mod subcircuit;
+pub mod tendermint;
 pub mod uint;
 pub mod vars;
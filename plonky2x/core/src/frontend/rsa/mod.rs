@@ -0,0 +1,166 @@
+//! Verification of RSA-2048 PKCS#1 v1.5 signatures of SHA-256 digests, such as the DKIM
+//! signatures of emails.
+//!
+//! The public exponent is fixed to 65537, which almost every key uses, so the exponentiation takes
+//! sixteen squarings and one multiplication, each reduced by a constrained division by the
+//! modulus. Moduli and signatures are big-endian byte arrays, as they appear in DNS records and
+//! DKIM headers once decoded from base64.
+
+use itertools::Itertools;
+
+use crate::frontend::uint::num::biguint::{
+    biguint_from_bytes_variable, BigUintTarget, CircuitBuilderBiguint,
+};
+use crate::prelude::*;
+
+pub const RSA_2048_BYTES: usize = 256;
+
+const RSA_2048_LIMBS: usize = RSA_2048_BYTES / 4;
+
+/// The DER encoding of the `DigestInfo` structure of a SHA-256 digest, without the digest.
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// The bytes of a PKCS#1 v1.5 encoded SHA-256 digest that come before the digest:
+/// `0x00 0x01 0xff..0xff 0x00 DigestInfo`.
+fn pkcs1_sha256_prefix() -> Vec<u8> {
+    let padding_len = RSA_2048_BYTES - 3 - SHA256_DIGEST_INFO.len() - 32;
+    let mut prefix = vec![0x00, 0x01];
+    prefix.extend(vec![0xff; padding_len]);
+    prefix.push(0x00);
+    prefix.extend(SHA256_DIGEST_INFO);
+    prefix
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    fn rsa_2048_to_biguint(&mut self, bytes: &BytesVariable<RSA_2048_BYTES>) -> BigUintTarget {
+        // `biguint_from_bytes_variable` reads the bytes in little-endian order.
+        let mut le_bytes = bytes.0;
+        le_bytes.reverse();
+        biguint_from_bytes_variable(self, BytesVariable(le_bytes))
+    }
+
+    /// Computes `a * b mod modulus` for `a` and `b` already reduced.
+    fn rsa_2048_mul_mod(
+        &mut self,
+        a: &BigUintTarget,
+        b: &BigUintTarget,
+        modulus: &BigUintTarget,
+    ) -> BigUintTarget {
+        let product = self.api.mul_biguint(a, b);
+        // Since `a < modulus`, the quotient is below the modulus and fits in as many limbs.
+        let (_, rem) = self.api._div_rem_biguint(&product, modulus, RSA_2048_LIMBS);
+        rem
+    }
+
+    fn rsa_2048_pow_65537(
+        &mut self,
+        base: &BigUintTarget,
+        modulus: &BigUintTarget,
+    ) -> BigUintTarget {
+        let mut result = base.clone();
+        for _ in 0..16 {
+            result = self.rsa_2048_mul_mod(&result, &result, modulus);
+        }
+        self.rsa_2048_mul_mod(&result, base, modulus)
+    }
+
+    /// Checks that `signature` is a PKCS#1 v1.5 signature of the SHA-256 `digest` under the
+    /// public key with the given modulus and exponent 65537.
+    pub fn rsa_2048_verify_sha256_digest(
+        &mut self,
+        digest: Bytes32Variable,
+        signature: &BytesVariable<RSA_2048_BYTES>,
+        modulus: &BytesVariable<RSA_2048_BYTES>,
+    ) {
+        let modulus = self.rsa_2048_to_biguint(modulus);
+        let signature = self.rsa_2048_to_biguint(signature);
+        let is_unreduced = self.api.cmp_biguint(&modulus, &signature);
+        self.api.assert_zero(is_unreduced.target);
+
+        let encoded = self.rsa_2048_pow_65537(&signature, &modulus);
+
+        let mut expected = pkcs1_sha256_prefix()
+            .into_iter()
+            .map(|byte| self.constant::<ByteVariable>(byte))
+            .collect_vec();
+        expected.extend(digest.as_bytes());
+        let expected = BytesVariable(expected.try_into().unwrap());
+        let expected = self.rsa_2048_to_biguint(&expected);
+        self.api.connect_biguint(&encoded, &expected);
+    }
+
+    /// Checks the `rsa-sha256` DKIM signature of the first `length` bytes of `header`.
+    ///
+    /// The header must already be canonicalized as specified by the `c=` tag of the signature,
+    /// with the signed header fields in the order of the `h=` tag followed by the
+    /// `DKIM-Signature` field itself, whose `b=` tag is empty and which has no trailing CRLF.
+    pub fn dkim_verify_header(
+        &mut self,
+        header: &[ByteVariable],
+        length: U32Variable,
+        signature: &BytesVariable<RSA_2048_BYTES>,
+        modulus: &BytesVariable<RSA_2048_BYTES>,
+    ) {
+        let digest = self.curta_sha256_variable(header, length);
+        self.rsa_2048_verify_sha256_digest(digest, signature, modulus);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::bytes;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+    const MAX_HEADER_LEN: usize = 192;
+
+    const HEADER: &[u8] = b"from:alice@example.com\r\nsubject:hello\r\ndkim-signature:v=1; \
+        a=rsa-sha256; d=example.com; s=sel; c=relaxed/relaxed; h=from:subject; bh=; b=";
+
+    const MODULUS: &str = "c62acc3ce1dd7c231ff263491bcd79da9e6e29860cc7e03f54a0d6840a57ad975a4af3\
+        ffc3892bcf63ad7ab3435e146896752ed8f09213af4b5c33881870866f4d6d707751a1aba47106871f0e444b7\
+        3e5ab01a9b941a606ce04e6d4f023bc2c64d779ddb14794dc45e3b8f8a317156d3dfb0a7af604fd8f37732f000\
+        ab95ca412f5b563147430bf222fb500a1bb78d6d99af56c5e7dfdcdb211f38ce94dab2cbe774fa6b49dc61f4f3\
+        f8356c9427dccd05e30b555d219a61b49818275189d9a5b31373aaae0de0fd005862cb6911de4ba0a863a55193\
+        0aa5d816d6b9ceeb19faba9745f774d2c8325b57001fb693924eb54d1e50f53c03eee28fa7a922e0dad";
+
+    const SIGNATURE: &str = "62d2a9940e2c85ea3108d5c7699f17f6d42e745ec34e3a181f54590f6ed66bdabe05\
+        6e91e22aade01db2f2227b10b891dda3a6597ac4d305626c202e4316df22c976d082e7ad6afe5d4bcc884e8d0a\
+        4ccb4d0b7a055015b102b003dedcbb20e371d511ac539bf724194d795c53d22d8fd84d0fb7b557bcda8a9ca10a\
+        0e31249d40b858ca6d45b46cd6d79edec674f8b873cf809dac797c63c51b709c0347acb7bd6e335ed2c75c29b5\
+        61fe15766210e8614be783ab8f26084efc3306f54966adc4aa3982224518c8f6c004f7f2d89e2a3e8375042b69\
+        d7912fbae9f84ba137c64201da85b15fd8c632d4d44a9be528a05a60aa6f536a96424983e12916e0b940";
+
+    #[test]
+    fn test_pkcs1_sha256_prefix() {
+        let prefix = pkcs1_sha256_prefix();
+        assert_eq!(prefix.len() + 32, RSA_2048_BYTES);
+        assert_eq!(prefix[..3], [0x00, 0x01, 0xff]);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_dkim_verify_header() {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let header = builder.read::<ArrayVariable<ByteVariable, MAX_HEADER_LEN>>();
+        let length = builder.read::<U32Variable>();
+        let signature = builder.read::<BytesVariable<RSA_2048_BYTES>>();
+        let modulus = builder.read::<BytesVariable<RSA_2048_BYTES>>();
+        builder.dkim_verify_header(header.as_slice(), length, &signature, &modulus);
+        let circuit = builder.build();
+
+        let mut padded_header = HEADER.to_vec();
+        padded_header.resize(MAX_HEADER_LEN, 0);
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<ByteVariable, MAX_HEADER_LEN>>(padded_header);
+        input.write::<U32Variable>(HEADER.len() as u32);
+        input.write::<BytesVariable<RSA_2048_BYTES>>(bytes!(SIGNATURE));
+        input.write::<BytesVariable<RSA_2048_BYTES>>(bytes!(MODULUS));
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
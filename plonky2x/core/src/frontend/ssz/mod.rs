@@ -0,0 +1,455 @@
+//! Constrained SSZ merkleization, i.e. `hash_tree_root`, of the SSZ composite types.
+//!
+//! Values are merkleized following the consensus specs: basic values are serialized in little
+//! endian and packed into 32-byte chunks, composite values are reduced to the roots of their
+//! elements, and the chunks are merkleized with sha256 into a tree whose width is the next power
+//! of two of the chunk limit of the type. The subtrees of zero chunks are constants, so the
+//! padding up to the limit costs no hashes. Lists and bitlists mix their length into the root.
+//!
+//! Containers are merkleized from the roots of their fields with `ssz_container_root`, which
+//! `SSZVariable` implementations of containers use instead of hashing their own tree.
+
+use itertools::Itertools;
+
+use crate::frontend::vars::SSZVariable;
+use crate::prelude::{
+    BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder, CircuitVariable,
+    PlonkParameters, U128Variable, U256Variable, U32Variable, U64Variable,
+};
+use crate::utils::hash::sha256;
+
+/// The number of bytes of an SSZ chunk.
+pub const SSZ_CHUNK_LEN: usize = 32;
+
+/// The number of bits of an SSZ chunk, i.e. the number of bits of a bitfield packed in a chunk.
+pub const SSZ_CHUNK_BITS: usize = SSZ_CHUNK_LEN * 8;
+
+/// An SSZ basic type, whose values are packed together into chunks when they are the elements of
+/// a vector or a list.
+pub trait SSZBasicVariable: CircuitVariable {
+    /// The number of bytes of the serialization of a value.
+    const SSZ_SIZE: usize;
+
+    /// Serializes the value in SSZ (little endian) order.
+    fn ssz_serialize<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable>;
+}
+
+macro_rules! impl_ssz_basic_uint {
+    ($a:ty, $size:expr) => {
+        impl SSZBasicVariable for $a {
+            const SSZ_SIZE: usize = $size;
+
+            fn ssz_serialize<L: PlonkParameters<D>, const D: usize>(
+                &self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> Vec<ByteVariable> {
+                self.to_little_endian(builder).bytes.0.to_vec()
+            }
+        }
+    };
+}
+
+impl_ssz_basic_uint!(U32Variable, 4);
+impl_ssz_basic_uint!(U64Variable, 8);
+impl_ssz_basic_uint!(U128Variable, 16);
+impl_ssz_basic_uint!(U256Variable, 32);
+
+impl SSZBasicVariable for ByteVariable {
+    const SSZ_SIZE: usize = 1;
+
+    fn ssz_serialize<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        _: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        vec![*self]
+    }
+}
+
+impl SSZBasicVariable for BoolVariable {
+    const SSZ_SIZE: usize = 1;
+
+    fn ssz_serialize<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        let false_v = builder._false();
+        let mut bits = [false_v; 8];
+        bits[7] = *self;
+        vec![ByteVariable(bits)]
+    }
+}
+
+/// Returns the roots of the trees of zero chunks of depth `0..=depth`.
+pub fn ssz_zero_hashes(depth: usize) -> Vec<[u8; 32]> {
+    let mut zero_hashes = vec![[0u8; 32]];
+    for i in 0..depth {
+        let mut input = zero_hashes[i].to_vec();
+        input.extend(zero_hashes[i]);
+        zero_hashes.push(sha256(&input));
+    }
+    zero_hashes
+}
+
+/// Returns the number of chunks of the packing of `len` basic values of `size` bytes.
+fn ssz_packed_chunks(len: usize, size: usize) -> usize {
+    (len * size).div_ceil(SSZ_CHUNK_LEN)
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Merkleizes `chunks` into a tree of `limit.next_power_of_two()` leaves, the missing leaves
+    /// being zero chunks.
+    pub fn ssz_merkleize(&mut self, chunks: &[Bytes32Variable], limit: usize) -> Bytes32Variable {
+        assert!(
+            chunks.len() <= limit,
+            "the number of chunks must not exceed the limit"
+        );
+        let depth = limit.next_power_of_two().trailing_zeros() as usize;
+        let zero_hashes = ssz_zero_hashes(depth);
+        if chunks.is_empty() {
+            return self.constant::<Bytes32Variable>(zero_hashes[depth].into());
+        }
+
+        let mut layer = chunks.to_vec();
+        for zero_hash in zero_hashes.iter().take(depth) {
+            if layer.len() % 2 == 1 {
+                layer.push(self.constant::<Bytes32Variable>((*zero_hash).into()));
+            }
+            layer = layer
+                .chunks_exact(2)
+                .map(|pair| self.curta_sha256_pair(pair[0], pair[1]))
+                .collect();
+        }
+        layer[0]
+    }
+
+    /// Packs the serializations of basic values into chunks, the last chunk being padded with
+    /// zeros.
+    pub fn ssz_pack<V: SSZBasicVariable>(&mut self, values: &[V]) -> Vec<Bytes32Variable> {
+        let mut bytes = values
+            .iter()
+            .flat_map(|value| value.ssz_serialize(self))
+            .collect_vec();
+        let zero = self.constant::<ByteVariable>(0);
+        bytes.resize(
+            ssz_packed_chunks(values.len(), V::SSZ_SIZE) * SSZ_CHUNK_LEN,
+            zero,
+        );
+        bytes
+            .chunks_exact(SSZ_CHUNK_LEN)
+            .map(|chunk| Bytes32Variable(BytesVariable(chunk.try_into().unwrap())))
+            .collect()
+    }
+
+    /// Packs bits into chunks, eight bits per byte in little endian order, the last chunk being
+    /// padded with zeros.
+    pub fn ssz_pack_bits(&mut self, bits: &[BoolVariable]) -> Vec<Bytes32Variable> {
+        let false_v = self._false();
+        let mut padded = bits.to_vec();
+        padded.resize(
+            bits.len().div_ceil(SSZ_CHUNK_BITS) * SSZ_CHUNK_BITS,
+            false_v,
+        );
+        padded
+            .chunks_exact(8)
+            .map(|byte_bits| {
+                let mut be_bits = [false_v; 8];
+                for (i, bit) in byte_bits.iter().enumerate() {
+                    be_bits[7 - i] = *bit;
+                }
+                ByteVariable(be_bits)
+            })
+            .collect_vec()
+            .chunks_exact(SSZ_CHUNK_LEN)
+            .map(|chunk| Bytes32Variable(BytesVariable(chunk.try_into().unwrap())))
+            .collect()
+    }
+
+    /// Mixes the length of an SSZ list into the root of its items, giving the root of the list.
+    pub fn ssz_mix_in_length(
+        &mut self,
+        items_root: Bytes32Variable,
+        length: U64Variable,
+    ) -> Bytes32Variable {
+        // The length is serialized as a little endian uint256.
+        let length_bytes = length.to_little_endian(self).bytes;
+        let zero = self.constant::<ByteVariable>(0);
+        let mut length_chunk = [zero; 32];
+        length_chunk[..8].copy_from_slice(&length_bytes.0);
+        self.curta_sha256_pair(items_root, Bytes32Variable(BytesVariable(length_chunk)))
+    }
+
+    pub fn ssz_hash_tree_root<V: SSZVariable>(&mut self, variable: V) -> Bytes32Variable {
+        variable.hash_tree_root(self)
+    }
+
+    /// Merkleizes a power of two number of leaves.
+    pub fn ssz_hash_leafs(&mut self, leafs: &[Bytes32Variable]) -> Bytes32Variable {
+        assert!(
+            leafs.len().is_power_of_two(),
+            "the number of leafs must be a power of two"
+        );
+        self.ssz_merkleize(leafs, leafs.len())
+    }
+
+    /// Returns the root of a container from the roots of its fields, in order.
+    pub fn ssz_container_root(&mut self, field_roots: &[Bytes32Variable]) -> Bytes32Variable {
+        assert!(!field_roots.is_empty(), "a container must have a field");
+        self.ssz_merkleize(field_roots, field_roots.len())
+    }
+
+    /// Returns the root of an SSZ `Vector` of composite values.
+    pub fn ssz_vector_root<V: SSZVariable>(&mut self, elements: &[V]) -> Bytes32Variable {
+        let roots = elements
+            .iter()
+            .map(|element| element.hash_tree_root(self))
+            .collect_vec();
+        self.ssz_merkleize(&roots, elements.len())
+    }
+
+    /// Returns the root of an SSZ `Vector` of basic values.
+    pub fn ssz_basic_vector_root<V: SSZBasicVariable>(
+        &mut self,
+        elements: &[V],
+    ) -> Bytes32Variable {
+        let chunks = self.ssz_pack(elements);
+        let limit = ssz_packed_chunks(elements.len(), V::SSZ_SIZE);
+        self.ssz_merkleize(&chunks, limit)
+    }
+
+    /// Returns the flags of the indices `0..n` that are at least `len`, asserting that `len` is at
+    /// most `n`.
+    fn ssz_past_length(&mut self, len: U64Variable, n: usize) -> Vec<BoolVariable> {
+        let n_v = self.constant::<U64Variable>(n as u64);
+        let in_bounds = self.lte(len, n_v);
+        let true_v = self._true();
+        self.assert_is_equal(in_bounds, true_v);
+
+        let mut past = self._false();
+        (0..n)
+            .map(|i| {
+                let i_v = self.constant::<U64Variable>(i as u64);
+                let is_len = self.is_equal(len, i_v);
+                past = self.or(past, is_len);
+                past
+            })
+            .collect()
+    }
+
+    /// Returns the root of an SSZ `List[V, limit]` of composite values whose items are the first
+    /// `len` elements of `items`. The elements past the length are ignored.
+    pub fn ssz_list_root<V: SSZVariable>(
+        &mut self,
+        items: &[V],
+        len: U64Variable,
+        limit: usize,
+    ) -> Bytes32Variable {
+        assert!(items.len() <= limit, "the list must fit in its limit");
+        let past = self.ssz_past_length(len, items.len());
+        let zero = self.constant::<Bytes32Variable>([0u8; 32].into());
+        let roots = items
+            .iter()
+            .zip(past)
+            .map(|(item, is_past)| {
+                let root = item.hash_tree_root(self);
+                self.select(is_past, zero, root)
+            })
+            .collect_vec();
+        let items_root = self.ssz_merkleize(&roots, limit);
+        self.ssz_mix_in_length(items_root, len)
+    }
+
+    /// Returns the root of an SSZ `List[V, limit]` of basic values whose items are the first `len`
+    /// elements of `items`. The elements past the length are ignored.
+    pub fn ssz_basic_list_root<V: SSZBasicVariable>(
+        &mut self,
+        items: &[V],
+        len: U64Variable,
+        limit: usize,
+    ) -> Bytes32Variable {
+        assert!(items.len() <= limit, "the list must fit in its limit");
+        let past = self.ssz_past_length(len, items.len());
+        let zero = self.constant::<ByteVariable>(0);
+        let bytes = items
+            .iter()
+            .zip(past)
+            .flat_map(|(item, is_past)| {
+                item.ssz_serialize(self)
+                    .into_iter()
+                    .map(|byte| self.select(is_past, zero, byte))
+                    .collect_vec()
+            })
+            .collect_vec();
+        let chunks = self.ssz_pack(&bytes);
+        let items_root = self.ssz_merkleize(&chunks, ssz_packed_chunks(limit, V::SSZ_SIZE));
+        self.ssz_mix_in_length(items_root, len)
+    }
+
+    /// Returns the root of an SSZ `Bitvector`.
+    pub fn ssz_bitvector_root(&mut self, bits: &[BoolVariable]) -> Bytes32Variable {
+        let chunks = self.ssz_pack_bits(bits);
+        let limit = bits.len().div_ceil(SSZ_CHUNK_BITS);
+        self.ssz_merkleize(&chunks, limit)
+    }
+
+    /// Returns the root of an SSZ `Bitlist[limit]` whose bits are the first `len` elements of
+    /// `bits`. The elements past the length are ignored.
+    pub fn ssz_bitlist_root(
+        &mut self,
+        bits: &[BoolVariable],
+        len: U64Variable,
+        limit: usize,
+    ) -> Bytes32Variable {
+        assert!(bits.len() <= limit, "the bitlist must fit in its limit");
+        let past = self.ssz_past_length(len, bits.len());
+        let masked = bits
+            .iter()
+            .zip(past)
+            .map(|(bit, is_past)| {
+                let not_past = self.not(is_past);
+                self.and(*bit, not_past)
+            })
+            .collect_vec();
+        let chunks = self.ssz_pack_bits(&masked);
+        let items_root = self.ssz_merkleize(&chunks, limit.div_ceil(SSZ_CHUNK_BITS));
+        self.ssz_mix_in_length(items_root, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+    use crate::utils::bytes32;
+
+    fn merkleize(chunks: &[[u8; 32]], limit: usize) -> [u8; 32] {
+        let depth = limit.next_power_of_two().trailing_zeros() as usize;
+        let zero_hashes = ssz_zero_hashes(depth);
+        let mut layer = chunks.to_vec();
+        for zero_hash in zero_hashes.iter().take(depth) {
+            if layer.len() % 2 == 1 {
+                layer.push(*zero_hash);
+            }
+            layer = layer
+                .chunks_exact(2)
+                .map(|pair| sha256(&pair.concat()))
+                .collect();
+        }
+        layer.first().copied().unwrap_or(zero_hashes[depth])
+    }
+
+    fn mix_in_length(root: [u8; 32], len: u64) -> [u8; 32] {
+        let mut length_chunk = [0u8; 32];
+        length_chunk[..8].copy_from_slice(&len.to_le_bytes());
+        sha256(&[root, length_chunk].concat())
+    }
+
+    fn pack(bytes: &[u8]) -> Vec<[u8; 32]> {
+        bytes
+            .chunks(32)
+            .map(|chunk| {
+                let mut padded = [0u8; 32];
+                padded[..chunk.len()].copy_from_slice(chunk);
+                padded
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ssz_zero_hashes() {
+        let zero_hashes = ssz_zero_hashes(1);
+        assert_eq!(
+            H256::from(zero_hashes[1]),
+            bytes32!("f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b")
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ssz_list_root() {
+        const LIMIT: usize = 10;
+        let values = [1u64, 2, 3, 4, 5, 6];
+        let len = 5;
+
+        let mut builder = DefaultBuilder::new();
+        let items = values.map(|value| builder.constant::<U64Variable>(value));
+        let len_variable = builder.read::<U64Variable>();
+        let list_root = builder.ssz_basic_list_root(&items, len_variable, LIMIT);
+        let vector_root = builder.ssz_basic_vector_root(&items);
+        let container_root = builder.ssz_container_root(&[list_root, vector_root]);
+        builder.write(list_root);
+        builder.write(container_root);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<U64Variable>(len);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let serialize = |values: &[u64]| {
+            values
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect::<Vec<_>>()
+        };
+        let items_root = merkleize(&pack(&serialize(&values[..len as usize])), 3);
+        let expected_list_root = mix_in_length(items_root, len);
+        let expected_vector_root = merkleize(&pack(&serialize(&values)), 2);
+        let expected_container_root = merkleize(&[expected_list_root, expected_vector_root], 2);
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            H256::from(expected_list_root)
+        );
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            H256::from(expected_container_root)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ssz_bitlist_root() {
+        const LIMIT: usize = 2048;
+        let bits = [
+            true, false, true, true, false, false, true, true, true, true,
+        ];
+        let len = 9;
+
+        let mut builder = DefaultBuilder::new();
+        let bit_variables = bits.map(|bit| builder.constant::<BoolVariable>(bit));
+        let len_variable = builder.read::<U64Variable>();
+        let bitlist_root = builder.ssz_bitlist_root(&bit_variables, len_variable, LIMIT);
+        let bitvector_root = builder.ssz_bitvector_root(&bit_variables);
+        builder.write(bitlist_root);
+        builder.write(bitvector_root);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<U64Variable>(len);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let pack_bits = |bits: &[bool]| {
+            let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+            for (i, bit) in bits.iter().enumerate() {
+                bytes[i / 8] |= (*bit as u8) << (i % 8);
+            }
+            pack(&bytes)
+        };
+        let items_root = merkleize(&pack_bits(&bits[..len as usize]), LIMIT / 256);
+        let expected_bitlist_root = mix_in_length(items_root, len);
+        let expected_bitvector_root = merkleize(&pack_bits(&bits), 1);
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            H256::from(expected_bitlist_root)
+        );
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            H256::from(expected_bitvector_root)
+        );
+    }
+}
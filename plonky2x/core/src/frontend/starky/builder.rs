@@ -0,0 +1,257 @@
+use core::marker::PhantomData;
+
+use plonky2::fri::FriParams;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use starky::config::StarkConfig;
+use starky::proof::StarkProofWithPublicInputsTarget;
+use starky::recursive_verifier::verify_stark_proof_circuit;
+use starky::stark::Stark;
+
+use super::hint::{StarkyProofHint, StarkyProver};
+use super::proof::StarkyProofVariable;
+use crate::prelude::*;
+
+/// The stark, config and trace length of the proofs to verify.
+#[derive(Debug, Clone)]
+pub struct StarkyParameters<S, C> {
+    pub stark: S,
+    pub config: StarkConfig,
+    pub degree_bits: usize,
+    _phantom: PhantomData<C>,
+}
+
+impl<S, C> StarkyParameters<S, C> {
+    pub fn new(stark: S, config: StarkConfig, degree_bits: usize) -> Self {
+        Self {
+            stark,
+            config,
+            degree_bits,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn fri_params(&self) -> FriParams {
+        self.config.fri_params(self.degree_bits)
+    }
+
+    /// The number of polynomials in each oracle opened by the FRI proof: the trace, then the
+    /// quotient polynomials.
+    pub fn num_leaves_per_oracle<F, const D: usize>(&self) -> Vec<usize>
+    where
+        F: RichField + Extendable<D>,
+        S: Stark<F, D>,
+    {
+        vec![S::COLUMNS, self.num_quotient_polys::<F, D>()]
+    }
+
+    pub fn num_quotient_polys<F, const D: usize>(&self) -> usize
+    where
+        F: RichField + Extendable<D>,
+        S: Stark<F, D>,
+    {
+        self.stark.quotient_degree_factor() * self.config.num_challenges
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Verifies a starky proof, whose public inputs are in `proof.public_inputs`.
+    pub fn verify_starky_proof<S, C>(
+        &mut self,
+        params: &StarkyParameters<S, C>,
+        proof: StarkyProofVariable<D>,
+    ) where
+        S: Stark<L::Field, D> + Clone,
+        C: GenericConfig<D, F = L::Field>,
+        C::Hasher: AlgebraicHasher<L::Field>,
+    {
+        assert!(
+            !params.stark.uses_lookups(),
+            "starks with lookups are not supported"
+        );
+        assert_eq!(proof.public_inputs.len(), S::PUBLIC_INPUTS);
+        let proof_with_pis = StarkProofWithPublicInputsTarget::from(proof);
+        verify_stark_proof_circuit::<L::Field, C, S, D>(
+            &mut self.api,
+            params.stark.clone(),
+            proof_with_pis,
+            &params.config,
+        );
+    }
+
+    /// Proves a stark with `prover` during witness generation, given the values of `input_stream`,
+    /// and verifies the proof. Returns the public inputs of the proof.
+    pub fn prove_starky<P: StarkyProver<L, D>>(
+        &mut self,
+        prover: P,
+        params: &StarkyParameters<P::Stark, L::Config>,
+        input_stream: VariableStream,
+    ) -> Vec<Variable>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    {
+        let output_stream = self.hint(input_stream, StarkyProofHint { prover });
+        let proof = output_stream.read_starky_proof(self, params);
+        let public_inputs = proof.public_inputs.clone();
+        self.verify_starky_proof(params, proof);
+        public_inputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::extension::FieldExtension;
+    use plonky2::field::packed::PackedField;
+    use plonky2::iop::ext_target::ExtensionTarget;
+    use plonky2::plonk::circuit_builder::CircuitBuilder as Plonky2Builder;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+    use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+    use starky::evaluation_frame::{StarkEvaluationFrame, StarkFrame};
+    use starky::proof::StarkProofWithPublicInputs;
+    use starky::prover::prove;
+    use starky::util::trace_rows_to_poly_values;
+
+    use super::*;
+    use crate::backend::circuit::{GateRegistry, HintRegistry};
+    use crate::frontend::starky::StarkyProver;
+
+    type L = DefaultParameters;
+    type C = <L as PlonkParameters<D>>::Config;
+    type F = GoldilocksField;
+    const D: usize = 2;
+
+    /// Proves that the last row of a trace of `n` rows `[x_i, x_{i+1}]` of a Fibonacci sequence is
+    /// `[x_{n-1}, x_n]`, with the public inputs `x_0`, `x_1` and `x_n`.
+    #[derive(Debug, Clone, Copy)]
+    struct FibonacciStark;
+
+    impl Stark<F, D> for FibonacciStark {
+        type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, 2, 3>
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+
+        type EvaluationFrameTarget = StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, 2, 3>;
+
+        fn eval_packed_generic<FE, P, const D2: usize>(
+            &self,
+            vars: &Self::EvaluationFrame<FE, P, D2>,
+            yield_constr: &mut ConstraintConsumer<P>,
+        ) where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>,
+        {
+            let local_values = vars.get_local_values();
+            let next_values = vars.get_next_values();
+            let public_inputs = vars.get_public_inputs();
+
+            yield_constr.constraint_first_row(local_values[0] - public_inputs[0]);
+            yield_constr.constraint_first_row(local_values[1] - public_inputs[1]);
+            yield_constr.constraint_last_row(local_values[1] - public_inputs[2]);
+            yield_constr.constraint_transition(next_values[0] - local_values[1]);
+            yield_constr.constraint_transition(next_values[1] - local_values[0] - local_values[1]);
+        }
+
+        fn eval_ext_circuit(
+            &self,
+            builder: &mut Plonky2Builder<F, D>,
+            vars: &Self::EvaluationFrameTarget,
+            yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        ) {
+            let local_values = vars.get_local_values();
+            let next_values = vars.get_next_values();
+            let public_inputs = vars.get_public_inputs();
+
+            let x0 = builder.sub_extension(local_values[0], public_inputs[0]);
+            yield_constr.constraint_first_row(builder, x0);
+            let x1 = builder.sub_extension(local_values[1], public_inputs[1]);
+            yield_constr.constraint_first_row(builder, x1);
+            let result = builder.sub_extension(local_values[1], public_inputs[2]);
+            yield_constr.constraint_last_row(builder, result);
+            let shift = builder.sub_extension(next_values[0], local_values[1]);
+            yield_constr.constraint_transition(builder, shift);
+            let sum = builder.add_extension(local_values[0], local_values[1]);
+            let sum = builder.sub_extension(next_values[1], sum);
+            yield_constr.constraint_transition(builder, sum);
+        }
+
+        fn constraint_degree(&self) -> usize {
+            2
+        }
+    }
+
+    /// Proves the Fibonacci sequence starting with the two values of the input stream.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct FibonacciProver {
+        num_rows: usize,
+    }
+
+    impl FibonacciProver {
+        fn config() -> StarkConfig {
+            StarkConfig::standard_fast_config()
+        }
+    }
+
+    impl StarkyProver<L, D> for FibonacciProver {
+        type Stark = FibonacciStark;
+
+        fn prove(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+        ) -> StarkProofWithPublicInputs<F, C, D> {
+            let x0 = input_stream.read_value::<Variable>();
+            let x1 = input_stream.read_value::<Variable>();
+            let rows = (0..self.num_rows)
+                .scan([x0, x1], |row, _| {
+                    let current = *row;
+                    *row = [current[1], current[0] + current[1]];
+                    Some(current)
+                })
+                .collect::<Vec<_>>();
+            let public_inputs = [x0, x1, rows[self.num_rows - 1][1]];
+            prove::<F, C, _, D>(
+                FibonacciStark,
+                &Self::config(),
+                trace_rows_to_poly_values(rows),
+                &public_inputs,
+                &mut TimingTree::default(),
+            )
+            .unwrap()
+        }
+    }
+
+    #[test]
+    fn test_prove_starky() {
+        let degree_bits = 5;
+        let num_rows = 1 << degree_bits;
+        let config = FibonacciProver::config();
+        let params = StarkyParameters::<_, C>::new(FibonacciStark, config, degree_bits);
+
+        let mut builder = DefaultBuilder::new();
+        let x0 = builder.read::<Variable>();
+        let x1 = builder.read::<Variable>();
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&x0);
+        input_stream.write(&x1);
+        let public_inputs =
+            builder.prove_starky(FibonacciProver { num_rows }, &params, input_stream);
+
+        // The proof must be about the inputs of the circuit.
+        builder.assert_is_equal(public_inputs[0], x0);
+        builder.assert_is_equal(public_inputs[1], x1);
+        builder.write(public_inputs[2]);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(F::ZERO);
+        input.write::<Variable>(F::ONE);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<Variable>(), F::from_canonical_u64(2178309));
+
+        // The circuit only serializes once the prover is registered.
+        let mut hint_registry = HintRegistry::<L, D>::new();
+        hint_registry.register_starky_prover::<FibonacciProver>();
+        circuit.test_serializers(&GateRegistry::new(), &hint_registry);
+    }
+}
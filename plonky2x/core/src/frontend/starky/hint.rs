@@ -0,0 +1,42 @@
+use core::fmt::Debug;
+
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use starky::proof::StarkProofWithPublicInputs;
+use starky::stark::Stark;
+
+use crate::frontend::hint::simple::hint::Hint;
+use crate::prelude::*;
+
+/// A prover of starky proofs, run during witness generation of the circuit verifying them.
+///
+/// The prover reads the values written to the input stream of `CircuitBuilder::prove_starky` and
+/// returns a proof of `Self::Stark`. Circuits using a prover can only be serialized with a
+/// `HintRegistry` where it was added with `HintRegistry::register_starky_prover`.
+pub trait StarkyProver<L: PlonkParameters<D>, const D: usize>:
+    'static + Debug + Clone + Send + Sync + Serialize + DeserializeOwned
+{
+    type Stark: Stark<L::Field, D> + Clone;
+
+    fn prove(
+        &self,
+        input_stream: &mut ValueStream<L, D>,
+    ) -> StarkProofWithPublicInputs<L::Field, L::Config, D>;
+}
+
+/// The hint writing the proof generated by a `StarkyProver`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StarkyProofHint<P> {
+    pub prover: P,
+}
+
+impl<L: PlonkParameters<D>, const D: usize, P: StarkyProver<L, D>> Hint<L, D> for StarkyProofHint<P>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+{
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let proof = self.prover.prove(input_stream);
+        output_stream.write_starky_proof(proof);
+    }
+}
@@ -0,0 +1,19 @@
+//! Verification of standalone starky proofs.
+//!
+//! A stark proven outside of plonky2x, e.g. by a custom prover running in a hint, is verified with
+//! plonky2's recursive starky verifier. The shape of its proof is fixed by `StarkyParameters`,
+//! which must be known when building the circuit. Starks using lookups or cross-table lookups are
+//! not supported.
+//!
+//! A `StarkyProver` generates the proof in a hint with `CircuitBuilder::prove_starky`. Its hint is
+//! not part of the default `HintRegistry`, since the stark is defined outside of plonky2x, and is
+//! added with `HintRegistry::register_starky_prover`. The verifier only uses the gates and
+//! generators of plonky2, which the default registries already contain.
+
+pub mod builder;
+pub mod hint;
+pub mod proof;
+
+pub use builder::StarkyParameters;
+pub use hint::{StarkyProofHint, StarkyProver};
+pub use proof::{StarkyOpeningSetVariable, StarkyProofVariable};
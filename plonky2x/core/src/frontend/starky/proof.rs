@@ -0,0 +1,171 @@
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use starky::proof::{
+    StarkOpeningSet, StarkOpeningSetTarget, StarkProof, StarkProofTarget,
+    StarkProofWithPublicInputs, StarkProofWithPublicInputsTarget,
+};
+use starky::stark::Stark;
+
+use super::builder::StarkyParameters;
+use crate::frontend::recursion::extension::ExtensionVariable;
+use crate::frontend::recursion::fri::proof::FriProofVariable;
+use crate::frontend::recursion::hash::MerkleCapVariable;
+use crate::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarkyProofVariable<const D: usize> {
+    pub trace_cap: MerkleCapVariable,
+    pub quotient_polys_cap: MerkleCapVariable,
+    pub openings: StarkyOpeningSetVariable<D>,
+    pub opening_proof: FriProofVariable<D>,
+    pub public_inputs: Vec<Variable>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarkyOpeningSetVariable<const D: usize> {
+    pub local_values: Vec<ExtensionVariable<D>>,
+    pub next_values: Vec<ExtensionVariable<D>>,
+    pub quotient_polys: Vec<ExtensionVariable<D>>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> OutputVariableStream<L, D> {
+    pub fn read_starky_proof<S, C>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        params: &StarkyParameters<S, C>,
+    ) -> StarkyProofVariable<D>
+    where
+        S: Stark<L::Field, D>,
+    {
+        let fri_params = params.fri_params();
+        let cap_height = fri_params.config.cap_height;
+
+        let trace_cap = self.read_merkle_cap(builder, cap_height);
+        let quotient_polys_cap = self.read_merkle_cap(builder, cap_height);
+        let openings = StarkyOpeningSetVariable {
+            local_values: self.read_vec::<ExtensionVariable<D>>(builder, S::COLUMNS),
+            next_values: self.read_vec::<ExtensionVariable<D>>(builder, S::COLUMNS),
+            quotient_polys: self.read_vec::<ExtensionVariable<D>>(
+                builder,
+                params.num_quotient_polys::<L::Field, D>(),
+            ),
+        };
+        let opening_proof = self.read_fri_proof(
+            builder,
+            &params.num_leaves_per_oracle::<L::Field, D>(),
+            &fri_params,
+        );
+        let public_inputs = self.read_vec::<Variable>(builder, S::PUBLIC_INPUTS);
+
+        StarkyProofVariable {
+            trace_cap,
+            quotient_polys_cap,
+            openings,
+            opening_proof,
+            public_inputs,
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> ValueStream<L, D> {
+    pub fn read_starky_proof<S, C>(
+        &mut self,
+        params: &StarkyParameters<S, C>,
+    ) -> StarkProofWithPublicInputs<L::Field, C, D>
+    where
+        S: Stark<L::Field, D>,
+        C: GenericConfig<D, F = L::Field>,
+        C::Hasher: AlgebraicHasher<L::Field>,
+    {
+        let fri_params = params.fri_params();
+        let cap_height = fri_params.config.cap_height;
+
+        let trace_cap = self.read_merkle_cap(cap_height);
+        let quotient_polys_cap = self.read_merkle_cap(cap_height);
+        let openings = StarkOpeningSet {
+            local_values: self.read_extension_vec(S::COLUMNS),
+            next_values: self.read_extension_vec(S::COLUMNS),
+            auxiliary_polys: None,
+            auxiliary_polys_next: None,
+            ctl_zs_first: None,
+            quotient_polys: self.read_extension_vec(params.num_quotient_polys::<L::Field, D>()),
+        };
+        let opening_proof =
+            self.read_fri_proof(&params.num_leaves_per_oracle::<L::Field, D>(), &fri_params);
+        let public_inputs = self.read_exact(S::PUBLIC_INPUTS).to_vec();
+
+        StarkProofWithPublicInputs {
+            proof: StarkProof {
+                trace_cap,
+                auxiliary_polys_cap: None,
+                quotient_polys_cap,
+                openings,
+                opening_proof,
+            },
+            public_inputs,
+        }
+    }
+
+    /// Writes a starky proof in the layout read by `OutputVariableStream::read_starky_proof`.
+    pub fn write_starky_proof<C>(&mut self, proof: StarkProofWithPublicInputs<L::Field, C, D>)
+    where
+        C: GenericConfig<D, F = L::Field>,
+        C::Hasher: AlgebraicHasher<L::Field>,
+    {
+        let StarkProofWithPublicInputs {
+            proof,
+            public_inputs,
+        } = proof;
+        let StarkProof {
+            trace_cap,
+            auxiliary_polys_cap,
+            quotient_polys_cap,
+            openings,
+            opening_proof,
+        } = proof;
+        assert!(
+            auxiliary_polys_cap.is_none(),
+            "starks with lookups are not supported"
+        );
+
+        self.write_merkle_cap(trace_cap);
+        self.write_merkle_cap(quotient_polys_cap);
+        self.write_extension_vec(openings.local_values);
+        self.write_extension_vec(openings.next_values);
+        self.write_extension_vec(openings.quotient_polys);
+        self.write_fri_proof(opening_proof);
+        self.write_slice(&public_inputs);
+    }
+}
+
+impl<const D: usize> From<StarkyOpeningSetVariable<D>> for StarkOpeningSetTarget<D> {
+    fn from(value: StarkyOpeningSetVariable<D>) -> Self {
+        let local_values = value.local_values.into_iter().map(|v| v.into()).collect();
+        let next_values = value.next_values.into_iter().map(|v| v.into()).collect();
+        let quotient_polys = value.quotient_polys.into_iter().map(|v| v.into()).collect();
+        Self {
+            local_values,
+            next_values,
+            auxiliary_polys: None,
+            auxiliary_polys_next: None,
+            ctl_zs_first: None,
+            quotient_polys,
+        }
+    }
+}
+
+impl<const D: usize> From<StarkyProofVariable<D>> for StarkProofWithPublicInputsTarget<D> {
+    fn from(value: StarkyProofVariable<D>) -> Self {
+        let proof = StarkProofTarget {
+            trace_cap: value.trace_cap.into(),
+            auxiliary_polys_cap: None,
+            quotient_polys_cap: value.quotient_polys_cap.into(),
+            openings: value.openings.into(),
+            opening_proof: value.opening_proof.into(),
+        };
+        let public_inputs = value.public_inputs.into_iter().map(|v| v.0).collect();
+        Self {
+            proof,
+            public_inputs,
+        }
+    }
+}
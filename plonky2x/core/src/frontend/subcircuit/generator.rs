@@ -0,0 +1,115 @@
+use core::marker::PhantomData;
+
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartitionWitness, WitnessWrite};
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::proof::ProofWithPublicInputsTarget;
+use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
+
+use crate::backend::circuit::{CircuitSerializer, PublicInput};
+use crate::backend::prover::EnvProver;
+use crate::prelude::{CircuitVariable, PlonkParameters};
+
+/// Proves one call of a sub-circuit on the value of its input.
+#[derive(Debug, Clone)]
+pub struct SubCircuitGenerator<L, Input, Serializer, const D: usize>
+where
+    L: PlonkParameters<D>,
+    <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    Input: CircuitVariable,
+    Serializer: CircuitSerializer,
+{
+    /// The identifier for the compiled sub-circuit.
+    pub circuit_id: String,
+
+    /// The input of the call.
+    pub input: Input,
+
+    /// The proof target for the sub-circuit proof.
+    pub proof: ProofWithPublicInputsTarget<D>,
+
+    /// Phantom data.
+    pub _phantom: PhantomData<(L, Serializer)>,
+}
+
+impl<L, Input, Serializer, const D: usize> SubCircuitGenerator<L, Input, Serializer, D>
+where
+    L: PlonkParameters<D>,
+    <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    Input: CircuitVariable,
+    Serializer: CircuitSerializer,
+{
+    pub fn id() -> String {
+        "SubCircuitGenerator".to_string()
+    }
+}
+
+impl<L, Input, Serializer, const D: usize> SimpleGenerator<L::Field, D>
+    for SubCircuitGenerator<L, Input, Serializer, D>
+where
+    L: PlonkParameters<D>,
+    <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    Input: CircuitVariable,
+    Serializer: CircuitSerializer,
+{
+    fn id(&self) -> String {
+        Self::id()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.input.targets()
+    }
+
+    fn run_once(
+        &self,
+        witness: &PartitionWitness<L::Field>,
+        out_buffer: &mut GeneratedValues<L::Field>,
+    ) {
+        let mut input = PublicInput::<L, D>::Elements(Vec::new());
+        input.write::<Input>(self.input.get(witness));
+
+        let (proof, _) = EnvProver::new()
+            .prove::<L, Serializer, D>(&self.circuit_id, &input)
+            .unwrap()
+            .materialize()
+            .unwrap();
+        out_buffer.set_proof_with_pis_target(&self.proof, &proof);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _: &CommonCircuitData<L::Field, D>) -> IoResult<()> {
+        // Write circuit id.
+        dst.write_usize(self.circuit_id.len())?;
+        dst.write_all(self.circuit_id.as_bytes())?;
+
+        // Write input.
+        dst.write_target_vec(&self.input.targets())?;
+
+        // Write proof target.
+        dst.write_target_proof_with_public_inputs(&self.proof)
+    }
+
+    fn deserialize(src: &mut Buffer, _: &CommonCircuitData<L::Field, D>) -> IoResult<Self> {
+        // Read circuit id.
+        let circuit_id_length = src.read_usize()?;
+        let mut circuit_id = vec![0u8; circuit_id_length];
+        src.read_exact(&mut circuit_id)?;
+
+        // Read input.
+        let input = Input::from_targets(&src.read_target_vec()?);
+
+        // Read proof target.
+        let proof = src.read_target_proof_with_public_inputs()?;
+
+        Ok(Self {
+            circuit_id: String::from_utf8(circuit_id).unwrap(),
+            input,
+            proof,
+            _phantom: PhantomData,
+        })
+    }
+}
@@ -0,0 +1,160 @@
+//! Memoized sub-circuits for gadgets that are instantiated many times.
+//!
+//! A circuit that repeats a heavy gadget hundreds of times pays for its gates on every use. As an
+//! opt-in alternative, the gadget can be compiled once into its own circuit with
+//! [`CircuitBuilder::subcircuit`], after which every [`CircuitBuilder::call_subcircuit`] costs one
+//! recursive verification instead of a fresh copy of the gadget. The inner proofs are generated
+//! during witness generation, so this only pays off when the gadget is larger than the recursive
+//! verifier.
+//!
+//! The public inputs of the inner circuit are the input of the gadget followed by its output. Each
+//! call connects the former to the variables passed by the caller and returns the latter.
+
+pub mod generator;
+
+use core::marker::PhantomData;
+
+use log::debug;
+use plonky2::plonk::circuit_data::{CommonCircuitData, VerifierCircuitTarget};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+
+use self::generator::SubCircuitGenerator;
+use crate::backend::circuit::CircuitSerializer;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::vars::CircuitVariable;
+use crate::prelude::PlonkParameters;
+use crate::utils::proof::ProofWithPublicInputsTargetUtils;
+
+/// A gadget compiled into its own circuit, which can be verified any number of times inside the
+/// builder that created it.
+#[derive(Debug, Clone)]
+pub struct SubCircuit<L: PlonkParameters<D>, Input, Output, Serializer, const D: usize> {
+    /// The identifier of the compiled circuit, saved to the build folder.
+    pub circuit_id: String,
+
+    /// The common data of the compiled circuit.
+    common: CommonCircuitData<L::Field, D>,
+
+    /// The verifier data of the compiled circuit, as constants of the outer circuit.
+    verifier_data: VerifierCircuitTarget,
+
+    /// Phantom data.
+    _phantom: PhantomData<(Input, Output, Serializer)>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Compiles `gadget` into its own circuit and saves it to the build folder, so that it can be
+    /// verified recursively with [`CircuitBuilder::call_subcircuit`].
+    pub fn subcircuit<Input, Output, Serializer, GadgetFn>(
+        &mut self,
+        gadget: GadgetFn,
+    ) -> SubCircuit<L, Input, Output, Serializer, D>
+    where
+        Input: CircuitVariable,
+        Output: CircuitVariable,
+        Serializer: CircuitSerializer,
+        GadgetFn: FnOnce(Input, &mut CircuitBuilder<L, D>) -> Output,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        builder.beacon_client = self.beacon_client.clone();
+        builder.execution_client = self.execution_client.clone();
+
+        let input = builder.read::<Input>();
+        let output = gadget(input, &mut builder);
+        builder.write(output);
+        let circuit = builder.build();
+
+        // Save the circuit so that the generators can load it to prove each call.
+        let circuit_id = circuit.id();
+        let gate_serializer = Serializer::gate_registry::<L, D>();
+        let generator_serializer = Serializer::generator_registry::<L, D>();
+        circuit.save(
+            &format!("./build/{}.circuit", circuit_id),
+            &gate_serializer,
+            &generator_serializer,
+        );
+        debug!("succesfully built subcircuit: id={}", circuit_id);
+
+        let verifier_data = self.constant_verifier_data::<L>(&circuit.data);
+        SubCircuit {
+            circuit_id,
+            common: circuit.data.common,
+            verifier_data,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Applies a compiled gadget to `input` by verifying a proof of the inner circuit.
+    pub fn call_subcircuit<Input, Output, Serializer>(
+        &mut self,
+        subcircuit: &SubCircuit<L, Input, Output, Serializer, D>,
+        input: Input,
+    ) -> Output
+    where
+        Input: CircuitVariable,
+        Output: CircuitVariable,
+        Serializer: CircuitSerializer,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let proof = self.add_virtual_proof_with_pis(&subcircuit.common);
+        let generator = SubCircuitGenerator::<L, Input, Serializer, D> {
+            circuit_id: subcircuit.circuit_id.clone(),
+            input: input.clone(),
+            proof: proof.clone(),
+            _phantom: PhantomData,
+        };
+        self.add_simple_generator(generator);
+
+        self.verify_proof::<L>(&proof, &subcircuit.verifier_data, &subcircuit.common);
+
+        // Bind the proof to the input of this call.
+        let proof_input = proof.read_start_from_pis::<Input>();
+        self.assert_is_equal(proof_input, input);
+
+        proof.read_end_from_pis::<Output>()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use plonky2::field::types::Field;
+
+    use crate::backend::circuit::DefaultSerializer;
+    use crate::prelude::*;
+
+    type L = DefaultParameters;
+    type F = <L as PlonkParameters<D>>::Field;
+    const D: usize = 2;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_subcircuit() {
+        env_logger::try_init().unwrap_or_default();
+
+        let mut builder = DefaultBuilder::new();
+        let square_plus_one =
+            builder.subcircuit::<Variable, Variable, DefaultSerializer, _>(|x, builder| {
+                let square = builder.mul(x, x);
+                let one: Variable = builder.one();
+                builder.add(square, one)
+            });
+
+        let mut x = builder.read::<Variable>();
+        for _ in 0..3 {
+            x = builder.call_subcircuit(&square_plus_one, x);
+        }
+        builder.write(x);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<Variable>(F::from_canonical_u64(2));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        // 2 -> 5 -> 26 -> 677.
+        assert_eq!(output.read::<Variable>(), F::from_canonical_u64(677));
+    }
+}
@@ -0,0 +1,540 @@
+//! Verification of Tendermint (CometBFT) commits for light clients.
+//!
+//! A validator commits to a block by signing the sign bytes of its precommit vote, the length
+//! prefixed protobuf encoding of
+//!
+//! `CanonicalVote { type, height, round, block_id, timestamp, chain_id }`,
+//!
+//! where `height` and `round` are `sfixed64`, the `block_id` holds the hash of the header and the
+//! part set header of the block, and the `timestamp` is the one of the commit signature of the
+//! validator. As in protobuf, the integer fields equal to zero are omitted. A commit is valid if
+//! the validators that signed it for the block hold more than two thirds of the voting power.
+//!
+//! The signatures are verified in a batch by `ed25519_verify_batch`. The validators are not checked
+//! against the validators hash of a header, which is left to the caller.
+
+use itertools::Itertools;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+
+use crate::frontend::curta::ec::point::CompressedEdwardsYVariable;
+use crate::frontend::ecc::curve25519::ed25519::eddsa::EDDSASignatureVariable;
+use crate::prelude::*;
+
+/// The type of precommit votes.
+const PRECOMMIT_TYPE: u8 = 2;
+
+/// The protobuf tags of the fields of a canonical vote, its block id and its timestamp.
+const TYPE_TAG: u8 = 0x08;
+const HEIGHT_TAG: u8 = 0x11;
+const ROUND_TAG: u8 = 0x19;
+const BLOCK_ID_TAG: u8 = 0x22;
+const TIMESTAMP_TAG: u8 = 0x2a;
+const CHAIN_ID_TAG: u8 = 0x32;
+const HASH_TAG: u8 = 0x0a;
+const PART_SET_HEADER_TAG: u8 = 0x12;
+const PART_SET_TOTAL_TAG: u8 = 0x08;
+const PART_SET_HASH_TAG: u8 = 0x12;
+const SECONDS_TAG: u8 = 0x08;
+const NANOS_TAG: u8 = 0x10;
+
+/// The number of bits of the length prefix of the sign bytes, which is at most two bytes long.
+const LENGTH_PREFIX_BITS: usize = 14;
+
+/// A validator of the validator set, with its ed25519 public key.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(TendermintValidator)]
+pub struct TendermintValidatorVariable {
+    pub pubkey: CompressedEdwardsYVariable,
+    pub voting_power: U64Variable,
+}
+
+/// The id of a block: the hash of its header and its part set header.
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(TendermintBlockId)]
+pub struct TendermintBlockIdVariable {
+    pub hash: Bytes32Variable,
+    pub part_set_total: U32Variable,
+    pub part_set_hash: Bytes32Variable,
+}
+
+/// A timestamp, whose seconds are the two's complement of an `int64`.
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(TendermintTimestamp)]
+pub struct TendermintTimestampVariable {
+    pub seconds: U64Variable,
+    pub nanos: U32Variable,
+}
+
+/// The signature of a validator in a commit. The validators whose vote is not for the block, or
+/// absent, have `is_commit` unset and their signature is ignored.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(TendermintCommitSig)]
+pub struct TendermintCommitSigVariable {
+    pub is_commit: BoolVariable,
+    pub timestamp: TendermintTimestampVariable,
+    pub signature: EDDSASignatureVariable,
+}
+
+/// A piece of sign bytes of variable length, padded with zeros.
+type Piece = (Vec<ByteVariable>, Variable);
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    fn tendermint_constant(&mut self, value: usize) -> Variable {
+        self.constant::<Variable>(L::Field::from_canonical_usize(value))
+    }
+
+    fn tendermint_bytes(&mut self, bytes: &[u8]) -> Vec<ByteVariable> {
+        bytes
+            .iter()
+            .map(|byte| self.constant::<ByteVariable>(*byte))
+            .collect()
+    }
+
+    /// Encodes the unsigned integer with the little endian `bits` as a protobuf varint.
+    fn tendermint_varint(&mut self, bits: &[BoolVariable]) -> Piece {
+        let false_variable = self._false();
+        let groups = bits
+            .chunks(7)
+            .map(|group| {
+                let mut group = group.to_vec();
+                group.resize(7, false_variable);
+                group
+            })
+            .collect_vec();
+
+        // Every byte but the last has its high bit set, so the length is one plus the number of
+        // groups followed by a nonzero group.
+        let mut is_continued = vec![false_variable; groups.len()];
+        let mut higher_nonzero = false_variable;
+        for (i, group) in groups.iter().enumerate().rev() {
+            is_continued[i] = higher_nonzero;
+            for bit in group.iter() {
+                higher_nonzero = self.or(higher_nonzero, *bit);
+            }
+        }
+        let mut len = self.one::<Variable>();
+        let mut bytes = Vec::with_capacity(groups.len());
+        for (group, continued) in groups.iter().zip(is_continued) {
+            len = self.add(len, continued.variable);
+            let mut be_bits = [continued; 8];
+            for (j, bit) in group.iter().enumerate() {
+                be_bits[7 - j] = *bit;
+            }
+            bytes.push(ByteVariable::from_be_bits(be_bits));
+        }
+        (bytes, len)
+    }
+
+    /// Encodes a varint field, which is omitted if it is zero.
+    fn tendermint_varint_field(&mut self, tag: u8, bits: &[BoolVariable]) -> Piece {
+        let zero = self.zero::<Variable>();
+        let (varint, varint_len) = self.tendermint_varint(bits);
+        let mut is_zero = self._true();
+        for bit in bits.iter() {
+            let not_bit = self.not(*bit);
+            is_zero = self.and(is_zero, not_bit);
+        }
+        let mut field = vec![self.constant::<ByteVariable>(tag)];
+        field.extend(varint);
+        let one = self.one::<Variable>();
+        let field_len = self.add(varint_len, one);
+        (field, self.select(is_zero, zero, field_len))
+    }
+
+    /// Concatenates pieces of variable length into `MAX_LEN` bytes padded with zeros, and returns
+    /// the length of the concatenation, which must be at most `MAX_LEN`.
+    fn tendermint_concat<const MAX_LEN: usize>(
+        &mut self,
+        pieces: &[Piece],
+    ) -> (ArrayVariable<ByteVariable, MAX_LEN>, Variable) {
+        let true_variable = self._true();
+        let false_variable = self._false();
+        let zero_byte = self.constant::<ByteVariable>(0);
+        let mut bytes = vec![zero_byte; MAX_LEN];
+        let mut offset = self.zero::<Variable>();
+        for (piece, len) in pieces.iter() {
+            assert!(piece.len() <= MAX_LEN, "piece longer than the sign bytes");
+            let mut padded = piece.clone();
+            padded.resize(MAX_LEN, zero_byte);
+            let shifted =
+                self.rotate_right(ArrayVariable::<ByteVariable, MAX_LEN>::new(padded), offset);
+            let end = self.add(offset, *len);
+            let mut within_piece = false_variable;
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                let index = self.tendermint_constant(i);
+                let at_start = self.is_equal(index, offset);
+                let at_end = self.is_equal(index, end);
+                within_piece = self.select(at_start, true_variable, within_piece);
+                within_piece = self.select(at_end, false_variable, within_piece);
+                *byte = self.select(within_piece, shifted[i], *byte);
+            }
+            offset = end;
+        }
+
+        let mut fits = false_variable;
+        for i in 0..=MAX_LEN {
+            let index = self.tendermint_constant(i);
+            let at_end = self.is_equal(index, offset);
+            fits = self.or(fits, at_end);
+        }
+        self.assert_is_equal(fits, true_variable);
+        (ArrayVariable::new(bytes), offset)
+    }
+
+    /// Encodes the fields of a precommit vote that are shared by all the validators: its type,
+    /// height, round and block id.
+    fn tendermint_vote_body<const MAX_LEN: usize>(
+        &mut self,
+        height: U64Variable,
+        round: U32Variable,
+        block_id: &TendermintBlockIdVariable,
+    ) -> Piece {
+        let zero = self.zero::<Variable>();
+        let zero_byte = self.constant::<ByteVariable>(0);
+        let vote_type = self.tendermint_bytes(&[TYPE_TAG, PRECOMMIT_TYPE]);
+        let two = self.tendermint_constant(2);
+        let nine = self.tendermint_constant(9);
+
+        let mut height_field = vec![self.constant::<ByteVariable>(HEIGHT_TAG)];
+        for limb in height.limbs.iter() {
+            height_field.extend(limb.encode(self).into_iter().rev());
+        }
+
+        let mut round_field = vec![self.constant::<ByteVariable>(ROUND_TAG)];
+        round_field.extend(round.encode(self).into_iter().rev());
+        round_field.extend([zero_byte; 4]);
+        let round_zero = self.constant::<U32Variable>(0);
+        let is_round_zero = self.is_equal(round, round_zero);
+        let round_len = self.select(is_round_zero, zero, nine);
+
+        // The block id and its part set header are shorter than 128 bytes, so their lengths are
+        // one byte long.
+        let total_bits = self.to_le_bits(block_id.part_set_total);
+        let (total_field, total_len) =
+            self.tendermint_varint_field(PART_SET_TOTAL_TAG, &total_bits);
+        let const_34 = self.tendermint_constant(34);
+        let const_36 = self.tendermint_constant(36);
+        let part_set_header_len = self.add(total_len, const_34);
+        let block_id_len = self.add(part_set_header_len, const_36);
+        let mut block_id_start = vec![
+            self.constant::<ByteVariable>(BLOCK_ID_TAG),
+            ByteVariable::from_variable(self, block_id_len),
+        ];
+        block_id_start.extend(self.tendermint_bytes(&[HASH_TAG, 32]));
+        block_id_start.extend(block_id.hash.as_bytes());
+        block_id_start.push(self.constant::<ByteVariable>(PART_SET_HEADER_TAG));
+        block_id_start.push(ByteVariable::from_variable(self, part_set_header_len));
+        let block_id_start_len = self.tendermint_constant(block_id_start.len());
+        let mut part_set_hash = self.tendermint_bytes(&[PART_SET_HASH_TAG, 32]);
+        part_set_hash.extend(block_id.part_set_hash.as_bytes());
+
+        let (body, body_len) = self.tendermint_concat::<MAX_LEN>(&[
+            (vote_type, two),
+            (height_field, nine),
+            (round_field, round_len),
+            (block_id_start, block_id_start_len),
+            (total_field, total_len),
+            (part_set_hash, const_34),
+        ]);
+        (body.as_vec(), body_len)
+    }
+
+    /// Completes the shared fields of a precommit vote with the timestamp of a validator and the
+    /// chain id, and prefixes the encoding with its length.
+    fn tendermint_vote_sign_bytes_from_body<const MAX_LEN: usize>(
+        &mut self,
+        chain_id: &[u8],
+        body: &Piece,
+        timestamp: &TendermintTimestampVariable,
+    ) -> (ArrayVariable<ByteVariable, MAX_LEN>, Variable) {
+        let seconds_bits = timestamp
+            .seconds
+            .limbs
+            .iter()
+            .flat_map(|limb| self.to_le_bits(*limb))
+            .collect_vec();
+        let nanos_bits = self.to_le_bits(timestamp.nanos);
+        let (seconds, seconds_len) = self.tendermint_varint_field(SECONDS_TAG, &seconds_bits);
+        let (nanos, nanos_len) = self.tendermint_varint_field(NANOS_TAG, &nanos_bits);
+        let timestamp_len = self.add(seconds_len, nanos_len);
+        let timestamp_start = vec![
+            self.constant::<ByteVariable>(TIMESTAMP_TAG),
+            ByteVariable::from_variable(self, timestamp_len),
+        ];
+        let two = self.tendermint_constant(2);
+
+        let mut chain_id_field = self.tendermint_bytes(&[CHAIN_ID_TAG, chain_id.len() as u8]);
+        chain_id_field.extend(self.tendermint_bytes(chain_id));
+        let chain_id_len = self.tendermint_constant(chain_id_field.len());
+
+        let (payload, payload_len) = self.tendermint_concat::<MAX_LEN>(&[
+            body.clone(),
+            (timestamp_start, two),
+            (seconds, seconds_len),
+            (nanos, nanos_len),
+            (chain_id_field, chain_id_len),
+        ]);
+        let payload_len_bits = self.to_le_bits(payload_len);
+        let length_prefix = self.tendermint_varint(&payload_len_bits[..LENGTH_PREFIX_BITS]);
+        self.tendermint_concat::<MAX_LEN>(&[length_prefix, (payload.as_vec(), payload_len)])
+    }
+
+    /// Computes the sign bytes of a precommit vote for `block_id` at `height` and `round` with
+    /// the given timestamp, padded with zeros to `MAX_LEN` bytes, and their length.
+    pub fn tendermint_vote_sign_bytes<const MAX_LEN: usize>(
+        &mut self,
+        chain_id: &str,
+        height: U64Variable,
+        round: U32Variable,
+        block_id: &TendermintBlockIdVariable,
+        timestamp: &TendermintTimestampVariable,
+    ) -> (ArrayVariable<ByteVariable, MAX_LEN>, Variable) {
+        assert!(chain_id.len() < 128, "chain id too long");
+        assert!(MAX_LEN < 1 << LENGTH_PREFIX_BITS, "sign bytes too long");
+        let body = self.tendermint_vote_body::<MAX_LEN>(height, round, block_id);
+        self.tendermint_vote_sign_bytes_from_body::<MAX_LEN>(chain_id.as_bytes(), &body, timestamp)
+    }
+
+    /// Verifies a commit for `block_id` at `height` and `round`: the signatures of the validators
+    /// whose vote is for the block are checked, and these validators must hold more than two
+    /// thirds of the voting power of `validators`. The sign bytes must be at most `MAX_LEN` bytes
+    /// long, and the total voting power must fit in 62 bits, as the one of Tendermint does.
+    pub fn tendermint_verify_commit<const N: usize, const MAX_LEN: usize>(
+        &mut self,
+        chain_id: &str,
+        height: U64Variable,
+        round: U32Variable,
+        block_id: &TendermintBlockIdVariable,
+        validators: &ArrayVariable<TendermintValidatorVariable, N>,
+        commit_sigs: &ArrayVariable<TendermintCommitSigVariable, N>,
+    ) where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        assert!(chain_id.len() < 128, "chain id too long");
+        assert!(MAX_LEN < 1 << LENGTH_PREFIX_BITS, "sign bytes too long");
+        let true_variable = self._true();
+        let body = self.tendermint_vote_body::<MAX_LEN>(height, round, block_id);
+        let (dummy_pubkey, dummy_signature, dummy_message, dummy_message_len) =
+            self.get_dummy_variables::<MAX_LEN>();
+
+        let mut messages = Vec::with_capacity(N);
+        let mut message_lens = Vec::with_capacity(N);
+        let mut signatures = Vec::with_capacity(N);
+        let mut pubkeys = Vec::with_capacity(N);
+        let mut total_power = self.zero::<U64Variable>();
+        let mut signed_power = self.zero::<U64Variable>();
+        for (validator, commit_sig) in validators.as_vec().iter().zip(commit_sigs.as_vec()) {
+            // The validators that did not commit to the block verify a dummy signature instead.
+            let is_commit = commit_sig.is_commit;
+            let (message, message_len) = self.tendermint_vote_sign_bytes_from_body::<MAX_LEN>(
+                chain_id.as_bytes(),
+                &body,
+                &commit_sig.timestamp,
+            );
+            let message = BytesVariable::<MAX_LEN>(message.as_vec().try_into().unwrap());
+            let message_len = U32Variable::from_variables_unsafe(&[message_len]);
+            messages.push(self.select(is_commit, message, dummy_message));
+            message_lens.push(self.select(is_commit, message_len, dummy_message_len));
+            signatures.push(self.select(
+                is_commit,
+                commit_sig.signature.clone(),
+                dummy_signature.clone(),
+            ));
+            pubkeys.push(self.select(is_commit, validator.pubkey.clone(), dummy_pubkey.clone()));
+
+            total_power = self.add(total_power, validator.voting_power);
+            let zero_power = self.zero::<U64Variable>();
+            let power = self.select(is_commit, validator.voting_power, zero_power);
+            signed_power = self.add(signed_power, power);
+        }
+        self.ed25519_verify_batch::<MAX_LEN, N>(
+            ArrayVariable::new(messages),
+            Some(ArrayVariable::new(message_lens)),
+            ArrayVariable::new(signatures),
+            ArrayVariable::new(pubkeys),
+        );
+
+        let two = self.constant::<U64Variable>(2);
+        let three = self.constant::<U64Variable>(3);
+        let signed_power = self.mul(signed_power, three);
+        let total_power = self.mul(total_power, two);
+        let has_quorum = self.gt(signed_power, total_power);
+        self.assert_is_equal(has_quorum, true_variable);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::edwards::CompressedEdwardsY;
+    use ed25519_dalek::{Signer, SigningKey};
+    use ethers::types::{H256, U256};
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::frontend::ecc::curve25519::ed25519::eddsa::EDDSASignatureVariableValue;
+
+    const CHAIN_ID: &str = "mocha-4";
+    const MAX_LEN: usize = 128;
+    const NUM_VALIDATORS: usize = 4;
+    const HEIGHT: u64 = 1_234_567;
+
+    fn varint(mut value: u64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        while value >= 0x80 {
+            bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes.push(value as u8);
+        bytes
+    }
+
+    fn varint_field(tag: u8, value: u64) -> Vec<u8> {
+        if value == 0 {
+            return vec![];
+        }
+        [vec![tag], varint(value)].concat()
+    }
+
+    /// The sign bytes of a precommit, encoded as by the protobuf `CanonicalVote`.
+    fn sign_bytes(round: u32, block_id: &TendermintBlockId, seconds: i64, nanos: u32) -> Vec<u8> {
+        let mut part_set_header = varint_field(0x08, block_id.part_set_total as u64);
+        part_set_header.extend([0x12, 32]);
+        part_set_header.extend(block_id.part_set_hash.as_bytes());
+        let mut encoded_block_id = vec![0x0a, 32];
+        encoded_block_id.extend(block_id.hash.as_bytes());
+        encoded_block_id.extend([0x12, part_set_header.len() as u8]);
+        encoded_block_id.extend(part_set_header);
+        let timestamp = [
+            varint_field(0x08, seconds as u64),
+            varint_field(0x10, nanos as u64),
+        ]
+        .concat();
+
+        let mut payload = vec![0x08, 0x02, 0x11];
+        payload.extend(HEIGHT.to_le_bytes());
+        if round != 0 {
+            payload.push(0x19);
+            payload.extend((round as u64).to_le_bytes());
+        }
+        payload.extend([0x22, encoded_block_id.len() as u8]);
+        payload.extend(encoded_block_id);
+        payload.extend([0x2a, timestamp.len() as u8]);
+        payload.extend(timestamp);
+        payload.extend([0x32, CHAIN_ID.len() as u8]);
+        payload.extend(CHAIN_ID.as_bytes());
+        [varint(payload.len() as u64), payload].concat()
+    }
+
+    fn block_id() -> TendermintBlockId {
+        TendermintBlockId {
+            hash: H256::repeat_byte(0xab),
+            part_set_total: 1,
+            part_set_hash: H256::repeat_byte(0xcd),
+        }
+    }
+
+    #[test]
+    fn test_tendermint_vote_sign_bytes() {
+        let mut builder = DefaultBuilder::new();
+        let height = builder.constant::<U64Variable>(HEIGHT);
+        let round = builder.read::<U32Variable>();
+        let block_id = builder.read::<TendermintBlockIdVariable>();
+        let timestamp = builder.read::<TendermintTimestampVariable>();
+        let (bytes, len) = builder
+            .tendermint_vote_sign_bytes::<MAX_LEN>(CHAIN_ID, height, round, &block_id, &timestamp);
+        builder.write(bytes);
+        builder.write(len);
+        let circuit = builder.build();
+
+        // A vote at the first round, and one at a later round with a timestamp before 1970, whose
+        // seconds are a ten byte varint, and no nanoseconds.
+        for (round, seconds, nanos) in [(0, 1_700_000_000, 123_456_789), (3, -62_135_596_800, 0)] {
+            let mut input = circuit.input();
+            input.write::<U32Variable>(round);
+            input.write::<TendermintBlockIdVariable>(block_id());
+            input.write::<TendermintTimestampVariable>(TendermintTimestamp {
+                seconds: seconds as u64,
+                nanos,
+            });
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+
+            let expected = sign_bytes(round, &block_id(), seconds, nanos);
+            let mut padded = expected.clone();
+            padded.resize(MAX_LEN, 0);
+            assert_eq!(
+                output.read::<ArrayVariable<ByteVariable, MAX_LEN>>(),
+                padded
+            );
+            assert_eq!(
+                output.read::<Variable>(),
+                GoldilocksField::from_canonical_usize(expected.len())
+            );
+        }
+    }
+
+    fn verify_commit(voting_powers: [u64; NUM_VALIDATORS], is_commit: [bool; NUM_VALIDATORS]) {
+        let mut builder = DefaultBuilder::new();
+        let height = builder.constant::<U64Variable>(HEIGHT);
+        let round = builder.constant::<U32Variable>(0);
+        let block_id = builder.read::<TendermintBlockIdVariable>();
+        let validators =
+            builder.read::<ArrayVariable<TendermintValidatorVariable, NUM_VALIDATORS>>();
+        let commit_sigs =
+            builder.read::<ArrayVariable<TendermintCommitSigVariable, NUM_VALIDATORS>>();
+        builder.tendermint_verify_commit::<NUM_VALIDATORS, MAX_LEN>(
+            CHAIN_ID,
+            height,
+            round,
+            &block_id,
+            &validators,
+            &commit_sigs,
+        );
+        let circuit = builder.build();
+
+        let mut validator_values = Vec::new();
+        let mut commit_sig_values = Vec::new();
+        for i in 0..NUM_VALIDATORS {
+            let signing_key = SigningKey::generate(&mut OsRng);
+            let nanos = 1000 * i as u32;
+            let signature = signing_key.sign(&sign_bytes(0, &block_id(), 1_700_000_000, nanos));
+            validator_values.push(TendermintValidator {
+                pubkey: CompressedEdwardsY(signing_key.verifying_key().to_bytes()),
+                voting_power: voting_powers[i],
+            });
+            commit_sig_values.push(TendermintCommitSig {
+                is_commit: is_commit[i],
+                timestamp: TendermintTimestamp {
+                    seconds: 1_700_000_000,
+                    nanos,
+                },
+                signature: EDDSASignatureVariableValue {
+                    r: CompressedEdwardsY(*signature.r_bytes()),
+                    s: U256::from_little_endian(signature.s_bytes()),
+                },
+            });
+        }
+
+        let mut input = circuit.input();
+        input.write::<TendermintBlockIdVariable>(block_id());
+        input.write::<ArrayVariable<TendermintValidatorVariable, NUM_VALIDATORS>>(validator_values);
+        input
+            .write::<ArrayVariable<TendermintCommitSigVariable, NUM_VALIDATORS>>(commit_sig_values);
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_tendermint_verify_commit() {
+        verify_commit([10, 20, 30, 40], [false, true, true, true]);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    #[should_panic]
+    fn test_tendermint_verify_commit_without_quorum() {
+        verify_commit([10, 20, 30, 40], [true, true, true, false]);
+    }
+}
@@ -0,0 +1,302 @@
+use array_macro::array;
+use ethers::types::U256;
+use num::BigUint;
+use plonky2::hash::hash_types::RichField;
+
+use super::uint256::U256Variable;
+use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
+use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::{CircuitBuilderU32, U32Target};
+use crate::prelude::{
+    Add, CircuitBuilder, CircuitVariable, Div, Mul, PlonkParameters, Sub, Variable,
+};
+
+/// How the result of an inexact fixed-point operation is rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Towards zero.
+    Down,
+    /// Away from zero.
+    Up,
+    /// To the nearest representable value, with ties rounded up.
+    Nearest,
+}
+
+/// An unsigned fixed-point number with `FRAC_BITS` fractional bits, stored as the U256Variable
+/// `raw = value * 2^FRAC_BITS`.
+///
+/// The value of the variable is the raw U256. Additions and subtractions wrap as for U256Variable,
+/// while multiplications, divisions and conversions fail the circuit if the result does not fit.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedPointVariable<const FRAC_BITS: usize> {
+    pub raw: U256Variable,
+}
+
+impl<const FRAC_BITS: usize> CircuitVariable for FixedPointVariable<FRAC_BITS> {
+    type ValueType<F: RichField> = U256;
+
+    fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Self {
+            raw: U256Variable::init_unsafe(builder),
+        }
+    }
+
+    fn variables(&self) -> Vec<Variable> {
+        self.raw.variables()
+    }
+
+    fn from_variables_unsafe(variables: &[Variable]) -> Self {
+        Self {
+            raw: U256Variable::from_variables_unsafe(variables),
+        }
+    }
+
+    fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        self.raw.assert_is_valid(builder)
+    }
+
+    fn nb_elements() -> usize {
+        U256Variable::nb_elements()
+    }
+
+    fn elements<F: RichField>(value: U256) -> Vec<F> {
+        U256Variable::elements(value)
+    }
+
+    fn from_elements<F: RichField>(elements: &[F]) -> U256 {
+        U256Variable::from_elements(elements)
+    }
+}
+
+fn to_biguint(value: U256Variable) -> BigUintTarget {
+    BigUintTarget {
+        limbs: value.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+    }
+}
+
+/// Truncates `value` to a U256Variable, asserting that its higher limbs are zero.
+fn to_u256<L: PlonkParameters<D>, const D: usize>(
+    value: &BigUintTarget,
+    builder: &mut CircuitBuilder<L, D>,
+) -> U256Variable {
+    for limb in value.limbs.iter().skip(8) {
+        builder.api.assert_zero_u32(*limb);
+    }
+    let zero = builder.api.zero_u32();
+    U256Variable {
+        limbs: array![i => value.limbs.get(i).copied().unwrap_or(zero).into(); 8],
+    }
+}
+
+/// Divides `numerator` by the non-zero `divisor`, rounding the quotient with `rounding`.
+fn div_round<L: PlonkParameters<D>, const D: usize>(
+    numerator: &BigUintTarget,
+    divisor: &BigUintTarget,
+    rounding: Rounding,
+    builder: &mut CircuitBuilder<L, D>,
+) -> U256Variable {
+    let (quotient, remainder) =
+        builder
+            .api
+            ._div_rem_biguint(numerator, divisor, numerator.num_limbs());
+    let round_up = match rounding {
+        Rounding::Down => return to_u256(&quotient, builder),
+        Rounding::Up => {
+            let zero = builder.api.zero_biguint();
+            let is_exact = builder.api.is_equal_biguint(&remainder, &zero);
+            builder.api.not(is_exact)
+        }
+        Rounding::Nearest => {
+            let twice_remainder = builder.api.add_biguint(&remainder, &remainder);
+            builder.api.cmp_biguint(divisor, &twice_remainder)
+        }
+    };
+    let round_up = BigUintTarget {
+        limbs: vec![U32Target::from_target_unsafe(round_up.target)],
+    };
+    let rounded = builder.api.add_biguint(&quotient, &round_up);
+    to_u256(&rounded, builder)
+}
+
+impl<const FRAC_BITS: usize> FixedPointVariable<FRAC_BITS> {
+    fn scale<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BigUintTarget {
+        assert!(
+            FRAC_BITS < 256,
+            "fixed-point numbers have at most 255 fractional bits"
+        );
+        builder
+            .api
+            .constant_biguint(&(BigUint::from(1u32) << FRAC_BITS))
+    }
+
+    /// Converts an integer to a fixed-point number, failing the circuit if it does not fit.
+    pub fn from_u256<L: PlonkParameters<D>, const D: usize>(
+        value: U256Variable,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        let scale = Self::scale(builder);
+        let raw = builder.api.mul_biguint(&to_biguint(value), &scale);
+        Self {
+            raw: to_u256(&raw, builder),
+        }
+    }
+
+    /// Converts the number to an integer, rounding its fractional part with `rounding`.
+    pub fn to_u256<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rounding: Rounding,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> U256Variable {
+        let scale = Self::scale(builder);
+        div_round(&to_biguint(self.raw), &scale, rounding, builder)
+    }
+
+    /// Multiplies two fixed-point numbers, rounding the product with `rounding`.
+    pub fn mul_round<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        rounding: Rounding,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        let product = builder
+            .api
+            .mul_biguint(&to_biguint(self.raw), &to_biguint(rhs.raw));
+        let scale = Self::scale(builder);
+        Self {
+            raw: div_round(&product, &scale, rounding, builder),
+        }
+    }
+
+    /// Divides two fixed-point numbers, rounding the quotient with `rounding`. Fails the circuit if
+    /// `rhs` is zero.
+    pub fn div_round<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        rounding: Rounding,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        // Divide by one instead of zero so that the witness generation does not panic.
+        let zero = builder.zero::<U256Variable>();
+        let one = builder.one::<U256Variable>();
+        let is_zero = builder.is_equal(rhs.raw, zero);
+        let _false = builder._false();
+        builder.assert_is_equal(is_zero, _false);
+        let divisor = builder.select(is_zero, one, rhs.raw);
+
+        let scale = Self::scale(builder);
+        let numerator = builder.api.mul_biguint(&to_biguint(self.raw), &scale);
+        Self {
+            raw: div_round(&numerator, &to_biguint(divisor), rounding, builder),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, const FRAC_BITS: usize> Add<L, D>
+    for FixedPointVariable<FRAC_BITS>
+{
+    type Output = Self;
+
+    fn add(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        Self {
+            raw: builder.add(self.raw, rhs.raw),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, const FRAC_BITS: usize> Sub<L, D>
+    for FixedPointVariable<FRAC_BITS>
+{
+    type Output = Self;
+
+    fn sub(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        Self {
+            raw: builder.sub(self.raw, rhs.raw),
+        }
+    }
+}
+
+/// Multiplication rounding down.
+impl<L: PlonkParameters<D>, const D: usize, const FRAC_BITS: usize> Mul<L, D>
+    for FixedPointVariable<FRAC_BITS>
+{
+    type Output = Self;
+
+    fn mul(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        self.mul_round(rhs, Rounding::Down, builder)
+    }
+}
+
+/// Division rounding down.
+impl<L: PlonkParameters<D>, const D: usize, const FRAC_BITS: usize> Div<L, D>
+    for FixedPointVariable<FRAC_BITS>
+{
+    type Output = Self;
+
+    fn div(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        self.div_round(rhs, Rounding::Down, builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::U256;
+
+    use super::*;
+    use crate::prelude::*;
+
+    type Fixed = FixedPointVariable<64>;
+
+    #[test]
+    fn test_fixed_point_arithmetic() {
+        // 1.5 and 2.25.
+        let a = U256::from(3) << 63;
+        let b = U256::from(9) << 62;
+        let quotient = (a << 64) / b;
+
+        let mut builder = DefaultBuilder::new();
+        let a_var = builder.constant::<Fixed>(a);
+        let b_var = builder.constant::<Fixed>(b);
+        let seven = builder.constant::<U256Variable>(U256::from(7));
+
+        let results = [
+            builder.add(a_var, b_var).raw,
+            builder.sub(b_var, a_var).raw,
+            builder.mul(a_var, b_var).raw,
+            builder.div(a_var, b_var).raw,
+            a_var.div_round(b_var, Rounding::Up, &mut builder).raw,
+            a_var.div_round(b_var, Rounding::Nearest, &mut builder).raw,
+            Fixed::from_u256(seven, &mut builder).raw,
+            a_var.to_u256(Rounding::Down, &mut builder),
+            a_var.to_u256(Rounding::Up, &mut builder),
+            a_var.to_u256(Rounding::Nearest, &mut builder),
+        ];
+        let expected = [
+            U256::from(15) << 62,
+            U256::from(3) << 62,
+            U256::from(27) << 61,
+            quotient,
+            quotient + 1,
+            // 2/3 is rounded up to the nearest value.
+            quotient + 1,
+            U256::from(7) << 64,
+            U256::from(1),
+            U256::from(2),
+            U256::from(2),
+        ];
+        for (result, expected) in results.into_iter().zip(expected) {
+            let expected = builder.constant::<U256Variable>(expected);
+            builder.assert_is_equal(result, expected);
+        }
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
@@ -0,0 +1,70 @@
+use array_macro::array;
+use ethers::types::{I256, U256};
+use plonky2::hash::hash_types::RichField;
+
+use super::uint256::U256Variable;
+use super::Int;
+use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
+use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::U32Target;
+use crate::frontend::vars::{BitDecomposition, EvmVariable, U32Variable};
+use crate::make_int32_n;
+use crate::prelude::{
+    Add, BoolVariable, ByteVariable, CircuitBuilder, CircuitVariable, LessThanOrEqual, Mul, Neg,
+    One, PlonkParameters, Sub, Variable, Zero,
+};
+
+impl Int<8> for I256 {
+    type Unsigned = U256;
+
+    fn to_unsigned(self) -> U256 {
+        self.into_raw()
+    }
+
+    fn from_unsigned(value: U256) -> Self {
+        I256::from_raw(value)
+    }
+}
+
+make_int32_n!(I256Variable, I256, U256Variable, 8);
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::I256;
+
+    use crate::frontend::uint::int256::I256Variable;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_i256_arithmetic() {
+        let a = I256::from_dec_str("-30000000000000000000000000000000000000000").unwrap();
+        let b = I256::from_dec_str("7000000000000000000000000000000").unwrap();
+
+        let mut builder = DefaultBuilder::new();
+        let a_var = builder.constant::<I256Variable>(a);
+        let b_var = builder.constant::<I256Variable>(b);
+
+        let sum = builder.add(a_var, b_var);
+        let diff = builder.sub(b_var, a_var);
+        let product = builder.mul(a_var, b_var);
+        let negated = builder.neg(a_var);
+        let lt = builder.lt(a_var, b_var);
+        let (_, overflow) = a_var.overflowing_mul(a_var, &mut builder);
+
+        let expected_sum = builder.constant::<I256Variable>(a + b);
+        let expected_diff = builder.constant::<I256Variable>(b - a);
+        let expected_product = builder.constant::<I256Variable>(a * b);
+        let expected_negated = builder.constant::<I256Variable>(-a);
+        let _true = builder._true();
+        builder.assert_is_equal(sum, expected_sum);
+        builder.assert_is_equal(diff, expected_diff);
+        builder.assert_is_equal(product, expected_product);
+        builder.assert_is_equal(negated, expected_negated);
+        builder.assert_is_equal(lt, _true);
+        builder.assert_is_equal(overflow, _true);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
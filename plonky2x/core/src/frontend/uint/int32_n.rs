@@ -0,0 +1,255 @@
+#[macro_export]
+macro_rules! make_int32_n {
+    ($a:ident, $b:ty, $u:ident, $c:expr) => {
+        /// A signed integer type encoded in two's complement as little-endian u32 limbs.
+        #[derive(Debug, Clone, Copy)]
+        pub struct $a {
+            pub limbs: [U32Variable; $c],
+        }
+
+        impl CircuitVariable for $a {
+            type ValueType<F: RichField> = $b;
+
+            fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> Self {
+                Self::from_unsigned($u::init_unsafe(builder))
+            }
+
+            fn variables(&self) -> Vec<Variable> {
+                self.to_unsigned().variables()
+            }
+
+            fn from_variables_unsafe(variables: &[Variable]) -> Self {
+                Self::from_unsigned($u::from_variables_unsafe(variables))
+            }
+
+            fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(
+                &self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) {
+                self.to_unsigned().assert_is_valid(builder)
+            }
+
+            fn nb_elements() -> usize {
+                $u::nb_elements()
+            }
+
+            fn elements<F: RichField>(value: $b) -> Vec<F> {
+                $u::elements(<$b as $crate::frontend::uint::Int<$c>>::to_unsigned(value))
+            }
+
+            fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F> {
+                <$b as $crate::frontend::uint::Int<$c>>::from_unsigned($u::from_elements(elements))
+            }
+        }
+
+        impl EvmVariable for $a {
+            fn encode<L: PlonkParameters<D>, const D: usize>(
+                &self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> Vec<ByteVariable> {
+                self.to_unsigned().encode(builder)
+            }
+
+            fn decode<L: PlonkParameters<D>, const D: usize>(
+                builder: &mut CircuitBuilder<L, D>,
+                bytes: &[ByteVariable],
+            ) -> Self {
+                Self::from_unsigned($u::decode(builder, bytes))
+            }
+
+            fn encode_value<F: RichField>(value: Self::ValueType<F>) -> Vec<u8> {
+                $u::encode_value::<F>(<$b as $crate::frontend::uint::Int<$c>>::to_unsigned(value))
+            }
+
+            fn decode_value<F: RichField>(bytes: &[u8]) -> Self::ValueType<F> {
+                <$b as $crate::frontend::uint::Int<$c>>::from_unsigned($u::decode_value::<F>(bytes))
+            }
+        }
+
+        impl $a {
+            /// Reinterprets the two's complement bits of an unsigned integer as a signed integer.
+            pub fn from_unsigned(value: $u) -> Self {
+                Self { limbs: value.limbs }
+            }
+
+            /// Reinterprets the two's complement bits of the integer as an unsigned integer.
+            pub fn to_unsigned(self) -> $u {
+                $u { limbs: self.limbs }
+            }
+
+            /// Returns whether the integer is negative, i.e. whether its top bit is set.
+            pub fn is_negative<L: PlonkParameters<D>, const D: usize>(
+                self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> BoolVariable {
+                builder.to_le_bits(self.limbs[$c - 1])[31]
+            }
+
+            /// Returns the absolute value of the integer, which always fits in the unsigned type.
+            pub fn unsigned_abs<L: PlonkParameters<D>, const D: usize>(
+                self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> $u {
+                let is_negative = self.is_negative(builder);
+                let zero = builder.zero::<$u>();
+                let negated = builder.sub(zero, self.to_unsigned());
+                builder.select(is_negative, negated, self.to_unsigned())
+            }
+
+            /// Adds two integers, returning the wrapped sum and whether it overflowed.
+            pub fn overflowing_add<L: PlonkParameters<D>, const D: usize>(
+                self,
+                rhs: Self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> (Self, BoolVariable) {
+                let sum = builder.add(self.to_unsigned(), rhs.to_unsigned());
+                let sum = Self::from_unsigned(sum);
+                // The sum overflows iff the operands have the same sign and the sum the other one.
+                let self_sign = self.is_negative(builder);
+                let rhs_sign = rhs.is_negative(builder);
+                let sum_sign = sum.is_negative(builder);
+                let same_sign = builder.is_equal(self_sign, rhs_sign);
+                let sign_changed = builder.xor(self_sign, sum_sign);
+                (sum, builder.and(same_sign, sign_changed))
+            }
+
+            /// Subtracts two integers, returning the wrapped difference and whether it overflowed.
+            pub fn overflowing_sub<L: PlonkParameters<D>, const D: usize>(
+                self,
+                rhs: Self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> (Self, BoolVariable) {
+                let diff = builder.sub(self.to_unsigned(), rhs.to_unsigned());
+                let diff = Self::from_unsigned(diff);
+                // The difference overflows iff the operands have different signs and the
+                // difference has the sign of `rhs`.
+                let self_sign = self.is_negative(builder);
+                let rhs_sign = rhs.is_negative(builder);
+                let diff_sign = diff.is_negative(builder);
+                let different_sign = builder.xor(self_sign, rhs_sign);
+                let sign_changed = builder.xor(self_sign, diff_sign);
+                (diff, builder.and(different_sign, sign_changed))
+            }
+
+            /// Multiplies two integers, returning the wrapped product and whether it overflowed.
+            pub fn overflowing_mul<L: PlonkParameters<D>, const D: usize>(
+                self,
+                rhs: Self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> (Self, BoolVariable) {
+                let self_sign = self.is_negative(builder);
+                let rhs_sign = rhs.is_negative(builder);
+                let is_negative = builder.xor(self_sign, rhs_sign);
+
+                // Multiply the absolute values, keeping the full product to detect overflows.
+                let self_abs = self.unsigned_abs(builder);
+                let rhs_abs = rhs.unsigned_abs(builder);
+                let self_biguint = BigUintTarget {
+                    limbs: self_abs.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+                };
+                let rhs_biguint = BigUintTarget {
+                    limbs: rhs_abs.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+                };
+                let product = builder.api.mul_biguint(&self_biguint, &rhs_biguint);
+                let low = $u {
+                    limbs: array![i => product.limbs[i].into(); $c],
+                };
+                let zero = builder.zero::<U32Variable>();
+                let mut high_is_zero = builder._true();
+                for limb in product.limbs[$c..].iter() {
+                    let limb_is_zero = builder.is_equal(U32Variable::from(*limb), zero);
+                    high_is_zero = builder.and(high_is_zero, limb_is_zero);
+                }
+
+                // The magnitude of the product must be below 2^(n-1), or equal to it if the product
+                // is negative.
+                let min_magnitude = builder.constant::<$u>(
+                    <$b as $crate::frontend::uint::Int<$c>>::to_unsigned(<$b>::MIN),
+                );
+                let below_min = builder.lt(low, min_magnitude);
+                let is_min = builder.is_equal(low, min_magnitude);
+                let negative_min = builder.and(is_negative, is_min);
+                let low_fits = builder.or(below_min, negative_min);
+                let fits = builder.and(high_is_zero, low_fits);
+
+                let zero = builder.zero::<$u>();
+                let negated = builder.sub(zero, low);
+                let product = builder.select(is_negative, negated, low);
+                (Self::from_unsigned(product), builder.not(fits))
+            }
+        }
+
+        impl<L: PlonkParameters<D>, const D: usize> Zero<L, D> for $a {
+            fn zero(builder: &mut CircuitBuilder<L, D>) -> Self {
+                Self::from_unsigned(builder.zero())
+            }
+        }
+
+        impl<L: PlonkParameters<D>, const D: usize> One<L, D> for $a {
+            fn one(builder: &mut CircuitBuilder<L, D>) -> Self {
+                Self::from_unsigned(builder.one())
+            }
+        }
+
+        /// Checked addition, which fails the circuit on overflows.
+        impl<L: PlonkParameters<D>, const D: usize> Add<L, D> for $a {
+            type Output = Self;
+
+            fn add(self, rhs: $a, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+                let (sum, overflow) = self.overflowing_add(rhs, builder);
+                let _false = builder._false();
+                builder.assert_is_equal(overflow, _false);
+                sum
+            }
+        }
+
+        /// Checked subtraction, which fails the circuit on overflows.
+        impl<L: PlonkParameters<D>, const D: usize> Sub<L, D> for $a {
+            type Output = Self;
+
+            fn sub(self, rhs: $a, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+                let (diff, overflow) = self.overflowing_sub(rhs, builder);
+                let _false = builder._false();
+                builder.assert_is_equal(overflow, _false);
+                diff
+            }
+        }
+
+        /// Checked multiplication, which fails the circuit on overflows.
+        impl<L: PlonkParameters<D>, const D: usize> Mul<L, D> for $a {
+            type Output = Self;
+
+            fn mul(self, rhs: $a, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+                let (product, overflow) = self.overflowing_mul(rhs, builder);
+                let _false = builder._false();
+                builder.assert_is_equal(overflow, _false);
+                product
+            }
+        }
+
+        /// Checked negation, which fails the circuit on the minimum value.
+        impl<L: PlonkParameters<D>, const D: usize> Neg<L, D> for $a {
+            type Output = Self;
+
+            fn neg(self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+                let zero = builder.zero::<Self>();
+                builder.sub(zero, self)
+            }
+        }
+
+        impl<L: PlonkParameters<D>, const D: usize> LessThanOrEqual<L, D> for $a {
+            #[must_use]
+            fn lte(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> BoolVariable {
+                // Two's complement integers of the same sign compare as their unsigned bits, and
+                // otherwise the negative one is the smallest.
+                let self_sign = self.is_negative(builder);
+                let rhs_sign = rhs.is_negative(builder);
+                let same_sign = builder.is_equal(self_sign, rhs_sign);
+                let unsigned_lte = builder.lte(self.to_unsigned(), rhs.to_unsigned());
+                builder.select(same_sign, unsigned_lte, self_sign)
+            }
+        }
+    };
+}
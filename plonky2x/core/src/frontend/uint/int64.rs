@@ -0,0 +1,121 @@
+use array_macro::array;
+use plonky2::hash::hash_types::RichField;
+
+use super::int256::I256Variable;
+use super::uint64::U64Variable;
+use super::Int;
+use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
+use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::U32Target;
+use crate::frontend::vars::{BitDecomposition, EvmVariable, U32Variable};
+use crate::make_int32_n;
+use crate::prelude::{
+    Add, BoolVariable, ByteVariable, CircuitBuilder, CircuitVariable, LessThanOrEqual, Mul, Neg,
+    One, PlonkParameters, Sub, Variable, Zero,
+};
+
+impl Int<2> for i64 {
+    type Unsigned = u64;
+
+    fn to_unsigned(self) -> u64 {
+        self as u64
+    }
+
+    fn from_unsigned(value: u64) -> Self {
+        value as i64
+    }
+}
+
+make_int32_n!(I64Variable, i64, U64Variable, 2);
+
+impl I64Variable {
+    /// Sign-extends the integer to an I256Variable.
+    pub fn to_i256<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> I256Variable {
+        let is_negative = self.is_negative(builder);
+        let ones = builder.constant::<U32Variable>(u32::MAX);
+        let zero = builder.zero::<U32Variable>();
+        let extension = builder.select(is_negative, ones, zero);
+        I256Variable {
+            limbs: array![i => if i < self.limbs.len() { self.limbs[i] } else { extension }; 8],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::I256;
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_i64_arithmetic() {
+        let mut rng = OsRng;
+        let values = [
+            (rng.gen::<i64>(), rng.gen::<i64>()),
+            (rng.gen::<i32>() as i64, rng.gen::<i32>() as i64),
+            (i64::MIN, -1),
+            (i64::MIN, 1),
+            (i64::MAX, 1),
+            (-1, -1),
+            (0, i64::MIN),
+        ];
+
+        let mut builder = DefaultBuilder::new();
+        for (a, b) in values {
+            let a_var = builder.constant::<I64Variable>(a);
+            let b_var = builder.constant::<I64Variable>(b);
+
+            let results = [
+                a_var.overflowing_add(b_var, &mut builder),
+                a_var.overflowing_sub(b_var, &mut builder),
+                a_var.overflowing_mul(b_var, &mut builder),
+            ];
+            let expected = [
+                a.overflowing_add(b),
+                a.overflowing_sub(b),
+                a.overflowing_mul(b),
+            ];
+            for ((result, overflow), (expected, expected_overflow)) in
+                results.into_iter().zip(expected)
+            {
+                let expected = builder.constant::<I64Variable>(expected);
+                let expected_overflow = builder.constant::<BoolVariable>(expected_overflow);
+                builder.assert_is_equal(result, expected);
+                builder.assert_is_equal(overflow, expected_overflow);
+            }
+
+            let lte = builder.lte(a_var, b_var);
+            let expected_lte = builder.constant::<BoolVariable>(a <= b);
+            builder.assert_is_equal(lte, expected_lte);
+
+            let extended = a_var.to_i256(&mut builder);
+            let expected_extended = builder.constant::<I256Variable>(I256::from(a));
+            builder.assert_is_equal(extended, expected_extended);
+        }
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_i64_checked_add_overflow() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.constant::<I64Variable>(i64::MAX);
+        let b = builder.constant::<I64Variable>(1);
+        let sum = builder.add(a, b);
+        builder.write(sum);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
@@ -1,5 +1,8 @@
 use core::fmt::Debug;
 
+pub mod fixed_point;
+pub mod int256;
+pub mod int64;
 pub mod uint128;
 pub mod uint256;
 pub mod uint32;
@@ -8,6 +11,7 @@ pub mod uint64;
 
 pub(crate) mod num;
 
+mod int32_n;
 mod uint32_n;
 
 pub trait Uint<const N: usize>: Debug + Clone + Copy + Sync + Send + 'static {
@@ -49,3 +53,12 @@ pub trait Uint<const N: usize>: Debug + Clone + Copy + Sync + Send + 'static {
         Self::from_little_endian(&bytes)
     }
 }
+
+/// A signed integer with the two's complement encoding of an unsigned integer of `N` u32 limbs.
+pub trait Int<const N: usize>: Debug + Clone + Copy + Sync + Send + 'static {
+    type Unsigned: Uint<N>;
+
+    fn to_unsigned(self) -> Self::Unsigned;
+
+    fn from_unsigned(value: Self::Unsigned) -> Self;
+}
@@ -1,10 +1,11 @@
 use array_macro::array;
 use ethers::types::U256;
 use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::BoolTarget;
 
 use super::Uint;
 use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
-use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::U32Target;
+use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::{CircuitBuilderU32, U32Target};
 use crate::frontend::vars::{EvmVariable, SSZVariable, U32Variable};
 use crate::prelude::{
     Add, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder,
@@ -44,3 +45,185 @@ impl Uint<8> for U256 {
 
 make_uint32_n!(U256Variable, U256, 8);
 make_uint32_n_tests!(U256Variable, U256, 8);
+
+impl U256Variable {
+    fn to_biguint(self) -> BigUintTarget {
+        BigUintTarget {
+            limbs: self.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+        }
+    }
+
+    fn from_biguint_limbs(limbs: &[U32Target]) -> Self {
+        Self {
+            limbs: array![i => limbs[i].into(); 8],
+        }
+    }
+
+    /// Reduces `value` modulo `modulus`, returning the quotient and the remainder, or zeros if
+    /// `modulus` is zero as in the EVM.
+    ///
+    /// The quotient and the remainder are provided by a hint and constrained by
+    /// `value = quotient * modulus + remainder` and `remainder < modulus`.
+    fn reduce<L: PlonkParameters<D>, const D: usize>(
+        value: &BigUintTarget,
+        modulus: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> (BigUintTarget, Self) {
+        let zero = builder.zero::<Self>();
+        let one = builder.one::<Self>();
+        let is_zero = builder.is_equal(modulus, zero);
+        let divisor = builder.select(is_zero, one, modulus);
+        let (quotient, remainder) =
+            builder
+                .api
+                ._div_rem_biguint(value, &divisor.to_biguint(), value.num_limbs());
+
+        let zero_target = builder.api.zero_u32();
+        let is_zero_target = BoolTarget::new_unsafe(is_zero.variable.0);
+        let quotient = BigUintTarget {
+            limbs: quotient
+                .limbs
+                .iter()
+                .map(|limb| {
+                    U32Target::from_target_unsafe(builder.api.select(
+                        is_zero_target,
+                        zero_target.target,
+                        limb.target,
+                    ))
+                })
+                .collect(),
+        };
+        let remainder = Self::from_biguint_limbs(&remainder.limbs);
+        let remainder = builder.select(is_zero, zero, remainder);
+        (quotient, remainder)
+    }
+
+    /// Returns the full 512-bit product of two U256Variables as its low and high halves.
+    pub fn widening_mul<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> (Self, Self) {
+        let product = builder
+            .api
+            .mul_biguint(&self.to_biguint(), &rhs.to_biguint());
+        // The carry limb of the product of two 256-bit integers is always zero.
+        builder.api.assert_zero_u32(product.limbs[16]);
+        (
+            Self::from_biguint_limbs(&product.limbs[..8]),
+            Self::from_biguint_limbs(&product.limbs[8..16]),
+        )
+    }
+
+    /// Returns the quotient and the remainder of the division of two U256Variables, which are both
+    /// zero if `rhs` is zero as for the `DIV` and `MOD` opcodes of the EVM.
+    pub fn div_rem<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> (Self, Self) {
+        let (quotient, remainder) = Self::reduce(&self.to_biguint(), rhs, builder);
+        (Self::from_biguint_limbs(&quotient.limbs), remainder)
+    }
+
+    /// Returns `(self + rhs) % modulus` without wrapping the sum, or zero if `modulus` is zero, as
+    /// the `ADDMOD` opcode of the EVM.
+    pub fn addmod<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        modulus: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        let sum = builder
+            .api
+            .add_biguint(&self.to_biguint(), &rhs.to_biguint());
+        Self::reduce(&sum, modulus, builder).1
+    }
+
+    /// Returns `(self * rhs) % modulus` without wrapping the product, or zero if `modulus` is zero,
+    /// as the `MULMOD` opcode of the EVM.
+    pub fn mulmod<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        modulus: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        let product = builder
+            .api
+            .mul_biguint(&self.to_biguint(), &rhs.to_biguint());
+        Self::reduce(&product, modulus, builder).1
+    }
+}
+
+#[cfg(test)]
+mod arithmetic_tests {
+    use ethers::types::U512;
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::*;
+    use crate::prelude::*;
+
+    fn random_u256() -> U256 {
+        U256::from_little_endian(&OsRng.gen::<[u8; 32]>())
+    }
+
+    fn to_u256(value: U512) -> U256 {
+        U256::try_from(value).unwrap()
+    }
+
+    #[test]
+    fn test_u256_evm_arithmetic() {
+        let a = random_u256();
+        let b = random_u256();
+        let m = random_u256() >> 64;
+
+        let product = a.full_mul(b);
+        let expected = [
+            to_u256(product % (U512::one() << 256)),
+            to_u256(product >> 256),
+            a / m,
+            a % m,
+            to_u256((U512::from(a) + U512::from(b)) % U512::from(m)),
+            to_u256(product % U512::from(m)),
+            U256::zero(),
+            U256::zero(),
+            U256::zero(),
+        ];
+
+        let mut builder = DefaultBuilder::new();
+        let a = builder.constant::<U256Variable>(a);
+        let b = builder.constant::<U256Variable>(b);
+        let m = builder.constant::<U256Variable>(m);
+        let zero = builder.zero::<U256Variable>();
+
+        let (low, high) = a.widening_mul(b, &mut builder);
+        let (quotient, remainder) = a.div_rem(m, &mut builder);
+        let addmod = a.addmod(b, m, &mut builder);
+        let mulmod = a.mulmod(b, m, &mut builder);
+        // Division and reduction by zero yield zero.
+        let (zero_quotient, zero_remainder) = a.div_rem(zero, &mut builder);
+        let zero_mulmod = a.mulmod(b, zero, &mut builder);
+
+        let results = [
+            low,
+            high,
+            quotient,
+            remainder,
+            addmod,
+            mulmod,
+            zero_quotient,
+            zero_remainder,
+            zero_mulmod,
+        ];
+        for (result, expected) in results.into_iter().zip(expected) {
+            let expected = builder.constant::<U256Variable>(expected);
+            builder.assert_is_equal(result, expected);
+        }
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
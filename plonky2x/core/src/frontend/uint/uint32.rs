@@ -6,8 +6,13 @@ use plonky2::iop::target::BoolTarget;
 use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
 use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::{CircuitBuilderU32, U32Target};
 use crate::frontend::uint::num::u32::gadgets::multiple_comparison::list_lte_circuit;
-use crate::frontend::vars::EvmVariable;
+use crate::frontend::uint::num::u32::gates::add_many_u32::MAX_NUM_ADDENDS;
+use crate::frontend::vars::{
+    BigEndianBytesVariable, BitDecomposition, EvmVariable, LittleEndianBytesVariable,
+    OrderedBytesVariable,
+};
 use crate::prelude::*;
+use crate::value::EvmValue;
 
 /// A variable in the circuit representing a u32 value.
 ///
@@ -102,20 +107,12 @@ impl EvmVariable for U32Variable {
     }
 
     fn encode_value<F: RichField>(value: Self::ValueType<F>) -> Vec<u8> {
-        let mut bytes = vec![0_u8; 4];
-        for i in 0..4 {
-            bytes[i] = ((value >> ((4 - i - 1) * 8)) & 0xff) as u8;
-        }
-        bytes
+        value.encode()
     }
 
     fn decode_value<F: RichField>(bytes: &[u8]) -> Self::ValueType<F> {
         assert_eq!(bytes.len(), 4);
-        let mut value = 0_u32;
-        for i in 0..4 {
-            value |= (bytes[i] as u32) << ((4 - i - 1) * 8);
-        }
-        value
+        u32::decode(bytes)
     }
 }
 
@@ -221,6 +218,26 @@ impl<L: PlonkParameters<D>, const D: usize> Sub<L, D> for U32Variable {
     }
 }
 
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Adds many values modulo `2^32`, using one row of `U32AddManyGate` for up to 64 operands
+    /// instead of a chain of two-operand additions.
+    pub fn add_many_u32(&mut self, values: &[U32Variable]) -> U32Variable {
+        let targets = values
+            .iter()
+            .map(|v| (*v).into())
+            .collect::<Vec<U32Target>>();
+        let mut chunks = targets.chunks(MAX_NUM_ADDENDS - 1);
+        let first = chunks.next().unwrap_or(&[]);
+        let (mut sum, _) = self.api.add_many_u32(first);
+        for chunk in chunks {
+            let mut addends = vec![sum];
+            addends.extend_from_slice(chunk);
+            (sum, _) = self.api.add_many_u32(&addends);
+        }
+        sum.into()
+    }
+}
+
 impl U32Variable {
     pub fn to_u64<L: PlonkParameters<D>, const D: usize>(
         &self,
@@ -238,12 +255,16 @@ impl U32Variable {
         result
     }
 
+    /// Recomposes a variable from at most 32 big endian bits.
+    #[deprecated(
+        note = "use `BitDecomposition::from_be_bits`, which takes exactly 32 bits, instead"
+    )]
     pub fn from_be_bits<L: PlonkParameters<D>, const D: usize>(
         bools: &[BoolVariable],
         builder: &mut CircuitBuilder<L, D>,
     ) -> Self {
         assert!(bools.len() <= 32);
-        // We simply sum the bits and don't need to do a range-check, as we know that the sum of 32 bits is always less than 2^32
+        // The sum of at most 32 bits is always less than 2^32, so no range check is needed.
         let var = builder.api.le_sum(
             bools
                 .iter()
@@ -252,21 +273,81 @@ impl U32Variable {
                 .collect::<Vec<BoolTarget>>()
                 .into_iter(),
         );
-        // It's okay to use from_targets which is unsafe, for the same reason as above.
         Self::from_targets(&[var])
     }
 
+    /// Decomposes the variable into 32 big endian bits.
+    #[deprecated(note = "use `BitDecomposition::to_be_bits` instead")]
     pub fn to_be_bits<L: PlonkParameters<D>, const D: usize>(
         &self,
         builder: &mut CircuitBuilder<L, D>,
     ) -> [BoolVariable; 32] {
-        let mut bits = builder.api.split_le(self.variable.0, 32);
-        bits.reverse();
-        bits.iter()
-            .map(|b| (*b).into())
-            .collect_vec()
-            .try_into()
-            .unwrap()
+        builder.to_be_bits(*self).try_into().unwrap()
+    }
+}
+
+impl U32Variable {
+    /// Encodes the value into bytes in EVM (big endian) order.
+    pub fn to_big_endian<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BigEndianBytesVariable<4> {
+        OrderedBytesVariable::from_variable(self, builder)
+    }
+
+    /// Encodes the value into bytes in SSZ (little endian) order.
+    pub fn to_little_endian<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> LittleEndianBytesVariable<4> {
+        OrderedBytesVariable::from_variable(self, builder)
+    }
+
+    /// Decodes a value from bytes in EVM (big endian) order.
+    pub fn from_big_endian<L: PlonkParameters<D>, const D: usize>(
+        bytes: &BigEndianBytesVariable<4>,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        bytes.to_variable(builder)
+    }
+
+    /// Decodes a value from bytes in SSZ (little endian) order.
+    pub fn from_little_endian<L: PlonkParameters<D>, const D: usize>(
+        bytes: &LittleEndianBytesVariable<4>,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        bytes.to_variable(builder)
+    }
+}
+
+impl BitDecomposition for U32Variable {
+    const NB_BITS: usize = 32;
+
+    fn to_le_bits<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<BoolVariable> {
+        builder
+            .api
+            .split_le(self.variable.0, Self::NB_BITS)
+            .into_iter()
+            .map(BoolVariable::from)
+            .collect()
+    }
+
+    fn from_le_bits<L: PlonkParameters<D>, const D: usize>(
+        bits: &[BoolVariable],
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        assert_eq!(bits.len(), Self::NB_BITS);
+        // The sum of 32 bits is always less than 2^32, so no range check is needed.
+        let var = builder.api.le_sum(
+            bits.iter()
+                .map(|b| (*b).into())
+                .collect::<Vec<BoolTarget>>()
+                .into_iter(),
+        );
+        Self::from_targets(&[var])
     }
 }
 
@@ -294,6 +375,34 @@ mod tests {
         circuit.data.verify(proof).unwrap();
     }
 
+    #[test]
+    #[allow(deprecated)]
+    fn test_u32_deprecated_bits() {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let var = U32Variable::constant(&mut builder, 0x12345678);
+
+        let be_bits = var.to_be_bits(&mut builder);
+        let expected_be_bits = builder.to_be_bits(var);
+        for (bit, expected) in be_bits.iter().zip(expected_be_bits) {
+            builder.assert_is_equal(*bit, expected);
+        }
+        let le_bits = EvmVariable::to_le_bits(&var, &mut builder);
+        let expected_le_bits = builder.to_le_bits(var);
+        for (bit, expected) in le_bits.into_iter().zip(expected_le_bits) {
+            builder.assert_is_equal(bit, expected);
+        }
+
+        // The legacy recomposition accepts fewer than 32 bits.
+        let low_byte = U32Variable::from_be_bits(&be_bits[24..], &mut builder);
+        let expected_low_byte = U32Variable::constant(&mut builder, 0x78);
+        builder.assert_is_equal(low_byte, expected_low_byte);
+
+        let circuit = builder.build();
+        let pw = PartialWitness::new();
+        let proof = circuit.data.prove(pw).unwrap();
+        circuit.data.verify(proof).unwrap();
+    }
+
     #[test]
     fn test_u32_evm() {
         let mut builder = CircuitBuilder::<L, D>::new();
@@ -356,6 +465,35 @@ mod tests {
         circuit.data.verify(proof).unwrap();
     }
 
+    #[test]
+    fn test_u32_add_many() {
+        let mut builder = CircuitBuilder::<L, D>::new();
+
+        let mut rng = rand::thread_rng();
+        let operands = (0..70).map(|_| rng.gen()).collect::<Vec<u32>>();
+        let expected_result = operands.iter().fold(0u32, |acc, x| acc.wrapping_add(*x));
+
+        let values = operands
+            .iter()
+            .map(|x| U32Variable::constant(&mut builder, *x))
+            .collect::<Vec<_>>();
+        let result = builder.add_many_u32(&values[..5]);
+        let expected_result_var = values[..5]
+            .iter()
+            .fold(builder.zero::<U32Variable>(), |acc, x| builder.add(acc, *x));
+        builder.assert_is_equal(result, expected_result_var);
+
+        let result = builder.add_many_u32(&values);
+        let expected_result_var = U32Variable::constant(&mut builder, expected_result);
+        builder.assert_is_equal(result, expected_result_var);
+
+        let circuit = builder.build();
+        let pw = PartialWitness::new();
+
+        let proof = circuit.data.prove(pw).unwrap();
+        circuit.data.verify(proof).unwrap();
+    }
+
     #[test]
     fn test_u32_sub() {
         let mut builder = CircuitBuilder::<L, D>::new();
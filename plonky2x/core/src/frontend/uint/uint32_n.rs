@@ -55,6 +55,10 @@ macro_rules! make_uint32_n {
                 }
                 <$b as Uint<$c>>::from_u32_limbs(value_limbs)
             }
+
+            fn format_value<F: RichField>(value: &Self::ValueType<F>) -> String {
+                format!("{} ({:#x})", value, value)
+            }
         }
 
 
@@ -86,13 +90,76 @@ macro_rules! make_uint32_n {
             }
 
             fn encode_value<F: RichField>(value: Self::ValueType<F>) -> Vec<u8> {
-                let mut bytes = vec![0u8; $c * 4];
-                <$b as Uint<$c>>::to_big_endian(&value, &mut bytes);
-                bytes
+                <$b as $crate::value::EvmValue>::encode(&value)
             }
 
             fn decode_value<F: RichField>(bytes: &[u8]) -> Self::ValueType<F> {
-                <$b as Uint<$c>>::from_big_endian(bytes)
+                <$b as $crate::value::EvmValue>::decode(bytes)
+            }
+        }
+
+        impl $crate::frontend::vars::BitDecomposition for $a {
+            const NB_BITS: usize = 32 * $c;
+
+            fn to_le_bits<L: PlonkParameters<D>, const D: usize>(
+                &self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> Vec<BoolVariable> {
+                self.limbs
+                    .iter()
+                    .flat_map(|x| $crate::frontend::vars::BitDecomposition::to_le_bits(x, builder))
+                    .collect()
+            }
+
+            fn from_le_bits<L: PlonkParameters<D>, const D: usize>(
+                bits: &[BoolVariable],
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> Self {
+                assert_eq!(bits.len(), 32 * $c);
+                let mut limbs = [U32Variable::init_unsafe(builder); $c];
+                for i in 0..$c {
+                    limbs[i] = <U32Variable as $crate::frontend::vars::BitDecomposition>::from_le_bits(
+                        &bits[i * 32..(i + 1) * 32],
+                        builder,
+                    );
+                }
+                Self {
+                    limbs
+                }
+            }
+        }
+
+        impl $a {
+            /// Encodes the value into bytes in EVM (big endian) order.
+            pub fn to_big_endian<L: PlonkParameters<D>, const D: usize>(
+                &self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> $crate::frontend::vars::BigEndianBytesVariable<{ $c * 4 }> {
+                $crate::frontend::vars::OrderedBytesVariable::from_variable(self, builder)
+            }
+
+            /// Encodes the value into bytes in SSZ (little endian) order.
+            pub fn to_little_endian<L: PlonkParameters<D>, const D: usize>(
+                &self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> $crate::frontend::vars::LittleEndianBytesVariable<{ $c * 4 }> {
+                $crate::frontend::vars::OrderedBytesVariable::from_variable(self, builder)
+            }
+
+            /// Decodes a value from bytes in EVM (big endian) order.
+            pub fn from_big_endian<L: PlonkParameters<D>, const D: usize>(
+                bytes: &$crate::frontend::vars::BigEndianBytesVariable<{ $c * 4 }>,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> Self {
+                bytes.to_variable(builder)
+            }
+
+            /// Decodes a value from bytes in SSZ (little endian) order.
+            pub fn from_little_endian<L: PlonkParameters<D>, const D: usize>(
+                bytes: &$crate::frontend::vars::LittleEndianBytesVariable<{ $c * 4 }>,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> Self {
+                bytes.to_variable(builder)
             }
         }
 
@@ -101,8 +168,7 @@ macro_rules! make_uint32_n {
                 &self,
                 builder: &mut CircuitBuilder<L, D>,
             ) -> Bytes32Variable {
-                let mut bytes = self.encode(builder);
-                bytes.reverse();
+                let mut bytes = self.to_little_endian(builder).bytes.0.to_vec();
                 if bytes.len() < 32 {
                     let zero = builder.constant::<ByteVariable>(0);
                     bytes.extend(vec![zero; 32 - bytes.len()]);
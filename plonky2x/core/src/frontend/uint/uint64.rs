@@ -6,7 +6,7 @@ use plonky2::hash::hash_types::RichField;
 use super::uint256::U256Variable;
 use super::Uint;
 use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
-use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::U32Target;
+use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::{CircuitBuilderU32, U32Target};
 use crate::frontend::vars::{EvmVariable, SSZVariable, U32Variable};
 use crate::prelude::{
     Add, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder,
@@ -80,4 +80,140 @@ impl U64Variable {
         }
         result
     }
+
+    /// Adds two U64Variables limb by limb, returning the wrapped sum and whether it overflowed.
+    pub fn overflowing_add<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> (Self, BoolVariable) {
+        let (low, carry) = builder
+            .api
+            .add_u32(self.limbs[0].into(), rhs.limbs[0].into());
+        let (high, overflow) = builder
+            .api
+            .add_u32s_with_carry(&[self.limbs[1].into(), rhs.limbs[1].into()], carry);
+        let result = Self {
+            limbs: [low.into(), high.into()],
+        };
+        // The carry of a sum of two u32 limbs and a carry is either 0 or 1.
+        (
+            result,
+            BoolVariable::from_variables_unsafe(&[Variable(overflow.target)]),
+        )
+    }
+
+    /// Subtracts two U64Variables limb by limb, returning the wrapped difference and whether it
+    /// underflowed.
+    pub fn overflowing_sub<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> (Self, BoolVariable) {
+        let zero = builder.api.zero_u32();
+        let (low, borrow) = builder
+            .api
+            .sub_u32(self.limbs[0].into(), rhs.limbs[0].into(), zero);
+        let (high, underflow) =
+            builder
+                .api
+                .sub_u32(self.limbs[1].into(), rhs.limbs[1].into(), borrow);
+        let result = Self {
+            limbs: [low.into(), high.into()],
+        };
+        // The subtraction gate constrains the borrow to be either 0 or 1.
+        (
+            result,
+            BoolVariable::from_variables_unsafe(&[Variable(underflow.target)]),
+        )
+    }
+
+    /// Multiplies two U64Variables modulo 2^64.
+    ///
+    /// Only the three partial products contributing to the low 64 bits are computed, instead of
+    /// the full 128-bit product computed by `builder.mul`.
+    pub fn wrapping_mul<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        let (a0, a1) = (self.limbs[0].into(), self.limbs[1].into());
+        let (b0, b1) = (rhs.limbs[0].into(), rhs.limbs[1].into());
+        let (low, carry) = builder.api.mul_u32(a0, b0);
+        let (partial, _) = builder.api.mul_add_u32(a0, b1, carry);
+        let (high, _) = builder.api.mul_add_u32(a1, b0, partial);
+        Self {
+            limbs: [low.into(), high.into()],
+        }
+    }
+
+    /// Returns the quotient and the remainder of the division of two U64Variables, sharing a single
+    /// division constraint between them.
+    pub fn div_rem<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> (Self, Self) {
+        let a = BigUintTarget {
+            limbs: self.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+        };
+        let b = BigUintTarget {
+            limbs: rhs.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+        };
+        // The quotient of two u64s fits in two limbs.
+        let (quotient, remainder) = builder.api._div_rem_biguint(&a, &b, 2);
+        (
+            Self {
+                limbs: [quotient.limbs[0].into(), quotient.limbs[1].into()],
+            },
+            Self {
+                limbs: [remainder.limbs[0].into(), remainder.limbs[1].into()],
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod limb_arithmetic_tests {
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_u64_limb_arithmetic() {
+        let mut rng = OsRng;
+        let a = rng.gen::<u64>();
+        let b = (rng.gen::<u64>() >> 16) | 1;
+
+        let mut builder = DefaultBuilder::new();
+        let a_var = builder.constant::<U64Variable>(a);
+        let b_var = builder.constant::<U64Variable>(b);
+
+        let (sum, overflow) = a_var.overflowing_add(b_var, &mut builder);
+        let (diff, underflow) = b_var.overflowing_sub(a_var, &mut builder);
+        let product = a_var.wrapping_mul(b_var, &mut builder);
+        let (quotient, remainder) = a_var.div_rem(b_var, &mut builder);
+
+        let expected_sum = builder.constant::<U64Variable>(a.wrapping_add(b));
+        let expected_overflow = builder.constant::<BoolVariable>(a.checked_add(b).is_none());
+        let expected_diff = builder.constant::<U64Variable>(b.wrapping_sub(a));
+        let expected_underflow = builder.constant::<BoolVariable>(b < a);
+        let expected_product = builder.constant::<U64Variable>(a.wrapping_mul(b));
+        let expected_quotient = builder.constant::<U64Variable>(a / b);
+        let expected_remainder = builder.constant::<U64Variable>(a % b);
+        builder.assert_is_equal(sum, expected_sum);
+        builder.assert_is_equal(overflow, expected_overflow);
+        builder.assert_is_equal(diff, expected_diff);
+        builder.assert_is_equal(underflow, expected_underflow);
+        builder.assert_is_equal(product, expected_product);
+        builder.assert_is_equal(quotient, expected_quotient);
+        builder.assert_is_equal(remainder, expected_remainder);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
 }
@@ -6,13 +6,17 @@ use plonky2::field::types::{Field, PrimeField64};
 use plonky2::hash::hash_types::RichField;
 use plonky2::hash::poseidon::PoseidonHash;
 use plonky2::iop::challenger::RecursiveChallenger;
-use plonky2::iop::target::Target;
+use plonky2::iop::target::{BoolTarget, Target};
 use serde::{Deserialize, Serialize};
 
 use super::{ByteVariable, CircuitVariable, ValueStream, Variable, VariableStream};
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::ops::{RotateLeft, RotateRight, Zero};
+
+/// The log2 of the number of elements read by a single random access gate in `select_index`.
+const RANDOM_ACCESS_CHUNK_BITS: usize = 5;
 
 /// A variable in the circuit representing a fixed length array of variables.
 /// We use this to avoid stack overflow arrays associated with fixed-length arrays.
@@ -39,6 +43,69 @@ impl<V: CircuitVariable, const N: usize> ArrayVariable<V, N> {
     pub fn len(&self) -> usize {
         self.data.len()
     }
+
+    /// Returns the sub-array `self[START..START + LEN]`.
+    pub fn slice<const START: usize, const LEN: usize>(&self) -> ArrayVariable<V, LEN> {
+        assert!(START + LEN <= N, "slice out of bounds");
+        ArrayVariable::new(self.data[START..START + LEN].to_vec())
+    }
+
+    /// Returns the concatenation of `self` and `other`. `O` must be equal to `N + M`.
+    pub fn concat<const M: usize, const O: usize>(
+        &self,
+        other: &ArrayVariable<V, M>,
+    ) -> ArrayVariable<V, O> {
+        assert_eq!(N + M, O, "concatenation has the wrong length");
+        let mut data = self.data.clone();
+        data.extend_from_slice(&other.data);
+        ArrayVariable::new(data)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, V: CircuitVariable, const N: usize>
+    RotateLeft<L, D, usize> for ArrayVariable<V, N>
+{
+    type Output = Self;
+
+    fn rotate_left(self, rhs: usize, _builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let mut data = self.data;
+        data.rotate_left(rhs % N);
+        ArrayVariable::new(data)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, V: CircuitVariable, const N: usize>
+    RotateRight<L, D, usize> for ArrayVariable<V, N>
+{
+    type Output = Self;
+
+    fn rotate_right(self, rhs: usize, _builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let mut data = self.data;
+        data.rotate_right(rhs % N);
+        ArrayVariable::new(data)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, V: CircuitVariable, const N: usize>
+    RotateLeft<L, D, Variable> for ArrayVariable<V, N>
+{
+    type Output = Self;
+
+    /// Rotates the array left by a dynamic amount, which must fit in as many bits as `N`.
+    fn rotate_left(self, rhs: Variable, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        builder.barrel_shift(self, rhs, |data, amount| data.rotate_left(amount))
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, V: CircuitVariable, const N: usize>
+    RotateRight<L, D, Variable> for ArrayVariable<V, N>
+{
+    type Output = Self;
+
+    /// Rotates the array right by a dynamic amount, which must fit in as many bits as `N`.
+    fn rotate_right(self, rhs: Variable, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        builder.barrel_shift(self, rhs, |data, amount| data.rotate_right(amount))
+    }
 }
 
 impl<V: CircuitVariable, const N: usize> Index<usize> for ArrayVariable<V, N> {
@@ -123,9 +190,76 @@ impl<V: CircuitVariable, const N: usize> CircuitVariable for ArrayVariable<V, N>
 
         res
     }
+
+    fn format_value<F: RichField>(value: &Self::ValueType<F>) -> String {
+        format!("[{}]", value.iter().map(V::format_value).join(", "))
+    }
 }
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Rotates `array` by a dynamic `amount` with a barrel shifter: for every bit `k` of
+    /// `amount`, the array is conditionally rotated by `2^k`. This costs `N * log2(N)` selects
+    /// instead of the `N^2` of selecting every element independently.
+    fn barrel_shift<V: CircuitVariable, const N: usize>(
+        &mut self,
+        array: ArrayVariable<V, N>,
+        amount: Variable,
+        rotate: impl Fn(&mut [V], usize),
+    ) -> ArrayVariable<V, N> {
+        let nb_bits = (usize::BITS - N.leading_zeros()).max(1) as usize;
+        let bits = self.api.split_le(amount.0, nb_bits);
+        let mut data = array.data;
+        for (k, bit) in bits.into_iter().enumerate() {
+            let mut rotated = data.clone();
+            rotate(&mut rotated, (1 << k) % N);
+            data = rotated
+                .into_iter()
+                .zip(data)
+                .map(|(rotated, current)| self.select(bit.into(), rotated, current))
+                .collect();
+        }
+        ArrayVariable::new(data)
+    }
+
+    /// Returns `array[offset..offset + len]` padded with zeros to `LEN` elements, where `offset`
+    /// and `len` are only known at proving time. Requires `len <= LEN` and `offset + len <= N`.
+    pub fn dynamic_slice<V: CircuitVariable + Zero<L, D>, const N: usize, const LEN: usize>(
+        &mut self,
+        array: &ArrayVariable<V, N>,
+        offset: Variable,
+        len: Variable,
+    ) -> ArrayVariable<V, LEN> {
+        assert!(LEN <= N, "slice is longer than the array");
+
+        // Check that `offset + len <= N`. The rotation below bounds `offset` and the loop bounds
+        // `len`, so the difference cannot wrap around the field.
+        let end = self.add(offset, len);
+        let size = self.constant::<Variable>(L::Field::from_canonical_usize(N));
+        let remaining = self.sub(size, end);
+        self.api.range_check(remaining.0, 32);
+
+        let rotated = self.rotate_left(array.clone(), offset);
+
+        // Keep the first `len` elements, checking along the way that `len` is in `0..=LEN`.
+        let false_v = self._false();
+        let mut within_slice = self._true();
+        let mut len_found = self._false();
+        let mut data = Vec::with_capacity(LEN);
+        for i in 0..=LEN {
+            let idx = self.constant::<Variable>(L::Field::from_canonical_usize(i));
+            let at_len = self.is_equal(idx, len);
+            len_found = self.or(len_found, at_len);
+            within_slice = self.select(at_len, false_v, within_slice);
+            if i < LEN {
+                let zero = self.zero::<V>();
+                data.push(self.select(within_slice, rotated[i].clone(), zero));
+            }
+        }
+        self.assert_is_equal(len_found, self._true());
+
+        ArrayVariable::new(data)
+    }
+
     /// Given `array` of variables and dynamic `selector`, returns `array[selector]` as a variable.
     pub fn select_array<V: CircuitVariable>(&mut self, array: &[V], selector: Variable) -> V {
         // The accumulator holds the variable of the selected result
@@ -177,6 +311,70 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         V::from_variables_unsafe(&selected_vars)
     }
 
+    /// Returns `array[index]` using plonky2's random access gates, which costs a few gates per
+    /// variable of `V` instead of the `O(N)` selects of `select_array`. Fails if `index >= N`.
+    ///
+    /// A random access gate only fits a few dozen elements, so larger arrays are split into chunks
+    /// of `2^RANDOM_ACCESS_CHUNK_BITS` elements: the element is selected within every chunk by the
+    /// low bits of `index`, and then among the results of the chunks by the remaining bits.
+    pub fn select_index<V: CircuitVariable, const N: usize>(
+        &mut self,
+        array: &ArrayVariable<V, N>,
+        index: Variable,
+    ) -> V {
+        self.select_slice_index(array.as_slice(), index)
+    }
+
+    /// The implementation of `select_index` for slices.
+    pub(crate) fn select_slice_index<V: CircuitVariable>(
+        &mut self,
+        array: &[V],
+        index: Variable,
+    ) -> V {
+        let len = array.len();
+        assert!(len > 0, "cannot index an empty array");
+        if len == 1 {
+            let zero = self.zero::<Variable>();
+            self.assert_is_equal(index, zero);
+            return array[0].clone();
+        }
+
+        // Decomposing the index bounds it by the padded length, so checking that
+        // `len - 1 - index` fits in 32 bits checks that `index < len`.
+        let nb_bits = (usize::BITS - (len - 1).leading_zeros()) as usize;
+        let index_bits = self.api.split_le(index.0, nb_bits);
+        let last = self.constant::<Variable>(L::Field::from_canonical_usize(len - 1));
+        let remaining = self.sub(last, index);
+        self.api.range_check(remaining.0, 32);
+
+        let zero = self.api.zero();
+        let nb_variables = array[0].variables().len();
+        let variables = (0..nb_variables)
+            .map(|i| {
+                let mut targets = array.iter().map(|v| v.variables()[i].0).collect_vec();
+                targets.resize(1 << nb_bits, zero);
+                Variable(self.random_access_chunked(&index_bits, targets))
+            })
+            .collect_vec();
+
+        // The selected variables are the variables of an element of `array`, so they are valid.
+        V::from_variables_unsafe(&variables)
+    }
+
+    fn random_access_chunked(&mut self, index_bits: &[BoolTarget], targets: Vec<Target>) -> Target {
+        if index_bits.len() <= RANDOM_ACCESS_CHUNK_BITS {
+            let index = self.api.le_sum(index_bits.iter());
+            return self.api.random_access(index, targets);
+        }
+        let (low_bits, high_bits) = index_bits.split_at(RANDOM_ACCESS_CHUNK_BITS);
+        let low_index = self.api.le_sum(low_bits.iter());
+        let chunk_results = targets
+            .chunks(1 << RANDOM_ACCESS_CHUNK_BITS)
+            .map(|chunk| self.api.random_access(low_index, chunk.to_vec()))
+            .collect_vec();
+        self.random_access_chunked(high_bits, chunk_results)
+    }
+
     /// Given an `array` of ByteVariable's, a dynamic `index` start_idx, and a commitment to the
     /// `array`, 'seed', return `array[start_idx..start_idx+sub_array_size]` as an `array`.
     /// `seed` is used to generate randomness for the proof, and must contain a valid commitment to
@@ -486,6 +684,32 @@ mod tests {
         assert_eq!(output.read::<U256Variable>(), input_u256[1]);
     }
 
+    #[test]
+    fn test_select_index_chunked() {
+        type F = GoldilocksField;
+        const INPUT_SIZE: usize = 100;
+
+        let mut builder = DefaultBuilder::new();
+        let b = builder.read::<ArrayVariable<U256Variable, INPUT_SIZE>>();
+        let selector = builder.read::<Variable>();
+        let result = builder.select_index(&b, selector);
+        builder.write(result);
+        let circuit = builder.build();
+
+        let mut rng = OsRng;
+        let values = (0..INPUT_SIZE)
+            .map(|_| U256::from(rng.gen::<u128>()))
+            .collect::<Vec<_>>();
+        for index in [0, 31, 32, 77, INPUT_SIZE - 1] {
+            let mut input = circuit.input();
+            input.write::<ArrayVariable<U256Variable, INPUT_SIZE>>(values.clone());
+            input.write::<Variable>(F::from_canonical_usize(index));
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<U256Variable>(), values[index]);
+        }
+    }
+
     #[test]
     fn test_get_fixed_subarray() {
         utils::setup_logger();
@@ -580,4 +804,83 @@ mod tests {
         let (proof, output) = circuit.prove(&input);
         circuit.verify(&proof, &input, &output);
     }
+
+    #[test]
+    fn test_array_slice_rotate_concat() {
+        type F = GoldilocksField;
+        const N: usize = 6;
+
+        let mut builder = DefaultBuilder::new();
+        let array = builder.read::<ArrayVariable<Variable, N>>();
+        let shift = builder.read::<Variable>();
+        let offset = builder.read::<Variable>();
+        let len = builder.read::<Variable>();
+
+        let sliced = array.slice::<1, 3>();
+        let concatenated = sliced.concat::<N, 9>(&array);
+        let rotated = builder.rotate_left(array.clone(), 2usize);
+        let dynamic_left = builder.rotate_left(array.clone(), shift);
+        let dynamic_right = builder.rotate_right(array.clone(), shift);
+        let dynamic_slice = builder.dynamic_slice::<Variable, N, 4>(&array, offset, len);
+        builder.write(concatenated);
+        builder.write(rotated);
+        builder.write(dynamic_left);
+        builder.write(dynamic_right);
+        builder.write(dynamic_slice);
+
+        let circuit = builder.build();
+        let values = (0..N as u64)
+            .map(|i| F::from_canonical_u64(10 + i))
+            .collect::<Vec<_>>();
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<Variable, N>>(values.clone());
+        input.write::<Variable>(F::from_canonical_u64(5));
+        input.write::<Variable>(F::from_canonical_u64(3));
+        input.write::<Variable>(F::from_canonical_u64(2));
+
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let expected_concat = [&values[1..4], &values[..]].concat();
+        assert_eq!(output.read::<ArrayVariable<Variable, 9>>(), expected_concat);
+
+        let mut expected = values.clone();
+        expected.rotate_left(2);
+        assert_eq!(output.read::<ArrayVariable<Variable, N>>(), expected);
+
+        let mut expected = values.clone();
+        expected.rotate_left(5);
+        assert_eq!(output.read::<ArrayVariable<Variable, N>>(), expected);
+
+        let mut expected = values.clone();
+        expected.rotate_right(5);
+        assert_eq!(output.read::<ArrayVariable<Variable, N>>(), expected);
+
+        assert_eq!(
+            output.read::<ArrayVariable<Variable, 4>>(),
+            vec![values[3], values[4], F::ZERO, F::ZERO]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_dynamic_slice_out_of_bounds() {
+        type F = GoldilocksField;
+        const N: usize = 6;
+
+        let mut builder = DefaultBuilder::new();
+        let array = builder.read::<ArrayVariable<Variable, N>>();
+        let offset = builder.read::<Variable>();
+        let len = builder.read::<Variable>();
+        let dynamic_slice = builder.dynamic_slice::<Variable, N, 4>(&array, offset, len);
+        builder.write(dynamic_slice);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<Variable, N>>(vec![F::ONE; N]);
+        input.write::<Variable>(F::from_canonical_u64(4));
+        input.write::<Variable>(F::from_canonical_u64(3));
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
 }
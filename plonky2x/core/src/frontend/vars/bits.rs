@@ -0,0 +1,288 @@
+use itertools::Itertools;
+use plonky2::field::types::Field;
+use plonky2::iop::target::BoolTarget;
+
+use super::{
+    BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitVariable, Variable,
+};
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+
+/// A variable that can be decomposed into bits and bytes.
+///
+/// Every implementation interprets the variable as an unsigned integer and follows the same
+/// conventions: in little endian order, the bit (resp. byte) at index `i` has weight `2^i`
+/// (resp. `256^i`), and big endian order is the exact reverse. Byte strings are interpreted as big
+/// endian integers, matching the EVM, so `to_be_bytes` returns the bytes in their natural order.
+pub trait BitDecomposition: CircuitVariable {
+    /// The number of bits of the decomposition.
+    const NB_BITS: usize;
+
+    /// Decomposes the variable into `NB_BITS` little endian bits.
+    fn to_le_bits<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<BoolVariable>;
+
+    /// Recomposes a variable from `NB_BITS` little endian bits.
+    fn from_le_bits<L: PlonkParameters<D>, const D: usize>(
+        bits: &[BoolVariable],
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self;
+
+    /// Decomposes the variable into `NB_BITS` big endian bits.
+    fn to_be_bits<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<BoolVariable> {
+        let mut bits = self.to_le_bits(builder);
+        bits.reverse();
+        bits
+    }
+
+    /// Recomposes a variable from `NB_BITS` big endian bits.
+    fn from_be_bits<L: PlonkParameters<D>, const D: usize>(
+        bits: &[BoolVariable],
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        let bits = bits.iter().rev().copied().collect_vec();
+        Self::from_le_bits(&bits, builder)
+    }
+
+    /// Decomposes the variable into `NB_BITS / 8` little endian bytes.
+    fn to_le_bytes<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        let mut bytes = self.to_be_bytes(builder);
+        bytes.reverse();
+        bytes
+    }
+
+    /// Recomposes a variable from `NB_BITS / 8` little endian bytes.
+    fn from_le_bytes<L: PlonkParameters<D>, const D: usize>(
+        bytes: &[ByteVariable],
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        let bits = bytes.iter().flat_map(|b| b.as_le_bits()).collect_vec();
+        Self::from_le_bits(&bits, builder)
+    }
+
+    /// Decomposes the variable into `NB_BITS / 8` big endian bytes.
+    fn to_be_bytes<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        assert_eq!(
+            Self::NB_BITS % 8,
+            0,
+            "variable is not a whole number of bytes"
+        );
+        self.to_be_bits(builder)
+            .chunks_exact(8)
+            .map(|chunk| ByteVariable(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    /// Recomposes a variable from `NB_BITS / 8` big endian bytes.
+    fn from_be_bytes<L: PlonkParameters<D>, const D: usize>(
+        bytes: &[ByteVariable],
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        let bits = bytes.iter().flat_map(|b| b.as_be_bits()).collect_vec();
+        Self::from_be_bits(&bits, builder)
+    }
+}
+
+impl BitDecomposition for BoolVariable {
+    const NB_BITS: usize = 1;
+
+    fn to_le_bits<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        _builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<BoolVariable> {
+        vec![*self]
+    }
+
+    fn from_le_bits<L: PlonkParameters<D>, const D: usize>(
+        bits: &[BoolVariable],
+        _builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        assert_eq!(bits.len(), Self::NB_BITS);
+        bits[0]
+    }
+}
+
+impl BitDecomposition for ByteVariable {
+    const NB_BITS: usize = 8;
+
+    fn to_le_bits<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        _builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<BoolVariable> {
+        self.as_le_bits().to_vec()
+    }
+
+    fn from_le_bits<L: PlonkParameters<D>, const D: usize>(
+        bits: &[BoolVariable],
+        _builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        assert_eq!(bits.len(), Self::NB_BITS);
+        let mut bits: [BoolVariable; 8] = bits.try_into().unwrap();
+        bits.reverse();
+        ByteVariable::from_be_bits(bits)
+    }
+}
+
+impl BitDecomposition for Variable {
+    const NB_BITS: usize = 64;
+
+    fn to_le_bits<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<BoolVariable> {
+        assert_eq!(L::Field::BITS, Self::NB_BITS);
+        let bits = builder.api.split_le(self.0, Self::NB_BITS);
+
+        // Small values have two 64-bit decompositions, `x` and `x + p`. With the Goldilocks prime
+        // `p = 2^64 - 2^32 + 1`, the decomposition is canonical (less than `p`) if and only if the
+        // low 32 bits are zero whenever the high 32 bits are all ones.
+        let lo = builder.api.le_sum(bits[..32].iter());
+        let hi = builder.api.le_sum(bits[32..].iter());
+        let hi_max = builder.api.constant(L::Field::from_canonical_u32(u32::MAX));
+        let hi_is_max = builder.api.is_equal(hi, hi_max);
+        let lo_if_hi_is_max = builder.api.mul(hi_is_max.target, lo);
+        builder.api.assert_zero(lo_if_hi_is_max);
+
+        bits.into_iter().map(BoolVariable::from).collect()
+    }
+
+    /// Recomposes a variable from its little endian bits, reduced modulo the field order.
+    fn from_le_bits<L: PlonkParameters<D>, const D: usize>(
+        bits: &[BoolVariable],
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        assert_eq!(bits.len(), Self::NB_BITS);
+        let sum = builder
+            .api
+            .le_sum(bits.iter().map(|b| BoolTarget::new_unsafe(b.variable.0)));
+        Variable(sum)
+    }
+}
+
+impl<const N: usize> BitDecomposition for BytesVariable<N> {
+    const NB_BITS: usize = 8 * N;
+
+    fn to_le_bits<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        _builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<BoolVariable> {
+        self.0.iter().rev().flat_map(|b| b.as_le_bits()).collect()
+    }
+
+    fn from_le_bits<L: PlonkParameters<D>, const D: usize>(
+        bits: &[BoolVariable],
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        assert_eq!(bits.len(), Self::NB_BITS);
+        let mut bytes = bits
+            .chunks_exact(8)
+            .map(|chunk| ByteVariable::from_le_bits(chunk, builder))
+            .collect_vec();
+        bytes.reverse();
+        BytesVariable(bytes.try_into().unwrap())
+    }
+
+    fn to_be_bytes<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        _builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        self.0.to_vec()
+    }
+
+    fn from_be_bytes<L: PlonkParameters<D>, const D: usize>(
+        bytes: &[ByteVariable],
+        _builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        BytesVariable(bytes.try_into().unwrap())
+    }
+}
+
+impl BitDecomposition for Bytes32Variable {
+    const NB_BITS: usize = 256;
+
+    fn to_le_bits<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<BoolVariable> {
+        self.0.to_le_bits(builder)
+    }
+
+    fn from_le_bits<L: PlonkParameters<D>, const D: usize>(
+        bits: &[BoolVariable],
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Bytes32Variable(BytesVariable::from_le_bits(bits, builder))
+    }
+
+    fn to_be_bytes<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        _builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        self.0 .0.to_vec()
+    }
+
+    fn from_be_bytes<L: PlonkParameters<D>, const D: usize>(
+        bytes: &[ByteVariable],
+        _builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Bytes32Variable(BytesVariable(bytes.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::U256;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn test_bit_decomposition() {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<U32Variable>();
+        let y = builder.read::<U256Variable>();
+        let z = builder.read::<Bytes32Variable>();
+        let v = builder.read::<Variable>();
+
+        // The same integer must decompose identically whatever its type.
+        let z_bits = z.to_le_bits(&mut builder);
+        let z_as_u256 = U256Variable::from_le_bits(&z_bits, &mut builder);
+        builder.assert_is_equal(z_as_u256, y);
+
+        let x_le_bytes = x.to_le_bytes(&mut builder);
+        let x_round_trip = U32Variable::from_le_bytes(&x_le_bytes, &mut builder);
+        builder.assert_is_equal(x_round_trip, x);
+        let x_be_bytes = x.to_be_bytes(&mut builder);
+        builder.write(BytesVariable::<4>(x_be_bytes.try_into().unwrap()));
+
+        let v_bits = builder.to_be_bits(v);
+        let v_round_trip = builder.from_be_bits::<Variable>(&v_bits);
+        builder.assert_is_equal(v_round_trip, v);
+        builder.write(v_bits[63]);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<U32Variable>(0x01020304);
+        input.write::<U256Variable>(U256::from(0xdeadbeefu64));
+        input.write::<Bytes32Variable>(bytes32!(
+            "0x00000000000000000000000000000000000000000000000000000000deadbeef"
+        ));
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(5));
+
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        assert_eq!(output.read::<BytesVariable<4>>(), [1, 2, 3, 4]);
+        assert!(output.read::<BoolVariable>());
+    }
+}
@@ -7,8 +7,10 @@ use serde::{Deserialize, Serialize};
 
 use super::{BoolVariable, CircuitVariable, EvmVariable, Variable};
 use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::lookup::ByteTable;
 use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::ops::{BitAnd, BitOr, BitXor, Not, RotateLeft, RotateRight, Shl, Shr, Zero};
+use crate::value::EvmValue;
 
 /// A variable in the circuit representing a byte value. Under the hood, it is represented as
 /// eight bits stored in big endian.
@@ -83,12 +85,11 @@ impl EvmVariable for ByteVariable {
     }
 
     fn encode_value<F: RichField>(value: Self::ValueType<F>) -> Vec<u8> {
-        vec![value]
+        value.encode()
     }
 
     fn decode_value<F: RichField>(bytes: &[u8]) -> Self::ValueType<F> {
-        assert_eq!(bytes.len(), 1);
-        bytes[0]
+        u8::decode(bytes)
     }
 }
 
@@ -198,14 +199,35 @@ impl<L: PlonkParameters<D>, const D: usize> Not<L, D> for ByteVariable {
     }
 }
 
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Applies the binary operation of `table` to two bytes with a single lookup into the shared
+    /// table of the circuit, which maps `256 * lhs + rhs` to `op(lhs, rhs)`.
+    fn byte_op_lookup(
+        &mut self,
+        table: ByteTable,
+        lhs: ByteVariable,
+        rhs: ByteVariable,
+    ) -> ByteVariable {
+        let input_bits = rhs
+            .as_le_bits()
+            .into_iter()
+            .chain(lhs.as_le_bits())
+            .map(|bit| BoolTarget::new_unsafe(bit.variable.0))
+            .collect::<Vec<_>>();
+        let input = self.api.le_sum(input_bits.into_iter());
+        let output = self.byte_lookup(table, Variable(input));
+        // The output of the lookup is at most 8 bits, so the decomposition is always valid.
+        let mut output_bits = self.api.split_le(output.0, 8);
+        output_bits.reverse();
+        ByteVariable(array![i => BoolVariable::from(output_bits[i]); 8])
+    }
+}
+
 impl<L: PlonkParameters<D>, const D: usize> BitAnd<L, D> for ByteVariable {
     type Output = Self;
 
     fn bitand(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
-        let self_bits = self.as_be_bits();
-        let rhs_bits = rhs.as_be_bits();
-        let mut and_bit = |i| builder.and(self_bits[i], rhs_bits[i]);
-        ByteVariable(array![i => and_bit(i); 8])
+        builder.byte_op_lookup(ByteTable::And, self, rhs)
     }
 }
 
@@ -213,10 +235,7 @@ impl<L: PlonkParameters<D>, const D: usize> BitOr<L, D> for ByteVariable {
     type Output = Self;
 
     fn bitor(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
-        let self_bits = self.as_be_bits();
-        let rhs_bits = rhs.as_be_bits();
-        let mut or_bit = |i| builder.or(self_bits[i], rhs_bits[i]);
-        ByteVariable(array![i => or_bit(i); 8])
+        builder.byte_op_lookup(ByteTable::Or, self, rhs)
     }
 }
 
@@ -224,10 +243,7 @@ impl<L: PlonkParameters<D>, const D: usize> BitXor<L, D> for ByteVariable {
     type Output = Self;
 
     fn bitxor(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
-        let self_bits = self.as_be_bits();
-        let rhs_bits = rhs.as_be_bits();
-        let mut xor_bit = |i| builder.xor(self_bits[i], rhs_bits[i]);
-        ByteVariable(array![i => xor_bit(i); 8])
+        builder.byte_op_lookup(ByteTable::Xor, self, rhs)
     }
 }
 
@@ -379,6 +395,40 @@ mod tests {
         circuit.data.verify(proof).unwrap();
     }
 
+    #[test]
+    fn test_byte_lookup_operations() {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let x = builder.read::<ByteVariable>();
+        let y = builder.read::<ByteVariable>();
+        for _ in 0..4 {
+            let x_and_y = builder.and(x, y);
+            let x_xor_y = builder.xor(x, y);
+            builder.write(x_and_y);
+            builder.write(x_xor_y);
+        }
+        let x_or_y = builder.or(x, y);
+        builder.write(x_or_y);
+
+        // Every operation of the same kind shares one lookup table.
+        assert_eq!(builder.byte_lookups.nb_tables(), 3);
+
+        let circuit = builder.build();
+
+        let (x_value, y_value) = (rand::random::<u8>(), rand::random::<u8>());
+        let mut inputs = circuit.input();
+        inputs.write::<ByteVariable>(x_value);
+        inputs.write::<ByteVariable>(y_value);
+
+        let (proof, mut output) = circuit.prove(&inputs);
+        circuit.verify(&proof, &inputs, &output);
+
+        for _ in 0..4 {
+            assert_eq!(output.read::<ByteVariable>(), x_value & y_value);
+            assert_eq!(output.read::<ByteVariable>(), x_value ^ y_value);
+        }
+        assert_eq!(output.read::<ByteVariable>(), x_value | y_value);
+    }
+
     #[test]
     fn test_to_nibbles() {
         let mut builder = CircuitBuilder::<L, D>::new();
@@ -4,11 +4,13 @@ use std::ops::{Index, Range};
 use array_macro::array;
 use plonky2::hash::hash_types::RichField;
 
-use super::{CircuitVariable, EvmVariable, Variable};
+use super::{ArrayVariable, CircuitVariable, EvmVariable, Variable};
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::ops::{BitAnd, BitOr, BitXor, Not, RotateLeft, RotateRight, Shl, Shr, Zero};
 use crate::frontend::vars::ByteVariable;
+use crate::utils::hex;
+use crate::value::EvmValue;
 
 /// A variable in the circuit representing a byte value.
 #[derive(Debug, Clone, Copy)]
@@ -75,6 +77,10 @@ impl<const N: usize> CircuitVariable for BytesVariable<N> {
             .try_into()
             .unwrap()
     }
+
+    fn format_value<F: RichField>(value: &Self::ValueType<F>) -> String {
+        hex!(value)
+    }
 }
 
 impl<const N: usize> Index<usize> for BytesVariable<N> {
@@ -101,6 +107,68 @@ impl<const N: usize> BytesVariable<N> {
     pub fn is_empty(&self) -> bool {
         N == 0
     }
+
+    /// Returns the bytes `self[START..START + LEN]`.
+    pub fn slice<const START: usize, const LEN: usize>(&self) -> BytesVariable<LEN> {
+        assert!(START + LEN <= N, "slice out of bounds");
+        BytesVariable(self.0[START..START + LEN].try_into().unwrap())
+    }
+
+    /// Returns the concatenation of `self` and `other`. `O` must be equal to `N + M`.
+    pub fn concat<const M: usize, const O: usize>(
+        &self,
+        other: &BytesVariable<M>,
+    ) -> BytesVariable<O> {
+        assert_eq!(N + M, O, "concatenation has the wrong length");
+        BytesVariable(
+            [self.0.as_slice(), other.0.as_slice()]
+                .concat()
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    /// Rotates the bytes left by `n` bytes. Unlike `RotateLeft`, which rotates bits, this is free.
+    pub fn rotate_bytes_left(&self, n: usize) -> Self {
+        let mut bytes = self.0;
+        bytes.rotate_left(n % N);
+        BytesVariable(bytes)
+    }
+
+    /// Rotates the bytes right by `n` bytes. Unlike `RotateRight`, which rotates bits, this is
+    /// free.
+    pub fn rotate_bytes_right(&self, n: usize) -> Self {
+        let mut bytes = self.0;
+        bytes.rotate_right(n % N);
+        BytesVariable(bytes)
+    }
+}
+
+impl<const N: usize> From<ArrayVariable<ByteVariable, N>> for BytesVariable<N> {
+    fn from(array: ArrayVariable<ByteVariable, N>) -> Self {
+        BytesVariable(array.data.try_into().unwrap())
+    }
+}
+
+impl<const N: usize> From<BytesVariable<N>> for ArrayVariable<ByteVariable, N> {
+    fn from(bytes: BytesVariable<N>) -> Self {
+        ArrayVariable::new(bytes.0.to_vec())
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns `bytes[offset..offset + len]` padded with zero bytes to `LEN` bytes, where `offset`
+    /// and `len` are only known at proving time. See `dynamic_slice`.
+    pub fn bytes_dynamic_slice<const N: usize, const LEN: usize>(
+        &mut self,
+        bytes: &BytesVariable<N>,
+        offset: Variable,
+        len: Variable,
+    ) -> BytesVariable<LEN> {
+        let array = ArrayVariable::<ByteVariable, N>::from(*bytes);
+        self.dynamic_slice::<ByteVariable, N, LEN>(&array, offset, len)
+            .into()
+    }
 }
 
 impl<const N: usize> EvmVariable for BytesVariable<N> {
@@ -120,11 +188,11 @@ impl<const N: usize> EvmVariable for BytesVariable<N> {
     }
 
     fn encode_value<F: RichField>(value: Self::ValueType<F>) -> Vec<u8> {
-        value.to_vec()
+        EvmValue::encode(&value)
     }
 
     fn decode_value<F: RichField>(value: &[u8]) -> Self::ValueType<F> {
-        value.try_into().unwrap()
+        <[u8; N]>::decode(value)
     }
 }
 
@@ -399,4 +467,35 @@ mod tests {
         let proof = circuit.data.prove(pw).unwrap();
         circuit.data.verify(proof).unwrap();
     }
+
+    #[test]
+    fn test_bytes_slice_rotate_concat() {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<BytesVariable<6>>();
+        let offset = builder.read::<Variable>();
+        let len = builder.read::<Variable>();
+
+        let head = x.slice::<0, 2>();
+        let tail = x.slice::<2, 4>();
+        let swapped = tail.concat::<2, 6>(&head);
+        builder.write(swapped);
+        builder.write(x.rotate_bytes_left(2));
+        builder.write(x.rotate_bytes_right(1));
+        let dynamic_slice = builder.bytes_dynamic_slice::<6, 4>(&x, offset, len);
+        builder.write(dynamic_slice);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<BytesVariable<6>>([1, 2, 3, 4, 5, 6]);
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(1));
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(3));
+
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        assert_eq!(output.read::<BytesVariable<6>>(), [3, 4, 5, 6, 1, 2]);
+        assert_eq!(output.read::<BytesVariable<6>>(), [3, 4, 5, 6, 1, 2]);
+        assert_eq!(output.read::<BytesVariable<6>>(), [6, 1, 2, 3, 4, 5]);
+        assert_eq!(output.read::<BytesVariable<4>>(), [2, 3, 4, 0]);
+    }
 }
@@ -8,6 +8,7 @@ use super::{
 };
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
+use crate::value::EvmValue;
 
 /// A variable in the circuit representing a byte32 value.
 #[derive(Debug, Clone, Copy)]
@@ -74,6 +75,10 @@ impl CircuitVariable for Bytes32Variable {
     fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F> {
         H256::from_slice(&BytesVariable::<32>::from_elements(elements))
     }
+
+    fn format_value<F: RichField>(value: &Self::ValueType<F>) -> String {
+        format!("{:#x}", value)
+    }
 }
 
 impl EvmVariable for Bytes32Variable {
@@ -92,11 +97,11 @@ impl EvmVariable for Bytes32Variable {
     }
 
     fn encode_value<F: RichField>(value: Self::ValueType<F>) -> Vec<u8> {
-        value.as_bytes().to_vec()
+        value.encode()
     }
 
     fn decode_value<F: RichField>(bytes: &[u8]) -> Self::ValueType<F> {
-        H256::from_slice(bytes)
+        H256::decode(bytes)
     }
 }
 
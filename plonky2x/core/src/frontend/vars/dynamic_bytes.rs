@@ -0,0 +1,226 @@
+use plonky2::field::types::{Field, PrimeField64};
+use plonky2::hash::hash_types::RichField;
+
+use super::{
+    ArrayVariable, BoolVariable, ByteVariable, Bytes32Variable, CircuitVariable, U32Variable,
+    Variable,
+};
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+
+/// A variable in the circuit representing a byte string of at most `MAX_LEN` bytes, whose length
+/// is only known at proving time.
+///
+/// The bytes after `len` are padding and can hold any value: equality, slicing and hashing only
+/// consider the first `len` bytes. Use `DynamicBytesVariable::is_equal` rather than
+/// `builder.is_equal`, which also compares the padding.
+#[derive(Debug, Clone)]
+pub struct DynamicBytesVariable<const MAX_LEN: usize> {
+    pub data: ArrayVariable<ByteVariable, MAX_LEN>,
+    pub len: Variable,
+}
+
+impl<const MAX_LEN: usize> CircuitVariable for DynamicBytesVariable<MAX_LEN> {
+    type ValueType<F: RichField> = Vec<u8>;
+
+    fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Self {
+            data: ArrayVariable::init_unsafe(builder),
+            len: Variable::init_unsafe(builder),
+        }
+    }
+
+    fn variables(&self) -> Vec<Variable> {
+        let mut variables = self.data.variables();
+        variables.push(self.len);
+        variables
+    }
+
+    fn from_variables_unsafe(variables: &[Variable]) -> Self {
+        assert_eq!(variables.len(), Self::nb_elements());
+        let (data, len) = variables.split_at(variables.len() - 1);
+        Self {
+            data: ArrayVariable::from_variables_unsafe(data),
+            len: len[0],
+        }
+    }
+
+    fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        self.data.assert_is_valid(builder);
+        // Check that `len <= MAX_LEN`.
+        let max_len = builder.constant::<Variable>(L::Field::from_canonical_usize(MAX_LEN));
+        let remaining = builder.sub(max_len, self.len);
+        builder.api.range_check(remaining.0, 32);
+    }
+
+    fn nb_elements() -> usize {
+        ArrayVariable::<ByteVariable, MAX_LEN>::nb_elements() + 1
+    }
+
+    fn elements<F: RichField>(value: Vec<u8>) -> Vec<F> {
+        assert!(
+            value.len() <= MAX_LEN,
+            "byte string of length {} exceeds the capacity {}",
+            value.len(),
+            MAX_LEN
+        );
+        let len = value.len();
+        let mut data = value;
+        data.resize(MAX_LEN, 0);
+        let mut elements = ArrayVariable::<ByteVariable, MAX_LEN>::elements(data);
+        elements.push(F::from_canonical_usize(len));
+        elements
+    }
+
+    fn from_elements<F: RichField>(elements: &[F]) -> Vec<u8> {
+        assert_eq!(elements.len(), Self::nb_elements());
+        let (data, len) = elements.split_at(elements.len() - 1);
+        let mut data = ArrayVariable::<ByteVariable, MAX_LEN>::from_elements(data);
+        data.truncate(len[0].to_canonical_u64() as usize);
+        data
+    }
+}
+
+impl<const MAX_LEN: usize> DynamicBytesVariable<MAX_LEN> {
+    pub fn new(data: ArrayVariable<ByteVariable, MAX_LEN>, len: Variable) -> Self {
+        Self { data, len }
+    }
+
+    /// Returns for each of the `MAX_LEN` bytes whether it is before `len`.
+    fn prefix_mask<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<BoolVariable> {
+        let false_v = builder._false();
+        let mut within_prefix = builder._true();
+        (0..MAX_LEN)
+            .map(|i| {
+                let idx = builder.constant::<Variable>(L::Field::from_canonical_usize(i));
+                let at_len = builder.is_equal(idx, self.len);
+                within_prefix = builder.select(at_len, false_v, within_prefix);
+                within_prefix
+            })
+            .collect()
+    }
+
+    /// Returns whether the two byte strings have the same length and the same bytes, ignoring
+    /// their padding.
+    pub fn is_equal<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        other: &Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BoolVariable {
+        let mut result = builder.is_equal(self.len, other.len);
+        let mask = self.prefix_mask(builder);
+        for (i, within_prefix) in mask.into_iter().enumerate() {
+            let byte_eq = builder.is_equal(self.data[i], other.data[i]);
+            let outside_prefix = builder.not(within_prefix);
+            let byte_ok = builder.or(outside_prefix, byte_eq);
+            result = builder.and(result, byte_ok);
+        }
+        result
+    }
+
+    /// Fails if the two byte strings differ, ignoring their padding.
+    pub fn assert_is_equal<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        other: &Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        let is_equal = self.is_equal(other, builder);
+        let true_v = builder._true();
+        builder.assert_is_equal(is_equal, true_v);
+    }
+
+    /// Returns the `len` bytes starting at `offset`, with a capacity of `LEN` bytes. Requires
+    /// `offset + len <= self.len` and `len <= LEN`.
+    pub fn slice<L: PlonkParameters<D>, const D: usize, const LEN: usize>(
+        &self,
+        offset: Variable,
+        len: Variable,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> DynamicBytesVariable<LEN> {
+        // Check that `offset + len <= self.len`. `dynamic_slice` bounds the end by `MAX_LEN`, so
+        // the difference cannot wrap around the field.
+        let end = builder.add(offset, len);
+        let remaining = builder.sub(self.len, end);
+        builder.api.range_check(remaining.0, 32);
+
+        DynamicBytesVariable {
+            data: builder.dynamic_slice(&self.data, offset, len),
+            len,
+        }
+    }
+
+    /// Returns the SHA-256 digest of the first `len` bytes.
+    pub fn sha256<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Bytes32Variable {
+        // `len <= MAX_LEN` is checked when the variable is initialized, so it fits in a u32.
+        let len = U32Variable::from_variables_unsafe(&[self.len]);
+        builder.curta_sha256_variable(self.data.as_slice(), len)
+    }
+
+    /// Returns the keccak256 digest of the first `len` bytes.
+    pub fn keccak256<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Bytes32Variable {
+        // `len <= MAX_LEN` is checked when the variable is initialized, so it fits in a u32.
+        let len = U32Variable::from_variables_unsafe(&[self.len]);
+        builder.keccak256_variable(self.data.as_slice(), len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_dynamic_bytes() {
+        let mut builder = DefaultBuilder::new();
+        let hello = builder.constant::<DynamicBytesVariable<8>>(b"hello".to_vec());
+
+        // The same bytes with a different padding.
+        let mut padded = b"hello".to_vec();
+        padded.resize(8, 0xff);
+        let data = builder.constant::<ArrayVariable<ByteVariable, 8>>(padded);
+        let len = builder.constant::<Variable>(GoldilocksField::from_canonical_u64(5));
+        let hello_padded = DynamicBytesVariable::new(data, len);
+        hello.assert_is_equal(&hello_padded, &mut builder);
+
+        let help = builder.constant::<DynamicBytesVariable<8>>(b"help".to_vec());
+        let is_equal = hello.is_equal(&help, &mut builder);
+        let false_v = builder._false();
+        builder.assert_is_equal(is_equal, false_v);
+
+        let offset = builder.constant::<Variable>(GoldilocksField::ONE);
+        let len = builder.constant::<Variable>(GoldilocksField::from_canonical_u64(3));
+        let ell: DynamicBytesVariable<4> = hello_padded.slice(offset, len, &mut builder);
+        let expected_ell = builder.constant::<DynamicBytesVariable<4>>(b"ell".to_vec());
+        ell.assert_is_equal(&expected_ell, &mut builder);
+
+        let digest = hello_padded.sha256(&mut builder);
+        let expected_digest =
+            builder.constant::<Bytes32Variable>(H256::from_slice(&Sha256::digest(b"hello")));
+        builder.assert_is_equal(digest, expected_digest);
+        builder.write(hello_padded);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<DynamicBytesVariable<8>>(), b"hello".to_vec());
+    }
+}
@@ -0,0 +1,152 @@
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use super::{BitDecomposition, ByteVariable, BytesVariable};
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+
+/// A byte order, used as a type-level marker on byte strings.
+pub trait ByteOrder: Debug + Clone + Copy + Sync + Send + 'static {
+    /// Whether the most significant byte comes first.
+    const IS_BIG_ENDIAN: bool;
+}
+
+/// The most significant byte comes first.
+#[derive(Debug, Clone, Copy)]
+pub struct BigEndian;
+
+/// The least significant byte comes first.
+#[derive(Debug, Clone, Copy)]
+pub struct LittleEndian;
+
+impl ByteOrder for BigEndian {
+    const IS_BIG_ENDIAN: bool = true;
+}
+
+impl ByteOrder for LittleEndian {
+    const IS_BIG_ENDIAN: bool = false;
+}
+
+/// The byte order of the EVM, i.e. of `abi.encodePacked(...)` and of `EvmVariable::encode`.
+pub type EvmByteOrder = BigEndian;
+
+/// The byte order of SSZ serialization, used by the beacon chain.
+pub type SszByteOrder = LittleEndian;
+
+/// The bytes of an integer, tagged with their byte order so that EVM and SSZ encodings cannot be
+/// mixed up silently.
+#[derive(Debug, Clone, Copy)]
+pub struct OrderedBytesVariable<O: ByteOrder, const N: usize> {
+    pub bytes: BytesVariable<N>,
+    _order: PhantomData<O>,
+}
+
+/// Bytes in EVM order.
+pub type BigEndianBytesVariable<const N: usize> = OrderedBytesVariable<BigEndian, N>;
+
+/// Bytes in SSZ order.
+pub type LittleEndianBytesVariable<const N: usize> = OrderedBytesVariable<LittleEndian, N>;
+
+impl<O: ByteOrder, const N: usize> OrderedBytesVariable<O, N> {
+    /// Tags `bytes` as being in the byte order `O`. No reordering happens.
+    pub fn new(bytes: BytesVariable<N>) -> Self {
+        Self {
+            bytes,
+            _order: PhantomData,
+        }
+    }
+
+    /// Encodes `variable` into `N` bytes in the byte order `O`.
+    pub fn from_variable<V: BitDecomposition, L: PlonkParameters<D>, const D: usize>(
+        variable: &V,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        assert_eq!(V::NB_BITS, 8 * N, "variable does not have {} bytes", N);
+        let bytes = if O::IS_BIG_ENDIAN {
+            variable.to_be_bytes(builder)
+        } else {
+            variable.to_le_bytes(builder)
+        };
+        Self::new(BytesVariable(bytes.try_into().unwrap()))
+    }
+
+    /// Decodes the bytes into a variable, taking their byte order into account.
+    pub fn to_variable<V: BitDecomposition, L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> V {
+        assert_eq!(V::NB_BITS, 8 * N, "variable does not have {} bytes", N);
+        if O::IS_BIG_ENDIAN {
+            V::from_be_bytes(&self.bytes.0, builder)
+        } else {
+            V::from_le_bytes(&self.bytes.0, builder)
+        }
+    }
+
+    /// Returns the bytes in big endian order.
+    pub fn to_big_endian(&self) -> BigEndianBytesVariable<N> {
+        OrderedBytesVariable::new(self.reordered::<BigEndian>())
+    }
+
+    /// Returns the bytes in little endian order.
+    pub fn to_little_endian(&self) -> LittleEndianBytesVariable<N> {
+        OrderedBytesVariable::new(self.reordered::<LittleEndian>())
+    }
+
+    /// Returns the bytes in the byte order `P`. Reordering bytes is free.
+    fn reordered<P: ByteOrder>(&self) -> BytesVariable<N> {
+        let mut bytes: [ByteVariable; N] = self.bytes.0;
+        if O::IS_BIG_ENDIAN != P::IS_BIG_ENDIAN {
+            bytes.reverse();
+        }
+        BytesVariable(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::U256;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn test_endianness_conversions() {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<U32Variable>();
+        let y = builder.read::<U64Variable>();
+        let z = builder.read::<U256Variable>();
+
+        let x_be = x.to_big_endian(&mut builder);
+        let x_le = x.to_little_endian(&mut builder);
+        builder.write(x_be.bytes);
+        builder.write(x_le.bytes);
+        // Both encodings decode to the same integer.
+        let x_from_le = U32Variable::from_little_endian(&x_be.to_little_endian(), &mut builder);
+        builder.assert_is_equal(x_from_le, x);
+
+        let y_le = y.to_little_endian(&mut builder);
+        builder.write(y_le.bytes);
+
+        let z_be = z.to_big_endian(&mut builder);
+        let z_from_be = U256Variable::from_big_endian(&z_be, &mut builder);
+        builder.assert_is_equal(z_from_be, z);
+        builder.write(z_be.to_little_endian().bytes);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<U32Variable>(0x01020304);
+        input.write::<U64Variable>(0x0102030405060708);
+        input.write::<U256Variable>(U256::from(0xabcd));
+
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        assert_eq!(output.read::<BytesVariable<4>>(), [1, 2, 3, 4]);
+        assert_eq!(output.read::<BytesVariable<4>>(), [4, 3, 2, 1]);
+        assert_eq!(output.read::<BytesVariable<8>>(), [8, 7, 6, 5, 4, 3, 2, 1]);
+        let mut expected = [0u8; 32];
+        expected[0] = 0xcd;
+        expected[1] = 0xab;
+        assert_eq!(output.read::<BytesVariable<32>>(), expected);
+    }
+}
@@ -1,19 +1,25 @@
 mod array;
+mod bits;
 mod boolean;
 mod byte;
 mod bytes;
 mod bytes32;
 mod collections;
+mod dynamic_bytes;
+mod endian;
 
 mod stream;
 mod variable;
 use std::fmt::Debug;
 
 pub use array::*;
+pub use bits::*;
 pub use boolean::*;
 pub use byte::*;
 pub use bytes::*;
 pub use bytes32::*;
+pub use dynamic_bytes::*;
+pub use endian::*;
 use itertools::Itertools;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::target::Target;
@@ -120,6 +126,11 @@ pub trait CircuitVariable: Debug + Clone + Sized + Sync + Send + 'static {
 
     /// Deserializes a list of field elements to the value type.
     fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F>;
+
+    /// Formats a value of the variable for logging, such as by `watch`.
+    fn format_value<F: RichField>(value: &Self::ValueType<F>) -> String {
+        format!("{:?}", value)
+    }
 }
 
 pub trait EvmVariable: CircuitVariable {
@@ -158,6 +169,7 @@ pub trait EvmVariable: CircuitVariable {
     fn decode_value<F: RichField>(bytes: &[u8]) -> Self::ValueType<F>;
 
     /// Serializes the variable to little endian bits.
+    #[deprecated(note = "use `BitDecomposition::to_le_bits` instead")]
     fn to_le_bits<L: PlonkParameters<D>, const D: usize>(
         &self,
         builder: &mut CircuitBuilder<L, D>,
@@ -169,13 +181,13 @@ pub trait EvmVariable: CircuitVariable {
     }
 
     /// Serializes the variable to big endian bits.
+    #[deprecated(note = "use `BitDecomposition::to_be_bits` instead")]
     fn to_be_bits<L: PlonkParameters<D>, const D: usize>(
         &self,
         builder: &mut CircuitBuilder<L, D>,
     ) -> Vec<BoolVariable> {
-        let mut bits = self.to_le_bits(builder);
-        bits.reverse();
-        bits
+        let bytes = self.encode(builder);
+        bytes.into_iter().flat_map(|b| b.as_be_bits()).collect_vec()
     }
 }
 
@@ -265,4 +277,38 @@ mod tests {
             y: GoldilocksField::ZERO,
         });
     }
+
+    #[test]
+    fn test_derive_generic_struct() {
+        #[derive(Debug, Clone, CircuitVariable)]
+        #[value_name(Grid)]
+        #[value_derive(PartialEq, Eq)]
+        struct GridVariable<V, const N: usize, const M: usize = 2>
+        where
+            V: CircuitVariable,
+        {
+            cells: ArrayVariable<ArrayVariable<V, N>, M>,
+            len: V,
+        }
+
+        type TestGrid = GridVariable<U32Variable, 3>;
+
+        let value: <TestGrid as CircuitVariable>::ValueType<GoldilocksField> = Grid {
+            cells: vec![vec![1, 2, 3], vec![4, 5, 6]],
+            len: 6,
+        };
+
+        let mut builder = CircuitBuilder::<DefaultParameters, 2>::new();
+        let grid = builder.read::<TestGrid>();
+        let constant_grid = builder.constant::<TestGrid>(value.clone());
+        builder.assert_is_equal(grid.clone(), constant_grid);
+        builder.write::<TestGrid>(grid);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<TestGrid>(value.clone());
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<TestGrid>(), value);
+    }
 }
@@ -1,7 +1,8 @@
+use alloc::collections::BTreeMap;
 use core::marker::PhantomData;
 
 use plonky2::iop::target::Target;
-use plonky2::util::serialization::{IoResult, Read, Write};
+use plonky2::util::serialization::{IoError, IoResult, Read, Write};
 use serde::{Deserialize, Serialize};
 
 use super::{CircuitVariable, Variable};
@@ -11,15 +12,35 @@ use crate::utils::stream::Stream;
 
 /// A stream of field elements.
 ///
-/// This struct is used as a buffer for `CircuitVariable`s values.
+/// This struct is used as a buffer for `CircuitVariable`s values. A hint can also write named
+/// fields, which are placed wherever the circuit reads them from the hint's output stream.
 #[derive(Debug, Clone)]
-pub struct ValueStream<L: PlonkParameters<D>, const D: usize>(Stream<L::Field>);
+pub struct ValueStream<L: PlonkParameters<D>, const D: usize> {
+    stream: Stream<L::Field>,
+    fields: BTreeMap<String, Vec<L::Field>>,
+}
 
 /// A stream of variables.
 ///
 /// This struct is used as a buffer for `CircuitVariable`s.
 #[derive(Debug, Clone)]
-pub struct VariableStream(Stream<Variable>);
+pub struct VariableStream {
+    stream: Stream<Variable>,
+    fields: Vec<NamedField>,
+}
+
+/// A named field of a hint's output stream, read by the circuit at `offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct NamedField {
+    name: String,
+    offset: usize,
+    len: usize,
+}
+
+/// The tag written before a serialized `VariableStream` with named fields. Streams without named
+/// fields are serialized in the legacy format, which starts with the number of variables, so the
+/// tag never collides with it.
+const NAMED_FIELDS_TAG: usize = usize::MAX - 1;
 
 /// A stream  
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,11 +66,41 @@ impl<L: PlonkParameters<D>, const D: usize> OutputVariableStream<L, D> {
             .get_mut(self.hint_id)
             .expect("Hint not found")
             .output_stream_mut();
-        stream.0.write_slice(&variables);
+        stream.stream.write_slice(&variables);
 
         variables
     }
 
+    /// Read the field `name` written by the hint with `ValueStream::write_named_value`.
+    ///
+    /// Named fields do not depend on the order in which the hint writes its outputs. The witness
+    /// generation fails if the hint does not write a field read by the circuit, or writes a field
+    /// the circuit does not read.
+    pub fn read_named<V: CircuitVariable>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        name: &str,
+    ) -> V {
+        let stream = builder
+            .hints
+            .get_mut(self.hint_id)
+            .expect("Hint not found")
+            .output_stream_mut();
+        assert!(
+            stream.fields.iter().all(|field| field.name != name),
+            "Hint output field `{}` is read twice",
+            name
+        );
+        let field = NamedField {
+            name: name.to_string(),
+            offset: stream.stream.read_all().len(),
+            len: V::nb_elements(),
+        };
+        stream.fields.push(field);
+
+        self.read::<V>(builder)
+    }
+
     /// Read a single variable from the stream.
     /// The output value is asserted to be a valid circuit variable.
     pub fn read<V: CircuitVariable>(&self, builder: &mut CircuitBuilder<L, D>) -> V {
@@ -76,17 +127,18 @@ impl<L: PlonkParameters<D>, const D: usize> OutputVariableStream<L, D> {
 
 impl VariableStream {
     pub fn new() -> Self {
-        Self(Stream::new(Vec::new()))
+        Self::from_variables(Vec::new())
     }
 
     pub fn from_variables(variables: Vec<Variable>) -> Self {
-        Self(Stream::new(variables))
+        Self {
+            stream: Stream::new(variables),
+            fields: Vec::new(),
+        }
     }
 
     pub fn from_targets(targets: Vec<Target>) -> Self {
-        Self(Stream::new(
-            targets.into_iter().map(Variable).collect::<Vec<_>>(),
-        ))
+        Self::from_variables(targets.into_iter().map(Variable).collect::<Vec<_>>())
     }
 
     pub fn init<L: PlonkParameters<D>, const D: usize>(
@@ -96,19 +148,19 @@ impl VariableStream {
         let variables = (0..size)
             .map(|_| builder.init::<Variable>())
             .collect::<Vec<_>>();
-        Self(Stream::new(variables))
+        Self::from_variables(variables)
     }
 
     pub fn real_all(&self) -> &[Variable] {
-        self.0.read_all()
+        self.stream.read_all()
     }
 
     pub fn read_exact(&mut self, len: usize) -> &[Variable] {
-        self.0.read_exact(len)
+        self.stream.read_exact(len)
     }
 
     pub fn read<V: CircuitVariable>(&mut self) -> V {
-        let variables = self.0.read_exact(V::nb_elements());
+        let variables = self.stream.read_exact(V::nb_elements());
         // Reads from stream don't do any validity checks on the circuit variable.  It is the
         // stream reader's responsibility to do so.
         V::from_variables_unsafe(variables)
@@ -119,7 +171,7 @@ impl VariableStream {
     }
 
     pub fn write<V: CircuitVariable>(&mut self, value: &V) {
-        self.0.write_slice(&value.variables());
+        self.stream.write_slice(&value.variables());
     }
 
     pub fn write_slice<V: CircuitVariable>(&mut self, values: &[V]) {
@@ -128,18 +180,52 @@ impl VariableStream {
 
     /// Derialize the stream from a buffer compatible with `Plonky2` serialization
     pub fn deserialize_from_reader(reader: &mut impl Read) -> IoResult<Self> {
-        let variables = reader
-            .read_target_vec()?
-            .into_iter()
-            .map(Variable)
-            .collect::<Vec<_>>();
-        Ok(VariableStream::from_variables(variables))
+        let tag = reader.read_usize()?;
+        let named = tag == NAMED_FIELDS_TAG;
+        let len = if named { reader.read_usize()? } else { tag };
+        let variables = (0..len)
+            .map(|_| reader.read_target().map(Variable))
+            .collect::<IoResult<Vec<_>>>()?;
+        let mut stream = VariableStream::from_variables(variables);
+        if !named {
+            return Ok(stream);
+        }
+        let nb_fields = reader.read_usize()?;
+        for _ in 0..nb_fields {
+            let mut name = vec![0u8; reader.read_usize()?];
+            reader.read_exact(&mut name)?;
+            let name = String::from_utf8(name).map_err(|_| IoError)?;
+            let offset = reader.read_usize()?;
+            let len = reader.read_usize()?;
+            stream.fields.push(NamedField { name, offset, len });
+        }
+        Ok(stream)
     }
 
     /// Serialize the stream to a buffer compatible with `Plonky2` serialization
+    ///
+    /// Streams without named fields are written in the legacy format, the vector of their targets.
+    /// Otherwise, the targets are preceded by `NAMED_FIELDS_TAG` and followed by the fields.
     pub fn serialize_to_writer(&self, writer: &mut impl Write) -> IoResult<()> {
-        let targets = self.0.read_all().iter().map(|v| v.0).collect::<Vec<_>>();
-        writer.write_target_vec(&targets)
+        let targets = self
+            .stream
+            .read_all()
+            .iter()
+            .map(|v| v.0)
+            .collect::<Vec<_>>();
+        if self.fields.is_empty() {
+            return writer.write_target_vec(&targets);
+        }
+        writer.write_usize(NAMED_FIELDS_TAG)?;
+        writer.write_target_vec(&targets)?;
+        writer.write_usize(self.fields.len())?;
+        for field in self.fields.iter() {
+            writer.write_usize(field.name.len())?;
+            writer.write_all(field.name.as_bytes())?;
+            writer.write_usize(field.offset)?;
+            writer.write_usize(field.len)?;
+        }
+        Ok(())
     }
 }
 
@@ -151,20 +237,23 @@ impl Default for VariableStream {
 
 impl<L: PlonkParameters<D>, const D: usize> ValueStream<L, D> {
     pub fn new() -> Self {
-        Self(Stream::new(Vec::new()))
+        Self::from_values(Vec::new())
     }
 
     pub fn from_values(values: Vec<L::Field>) -> Self {
-        Self(Stream::new(values))
+        Self {
+            stream: Stream::new(values),
+            fields: BTreeMap::new(),
+        }
     }
 
     pub fn read_value<V: CircuitVariable>(&mut self) -> V::ValueType<L::Field> {
-        let elements = self.0.read_exact(V::nb_elements());
+        let elements = self.stream.read_exact(V::nb_elements());
         V::from_elements::<L::Field>(elements)
     }
 
     pub fn read_exact(&mut self, len: usize) -> &[L::Field] {
-        self.0.read_exact(len)
+        self.stream.read_exact(len)
     }
 
     pub fn read_vec<V: CircuitVariable>(&mut self, len: usize) -> Vec<V::ValueType<L::Field>> {
@@ -172,15 +261,69 @@ impl<L: PlonkParameters<D>, const D: usize> ValueStream<L, D> {
     }
 
     pub fn write_slice(&mut self, values: &[L::Field]) {
-        self.0.write_slice(values);
+        self.stream.write_slice(values);
     }
 
     pub fn read_all(&mut self) -> &[L::Field] {
-        self.0.read_all()
+        self.stream.read_all()
     }
 
     pub fn write_value<V: CircuitVariable>(&mut self, value: V::ValueType<L::Field>) {
-        self.0.write_slice(&V::elements::<L::Field>(value));
+        self.stream.write_slice(&V::elements::<L::Field>(value));
+    }
+
+    /// Write a value as the field `name`, read by the circuit with
+    /// `OutputVariableStream::read_named`.
+    pub fn write_named_value<V: CircuitVariable>(
+        &mut self,
+        name: &str,
+        value: V::ValueType<L::Field>,
+    ) {
+        let elements = V::elements::<L::Field>(value);
+        let previous = self.fields.insert(name.to_string(), elements);
+        assert!(
+            previous.is_none(),
+            "Hint output field `{}` is written twice",
+            name
+        );
+    }
+
+    /// The values of the output variables of a hint, with the named fields placed where the
+    /// circuit read them and the unnamed values in between in the order they were written.
+    pub(crate) fn into_output_values(mut self, output_stream: &VariableStream) -> Vec<L::Field> {
+        let nb_variables = output_stream.real_all().len();
+        let mut unnamed = self.stream.read_all().iter();
+        let mut fields = output_stream.fields.iter().peekable();
+        let mut values = Vec::with_capacity(nb_variables);
+        loop {
+            while let Some(field) = fields.next_if(|field| field.offset == values.len()) {
+                let elements = self.fields.remove(&field.name).unwrap_or_else(|| {
+                    panic!("Hint output field `{}` was not written", field.name)
+                });
+                assert_eq!(
+                    elements.len(),
+                    field.len,
+                    "Hint output field `{}` does not match the length of its variable",
+                    field.name
+                );
+                values.extend(elements);
+            }
+            if values.len() >= nb_variables {
+                break;
+            }
+            match unnamed.next() {
+                Some(value) => values.push(*value),
+                None => break,
+            }
+        }
+        assert!(
+            values.len() == nb_variables && unnamed.next().is_none(),
+            "Hint output stream length does not match output variables length"
+        );
+        if let Some(name) = self.fields.keys().next() {
+            panic!("Hint output field `{}` was not read", name);
+        }
+        values
     }
 }
 
@@ -189,3 +332,52 @@ impl<L: PlonkParameters<D>, const D: usize> Default for ValueStream<L, D> {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use plonky2::util::serialization::Buffer;
+
+    use super::*;
+
+    fn targets() -> Vec<Target> {
+        (0..3)
+            .map(|index| Target::VirtualTarget { index })
+            .collect()
+    }
+
+    #[test]
+    fn test_variable_stream_legacy_serialization() {
+        // Streams were serialized as the vector of their targets before named fields were added.
+        let mut bytes = Vec::new();
+        bytes.write_target_vec(&targets()).unwrap();
+
+        let stream = VariableStream::deserialize_from_reader(&mut Buffer::new(&bytes)).unwrap();
+        assert_eq!(
+            stream.real_all(),
+            VariableStream::from_targets(targets()).real_all()
+        );
+        assert!(stream.fields.is_empty());
+
+        let mut serialized = Vec::new();
+        stream.serialize_to_writer(&mut serialized).unwrap();
+        assert_eq!(serialized, bytes);
+    }
+
+    #[test]
+    fn test_variable_stream_named_fields_serialization() {
+        let mut stream = VariableStream::from_targets(targets());
+        let field = NamedField {
+            name: "quotient".to_string(),
+            offset: 1,
+            len: 2,
+        };
+        stream.fields.push(field.clone());
+
+        let mut bytes = Vec::new();
+        stream.serialize_to_writer(&mut bytes).unwrap();
+        let read_stream =
+            VariableStream::deserialize_from_reader(&mut Buffer::new(&bytes)).unwrap();
+        assert_eq!(read_stream.real_all(), stream.real_all());
+        assert_eq!(read_stream.fields, vec![field]);
+    }
+}
@@ -6,12 +6,18 @@
 #![feature(trait_upcasting)]
 
 extern crate alloc;
+#[cfg(feature = "circuit")]
 extern crate clap;
 
+#[cfg(feature = "circuit")]
 pub mod backend;
+#[cfg(feature = "circuit")]
 pub mod frontend;
+#[cfg(feature = "circuit")]
 pub mod utils;
+pub mod value;
 
+#[cfg(feature = "circuit")]
 pub mod prelude {
     pub use plonky2;
     pub use plonky2::field::extension::Extendable;
@@ -28,12 +34,15 @@ pub mod prelude {
     pub use crate::backend::circuit::{GateRegistry, HintRegistry};
     pub use crate::frontend::builder::{CircuitBuilder, DefaultBuilder};
     pub use crate::frontend::ops::*;
+    pub use crate::frontend::uint::int256::I256Variable;
+    pub use crate::frontend::uint::int64::I64Variable;
     pub use crate::frontend::uint::uint128::U128Variable;
     pub use crate::frontend::uint::uint256::U256Variable;
     pub use crate::frontend::uint::uint64::U64Variable;
     pub use crate::frontend::vars::{
-        ArrayVariable, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitVariable,
-        OutputVariableStream, U32Variable, ValueStream, Variable, VariableStream,
+        ArrayVariable, BitDecomposition, BoolVariable, ByteVariable, Bytes32Variable,
+        BytesVariable, CircuitVariable, OutputVariableStream, U32Variable, ValueStream, Variable,
+        VariableStream,
     };
     pub use crate::utils::{address, bytes, bytes32, hex};
 }
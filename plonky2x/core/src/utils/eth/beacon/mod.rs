@@ -335,6 +335,67 @@ pub struct GetBeaconGraffiti {
     pub proof: Vec<String>,
 }
 
+/// The result returned from `/api/beacon/proof/pendingDeposit/[beacon_id]`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBeaconPendingDepositsRoot {
+    pub pending_deposits_root: String,
+    #[serde(deserialize_with = "deserialize_bigint")]
+    pub gindex: BigInt,
+    pub depth: u64,
+    pub proof: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingDeposit {
+    pub pubkey: String,
+    pub withdrawal_credentials: String,
+    pub amount: u64,
+    pub signature: String,
+    pub slot: u64,
+}
+
+/// The result returned from `/api/beacon/proof/pendingDeposit/[beacon_id]/[idx]`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBeaconPendingDeposit {
+    pub pending_deposit: PendingDeposit,
+    #[serde(deserialize_with = "deserialize_bigint")]
+    pub gindex: BigInt,
+    pub depth: u64,
+    pub proof: Vec<String>,
+}
+
+/// The result returned from `/api/beacon/proof/pendingConsolidation/[beacon_id]`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBeaconPendingConsolidationsRoot {
+    pub pending_consolidations_root: String,
+    #[serde(deserialize_with = "deserialize_bigint")]
+    pub gindex: BigInt,
+    pub depth: u64,
+    pub proof: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingConsolidation {
+    pub source_index: u64,
+    pub target_index: u64,
+}
+
+/// The result returned from `/api/beacon/proof/pendingConsolidation/[beacon_id]/[idx]`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBeaconPendingConsolidation {
+    pub pending_consolidation: PendingConsolidation,
+    #[serde(deserialize_with = "deserialize_bigint")]
+    pub gindex: BigInt,
+    pub depth: u64,
+    pub proof: Vec<String>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetBeaconHeadersFromOffsetRange {
@@ -752,6 +813,76 @@ impl BeaconClient {
         Ok(response.result)
     }
 
+    /// Gets the root of the pending deposits and the SSZ proof from
+    /// `blockRoot -> pendingDepositsRoot`. Only available after Electra.
+    pub fn get_pending_deposits_root(
+        &self,
+        beacon_id: String,
+    ) -> Result<GetBeaconPendingDepositsRoot> {
+        let endpoint = format!(
+            "{}/api/beacon/proof/pendingDeposit/{}",
+            self.rpc_url, beacon_id
+        );
+        info!("{}", endpoint);
+        let response = self.client.fetch(&endpoint)?;
+        let response: CustomResponse<GetBeaconPendingDepositsRoot> = response.json()?;
+        assert!(response.success);
+        Ok(response.result)
+    }
+
+    /// Gets the pending deposit at `idx` and the SSZ proof from
+    /// `pendingDepositsRoot -> pendingDeposits[idx]`, including the length mix-in.
+    pub fn get_pending_deposit(
+        &self,
+        beacon_id: String,
+        idx: u64,
+    ) -> Result<GetBeaconPendingDeposit> {
+        let endpoint = format!(
+            "{}/api/beacon/proof/pendingDeposit/{}/{}",
+            self.rpc_url, beacon_id, idx
+        );
+        info!("{}", endpoint);
+        let response = self.client.fetch(&endpoint)?;
+        let response: CustomResponse<GetBeaconPendingDeposit> = response.json()?;
+        assert!(response.success);
+        Ok(response.result)
+    }
+
+    /// Gets the root of the pending consolidations and the SSZ proof from
+    /// `blockRoot -> pendingConsolidationsRoot`. Only available after Electra.
+    pub fn get_pending_consolidations_root(
+        &self,
+        beacon_id: String,
+    ) -> Result<GetBeaconPendingConsolidationsRoot> {
+        let endpoint = format!(
+            "{}/api/beacon/proof/pendingConsolidation/{}",
+            self.rpc_url, beacon_id
+        );
+        info!("{}", endpoint);
+        let response = self.client.fetch(&endpoint)?;
+        let response: CustomResponse<GetBeaconPendingConsolidationsRoot> = response.json()?;
+        assert!(response.success);
+        Ok(response.result)
+    }
+
+    /// Gets the pending consolidation at `idx` and the SSZ proof from
+    /// `pendingConsolidationsRoot -> pendingConsolidations[idx]`, including the length mix-in.
+    pub fn get_pending_consolidation(
+        &self,
+        beacon_id: String,
+        idx: u64,
+    ) -> Result<GetBeaconPendingConsolidation> {
+        let endpoint = format!(
+            "{}/api/beacon/proof/pendingConsolidation/{}/{}",
+            self.rpc_url, beacon_id, idx
+        );
+        info!("{}", endpoint);
+        let response = self.client.fetch(&endpoint)?;
+        let response: CustomResponse<GetBeaconPendingConsolidation> = response.json()?;
+        assert!(response.success);
+        Ok(response.result)
+    }
+
     pub fn get_headers_from_offset_range(
         &self,
         beacon_id: String,
@@ -1,10 +1,14 @@
 extern crate dotenv;
 
 use std::env;
+use std::fmt::Display;
+use std::future::Future;
 
 use ethers::providers::{Http, Provider};
+use log::warn;
 
 pub mod beacon;
+pub mod storage;
 
 #[derive(Debug, Clone)]
 pub struct Address(pub [u8; 20]);
@@ -20,6 +24,46 @@ pub fn get_provider(chain_id: u64) -> Provider<Http> {
     Provider::<Http>::try_from(rpc_url).unwrap()
 }
 
+/// Returns a provider for the archive node of the given chain, read from the
+/// `ARCHIVE_RPC_{chain_id}` environment variable, if one is configured.
+pub fn get_archive_provider(chain_id: u64) -> Option<Provider<Http>> {
+    dotenv::dotenv().ok();
+    let rpc_url = env::var(format!("ARCHIVE_RPC_{}", chain_id)).ok()?;
+    Some(Provider::<Http>::try_from(rpc_url).unwrap())
+}
+
+/// Whether an RPC error indicates that the node has pruned the requested historical state.
+pub fn is_missing_state_error(err: &impl Display) -> bool {
+    let message = err.to_string().to_lowercase();
+    message.contains("missing trie node")
+        || message.contains("header not found")
+        || message.contains("historical state")
+}
+
+/// Runs a query against the primary RPC of the given chain. If the primary node no longer has
+/// the historical state needed to answer it and an archive RPC is configured, the query is
+/// retried against the archive node.
+pub async fn query_with_archive_fallback<T, E, F, Fut>(chain_id: u64, query: F) -> Result<T, E>
+where
+    E: Display,
+    F: Fn(Provider<Http>) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    match query(get_provider(chain_id)).await {
+        Err(err) if is_missing_state_error(&err) => match get_archive_provider(chain_id) {
+            Some(archive) => {
+                warn!(
+                    "primary RPC for chain {} is missing state ({}), retrying on archive RPC",
+                    chain_id, err
+                );
+                query(archive).await
+            }
+            None => Err(err),
+        },
+        result => result,
+    }
+}
+
 pub fn concat_g_indices(gindexes: &[usize]) -> usize {
     let mut index = 1;
     for &g in gindexes {
@@ -35,3 +79,17 @@ pub fn concat_g_indices(gindexes: &[usize]) -> usize {
     }
     index
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_missing_state_error() {
+        assert!(is_missing_state_error(
+            &"(code: -32000, message: missing trie node 5c1ed0b5 (path ), data: None)"
+        ));
+        assert!(is_missing_state_error(&"header not found"));
+        assert!(!is_missing_state_error(&"execution reverted"));
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+use ethers::providers::Middleware;
+use ethers::types::{Address, EIP1186ProofResponse, H256};
+use futures::future::try_join_all;
+use lazy_static::lazy_static;
+use log::debug;
+
+use super::query_with_archive_fallback;
+use crate::frontend::eth::utils::u256_to_h256_be;
+
+/// A single storage slot lookup at a given block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StorageQuery {
+    pub chain_id: u64,
+    pub block_hash: H256,
+    pub address: Address,
+    pub location: H256,
+}
+
+lazy_static! {
+    /// Storage values that have already been fetched by some hint in this process. Storage at a
+    /// fixed block hash is immutable, so entries never need to be invalidated.
+    static ref STORAGE_CACHE: Mutex<HashMap<StorageQuery, H256>> = Mutex::new(HashMap::new());
+}
+
+/// Groups the given slots by address, dropping duplicate locations. The order of addresses and
+/// locations follows the order in which they first appear.
+pub fn group_storage_slots(slots: &[(Address, H256)]) -> Vec<(Address, Vec<H256>)> {
+    let mut address_index = BTreeMap::new();
+    let mut groups: Vec<(Address, Vec<H256>)> = Vec::new();
+    for (address, location) in slots.iter() {
+        let index = *address_index.entry(*address).or_insert_with(|| {
+            groups.push((*address, Vec::new()));
+            groups.len() - 1
+        });
+        let locations = &mut groups[index].1;
+        if !locations.contains(location) {
+            locations.push(*location);
+        }
+    }
+    groups
+}
+
+/// Fetches the storage values of `slots` at `block_hash`.
+///
+/// Slots that were already fetched (by this call or by any other hint in the process) are served
+/// from a cache. The remaining slots are grouped by address so that each address costs a single
+/// `eth_getProof` request, and the requests for different addresses are sent concurrently. Requests
+/// for state that the primary node has pruned are retried on the archive node, if configured.
+pub async fn get_storage_values(
+    chain_id: u64,
+    block_hash: H256,
+    slots: &[(Address, H256)],
+) -> Result<Vec<H256>> {
+    let query = |address: Address, location: H256| StorageQuery {
+        chain_id,
+        block_hash,
+        address,
+        location,
+    };
+
+    let missing = {
+        let cache = STORAGE_CACHE.lock().unwrap();
+        slots
+            .iter()
+            .filter(|(address, location)| !cache.contains_key(&query(*address, *location)))
+            .copied()
+            .collect::<Vec<_>>()
+    };
+
+    if !missing.is_empty() {
+        let groups = group_storage_slots(&missing);
+        debug!(
+            "fetching {} storage slots at block {:?} with {} requests",
+            missing.len(),
+            block_hash,
+            groups.len()
+        );
+        let responses = try_join_all(groups.iter().map(|(address, locations)| {
+            query_with_archive_fallback(chain_id, move |provider| async move {
+                provider
+                    .get_proof(*address, locations.clone(), Some(block_hash.into()))
+                    .await
+            })
+        }))
+        .await?;
+
+        let mut cache = STORAGE_CACHE.lock().unwrap();
+        for ((address, locations), response) in groups.iter().zip(responses) {
+            // The node returns the storage proofs in the same order as the requested keys.
+            for (location, storage_proof) in locations.iter().zip(response.storage_proof.iter()) {
+                cache.insert(
+                    query(*address, *location),
+                    u256_to_h256_be(storage_proof.value),
+                );
+            }
+        }
+    }
+
+    let cache = STORAGE_CACHE.lock().unwrap();
+    slots
+        .iter()
+        .map(|(address, location)| {
+            cache.get(&query(*address, *location)).copied().ok_or_else(|| {
+                anyhow!(
+                    "storage slot {:?} of {:?} missing from eth_getProof response",
+                    location,
+                    address
+                )
+            })
+        })
+        .collect()
+}
+
+/// Fetches the account proof of `address` and the storage proof of `location` at `block_hash`,
+/// retrying on the archive node if the primary node has pruned the state.
+pub async fn get_storage_proof(
+    chain_id: u64,
+    block_hash: H256,
+    address: Address,
+    location: H256,
+) -> Result<EIP1186ProofResponse> {
+    query_with_archive_fallback(chain_id, move |provider| async move {
+        provider
+            .get_proof(address, vec![location], Some(block_hash.into()))
+            .await
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{address, bytes32};
+
+    #[test]
+    fn test_group_storage_slots() {
+        let a = address!("0x55032650b14df07b85bF18A3a3eC8E0Af2e028d5");
+        let b = address!("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        let x = bytes32!("0xad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5");
+        let y = bytes32!("0x0000000000000000000000000000000000000000000000000000000000000001");
+
+        let groups = group_storage_slots(&[(a, x), (b, y), (a, y), (a, x), (b, y)]);
+        assert_eq!(groups, vec![(a, vec![x, y]), (b, vec![y])]);
+    }
+}
@@ -7,6 +7,9 @@ pub mod proof;
 pub mod reqwest;
 pub mod serde;
 pub mod stream;
+#[cfg(test)]
+pub(crate) mod test_circuits;
+pub mod test_utils;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use log::{set_max_level, LevelFilter};
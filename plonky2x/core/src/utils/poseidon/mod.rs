@@ -6,22 +6,20 @@ use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
 use crate::frontend::hash::poseidon::poseidon256::PoseidonHashOutVariable;
 use crate::prelude::{CircuitBuilder, CircuitVariable, PlonkParameters};
 
-pub fn mapreduce_merkle_tree_root<
+/// Computes the leaf hashes of the mapreduce merkle tree, one per batch of `B` inputs.
+pub fn mapreduce_merkle_tree_leaves<
     L: PlonkParameters<D>,
     Input: CircuitVariable,
     const B: usize,
     const D: usize,
 >(
     inputs: &[Input::ValueType<L::Field>],
-) -> HashOut<L::Field>
+) -> Vec<HashOut<L::Field>>
 where
     <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
         AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
 {
     assert_eq!(inputs.len() % B, 0, "inputs length must be a multiple of B");
-    let inputs = inputs.to_vec();
-
-    // Calculate leafs.
     let mut leafs = Vec::new();
     for i in 0..inputs.len() / B {
         let mut input = Vec::new();
@@ -34,23 +32,57 @@ where
                 <L as PlonkParameters<D>>::Field,
             >>::Permutation,
         >(&input);
-        leafs.push(h.elements);
+        leafs.push(h);
     }
+    leafs
+}
+
+pub fn mapreduce_merkle_tree_root<
+    L: PlonkParameters<D>,
+    Input: CircuitVariable,
+    const B: usize,
+    const D: usize,
+>(
+    inputs: &[Input::ValueType<L::Field>],
+) -> HashOut<L::Field>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    mapreduce_merkle_tree_root_with_arity::<L, Input, B, 2, D>(inputs)
+}
+
+/// Computes the root of the mapreduce merkle tree in which each node hashes the concatenation of
+/// its `R` children. The number of leaves must be a power of `R`.
+pub fn mapreduce_merkle_tree_root_with_arity<
+    L: PlonkParameters<D>,
+    Input: CircuitVariable,
+    const B: usize,
+    const R: usize,
+    const D: usize,
+>(
+    inputs: &[Input::ValueType<L::Field>],
+) -> HashOut<L::Field>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    // Calculate leafs.
+    let mut leafs = mapreduce_merkle_tree_leaves::<L, Input, B, D>(inputs)
+        .into_iter()
+        .map(|h| h.elements)
+        .collect_vec();
 
     assert!(
-        leafs.len().is_power_of_two(),
-        "leafs length must be a power of two"
+        mapreduce_nb_layers(leafs.len(), R).is_some(),
+        "leafs length must be a power of the arity"
     );
 
     // Calculate the root.
     while leafs.len() != 1 {
         let mut tmp = Vec::new();
-        for i in 0..leafs.len() / 2 {
-            let left = leafs[i * 2];
-            let right = leafs[i * 2 + 1];
-            let mut input = Vec::new();
-            input.extend(&left);
-            input.extend(&right);
+        for children in leafs.chunks(R) {
+            let input = children.iter().flatten().copied().collect_vec();
             let h = hash_n_to_hash_no_pad::<
                 L::Field,
                 <<<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher as Hasher<
@@ -65,6 +97,51 @@ where
     PoseidonHashOutVariable::from_elements::<L::Field>(&leafs[0])
 }
 
+/// Returns the number of layers of a tree of arity `arity` with `nb_leaves` leaves, if it is a
+/// power of the arity.
+pub fn mapreduce_nb_layers(nb_leaves: usize, arity: usize) -> Option<usize> {
+    assert!(arity >= 2, "arity must be at least two");
+    let mut nb_nodes = nb_leaves;
+    let mut nb_layers = 0;
+    while nb_nodes > 1 {
+        if nb_nodes % arity != 0 {
+            return None;
+        }
+        nb_nodes /= arity;
+        nb_layers += 1;
+    }
+    (nb_nodes == 1).then_some(nb_layers)
+}
+
+/// Computes the hash chain of the leaf hashes of the mapreduce inputs, starting from the zero hash
+/// and absorbing one leaf at a time, which commits to the order of the inputs.
+pub fn mapreduce_hash_chain<
+    L: PlonkParameters<D>,
+    Input: CircuitVariable,
+    const B: usize,
+    const D: usize,
+>(
+    inputs: &[Input::ValueType<L::Field>],
+) -> HashOut<L::Field>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    let mut acc = HashOut::ZERO;
+    for leaf in mapreduce_merkle_tree_leaves::<L, Input, B, D>(inputs) {
+        let mut input = Vec::new();
+        input.extend(&acc.elements);
+        input.extend(&leaf.elements);
+        acc = hash_n_to_hash_no_pad::<
+            L::Field,
+            <<<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher as Hasher<
+                <L as PlonkParameters<D>>::Field,
+            >>::Permutation,
+        >(&input);
+    }
+    acc
+}
+
 pub trait MapReducePoseidonBuilderMethods<L: PlonkParameters<D>, const D: usize> {
     fn mapreduce_merkle_tree_root<Input: CircuitVariable, const B: usize>(
         &mut self,
@@ -9,6 +9,8 @@ use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
 use serde::ser::SerializeSeq;
 use serde::Deserialize;
 
+use crate::backend::circuit::{deserialize_proof, serialize_proof};
+
 pub trait BufferRead: Read {
     fn read_bytes(&mut self) -> IoResult<Vec<u8>> {
         let len = self.read_usize()?;
@@ -91,11 +93,8 @@ pub fn serialize_proof_with_pis<
 where
     S: serde::Serializer,
 {
-    let bytes = format!(
-        "0x{}",
-        hex::encode(bincode::serialize(proof_with_pis).unwrap())
-    );
-    serializer.serialize_str(&bytes)
+    let bytes = serialize_proof(proof_with_pis).map_err(serde::ser::Error::custom)?;
+    serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
 }
 
 pub fn deserialize_proof_with_pis<
@@ -111,7 +110,19 @@ where
     D: serde::Deserializer<'de>,
 {
     let s: String = Deserialize::deserialize(deserializer)?;
-    Ok(bincode::deserialize(&hex::decode(&s[2..]).unwrap()).unwrap())
+    decode_proof_with_pis(&s).map_err(serde::de::Error::custom)
+}
+
+/// Decodes a hex-encoded proof in any of the formats accepted by `deserialize_proof`.
+fn decode_proof_with_pis<
+    F: RichField + Extendable<E>,
+    C: GenericConfig<E, F = F>,
+    const E: usize,
+>(
+    s: &str,
+) -> anyhow::Result<ProofWithPublicInputs<F, C, E>> {
+    let bytes = hex::decode(s.strip_prefix("0x").unwrap_or(s))?;
+    deserialize_proof(&bytes, None)
 }
 
 pub fn serialize_proof_with_pis_vec<
@@ -126,10 +137,11 @@ pub fn serialize_proof_with_pis_vec<
 where
     S: serde::Serializer,
 {
-    let serialized_proofs: Vec<String> = proof_with_pis_vec
+    let serialized_proofs = proof_with_pis_vec
         .iter()
-        .map(|proof| format!("0x{}", hex::encode(bincode::serialize(proof).unwrap())))
-        .collect();
+        .map(|proof| Ok(format!("0x{}", hex::encode(serialize_proof(proof)?))))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(serde::ser::Error::custom)?;
     let mut seq = serializer.serialize_seq(Some(serialized_proofs.len()))?;
     for proof in serialized_proofs {
         seq.serialize_element(&proof)?;
@@ -150,9 +162,10 @@ where
     D: serde::Deserializer<'de>,
 {
     let s: Vec<String> = Vec::deserialize(deserializer)?;
-    Ok(s.into_iter()
-        .map(|q| bincode::deserialize(&hex::decode(&q[2..]).unwrap()).unwrap())
-        .collect_vec())
+    s.iter()
+        .map(|q| decode_proof_with_pis(q))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(serde::de::Error::custom)
 }
 
 pub fn serialize_proof_with_pis_target<S, const D: usize>(
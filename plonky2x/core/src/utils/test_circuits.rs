@@ -0,0 +1,76 @@
+//! Small circuits shared by the tests of the backend.
+
+use plonky2::field::types::Field;
+use serde::{Deserialize, Serialize};
+
+use crate::backend::circuit::{CircuitBuild, DefaultParameters, PlonkParameters, PublicInput};
+use crate::frontend::builder::lookup::ByteTable;
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::hint::synchronous::Async;
+use crate::frontend::vars::{ValueStream, VariableStream};
+use crate::prelude::{CircuitBuilder, GoldilocksField, Variable};
+
+type L = DefaultParameters;
+const D: usize = 2;
+
+/// Builds a circuit reading two field elements and writing their sum.
+pub(crate) fn build_add_circuit() -> CircuitBuild<L, D> {
+    let mut builder = CircuitBuilder::<L, D>::new();
+    let a = builder.read::<Variable>();
+    let b = builder.read::<Variable>();
+    let c = builder.add(a, b);
+    builder.write(c);
+    builder.build()
+}
+
+/// Returns the input of `circuit` writing the field elements `a` and `b`, as read by the circuits
+/// built by `build_add_circuit` and `build_xor_lookup_circuit`.
+pub(crate) fn add_input(circuit: &CircuitBuild<L, D>, a: u64, b: u64) -> PublicInput<L, D> {
+    let mut input = circuit.input();
+    input.write::<Variable>(GoldilocksField::from_canonical_u64(a));
+    input.write::<Variable>(GoldilocksField::from_canonical_u64(b));
+    input
+}
+
+/// Builds a circuit reading two bytes as field elements and writing their xor, which is looked up
+/// in a byte table so that the proofs of the circuit have lookup openings.
+pub(crate) fn build_xor_lookup_circuit() -> CircuitBuild<L, D> {
+    let mut builder = CircuitBuilder::<L, D>::new();
+    let a = builder.read::<Variable>();
+    let b = builder.read::<Variable>();
+    builder.assert_is_byte(a);
+    builder.assert_is_byte(b);
+    let shift = builder.constant::<Variable>(GoldilocksField::from_canonical_u64(256));
+    let a_shifted = builder.mul(a, shift);
+    let index = builder.add(a_shifted, b);
+    let c = builder.byte_lookup(ByteTable::Xor, index);
+    builder.write(c);
+    builder.build()
+}
+
+/// A hint writing the square of a field element.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SquareHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for SquareHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let x = input_stream.read_value::<Variable>();
+        output_stream.write_value::<Variable>(x * x);
+    }
+}
+
+/// Builds a circuit reading a field element and writing its square, computed by an async
+/// `SquareHint` and checked in the circuit. Serializing the circuit needs a `HintRegistry` where
+/// `Async<SquareHint>` was added with `register_async_hint`.
+pub(crate) fn build_square_circuit() -> CircuitBuild<L, D> {
+    let mut builder = CircuitBuilder::<L, D>::new();
+    let x = builder.read::<Variable>();
+    let mut input_stream = VariableStream::new();
+    input_stream.write(&x);
+    let output_stream = builder.async_hint(input_stream, Async(SquareHint));
+    let square = output_stream.read::<Variable>(&mut builder);
+    let expected = builder.mul(x, x);
+    builder.assert_is_equal(square, expected);
+    builder.write(square);
+    builder.build()
+}
@@ -0,0 +1,429 @@
+//! A property-testing harness for gadgets.
+//!
+//! `PropertyTest::check` builds a circuit applying a gadget to an input variable, then checks the
+//! gadget against a reference implementation on random inputs with `CircuitBuild::mock_prove`. A
+//! failing input is shrunk to a simpler one which still fails before it is reported, along with
+//! the seed to reproduce the run.
+//!
+//! ```ignore
+//! PropertyTest::new().check(
+//!     |builder, (a, b): (U32Variable, U32Variable)| builder.add(a, b),
+//!     |(a, b)| a.wrapping_add(*b),
+//! );
+//! ```
+//!
+//! The number of cases and the seed default to the env variables `PROPERTY_TEST_CASES` and
+//! `PROPERTY_TEST_SEED`.
+
+use core::fmt::Debug;
+use core::ops::{Div, Sub};
+use std::env;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use ethers::types::{H256, U128, U256};
+use plonky2::field::types::{Field, Field64, PrimeField64};
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::backend::circuit::{CircuitBuild, PlonkParameters};
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::uint::uint128::U128Variable;
+use crate::frontend::uint::uint256::U256Variable;
+use crate::frontend::uint::uint64::U64Variable;
+use crate::frontend::vars::{
+    ArrayVariable, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitVariable,
+    U32Variable, Variable,
+};
+
+/// The number of cases checked by default.
+const DEFAULT_CASES: usize = 64;
+
+/// The maximum number of times a failing input is replaced by a simpler one.
+const MAX_SHRINKS: usize = 1024;
+
+/// A variable whose values can be generated at random and shrunk.
+pub trait ArbitraryVariable: CircuitVariable {
+    /// Generates a random value, favoring the edge cases of the type.
+    fn arbitrary<F: RichField, R: Rng>(rng: &mut R) -> Self::ValueType<F>;
+
+    /// Returns simpler values than `value` to try when it fails, the simplest first.
+    fn shrink<F: RichField>(_value: &Self::ValueType<F>) -> Vec<Self::ValueType<F>> {
+        Vec::new()
+    }
+}
+
+/// Returns a random integer, or one of the edge cases `0`, `1`, `max - 1` and `max`.
+fn arbitrary_uint<T: From<u8> + Sub<Output = T> + Copy, R: Rng>(
+    rng: &mut R,
+    max: T,
+    random: T,
+) -> T {
+    match rng.gen_range(0..8) {
+        0 => T::from(0),
+        1 => T::from(1),
+        2 => max - T::from(1),
+        3 => max,
+        _ => random,
+    }
+}
+
+/// Returns `0`, `value / 2` and `value - 1`, without duplicates.
+fn shrink_uint<T>(value: T) -> Vec<T>
+where
+    T: From<u8> + Sub<Output = T> + Div<Output = T> + PartialEq + Copy,
+{
+    let zero = T::from(0);
+    if value == zero {
+        return Vec::new();
+    }
+    let mut candidates = vec![zero];
+    for candidate in [value / T::from(2), value - T::from(1)] {
+        if !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+/// Returns the bytes with all bytes zeroed, then with each nonzero byte zeroed.
+fn shrink_bytes<const N: usize>(value: &[u8; N]) -> Vec<[u8; N]> {
+    if value.iter().all(|byte| *byte == 0) {
+        return Vec::new();
+    }
+    let mut candidates = vec![[0; N]];
+    for i in 0..N {
+        if value[i] != 0 {
+            let mut candidate = *value;
+            candidate[i] = 0;
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+impl ArbitraryVariable for Variable {
+    fn arbitrary<F: RichField, R: Rng>(rng: &mut R) -> F {
+        let max = F::ORDER - 1;
+        let random = rng.gen_range(0..=max);
+        F::from_canonical_u64(arbitrary_uint(rng, max, random))
+    }
+
+    fn shrink<F: RichField>(value: &F) -> Vec<F> {
+        shrink_uint(value.to_canonical_u64())
+            .into_iter()
+            .map(F::from_canonical_u64)
+            .collect()
+    }
+}
+
+impl ArbitraryVariable for BoolVariable {
+    fn arbitrary<F: RichField, R: Rng>(rng: &mut R) -> bool {
+        rng.gen()
+    }
+
+    fn shrink<F: RichField>(value: &bool) -> Vec<bool> {
+        match value {
+            true => vec![false],
+            false => Vec::new(),
+        }
+    }
+}
+
+macro_rules! impl_arbitrary_uint {
+    ($variable:ty, $value:ty) => {
+        impl ArbitraryVariable for $variable {
+            fn arbitrary<F: RichField, R: Rng>(rng: &mut R) -> $value {
+                let random = rng.gen();
+                arbitrary_uint(rng, <$value>::MAX, random)
+            }
+
+            fn shrink<F: RichField>(value: &$value) -> Vec<$value> {
+                shrink_uint(*value)
+            }
+        }
+    };
+}
+
+impl_arbitrary_uint!(ByteVariable, u8);
+impl_arbitrary_uint!(U32Variable, u32);
+impl_arbitrary_uint!(U64Variable, u64);
+
+impl ArbitraryVariable for U128Variable {
+    fn arbitrary<F: RichField, R: Rng>(rng: &mut R) -> U128 {
+        let random = U128::from_little_endian(&rng.gen::<[u8; 16]>());
+        arbitrary_uint(rng, U128::MAX, random)
+    }
+
+    fn shrink<F: RichField>(value: &U128) -> Vec<U128> {
+        shrink_uint(*value)
+    }
+}
+
+impl ArbitraryVariable for U256Variable {
+    fn arbitrary<F: RichField, R: Rng>(rng: &mut R) -> U256 {
+        let random = U256::from_little_endian(&rng.gen::<[u8; 32]>());
+        arbitrary_uint(rng, U256::MAX, random)
+    }
+
+    fn shrink<F: RichField>(value: &U256) -> Vec<U256> {
+        shrink_uint(*value)
+    }
+}
+
+impl<const N: usize> ArbitraryVariable for BytesVariable<N> {
+    fn arbitrary<F: RichField, R: Rng>(rng: &mut R) -> [u8; N] {
+        core::array::from_fn(|_| rng.gen())
+    }
+
+    fn shrink<F: RichField>(value: &[u8; N]) -> Vec<[u8; N]> {
+        shrink_bytes(value)
+    }
+}
+
+impl ArbitraryVariable for Bytes32Variable {
+    fn arbitrary<F: RichField, R: Rng>(rng: &mut R) -> H256 {
+        H256(rng.gen())
+    }
+
+    fn shrink<F: RichField>(value: &H256) -> Vec<H256> {
+        shrink_bytes(&value.0).into_iter().map(H256).collect()
+    }
+}
+
+impl<V: ArbitraryVariable, const N: usize> ArbitraryVariable for ArrayVariable<V, N> {
+    fn arbitrary<F: RichField, R: Rng>(rng: &mut R) -> Self::ValueType<F> {
+        (0..N).map(|_| V::arbitrary(rng)).collect()
+    }
+
+    fn shrink<F: RichField>(value: &Self::ValueType<F>) -> Vec<Self::ValueType<F>> {
+        let mut candidates = Vec::new();
+        for (i, element) in value.iter().enumerate() {
+            for candidate in V::shrink::<F>(element) {
+                let mut shrunk = value.clone();
+                shrunk[i] = candidate;
+                candidates.push(shrunk);
+            }
+        }
+        candidates
+    }
+}
+
+impl<V1: ArbitraryVariable, V2: ArbitraryVariable> ArbitraryVariable for (V1, V2) {
+    fn arbitrary<F: RichField, R: Rng>(rng: &mut R) -> Self::ValueType<F> {
+        (V1::arbitrary(rng), V2::arbitrary(rng))
+    }
+
+    fn shrink<F: RichField>(value: &Self::ValueType<F>) -> Vec<Self::ValueType<F>> {
+        let (v1, v2) = value;
+        let shrunk_v1 = V1::shrink::<F>(v1).into_iter().map(|v| (v, v2.clone()));
+        let shrunk_v2 = V2::shrink::<F>(v2).into_iter().map(|v| (v1.clone(), v));
+        shrunk_v1.chain(shrunk_v2).collect()
+    }
+}
+
+impl<V1: ArbitraryVariable, V2: ArbitraryVariable, V3: ArbitraryVariable> ArbitraryVariable
+    for (V1, V2, V3)
+{
+    fn arbitrary<F: RichField, R: Rng>(rng: &mut R) -> Self::ValueType<F> {
+        (V1::arbitrary(rng), V2::arbitrary(rng), V3::arbitrary(rng))
+    }
+
+    fn shrink<F: RichField>(value: &Self::ValueType<F>) -> Vec<Self::ValueType<F>> {
+        let (v1, v2, v3) = value;
+        let shrunk_v1 = V1::shrink::<F>(v1)
+            .into_iter()
+            .map(|v| (v, v2.clone(), v3.clone()));
+        let shrunk_v2 = V2::shrink::<F>(v2)
+            .into_iter()
+            .map(|v| (v1.clone(), v, v3.clone()));
+        let shrunk_v3 = V3::shrink::<F>(v3)
+            .into_iter()
+            .map(|v| (v1.clone(), v2.clone(), v));
+        shrunk_v1.chain(shrunk_v2).chain(shrunk_v3).collect()
+    }
+}
+
+/// Replaces `value`, which fails with `failure`, by the simplest value it shrinks to which still
+/// fails. Returns the value, its failure and the number of shrinks.
+fn shrink_failure<T: Clone>(
+    mut value: T,
+    mut failure: String,
+    shrink: impl Fn(&T) -> Vec<T>,
+    run: impl Fn(&T) -> Result<(), String>,
+) -> (T, String, usize) {
+    let mut nb_shrinks = 0;
+    'shrink: while nb_shrinks < MAX_SHRINKS {
+        for candidate in shrink(&value) {
+            if let Err(candidate_failure) = run(&candidate) {
+                value = candidate;
+                failure = candidate_failure;
+                nb_shrinks += 1;
+                continue 'shrink;
+            }
+        }
+        break;
+    }
+    (value, failure, nb_shrinks)
+}
+
+/// The configuration of a property test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PropertyTest {
+    /// The number of random inputs checked.
+    pub cases: usize,
+    /// The seed of the random inputs.
+    pub seed: u64,
+}
+
+impl Default for PropertyTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PropertyTest {
+    /// Creates a property test with the number of cases and the seed given by
+    /// `PROPERTY_TEST_CASES` and `PROPERTY_TEST_SEED`, or 64 cases and a random seed.
+    pub fn new() -> Self {
+        let cases = env::var("PROPERTY_TEST_CASES")
+            .ok()
+            .and_then(|cases| cases.parse().ok())
+            .unwrap_or(DEFAULT_CASES);
+        let seed = env::var("PROPERTY_TEST_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        Self { cases, seed }
+    }
+
+    pub fn with_cases(self, cases: usize) -> Self {
+        Self { cases, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// Checks that `gadget` computes the same output as `reference` on random inputs, and panics
+    /// with the shrunk failing input otherwise. The gadget fails on an input if its output differs
+    /// from the reference, if a constraint is not satisfied or if witness generation panics.
+    pub fn check<L: PlonkParameters<D>, const D: usize, I, O, G, R>(&self, gadget: G, reference: R)
+    where
+        I: ArbitraryVariable,
+        O: CircuitVariable,
+        G: FnOnce(&mut CircuitBuilder<L, D>, I) -> O,
+        R: Fn(&I::ValueType<L::Field>) -> O::ValueType<L::Field>,
+        O::ValueType<L::Field>: PartialEq,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let input = builder.read::<I>();
+        let output = gadget(&mut builder, input);
+        builder.write(output);
+        let circuit = builder.build();
+
+        let run =
+            |value: &I::ValueType<L::Field>| run_case::<L, D, I, O, R>(&circuit, value, &reference);
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        for case in 0..self.cases {
+            let value = I::arbitrary::<L::Field, _>(&mut rng);
+            if let Err(failure) = run(&value) {
+                let (value, failure, nb_shrinks) =
+                    shrink_failure(value, failure, I::shrink::<L::Field>, run);
+                panic!(
+                    "property failed on case {} of seed {} (shrunk {} times): input {:?}: {}",
+                    case, self.seed, nb_shrinks, value, failure
+                );
+            }
+        }
+    }
+}
+
+/// Checks the output of `circuit` on `value` against `reference`.
+fn run_case<L: PlonkParameters<D>, const D: usize, I, O, R>(
+    circuit: &CircuitBuild<L, D>,
+    value: &I::ValueType<L::Field>,
+    reference: &R,
+) -> Result<(), String>
+where
+    I: ArbitraryVariable,
+    O: CircuitVariable,
+    R: Fn(&I::ValueType<L::Field>) -> O::ValueType<L::Field>,
+    O::ValueType<L::Field>: PartialEq,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    let expected = reference(value);
+    let mut input = circuit.input();
+    input.write::<I>(value.clone());
+    match catch_unwind(AssertUnwindSafe(|| circuit.mock_prove(&input))) {
+        Ok(Ok(mut output)) => {
+            let actual = output.read::<O>();
+            match actual == expected {
+                true => Ok(()),
+                false => Err(format!("expected {:?}, got {:?}", expected, actual)),
+            }
+        }
+        Ok(Err(e)) => Err(format!("{:#}", e)),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default();
+            Err(format!("witness generation panicked: {}", message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    #[test]
+    fn test_property_test() {
+        PropertyTest::new().with_cases(8).check(
+            |builder: &mut CircuitBuilder<L, D>, (a, b): (U32Variable, U32Variable)| {
+                builder.add(a, b)
+            },
+            |(a, b)| a.wrapping_add(*b),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "property failed")]
+    fn test_property_test_failure() {
+        PropertyTest::new().with_cases(8).check(
+            |builder: &mut CircuitBuilder<L, D>, (a, b): (U32Variable, U32Variable)| {
+                builder.add(a, b)
+            },
+            |(a, b)| a.wrapping_add(*b).wrapping_add(1),
+        );
+    }
+
+    #[test]
+    fn test_shrink_failure() {
+        let run = |value: &u64| match *value >= 100 {
+            true => Err(format!("{} is too large", value)),
+            false => Ok(()),
+        };
+        let (value, failure, _) = shrink_failure(
+            u64::MAX,
+            String::new(),
+            U64Variable::shrink::<GoldilocksField>,
+            run,
+        );
+        assert_eq!(value, 100);
+        assert_eq!(failure, "100 is too large");
+
+        let shrunk = ArrayVariable::<ByteVariable, 2>::shrink::<GoldilocksField>(&vec![4, 0]);
+        assert_eq!(shrunk, vec![vec![0, 0], vec![2, 0], vec![3, 0]]);
+    }
+}
@@ -0,0 +1,110 @@
+use alloc::vec::Vec;
+
+use ethers::types::H256;
+use sha2::{Digest, Sha256};
+
+use super::{EvmReader, EvmWriter};
+
+/// The native counterpart of a circuit with bytes io.
+///
+/// Implementing it next to the circuit lets front-ends compute the expected output for a given
+/// input without building the circuit or generating a witness.
+pub trait EvmFunction {
+    /// Reads the input of the circuit from `input` and writes the expected output to `output`,
+    /// in the order of `builder.evm_read` and `builder.evm_write`.
+    fn execute(input: &mut EvmReader, output: &mut EvmWriter);
+}
+
+/// Computes the expected output bytes of `Function` on the given input bytes.
+pub fn execute<Function: EvmFunction>(input: &[u8]) -> Vec<u8> {
+    let mut reader = EvmReader::new(input);
+    let mut writer = EvmWriter::new();
+    Function::execute(&mut reader, &mut writer);
+    assert!(
+        reader.remaining().is_empty(),
+        "{} input bytes were not read",
+        reader.remaining().len()
+    );
+    writer.into_bytes()
+}
+
+/// The hash of the input (or output) bytes of a request, as computed by the `SuccinctGateway`
+/// contract.
+pub fn io_hash(bytes: &[u8]) -> H256 {
+    H256::from_slice(Sha256::digest(bytes).as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns `a + b` for two integers and `c ^ d` for two bytes.
+    struct AddXor;
+
+    impl EvmFunction for AddXor {
+        fn execute(input: &mut EvmReader, output: &mut EvmWriter) {
+            let a = input.read::<u64>();
+            let b = input.read::<u64>();
+            let c = input.read::<u8>();
+            let d = input.read::<u8>();
+            output.write(a.wrapping_add(b));
+            output.write(c ^ d);
+        }
+    }
+
+    fn add_xor_input() -> EvmWriter {
+        let mut input = EvmWriter::new();
+        input.write(1u64 << 40);
+        input.write(7u64);
+        input.write(200u8);
+        input.write(100u8);
+        input
+    }
+
+    #[test]
+    fn test_execute() {
+        let input = add_xor_input();
+        let output = execute::<AddXor>(input.bytes());
+
+        let mut reader = EvmReader::new(&output);
+        assert_eq!(reader.read::<u64>(), (1 << 40) + 7);
+        assert_eq!(reader.read::<u8>(), 200 ^ 100);
+        assert!(reader.remaining().is_empty());
+
+        assert_eq!(
+            io_hash(&[]),
+            "0xe3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+                .parse::<H256>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "circuit")]
+    fn test_execute_matches_circuit() {
+        use crate::backend::circuit::PublicOutput;
+        use crate::prelude::*;
+
+        let mut builder = DefaultBuilder::new();
+        let a = builder.evm_read::<U64Variable>();
+        let b = builder.evm_read::<U64Variable>();
+        let c = builder.evm_read::<ByteVariable>();
+        let d = builder.evm_read::<ByteVariable>();
+        let sum = builder.add(a, b);
+        builder.evm_write(sum);
+        let xor = builder.xor(c, d);
+        builder.evm_write(xor);
+        let circuit = builder.build();
+
+        let input = add_xor_input();
+        let mut circuit_input = circuit.input();
+        circuit_input.evm_write_all(input.bytes());
+        let (proof, output) = circuit.prove(&circuit_input);
+        circuit.verify(&proof, &circuit_input, &output);
+
+        match output {
+            PublicOutput::Bytes(bytes) => assert_eq!(bytes, execute::<AddXor>(input.bytes())),
+            _ => panic!("expected bytes output"),
+        }
+    }
+}
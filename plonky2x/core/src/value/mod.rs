@@ -0,0 +1,163 @@
+//! Value-level encoding of circuit inputs and outputs.
+//!
+//! This module depends neither on the circuit frontend nor on the prover, and does no network
+//! access, so it also builds for `wasm32-unknown-unknown` with `default-features = false`.
+//! Front-ends can use it to encode requests and to compute the expected outputs of a circuit with
+//! bytes io before sending anything to the prover.
+
+mod function;
+mod stream;
+
+use alloc::vec::Vec;
+
+use ethers::types::{H160, H256, U128, U256, U512};
+
+pub use self::function::*;
+pub use self::stream::*;
+
+/// A value with a fixed size encoding matching `abi.encodePacked(...)`.
+///
+/// The encoding of a value is the encoding of the corresponding `EvmVariable` in the circuit.
+pub trait EvmValue: Sized {
+    /// The number of bytes of the encoding.
+    const NB_BYTES: usize;
+
+    /// Serializes the value to `NB_BYTES` bytes.
+    fn encode(&self) -> Vec<u8>;
+
+    /// Deserializes a value from `NB_BYTES` bytes.
+    fn decode(bytes: &[u8]) -> Self;
+}
+
+impl EvmValue for u8 {
+    const NB_BYTES: usize = 1;
+
+    fn encode(&self) -> Vec<u8> {
+        vec![*self]
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        assert_eq!(bytes.len(), Self::NB_BYTES);
+        bytes[0]
+    }
+}
+
+impl EvmValue for u32 {
+    const NB_BYTES: usize = 4;
+
+    fn encode(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        u32::from_be_bytes(bytes.try_into().unwrap())
+    }
+}
+
+impl EvmValue for u64 {
+    const NB_BYTES: usize = 8;
+
+    fn encode(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        u64::from_be_bytes(bytes.try_into().unwrap())
+    }
+}
+
+macro_rules! impl_evm_value_for_uint {
+    ($t:ty, $nb_bytes:expr) => {
+        impl EvmValue for $t {
+            const NB_BYTES: usize = $nb_bytes;
+
+            fn encode(&self) -> Vec<u8> {
+                let mut bytes = vec![0u8; Self::NB_BYTES];
+                self.to_big_endian(&mut bytes);
+                bytes
+            }
+
+            fn decode(bytes: &[u8]) -> Self {
+                assert_eq!(bytes.len(), Self::NB_BYTES);
+                <$t>::from_big_endian(bytes)
+            }
+        }
+    };
+}
+
+impl_evm_value_for_uint!(U128, 16);
+impl_evm_value_for_uint!(U256, 32);
+impl_evm_value_for_uint!(U512, 64);
+
+impl EvmValue for H160 {
+    const NB_BYTES: usize = 20;
+
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        H160::from_slice(bytes)
+    }
+}
+
+impl EvmValue for H256 {
+    const NB_BYTES: usize = 32;
+
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        H256::from_slice(bytes)
+    }
+}
+
+impl<const N: usize> EvmValue for [u8; N] {
+    const NB_BYTES: usize = N;
+
+    fn encode(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        bytes.try_into().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evm_value_encoding() {
+        assert_eq!(0x01020304u32.encode(), [1, 2, 3, 4]);
+        assert_eq!(
+            u64::decode(&0x0102030405060708u64.encode()),
+            0x0102030405060708
+        );
+        let x = U256::from(0xabcdu64);
+        let bytes = x.encode();
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(bytes[30..], [0xab, 0xcd]);
+        assert_eq!(U256::decode(&bytes), x);
+        assert_eq!(<[u8; 3]>::decode(&[7, 8, 9]), [7, 8, 9]);
+    }
+
+    #[test]
+    #[cfg(feature = "circuit")]
+    fn test_evm_value_matches_evm_variable() {
+        use crate::frontend::eth::vars::AddressVariable;
+        use crate::frontend::vars::EvmVariable;
+        use crate::prelude::*;
+
+        type F = GoldilocksField;
+
+        let x = 0xdeadbeefu32;
+        assert_eq!(x.encode(), U32Variable::encode_value::<F>(x));
+        let y = U256::from(0x0102030405060708u64) << 100;
+        assert_eq!(y.encode(), U256Variable::encode_value::<F>(y));
+        let z = address!("0x55032650b14df07b85bF18A3a3eC8E0Af2e028d5");
+        assert_eq!(z.encode(), AddressVariable::encode_value::<F>(z));
+    }
+}
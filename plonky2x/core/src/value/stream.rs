@@ -0,0 +1,63 @@
+use alloc::vec::Vec;
+
+use super::EvmValue;
+
+/// Writes values to the bytes input of a circuit, in the same layout as `PublicInput::evm_write`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EvmWriter {
+    bytes: Vec<u8>,
+}
+
+impl EvmWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the encoding of `value`.
+    pub fn write<V: EvmValue>(&mut self, value: V) {
+        self.bytes.extend(value.encode());
+    }
+
+    /// Appends raw bytes.
+    pub fn write_all(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// The bytes written so far.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads values from the bytes output of a circuit, in the same layout as `PublicOutput::evm_read`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvmReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> EvmReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Reads the next value.
+    pub fn read<V: EvmValue>(&mut self) -> V {
+        assert!(
+            self.bytes.len() >= V::NB_BYTES,
+            "not enough bytes to read a value of {} bytes",
+            V::NB_BYTES
+        );
+        let (value, rest) = self.bytes.split_at(V::NB_BYTES);
+        self.bytes = rest;
+        V::decode(value)
+    }
+
+    /// The bytes that have not been read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
@@ -12,11 +12,12 @@ use assert_is_valid::assert_is_valid;
 use constant::constant;
 use elements::{elements, from_elements, nb_elements};
 use init::init_unsafe;
-use proc_macro2::Ident;
+use proc_macro2::{Ident, TokenStream};
 use quote::quote;
+use syn::spanned::Spanned;
 use syn::{
-    parse_macro_input, parse_quote, Data, DeriveInput, Generics, Meta, Type, Visibility,
-    WherePredicate,
+    parse_macro_input, parse_quote, parse_quote_spanned, Data, DeriveInput, Generics, Meta, Type,
+    Visibility, WherePredicate,
 };
 use value::value;
 use variables::{from_variables_unsafe, variables};
@@ -29,32 +30,38 @@ struct StructData {
 #[proc_macro_derive(CircuitVariable, attributes(value_name, value_derive))]
 pub fn derive_circuit_variable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(expanded) => proc_macro::TokenStream::from(expanded),
+        Err(error) => proc_macro::TokenStream::from(error.to_compile_error()),
+    }
+}
 
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
     let name = input.ident;
-    let data = parse_struct_data(input.data);
+    let data = parse_struct_data(&name, input.data)?;
 
     let mut value_ident = Ident::new(&format!("{}Value", name), name.span());
     let mut value_derive = vec![parse_quote!(Debug), parse_quote!(Clone)];
 
     for attr in &input.attrs {
         if attr.path().is_ident("value_name") {
-            value_ident = attr.parse_args::<Ident>().unwrap();
+            value_ident = attr.parse_args::<Ident>()?;
         }
         if attr.path().is_ident("value_derive") {
             match attr.meta {
-                Meta::Path(ref path) => value_derive.push(path.get_ident().unwrap().clone()),
+                Meta::Path(ref path) => value_derive.push(path.require_ident()?.clone()),
                 Meta::List(ref list) => {
                     list.parse_nested_meta(|meta| {
-                        let ident = meta
-                            .path
-                            .get_ident()
-                            .expect("Could not parse value_derive attribute");
-                        value_derive.push(ident.clone());
+                        value_derive.push(meta.path.require_ident()?.clone());
                         Ok(())
-                    })
-                    .expect("Could not parse value_derive atrributes");
+                    })?;
+                }
+                Meta::NameValue(_) => {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "value_derive cannot be a named value",
+                    ))
                 }
-                Meta::NameValue(_) => panic!("value_derive cannot be a named value"),
             }
         }
     }
@@ -62,13 +69,9 @@ pub fn derive_circuit_variable(input: proc_macro::TokenStream) -> proc_macro::To
     let mut generics = input.generics;
     make_where_clause(&data, &mut generics);
 
-    if value_derive.len() > 2 && !generics.params.is_empty() {
-        panic!("Cannot use [value_derive] with generic parameters");
-    }
-
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let (value_generics, value_expanded) = value(&value_ident, &value_derive, &data, &generics);
+    let (value_generics, value_expanded) = value(&value_ident, &value_derive, &data, &generics)?;
     let (_, value_ty_generics, _) = value_generics.split_for_impl();
 
     let init_unsafe_expanded = init_unsafe(&data);
@@ -120,32 +123,57 @@ pub fn derive_circuit_variable(input: proc_macro::TokenStream) -> proc_macro::To
         }
     };
 
-    proc_macro::TokenStream::from(expanded)
+    Ok(expanded)
 }
 
-fn parse_struct_data(data: Data) -> StructData {
-    match data {
-        Data::Struct(data) => StructData {
-            fields: data
-                .fields
-                .into_iter()
-                .map(|f| {
-                    let name = f.ident;
-                    let ty = f.ty;
-                    let vis = f.vis;
-
-                    (name, ty, vis)
-                })
-                .collect(),
-        },
-        Data::Enum(_) => unimplemented!("enums not supported"),
-        Data::Union(_) => unimplemented!("unions not supported"),
+fn parse_struct_data(name: &Ident, data: Data) -> syn::Result<StructData> {
+    let data = match data {
+        Data::Struct(data) => data,
+        Data::Enum(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "CircuitVariable cannot be derived for enums",
+            ))
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "CircuitVariable cannot be derived for unions",
+            ))
+        }
+    };
+    let mut fields = Vec::new();
+    for field in data.fields {
+        if field.ident.is_none() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "CircuitVariable can only be derived for structs with named fields",
+            ));
+        }
+        match field.ty {
+            Type::Array(_) => {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "arrays are not variables, use `ArrayVariable<V, N>` instead",
+                ))
+            }
+            Type::Reference(_) | Type::Ptr(_) => {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "fields of a variable must be owned variables",
+                ))
+            }
+            _ => {}
+        }
+        fields.push((field.ident, field.ty, field.vis));
     }
+    Ok(StructData { fields })
 }
 
 fn make_where_clause(data: &StructData, generics: &mut Generics) {
+    // The bounds are spanned at the fields so a field that is not a variable is reported there.
     let circuit_var_recurse = data.fields.iter().map(|(_, ty, _)| -> WherePredicate {
-        parse_quote! {
+        parse_quote_spanned! {ty.span()=>
             #ty: CircuitVariable
         }
     });
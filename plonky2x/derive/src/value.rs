@@ -1,6 +1,6 @@
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
-use syn::{parse_quote, Generics};
+use syn::{parse_quote, GenericParam, Generics, WherePredicate};
 
 use crate::StructData;
 
@@ -9,13 +9,34 @@ pub(crate) fn value(
     value_derive: &[Ident],
     data: &StructData,
     generics: &Generics,
-) -> (Generics, TokenStream) {
+) -> syn::Result<(Generics, TokenStream)> {
     let mut value_generics = generics.clone();
+    // Defaults are only allowed on the trailing parameters, and the field `F` comes last.
+    for param in value_generics.params.iter_mut() {
+        match param {
+            GenericParam::Type(param) => {
+                param.eq_token = None;
+                param.default = None;
+            }
+            GenericParam::Const(param) => {
+                param.eq_token = None;
+                param.default = None;
+            }
+            GenericParam::Lifetime(_) => {}
+        }
+    }
     value_generics.params.push(parse_quote!(F: RichField));
 
     let (_, _, where_clause) = value_generics.split_for_impl();
 
-    let value_derive_recurs = value_derive
+    // A derive would bound the type parameters themselves, but the fields hold the values of the
+    // variables, so the traits are implemented with bounds on the value types instead.
+    let is_generic = generics.type_params().next().is_some();
+    let (derived, implemented): (Vec<_>, Vec<_>) = value_derive
+        .iter()
+        .partition(|d| !is_generic || **d == "Debug" || **d == "Clone");
+
+    let value_derive_recurs = derived
         .iter()
         .map(|d| {
             quote! {
@@ -34,11 +55,60 @@ pub(crate) fn value(
         }
     });
 
+    let implemented_expanded = implemented
+        .iter()
+        .map(|d| value_impl(name, d, data, &value_generics))
+        .collect::<syn::Result<Vec<_>>>()?;
+
     let value_expanded = quote! {
         #value_derive_expanded
         pub struct #name #value_generics #where_clause {
             #(#recurse)*
         }
+
+        #(#implemented_expanded)*
     };
-    (value_generics, value_expanded)
+    Ok((value_generics, value_expanded))
+}
+
+fn value_impl(
+    name: &Ident,
+    value_trait: &Ident,
+    data: &StructData,
+    value_generics: &Generics,
+) -> syn::Result<TokenStream> {
+    let mut generics = value_generics.clone();
+    let where_clause = generics.make_where_clause();
+    for (_, ty, _) in data.fields.iter() {
+        let predicate: WherePredicate = parse_quote! {
+            <#ty as CircuitVariable>::ValueType<F>: #value_trait
+        };
+        where_clause.predicates.push(predicate);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    if value_trait == "PartialEq" {
+        let recurse = data.fields.iter().map(|(name, _, _)| {
+            quote! {
+                && self.#name == other.#name
+            }
+        });
+        Ok(quote! {
+            impl #impl_generics PartialEq for #name #ty_generics #where_clause {
+                fn eq(&self, other: &Self) -> bool {
+                    true #(#recurse)*
+                }
+            }
+        })
+    } else if value_trait == "Eq" {
+        Ok(quote! {
+            impl #impl_generics Eq for #name #ty_generics #where_clause {}
+        })
+    } else {
+        Err(syn::Error::new_spanned(
+            value_trait,
+            "only Debug, Clone, PartialEq and Eq can be derived for the values of variables with \
+             type parameters",
+        ))
+    }
 }
@@ -12,6 +12,7 @@ import (
 	"github.com/consensys/gnark/constraint"
 	"github.com/consensys/gnark/frontend"
 	"github.com/consensys/gnark/frontend/cs/scs"
+	gnarkio "github.com/consensys/gnark/io"
 	"github.com/consensys/gnark/logger"
 	"github.com/succinctlabs/gnark-plonky2-verifier/trusted_setup"
 	"github.com/succinctlabs/gnark-plonky2-verifier/types"
@@ -76,8 +77,9 @@ func (c *Plonky2xVerifierCircuit) Define(api frontend.API) error {
 	return nil
 }
 
-func CompileVerifierCircuit(dummyCircuitPath string) (constraint.ConstraintSystem, plonk.ProvingKey, plonk.VerifyingKey, error) {
-	log := logger.Logger()
+// NewVerifierCircuit returns the verifier circuit of the wrapped plonky2x proofs whose common
+// circuit data is the one of the dummy proof in dummyCircuitPath, to be compiled.
+func NewVerifierCircuit(dummyCircuitPath string) Plonky2xVerifierCircuit {
 	verifierOnlyCircuitData := variables.DeserializeVerifierOnlyCircuitData(
 		types.ReadVerifierOnlyCircuitData(dummyCircuitPath + "/verifier_only_circuit_data.json"),
 	)
@@ -94,6 +96,12 @@ func CompileVerifierCircuit(dummyCircuitPath string) (constraint.ConstraintSyste
 		OutputHash:        new(frontend.Variable),
 		CommonCircuitData: commonCircuitData,
 	}
+	return circuit
+}
+
+func CompileVerifierCircuit(dummyCircuitPath string) (constraint.ConstraintSystem, plonk.ProvingKey, plonk.VerifyingKey, error) {
+	log := logger.Logger()
+	circuit := NewVerifierCircuit(dummyCircuitPath)
 	r1cs, err := frontend.Compile(ecc.BN254.ScalarField(), scs.NewBuilder, &circuit)
 	if err != nil {
 		return nil, nil, nil, fmt.Errorf("failed to compile circuit: %w", err)
@@ -130,7 +138,8 @@ func CompileVerifierCircuit(dummyCircuitPath string) (constraint.ConstraintSyste
 	return r1cs, pk, vk, nil
 }
 
-func SaveVerifierCircuit(path string, r1cs constraint.ConstraintSystem, pk plonk.ProvingKey, vk plonk.VerifyingKey) error {
+// SaveVerifierCircuit saves the compiled verifier circuit and its keys, of either proof system.
+func SaveVerifierCircuit(path string, r1cs constraint.ConstraintSystem, pk gnarkio.WriterRawTo, vk gnarkio.WriterRawTo) error {
 	log := logger.Logger()
 	os.MkdirAll(path, 0755)
 	log.Info().Msg("Saving circuit constraints to " + path + "/r1cs.bin")
@@ -19,6 +19,7 @@ func main() {
 	verifyFlag := flag.Bool("verify", false, "verify a proof")
 	compileFlag := flag.Bool("compile", false, "Compile and save the universal verifier circuit")
 	contractFlag := flag.Bool("contract", true, "Generate solidity contract")
+	systemFlag := flag.String("system", "plonk", "proof system of the wrapper: plonk or groth16")
 	flag.Parse()
 
 	log := logger.Logger()
@@ -35,6 +36,16 @@ func main() {
 	log.Debug().Msg("Circuit path: " + *circuitPath)
 	log.Debug().Msg("Data path: " + *dataPath)
 
+	switch *systemFlag {
+	case "plonk":
+	case "groth16":
+		RunGroth16(*circuitPath, *dataPath, *compileFlag, *contractFlag, *proofFlag, *verifyFlag)
+		return
+	default:
+		log.Error().Msg("unknown proof system " + *systemFlag + ", expected plonk or groth16")
+		os.Exit(1)
+	}
+
 	if *compileFlag {
 		log.Info().Msg("compiling verifier circuit")
 		r1cs, pk, vk, err := CompileVerifierCircuit("./data/dummy")
@@ -74,13 +85,7 @@ func main() {
 		// If the circuitPath is "" and not provided as part of the CLI flags, then we wait
 		// for user input.
 		if *circuitPath == "" {
-			log.Info().Msg("Waiting for user to provide circuitPath from stdin")
-			reader := bufio.NewReader(os.Stdin)
-			str, err := reader.ReadString('\n')
-			if err != nil {
-				log.Err(err).Msg("failed to parse the user provided circuitPath")
-			}
-			trimmed := strings.TrimSuffix(str, "\n")
+			trimmed := ReadCircuitPath()
 			circuitPath = &trimmed
 		}
 
@@ -126,3 +131,15 @@ func main() {
 		log.Info().Msg("Successfully verified proof")
 	}
 }
+
+// ReadCircuitPath waits for the user to provide the circuitPath from stdin.
+func ReadCircuitPath() string {
+	log := logger.Logger()
+	log.Info().Msg("Waiting for user to provide circuitPath from stdin")
+	reader := bufio.NewReader(os.Stdin)
+	str, err := reader.ReadString('\n')
+	if err != nil {
+		log.Err(err).Msg("failed to parse the user provided circuitPath")
+	}
+	return strings.TrimSuffix(str, "\n")
+}
@@ -0,0 +1,110 @@
+package main
+
+// An in-process interface to the prover, for the `gnark-ffi` feature of plonky2x which links the
+// verifier built with `go build -buildmode=c-archive` instead of spawning its binary. The exported
+// functions return NULL on success and an error message otherwise, which the caller frees with
+// gnark_free_string.
+
+// #include <stdlib.h>
+import "C"
+
+import (
+	"fmt"
+	"sync"
+	"unsafe"
+
+	"github.com/consensys/gnark/backend/groth16"
+	"github.com/consensys/gnark/backend/plonk"
+	"github.com/consensys/gnark/constraint"
+)
+
+// The prover data loaded by gnark_load_prover.
+var ffiProver struct {
+	sync.Mutex
+	system    string
+	r1cs      constraint.ConstraintSystem
+	plonkPk   plonk.ProvingKey
+	plonkVk   plonk.VerifyingKey
+	groth16Pk groth16.ProvingKey
+	groth16Vk groth16.VerifyingKey
+}
+
+// gnark_load_prover loads the compiled verifier circuit and the keys of the proof system from
+// dataPath, replacing the data loaded before.
+//
+//export gnark_load_prover
+func gnark_load_prover(dataPath *C.char, system *C.char) (result *C.char) {
+	defer recoverFfiError(&result)
+	ffiProver.Lock()
+	defer ffiProver.Unlock()
+
+	path := C.GoString(dataPath)
+	var err error
+	switch C.GoString(system) {
+	case "plonk":
+		ffiProver.r1cs, ffiProver.plonkPk, err = LoadProverData(path)
+		if err == nil {
+			ffiProver.plonkVk, err = LoadVerifierKey(path)
+		}
+	case "groth16":
+		ffiProver.r1cs, ffiProver.groth16Pk, err = LoadProverDataGroth16(path)
+		if err == nil {
+			ffiProver.groth16Vk, err = LoadVerifierKeyGroth16(path)
+		}
+	default:
+		err = fmt.Errorf("unknown proof system %s, expected plonk or groth16", C.GoString(system))
+	}
+	if err != nil {
+		ffiProver.system = ""
+		return ffiError(err)
+	}
+	ffiProver.system = C.GoString(system)
+	return nil
+}
+
+// gnark_prove proves and verifies the wrapped plonky2x proof in circuitPath with the loaded
+// prover data, saving the proof like the `-prove` command.
+//
+//export gnark_prove
+func gnark_prove(circuitPath *C.char) (result *C.char) {
+	defer recoverFfiError(&result)
+	ffiProver.Lock()
+	defer ffiProver.Unlock()
+
+	path := C.GoString(circuitPath)
+	switch ffiProver.system {
+	case "plonk":
+		proof, publicWitness, err := Prove(path, ffiProver.r1cs, ffiProver.plonkPk)
+		if err != nil {
+			return ffiError(err)
+		}
+		return ffiError(plonk.Verify(proof, ffiProver.plonkVk, publicWitness))
+	case "groth16":
+		proof, publicWitness, err := ProveGroth16(path, ffiProver.r1cs, ffiProver.groth16Pk)
+		if err != nil {
+			return ffiError(err)
+		}
+		return ffiError(groth16.Verify(proof, ffiProver.groth16Vk, publicWitness))
+	default:
+		return ffiError(fmt.Errorf("the prover data is not loaded"))
+	}
+}
+
+//export gnark_free_string
+func gnark_free_string(s *C.char) {
+	C.free(unsafe.Pointer(s))
+}
+
+func ffiError(err error) *C.char {
+	if err == nil {
+		return nil
+	}
+	return C.CString(err.Error())
+}
+
+// recoverFfiError returns a panic as an error, as it cannot unwind into the caller.
+func recoverFfiError(result **C.char) {
+	if r := recover(); r != nil {
+		*result = C.CString(fmt.Sprint(r))
+	}
+}
@@ -0,0 +1,271 @@
+package main
+
+// The Groth16 wrapping backend, selected with `-system groth16`. A Groth16 proof over BN254 costs
+// ~200k gas to verify on-chain, less than a PLONK proof, and can be verified by the existing
+// Groth16 verifier contracts. Unlike PLONK, whose setup is the universal Aztec Ignition SRS,
+// Groth16 needs a setup specific to the verifier circuit: `groth16.Setup` runs it in a single
+// party, so whoever compiles the circuit knows its toxic waste. A production deployment should
+// replace the keys with the output of a multi-party ceremony.
+
+import (
+	"bufio"
+	"bytes"
+	"fmt"
+	"os"
+	"time"
+
+	"github.com/consensys/gnark-crypto/ecc"
+	"github.com/consensys/gnark/backend/groth16"
+	groth16_bn254 "github.com/consensys/gnark/backend/groth16/bn254"
+	"github.com/consensys/gnark/backend/witness"
+	"github.com/consensys/gnark/constraint"
+	"github.com/consensys/gnark/frontend"
+	"github.com/consensys/gnark/frontend/cs/r1cs"
+	"github.com/consensys/gnark/logger"
+)
+
+// The size in bytes of the proof expected by the Solidity verifier: the points A, B and C as 8
+// uint256 words, which are the first bytes of the raw encoding of the proof.
+const groth16SolidityProofSize = 8 * 32
+
+func CompileVerifierCircuitGroth16(dummyCircuitPath string) (constraint.ConstraintSystem, groth16.ProvingKey, groth16.VerifyingKey, error) {
+	log := logger.Logger()
+	circuit := NewVerifierCircuit(dummyCircuitPath)
+	r1cs, err := frontend.Compile(ecc.BN254.ScalarField(), r1cs.NewBuilder, &circuit)
+	if err != nil {
+		return nil, nil, nil, fmt.Errorf("failed to compile circuit: %w", err)
+	}
+	log.Info().Msg("Successfully compiled verifier circuit")
+
+	log.Info().Msg("Running circuit setup")
+	start := time.Now()
+	pk, vk, err := groth16.Setup(r1cs)
+	if err != nil {
+		return nil, nil, nil, err
+	}
+	elapsed := time.Since(start)
+	log.Info().Msg("Successfully ran circuit setup, time: " + elapsed.String())
+
+	// The Solidity verifier takes the proof as the points A, B and C only, so the circuit must not
+	// use commitments.
+	if len(vk.(*groth16_bn254.VerifyingKey).PublicAndCommitmentCommitted) > 0 {
+		return nil, nil, nil, fmt.Errorf("the Groth16 verifier circuit must not use commitments")
+	}
+
+	return r1cs, pk, vk, nil
+}
+
+func LoadProverDataGroth16(path string) (constraint.ConstraintSystem, groth16.ProvingKey, error) {
+	log := logger.Logger()
+	r1csFile, err := os.Open(path + "/r1cs.bin")
+	if err != nil {
+		return nil, nil, fmt.Errorf("failed to open r1cs file: %w", err)
+	}
+	r1cs := groth16.NewCS(ecc.BN254)
+	start := time.Now()
+	r1csReader := bufio.NewReader(r1csFile)
+	_, err = r1cs.ReadFrom(r1csReader)
+	if err != nil {
+		return nil, nil, fmt.Errorf("failed to read r1cs file: %w", err)
+	}
+	r1csFile.Close()
+	elapsed := time.Since(start)
+	log.Debug().Msg("Successfully loaded constraint system, time: " + elapsed.String())
+
+	pkFile, err := os.Open(path + "/pk.bin")
+	if err != nil {
+		return nil, nil, fmt.Errorf("failed to open pk file: %w", err)
+	}
+	pk := groth16.NewProvingKey(ecc.BN254)
+	start = time.Now()
+	pkReader := bufio.NewReader(pkFile)
+	_, err = pk.ReadFrom(pkReader)
+	if err != nil {
+		return nil, nil, fmt.Errorf("failed to read pk file: %w", err)
+	}
+	pkFile.Close()
+	elapsed = time.Since(start)
+	log.Debug().Msg("Successfully loaded proving key, time: " + elapsed.String())
+
+	return r1cs, pk, nil
+}
+
+func LoadVerifierKeyGroth16(path string) (groth16.VerifyingKey, error) {
+	log := logger.Logger()
+	vkFile, err := os.Open(path + "/vk.bin")
+	if err != nil {
+		return nil, fmt.Errorf("failed to open vk file: %w", err)
+	}
+	vk := groth16.NewVerifyingKey(ecc.BN254)
+	start := time.Now()
+	_, err = vk.ReadFrom(vkFile)
+	if err != nil {
+		return nil, fmt.Errorf("failed to read vk file: %w", err)
+	}
+	vkFile.Close()
+	elapsed := time.Since(start)
+	log.Debug().Msg("Successfully loaded verifying key, time: " + elapsed.String())
+
+	return vk, nil
+}
+
+func LoadProofGroth16() (groth16.Proof, error) {
+	log := logger.Logger()
+	proofFile, err := os.Open("proof.bin")
+	if err != nil {
+		return nil, fmt.Errorf("failed to open proof file: %w", err)
+	}
+	proof := groth16.NewProof(ecc.BN254)
+	_, err = proof.ReadFrom(proofFile)
+	if err != nil {
+		return nil, fmt.Errorf("failed to read proof file: %w", err)
+	}
+	proofFile.Close()
+	log.Debug().Msg("Successfully loaded proof")
+
+	return proof, nil
+}
+
+func ProveGroth16(circuitPath string, r1cs constraint.ConstraintSystem, pk groth16.ProvingKey) (groth16.Proof, witness.Witness, error) {
+	log := logger.Logger()
+
+	assignment, inputHash, outputHash, verifierDigest := NewVerifierAssignment(circuitPath)
+
+	log.Debug().Msg("Generating witness")
+	start := time.Now()
+	witness, err := frontend.NewWitness(assignment, ecc.BN254.ScalarField())
+	if err != nil {
+		return nil, nil, fmt.Errorf("failed to generate witness: %w", err)
+	}
+	elapsed := time.Since(start)
+	log.Debug().Msg("Successfully generated witness, time: " + elapsed.String())
+
+	log.Debug().Msg("Creating proof")
+	start = time.Now()
+	proof, err := groth16.Prove(r1cs, pk, witness)
+	if err != nil {
+		return nil, nil, fmt.Errorf("failed to create proof: %w", err)
+	}
+	elapsed = time.Since(start)
+	log.Info().Msg("Successfully created proof, time: " + elapsed.String())
+
+	publicWitness, err := witness.Public()
+	if err != nil {
+		return nil, nil, fmt.Errorf("failed to get public witness: %w", err)
+	}
+
+	buf := new(bytes.Buffer)
+	_, err = proof.WriteRawTo(buf)
+	if err != nil {
+		return nil, nil, fmt.Errorf("failed to serialize proof: %w", err)
+	}
+	err = os.WriteFile("proof.bin", buf.Bytes(), 0644)
+	if err != nil {
+		return nil, nil, fmt.Errorf("failed to write proof file: %w", err)
+	}
+	solidityProof := buf.Bytes()[:groth16SolidityProofSize]
+	err = SaveProof(inputHash, outputHash, verifierDigest, solidityProof, publicWitness)
+	if err != nil {
+		return nil, nil, err
+	}
+
+	return proof, publicWitness, nil
+}
+
+func ExportIFunctionVerifierSolidityGroth16(path string, vk groth16.VerifyingKey) error {
+	log := logger.Logger()
+	buf := new(bytes.Buffer)
+	err := vk.ExportSolidity(buf)
+	if err != nil {
+		log.Err(err).Msg("failed to export verifying key to solidity")
+		return err
+	}
+	return os.WriteFile(path+"/Verifier.sol", buf.Bytes(), 0644)
+}
+
+// RunGroth16 runs the commands of the CLI with the Groth16 backend.
+func RunGroth16(circuitPath string, dataPath string, compile bool, contract bool, prove bool, verify bool) {
+	log := logger.Logger()
+
+	if compile {
+		log.Info().Msg("compiling verifier circuit")
+		r1cs, pk, vk, err := CompileVerifierCircuitGroth16("./data/dummy")
+		if err != nil {
+			log.Error().Msg("failed to compile verifier circuit:" + err.Error())
+			os.Exit(1)
+		}
+		err = SaveVerifierCircuit(dataPath, r1cs, pk, vk)
+		if err != nil {
+			log.Error().Msg("failed to save verifier circuit:" + err.Error())
+			os.Exit(1)
+		}
+
+		if contract {
+			log.Info().Msg("generating solidity contract")
+			err := ExportIFunctionVerifierSolidityGroth16(dataPath, vk)
+			if err != nil {
+				log.Error().Msg("failed to generate solidity contract:" + err.Error())
+				os.Exit(1)
+			}
+		}
+	}
+
+	if prove {
+		log.Info().Msg("loading the groth16 proving key, circuit data and verifying key")
+		r1cs, pk, err := LoadProverDataGroth16(dataPath)
+		if err != nil {
+			log.Err(err).Msg("failed to load the verifier circuit")
+			os.Exit(1)
+		}
+		vk, err := LoadVerifierKeyGroth16(dataPath)
+		if err != nil {
+			log.Err(err).Msg("failed to load the verifier key")
+			os.Exit(1)
+		}
+
+		if circuitPath == "" {
+			circuitPath = ReadCircuitPath()
+		}
+
+		log.Info().Msg(fmt.Sprintf("Generating the proof with circuitPath %s", circuitPath))
+		proof, publicWitness, err := ProveGroth16(circuitPath, r1cs, pk)
+		if err != nil {
+			log.Err(err).Msg("failed to create the proof")
+			os.Exit(1)
+		}
+
+		log.Info().Msg("Verifying proof")
+		err = groth16.Verify(proof, vk, publicWitness)
+		if err != nil {
+			log.Err(err).Msg("failed to verify proof")
+			os.Exit(1)
+		}
+		log.Info().Msg("Successfully verified proof")
+	}
+
+	if verify {
+		log.Info().Msg("loading the proof, verifying key and public inputs")
+		vk, err := LoadVerifierKeyGroth16(dataPath)
+		if err != nil {
+			log.Err(err).Msg("failed to load the verifier key")
+			os.Exit(1)
+		}
+		publicWitness, err := LoadPublicWitness(circuitPath)
+		if err != nil {
+			log.Err(err).Msg("failed to load the public witness")
+			os.Exit(1)
+		}
+
+		proof, err := LoadProofGroth16()
+		if err != nil {
+			log.Err(err).Msg("failed to load the proof")
+			os.Exit(1)
+		}
+		err = groth16.Verify(proof, vk, publicWitness)
+		if err != nil {
+			log.Err(err).Msg("failed to verify proof")
+			os.Exit(1)
+		}
+		log.Info().Msg("Successfully verified proof")
+	}
+}
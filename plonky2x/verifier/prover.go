@@ -78,9 +78,9 @@ func GetInputHashOutputHash(proofWithPis gnark_verifier_types.ProofWithPublicInp
 	return inputHash, outputHash
 }
 
-func Prove(circuitPath string, r1cs constraint.ConstraintSystem, pk plonk.ProvingKey) (plonk.Proof, witness.Witness, error) {
-	log := logger.Logger()
-
+// NewVerifierAssignment reads the wrapped plonky2x proof in circuitPath and returns the assignment
+// of the verifier circuit, along with its public inputs.
+func NewVerifierAssignment(circuitPath string) (*Plonky2xVerifierCircuit, *big.Int, *big.Int, *big.Int) {
 	verifierOnlyCircuitData := variables.DeserializeVerifierOnlyCircuitData(
 		gnark_verifier_types.ReadVerifierOnlyCircuitData(circuitPath + "/verifier_only_circuit_data.json"),
 	)
@@ -97,6 +97,14 @@ func Prove(circuitPath string, r1cs constraint.ConstraintSystem, pk plonk.Provin
 		InputHash:      frontend.Variable(inputHash),
 		OutputHash:     frontend.Variable(outputHash),
 	}
+	verifierDigest := (verifierOnlyCircuitData.CircuitDigest).(*big.Int)
+	return assignment, inputHash, outputHash, verifierDigest
+}
+
+func Prove(circuitPath string, r1cs constraint.ConstraintSystem, pk plonk.ProvingKey) (plonk.Proof, witness.Witness, error) {
+	log := logger.Logger()
+
+	assignment, inputHash, outputHash, verifierDigest := NewVerifierAssignment(circuitPath)
 
 	log.Debug().Msg("Generating witness")
 	start := time.Now()
@@ -116,23 +124,41 @@ func Prove(circuitPath string, r1cs constraint.ConstraintSystem, pk plonk.Provin
 	elapsed = time.Since(start)
 	log.Info().Msg("Successfully created proof, time: " + elapsed.String())
 
+	publicWitness, err := witness.Public()
+	if err != nil {
+		return nil, nil, fmt.Errorf("failed to get public witness: %w", err)
+	}
+
 	_proof := proof.(*plonk_bn254.Proof)
+	err = SaveProof(inputHash, outputHash, verifierDigest, _proof.MarshalSolidity(), publicWitness)
+	if err != nil {
+		return nil, nil, err
+	}
+
+	return proof, publicWitness, nil
+}
+
+// SaveProof saves the proof in the format of the Solidity verifier to proof.json and
+// proof_with_witness.json, and the public witness to public_witness.bin.
+func SaveProof(inputHash, outputHash, verifierDigest *big.Int, proof []byte, publicWitness witness.Witness) error {
+	log := logger.Logger()
+
 	log.Info().Msg("Saving proof to proof.json")
 	jsonProof, err := json.Marshal(types.ProofResult{
 		// Output will be filled in by plonky2x CLI
 		Output: []byte{},
-		Proof:  _proof.MarshalSolidity(),
+		Proof:  proof,
 	})
 	if err != nil {
-		return nil, nil, fmt.Errorf("failed to marshal proof: %w", err)
+		return fmt.Errorf("failed to marshal proof: %w", err)
 	}
 	proofFile, err := os.Create("proof.json")
 	if err != nil {
-		return nil, nil, fmt.Errorf("failed to create proof file: %w", err)
+		return fmt.Errorf("failed to create proof file: %w", err)
 	}
 	_, err = proofFile.Write(jsonProof)
 	if err != nil {
-		return nil, nil, fmt.Errorf("failed to write proof file: %w", err)
+		return fmt.Errorf("failed to write proof file: %w", err)
 	}
 	proofFile.Close()
 	log.Info().Msg("Successfully saved proof")
@@ -146,41 +172,36 @@ func Prove(circuitPath string, r1cs constraint.ConstraintSystem, pk plonk.Provin
 	}{
 		InputHash:      inputHash.Bytes(),
 		OutputHash:     outputHash.Bytes(),
-		VerifierDigest: (verifierOnlyCircuitData.CircuitDigest).(*big.Int).Bytes(),
-		Proof:          _proof.MarshalSolidity(),
+		VerifierDigest: verifierDigest.Bytes(),
+		Proof:          proof,
 	})
 	if err != nil {
-		return nil, nil, fmt.Errorf("failed to marshal proof with witness: %w", err)
+		return fmt.Errorf("failed to marshal proof with witness: %w", err)
 	}
 	proofFile, err = os.Create("proof_with_witness.json")
 	if err != nil {
-		return nil, nil, fmt.Errorf("failed to create proof_with_witness file: %w", err)
+		return fmt.Errorf("failed to create proof_with_witness file: %w", err)
 	}
 	_, err = proofFile.Write(jsonProofWithWitness)
 	if err != nil {
-		return nil, nil, fmt.Errorf("failed to write proof_with_witness file: %w", err)
+		return fmt.Errorf("failed to write proof_with_witness file: %w", err)
 	}
 	proofFile.Close()
 	log.Info().Msg("Proof with witness")
 	log.Info().Msg(string(jsonProofWithWitness))
 	log.Info().Msg("Successfully saved proof_with_witness")
 
-	publicWitness, err := witness.Public()
-	if err != nil {
-		return nil, nil, fmt.Errorf("failed to get public witness: %w", err)
-	}
-
 	log.Info().Msg("Saving public witness to public_witness.bin")
 	witnessFile, err := os.Create("public_witness.bin")
 	if err != nil {
-		return nil, nil, fmt.Errorf("failed to create public witness file: %w", err)
+		return fmt.Errorf("failed to create public witness file: %w", err)
 	}
 	_, err = publicWitness.WriteTo(witnessFile)
 	if err != nil {
-		return nil, nil, fmt.Errorf("failed to write public witness file: %w", err)
+		return fmt.Errorf("failed to write public witness file: %w", err)
 	}
 	witnessFile.Close()
 	log.Info().Msg("Successfully saved public witness")
 
-	return proof, publicWitness, nil
+	return nil
 }